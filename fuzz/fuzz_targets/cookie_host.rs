@@ -0,0 +1,13 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use tauri_webview_util::CookieHost;
+
+// NOTE: hosts that parse are written back in a canonical form, which has to parse to the same host.
+fuzz_target!(|text: &str| {
+    if let Ok(host) = text.parse::<CookieHost>() {
+        let written = host.to_string();
+        let reparsed = written.parse::<CookieHost>().expect("a written host should parse");
+        assert_eq!(host, reparsed);
+    }
+});
@@ -0,0 +1,23 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use tauri_webview_util::{deserialize_cookies, serialize_cookies, CookieJarFormat};
+
+// NOTE: cookie files come from other browsers and tools, so whatever they hold has to be rejected
+// with an error or read back unchanged once written. DevTools JSON is compared after one write,
+// since writing it normalizes the same-site policy.
+fuzz_target!(|text: &str| {
+    if let Ok(cookies) = deserialize_cookies(text, CookieJarFormat::Netscape) {
+        let written = serialize_cookies(&cookies, CookieJarFormat::Netscape).unwrap();
+        assert_eq!(
+            cookies,
+            deserialize_cookies(&written, CookieJarFormat::Netscape).unwrap()
+        );
+    }
+    if let Ok(cookies) = deserialize_cookies(text, CookieJarFormat::DevToolsJson) {
+        let written = serialize_cookies(&cookies, CookieJarFormat::DevToolsJson).unwrap();
+        let cookies = deserialize_cookies(&written, CookieJarFormat::DevToolsJson).unwrap();
+        let rewritten = serialize_cookies(&cookies, CookieJarFormat::DevToolsJson).unwrap();
+        assert_eq!(written, rewritten);
+    }
+});
@@ -0,0 +1,43 @@
+#![no_main]
+
+use arbitrary::Arbitrary;
+use libfuzzer_sys::fuzz_target;
+use tauri_webview_util::{deserialize_cookies, CookieJarFormat, CookiePattern, CookiePatternSpec};
+
+#[derive(Arbitrary, Debug)]
+struct Input<'a> {
+    domain_glob: &'a str,
+    name_glob: &'a str,
+    path_prefix: &'a str,
+    hosts: Vec<String>,
+    schemes: Vec<String>,
+    include_subdomains: bool,
+    name_regex: Option<String>,
+    cookies: &'a str,
+}
+
+// NOTE: patterns come from settings files and IPC payloads, and are matched against cookies set by
+// web content, so neither should be able to panic or hang the matcher.
+fuzz_target!(|input: Input| {
+    let Ok(cookies) = deserialize_cookies(input.cookies, CookieJarFormat::Netscape) else {
+        return;
+    };
+    let globs = CookiePattern::builder()
+        .match_domain(input.domain_glob)
+        .match_name(input.name_glob)
+        .match_path_prefix(input.path_prefix)
+        .build();
+    let spec = CookiePatternSpec {
+        hosts: input.hosts,
+        schemes: input.schemes,
+        include_subdomains: input.include_subdomains,
+        name_regex: input.name_regex,
+    };
+    let spec = CookiePattern::try_from(spec).ok();
+    for cookie in &cookies {
+        globs.matches(cookie);
+        if let Some(spec) = &spec {
+            spec.matches(cookie);
+        }
+    }
+});
@@ -0,0 +1,68 @@
+use crate::{platform, script_result, BoxResult, Error};
+use serde::{Deserialize, Serialize};
+use tauri::Window;
+
+// NOTE: the reason a promise was rejected with. Errors are described by their name, message, and
+// stack, while other thrown values are kept as `value` (when they have a JSON representation) along
+// with their string conversion as `message`.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[non_exhaustive]
+pub struct ScriptRejection {
+    pub name: Option<String>,
+    pub message: String,
+    pub stack: Option<String>,
+    #[serde(default)]
+    pub value: serde_json::Value,
+}
+
+impl std::fmt::Display for ScriptRejection {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match &self.name {
+            Some(name) => write!(f, "{name}: {}", self.message),
+            None => f.write_str(&self.message),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Settled {
+    #[serde(default)]
+    value: serde_json::Value,
+    rejection: Option<ScriptRejection>,
+}
+
+// NOTE: the script is awaited as an expression, so it can evaluate to a promise or to a plain value.
+// The outcome is always returned as a settled record, since the platforms report rejections as
+// plain error strings (or not at all).
+fn body(script: &str) -> String {
+    format!(
+        r#"try {{
+  return {{ value: await ({script}
+) }};
+}} catch (error) {{
+  const rejection = {{ name: null, message: String(error), stack: null, value: null }};
+  if (error instanceof Error) {{
+    Object.assign(rejection, {{ name: error.name, message: error.message, stack: error.stack ?? null }});
+  }} else {{
+    try {{ rejection.value = JSON.parse(JSON.stringify(error)) ?? null; }} catch {{}}
+  }}
+  return {{ rejection }};
+}}"#
+    )
+}
+
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window, script),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn eval(window: &Window, script: String) -> BoxResult<serde_json::Value> {
+    let settled = platform::eval_async_js(window, body(&script)).await?;
+    match serde_json::from_value::<Settled>(settled)? {
+        Settled {
+            rejection: Some(rejection),
+            ..
+        } => Err(Error::ScriptRejected(rejection).into()),
+        Settled { value, .. } => script_result::check_size(value),
+    }
+}
@@ -0,0 +1,70 @@
+use crate::{bridge, platform, state, BoxResult};
+use futures::{future::BoxFuture, prelude::*};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tauri::Window;
+use url::Url;
+
+pub(crate) type AuthHandler =
+    Arc<dyn Fn(AuthChallenge) -> BoxFuture<'static, Option<Credentials>> + Send + Sync + 'static>;
+
+// NOTE: `url` is the URL of the request when the platform reports it, which webkit2gtk does not.
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[non_exhaustive]
+pub struct AuthChallenge {
+    pub url: Option<Url>,
+    pub host: String,
+    pub port: u16,
+    pub realm: Option<String>,
+    pub is_proxy: bool,
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct Credentials {
+    pub username: String,
+    pub password: String,
+}
+
+impl Credentials {
+    pub fn new(username: impl Into<String>, password: impl Into<String>) -> Self {
+        Self {
+            username: username.into(),
+            password: password.into(),
+        }
+    }
+}
+
+// NOTE: the request waits while the handler runs, and goes on without authentication when it
+// returns no credentials. Credentials are kept for the session, so the handler is not asked again
+// for the same protection space. Later calls replace the handler.
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window, handler),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn on_challenge(
+    window: &Window,
+    handler: impl Fn(AuthChallenge) -> BoxFuture<'static, Option<Credentials>> + Send + Sync + 'static,
+) -> BoxResult<()> {
+    let state = state::window_state(window)?;
+    *state.auth_handler.lock()? = Some(Arc::new(handler));
+    if let Some(install) = bridge::install_once(&state, "auth-challenge").await? {
+        let handler: AuthHandler = Arc::new({
+            let window = window.clone();
+            move |challenge| respond(&window, challenge)
+        });
+        platform::add_auth_handler(window, handler).await?;
+        install.done();
+    }
+    Ok(())
+}
+
+fn respond(window: &Window, challenge: AuthChallenge) -> BoxFuture<'static, Option<Credentials>> {
+    let handler = state::window_state(window)
+        .and_then(|state| Ok(state.auth_handler.lock()?.clone()))
+        .ok()
+        .flatten();
+    match handler {
+        Some(handler) => handler(challenge),
+        None => future::ready(None).boxed(),
+    }
+}
@@ -0,0 +1,79 @@
+use crate::{error::WindowContext, platform, Cookie, CookiePattern, Result, WebviewExt};
+use futures::{future::BoxFuture, prelude::*};
+use tauri::Window;
+use url::Url;
+
+/// The cookie, cache, and navigation methods of a webview, for code that should also run against
+/// [`crate::MockWebView`] in tests.
+///
+/// [`WebviewExt`] can not be implemented by a mock, since its other methods drive the native
+/// webview of a window. For [`tauri::Window`] these methods behave like their `webview_` namesakes.
+pub trait WebviewBackend: private::WebviewBackendSealed + Send + Sync {
+    fn get_cookies(&self, url: Option<Url>) -> BoxFuture<Result<Vec<Cookie>>>;
+    fn set_cookie(&self, cookie: Cookie) -> BoxFuture<Result<()>>;
+    fn delete_cookies(&self, url: Option<Url>) -> BoxFuture<Result<Vec<Cookie>>>;
+    fn clear_cache(&self) -> BoxFuture<Result<()>>;
+    fn navigate(&self, url: Url) -> Result<()>;
+
+    fn collect_cookies(&self, pattern: CookiePattern) -> BoxFuture<Result<Vec<Cookie>>> {
+        async move {
+            let mut cookies = self.get_cookies(None).await?;
+            cookies.retain(|cookie| pattern.matches(cookie));
+            Ok(cookies)
+        }
+        .boxed()
+    }
+
+    // NOTE: like `webview_delete_matching_cookies`, cookies are deleted by writing them again with an
+    // expiry in the past.
+    fn delete_matching_cookies(&self, pattern: CookiePattern) -> BoxFuture<Result<Vec<Cookie>>> {
+        async move {
+            let cookies = self.collect_cookies(pattern).await?;
+            for cookie in cookies.iter().cloned() {
+                let cookie = Cookie {
+                    expires: Some(time::OffsetDateTime::UNIX_EPOCH),
+                    session: false,
+                    ..cookie
+                };
+                self.set_cookie(cookie).await?;
+            }
+            Ok(cookies)
+        }
+        .boxed()
+    }
+}
+
+impl WebviewBackend for Window {
+    fn get_cookies(&self, url: Option<Url>) -> BoxFuture<Result<Vec<Cookie>>> {
+        self.webview_get_cookies(url)
+    }
+
+    fn set_cookie(&self, cookie: Cookie) -> BoxFuture<Result<()>> {
+        platform::set_cookie(self, cookie)
+            .map(move |result| result.window_context(self, "set cookie"))
+            .boxed()
+    }
+
+    fn delete_cookies(&self, url: Option<Url>) -> BoxFuture<Result<Vec<Cookie>>> {
+        self.webview_delete_cookies(url)
+    }
+
+    fn clear_cache(&self) -> BoxFuture<Result<()>> {
+        self.webview_clear_cache()
+    }
+
+    fn navigate(&self, url: Url) -> Result<()> {
+        self.webview_navigate(url)
+    }
+}
+
+mod private {
+    use tauri::Window;
+
+    pub trait WebviewBackendSealed {}
+    impl WebviewBackendSealed for Window {
+    }
+    #[cfg(feature = "mock")]
+    impl WebviewBackendSealed for crate::MockWebView {
+    }
+}
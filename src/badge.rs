@@ -0,0 +1,43 @@
+use crate::{bridge, BoxResult};
+use serde::Deserialize;
+use tauri::Window;
+
+const BADGE_SCRIPT: &str = include_str!("js/badge.js");
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Badge {
+    Clear,
+    Flag,
+    Count(u64),
+}
+
+#[derive(Debug, Deserialize)]
+struct BadgeRequest {
+    count: Option<u64>,
+}
+
+impl From<BadgeRequest> for Badge {
+    fn from(request: BadgeRequest) -> Self {
+        match request.count {
+            None => Self::Flag,
+            Some(0) => Self::Clear,
+            Some(count) => Self::Count(count),
+        }
+    }
+}
+
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window, handler),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn install(window: &Window, handler: impl Fn(Badge) + Send + Sync + 'static) -> BoxResult<()> {
+    let label = String::from(window.label());
+    bridge::on_message(window, "badge", move |payload| {
+        match serde_json::from_value::<BadgeRequest>(payload) {
+            Ok(request) => handler(request.into()),
+            Err(err) => crate::report_error(&label, "malformed badge request", err),
+        }
+    })
+    .await?;
+    bridge::add_script(window, BADGE_SCRIPT).await
+}
@@ -0,0 +1,41 @@
+use crate::{bridge, state, BoxResult};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use tauri::Window;
+
+const BANDWIDTH_SCRIPT: &str = include_str!("js/bandwidth.js");
+
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TrafficStats {
+    pub requests: u64,
+    pub transferred_bytes: u64,
+    pub encoded_body_bytes: u64,
+    pub decoded_body_bytes: u64,
+}
+
+// NOTE: the stats come from the resource timing entries of the page, so they are accumulated since
+// the document was loaded. Engines report sizes of zero for cross-origin responses without a
+// `Timing-Allow-Origin` header, in which case only the request count is accurate.
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn stats(window: &Window) -> BoxResult<BTreeMap<String, TrafficStats>> {
+    let state = state::window_state(window)?;
+    if let Some(install) = bridge::install_once(&state, "bandwidth").await? {
+        bridge::add_script(window, BANDWIDTH_SCRIPT).await?;
+        install.done();
+    }
+    let stats = bridge::call(window, "return window.__TAURI_WEBVIEW_UTIL_BANDWIDTH__?.stats() ?? {};").await?;
+    Ok(serde_json::from_value(stats)?)
+}
+
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn reset(window: &Window) -> BoxResult<()> {
+    bridge::call(window, "window.__TAURI_WEBVIEW_UTIL_BANDWIDTH__?.reset();").await?;
+    Ok(())
+}
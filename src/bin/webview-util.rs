@@ -0,0 +1,73 @@
+use serde_json::{json, Value};
+use std::{
+    io::{BufRead, BufReader, Write},
+    net::TcpStream,
+    process::ExitCode,
+};
+use tauri_webview_util::{DEBUG_ADDR_VAR, DEFAULT_DEBUG_ADDR};
+
+const USAGE: &str = "\
+usage: webview-util [--addr <addr>] [--window <label>] <command>
+
+Attaches to the debug socket of a running app, which debug builds with the `webview-util` plugin
+listen on when the `cli` feature is enabled.
+
+commands:
+    windows          list the labels of the windows
+    cookies [<url>]  dump the cookies, or the cookies for the url
+    clear-cache      clear the cache
+    navigate <url>   navigate to the url
+
+options:
+    --addr <addr>     the address of the debug socket [env: TAURI_WEBVIEW_UTIL_DEBUG_ADDR]
+    --window <label>  the window to act on, which can be left out when the app has only one";
+
+fn main() -> ExitCode {
+    match run(std::env::args().skip(1)) {
+        Ok(value) => {
+            println!("{value:#}");
+            ExitCode::SUCCESS
+        },
+        Err(message) => {
+            eprintln!("error: {message}");
+            ExitCode::FAILURE
+        },
+    }
+}
+
+fn run(mut args: impl Iterator<Item = String>) -> Result<Value, String> {
+    let mut addr = std::env::var(DEBUG_ADDR_VAR).unwrap_or_else(|_| DEFAULT_DEBUG_ADDR.into());
+    let mut window = None;
+    let mut command = vec![];
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--addr" => addr = args.next().ok_or("`--addr` takes an address")?,
+            "--window" => window = Some(args.next().ok_or("`--window` takes a label")?),
+            "-h" | "--help" => {
+                println!("{USAGE}");
+                std::process::exit(0);
+            },
+            _ => command.push(arg),
+        }
+    }
+    let request = match command.iter().map(String::as_str).collect::<Vec<_>>().as_slice() {
+        ["windows"] => json!({ "command": "windows" }),
+        ["cookies"] => json!({ "command": "cookies", "window": window, "url": null }),
+        ["cookies", url] => json!({ "command": "cookies", "window": window, "url": url }),
+        ["clear-cache"] => json!({ "command": "clear-cache", "window": window }),
+        ["navigate", url] => json!({ "command": "navigate", "window": window, "url": url }),
+        _ => return Err(USAGE.into()),
+    };
+    let mut stream = TcpStream::connect(&addr)
+        .map_err(|err| format!("failed to connect to `{addr}`, is the app running in debug mode? ({err})"))?;
+    writeln!(stream, "{request}").map_err(|err| err.to_string())?;
+    let mut line = String::new();
+    BufReader::new(stream)
+        .read_line(&mut line)
+        .map_err(|err| err.to_string())?;
+    let mut response = serde_json::from_str::<Value>(&line).map_err(|err| err.to_string())?;
+    match response.get("error").and_then(Value::as_str) {
+        Some(message) => Err(message.into()),
+        None => Ok(response["ok"].take()),
+    }
+}
@@ -0,0 +1,70 @@
+use crate::{error::WindowContext, options, Cookie, Error, Result, WebviewExt, WebviewFutureExt};
+use futures::future::BoxFuture;
+use tauri::Window;
+use url::Url;
+
+/// Blocking variants of the cookie and cache methods of [`WebviewExt`], for code that is not async.
+///
+/// The webview is only reachable from the main thread, which runs the event loop, so these must be
+/// called from another thread that is not running async code. Otherwise they would wait for the main
+/// thread forever, and they return an error instead. The default timeout of [`crate::options`]
+/// applies when it is set.
+pub trait WebviewExtBlocking: private::WebviewExtBlockingSealed {
+    fn webview_get_cookies_blocking(&self, url: Option<Url>) -> Result<Vec<Cookie>>;
+    fn webview_delete_cookies_blocking(&self, url: Option<Url>) -> Result<Vec<Cookie>>;
+    fn webview_clear_cache_blocking(&self) -> Result<()>;
+}
+
+impl WebviewExtBlocking for Window {
+    fn webview_get_cookies_blocking(&self, url: Option<Url>) -> Result<Vec<Cookie>> {
+        block_on(self, "get cookies", || self.webview_get_cookies(url))
+    }
+
+    fn webview_delete_cookies_blocking(&self, url: Option<Url>) -> Result<Vec<Cookie>> {
+        block_on(self, "delete cookies", || self.webview_delete_cookies(url))
+    }
+
+    fn webview_clear_cache_blocking(&self) -> Result<()> {
+        block_on(self, "clear cache", || self.webview_clear_cache())
+    }
+}
+
+fn block_on<'a, T: Send + 'a>(
+    window: &Window,
+    operation: &'static str,
+    future: impl FnOnce() -> BoxFuture<'a, Result<T>>,
+) -> Result<T> {
+    if on_main_thread() {
+        let error = Error::UnsupportedPlatform("blocking calls can not be made on the main thread");
+        return Err(error).window_context(window, operation);
+    }
+    if tokio::runtime::Handle::try_current().is_ok() {
+        let error = Error::UnsupportedPlatform("blocking calls can not be made from async code");
+        return Err(error).window_context(window, operation);
+    }
+    let future = future();
+    match options::options().default_timeout {
+        Some(timeout) => tauri::async_runtime::block_on(future.with_timeout(timeout)),
+        None => tauri::async_runtime::block_on(future),
+    }
+}
+
+#[cfg(target_os = "macos")]
+pub(crate) fn on_main_thread() -> bool {
+    icrate::Foundation::is_main_thread()
+}
+
+// NOTE: the main thread of the process is the one tauri runs its event loop on, and it is the only
+// thread the standard library names `main`.
+#[cfg(not(target_os = "macos"))]
+pub(crate) fn on_main_thread() -> bool {
+    std::thread::current().name() == Some("main")
+}
+
+mod private {
+    use tauri::Window;
+
+    pub trait WebviewExtBlockingSealed {}
+    impl WebviewExtBlockingSealed for Window {
+    }
+}
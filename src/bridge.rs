@@ -0,0 +1,380 @@
+use crate::{
+    document,
+    options,
+    platform,
+    state,
+    state::WindowState,
+    stream::{self, StreamConfig},
+    BoxResult,
+    Error,
+    Result,
+};
+use futures::stream::{BoxStream, StreamExt};
+use serde::Deserialize;
+use std::{collections::HashMap, sync::Arc, time::Duration};
+use tauri::Window;
+use url::Url;
+
+pub(crate) const HANDLER_NAME: &str = "tauriWebviewUtil";
+
+const BRIDGE_SCRIPT: &str = include_str!("js/bridge.js");
+
+pub(crate) type MessageHandler = Arc<dyn Fn(&Url, serde_json::Value) + Send + Sync + 'static>;
+
+#[derive(Default)]
+pub(crate) struct BridgeState {
+    installs: HashMap<&'static str, Arc<tokio::sync::Mutex<bool>>>,
+    handlers: HashMap<String, MessageHandler>,
+    calls: HashMap<u64, oneshot::Sender<Result<serde_json::Value, String>>>,
+    streams: HashMap<u64, stream::Sender<Result<String>>>,
+    next_call: u64,
+}
+
+// NOTE: a message posted to the bridge, with the frame and the URL of the document it was posted from
+// as reported by the webview, which (unlike the body) the page can not forge.
+pub(crate) struct ScriptMessage {
+    pub(crate) body: String,
+    pub(crate) main_frame: bool,
+    pub(crate) url: Option<Url>,
+}
+
+// NOTE: the lock of a feature that is being installed. The feature is only marked as installed by
+// `done`, so an install that fails or is cancelled is retried by the next caller, and concurrent
+// callers wait for the install in progress rather than going on before the feature is ready.
+pub(crate) struct Install(tokio::sync::OwnedMutexGuard<bool>);
+
+impl Install {
+    pub(crate) fn done(mut self) {
+        *self.0 = true;
+    }
+}
+
+#[derive(Deserialize)]
+struct Message {
+    token: String,
+    name: String,
+    #[serde(default)]
+    payload: serde_json::Value,
+}
+
+#[derive(Deserialize)]
+struct Reply {
+    id: u64,
+    #[serde(default)]
+    value: serde_json::Value,
+    error: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Chunk {
+    id: u64,
+    data: Option<String>,
+    #[serde(default)]
+    done: bool,
+    error: Option<String>,
+    too_large: Option<TooLarge>,
+}
+
+#[derive(Deserialize)]
+struct TooLarge {
+    size: usize,
+    limit: usize,
+}
+
+impl From<TooLarge> for Error {
+    fn from(TooLarge { size, limit }: TooLarge) -> Self {
+        Error::ResultTooLarge { size, limit }
+    }
+}
+
+struct PendingCall<'a> {
+    state: &'a WindowState,
+    id: u64,
+}
+
+impl Drop for PendingCall<'_> {
+    fn drop(&mut self) {
+        if let Ok(mut bridge) = self.state.bridge.lock() {
+            bridge.calls.remove(&self.id);
+        }
+    }
+}
+
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn install(window: &Window) -> BoxResult<()> {
+    let state = state::window_state(window)?;
+    let Some(install) = install_once(&state, "bridge").await? else {
+        return Ok(());
+    };
+    let mut token = [0u8; 16];
+    getrandom::getrandom(&mut token)?;
+    let token = token.iter().map(|byte| format!("{byte:02x}")).collect::<String>();
+    let handler = {
+        let state = state.clone();
+        let window = window.clone();
+        let token = token.clone();
+        move |message| dispatch(&window, &state, &token, message)
+    };
+    platform::add_script_message_handler(window, HANDLER_NAME, handler).await?;
+    let token = serde_json::to_string(&token)?;
+    add_script(window, &format!("({BRIDGE_SCRIPT})({token});")).await?;
+    install.done();
+    Ok(())
+}
+
+pub(crate) async fn install_once(state: &WindowState, feature: &'static str) -> BoxResult<Option<Install>> {
+    let install = state.bridge.lock()?.installs.entry(feature).or_default().clone();
+    let installed = install.lock_owned().await;
+    Ok((!*installed).then(|| Install(installed)))
+}
+
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window, handler),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn on_message(
+    window: &Window,
+    name: &str,
+    handler: impl Fn(serde_json::Value) + Send + Sync + 'static,
+) -> BoxResult<()> {
+    on_message_from(window, name, move |_origin, payload| handler(payload)).await
+}
+
+// NOTE: like `on_message`, with the origin of the document that posted the message as reported by
+// the webview.
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window, handler),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn on_message_from(
+    window: &Window,
+    name: &str,
+    handler: impl Fn(&Url, serde_json::Value) + Send + Sync + 'static,
+) -> BoxResult<()> {
+    let state = state::window_state(window)?;
+    state.bridge.lock()?.handlers.insert(name.into(), Arc::new(handler));
+    install(window).await
+}
+
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window, source),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn add_script(window: &Window, source: &str) -> BoxResult<()> {
+    platform::add_user_script(window, source.into()).await?;
+    // NOTE: user scripts only apply to documents created after they are added, so the script is
+    // also evaluated once in the current document. Scripts must therefore be idempotent.
+    window.eval(source)?;
+    Ok(())
+}
+
+// NOTE: `body` is evaluated as the body of an async function in the current document, and the
+// value it returns (which must be JSON serializable) is sent back through the bridge.
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window, body),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn call(window: &Window, body: &str) -> BoxResult<serde_json::Value> {
+    call_with_timeout(window, body, options::options().default_timeout).await
+}
+
+// NOTE: when the timeout elapses the call is forgotten, and its reply is ignored if it still arrives.
+pub(crate) async fn call_with_timeout(
+    window: &Window,
+    body: &str,
+    timeout: Option<Duration>,
+) -> BoxResult<serde_json::Value> {
+    let state = state::window_state(window)?;
+    if let Some(install) = install_once(&state, "reply").await? {
+        on_message(window, "reply", {
+            let state = state.clone();
+            let label = String::from(window.label());
+            move |payload| reply(&label, &state, payload)
+        })
+        .await?;
+        install.done();
+    }
+    // NOTE: the document module cancels pending calls when the document unloads, since their
+    // replies would otherwise never arrive.
+    document::install(window).await?;
+    let (reply_tx, reply_rx) = oneshot::channel();
+    let id = {
+        let mut bridge = state.bridge.lock()?;
+        bridge.next_call += 1;
+        let id = bridge.next_call;
+        bridge.calls.insert(id, reply_tx);
+        id
+    };
+    // NOTE: the call is forgotten when this future is dropped, like when it is cancelled, so that its
+    // reply channel is not left behind.
+    let _pending = PendingCall { state: &state, id };
+    let script = format!("window.__TAURI_WEBVIEW_UTIL__.reply({id}, async () => {{\n{body}\n}});");
+    window.eval(&script)?;
+    let reply = match timeout {
+        Some(timeout) => match tokio::time::timeout(timeout, reply_rx).await {
+            Ok(reply) => reply,
+            Err(_) => return Err(Error::Timeout.into()),
+        },
+        None => reply_rx.await,
+    };
+    match reply {
+        Ok(Ok(value)) => Ok(value),
+        Ok(Err(err)) => Err(Error::Script(err).into()),
+        // NOTE: the reply channel is dropped when the document unloads before the script completed.
+        Err(_) => Err(Error::ChannelClosed.into()),
+    }
+}
+
+// NOTE: the result is sent as chunks of its JSON text, which are yielded in order until the stream
+// ends after the last one. Streams are not limited by the default timeout, since the result may take
+// a while to arrive in full.
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window, body),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn call_chunked(
+    window: &Window,
+    body: &str,
+    chunk_size: usize,
+) -> BoxResult<BoxStream<'static, Result<String>>> {
+    let state = state::window_state(window)?;
+    if let Some(install) = install_once(&state, "result-chunk").await? {
+        on_message(window, "result-chunk", {
+            let state = state.clone();
+            let label = String::from(window.label());
+            move |payload| chunk(&label, &state, payload)
+        })
+        .await?;
+        install.done();
+    }
+    document::install(window).await?;
+    let (chunk_tx, chunks) = stream::channel(StreamConfig::unbounded());
+    let id = {
+        let mut bridge = state.bridge.lock()?;
+        bridge.next_call += 1;
+        let id = bridge.next_call;
+        bridge.streams.insert(id, chunk_tx);
+        id
+    };
+    let max_bytes = options::options()
+        .max_result_bytes
+        .map_or_else(|| String::from("null"), |max| max.to_string());
+    let script =
+        format!("window.__TAURI_WEBVIEW_UTIL__.stream({id}, async () => {{\n{body}\n}}, {chunk_size}, {max_bytes});");
+    if let Err(err) = window.eval(&script) {
+        state.bridge.lock()?.streams.remove(&id);
+        return Err(err.into());
+    }
+    Ok(chunks.map(|chunk| chunk.and_then(std::convert::identity)).boxed())
+}
+
+// NOTE: streams that are still open are ended with an error, so a partial result is not mistaken for
+// a whole one.
+pub(crate) fn cancel_calls(window: &Window) {
+    if let Ok(state) = state::window_state(window) {
+        if let Ok(mut bridge) = state.bridge.lock() {
+            bridge.calls.clear();
+            for (_, chunk_tx) in bridge.streams.drain() {
+                chunk_tx.send(Err(Error::ChannelClosed)).ok();
+            }
+        }
+    }
+}
+
+fn reply(label: &str, state: &WindowState, payload: serde_json::Value) {
+    let reply = match serde_json::from_value::<Reply>(payload) {
+        Ok(reply) => reply,
+        Err(err) => return crate::report_error(label, "malformed bridge reply", err),
+    };
+    let reply_tx = match state.bridge.lock() {
+        Ok(mut bridge) => bridge.calls.remove(&reply.id),
+        Err(_) => None,
+    };
+    if let Some(reply_tx) = reply_tx {
+        let result = match reply.error {
+            Some(error) => Err(error),
+            None => Ok(reply.value),
+        };
+        reply_tx.send(result).ok();
+    }
+}
+
+fn chunk(label: &str, state: &WindowState, payload: serde_json::Value) {
+    let chunk = match serde_json::from_value::<Chunk>(payload) {
+        Ok(chunk) => chunk,
+        Err(err) => return crate::report_error(label, "malformed bridge chunk", err),
+    };
+    let id = chunk.id;
+    let last = chunk.done || chunk.error.is_some() || chunk.too_large.is_some();
+    let chunk_tx = match state.bridge.lock() {
+        Ok(mut bridge) if last => bridge.streams.remove(&id),
+        Ok(bridge) => bridge.streams.get(&id).cloned(),
+        Err(_) => None,
+    };
+    let Some(chunk_tx) = chunk_tx else {
+        return;
+    };
+    let item = match chunk {
+        Chunk { error: Some(error), .. } => Err(Error::Script(error)),
+        Chunk {
+            too_large: Some(too_large),
+            ..
+        } => Err(too_large.into()),
+        Chunk { data: Some(data), .. } => Ok(data),
+        _ => return,
+    };
+    // NOTE: the stream was dropped, so the rest of the result is not needed.
+    if chunk_tx.send(item).is_err() {
+        if let Ok(mut bridge) = state.bridge.lock() {
+            bridge.streams.remove(&id);
+        }
+    }
+}
+
+// NOTE: the token is only known to the bridge script of the main frame, which keeps out messages
+// posted by subframes on webviews that do not report the frame a message was posted from. Messages
+// are also rejected when the document that posted them is not of the origin of the window, like
+// when they arrive after a navigation.
+fn dispatch(window: &Window, state: &WindowState, token: &str, message: ScriptMessage) {
+    let label = window.label();
+    let body = match serde_json::from_str::<Message>(&message.body) {
+        Ok(body) => body,
+        Err(err) => return crate::report_error(label, "malformed bridge message", err),
+    };
+    if body.token != token || !message.main_frame {
+        return crate::report_error(label, "rejected bridge message", "not posted from the main frame");
+    }
+    let origin = match (message.url, window.url()) {
+        (Some(url), Ok(expected)) if same_origin(&url, &expected) => origin(&url),
+        _ => return crate::report_error(label, "rejected bridge message", "not posted from the window origin"),
+    };
+    let handler = match state.bridge.lock() {
+        Ok(bridge) => bridge.handlers.get(&body.name).cloned(),
+        Err(_) => None,
+    };
+    if let Some(handler) = handler {
+        handler(&origin, body.payload);
+    }
+}
+
+// NOTE: origins are compared by scheme, host, and port rather than with `Url::origin`, which is opaque
+// (and never equal) for custom schemes like the one of the app itself.
+fn same_origin(lhs: &Url, rhs: &Url) -> bool {
+    lhs.scheme() == rhs.scheme()
+        && lhs.host() == rhs.host()
+        && lhs.port_or_known_default() == rhs.port_or_known_default()
+}
+
+fn origin(url: &Url) -> Url {
+    let mut origin = url.clone();
+    origin.set_username("").ok();
+    origin.set_password(None).ok();
+    origin.set_path("");
+    origin.set_query(None);
+    origin.set_fragment(None);
+    origin
+}
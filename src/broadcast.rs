@@ -0,0 +1,91 @@
+use crate::{bridge, error::WindowContext, init_script, state, stream, BoxResult, Result};
+use futures::stream::BoxStream;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use tauri::Window;
+
+const BROADCAST_SCRIPT: &str = include_str!("js/broadcast.js");
+
+#[derive(Default)]
+pub(crate) struct BroadcastState {
+    channels: BTreeMap<String, Vec<stream::Sender<serde_json::Value>>>,
+}
+
+#[derive(Deserialize)]
+struct BroadcastMessage {
+    name: String,
+    data: serde_json::Value,
+}
+
+#[derive(Clone)]
+pub struct BroadcastSender {
+    window: Window,
+    name: String,
+}
+
+impl BroadcastSender {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    // NOTE: the message is delivered to the channels of the current document with the same name, and
+    // to those of other documents of its origin, like a message posted by another document would be.
+    pub fn send(&self, message: &serde_json::Value) -> Result<()> {
+        let name = serde_json::to_string(&self.name)?;
+        let script = format!("window.__TAURI_WEBVIEW_UTIL_BROADCAST__?.post({name}, {message});");
+        self.window
+            .eval(&script)
+            .window_context(&self.window, "post broadcast message")
+    }
+}
+
+// NOTE: only messages that can be represented as JSON are reported, messages with other structured
+// clonable values (like blobs or typed arrays) are skipped.
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn channel(
+    window: &Window,
+    name: String,
+) -> BoxResult<(BroadcastSender, BoxStream<'static, Result<serde_json::Value>>)> {
+    let state = state::window_state(window)?;
+    let (message_tx, message_rx) = stream::channel(stream::stream_config());
+    let (added, names) = {
+        let mut broadcast = state.broadcast.lock()?;
+        let added = !broadcast.channels.contains_key(&name);
+        broadcast.channels.entry(name.clone()).or_default().push(message_tx);
+        (added, broadcast.channels.keys().cloned().collect::<Vec<_>>())
+    };
+    if let Some(install) = bridge::install_once(&state, "broadcast").await? {
+        bridge::on_message(window, "broadcast", {
+            let window = window.clone();
+            move |payload| match serde_json::from_value::<BroadcastMessage>(payload) {
+                Ok(message) => publish(&window, message),
+                Err(err) => crate::report_error(window.label(), "malformed broadcast message", err),
+            }
+        })
+        .await?;
+        install.done();
+    }
+    if added {
+        let config = serde_json::json!({ "names": names });
+        let source = format!("({BROADCAST_SCRIPT})({config});");
+        init_script::replace(window, &state.broadcast_script, source).await?;
+    }
+    let sender = BroadcastSender {
+        window: window.clone(),
+        name,
+    };
+    Ok((sender, message_rx))
+}
+
+fn publish(window: &Window, message: BroadcastMessage) {
+    if let Ok(state) = state::window_state(window) {
+        if let Ok(mut broadcast) = state.broadcast.lock() {
+            if let Some(subscribers) = broadcast.channels.get_mut(&message.name) {
+                subscribers.retain(|subscriber| subscriber.send(message.data.clone()).is_ok());
+            }
+        }
+    }
+}
@@ -0,0 +1,421 @@
+use crate::{cookie_db, platform, BoxResult, Cookie, CookiePattern, Error, Result};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use tauri::Window;
+use time::OffsetDateTime;
+
+// NOTE: Safari stores dates in seconds since 2001-01-01.
+const MAC_EPOCH_OFFSET: f64 = 978_307_200.0;
+// NOTE: since this version of the database Chromium prefixes the values with the SHA-256 of the host
+// before encrypting them.
+const CHROMIUM_HASHED_HOST_VERSION: i64 = 24;
+
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[non_exhaustive]
+pub enum Browser {
+    Brave,
+    Chrome,
+    Chromium,
+    Edge,
+    Firefox,
+    Safari,
+}
+
+impl Browser {
+    fn is_chromium(&self) -> bool {
+        matches!(self, Self::Brave | Self::Chrome | Self::Chromium | Self::Edge)
+    }
+
+    // NOTE: the directory that contains the profiles (and for Chromium the `Local State` file).
+    fn directory(&self) -> Option<PathBuf> {
+        #[cfg(target_os = "windows")]
+        {
+            let local = PathBuf::from(std::env::var_os("LOCALAPPDATA")?);
+            let roaming = PathBuf::from(std::env::var_os("APPDATA")?);
+            match self {
+                Self::Brave => Some(local.join(r"BraveSoftware\Brave-Browser\User Data")),
+                Self::Chrome => Some(local.join(r"Google\Chrome\User Data")),
+                Self::Chromium => Some(local.join(r"Chromium\User Data")),
+                Self::Edge => Some(local.join(r"Microsoft\Edge\User Data")),
+                Self::Firefox => Some(roaming.join(r"Mozilla\Firefox")),
+                Self::Safari => None,
+            }
+        }
+        #[cfg(target_os = "macos")]
+        {
+            let home = PathBuf::from(std::env::var_os("HOME")?);
+            let support = home.join("Library/Application Support");
+            match self {
+                Self::Brave => Some(support.join("BraveSoftware/Brave-Browser")),
+                Self::Chrome => Some(support.join("Google/Chrome")),
+                Self::Chromium => Some(support.join("Chromium")),
+                Self::Edge => Some(support.join("Microsoft Edge")),
+                Self::Firefox => Some(support.join("Firefox")),
+                Self::Safari => Some(home.join("Library/Containers/com.apple.Safari/Data/Library/Cookies")),
+            }
+        }
+        #[cfg(not(any(target_os = "macos", target_os = "windows")))]
+        {
+            let home = PathBuf::from(std::env::var_os("HOME")?);
+            let config = std::env::var_os("XDG_CONFIG_HOME").map_or_else(|| home.join(".config"), PathBuf::from);
+            match self {
+                Self::Brave => Some(config.join("BraveSoftware/Brave-Browser")),
+                Self::Chrome => Some(config.join("google-chrome")),
+                Self::Chromium => Some(config.join("chromium")),
+                Self::Edge => Some(config.join("microsoft-edge")),
+                Self::Firefox => Some(home.join(".mozilla/firefox")),
+                Self::Safari => None,
+            }
+        }
+    }
+
+    // NOTE: the name under which the browser keeps the password for its cookie key in the keychain
+    // (macOS) or the secret service (Linux).
+    #[cfg(not(target_os = "windows"))]
+    fn keychain_name(&self) -> &'static str {
+        match self {
+            Self::Brave => "Brave",
+            Self::Chrome => "Chrome",
+            Self::Edge => "Microsoft Edge",
+            _ => "Chromium",
+        }
+    }
+}
+
+// NOTE: reads the cookies matching the pattern from a desktop browser. The profile is the name of
+// the profile directory for Chromium based browsers (`Default` when not given) and the name of the
+// profile for Firefox (the default profile when not given), and is ignored for Safari. Expired
+// cookies and cookies that can not be decrypted are skipped, which includes the app-bound
+// encrypted cookies of Chromium on Windows. Reading the cookies of Safari requires full disk access,
+// and reading the key of a Chromium based browser may prompt the user for access to the keychain.
+pub async fn import_from_browser(
+    browser: Browser,
+    profile: Option<String>,
+    pattern: CookiePattern,
+) -> Result<Vec<Cookie>> {
+    Ok(read(browser, profile, pattern).await?)
+}
+
+#[cfg_attr(feature = "tracing", tracing::instrument(skip(pattern)))]
+pub(crate) async fn read(browser: Browser, profile: Option<String>, pattern: CookiePattern) -> BoxResult<Vec<Cookie>> {
+    let directory = browser.directory().ok_or(Error::UnsupportedPlatform(
+        "the browser is not supported on this platform",
+    ))?;
+    let keys = if browser.is_chromium() {
+        keys(browser, &directory).await?
+    } else {
+        vec![]
+    };
+    let mut cookies = tauri::async_runtime::spawn_blocking(move || match browser {
+        Browser::Firefox => read_firefox(&directory, profile.as_deref()),
+        Browser::Safari => read_safari(&directory),
+        _ => read_chromium(&directory, profile.as_deref().unwrap_or("Default"), &keys),
+    })
+    .await??;
+    let now = OffsetDateTime::now_utc();
+    cookies.retain(|cookie| cookie.expires.map_or(true, |expires| expires > now) && pattern.matches(cookie));
+    Ok(cookies)
+}
+
+// NOTE: returns the cookies that were installed into the webview.
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window, pattern),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn install(
+    window: &Window,
+    browser: Browser,
+    profile: Option<String>,
+    pattern: CookiePattern,
+) -> BoxResult<Vec<Cookie>> {
+    let cookies = read(browser, profile, pattern).await?;
+    for cookie in &cookies {
+        platform::set_cookie(window, cookie.clone()).await?;
+    }
+    Ok(cookies)
+}
+
+// NOTE: the browsers keep their databases locked while they run, so they are copied (along with
+// their write-ahead log) to be read.
+fn with_copy<T>(path: &Path, read: impl FnOnce(&Path) -> BoxResult<T>) -> BoxResult<T> {
+    let nanos = OffsetDateTime::now_utc().unix_timestamp_nanos();
+    let directory = std::env::temp_dir()
+        .join("tauri-webview-util")
+        .join("browser-import")
+        .join(nanos.to_string());
+    std::fs::create_dir_all(&directory)?;
+    let result = (|| {
+        let name = path
+            .file_name()
+            .ok_or_else(|| Error::Other("invalid database path".into()))?;
+        let copy = directory.join(name);
+        std::fs::copy(path, &copy)?;
+        let mut wal = path.as_os_str().to_os_string();
+        wal.push("-wal");
+        if Path::new(&wal).exists() {
+            let mut copy_wal = copy.as_os_str().to_os_string();
+            copy_wal.push("-wal");
+            std::fs::copy(wal, copy_wal)?;
+        }
+        read(&copy)
+    })();
+    std::fs::remove_dir_all(&directory).ok();
+    result
+}
+
+enum Cipher {
+    #[cfg(not(target_os = "windows"))]
+    Cbc([u8; 16]),
+    #[cfg(target_os = "windows")]
+    Gcm([u8; 32]),
+}
+
+// NOTE: the encrypted values are prefixed with the version of their key.
+type Keys = Vec<(&'static [u8], Cipher)>;
+
+#[cfg(not(target_os = "windows"))]
+fn derive_key(password: &[u8], rounds: u32) -> [u8; 16] {
+    let mut key = [0; 16];
+    pbkdf2::pbkdf2_hmac::<sha1::Sha1>(password, b"saltysalt", rounds, &mut key);
+    key
+}
+
+// NOTE: `v10` values are encrypted with a hardcoded password when no secret service is available,
+// and `v11` values with the password that the browser keeps in the secret service. Without a secret
+// service only the former can be decrypted.
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+async fn keys(browser: Browser, _directory: &Path) -> BoxResult<Keys> {
+    let mut keys = vec![(&b"v10"[..], Cipher::Cbc(derive_key(b"peanuts", 1)))];
+    match secret(browser).await {
+        Ok(Some(password)) => keys.push((&b"v11"[..], Cipher::Cbc(derive_key(&password, 1)))),
+        Ok(None) => {},
+        #[allow(unused_variables)]
+        Err(err) => {
+            #[cfg(feature = "tracing")]
+            tracing::warn!(%err, "failed to read the cookie key from the secret service");
+        },
+    }
+    Ok(keys)
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+async fn secret(browser: Browser) -> BoxResult<Option<Vec<u8>>> {
+    use secret_service::{EncryptionType, SecretService};
+    let application = browser.keychain_name().to_lowercase().replace(' ', "-");
+    let service = SecretService::connect(EncryptionType::Dh).await?;
+    let attributes = std::collections::HashMap::from([("application", application.as_str())]);
+    let items = service.search_items(attributes).await?;
+    let item = match items.unlocked.into_iter().chain(items.locked).next() {
+        Some(item) => item,
+        None => return Ok(None),
+    };
+    if item.is_locked().await? {
+        item.unlock().await?;
+    }
+    Ok(Some(item.get_secret().await?))
+}
+
+#[cfg(target_os = "macos")]
+async fn keys(browser: Browser, _directory: &Path) -> BoxResult<Keys> {
+    let name = browser.keychain_name();
+    let password = security_framework::passwords::get_generic_password(&format!("{name} Safe Storage"), name)?;
+    Ok(vec![(&b"v10"[..], Cipher::Cbc(derive_key(&password, 1003)))])
+}
+
+// NOTE: the key is kept in the `Local State` file, encrypted with DPAPI for the current user.
+#[cfg(target_os = "windows")]
+async fn keys(_browser: Browser, directory: &Path) -> BoxResult<Keys> {
+    use base64::Engine;
+    use windows::Win32::{
+        Security::Cryptography::{CryptUnprotectData, CRYPT_INTEGER_BLOB},
+        System::Memory::LocalFree,
+    };
+    let local_state = std::fs::read_to_string(directory.join("Local State"))?;
+    let local_state = serde_json::from_str::<serde_json::Value>(&local_state)?;
+    let encrypted_key = local_state["os_crypt"]["encrypted_key"]
+        .as_str()
+        .ok_or_else(|| Error::Other("the browser has no cookie key".into()))?;
+    let encrypted_key = base64::engine::general_purpose::STANDARD.decode(encrypted_key)?;
+    let encrypted_key = encrypted_key
+        .strip_prefix(b"DPAPI")
+        .ok_or_else(|| Error::Other("the cookie key is not encrypted with DPAPI".into()))?
+        .to_vec();
+    let input = CRYPT_INTEGER_BLOB {
+        cbData: encrypted_key.len() as u32,
+        pbData: encrypted_key.as_ptr() as *mut u8,
+    };
+    let mut output = CRYPT_INTEGER_BLOB::default();
+    let key = unsafe {
+        CryptUnprotectData(&input, None, None, None, None, 0, &mut output)
+            .ok()
+            .map_err(|err| Error::PlatformApi(err.into()))?;
+        let key = std::slice::from_raw_parts(output.pbData, output.cbData as usize).to_vec();
+        LocalFree(output.pbData as isize);
+        key
+    };
+    let key = <[u8; 32]>::try_from(key).map_err(|_| Error::Other("the cookie key has an invalid size".into()))?;
+    Ok(vec![(&b"v10"[..], Cipher::Gcm(key))])
+}
+
+fn decrypt(keys: &Keys, hashed_host: bool, host: &str, value: &[u8]) -> Option<String> {
+    let (data, cipher) = keys
+        .iter()
+        .find_map(|(prefix, cipher)| Some((value.strip_prefix(*prefix)?, cipher)))?;
+    let decrypted = match cipher {
+        #[cfg(not(target_os = "windows"))]
+        Cipher::Cbc(key) => {
+            use cbc::cipher::{block_padding::Pkcs7, BlockDecryptMut, KeyIvInit};
+            cbc::Decryptor::<aes::Aes128>::new(key.into(), &[b' '; 16].into())
+                .decrypt_padded_vec_mut::<Pkcs7>(data)
+                .ok()?
+        },
+        #[cfg(target_os = "windows")]
+        Cipher::Gcm(key) => {
+            use aes_gcm::{aead::Aead, Aes256Gcm, KeyInit, Nonce};
+            if data.len() < 12 {
+                return None;
+            }
+            let (nonce, ciphertext) = data.split_at(12);
+            Aes256Gcm::new(key.into())
+                .decrypt(Nonce::from_slice(nonce), ciphertext)
+                .ok()?
+        },
+    };
+    let decrypted = if hashed_host {
+        use sha2::{Digest, Sha256};
+        decrypted.strip_prefix(&Sha256::digest(host.as_bytes())[..])?.to_vec()
+    } else {
+        decrypted
+    };
+    String::from_utf8(decrypted).ok()
+}
+
+fn read_chromium(directory: &Path, profile: &str, keys: &Keys) -> BoxResult<Vec<Cookie>> {
+    let profile = directory.join(profile);
+    let path = [profile.join("Network").join("Cookies"), profile.join("Cookies")]
+        .into_iter()
+        .find(|path| path.exists())
+        .ok_or_else(|| Error::Other(format!("no cookies found in `{}`", profile.display()).into()))?;
+    with_copy(&path, |path| {
+        let version = cookie_db::open(path)?
+            .query_row("SELECT value FROM meta WHERE key = 'version'", [], |row| {
+                row.get::<_, String>(0)
+            })
+            .ok()
+            .and_then(|version| version.parse::<i64>().ok())
+            .unwrap_or_default();
+        let hashed_host = version >= CHROMIUM_HASHED_HOST_VERSION;
+        let (cookies, _) =
+            cookie_db::read_chromium_cookies(path, |host, value| decrypt(keys, hashed_host, host, value))?;
+        Ok(cookies)
+    })
+}
+
+// NOTE: without a profile name, the default profile of the most recent installation is used, and
+// otherwise the profile that is marked as the default.
+fn read_firefox(directory: &Path, profile: Option<&str>) -> BoxResult<Vec<Cookie>> {
+    let profiles = std::fs::read_to_string(directory.join("profiles.ini"))?;
+    let mut sections = vec![];
+    for line in profiles.lines().map(str::trim) {
+        if let Some(section) = line.strip_prefix('[').and_then(|line| line.strip_suffix(']')) {
+            sections.push((section, vec![]));
+        } else if let (Some((key, value)), Some((_, entries))) = (line.split_once('='), sections.last_mut()) {
+            entries.push((key.trim(), value.trim()));
+        }
+    }
+    let get = |entries: &[(&str, &str)], key: &str| {
+        entries
+            .iter()
+            .find(|(entry, _)| *entry == key)
+            .map(|(_, value)| String::from(*value))
+    };
+    let profile_sections = sections
+        .iter()
+        .filter(|(section, _)| section.starts_with("Profile"))
+        .map(|(_, entries)| entries.as_slice());
+    let path = match profile {
+        Some(profile) => profile_sections
+            .clone()
+            .find(|entries| get(entries, "Name").as_deref() == Some(profile))
+            .and_then(|entries| profile_path(entries, &get)),
+        None => sections
+            .iter()
+            .filter(|(section, _)| section.starts_with("Install"))
+            .find_map(|(_, entries)| get(entries, "Default"))
+            .map(|path| (path, true))
+            .or_else(|| {
+                profile_sections
+                    .clone()
+                    .find(|entries| get(entries, "Default").as_deref() == Some("1"))
+                    .and_then(|entries| profile_path(entries, &get))
+            }),
+    };
+    let (path, relative) = path.ok_or_else(|| Error::Other("no such Firefox profile".into()))?;
+    let path = if relative {
+        directory.join(path)
+    } else {
+        PathBuf::from(path)
+    };
+    with_copy(&path.join("cookies.sqlite"), cookie_db::read_moz_cookies)
+}
+
+fn profile_path(
+    entries: &[(&str, &str)],
+    get: &impl Fn(&[(&str, &str)], &str) -> Option<String>,
+) -> Option<(String, bool)> {
+    let path = get(entries, "Path")?;
+    let relative = get(entries, "IsRelative").as_deref() != Some("0");
+    Some((path, relative))
+}
+
+// NOTE: the binary cookies format consists of big-endian page sizes followed by pages of
+// little-endian cookie records, whose strings are referenced by offsets into the record.
+fn read_safari(directory: &Path) -> BoxResult<Vec<Cookie>> {
+    let bytes = std::fs::read(directory.join("Cookies.binarycookies"))?;
+    parse_binary_cookies(&bytes).ok_or_else(|| Error::CookieConversion("invalid binary cookies".into()).into())
+}
+
+fn parse_binary_cookies(bytes: &[u8]) -> Option<Vec<Cookie>> {
+    let be_u32 = |offset: usize| Some(u32::from_be_bytes(bytes.get(offset .. offset + 4)?.try_into().ok()?) as usize);
+    if bytes.get(.. 4)? != b"cook" {
+        return None;
+    }
+    let pages = be_u32(4)?;
+    let mut offset = 8 + pages * 4;
+    let mut cookies = vec![];
+    for index in 0 .. pages {
+        let size = be_u32(8 + index * 4)?;
+        let page = bytes.get(offset .. offset + size)?;
+        offset += size;
+        let le_u32 =
+            |offset: usize| Some(u32::from_le_bytes(page.get(offset .. offset + 4)?.try_into().ok()?) as usize);
+        for cookie in 0 .. le_u32(4)? {
+            let start = le_u32(8 + cookie * 4)?;
+            let record = page.get(start .. start + le_u32(start)?)?;
+            cookies.push(parse_binary_cookie(record)?);
+        }
+    }
+    Some(cookies)
+}
+
+fn parse_binary_cookie(record: &[u8]) -> Option<Cookie> {
+    let u32_at = |offset: usize| Some(u32::from_le_bytes(record.get(offset .. offset + 4)?.try_into().ok()?));
+    let f64_at = |offset: usize| Some(f64::from_le_bytes(record.get(offset .. offset + 8)?.try_into().ok()?));
+    let string_at = |offset: usize| {
+        let start = u32_at(offset)? as usize;
+        let bytes = record.get(start ..)?;
+        let end = bytes.iter().position(|byte| *byte == 0)?;
+        String::from_utf8(bytes[.. end].to_vec()).ok()
+    };
+    let flags = u32_at(8)?;
+    let expires = OffsetDateTime::from_unix_timestamp((f64_at(40)? + MAC_EPOCH_OFFSET) as i64).ok()?;
+    Some(Cookie {
+        domain: string_at(16)?,
+        path: string_at(24)?,
+        expires: Some(expires),
+        http_only: flags & 4 != 0,
+        secure: flags & 1 != 0,
+        session: false,
+        ..Cookie::new(string_at(20)?, string_at(28)?)
+    })
+}
@@ -0,0 +1,73 @@
+use crate::{bridge, event_bus, event_bus::WebviewEvent, init_script, state, BoxResult};
+use serde::{Deserialize, Serialize};
+use tauri::Window;
+use url::Url;
+
+const BUDGET_SCRIPT: &str = include_str!("js/budget.js");
+
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub enum BudgetAction {
+    #[default]
+    Warn,
+    Block,
+    Event,
+}
+
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct PageBudget {
+    pub max_bytes: Option<u64>,
+    pub max_requests: Option<u64>,
+    pub on_exceed: BudgetAction,
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct BudgetExceeded {
+    pub url: Url,
+    pub bytes: u64,
+    pub requests: u64,
+}
+
+// NOTE: usage is counted from the resource timing entries of each document, with the same caveat as
+// the bandwidth stats that cross-origin responses without `Timing-Allow-Origin` count as zero bytes.
+// Blocking stops the document from loading anything further, and is reported like an event.
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn enforce(window: &Window, budget: PageBudget) -> BoxResult<()> {
+    let state = state::window_state(window)?;
+    *state.budget.lock()? = budget;
+    if let Some(install) = bridge::install_once(&state, "budget").await? {
+        bridge::on_message(window, "budget-exceeded", {
+            let window = window.clone();
+            move |payload| {
+                let exceeded = match serde_json::from_value::<BudgetExceeded>(payload) {
+                    Ok(exceeded) => exceeded,
+                    Err(err) => return crate::report_error(window.label(), "malformed budget report", err),
+                };
+                let action = state::window_state(&window)
+                    .and_then(|state| Ok(state.budget.lock()?.on_exceed))
+                    .unwrap_or_default();
+                match action {
+                    BudgetAction::Warn => crate::report_error(
+                        window.label(),
+                        "page budget exceeded",
+                        format!("{} bytes in {} requests", exceeded.bytes, exceeded.requests),
+                    ),
+                    BudgetAction::Block | BudgetAction::Event => {
+                        event_bus::publish(&window, WebviewEvent::BudgetExceeded(exceeded))
+                    },
+                }
+            }
+        })
+        .await?;
+        install.done();
+    }
+    let config = serde_json::json!({
+        "maxBytes": budget.max_bytes,
+        "maxRequests": budget.max_requests,
+        "block": budget.on_exceed == BudgetAction::Block,
+    });
+    let source = format!("({BUDGET_SCRIPT})({config});");
+    init_script::replace(window, &state.budget_script, source).await
+}
@@ -0,0 +1,57 @@
+#[cfg(target_os = "windows")]
+use crate::DataRecord;
+use crate::{platform, BoxResult, DataKinds};
+use serde::{Deserialize, Serialize};
+use tauri::Window;
+use url::Url;
+
+// NOTE: like the name of a `DataRecord`, the origin is what the engine groups the data under, which
+// is a domain with WebKit. Sizes are `None` where the engine does not report them.
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct OriginUsage {
+    pub origin: String,
+    pub disk_cache: Option<u64>,
+    pub memory_cache: Option<u64>,
+}
+
+// NOTE: records of WebView2 are named by origin and are cleared through the DevTools protocol,
+// whether or not they were listed. The HTTP cache is shared by all origins there, so only Cache
+// Storage is cleared.
+#[cfg(target_os = "windows")]
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn clear_for_origins(window: &Window, origins: Vec<Url>) -> BoxResult<()> {
+    let records = origins
+        .iter()
+        .map(|origin| DataRecord {
+            name: origin.origin().ascii_serialization(),
+            kinds: DataKinds::CACHES,
+        })
+        .collect();
+    platform::remove_data_records(window, records).await
+}
+
+// NOTE: records of WebKit are named by the registrable domain, so an origin clears the caches of
+// every origin of its domain.
+#[cfg(not(target_os = "windows"))]
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn clear_for_origins(window: &Window, origins: Vec<Url>) -> BoxResult<()> {
+    let records = platform::list_data_records(window, DataKinds::CACHES)
+        .await?
+        .into_iter()
+        .filter(|record| origins.iter().any(|origin| in_domain(origin, &record.name)))
+        .collect();
+    platform::remove_data_records(window, records).await
+}
+
+#[cfg(not(target_os = "windows"))]
+fn in_domain(origin: &Url, domain: &str) -> bool {
+    origin.host_str().map_or(false, |host| {
+        host == domain || host.strip_suffix(domain).map_or(false, |prefix| prefix.ends_with('.'))
+    })
+}
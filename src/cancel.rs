@@ -0,0 +1,191 @@
+use crate::{Error, Result};
+use futures::{
+    channel::oneshot,
+    future::{self, BoxFuture, Either, Shared},
+    prelude::*,
+    stream::{self, BoxStream},
+};
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+// NOTE: cancelling an operation drops its future. Completion handlers of the platform that fire
+// later find their channel closed and do nothing, and scripts still running in the page are
+// forgotten, so their replies are ignored.
+#[derive(Clone)]
+pub struct CancellationToken {
+    sender: Arc<Mutex<Option<oneshot::Sender<()>>>>,
+    cancelled: Shared<oneshot::Receiver<()>>,
+}
+
+impl CancellationToken {
+    pub fn new() -> Self {
+        let (sender, cancelled) = oneshot::channel();
+        Self {
+            sender: Arc::new(Mutex::new(Some(sender))),
+            cancelled: cancelled.shared(),
+        }
+    }
+
+    pub fn cancel(&self) {
+        if let Ok(mut sender) = self.sender.lock() {
+            sender.take();
+        }
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.sender.lock().map_or(true, |sender| sender.is_none())
+    }
+
+    pub fn cancelled(&self) -> impl Future<Output = ()> + Send + 'static {
+        self.cancelled.clone().map(|_| ())
+    }
+}
+
+impl Default for CancellationToken {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl std::fmt::Debug for CancellationToken {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.debug_struct("CancellationToken")
+            .field("cancelled", &self.is_cancelled())
+            .finish()
+    }
+}
+
+// NOTE: for the futures returned by `WebviewExt`. Operations fail with `Error::Timeout` or
+// `Error::Cancelled`, and are never started when the token was cancelled before. The timer of a
+// timeout is only created once the future is first polled, which must happen within a tokio runtime
+// like the async runtime of tauri, so the future can still be created on any thread.
+pub trait WebviewFutureExt<'a, T>: private::Sealed {
+    fn with_timeout(self, timeout: Duration) -> BoxFuture<'a, Result<T>>;
+    fn with_cancellation(self, token: &CancellationToken) -> BoxFuture<'a, Result<T>>;
+}
+
+impl<'a, T: Send + 'a> WebviewFutureExt<'a, T> for BoxFuture<'a, Result<T>> {
+    fn with_timeout(self, timeout: Duration) -> BoxFuture<'a, Result<T>> {
+        async move {
+            tokio::time::timeout(timeout, self)
+                .await
+                .unwrap_or_else(|_| Err(Error::Timeout))
+        }
+        .boxed()
+    }
+
+    fn with_cancellation(self, token: &CancellationToken) -> BoxFuture<'a, Result<T>> {
+        let token = token.clone();
+        async move {
+            if token.is_cancelled() {
+                return Err(Error::Cancelled);
+            }
+            match future::select(self, token.cancelled().boxed()).await {
+                Either::Left((result, _)) => result,
+                Either::Right(((), _)) => Err(Error::Cancelled),
+            }
+        }
+        .boxed()
+    }
+}
+
+// NOTE: for the streams returned by `WebviewExt`. The timeout is the longest wait for the next item,
+// after which the stream yields `Error::Timeout` and ends. Cancelled streams end without an error,
+// and the token is checked before every item, so no item is yielded once it was cancelled. The
+// subscription of the inner stream is removed with the next event after it is dropped. Like the
+// timeout of futures, the timeout of streams must be polled within a tokio runtime.
+pub trait WebviewStreamExt<'a, T>: private::Sealed {
+    fn with_timeout(self, timeout: Duration) -> BoxStream<'a, Result<T>>;
+    fn with_cancellation(self, token: &CancellationToken) -> BoxStream<'a, Result<T>>;
+}
+
+impl<'a, T: Send + 'a> WebviewStreamExt<'a, T> for BoxStream<'a, Result<T>> {
+    fn with_timeout(self, timeout: Duration) -> BoxStream<'a, Result<T>> {
+        stream::unfold(Some(self), move |stream| async move {
+            let mut stream = stream?;
+            match tokio::time::timeout(timeout, stream.next()).await {
+                Ok(Some(item)) => Some((item, Some(stream))),
+                Ok(None) => None,
+                Err(_) => Some((Err(Error::Timeout), None)),
+            }
+        })
+        .boxed()
+    }
+
+    fn with_cancellation(self, token: &CancellationToken) -> BoxStream<'a, Result<T>> {
+        self.take_until(token.cancelled()).boxed()
+    }
+}
+
+mod private {
+    use crate::Result;
+    use futures::{future::BoxFuture, stream::BoxStream};
+
+    pub trait Sealed {}
+    impl<'a, T> Sealed for BoxFuture<'a, Result<T>> {
+    }
+    impl<'a, T> Sealed for BoxStream<'a, Result<T>> {
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TIMEOUT: Duration = Duration::from_millis(10);
+
+    #[test]
+    fn futures_time_out() {
+        let result = tauri::async_runtime::block_on(future::pending::<Result<()>>().boxed().with_timeout(TIMEOUT));
+        assert!(matches!(result, Err(Error::Timeout)));
+    }
+
+    #[test]
+    fn futures_of_cancelled_tokens_are_not_started() {
+        let token = CancellationToken::new();
+        token.cancel();
+        let future: BoxFuture<Result<()>> = async { panic!("the future was started") }.boxed();
+        let result = tauri::async_runtime::block_on(future.with_cancellation(&token));
+        assert!(matches!(result, Err(Error::Cancelled)));
+    }
+
+    #[test]
+    fn cancelling_ends_pending_futures() {
+        let token = CancellationToken::new();
+        let future = future::pending::<Result<()>>().boxed().with_cancellation(&token);
+        let (result, ()) = tauri::async_runtime::block_on(future::join(future, async { token.cancel() }));
+        assert!(matches!(result, Err(Error::Cancelled)));
+    }
+
+    #[test]
+    fn streams_time_out_once_and_end() {
+        let stream = stream::iter([Ok::<_, Error>(1)]).chain(stream::pending()).boxed();
+        let items = tauri::async_runtime::block_on(stream.with_timeout(TIMEOUT).collect::<Vec<_>>());
+        assert!(matches!(items[..], [Ok(1), Err(Error::Timeout)]));
+    }
+
+    #[test]
+    fn cancelling_ends_pending_streams() {
+        let token = CancellationToken::new();
+        let mut stream = stream::iter([Ok::<_, Error>(1), Ok(2)])
+            .chain(stream::pending())
+            .boxed()
+            .with_cancellation(&token);
+        tauri::async_runtime::block_on(async {
+            assert!(matches!(stream.next().await, Some(Ok(1))));
+            token.cancel();
+            assert!(stream.next().await.is_none());
+        });
+    }
+
+    #[test]
+    fn streams_of_cancelled_tokens_are_empty() {
+        let token = CancellationToken::new();
+        token.cancel();
+        let stream = stream::iter([Ok::<_, Error>(1)]).boxed().with_cancellation(&token);
+        let items = tauri::async_runtime::block_on(stream.collect::<Vec<_>>());
+        assert!(items.is_empty());
+    }
+}
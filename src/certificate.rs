@@ -0,0 +1,73 @@
+use crate::{bridge, platform, state, BoxResult};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tauri::Window;
+use url::Url;
+
+pub(crate) type CertificateErrorHandler = Arc<dyn Fn(CertificateErrorInfo) -> CertDecision + Send + Sync + 'static>;
+
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[non_exhaustive]
+pub enum CertificateErrorKind {
+    Expired,
+    UnknownAuthority,
+    HostMismatch,
+    Revoked,
+    Invalid,
+    Other,
+}
+
+// NOTE: with WKWebView, the URL is the origin of the server, since the request is not known when
+// its certificate is evaluated.
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[non_exhaustive]
+pub struct CertificateErrorInfo {
+    pub url: Url,
+    pub kind: CertificateErrorKind,
+}
+
+impl CertificateErrorInfo {
+    pub fn host(&self) -> Option<&str> {
+        self.url.host_str()
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub enum CertDecision {
+    // NOTE: the load fails like it would without a handler.
+    #[default]
+    Deny,
+    Proceed,
+}
+
+// NOTE: once a certificate is accepted for a host, webkit2gtk and WebView2 remember it until the
+// app exits, so the handler is not asked again for that host. Later calls replace the handler.
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window, handler),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn on_error(
+    window: &Window,
+    handler: impl Fn(CertificateErrorInfo) -> CertDecision + Send + Sync + 'static,
+) -> BoxResult<()> {
+    let state = state::window_state(window)?;
+    *state.certificate_error_handler.lock()? = Some(Arc::new(handler));
+    if let Some(install) = bridge::install_once(&state, "certificate-error").await? {
+        platform::add_certificate_error_handler(window, {
+            let window = window.clone();
+            move |info| decide(&window, info)
+        })
+        .await?;
+        install.done();
+    }
+    Ok(())
+}
+
+fn decide(window: &Window, info: CertificateErrorInfo) -> CertDecision {
+    state::window_state(window)
+        .and_then(|state| Ok(state.certificate_error_handler.lock()?.clone()))
+        .ok()
+        .flatten()
+        .map(|handler| handler(info))
+        .unwrap_or_default()
+}
@@ -0,0 +1,11 @@
+use serde::{Deserialize, Serialize};
+
+// NOTE: `Auto` follows the system again. The scheme is what `prefers-color-scheme` matches in the
+// page, and what the platform uses for the parts of the webview it draws itself.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub enum ColorScheme {
+    #[default]
+    Auto,
+    Light,
+    Dark,
+}
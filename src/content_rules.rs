@@ -0,0 +1,241 @@
+use crate::{platform, state, BoxError, BoxResult, Error, Result};
+use regex::{Regex, RegexBuilder};
+use serde::Deserialize;
+use std::{str::FromStr, sync::Arc};
+use tauri::Window;
+#[cfg(target_os = "windows")]
+use url::Url;
+
+#[cfg(not(target_os = "windows"))]
+pub(crate) const CONTENT_RULES_ID: &str = "tauri-webview-util-content-rules";
+
+// NOTE: a list of rules in the JSON format of WebKit content blockers, as used by Safari and by most
+// ad-block list converters. The rules are checked when the list is created, so that invalid lists
+// are rejected the same way on every platform.
+#[derive(Clone, Debug)]
+pub struct ContentRuleList {
+    json: String,
+    #[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+    rules: Vec<ContentRule>,
+}
+
+impl ContentRuleList {
+    pub fn from_json(json: impl Into<String>) -> Result<Self> {
+        let json = json.into();
+        let rules = serde_json::from_str::<Vec<RawContentRule>>(&json)?
+            .into_iter()
+            .enumerate()
+            .map(|(index, rule)| {
+                ContentRule::try_from(rule)
+                    .map_err(|err| Error::Other(format!("invalid content rule at index {index}: {err}").into()))
+            })
+            .collect::<Result<_>>()?;
+        Ok(Self { json, rules })
+    }
+
+    pub fn as_json(&self) -> &str {
+        &self.json
+    }
+
+    pub fn len(&self) -> usize {
+        self.rules.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    // NOTE: rules apply in order, so a later `ignore-previous-rules` undoes an earlier `block`. Only
+    // `block` and `ignore-previous-rules` have an effect, and requests are third-party when their
+    // host is neither the host of the document nor one of its subdomains.
+    #[cfg(target_os = "windows")]
+    pub(crate) fn blocks(&self, url: &str, document: Option<&Url>, resource_type: Option<ResourceType>) -> bool {
+        let host = Url::parse(url)
+            .ok()
+            .and_then(|url| url.host_str().map(str::to_ascii_lowercase));
+        let document_host = document.and_then(Url::host_str).map(str::to_ascii_lowercase);
+        let third_party = match (&host, &document_host) {
+            (Some(host), Some(document_host)) => !domain_matches(document_host, host, true),
+            _ => false,
+        };
+        let mut blocked = false;
+        for rule in &self.rules {
+            if rule.matches(url, document_host.as_deref(), resource_type, third_party) {
+                match rule.action {
+                    ContentAction::Block => blocked = true,
+                    ContentAction::IgnorePreviousRules => blocked = false,
+                    _ => {},
+                }
+            }
+        }
+        blocked
+    }
+}
+
+impl Default for ContentRuleList {
+    fn default() -> Self {
+        Self {
+            json: "[]".into(),
+            rules: vec![],
+        }
+    }
+}
+
+impl FromStr for ContentRuleList {
+    type Err = Error;
+
+    fn from_str(json: &str) -> Result<Self> {
+        Self::from_json(json)
+    }
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+pub(crate) enum ResourceType {
+    Document,
+    Image,
+    StyleSheet,
+    Script,
+    Font,
+    Raw,
+    SvgDocument,
+    Media,
+    Popup,
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+enum LoadType {
+    FirstParty,
+    ThirdParty,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "kebab-case")]
+enum ContentAction {
+    Block,
+    BlockCookies,
+    CssDisplayNone {
+        #[allow(dead_code)]
+        selector: String,
+    },
+    IgnorePreviousRules,
+    MakeHttps,
+}
+
+#[derive(Deserialize)]
+struct RawContentRule {
+    trigger: RawTrigger,
+    action: ContentAction,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "kebab-case")]
+struct RawTrigger {
+    url_filter: String,
+    #[serde(default)]
+    url_filter_is_case_sensitive: bool,
+    #[serde(default)]
+    if_domain: Vec<String>,
+    #[serde(default)]
+    unless_domain: Vec<String>,
+    #[serde(default)]
+    resource_type: Vec<ResourceType>,
+    #[serde(default)]
+    load_type: Vec<LoadType>,
+}
+
+#[derive(Clone, Debug)]
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+struct ContentRule {
+    url_filter: Regex,
+    if_domain: Vec<String>,
+    unless_domain: Vec<String>,
+    resource_type: Vec<ResourceType>,
+    load_type: Vec<LoadType>,
+    action: ContentAction,
+}
+
+impl TryFrom<RawContentRule> for ContentRule {
+    type Error = BoxError;
+
+    fn try_from(RawContentRule { trigger, action }: RawContentRule) -> Result<Self, Self::Error> {
+        if !trigger.if_domain.is_empty() && !trigger.unless_domain.is_empty() {
+            return Err("`if-domain` and `unless-domain` can not both be set".into());
+        }
+        let url_filter = RegexBuilder::new(&trigger.url_filter)
+            .case_insensitive(!trigger.url_filter_is_case_sensitive)
+            .build()?;
+        let lowercase = |domains: Vec<String>| domains.into_iter().map(|domain| domain.to_ascii_lowercase()).collect();
+        Ok(Self {
+            url_filter,
+            if_domain: lowercase(trigger.if_domain),
+            unless_domain: lowercase(trigger.unless_domain),
+            resource_type: trigger.resource_type,
+            load_type: trigger.load_type,
+            action,
+        })
+    }
+}
+
+#[cfg(target_os = "windows")]
+impl ContentRule {
+    fn matches(
+        &self,
+        url: &str,
+        document_host: Option<&str>,
+        resource_type: Option<ResourceType>,
+        third_party: bool,
+    ) -> bool {
+        let on_domain = |domains: &[String]| {
+            document_host.map_or(false, |host| {
+                domains.iter().any(|domain| match domain.strip_prefix('*') {
+                    Some(domain) => domain_matches(domain, host, true),
+                    None => domain_matches(domain, host, false),
+                })
+            })
+        };
+        self.url_filter.is_match(url)
+            && (self.if_domain.is_empty() || on_domain(&self.if_domain))
+            && (self.unless_domain.is_empty() || !on_domain(&self.unless_domain))
+            && (self.resource_type.is_empty()
+                || resource_type.map_or(false, |resource_type| self.resource_type.contains(&resource_type)))
+            && (self.load_type.is_empty()
+                || self.load_type.contains(if third_party {
+                    &LoadType::ThirdParty
+                } else {
+                    &LoadType::FirstParty
+                }))
+    }
+}
+
+#[cfg(target_os = "windows")]
+fn domain_matches(domain: &str, host: &str, include_subdomains: bool) -> bool {
+    host == domain || (include_subdomains && host.strip_suffix(domain).map_or(false, |prefix| prefix.ends_with('.')))
+}
+
+// NOTE: the list replaces the one set before, and an empty list removes it.
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window, rules),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn set(window: &Window, rules: ContentRuleList) -> BoxResult<()> {
+    let state = state::window_state(window)?;
+    let rules = Arc::new(rules);
+    *state.content_rules.lock()? = Some(rules.clone());
+    platform::set_content_rules(window, rules).await
+}
+
+#[cfg(target_os = "windows")]
+pub(crate) fn is_blocked(
+    window: &Window,
+    url: &str,
+    document: Option<&Url>,
+    resource_type: Option<ResourceType>,
+) -> bool {
+    state::window_state(window)
+        .and_then(|state| Ok(state.content_rules.lock()?.clone()))
+        .ok()
+        .flatten()
+        .map_or(false, |rules| rules.blocks(url, document, resource_type))
+}
@@ -0,0 +1,29 @@
+use crate::{fetch, BoxResult};
+use std::path::PathBuf;
+use tauri::Window;
+#[cfg(feature = "clipboard")]
+use tauri::{ClipboardManager, Manager};
+use url::Url;
+
+// NOTE: these back the "Save image as…" and "Copy link address" actions of a context menu. The
+// resource is fetched by the current document with its credentials, like with
+// `webview_fetch_with_session`.
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn save_resource(window: &Window, url: Url, path: PathBuf) -> BoxResult<()> {
+    let resource = fetch::fetch_with_session(window, url).await?;
+    std::fs::write(path, resource.bytes)?;
+    Ok(())
+}
+
+#[cfg(feature = "clipboard")]
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) fn copy_link(window: &Window, url: Url) -> BoxResult<()> {
+    window.app_handle().clipboard_manager().write_text(url.to_string())?;
+    Ok(())
+}
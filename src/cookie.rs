@@ -1,14 +1,10 @@
 #[cfg(feature = "async-graphql")]
 use async_graphql::SimpleObject;
-
-#[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
-
 use url::Url;
 
 #[cfg_attr(feature = "async-graphql", derive(SimpleObject))]
-#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
-#[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+#[derive(Clone, Debug, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
 #[non_exhaustive]
 pub struct Cookie {
     pub name: String,
@@ -25,12 +21,123 @@ pub struct Cookie {
     pub comment_url: Option<Url>,
 }
 
+impl Cookie {
+    pub const MAX_ATTRIBUTE_SIZE: usize = 1024;
+    // NOTE: RFC 6265 only requires user agents to support cookies of at least 4096 bytes (name,
+    // value, and attributes), and browsers additionally reject attribute values over 1024 bytes.
+    pub const MAX_SIZE: usize = 4096;
+
+    // NOTE: a session cookie without attributes, on which the fields that are known are then set.
+    pub(crate) fn new(name: impl Into<String>, value: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            value: value.into(),
+            domain: String::new(),
+            path: String::new(),
+            port_list: None,
+            expires: None,
+            http_only: false,
+            same_site: None,
+            secure: false,
+            session: true,
+            comment: None,
+            comment_url: None,
+        }
+    }
+
+    pub fn estimated_size(&self) -> usize {
+        // NOTE: the size of the equivalent `Set-Cookie` value, with dates in the fixed-width
+        // IMF-fixdate format.
+        const EXPIRES_SIZE: usize = "; Expires=Thu, 01 Jan 1970 00:00:00 GMT".len();
+        let mut size = self.name.len() + "=".len() + self.value.len();
+        if !self.domain.is_empty() {
+            size += "; Domain=".len() + self.domain.len();
+        }
+        if !self.path.is_empty() {
+            size += "; Path=".len() + self.path.len();
+        }
+        if self.expires.is_some() {
+            size += EXPIRES_SIZE;
+        }
+        if self.http_only {
+            size += "; HttpOnly".len();
+        }
+        if let Some(same_site) = &self.same_site {
+            size += "; SameSite=".len() + same_site.len();
+        }
+        if self.secure {
+            size += "; Secure".len();
+        }
+        size
+    }
+
+    pub fn is_oversized(&self) -> bool {
+        self.estimated_size() > Self::MAX_SIZE
+            || self.domain.len() > Self::MAX_ATTRIBUTE_SIZE
+            || self.path.len() > Self::MAX_ATTRIBUTE_SIZE
+    }
+
+    // NOTE: a line of a Netscape `cookies.txt` file as written by curl, where HTTP-only cookies have
+    // their domain prefixed with `#HttpOnly_` and session cookies expire at 0.
+    pub fn to_netscape_line(&self) -> String {
+        let flag = |value: bool| if value { "TRUE" } else { "FALSE" };
+        let http_only = if self.http_only { "#HttpOnly_" } else { "" };
+        let include_subdomains = flag(self.domain.starts_with('.'));
+        let secure = flag(self.secure);
+        let expires = self
+            .expires
+            .map(time::OffsetDateTime::unix_timestamp)
+            .unwrap_or_default();
+        format!(
+            "{http_only}{}\t{include_subdomains}\t{}\t{secure}\t{expires}\t{}\t{}",
+            self.domain, self.path, self.name, self.value
+        )
+    }
+
+    pub fn display_domain(&self) -> String {
+        let (dot, domain) = match self.domain.strip_prefix('.') {
+            Some(domain) => (".", domain),
+            None => ("", self.domain.as_str()),
+        };
+        let (unicode, result) = idna::domain_to_unicode(domain);
+        if result.is_err() || unicode.split('.').any(is_mixed_script_label) {
+            return self.domain.clone();
+        }
+        format!("{dot}{unicode}")
+    }
+}
+
+// NOTE: labels mixing Latin, Greek, and Cyrillic letters are the usual shape of homograph
+// spoofing, so (like browsers do) such domains are left in punycode rather than displayed.
+fn is_mixed_script_label(label: &str) -> bool {
+    #[derive(PartialEq)]
+    enum Script {
+        Latin,
+        Greek,
+        Cyrillic,
+    }
+    let mut scripts = label.chars().filter_map(|c| match c {
+        'a' ..= 'z' | 'A' ..= 'Z' | '\u{00c0}' ..= '\u{024f}' => Some(Script::Latin),
+        '\u{0370}' ..= '\u{03ff}' => Some(Script::Greek),
+        '\u{0400}' ..= '\u{052f}' => Some(Script::Cyrillic),
+        _ => None,
+    });
+    match scripts.next() {
+        Some(first) => scripts.any(|script| script != first),
+        None => false,
+    }
+}
+
 impl std::fmt::Display for Cookie {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         struct Value<'a>(&'a str);
         impl<'a> std::fmt::Debug for Value<'a> {
             fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
-                f.write_str("<...>")
+                if crate::options().redact_cookie_values {
+                    f.write_str("<...>")
+                } else {
+                    std::fmt::Debug::fmt(self.0, f)
+                }
             }
         }
 
@@ -62,3 +169,44 @@ impl std::fmt::Display for Cookie {
         r.finish_non_exhaustive()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn estimated_size_is_the_size_of_set_cookie() {
+        let mut cookie = Cookie::new("name", "value");
+        assert_eq!(cookie.estimated_size(), "name=value".len());
+        cookie.domain = "example.com".into();
+        cookie.path = "/".into();
+        cookie.expires = Some(time::OffsetDateTime::UNIX_EPOCH);
+        cookie.http_only = true;
+        cookie.same_site = Some("Lax".into());
+        cookie.secure = true;
+        let set_cookie = "name=value; Domain=example.com; Path=/; Expires=Thu, 01 Jan 1970 00:00:00 GMT; HttpOnly; \
+                          SameSite=Lax; Secure";
+        assert_eq!(cookie.estimated_size(), set_cookie.len());
+    }
+
+    #[test]
+    fn cookies_over_the_size_limit_are_oversized() {
+        let mut cookie = Cookie::new("name", &"x".repeat(Cookie::MAX_SIZE - "name=".len()));
+        assert_eq!(cookie.estimated_size(), Cookie::MAX_SIZE);
+        assert!(!cookie.is_oversized());
+        cookie.value.push('x');
+        assert!(cookie.is_oversized());
+    }
+
+    #[test]
+    fn long_attributes_are_oversized() {
+        let long = "x".repeat(Cookie::MAX_ATTRIBUTE_SIZE + 1);
+        let mut cookie = Cookie::new("name", "value");
+        cookie.domain = long.clone();
+        assert!(cookie.estimated_size() <= Cookie::MAX_SIZE);
+        assert!(cookie.is_oversized());
+        cookie.domain = String::new();
+        cookie.path = long;
+        assert!(cookie.is_oversized());
+    }
+}
@@ -0,0 +1,102 @@
+use crate::{BoxResult, Cookie};
+use rusqlite::{Connection, OpenFlags};
+use std::path::Path;
+use time::OffsetDateTime;
+
+// NOTE: Chromium stores the expiry in microseconds since 1601-01-01.
+const CHROMIUM_EPOCH_OFFSET: i64 = 11_644_473_600;
+// NOTE: newer versions of Firefox store the expiry in milliseconds instead of seconds, and no expiry
+// in seconds is this far in the future.
+const MILLISECONDS_THRESHOLD: i64 = 100_000_000_000;
+
+pub(crate) fn open(path: &Path) -> rusqlite::Result<Connection> {
+    Connection::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_NO_MUTEX)
+}
+
+// NOTE: libsoup, Firefox and Chromium all number the same site policies from none to strict.
+fn same_site(policy: i64) -> Option<String> {
+    match policy {
+        0 => Some("None".into()),
+        1 => Some("Lax".into()),
+        2 => Some("Strict".into()),
+        _ => None,
+    }
+}
+
+// NOTE: the `moz_cookies` table is used by both Firefox and libsoup, which only persist cookies
+// with an expiry.
+pub(crate) fn read_moz_cookies(path: &Path) -> BoxResult<Vec<Cookie>> {
+    let connection = open(path)?;
+    let mut statement = connection
+        .prepare("SELECT name, value, host, path, expiry, isSecure, isHttpOnly, sameSite FROM moz_cookies")?;
+    let mut rows = statement.query([])?;
+    let mut cookies = vec![];
+    while let Some(row) = rows.next()? {
+        let mut expiry: i64 = row.get(4)?;
+        if expiry > MILLISECONDS_THRESHOLD {
+            expiry /= 1_000;
+        }
+        cookies.push(Cookie {
+            domain: row.get(2)?,
+            path: row.get(3)?,
+            expires: Some(OffsetDateTime::from_unix_timestamp(expiry)?),
+            http_only: row.get(6)?,
+            same_site: same_site(row.get(7)?),
+            secure: row.get(5)?,
+            session: false,
+            ..Cookie::new(row.get::<_, String>(0)?, row.get::<_, String>(1)?)
+        });
+    }
+    Ok(cookies)
+}
+
+// NOTE: cookies that only have an encrypted value are passed to `decrypt` with their host, and are
+// skipped when it returns `None`. Returns the readable cookies along with the number of skipped ones.
+pub(crate) fn read_chromium_cookies(
+    path: &Path,
+    mut decrypt: impl FnMut(&str, &[u8]) -> Option<String>,
+) -> BoxResult<(Vec<Cookie>, usize)> {
+    let connection = open(path)?;
+    let mut statement = connection.prepare(
+        "SELECT name, value, encrypted_value, host_key, path, expires_utc, has_expires, is_secure, is_httponly, \
+         samesite FROM cookies",
+    )?;
+    let mut rows = statement.query([])?;
+    let mut cookies = vec![];
+    let mut skipped = 0;
+    while let Some(row) = rows.next()? {
+        let domain: String = row.get(3)?;
+        let mut value: String = row.get(1)?;
+        let encrypted_value: Vec<u8> = row.get(2)?;
+        if value.is_empty() && !encrypted_value.is_empty() {
+            match decrypt(&domain, &encrypted_value) {
+                Some(decrypted) => value = decrypted,
+                None => {
+                    skipped += 1;
+                    continue;
+                },
+            }
+        }
+        let expires_utc: i64 = row.get(5)?;
+        let has_expires: bool = row.get(6)?;
+        let session = !has_expires || expires_utc == 0;
+        let expires = if session {
+            None
+        } else {
+            Some(OffsetDateTime::from_unix_timestamp(
+                expires_utc / 1_000_000 - CHROMIUM_EPOCH_OFFSET,
+            )?)
+        };
+        cookies.push(Cookie {
+            domain,
+            path: row.get(4)?,
+            expires,
+            http_only: row.get(8)?,
+            same_site: same_site(row.get(9)?),
+            secure: row.get(7)?,
+            session,
+            ..Cookie::new(row.get::<_, String>(0)?, value)
+        });
+    }
+    Ok((cookies, skipped))
+}
@@ -0,0 +1,50 @@
+use crate::{Cookie, CookiePattern, Error};
+use serde::Serialize;
+
+/// A cookie as the webview reports it, next to the cookie it was converted to, for reporting bugs
+/// in the conversion like a wrong expiry or a missing same-site policy.
+///
+/// The raw attributes are named and formatted like the native API they were read from, so that
+/// e.g. an expiry is shown before it is parsed. The value of the cookie is redacted in both.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+pub struct RawCookieDebug {
+    pub raw: Vec<(String, String)>,
+    pub cookie: Option<Cookie>,
+    pub error: Option<String>,
+}
+
+impl RawCookieDebug {
+    pub(crate) fn new(raw: Vec<(&str, String)>, cookie: Result<Cookie, Error>) -> Self {
+        let raw = raw.into_iter().map(|(name, value)| (name.into(), value)).collect();
+        match cookie {
+            Ok(cookie) => Self {
+                raw,
+                cookie: Some(Cookie {
+                    value: redact(&cookie.value),
+                    ..cookie
+                }),
+                error: None,
+            },
+            Err(err) => Self {
+                raw,
+                cookie: None,
+                error: Some(err.to_string()),
+            },
+        }
+    }
+}
+
+// NOTE: only the length of a value is kept, which is enough to tell an empty or truncated value
+// apart from a complete one.
+pub(crate) fn redact(value: &str) -> String {
+    format!("<redacted, {} bytes>", value.len())
+}
+
+// NOTE: cookies that could not be converted are always kept, since they can not be matched and are
+// the ones a report is most likely about.
+pub(crate) fn filter(cookies: Vec<RawCookieDebug>, pattern: &CookiePattern) -> Vec<RawCookieDebug> {
+    cookies
+        .into_iter()
+        .filter(|debug| debug.cookie.as_ref().map_or(true, |cookie| pattern.matches(cookie)))
+        .collect()
+}
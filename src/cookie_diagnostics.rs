@@ -0,0 +1,112 @@
+use crate::{bridge, platform, BoxResult, Cookie, WebviewExt};
+use serde::{Deserialize, Serialize};
+use tauri::Window;
+use time::{Duration, OffsetDateTime};
+use url::Url;
+
+const PROBE_NAME: &str = "__tauri_webview_util_probe";
+
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub enum CookieWriteProbe {
+    Store,
+    FirstParty,
+    CrossSite,
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub enum CookieWriteStatus {
+    Accepted,
+    Blocked,
+    Untested,
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct CookieWriteDiagnostic {
+    pub origin: Url,
+    pub probe: CookieWriteProbe,
+    pub status: CookieWriteStatus,
+}
+
+// NOTE: the store probe writes through the native cookie store, which is not subject to tracking
+// prevention, while the document probes write through `document.cookie` like page scripts do.
+// Document probes can only run for the origin of the currently loaded document, and are reported
+// as untested for any other origin.
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn diagnose(window: &Window, origins: Vec<Url>) -> BoxResult<Vec<CookieWriteDiagnostic>> {
+    let current = window.url();
+    let mut diagnostics = vec![];
+    for origin in origins {
+        let status = probe_store(window, &origin).await?;
+        diagnostics.push(CookieWriteDiagnostic {
+            origin: origin.clone(),
+            probe: CookieWriteProbe::Store,
+            status,
+        });
+        let probes = [
+            (CookieWriteProbe::FirstParty, "SameSite=Lax"),
+            (CookieWriteProbe::CrossSite, "SameSite=None; Secure"),
+        ];
+        for (probe, attributes) in probes {
+            let status = if current.origin() == origin.origin() {
+                probe_document(window, attributes).await?
+            } else {
+                CookieWriteStatus::Untested
+            };
+            diagnostics.push(CookieWriteDiagnostic {
+                origin: origin.clone(),
+                probe,
+                status,
+            });
+        }
+    }
+    Ok(diagnostics)
+}
+
+async fn probe_store(window: &Window, origin: &Url) -> BoxResult<CookieWriteStatus> {
+    let host = match origin.host_str() {
+        Some(host) => host,
+        None => return Ok(CookieWriteStatus::Untested),
+    };
+    let mut cookie = Cookie {
+        domain: host.into(),
+        path: "/".into(),
+        expires: Some(OffsetDateTime::now_utc() + Duration::minutes(1)),
+        secure: origin.scheme() == "https",
+        session: false,
+        ..Cookie::new(PROBE_NAME, "1")
+    };
+    platform::set_cookie(window, cookie.clone()).await?;
+    let accepted = window
+        .webview_get_cookies(Some(origin.clone()))
+        .await?
+        .iter()
+        .any(|cookie| cookie.name == PROBE_NAME);
+    // NOTE: writing the cookie again with an expiry in the past removes it from the store.
+    cookie.expires = Some(OffsetDateTime::UNIX_EPOCH);
+    platform::set_cookie(window, cookie).await?;
+    if accepted {
+        Ok(CookieWriteStatus::Accepted)
+    } else {
+        Ok(CookieWriteStatus::Blocked)
+    }
+}
+
+async fn probe_document(window: &Window, attributes: &str) -> BoxResult<CookieWriteStatus> {
+    let body = format!(
+        r#"
+        const name = "{PROBE_NAME}";
+        document.cookie = `${{name}}=1; Path=/; Max-Age=60; {attributes}`;
+        const accepted = document.cookie.split("; ").some((cookie) => cookie.startsWith(`${{name}}=`));
+        document.cookie = `${{name}}=; Path=/; Max-Age=0; {attributes}`;
+        return accepted;
+        "#
+    );
+    if bridge::call(window, &body).await?.as_bool() == Some(true) {
+        Ok(CookieWriteStatus::Accepted)
+    } else {
+        Ok(CookieWriteStatus::Blocked)
+    }
+}
@@ -0,0 +1,185 @@
+use crate::{Cookie, Error};
+use std::str::FromStr;
+
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum CookieHostScheme {
+    Http,
+    Https,
+}
+
+impl CookieHostScheme {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Http => "http",
+            Self::Https => "https",
+        }
+    }
+}
+
+impl std::fmt::Display for CookieHostScheme {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for CookieHostScheme {
+    type Err = Error;
+
+    fn from_str(scheme: &str) -> Result<Self, Self::Err> {
+        if scheme.eq_ignore_ascii_case("http") {
+            Ok(Self::Http)
+        } else if scheme.eq_ignore_ascii_case("https") {
+            Ok(Self::Https)
+        } else {
+            Err(Error::Other(format!("`{scheme}` is not a cookie host scheme").into()))
+        }
+    }
+}
+
+// NOTE: parsed from `host`, `.host` (which also matches subdomains), or either of them prefixed with
+// `http://` or `https://`. Without a scheme, cookies match regardless of the schemes they are sent
+// over, and with `http` only cookies that are not secure match.
+#[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct CookieHost {
+    pub scheme: Option<CookieHostScheme>,
+    pub host: String,
+    pub include_subdomains: bool,
+}
+
+impl CookieHost {
+    pub fn new(host: impl Into<String>) -> Self {
+        Self {
+            scheme: None,
+            host: host.into().to_ascii_lowercase(),
+            include_subdomains: false,
+        }
+    }
+
+    pub fn matches(&self, cookie: &Cookie) -> bool {
+        let domain = cookie.domain.trim_start_matches('.').to_ascii_lowercase();
+        let host_matches = domain == self.host
+            || (self.include_subdomains
+                && domain
+                    .strip_suffix(&self.host)
+                    .map_or(false, |prefix| prefix.ends_with('.')));
+        host_matches && (self.scheme != Some(CookieHostScheme::Http) || !cookie.secure)
+    }
+}
+
+impl std::fmt::Display for CookieHost {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        if let Some(scheme) = self.scheme {
+            write!(f, "{scheme}://")?;
+        }
+        if self.include_subdomains {
+            f.write_str(".")?;
+        }
+        f.write_str(&self.host)
+    }
+}
+
+impl FromStr for CookieHost {
+    type Err = Error;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let (scheme, rest) = match text.split_once("://") {
+            Some((scheme, rest)) => (Some(scheme.parse()?), rest),
+            None => (None, text),
+        };
+        let (include_subdomains, host) = match rest.strip_prefix('.') {
+            Some(host) => (true, host),
+            None => (false, rest),
+        };
+        // NOTE: cookie domains are stored in punycode, so internationalized hosts are converted to
+        // it before the labels are checked.
+        let host = idna::domain_to_ascii(host).unwrap_or_default();
+        let valid = !host.is_empty()
+            && host
+                .split('.')
+                .all(|label| !label.is_empty() && label.chars().all(|c| c.is_ascii_alphanumeric() || c == '-'));
+        if !valid {
+            return Err(Error::Other(format!("`{text}` is not a cookie host").into()));
+        }
+        Ok(Self {
+            scheme,
+            host,
+            include_subdomains,
+        })
+    }
+}
+
+impl serde::Serialize for CookieHost {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for CookieHost {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let text = String::deserialize(deserializer)?;
+        text.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hosts_round_trip_through_display() {
+        for text in [
+            "example.com",
+            ".example.com",
+            "http://example.com",
+            "https://.sub.example.com",
+        ] {
+            assert_eq!(text.parse::<CookieHost>().unwrap().to_string(), text);
+        }
+    }
+
+    #[test]
+    fn schemes_round_trip_through_display() {
+        for scheme in [CookieHostScheme::Http, CookieHostScheme::Https] {
+            assert_eq!(scheme.to_string().parse::<CookieHostScheme>().unwrap(), scheme);
+        }
+    }
+
+    #[test]
+    fn hosts_and_schemes_are_parsed_case_insensitively() {
+        let host = "HTTPS://.Example.COM".parse::<CookieHost>().unwrap();
+        assert_eq!(host, CookieHost {
+            scheme: Some(CookieHostScheme::Https),
+            host: "example.com".into(),
+            include_subdomains: true,
+        });
+        assert_eq!(host.to_string(), "https://.example.com");
+    }
+
+    #[test]
+    fn invalid_hosts_are_rejected() {
+        for text in [
+            "",
+            ".",
+            "http://",
+            "ftp://example.com",
+            "example..com",
+            "example.com/path",
+            "exa mple.com",
+            "exa_mple.com",
+        ] {
+            assert!(text.parse::<CookieHost>().is_err(), "`{text}` was parsed");
+        }
+    }
+
+    #[test]
+    fn internationalized_hosts_are_converted_to_punycode() {
+        let host = "https://.BÜCHER.example".parse::<CookieHost>().unwrap();
+        assert_eq!(host.host, "xn--bcher-kva.example");
+        assert_eq!(host.to_string(), "https://.xn--bcher-kva.example");
+        let cookie = Cookie {
+            domain: ".xn--bcher-kva.example".into(),
+            ..Cookie::new("name", "value")
+        };
+        assert!(host.matches(&cookie));
+    }
+}
@@ -0,0 +1,146 @@
+use crate::{error::WindowContext, BoxError, BoxResult, Cookie, CookiePattern, Error, Result, WebviewExt};
+use cookie_store::{CookieDomain, CookieExpiration};
+use reqwest_cookie_store::CookieStoreMutex;
+use tauri::Window;
+use time::OffsetDateTime;
+use url::Url;
+
+// NOTE: cookies with a leading dot in their domain get a `Domain` attribute, which is what makes a
+// cookie apply to subdomains as well. The others are host-only cookies, which have no `Domain`
+// attribute, like when they were set by a `Set-Cookie` header without one.
+impl From<Cookie> for raw_cookie::Cookie<'static> {
+    fn from(cookie: Cookie) -> Self {
+        let mut builder = raw_cookie::Cookie::build(cookie.name, cookie.value)
+            .path(if cookie.path.is_empty() {
+                String::from("/")
+            } else {
+                cookie.path
+            })
+            .http_only(cookie.http_only)
+            .secure(cookie.secure);
+        if let Some(domain) = cookie.domain.strip_prefix('.') {
+            builder = builder.domain(String::from(domain));
+        }
+        if let Some(expires) = cookie.expires.filter(|_| !cookie.session) {
+            builder = builder.expires(expires);
+        }
+        if let Some(same_site) = cookie.same_site.as_deref().and_then(same_site) {
+            builder = builder.same_site(same_site);
+        }
+        builder.finish()
+    }
+}
+
+// NOTE: only cookies with a `Domain` attribute can be converted, since the host of a host-only
+// cookie is not part of the cookie itself. A `Max-Age` is converted to the time it expires at from
+// now on.
+impl<'a> TryFrom<raw_cookie::Cookie<'a>> for Cookie {
+    type Error = Error;
+
+    fn try_from(cookie: raw_cookie::Cookie<'a>) -> Result<Self> {
+        let domain = cookie
+            .domain()
+            .filter(|domain| !domain.is_empty())
+            .map(|domain| format!(".{}", domain.trim_start_matches('.')))
+            .ok_or_else(|| Error::CookieConversion("the cookie has no domain".into()))?;
+        let expires = cookie.expires_datetime().or_else(|| {
+            let max_age = cookie.max_age()?;
+            Some(OffsetDateTime::now_utc() + max_age)
+        });
+        Ok(convert(&cookie, domain, cookie.path().unwrap_or("/").into(), expires))
+    }
+}
+
+impl TryFrom<&cookie_store::Cookie<'_>> for Cookie {
+    type Error = Error;
+
+    fn try_from(cookie: &cookie_store::Cookie<'_>) -> Result<Self> {
+        let domain = match &cookie.domain {
+            CookieDomain::HostOnly(host) => host.clone(),
+            CookieDomain::Suffix(domain) => format!(".{domain}"),
+            CookieDomain::NotPresent | CookieDomain::Empty => {
+                return Err(Error::CookieConversion("the cookie has no domain".into()));
+            },
+        };
+        let expires = match cookie.expires {
+            CookieExpiration::AtUtc(expires) => Some(expires),
+            CookieExpiration::SessionEnd => None,
+        };
+        Ok(convert(cookie, domain, String::from(&cookie.path), expires))
+    }
+}
+
+fn convert(cookie: &raw_cookie::Cookie, domain: String, path: String, expires: Option<OffsetDateTime>) -> Cookie {
+    Cookie {
+        domain,
+        path,
+        expires,
+        http_only: cookie.http_only().unwrap_or(false),
+        same_site: cookie.same_site().map(|same_site| same_site.to_string()),
+        secure: cookie.secure().unwrap_or(false),
+        session: expires.is_none(),
+        ..Cookie::new(cookie.name(), cookie.value())
+    }
+}
+
+fn same_site(same_site: &str) -> Option<raw_cookie::SameSite> {
+    match same_site.to_ascii_lowercase().as_str() {
+        "strict" => Some(raw_cookie::SameSite::Strict),
+        "lax" => Some(raw_cookie::SameSite::Lax),
+        "none" => Some(raw_cookie::SameSite::None),
+        _ => None,
+    }
+}
+
+// NOTE: the store decides whether a cookie is host-only from the URL it was received from, so each
+// cookie is inserted as if it was set by a response of its own host and path.
+fn request_url(cookie: &Cookie) -> BoxResult<Url> {
+    let host = cookie.domain.trim_start_matches('.');
+    if host.is_empty() {
+        return Err(Error::CookieConversion("the cookie has no domain".into()).into());
+    }
+    let scheme = if cookie.secure { "https" } else { "http" };
+    let path = if cookie.path.is_empty() { "/" } else { &cookie.path };
+    Ok(Url::parse(&format!("{scheme}://{host}{path}"))?)
+}
+
+/// Copies the cookies of the webview that match the pattern into a cookie store, like the one of a
+/// `reqwest` client, and returns how many were copied.
+///
+/// Host-only cookies stay host-only, and cookies of a domain also apply to its subdomains in the
+/// store. Cookies the store rejects, like cookies for a public suffix, fail the whole sync.
+pub async fn sync_cookies_to_store(window: &Window, store: &CookieStoreMutex, pattern: CookiePattern) -> Result<usize> {
+    let cookies = window.webview_collect_cookies(pattern).await?;
+    async {
+        let mut store = store.lock().map_err(|_| Error::Poisoned)?;
+        for cookie in &cookies {
+            let url = request_url(cookie)?;
+            store
+                .insert_raw(&cookie.clone().into(), &url)
+                .map_err(|err| Error::CookieConversion(err.into()))?;
+        }
+        Ok::<_, BoxError>(cookies.len())
+    }
+    .await
+    .window_context(window, "sync cookies to store")
+}
+
+/// Copies the unexpired cookies of a cookie store that match the pattern into the webview, and
+/// returns how many were copied.
+pub async fn sync_cookies_from_store(
+    window: &Window,
+    store: &CookieStoreMutex,
+    pattern: CookiePattern,
+) -> Result<usize> {
+    let cookies = {
+        let store = store.lock().map_err(|_| Error::Poisoned)?;
+        store
+            .iter_unexpired()
+            .map(Cookie::try_from)
+            .filter(|cookie| cookie.as_ref().map_or(true, |cookie| pattern.matches(cookie)))
+            .collect::<Result<Vec<_>>>()?
+    };
+    let count = cookies.len();
+    window.webview_set_cookies(cookies).await?;
+    Ok(count)
+}
@@ -0,0 +1,225 @@
+use crate::{BoxResult, Cookie, Error, Result};
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub enum CookieJarFormat {
+    // NOTE: the `cookies.txt` format read and written by curl (`--cookie` and `--cookie-jar`).
+    Netscape,
+    // NOTE: the JSON array of cookies used by the Chrome DevTools protocol (`Network.getCookies`)
+    // and by most browser extensions that export cookies.
+    DevToolsJson,
+}
+
+#[derive(Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct DevToolsCookie {
+    name: String,
+    value: String,
+    domain: String,
+    #[serde(default = "default_path")]
+    path: String,
+    // NOTE: seconds since the epoch, or -1 for session cookies.
+    #[serde(default = "default_expires")]
+    expires: f64,
+    #[serde(default)]
+    http_only: bool,
+    #[serde(default)]
+    secure: bool,
+    #[serde(default)]
+    session: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    same_site: Option<String>,
+}
+
+fn default_path() -> String {
+    "/".into()
+}
+
+fn default_expires() -> f64 {
+    -1.0
+}
+
+pub fn serialize_cookies(cookies: &[Cookie], format: CookieJarFormat) -> Result<String> {
+    match format {
+        CookieJarFormat::Netscape => {
+            let mut text = String::from("# Netscape HTTP Cookie File\n");
+            for cookie in cookies {
+                text.push_str(&cookie.to_netscape_line());
+                text.push('\n');
+            }
+            Ok(text)
+        },
+        CookieJarFormat::DevToolsJson => {
+            let cookies = cookies.iter().map(to_devtools).collect::<Vec<_>>();
+            Ok(serde_json::to_string_pretty(&cookies)?)
+        },
+    }
+}
+
+pub fn deserialize_cookies(text: &str, format: CookieJarFormat) -> Result<Vec<Cookie>> {
+    match format {
+        CookieJarFormat::Netscape => text
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| {
+                let line = line.trim();
+                !line.is_empty() && (!line.starts_with('#') || line.starts_with("#HttpOnly_"))
+            })
+            .map(|(index, line)| {
+                from_netscape_line(line).map_err(|err| {
+                    let err = format!("invalid cookie on line {}: {err}", index + 1);
+                    Error::CookieConversion(err.into())
+                })
+            })
+            .collect(),
+        CookieJarFormat::DevToolsJson => serde_json::from_str::<Vec<DevToolsCookie>>(text)?
+            .into_iter()
+            .map(|cookie| from_devtools(cookie).map_err(Error::CookieConversion))
+            .collect(),
+    }
+}
+
+fn from_netscape_line(line: &str) -> BoxResult<Cookie> {
+    let line = line.trim_end_matches(['\r', '\n']);
+    let (http_only, line) = match line.strip_prefix("#HttpOnly_") {
+        Some(line) => (true, line),
+        None => (false, line),
+    };
+    let fields = line.splitn(7, '\t').collect::<Vec<_>>();
+    let [domain, _include_subdomains, path, secure, expires, name, value] = fields[..] else {
+        return Err(format!("expected 7 tab separated fields but found {}", fields.len()).into());
+    };
+    let expires = expires.parse::<i64>()?;
+    let session = expires == 0;
+    let expires = if session {
+        None
+    } else {
+        Some(OffsetDateTime::from_unix_timestamp(expires)?)
+    };
+    Ok(Cookie {
+        domain: domain.into(),
+        path: path.into(),
+        expires,
+        http_only,
+        secure: secure.eq_ignore_ascii_case("TRUE"),
+        session,
+        ..Cookie::new(name, value)
+    })
+}
+
+fn to_devtools(cookie: &Cookie) -> DevToolsCookie {
+    // NOTE: DevTools spells the same site policies with a capital letter, while the engines differ.
+    let same_site = cookie.same_site.as_deref().map(|same_site| {
+        let mut chars = same_site.chars();
+        match chars.next() {
+            Some(first) => first.to_ascii_uppercase().to_string() + &chars.as_str().to_ascii_lowercase(),
+            None => String::new(),
+        }
+    });
+    DevToolsCookie {
+        name: cookie.name.clone(),
+        value: cookie.value.clone(),
+        domain: cookie.domain.clone(),
+        path: cookie.path.clone(),
+        expires: cookie.expires.map_or(-1.0, |expires| expires.unix_timestamp() as f64),
+        http_only: cookie.http_only,
+        secure: cookie.secure,
+        session: cookie.session,
+        same_site,
+    }
+}
+
+fn from_devtools(cookie: DevToolsCookie) -> BoxResult<Cookie> {
+    let session = cookie.session || cookie.expires < 0.0;
+    let expires = if session {
+        None
+    } else {
+        Some(OffsetDateTime::from_unix_timestamp(cookie.expires.round() as i64)?)
+    };
+    Ok(Cookie {
+        domain: cookie.domain,
+        path: cookie.path,
+        expires,
+        http_only: cookie.http_only,
+        same_site: cookie.same_site,
+        secure: cookie.secure,
+        session,
+        ..Cookie::new(cookie.name, cookie.value)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn netscape(text: &str) -> Vec<Cookie> {
+        deserialize_cookies(text, CookieJarFormat::Netscape).unwrap()
+    }
+
+    #[test]
+    fn malformed_lines_are_reported_with_their_line_number() {
+        let missing_field = "# Netscape HTTP Cookie File\n\n.example.com\tTRUE\t/\tFALSE\t0\tname\n";
+        let err = deserialize_cookies(missing_field, CookieJarFormat::Netscape).unwrap_err();
+        assert!(err.to_string().contains("line 3"), "{err}");
+        let bad_expiry = ".example.com\tTRUE\t/\tFALSE\tnever\tname\tvalue\n";
+        let err = deserialize_cookies(bad_expiry, CookieJarFormat::Netscape).unwrap_err();
+        assert!(err.to_string().contains("line 1"), "{err}");
+    }
+
+    #[test]
+    fn comments_and_blank_lines_are_skipped() {
+        let cookies =
+            netscape("# Netscape HTTP Cookie File\n# a comment\n\n  \nexample.com\tFALSE\t/\tFALSE\t0\ta\tb\n");
+        assert_eq!(cookies.len(), 1);
+        assert_eq!((cookies[0].name.as_str(), cookies[0].value.as_str()), ("a", "b"));
+    }
+
+    #[test]
+    fn http_only_prefix_is_not_part_of_the_domain() {
+        let cookies = netscape("#HttpOnly_.example.com\tTRUE\t/\tTRUE\t0\tname\tvalue\n");
+        assert!(cookies[0].http_only);
+        assert!(cookies[0].secure);
+        assert_eq!(cookies[0].domain, ".example.com");
+        let cookies = netscape(".example.com\tTRUE\t/\tFALSE\t0\tname\tvalue\n");
+        assert!(!cookies[0].http_only);
+    }
+
+    #[test]
+    fn zero_expiry_is_a_session_cookie() {
+        let cookies = netscape("example.com\tFALSE\t/\tFALSE\t0\tname\tvalue\n");
+        assert!(cookies[0].session);
+        assert_eq!(cookies[0].expires, None);
+        let cookies = netscape("example.com\tFALSE\t/\tFALSE\t784111777\tname\tvalue\n");
+        assert!(!cookies[0].session);
+        assert_eq!(cookies[0].expires.map(OffsetDateTime::unix_timestamp), Some(784111777));
+    }
+
+    #[test]
+    fn values_keep_their_tabs() {
+        let cookies = netscape("example.com\tFALSE\t/\tFALSE\t0\tname\ta\tb\n");
+        assert_eq!(cookies[0].value, "a\tb");
+    }
+
+    #[test]
+    fn lines_round_trip_through_to_netscape_line() {
+        let lines = [
+            "#HttpOnly_.example.com\tTRUE\t/\tTRUE\t784111777\tname\tvalue",
+            "example.com\tFALSE\t/path\tFALSE\t0\tsession\t",
+        ];
+        for line in lines {
+            assert_eq!(netscape(line)[0].to_netscape_line(), line);
+        }
+        let text = serialize_cookies(&netscape(&lines.join("\n")), CookieJarFormat::Netscape).unwrap();
+        assert_eq!(text, format!("# Netscape HTTP Cookie File\n{}\n", lines.join("\n")));
+    }
+
+    #[test]
+    fn devtools_cookies_without_expiry_are_session_cookies() {
+        let text = r#"[{"name":"a","value":"b","domain":"example.com"}]"#;
+        let cookies = deserialize_cookies(text, CookieJarFormat::DevToolsJson).unwrap();
+        assert_eq!(cookies[0].path, "/");
+        assert!(cookies[0].session);
+        assert_eq!(cookies[0].expires, None);
+    }
+}
@@ -0,0 +1,343 @@
+use crate::{glob, Cookie, CookieHost, Error};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+
+// NOTE: every predicate that is set has to match (so an empty pattern matches any cookie). Globs
+// match whole names or domains, and domains are matched without their leading dot.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct CookiePattern {
+    domain: Option<String>,
+    name: Option<String>,
+    names: Option<Vec<String>>,
+    #[serde(default, with = "serde_regex")]
+    name_regex: Option<Regex>,
+    path_prefix: Option<String>,
+    expired_only: bool,
+    hosts: Option<Vec<CookieHost>>,
+    schemes: Option<Vec<String>>,
+    party: Option<(CookieParty, String)>,
+}
+
+// NOTE: a cookie is first-party for a top-level site when its domain belongs to the same site, which
+// is the registrable domain (one label more than the public suffix) of the host of the site. Cookies
+// of other sites, like those of embedded widgets or identity providers, are third-party.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub enum CookieParty {
+    FirstParty,
+    ThirdParty,
+}
+
+fn site(host: &str) -> String {
+    let host = host.trim_start_matches('.').to_ascii_lowercase();
+    psl::domain_str(&host).unwrap_or(&host).to_owned()
+}
+
+impl CookiePattern {
+    pub fn builder() -> CookiePatternBuilder {
+        CookiePatternBuilder::default()
+    }
+
+    pub fn matches(&self, cookie: &Cookie) -> bool {
+        let domain = cookie.domain.trim_start_matches('.');
+        self.domain
+            .as_deref()
+            .map_or(true, |pattern| glob::matches(pattern, domain))
+            && self
+                .name
+                .as_deref()
+                .map_or(true, |pattern| glob::matches(pattern, &cookie.name))
+            && self.names.as_ref().map_or(true, |names| names.contains(&cookie.name))
+            && self
+                .name_regex
+                .as_ref()
+                .map_or(true, |regex| regex.is_match(&cookie.name))
+            && self
+                .path_prefix
+                .as_deref()
+                .map_or(true, |prefix| path_matches(prefix, &cookie.path))
+            && (!self.expired_only
+                || cookie
+                    .expires
+                    .map_or(false, |expires| expires <= OffsetDateTime::now_utc()))
+            && self
+                .hosts
+                .as_ref()
+                .map_or(true, |hosts| hosts.iter().any(|host| host.matches(cookie)))
+            // NOTE: secure cookies are only sent over secure schemes, while other cookies are sent
+            // over any scheme.
+            && self.schemes.as_ref().map_or(true, |schemes| {
+                schemes
+                    .iter()
+                    .any(|scheme| !cookie.secure || matches!(scheme.as_str(), "https" | "wss"))
+            })
+            && self.party.as_ref().map_or(true, |(party, top_level)| {
+                let first_party = site(&cookie.domain) == site(top_level);
+                first_party == (*party == CookieParty::FirstParty)
+            })
+    }
+}
+
+// NOTE: a plain description of a pattern for settings files and IPC payloads, where an empty list
+// of hosts or schemes matches any host or scheme.
+#[serde(default)]
+#[derive(Clone, Debug, Default, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct CookiePatternSpec {
+    pub hosts: Vec<String>,
+    pub schemes: Vec<String>,
+    pub include_subdomains: bool,
+    pub name_regex: Option<String>,
+}
+
+impl TryFrom<CookiePatternSpec> for CookiePattern {
+    type Error = Error;
+
+    fn try_from(spec: CookiePatternSpec) -> Result<Self, Self::Error> {
+        let mut builder = CookiePattern::builder();
+        if !spec.hosts.is_empty() {
+            builder = builder.match_hosts(spec.hosts, spec.include_subdomains);
+        }
+        if !spec.schemes.is_empty() {
+            builder = builder.match_schemes(spec.schemes);
+        }
+        if let Some(name_regex) = spec.name_regex {
+            builder = builder.match_name_regex(Regex::new(&name_regex)?);
+        }
+        Ok(builder.build())
+    }
+}
+
+// NOTE: prefixes match whole segments, like the path-match of RFC 6265, so `/api` matches `/api` and
+// `/api/v1` but not `/apis`.
+fn path_matches(prefix: &str, path: &str) -> bool {
+    path.strip_prefix(prefix).map_or(false, |rest| {
+        rest.is_empty() || rest.starts_with('/') || prefix.ends_with('/')
+    })
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct CookiePatternBuilder {
+    pattern: CookiePattern,
+}
+
+impl CookiePatternBuilder {
+    pub fn match_domain(mut self, glob: impl Into<String>) -> Self {
+        self.pattern.domain = Some(glob.into());
+        self
+    }
+
+    pub fn match_name(mut self, glob: impl Into<String>) -> Self {
+        self.pattern.name = Some(glob.into());
+        self
+    }
+
+    pub fn match_names(mut self, names: Vec<String>) -> Self {
+        self.pattern.names = Some(names);
+        self
+    }
+
+    pub fn match_name_regex(mut self, regex: Regex) -> Self {
+        self.pattern.name_regex = Some(regex);
+        self
+    }
+
+    pub fn match_path_prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.pattern.path_prefix = Some(prefix.into());
+        self
+    }
+
+    pub fn match_hosts(self, hosts: Vec<String>, include_subdomains: bool) -> Self {
+        let hosts = hosts
+            .into_iter()
+            .map(|host| CookieHost {
+                include_subdomains,
+                ..CookieHost::new(host.trim_start_matches('.'))
+            })
+            .collect();
+        self.match_cookie_hosts(hosts)
+    }
+
+    pub fn match_cookie_hosts(mut self, hosts: Vec<CookieHost>) -> Self {
+        self.pattern.hosts = Some(hosts);
+        self
+    }
+
+    pub fn match_schemes(mut self, schemes: Vec<String>) -> Self {
+        self.pattern.schemes = Some(schemes.into_iter().map(|scheme| scheme.to_ascii_lowercase()).collect());
+        self
+    }
+
+    // NOTE: the top-level site is given by its host, like the host of the URL of the main frame.
+    pub fn match_party(mut self, party: CookieParty, top_level_host: impl Into<String>) -> Self {
+        self.pattern.party = Some((party, top_level_host.into()));
+        self
+    }
+
+    pub fn expired_only(mut self) -> Self {
+        self.pattern.expired_only = true;
+        self
+    }
+
+    pub fn build(self) -> CookiePattern {
+        self.pattern
+    }
+}
+
+mod serde_regex {
+    use regex::Regex;
+    use serde::{de::Error, Deserialize, Deserializer, Serializer};
+
+    pub(super) fn serialize<S: Serializer>(regex: &Option<Regex>, serializer: S) -> Result<S::Ok, S::Error> {
+        match regex {
+            Some(regex) => serializer.serialize_some(regex.as_str()),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub(super) fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<Regex>, D::Error> {
+        Option::<String>::deserialize(deserializer)?
+            .map(|pattern| Regex::new(&pattern).map_err(D::Error::custom))
+            .transpose()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use time::Duration;
+
+    #[test]
+    fn empty_patterns_match_any_cookie() {
+        assert!(CookiePattern::default().matches(&Cookie::new("name", "value")));
+    }
+
+    #[test]
+    fn name_and_domain_globs_match_whole_values() {
+        let cookie = Cookie {
+            domain: ".sub.example.com".into(),
+            ..Cookie::new("session_id", "value")
+        };
+        let matches = |pattern: CookiePatternBuilder| pattern.build().matches(&cookie);
+        assert!(matches(CookiePattern::builder().match_name("session_*")));
+        assert!(!matches(CookiePattern::builder().match_name("session")));
+        assert!(matches(CookiePattern::builder().match_domain("*.example.com")));
+        assert!(matches(CookiePattern::builder().match_domain("sub.example.com")));
+        assert!(!matches(CookiePattern::builder().match_domain("example.com")));
+        assert!(!matches(CookiePattern::builder().match_domain("sub?example.com")));
+    }
+
+    #[test]
+    fn every_predicate_has_to_match() {
+        let cookie = Cookie::new("token", "value");
+        let pattern = CookiePattern::builder()
+            .match_names(vec!["token".into(), "csrf".into()])
+            .match_name_regex(Regex::new("^t").unwrap());
+        assert!(pattern.clone().build().matches(&cookie));
+        assert!(!pattern.match_name("csrf").build().matches(&cookie));
+    }
+
+    #[test]
+    fn path_prefixes_match_whole_segments() {
+        let matches = |prefix: &str, path: &str| {
+            let cookie = Cookie {
+                path: path.into(),
+                ..Cookie::new("name", "value")
+            };
+            CookiePattern::builder()
+                .match_path_prefix(prefix)
+                .build()
+                .matches(&cookie)
+        };
+        assert!(matches("/api", "/api"));
+        assert!(matches("/api", "/api/v1"));
+        assert!(matches("/api/", "/api/v1"));
+        assert!(matches("/", "/api"));
+        assert!(!matches("/api", "/apis"));
+        assert!(!matches("/api/v1", "/api"));
+    }
+
+    #[test]
+    fn expired_only_matches_cookies_that_have_expired() {
+        let expiring = |expires| Cookie {
+            expires,
+            ..Cookie::new("name", "value")
+        };
+        let pattern = CookiePattern::builder().expired_only().build();
+        let now = OffsetDateTime::now_utc();
+        assert!(pattern.matches(&expiring(Some(now - Duration::hours(1)))));
+        assert!(!pattern.matches(&expiring(Some(now + Duration::hours(1)))));
+        assert!(!pattern.matches(&expiring(None)));
+    }
+
+    #[test]
+    fn specs_build_host_and_scheme_patterns() {
+        let cookie = |domain: &str, secure| Cookie {
+            domain: domain.into(),
+            secure,
+            ..Cookie::new("name", "value")
+        };
+        let pattern = CookiePattern::try_from(CookiePatternSpec::default()).unwrap();
+        assert!(pattern.matches(&cookie("example.com", true)));
+        let spec = CookiePatternSpec {
+            hosts: vec![".Example.com".into()],
+            schemes: vec!["HTTP".into()],
+            ..CookiePatternSpec::default()
+        };
+        let pattern = CookiePattern::try_from(spec.clone()).unwrap();
+        assert!(pattern.matches(&cookie(".example.com", false)));
+        assert!(!pattern.matches(&cookie("sub.example.com", false)));
+        assert!(!pattern.matches(&cookie("example.com", true)));
+        let pattern = CookiePattern::try_from(CookiePatternSpec {
+            include_subdomains: true,
+            ..spec
+        })
+        .unwrap();
+        assert!(pattern.matches(&cookie("sub.example.com", false)));
+        assert!(!pattern.matches(&cookie("subexample.com", false)));
+    }
+
+    #[test]
+    fn specs_with_invalid_name_regexes_are_rejected() {
+        let spec = CookiePatternSpec {
+            name_regex: Some("(".into()),
+            ..CookiePatternSpec::default()
+        };
+        assert!(matches!(CookiePattern::try_from(spec), Err(Error::PatternBuild(_))));
+    }
+
+    #[test]
+    fn parties_compare_registrable_domains() {
+        let cookie = |domain: &str| Cookie {
+            domain: domain.into(),
+            ..Cookie::new("name", "value")
+        };
+        let first_party = CookiePattern::builder()
+            .match_party(CookieParty::FirstParty, "www.example.co.uk")
+            .build();
+        let third_party = CookiePattern::builder()
+            .match_party(CookieParty::ThirdParty, "www.example.co.uk")
+            .build();
+        for domain in [".example.co.uk", "login.example.co.uk", "EXAMPLE.co.uk"] {
+            assert!(first_party.matches(&cookie(domain)), "{domain}");
+            assert!(!third_party.matches(&cookie(domain)), "{domain}");
+        }
+        for domain in ["other.co.uk", ".co.uk", "example.com"] {
+            assert!(!first_party.matches(&cookie(domain)), "{domain}");
+            assert!(third_party.matches(&cookie(domain)), "{domain}");
+        }
+    }
+
+    #[test]
+    fn name_regexes_round_trip_through_serde() {
+        let pattern = CookiePattern::builder()
+            .match_name_regex(Regex::new("^a+$").unwrap())
+            .build();
+        let json = serde_json::to_value(&pattern).unwrap();
+        assert_eq!(json["name_regex"], "^a+$");
+        let pattern = serde_json::from_value::<CookiePattern>(json).unwrap();
+        assert!(pattern.matches(&Cookie::new("aaa", "value")));
+        assert!(!pattern.matches(&Cookie::new("ab", "value")));
+        let invalid = serde_json::json!({ "name_regex": "(", "expired_only": false });
+        assert!(serde_json::from_value::<CookiePattern>(invalid).is_err());
+    }
+}
@@ -0,0 +1,71 @@
+use crate::{bridge, platform, BoxResult, WebviewExt};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use tauri::Window;
+use time::OffsetDateTime;
+
+const PROBE_NAME: &str = "__tauri_webview_util_policy_probe";
+const PROBE_MAX_AGE: Duration = Duration::from_secs(400 * 24 * 60 * 60);
+
+// NOTE: `script_cookie_max_age` is the longest lifetime a cookie written by a page script keeps,
+// which is measured on the loaded page when it is served over http(s), and otherwise the documented
+// limit of the engine. `None` means no limit is known.
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[non_exhaustive]
+pub struct CookiePolicyInfo {
+    pub engine: String,
+    pub engine_version: String,
+    pub blocks_third_party_cookies: Option<bool>,
+    pub script_cookie_max_age: Option<Duration>,
+    pub script_cookie_max_age_probed: bool,
+    pub max_cookies_per_domain: Option<usize>,
+}
+
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn info(window: &Window) -> BoxResult<CookiePolicyInfo> {
+    let mut info = platform::cookie_policy(window).await?;
+    let url = window.url();
+    if matches!(url.scheme(), "http" | "https") {
+        if let Some(max_age) = probe_script_cookie_max_age(window, &url).await? {
+            info.script_cookie_max_age = max_age;
+            info.script_cookie_max_age_probed = true;
+        }
+    }
+    Ok(info)
+}
+
+// NOTE: the cookie is written through `document.cookie` and its expiry read back from the native
+// store, since pages can not see the expiry of cookies. Expiries within a minute of the requested one
+// count as not capped. The probe is inconclusive when the cookie was not stored at all.
+async fn probe_script_cookie_max_age(window: &Window, url: &url::Url) -> BoxResult<Option<Option<Duration>>> {
+    let max_age = PROBE_MAX_AGE.as_secs();
+    let body = format!(
+        r#"
+        document.cookie = "{PROBE_NAME}=1; Path=/; Max-Age={max_age}; SameSite=Lax";
+        return null;
+        "#
+    );
+    let written = OffsetDateTime::now_utc();
+    bridge::call(window, &body).await?;
+    let expires = window
+        .webview_get_cookies(Some(url.clone()))
+        .await?
+        .into_iter()
+        .find(|cookie| cookie.name == PROBE_NAME)
+        .and_then(|cookie| cookie.expires);
+    let body = format!(
+        r#"
+        document.cookie = "{PROBE_NAME}=; Path=/; Max-Age=0; SameSite=Lax";
+        return null;
+        "#
+    );
+    bridge::call(window, &body).await?;
+    let max_age = expires.map(|expires| {
+        Some(Duration::try_from(expires - written).unwrap_or_default())
+            .filter(|max_age| *max_age + Duration::from_secs(60) < PROBE_MAX_AGE)
+    });
+    Ok(max_age)
+}
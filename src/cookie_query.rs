@@ -0,0 +1,197 @@
+use crate::Cookie;
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub enum CookieSortKey {
+    #[default]
+    Domain,
+    Name,
+    Expiry,
+    Size,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct CookieQuery {
+    pub sort_by: CookieSortKey,
+    pub descending: bool,
+    pub page: usize,
+    pub page_size: Option<usize>,
+    pub search: Option<String>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct CookiePage {
+    pub cookies: Vec<Cookie>,
+    pub page: usize,
+    pub page_count: usize,
+    pub total: usize,
+}
+
+impl CookieQuery {
+    pub fn apply(&self, cookies: impl IntoIterator<Item = Cookie>) -> CookiePage {
+        let mut cookies = cookies
+            .into_iter()
+            .filter(|cookie| self.matches(cookie))
+            .collect::<Vec<_>>();
+        cookies.sort_by(|lhs, rhs| {
+            let ordering = self.compare(lhs, rhs);
+            if self.descending {
+                ordering.reverse()
+            } else {
+                ordering
+            }
+        });
+        let total = cookies.len();
+        match self.page_size {
+            Some(page_size) if page_size > 0 => {
+                let page_count = total.div_ceil(page_size);
+                let cookies = cookies
+                    .into_iter()
+                    .skip(self.page.saturating_mul(page_size))
+                    .take(page_size)
+                    .collect();
+                CookiePage {
+                    cookies,
+                    page: self.page,
+                    page_count,
+                    total,
+                }
+            },
+            _ => CookiePage {
+                cookies,
+                page: 0,
+                page_count: 1,
+                total,
+            },
+        }
+    }
+
+    // NOTE: values are deliberately not searched so that a search box in a cookie manager can not
+    // be used to probe for secrets.
+    fn matches(&self, cookie: &Cookie) -> bool {
+        match self.search.as_deref().map(str::trim) {
+            None | Some("") => true,
+            Some(search) => {
+                let search = search.to_lowercase();
+                [&cookie.name, &cookie.domain, &cookie.display_domain(), &cookie.path]
+                    .iter()
+                    .any(|field| field.to_lowercase().contains(&search))
+            },
+        }
+    }
+
+    fn compare(&self, lhs: &Cookie, rhs: &Cookie) -> Ordering {
+        let by_domain = || {
+            lhs.domain
+                .trim_start_matches('.')
+                .cmp(rhs.domain.trim_start_matches('.'))
+        };
+        let by_name = || lhs.name.cmp(&rhs.name);
+        match self.sort_by {
+            CookieSortKey::Domain => by_domain().then_with(by_name),
+            CookieSortKey::Name => by_name().then_with(by_domain),
+            // NOTE: session cookies have no expiry and are sorted after persistent ones.
+            CookieSortKey::Expiry => match (lhs.expires, rhs.expires) {
+                (Some(lhs), Some(rhs)) => lhs.cmp(&rhs),
+                (Some(_), None) => Ordering::Less,
+                (None, Some(_)) => Ordering::Greater,
+                (None, None) => Ordering::Equal,
+            }
+            .then_with(by_domain),
+            CookieSortKey::Size => lhs.estimated_size().cmp(&rhs.estimated_size()).then_with(by_domain),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cookie(name: &str, domain: &str) -> Cookie {
+        Cookie {
+            domain: domain.into(),
+            path: "/".into(),
+            ..Cookie::new(name, "secret")
+        }
+    }
+
+    fn cookies() -> Vec<Cookie> {
+        ["a", "b", "c", "d", "e"]
+            .into_iter()
+            .map(|name| cookie(name, &format!("{name}.test")))
+            .collect()
+    }
+
+    fn names(page: &CookiePage) -> Vec<&str> {
+        page.cookies.iter().map(|cookie| cookie.name.as_str()).collect()
+    }
+
+    fn paged(page: usize, page_size: usize) -> CookieQuery {
+        CookieQuery {
+            page,
+            page_size: Some(page_size),
+            ..CookieQuery::default()
+        }
+    }
+
+    #[test]
+    fn pages_split_sorted_cookies() {
+        let page = paged(1, 2).apply(cookies());
+        assert_eq!(names(&page), ["c", "d"]);
+        assert_eq!((page.page, page.page_count, page.total), (1, 3, 5));
+    }
+
+    #[test]
+    fn last_page_is_partial() {
+        let page = paged(2, 2).apply(cookies());
+        assert_eq!(names(&page), ["e"]);
+    }
+
+    #[test]
+    fn pages_past_the_end_are_empty() {
+        let page = paged(10, 2).apply(cookies());
+        assert!(page.cookies.is_empty());
+        assert_eq!((page.page, page.page_count, page.total), (10, 3, 5));
+    }
+
+    #[test]
+    fn huge_pages_do_not_overflow() {
+        let page = paged(usize::MAX, usize::MAX).apply(cookies());
+        assert!(page.cookies.is_empty());
+        assert_eq!((page.page_count, page.total), (1, 5));
+    }
+
+    #[test]
+    fn without_page_size_all_cookies_are_one_page() {
+        for page_size in [None, Some(0)] {
+            let query = CookieQuery {
+                page: 3,
+                page_size,
+                ..CookieQuery::default()
+            };
+            let page = query.apply(cookies());
+            assert_eq!(names(&page), ["a", "b", "c", "d", "e"]);
+            assert_eq!((page.page, page.page_count), (0, 1));
+        }
+    }
+
+    #[test]
+    fn descending_pages_start_from_the_end() {
+        let query = CookieQuery {
+            descending: true,
+            ..paged(0, 2)
+        };
+        assert_eq!(names(&query.apply(cookies())), ["e", "d"]);
+    }
+
+    #[test]
+    fn search_does_not_match_values() {
+        let search = |search: &str| CookieQuery {
+            search: Some(search.into()),
+            ..CookieQuery::default()
+        };
+        assert_eq!(names(&search("C.TEST").apply(cookies())), ["c"]);
+        assert_eq!(search("secret").apply(cookies()).total, 0);
+    }
+}
@@ -0,0 +1,198 @@
+use crate::{
+    cookie_jar::{deserialize_cookies, serialize_cookies, CookieJarFormat},
+    CookiePattern,
+    Error,
+    Result,
+    WebviewExt,
+};
+use chacha20poly1305::{
+    aead::{Aead, AeadCore, KeyInit, OsRng},
+    ChaCha20Poly1305,
+    Key,
+    Nonce,
+};
+use std::{
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+use tauri::{Window, WindowEvent};
+use time::OffsetDateTime;
+
+const SNAPSHOT_PREFIX: &str = "cookies-";
+const SNAPSHOT_EXTENSION: &str = "snapshot";
+const NONCE_SIZE: usize = 12;
+
+// NOTE: snapshots are the matching cookies in the DevTools JSON format, encrypted with
+// ChaCha20-Poly1305 and prefixed with their nonce. The file names contain the time of the snapshot
+// in milliseconds, padded so that they sort chronologically.
+#[derive(Clone)]
+pub struct CookieSnapshotter {
+    window: Window,
+    pattern: CookiePattern,
+    interval: Duration,
+    keep: usize,
+    dir: Option<PathBuf>,
+    key: Option<[u8; 32]>,
+}
+
+impl CookieSnapshotter {
+    pub fn new(window: &Window, pattern: CookiePattern) -> Self {
+        Self {
+            window: window.clone(),
+            pattern,
+            interval: Duration::from_secs(300),
+            keep: 5,
+            dir: None,
+            key: None,
+        }
+    }
+
+    pub fn interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    pub fn keep(mut self, keep: usize) -> Self {
+        self.keep = keep.max(1);
+        self
+    }
+
+    // NOTE: defaults to a directory per window label in the app data directory.
+    pub fn dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.dir = Some(dir.into());
+        self
+    }
+
+    pub fn encryption_key(mut self, key: [u8; 32]) -> Self {
+        self.key = Some(key);
+        self
+    }
+
+    // NOTE: the first snapshot is taken after one interval, and snapshots stop when the window is
+    // destroyed or the returned handle is aborted.
+    pub fn spawn(&self) -> Result<tauri::async_runtime::JoinHandle<()>> {
+        self.cipher()?;
+        let destroyed = Arc::new(AtomicBool::new(false));
+        self.window.on_window_event({
+            let destroyed = destroyed.clone();
+            move |event| {
+                if let WindowEvent::Destroyed = event {
+                    destroyed.store(true, Ordering::Relaxed);
+                }
+            }
+        });
+        let snapshotter = self.clone();
+        Ok(tauri::async_runtime::spawn(async move {
+            loop {
+                tokio::time::sleep(snapshotter.interval).await;
+                if destroyed.load(Ordering::Relaxed) {
+                    break;
+                }
+                if let Err(err) = snapshotter.snapshot().await {
+                    crate::report_error(snapshotter.window.label(), "failed to snapshot cookies", err);
+                }
+            }
+        }))
+    }
+
+    pub async fn snapshot(&self) -> Result<PathBuf> {
+        let cipher = self.cipher()?;
+        let cookies = self.window.webview_collect_cookies(self.pattern.clone()).await?;
+        let text = serialize_cookies(&cookies, CookieJarFormat::DevToolsJson)?;
+        let nonce = ChaCha20Poly1305::generate_nonce(&mut OsRng);
+        let ciphertext = cipher
+            .encrypt(&nonce, text.as_bytes())
+            .map_err(|_| Error::Other("failed to encrypt cookie snapshot".into()))?;
+        let dir = self.directory()?;
+        std::fs::create_dir_all(&dir)?;
+        let millis = OffsetDateTime::now_utc().unix_timestamp_nanos() / 1_000_000;
+        let path = dir.join(format!("{SNAPSHOT_PREFIX}{millis:020}.{SNAPSHOT_EXTENSION}"));
+        // NOTE: the snapshot is written next to its destination first, so that an interrupted write
+        // never leaves a truncated snapshot behind as the latest one.
+        let partial = path.with_extension("partial");
+        let mut bytes = nonce.to_vec();
+        bytes.extend(ciphertext);
+        std::fs::write(&partial, bytes)?;
+        std::fs::rename(&partial, &path)?;
+        self.rotate()?;
+        Ok(path)
+    }
+
+    // NOTE: returns the number of restored cookies, which is zero when there is no snapshot yet.
+    pub async fn restore_latest(&self) -> Result<usize> {
+        let cipher = self.cipher()?;
+        let path = match self.snapshots()?.pop() {
+            Some(path) => path,
+            None => return Ok(0),
+        };
+        let bytes = std::fs::read(path)?;
+        if bytes.len() < NONCE_SIZE {
+            return Err(Error::Other("cookie snapshot is truncated".into()));
+        }
+        let (nonce, ciphertext) = bytes.split_at(NONCE_SIZE);
+        let text = cipher
+            .decrypt(Nonce::from_slice(nonce), ciphertext)
+            .map_err(|_| Error::Other("failed to decrypt cookie snapshot".into()))?;
+        let text = String::from_utf8(text).map_err(|err| Error::CookieConversion(err.into()))?;
+        let cookies = deserialize_cookies(&text, CookieJarFormat::DevToolsJson)?;
+        let count = cookies.len();
+        self.window.webview_set_cookies(cookies).await?;
+        Ok(count)
+    }
+
+    fn cipher(&self) -> Result<ChaCha20Poly1305> {
+        let key = self
+            .key
+            .ok_or_else(|| Error::Other("cookie snapshots require an encryption key".into()))?;
+        Ok(ChaCha20Poly1305::new(Key::from_slice(&key)))
+    }
+
+    fn directory(&self) -> Result<PathBuf> {
+        if let Some(dir) = &self.dir {
+            return Ok(dir.clone());
+        }
+        let dir = self
+            .window
+            .app_handle()
+            .path_resolver()
+            .app_data_dir()
+            .ok_or_else(|| Error::PlatformApi("failed to resolve the app data directory".into()))?;
+        Ok(dir.join("cookie-snapshots").join(self.window.label()))
+    }
+
+    // NOTE: sorted from the oldest to the latest snapshot.
+    fn snapshots(&self) -> Result<Vec<PathBuf>> {
+        let dir = self.directory()?;
+        if !dir.exists() {
+            return Ok(vec![]);
+        }
+        let mut snapshots = std::fs::read_dir(dir)?
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| {
+                let is_snapshot = path
+                    .extension()
+                    .map_or(false, |extension| extension == SNAPSHOT_EXTENSION);
+                let has_prefix = path
+                    .file_name()
+                    .and_then(|name| name.to_str())
+                    .map_or(false, |name| name.starts_with(SNAPSHOT_PREFIX));
+                is_snapshot && has_prefix
+            })
+            .collect::<Vec<_>>();
+        snapshots.sort();
+        Ok(snapshots)
+    }
+
+    fn rotate(&self) -> Result<()> {
+        let snapshots = self.snapshots()?;
+        let excess = snapshots.len().saturating_sub(self.keep);
+        for path in &snapshots[.. excess] {
+            std::fs::remove_file(path)?;
+        }
+        Ok(())
+    }
+}
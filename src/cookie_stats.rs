@@ -0,0 +1,53 @@
+use crate::Cookie;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use time::OffsetDateTime;
+
+// NOTE: `header_size` is the size of the `Cookie` header that would carry all of the cookies of the
+// domain, which is what servers limit (commonly to 8 KiB), while `total_size` is the size of the
+// `Set-Cookie` values as estimated by `Cookie::estimated_size`.
+#[derive(Clone, Debug, Default, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct DomainCookieStats {
+    pub count: usize,
+    pub total_size: usize,
+    pub header_size: usize,
+}
+
+// NOTE: domains are keyed without their leading dot, so host-only and domain cookies for the same
+// domain are counted together. The soonest expiry is that of the persistent cookies.
+#[derive(Clone, Debug, Default, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct CookieStats {
+    pub count: usize,
+    pub total_size: usize,
+    pub session_count: usize,
+    pub persistent_count: usize,
+    pub soonest_expiry: Option<OffsetDateTime>,
+    pub domains: BTreeMap<String, DomainCookieStats>,
+}
+
+impl CookieStats {
+    pub fn from_cookies<'a>(cookies: impl IntoIterator<Item = &'a Cookie>) -> Self {
+        let mut stats = Self::default();
+        for cookie in cookies {
+            let size = cookie.estimated_size();
+            stats.count += 1;
+            stats.total_size += size;
+            match cookie.expires {
+                Some(expires) if !cookie.session => {
+                    stats.persistent_count += 1;
+                    stats.soonest_expiry = Some(stats.soonest_expiry.map_or(expires, |soonest| soonest.min(expires)));
+                },
+                _ => stats.session_count += 1,
+            }
+            let domain = cookie.domain.trim_start_matches('.').to_ascii_lowercase();
+            let domain = stats.domains.entry(domain).or_default();
+            if domain.count > 0 {
+                domain.header_size += "; ".len();
+            }
+            domain.count += 1;
+            domain.total_size += size;
+            domain.header_size += cookie.name.len() + "=".len() + cookie.value.len();
+        }
+        stats
+    }
+}
@@ -0,0 +1,315 @@
+use crate::{
+    bridge,
+    event_bus::{self, WebviewEvent},
+    platform,
+    self_events::{self, SelfTags},
+    state::{self, WindowState},
+    stream,
+    BoxResult,
+    Cookie,
+    CookiePattern,
+    Error,
+    Result,
+    WebviewExt,
+};
+use futures::stream::BoxStream;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+        RwLock,
+    },
+    time::Duration,
+};
+use tauri::Window;
+
+type CookieKey = (String, String, String);
+
+const MIN_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct CookiePolling {
+    pub interval: Duration,
+    pub mode: CookiePollMode,
+}
+
+impl CookiePolling {
+    pub const fn new() -> Self {
+        Self {
+            interval: Duration::from_secs(1),
+            mode: CookiePollMode::Fallback,
+        }
+    }
+}
+
+impl Default for CookiePolling {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub enum CookiePollMode {
+    // NOTE: polls only with engines that do not report changes to the cookie store, which is
+    // WebView2. `Always` is for engines that report them unreliably, like older webkit2gtk.
+    #[default]
+    Fallback,
+    Always,
+}
+
+static COOKIE_POLLING: RwLock<CookiePolling> = RwLock::new(CookiePolling::new());
+
+// NOTE: the mode applies to windows that start watching cookies afterwards, while the interval also
+// applies to windows that are already polling, from their next poll on.
+pub fn set_cookie_polling(polling: CookiePolling) {
+    if let Ok(mut current) = COOKIE_POLLING.write() {
+        *current = polling;
+    }
+}
+
+pub fn cookie_polling() -> CookiePolling {
+    COOKIE_POLLING.read().map(|polling| *polling).unwrap_or_default()
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub enum CookieChange {
+    Added(Cookie),
+    Deleted(Cookie),
+    Updated { previous: Cookie, current: Cookie },
+}
+
+impl CookieChange {
+    pub fn cookie(&self) -> &Cookie {
+        match self {
+            Self::Added(cookie) | Self::Deleted(cookie) => cookie,
+            Self::Updated { current, .. } => current,
+        }
+    }
+}
+
+// NOTE: `Native` changes were made through this crate, like with `webview_set_cookie`, and `Page`
+// changes by scripts or responses of the page. Deletions that were not made through this crate are
+// `Unknown`, since cookies are also deleted when they expire or when the engine evicts them.
+#[cfg_attr(feature = "async-graphql", derive(async_graphql::Enum))]
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub enum ChangeOrigin {
+    Page,
+    Native,
+    Unknown,
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct CookieChangeEvent {
+    pub change: CookieChange,
+    pub origin: ChangeOrigin,
+}
+
+impl CookieChangeEvent {
+    pub fn cookie(&self) -> &Cookie {
+        self.change.cookie()
+    }
+}
+
+type Subscriber = (CookiePattern, bool, stream::Sender<CookieChangeEvent>);
+
+#[derive(Default)]
+pub(crate) struct CookieWatchState {
+    subscribers: Vec<Subscriber>,
+    snapshot: Option<BTreeMap<CookieKey, Cookie>>,
+    native: SelfTags<CookieKey>,
+    refreshing: bool,
+    dirty: bool,
+}
+
+// NOTE: a window with an event bus keeps watching its cookies, so that their changes can be
+// published on the bus.
+fn watching(state: &WindowState, watch: &CookieWatchState) -> bool {
+    !watch.subscribers.is_empty() || state.event_buses.lock().map_or(false, |buses| !buses.is_empty())
+}
+
+// NOTE: called by the platforms when they write or delete a cookie, so that the change it causes can
+// be attributed to this crate. Nothing is recorded while no one is watching.
+pub(crate) fn tag(window: &Window, cookie: &Cookie) {
+    let Ok(state) = state::window_state(window) else {
+        return;
+    };
+    let Ok(mut watch) = state.cookie_watch.lock() else {
+        return;
+    };
+    if watching(&state, &watch) {
+        watch.native.tag(key(cookie));
+    }
+}
+
+// NOTE: the engines only report that the cookie store changed (or, with WebView2, not even that),
+// so the whole store is read again and compared with the previous snapshot to find the changes.
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn watch(
+    window: &Window,
+    pattern: CookiePattern,
+) -> BoxResult<BoxStream<'static, Result<CookieChangeEvent>>> {
+    let (change_tx, change_rx) = stream::channel(stream::stream_config());
+    start(window, Some((pattern, self_events::ignore_self(), change_tx))).await?;
+    Ok(change_rx)
+}
+
+pub(crate) async fn install(window: &Window) -> BoxResult<()> {
+    start(window, None).await
+}
+
+async fn start(window: &Window, subscriber: Option<Subscriber>) -> BoxResult<()> {
+    let state = state::window_state(window)?;
+    let cookies = window.webview_get_cookies(None).await?;
+    {
+        let mut watch = state.cookie_watch.lock()?;
+        watch.snapshot.get_or_insert_with(|| index(cookies));
+        watch.subscribers.extend(subscriber);
+    }
+    if let Some(install) = bridge::install_once(&state, "cookie-watch").await? {
+        let observed = match cookie_polling().mode {
+            CookiePollMode::Always => false,
+            CookiePollMode::Fallback => {
+                let observer = {
+                    let window = window.clone();
+                    move || schedule_refresh(&window)
+                };
+                match platform::add_cookie_observer(window, observer).await {
+                    Ok(()) => true,
+                    Err(err) if matches!(err.downcast_ref::<Error>(), Some(Error::UnsupportedPlatform(_))) => false,
+                    Err(err) => return Err(err),
+                }
+            },
+        };
+        if !observed {
+            poll(window);
+        }
+        install.done();
+    }
+    Ok(())
+}
+
+// NOTE: each poll waits for the previous one to finish, so a slow cookie store is read no more often
+// than the interval allows. Polls are skipped while nothing is watching, until the window is
+// destroyed.
+fn poll(window: &Window) {
+    let destroyed = Arc::new(AtomicBool::new(false));
+    window.on_window_event({
+        let destroyed = destroyed.clone();
+        move |event| {
+            if let tauri::WindowEvent::Destroyed = event {
+                destroyed.store(true, Ordering::Relaxed);
+            }
+        }
+    });
+    let window = window.clone();
+    tauri::async_runtime::spawn(async move {
+        while !destroyed.load(Ordering::Relaxed) {
+            tokio::time::sleep(cookie_polling().interval.max(MIN_POLL_INTERVAL)).await;
+            if let Err(err) = refresh(&window).await {
+                crate::report_error(window.label(), "failed to refresh watched cookies", err);
+            }
+        }
+    });
+}
+
+// NOTE: refreshes run one at a time, and the changes reported while one is running are coalesced into
+// a single refresh after it, so that a stale snapshot never replaces a newer one.
+fn schedule_refresh(window: &Window) {
+    let Ok(state) = state::window_state(window) else {
+        return;
+    };
+    match state.cookie_watch.lock() {
+        Ok(mut watch) => {
+            watch.dirty = true;
+            if std::mem::replace(&mut watch.refreshing, true) {
+                return;
+            }
+        },
+        Err(_) => return,
+    }
+    let window = window.clone();
+    tauri::async_runtime::spawn(async move {
+        loop {
+            match state.cookie_watch.lock() {
+                Ok(mut watch) if watch.dirty => watch.dirty = false,
+                Ok(mut watch) => {
+                    watch.refreshing = false;
+                    break;
+                },
+                Err(_) => break,
+            }
+            if let Err(err) = refresh(&window).await {
+                crate::report_error(window.label(), "failed to refresh watched cookies", err);
+            }
+        }
+    });
+}
+
+async fn refresh(window: &Window) -> BoxResult<()> {
+    let state = state::window_state(window)?;
+    if !watching(&state, &*state.cookie_watch.lock()?) {
+        return Ok(());
+    }
+    let current = index(window.webview_get_cookies(None).await?);
+    let events = {
+        let mut watch = state.cookie_watch.lock()?;
+        let previous = watch.snapshot.replace(current.clone()).unwrap_or_default();
+        let events = diff(previous, current)
+            .into_iter()
+            .map(|change| {
+                let origin = if watch.native.take(&key(change.cookie())) {
+                    ChangeOrigin::Native
+                } else if matches!(change, CookieChange::Deleted(_)) {
+                    ChangeOrigin::Unknown
+                } else {
+                    ChangeOrigin::Page
+                };
+                CookieChangeEvent { change, origin }
+            })
+            .collect::<Vec<_>>();
+        watch.subscribers.retain(|(pattern, ignore_self, subscriber)| {
+            events
+                .iter()
+                .filter(|event| pattern.matches(event.cookie()))
+                .filter(|event| !(*ignore_self && event.origin == ChangeOrigin::Native))
+                .all(|event| subscriber.send(event.clone()).is_ok())
+        });
+        // NOTE: a new snapshot is taken by the next subscriber, rather than comparing against a stale
+        // one.
+        if !watching(&state, &watch) {
+            watch.snapshot = None;
+            watch.native.clear();
+        }
+        events
+    };
+    for event in events {
+        event_bus::publish(window, WebviewEvent::CookieChanged(event));
+    }
+    Ok(())
+}
+
+fn key(cookie: &Cookie) -> CookieKey {
+    (cookie.domain.clone(), cookie.path.clone(), cookie.name.clone())
+}
+
+fn index(cookies: Vec<Cookie>) -> BTreeMap<CookieKey, Cookie> {
+    cookies.into_iter().map(|cookie| (key(&cookie), cookie)).collect()
+}
+
+fn diff(mut previous: BTreeMap<CookieKey, Cookie>, current: BTreeMap<CookieKey, Cookie>) -> Vec<CookieChange> {
+    let mut changes = vec![];
+    for (key, current) in current {
+        match previous.remove(&key) {
+            None => changes.push(CookieChange::Added(current)),
+            Some(previous) if previous != current => changes.push(CookieChange::Updated { previous, current }),
+            Some(_) => {},
+        }
+    }
+    changes.extend(previous.into_values().map(CookieChange::Deleted));
+    changes
+}
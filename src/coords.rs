@@ -0,0 +1,57 @@
+use crate::{bridge, platform, BoxResult};
+use serde::Deserialize;
+use tauri::{LogicalPosition, PhysicalPosition, Window};
+
+const PAGE_METRICS_SCRIPT: &str = include_str!("js/page_metrics.js");
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PageMetrics {
+    scroll_x: f64,
+    scroll_y: f64,
+    viewport_x: f64,
+    viewport_y: f64,
+    scale: f64,
+    device_pixel_ratio: f64,
+}
+
+// NOTE: the device pixel ratio of the page includes its zoom in WebView2, like it does in Chromium,
+// but not in WebKit, where the zoom of the webview is applied on top of it.
+pub(crate) async fn device_pixel_ratio(window: &Window, page_ratio: f64) -> BoxResult<f64> {
+    if cfg!(target_os = "windows") {
+        Ok(page_ratio)
+    } else {
+        Ok(page_ratio * platform::zoom(window).await?)
+    }
+}
+
+// NOTE: page coordinates are CSS pixels relative to the document, so they are moved by the scroll
+// offset of the page and by the visual viewport while it is pinch-zoomed.
+async fn metrics(window: &Window) -> BoxResult<(PageMetrics, f64)> {
+    let body = format!("return ({PAGE_METRICS_SCRIPT})();");
+    let metrics = serde_json::from_value::<PageMetrics>(bridge::call(window, &body).await?)?;
+    let ratio = device_pixel_ratio(window, metrics.device_pixel_ratio).await?;
+    Ok((metrics, ratio))
+}
+
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn page_to_window(window: &Window, point: LogicalPosition<f64>) -> BoxResult<PhysicalPosition<f64>> {
+    let (metrics, ratio) = metrics(window).await?;
+    let x = (point.x - metrics.scroll_x - metrics.viewport_x) * metrics.scale * ratio;
+    let y = (point.y - metrics.scroll_y - metrics.viewport_y) * metrics.scale * ratio;
+    Ok(PhysicalPosition::new(x, y))
+}
+
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn window_to_page(window: &Window, point: PhysicalPosition<f64>) -> BoxResult<LogicalPosition<f64>> {
+    let (metrics, ratio) = metrics(window).await?;
+    let x = point.x / ratio / metrics.scale + metrics.viewport_x + metrics.scroll_x;
+    let y = point.y / ratio / metrics.scale + metrics.viewport_y + metrics.scroll_y;
+    Ok(LogicalPosition::new(x, y))
+}
@@ -0,0 +1,90 @@
+use crate::{bridge, init_script, state, BoxResult};
+use serde::{Deserialize, Serialize};
+use std::{fmt, sync::Arc};
+use tauri::Window;
+use url::{Origin, Url};
+
+const CREDENTIALS_SCRIPT: &str = include_str!("js/credentials.js");
+
+#[derive(Clone)]
+pub(crate) struct CredentialCapture {
+    origins: Vec<Origin>,
+    handler: Arc<dyn Fn(SubmittedCredentials) + Send + Sync + 'static>,
+}
+
+#[derive(Clone, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct SubmittedCredentials {
+    pub origin: Url,
+    pub username: Option<String>,
+    pub password: String,
+}
+
+// NOTE: the origin of a submission is not taken from the page but from the webview.
+#[derive(Deserialize)]
+struct Submission {
+    username: Option<String>,
+    password: String,
+}
+
+impl fmt::Debug for SubmittedCredentials {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SubmittedCredentials")
+            .field("origin", &self.origin)
+            .field("username", &self.username)
+            .field("password", &"<redacted>")
+            .finish()
+    }
+}
+
+// NOTE: asking the user for consent is left to the app, which should only call this once it has
+// been given. Submissions are observed in the main frame only, and their origin is the one the
+// webview reports for the document that posted them, which is checked against the allowed origins
+// before the handler is called, so a page can not pass credentials off as coming from another origin.
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window, handler),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn capture(
+    window: &Window,
+    origins: Vec<Url>,
+    handler: impl Fn(SubmittedCredentials) + Send + Sync + 'static,
+) -> BoxResult<()> {
+    let state = state::window_state(window)?;
+    let allowed = origins
+        .iter()
+        .map(|origin| origin.origin().ascii_serialization())
+        .collect::<Vec<_>>();
+    *state.credential_capture.lock()? = Some(CredentialCapture {
+        origins: origins.iter().map(Url::origin).collect(),
+        handler: Arc::new(handler),
+    });
+    if let Some(install) = bridge::install_once(&state, "credentials").await? {
+        bridge::on_message_from(window, "credentials-submitted", {
+            let window = window.clone();
+            move |origin, payload| {
+                let Submission { username, password } = match serde_json::from_value::<Submission>(payload) {
+                    Ok(submission) => submission,
+                    Err(err) => return crate::report_error(window.label(), "malformed submitted credentials", err),
+                };
+                let capture = state::window_state(&window)
+                    .and_then(|state| Ok(state.credential_capture.lock()?.clone()))
+                    .ok()
+                    .flatten();
+                if let Some(capture) = capture {
+                    if capture.origins.contains(&origin.origin()) {
+                        (capture.handler)(SubmittedCredentials {
+                            origin: origin.clone(),
+                            username,
+                            password,
+                        });
+                    }
+                }
+            }
+        })
+        .await?;
+        install.done();
+    }
+    let allowed = serde_json::to_string(&allowed)?;
+    let source = format!("({CREDENTIALS_SCRIPT})({allowed});");
+    init_script::replace(window, &state.credential_script, source).await
+}
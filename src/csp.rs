@@ -0,0 +1,219 @@
+use crate::{
+    bridge,
+    scheme_handler::{Handler, ResponseParts, SchemeRequest},
+    state,
+    stream,
+    BoxResult,
+    Result,
+};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use futures::{stream::BoxStream, FutureExt};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tauri::Window;
+
+const CSP_SCRIPT: &str = include_str!("js/csp.js");
+
+// NOTE: only the scripts and styles of the served origin itself are allowed, scripts only with the
+// nonce of the document, and the document can not be framed by other origins.
+const DEFAULT_POLICY: &str = concat!(
+    "default-src 'self'; ",
+    "script-src 'self' 'nonce-{nonce}'; ",
+    "style-src 'self' 'unsafe-inline'; ",
+    "img-src 'self' data: blob:; ",
+    "object-src 'none'; ",
+    "base-uri 'self'; ",
+    "frame-ancestors 'self'",
+);
+
+/// A `Content-Security-Policy` that is attached to the responses of a custom scheme or served
+/// directory, see [`crate::SchemeOptions`].
+///
+/// With `nonce`, every `{nonce}` in the policy is replaced with a new nonce for each response, and
+/// the `<script>` elements of HTML documents get that nonce, so that only the scripts the app
+/// serves itself can run. Such documents are neither compressed nor cached, and documents that can
+/// not be rewritten, like streamed responses, get the policy without the nonce. With `report_only`,
+/// the policy is sent as `Content-Security-Policy-Report-Only`, whose violations can be watched with
+/// [`crate::WebviewExt::webview_csp_reports`]. The default policy only allows content of the served
+/// origin itself.
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct CspOptions {
+    pub policy: String,
+    pub nonce: bool,
+    pub report_only: bool,
+}
+
+impl Default for CspOptions {
+    fn default() -> Self {
+        Self {
+            policy: DEFAULT_POLICY.into(),
+            nonce: true,
+            report_only: false,
+        }
+    }
+}
+
+impl CspOptions {
+    pub fn new(policy: impl Into<String>) -> Self {
+        let policy = policy.into();
+        Self {
+            nonce: policy.contains("{nonce}"),
+            policy,
+            report_only: false,
+        }
+    }
+
+    // NOTE: a document with a nonce is only served in full and uncompressed, since the nonce has to
+    // be added to its body. Its validators are removed and it is not stored, because a revalidated
+    // document would keep the nonce of the response it was first served with.
+    pub(crate) fn with_handler<R: ResponseParts>(self, handler: Handler<R>) -> Handler<R> {
+        Arc::new(move |request: SchemeRequest| {
+            let csp = self.clone();
+            let handler = handler.clone();
+            async move {
+                let retry = csp.nonce.then(|| SchemeRequest {
+                    headers: request
+                        .headers
+                        .iter()
+                        .filter(|(name, _)| {
+                            !NEGOTIATION_HEADERS
+                                .iter()
+                                .any(|header| name.eq_ignore_ascii_case(header))
+                        })
+                        .cloned()
+                        .collect(),
+                    ..request.clone()
+                });
+                let mut response = handler(request).await;
+                if let Some(retry) = retry {
+                    if response.is_html() && response.has_header("Content-Encoding") {
+                        response = handler(retry).await;
+                    }
+                }
+                csp.apply(response)
+            }
+            .boxed()
+        })
+    }
+
+    // NOTE: a `304` gets no policy with a nonce, so that the webview keeps the policy it stored
+    // together with the body. A body that could not be given the nonce gets the policy without it,
+    // since the nonce would only block the scripts of the body.
+    fn apply<R: ResponseParts>(&self, mut response: R) -> R {
+        let header = if self.report_only {
+            "Content-Security-Policy-Report-Only"
+        } else {
+            "Content-Security-Policy"
+        };
+        if response.has_header(header) {
+            return response;
+        }
+        if !self.nonce {
+            return response.with_header(header, self.policy.clone());
+        }
+        if response.status() == 304 {
+            return response;
+        }
+        let mut nonce = [0; 16];
+        if getrandom::getrandom(&mut nonce).is_err() {
+            return response.with_header(header, without_nonce(&self.policy));
+        }
+        let nonce = STANDARD.encode(nonce);
+        if !response.add_script_nonce(&nonce) {
+            return response.with_header(header, without_nonce(&self.policy));
+        }
+        for name in ["ETag", "Last-Modified", "Cache-Control"] {
+            response.remove_header(name);
+        }
+        response
+            .with_header("Cache-Control", "no-store".into())
+            .with_header(header, self.policy.replace("{nonce}", &nonce))
+    }
+}
+
+const NEGOTIATION_HEADERS: &[&str] = &["Accept-Encoding", "If-Modified-Since", "If-None-Match", "If-Range"];
+
+fn without_nonce(policy: &str) -> String {
+    policy
+        .split(';')
+        .map(|directive| {
+            directive
+                .split_whitespace()
+                .filter(|source| !source.contains("{nonce}"))
+                .collect::<Vec<_>>()
+                .join(" ")
+        })
+        .filter(|directive| !directive.is_empty())
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
+// NOTE: the nonce is added to every `<script` tag of the document, including those of scripts that
+// are only written out as text, which is harmless since a nonce does not make text a script. Scripts
+// that already have a nonce keep theirs.
+pub(crate) fn add_script_nonce(body: &[u8], nonce: &str) -> Option<Vec<u8>> {
+    let html = std::str::from_utf8(body).ok()?;
+    let lower = html.to_ascii_lowercase();
+    let mut result = String::with_capacity(html.len());
+    let mut rest = 0;
+    for (start, _) in lower.match_indices("<script") {
+        let end = start + "<script".len();
+        let tag_end = lower[end ..].find('>').map_or(lower.len(), |index| end + index);
+        let is_tag = lower[end ..].starts_with(|c: char| c.is_ascii_whitespace() || c == '>' || c == '/');
+        if !is_tag || lower[end .. tag_end].contains("nonce=") {
+            continue;
+        }
+        result.push_str(&html[rest .. end]);
+        result.push_str(&format!(" nonce=\"{nonce}\""));
+        rest = end;
+    }
+    result.push_str(&html[rest ..]);
+    Some(result.into_bytes())
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CspViolation {
+    pub document_uri: String,
+    pub blocked_uri: Option<String>,
+    pub effective_directive: String,
+    pub original_policy: String,
+    // NOTE: either "enforce" or "report", the latter for `Content-Security-Policy-Report-Only`.
+    pub disposition: String,
+    pub source_file: Option<String>,
+    pub line_number: Option<u32>,
+    pub column_number: Option<u32>,
+    pub sample: Option<String>,
+}
+
+// NOTE: violations are reported by a `securitypolicyviolation` listener that is added at document
+// start, which also sees violations of report-only policies, so no report endpoint is needed.
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn reports(window: &Window) -> BoxResult<BoxStream<'static, Result<CspViolation>>> {
+    let state = state::window_state(window)?;
+    let (violation_tx, violation_rx) = stream::channel(stream::stream_config());
+    state.csp_subscribers.lock()?.push(violation_tx);
+    if let Some(install) = bridge::install_once(&state, "csp").await? {
+        bridge::on_message(window, "csp-violation", {
+            let window = window.clone();
+            move |payload| {
+                let violation = match serde_json::from_value::<CspViolation>(payload) {
+                    Ok(violation) => violation,
+                    Err(err) => return crate::report_error(window.label(), "malformed csp violation", err),
+                };
+                if let Ok(state) = state::window_state(&window) {
+                    if let Ok(mut subscribers) = state.csp_subscribers.lock() {
+                        subscribers.retain(|subscriber| subscriber.send(violation.clone()).is_ok());
+                    }
+                }
+            }
+        })
+        .await?;
+        bridge::add_script(window, CSP_SCRIPT).await?;
+        install.done();
+    }
+    Ok(violation_rx)
+}
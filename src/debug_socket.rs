@@ -0,0 +1,99 @@
+use crate::{Error, WebviewExt};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use tauri::{AppHandle, Manager, Window, Wry};
+use tokio::{
+    io::{AsyncBufReadExt, AsyncWriteExt, BufReader},
+    net::{TcpListener, TcpStream},
+};
+use url::Url;
+
+pub const DEBUG_ADDR_VAR: &str = "TAURI_WEBVIEW_UTIL_DEBUG_ADDR";
+pub const DEFAULT_DEBUG_ADDR: &str = "127.0.0.1:7357";
+
+#[derive(Debug, Deserialize)]
+struct DebugRequest {
+    window: Option<String>,
+    #[serde(flatten)]
+    command: DebugCommand,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "command", rename_all = "kebab-case")]
+enum DebugCommand {
+    Windows,
+    Cookies { url: Option<Url> },
+    ClearCache,
+    Navigate { url: Url },
+}
+
+// NOTE: the socket only listens in debug builds, on the loopback address from the environment, and
+// takes one JSON request per line, which is answered with one JSON response per line.
+pub(crate) fn serve(app: AppHandle<Wry>) {
+    if !cfg!(debug_assertions) {
+        return;
+    }
+    let addr = std::env::var(DEBUG_ADDR_VAR).unwrap_or_else(|_| DEFAULT_DEBUG_ADDR.into());
+    tauri::async_runtime::spawn(async move {
+        let listener = match TcpListener::bind(&addr).await {
+            Ok(listener) if listener.local_addr().map_or(false, |addr| addr.ip().is_loopback()) => listener,
+            #[allow(unused_variables)]
+            result => {
+                #[cfg(feature = "tracing")]
+                tracing::warn!(%addr, error = ?result.err(), "the debug socket could not listen on a loopback address");
+                return;
+            },
+        };
+        while let Ok((stream, _)) = listener.accept().await {
+            tauri::async_runtime::spawn(connect(app.clone(), stream));
+        }
+    });
+}
+
+async fn connect(app: AppHandle<Wry>, stream: TcpStream) {
+    let (reader, mut writer) = stream.into_split();
+    let mut lines = BufReader::new(reader).lines();
+    while let Ok(Some(line)) = lines.next_line().await {
+        let response = match respond(&app, &line).await {
+            Ok(value) => json!({ "ok": value }),
+            Err(err) => json!({ "error": err.to_string() }),
+        };
+        let mut response = response.to_string();
+        response.push('\n');
+        if writer.write_all(response.as_bytes()).await.is_err() {
+            break;
+        }
+    }
+}
+
+async fn respond(app: &AppHandle<Wry>, line: &str) -> crate::Result<Value> {
+    let DebugRequest { window, command } = serde_json::from_str(line).map_err(Error::Serialization)?;
+    let window = window.as_deref();
+    match command {
+        DebugCommand::Windows => {
+            let mut labels = app.windows().into_keys().collect::<Vec<_>>();
+            labels.sort();
+            Ok(json!(labels))
+        },
+        DebugCommand::Cookies { url } => Ok(json!(find_window(app, window)?.webview_get_cookies(url).await?)),
+        DebugCommand::ClearCache => Ok(json!(find_window(app, window)?.webview_clear_cache().await?)),
+        DebugCommand::Navigate { url } => Ok(json!(find_window(app, window)?.webview_navigate(url)?)),
+    }
+}
+
+// NOTE: the window can be left out when the app has only one.
+fn find_window(app: &AppHandle<Wry>, label: Option<&str>) -> crate::Result<Window> {
+    if let Some(label) = label {
+        return app
+            .get_window(label)
+            .ok_or_else(|| Error::Other(format!("there is no window labeled `{label}`").into()));
+    }
+    let mut windows = app.windows().into_values();
+    match (windows.next(), windows.next()) {
+        (Some(window), None) => Ok(window),
+        (None, _) => Err(Error::Other("the app has no windows".into())),
+        (Some(_), Some(_)) => Err(Error::Other(
+            "the app has several windows, so one must be chosen".into(),
+        )),
+    }
+}
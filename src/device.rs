@@ -0,0 +1,60 @@
+use crate::{init_script, platform, state, BoxResult};
+use serde::{Deserialize, Serialize};
+use tauri::{LogicalSize, PhysicalSize, Size, Window};
+
+const DEVICE_SCRIPT: &str = include_str!("js/device.js");
+
+// NOTE: `width` and `height` are the size of the viewport in CSS pixels, and `ua` replaces the user
+// agent while the profile is emulated.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct DeviceProfile {
+    pub width: u32,
+    pub height: u32,
+    pub dpr: f64,
+    pub touch: bool,
+    pub ua: Option<String>,
+}
+
+#[derive(Default)]
+pub(crate) struct DeviceState {
+    window_size: Option<PhysicalSize<u32>>,
+}
+
+// NOTE: WebView2 emulates the metrics and touch input of the device itself. With WebKit the window is
+// resized to fit the viewport instead, and is restored once emulation stops, while the pixel ratio
+// and touch points are only overridden for scripts in the top frame, so media queries and the
+// resolution the page is drawn at are not affected.
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn emulate(window: &Window, profile: Option<DeviceProfile>) -> BoxResult<()> {
+    let state = state::window_state(window)?;
+    let user_agent = match profile.as_ref().and_then(|profile| profile.ua.clone()) {
+        Some(user_agent) => Some(user_agent),
+        None => state.user_agent.lock()?.clone(),
+    };
+    platform::set_user_agent(window, user_agent).await?;
+    if platform::emulate_device(window, profile.as_ref()).await? {
+        return Ok(());
+    }
+    match &profile {
+        Some(profile) => {
+            let mut device = state.device.lock()?;
+            if device.window_size.is_none() {
+                device.window_size = Some(window.inner_size()?);
+            }
+            let size = LogicalSize::new(f64::from(profile.width), f64::from(profile.height));
+            window.set_size(Size::Logical(size))?;
+        },
+        None => {
+            let window_size = state.device.lock()?.window_size.take();
+            if let Some(size) = window_size {
+                window.set_size(Size::Physical(size))?;
+            }
+        },
+    }
+    let device = profile.map(|profile| serde_json::json!({ "dpr": profile.dpr, "touch": profile.touch }));
+    let source = format!("({DEVICE_SCRIPT})({});", serde_json::to_string(&device)?);
+    init_script::replace(window, &state.device_script, source).await
+}
@@ -0,0 +1,47 @@
+use crate::{bridge, event_bus, event_bus::WebviewEvent, navigation_events, navigation_queue, state, BoxResult};
+use serde::Deserialize;
+use tauri::Window;
+use url::Url;
+
+const DOCUMENT_SCRIPT: &str = include_str!("js/document.js");
+
+#[derive(Debug, Deserialize)]
+struct DocumentReady {
+    url: Url,
+}
+
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn install(window: &Window) -> BoxResult<()> {
+    let state = state::window_state(window)?;
+    let Some(install) = bridge::install_once(&state, "document").await? else {
+        return Ok(());
+    };
+    bridge::on_message(window, "document-ready", {
+        let window = window.clone();
+        move |payload| {
+            let ready = match serde_json::from_value::<DocumentReady>(payload) {
+                Ok(ready) => ready,
+                Err(err) => return crate::report_error(window.label(), "malformed document event", err),
+            };
+            navigation_queue::on_document_ready(&window);
+            event_bus::publish(&window, WebviewEvent::DocumentReady { url: ready.url });
+        }
+    })
+    .await?;
+    bridge::on_message(window, "document-unload", {
+        let window = window.clone();
+        move |_payload| {
+            bridge::cancel_calls(&window);
+            navigation_queue::on_document_unload(&window);
+            event_bus::publish(&window, WebviewEvent::DocumentUnload);
+        }
+    })
+    .await?;
+    navigation_events::install(window).await?;
+    bridge::add_script(window, DOCUMENT_SCRIPT).await?;
+    install.done();
+    Ok(())
+}
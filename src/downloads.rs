@@ -0,0 +1,308 @@
+use crate::{
+    bridge,
+    error::WindowContext,
+    event_bus::{self, WebviewEvent},
+    platform::{self, NativeDownload},
+    state,
+    stream,
+    BoxResult,
+    Error,
+    Result,
+};
+use futures::stream::BoxStream;
+use std::{
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+        Mutex,
+    },
+};
+use tauri::Window;
+use url::Url;
+
+static NEXT_ID: AtomicU64 = AtomicU64::new(1);
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum DownloadEvent {
+    // NOTE: the download waits for its destination until `set_destination`, `resume` or `cancel` is
+    // called on the handle, or until the handle (and every clone of it) is dropped, in which case it
+    // is saved to the downloads directory. With webkit2gtk it is saved there right away.
+    Started {
+        download: DownloadHandle,
+        url: Url,
+        suggested_filename: Option<String>,
+    },
+    Progress {
+        id: u64,
+        received_bytes: u64,
+        total_bytes: Option<u64>,
+    },
+    Paused {
+        id: u64,
+    },
+    Resumed {
+        id: u64,
+    },
+    Finished {
+        id: u64,
+        destination: Option<PathBuf>,
+    },
+    Failed {
+        id: u64,
+        reason: String,
+    },
+    Cancelled {
+        id: u64,
+    },
+}
+
+impl DownloadEvent {
+    pub fn id(&self) -> u64 {
+        match self {
+            Self::Started { download, .. } => download.id,
+            Self::Progress { id, .. }
+            | Self::Paused { id }
+            | Self::Resumed { id }
+            | Self::Finished { id, .. }
+            | Self::Failed { id, .. }
+            | Self::Cancelled { id } => *id,
+        }
+    }
+
+    fn is_final(&self) -> bool {
+        matches!(
+            self,
+            Self::Finished { .. } | Self::Failed { .. } | Self::Cancelled { .. }
+        )
+    }
+}
+
+#[derive(Debug, Default)]
+pub(crate) enum DownloadDecision {
+    #[default]
+    Default,
+    Destination(PathBuf),
+    Cancel,
+}
+
+// NOTE: what the platforms report, where `Started` carries the native download and the sender for
+// the decision about its destination.
+pub(crate) enum DownloadUpdate {
+    Started {
+        id: u64,
+        download: NativeDownload,
+        url: Url,
+        suggested_filename: Option<String>,
+        decision: oneshot::Sender<DownloadDecision>,
+    },
+    Event(DownloadEvent),
+}
+
+#[derive(Clone)]
+pub struct DownloadHandle {
+    id: u64,
+    window: Window,
+    decision: Arc<Mutex<Option<oneshot::Sender<DownloadDecision>>>>,
+}
+
+impl std::fmt::Debug for DownloadHandle {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DownloadHandle")
+            .field("id", &self.id)
+            .field("window", &self.window.label())
+            .finish()
+    }
+}
+
+impl PartialEq for DownloadHandle {
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id && self.window.label() == other.window.label()
+    }
+}
+
+impl Eq for DownloadHandle {
+}
+
+impl DownloadHandle {
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    // NOTE: not supported with webkit2gtk and WKWebView, which can only cancel downloads.
+    pub async fn pause(&self) -> Result<()> {
+        let window = &self.window;
+        async {
+            platform::pause_download(window, self.native()?).await?;
+            publish(window, DownloadEvent::Paused { id: self.id });
+            BoxResult::Ok(())
+        }
+        .await
+        .window_context(window, "pause download")
+    }
+
+    // NOTE: a download that is waiting for its destination is saved to the downloads directory.
+    pub async fn resume(&self) -> Result<()> {
+        let window = &self.window;
+        async {
+            if self.decide(DownloadDecision::Default)? {
+                return Ok(());
+            }
+            platform::resume_download(window, self.native()?).await?;
+            publish(window, DownloadEvent::Resumed { id: self.id });
+            BoxResult::Ok(())
+        }
+        .await
+        .window_context(window, "resume download")
+    }
+
+    pub async fn cancel(&self) -> Result<()> {
+        let window = &self.window;
+        async {
+            if self.decide(DownloadDecision::Cancel)? {
+                return Ok(());
+            }
+            platform::cancel_download(window, self.native()?).await
+        }
+        .await
+        .window_context(window, "cancel download")
+    }
+
+    // NOTE: the destination is the path of the file, which must be absolute, and can only be set
+    // while the download is waiting for it.
+    pub async fn set_destination(&self, path: impl Into<PathBuf>) -> Result<()> {
+        let path = path.into();
+        let window = &self.window;
+        async {
+            if !path.is_absolute() {
+                let message = format!("`{}` is not an absolute path", path.display());
+                return Err(Error::Other(message.into()).into());
+            }
+            if !self.decide(DownloadDecision::Destination(path))? {
+                return Err(Error::Other("the destination of the download was already decided".into()).into());
+            }
+            BoxResult::Ok(())
+        }
+        .await
+        .window_context(window, "set download destination")
+    }
+
+    // NOTE: returns whether the download was still waiting for a decision.
+    fn decide(&self, decision: DownloadDecision) -> BoxResult<bool> {
+        match self.decision.lock()?.take() {
+            Some(decision_tx) => Ok(decision_tx.send(decision).is_ok()),
+            None => Ok(false),
+        }
+    }
+
+    fn native(&self) -> BoxResult<NativeDownload> {
+        let state = state::window_state(&self.window)?;
+        let downloads = state.downloads.lock()?;
+        let download = downloads
+            .get(&self.id)
+            .cloned()
+            .ok_or_else(|| Error::Other("the download has already ended".into()))?;
+        Ok(download)
+    }
+}
+
+pub(crate) fn next_id() -> u64 {
+    NEXT_ID.fetch_add(1, Ordering::Relaxed)
+}
+
+// NOTE: appends a number to the file name when it is taken, like browsers do for downloads.
+#[cfg(target_os = "macos")]
+pub(crate) fn unique_path(directory: &std::path::Path, filename: &str) -> PathBuf {
+    use std::path::Path;
+
+    let filename = Path::new(filename)
+        .file_name()
+        .map_or_else(|| "download".into(), |name| name.to_string_lossy().into_owned());
+    let path = directory.join(&filename);
+    if !path.exists() {
+        return path;
+    }
+    let stem = Path::new(&filename)
+        .file_stem()
+        .map_or_else(String::new, |stem| stem.to_string_lossy().into_owned());
+    let extension = Path::new(&filename)
+        .extension()
+        .map(|extension| format!(".{}", extension.to_string_lossy()))
+        .unwrap_or_default();
+    (1 ..)
+        .map(|index| directory.join(format!("{stem} ({index}){extension}")))
+        .find(|path| !path.exists())
+        .unwrap_or(path)
+}
+
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn subscribe(window: &Window) -> BoxResult<BoxStream<'static, Result<DownloadEvent>>> {
+    let state = state::window_state(window)?;
+    let (event_tx, event_rx) = stream::channel(stream::stream_config());
+    state.download_subscribers.lock()?.push(event_tx);
+    install(window).await?;
+    Ok(event_rx)
+}
+
+pub(crate) async fn install(window: &Window) -> BoxResult<()> {
+    let state = state::window_state(window)?;
+    if let Some(install) = bridge::install_once(&state, "downloads").await? {
+        platform::add_download_observer(window, {
+            let window = window.clone();
+            move |update| observe(&window, update)
+        })
+        .await?;
+        install.done();
+    }
+    Ok(())
+}
+
+fn observe(window: &Window, update: DownloadUpdate) {
+    let event = match update {
+        DownloadUpdate::Started {
+            id,
+            download,
+            url,
+            suggested_filename,
+            decision,
+        } => {
+            if let Ok(state) = state::window_state(window) {
+                if let Ok(mut downloads) = state.downloads.lock() {
+                    downloads.insert(id, download);
+                }
+            }
+            let download = DownloadHandle {
+                id,
+                window: window.clone(),
+                decision: Arc::new(Mutex::new(Some(decision))),
+            };
+            DownloadEvent::Started {
+                download,
+                url,
+                suggested_filename,
+            }
+        },
+        DownloadUpdate::Event(event) => event,
+    };
+    if event.is_final() {
+        if let Ok(state) = state::window_state(window) {
+            if let Ok(mut downloads) = state.downloads.lock() {
+                downloads.remove(&event.id());
+            }
+        }
+    }
+    publish(window, event);
+}
+
+fn publish(window: &Window, event: DownloadEvent) {
+    if let Ok(state) = state::window_state(window) {
+        if let Ok(mut subscribers) = state.download_subscribers.lock() {
+            subscribers.retain(|subscriber| subscriber.send(event.clone()).is_ok());
+        }
+    }
+    event_bus::publish(window, WebviewEvent::Download(event));
+}
@@ -0,0 +1,124 @@
+use crate::{state, BoxResult, Cookie, DataKinds, DataRecord, StorageArea, WebviewExt};
+use std::{collections::VecDeque, future::Future};
+use tauri::Window;
+use url::Url;
+
+// NOTE: the oldest actions are dropped once this many have been recorded and not taken, so that a
+// window left in dry-run mode does not grow without bound.
+const MAX_ACTIONS: usize = 1024;
+
+/// A mutating operation that was skipped because its window is in dry-run mode.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum DryRunAction {
+    SetCookie(Cookie),
+    DeleteCookie(Cookie),
+    ClearCache,
+    ClearCacheForOrigins(Vec<Url>),
+    ClearData(DataKinds),
+    RemoveDataRecords(Vec<DataRecord>),
+    SetStorageItem {
+        area: StorageArea,
+        origin: Option<Url>,
+        key: String,
+        value: Option<String>,
+    },
+    ClearStorage {
+        area: StorageArea,
+        origin: Option<Url>,
+    },
+}
+
+impl DryRunAction {
+    #[cfg(feature = "tracing")]
+    fn name(&self) -> &'static str {
+        match self {
+            Self::SetCookie(_) => "set cookie",
+            Self::DeleteCookie(_) => "delete cookie",
+            Self::ClearCache => "clear cache",
+            Self::ClearCacheForOrigins(_) => "clear cache for origins",
+            Self::ClearData(_) => "clear data",
+            Self::RemoveDataRecords(_) => "remove data records",
+            Self::SetStorageItem { .. } => "set storage item",
+            Self::ClearStorage { .. } => "clear storage",
+        }
+    }
+}
+
+#[derive(Default)]
+pub(crate) struct DryRunState {
+    enabled: bool,
+    actions: VecDeque<DryRunAction>,
+}
+
+// NOTE: in dry-run mode the operations of this crate that change the data of a window are skipped
+// and recorded instead, and return what they would have returned. Operations that delete cookies
+// return the cookies they would have deleted. Evaluating scripts is not affected, since what a
+// script does can not be known in advance.
+pub(crate) fn set(window: &Window, enabled: bool) -> BoxResult<()> {
+    state::window_state(window)?.dry_run.lock()?.enabled = enabled;
+    Ok(())
+}
+
+pub(crate) fn is_enabled(window: &Window) -> BoxResult<bool> {
+    Ok(state::window_state(window)?.dry_run.lock()?.enabled)
+}
+
+pub(crate) fn take_actions(window: &Window) -> BoxResult<Vec<DryRunAction>> {
+    let state = state::window_state(window)?;
+    let mut dry_run = state.dry_run.lock()?;
+    Ok(dry_run.actions.drain(..).collect())
+}
+
+// NOTE: returns whether the operation should be skipped, in which case its action was recorded.
+pub(crate) fn intercept(window: &Window, action: impl FnOnce() -> DryRunAction) -> BoxResult<bool> {
+    let state = state::window_state(window)?;
+    let mut dry_run = state.dry_run.lock()?;
+    if !dry_run.enabled {
+        return Ok(false);
+    }
+    let action = action();
+    #[cfg(feature = "tracing")]
+    tracing::info!(action = action.name(), details = ?action, "skipped in dry-run mode");
+    if dry_run.actions.len() == MAX_ACTIONS {
+        dry_run.actions.pop_front();
+    }
+    dry_run.actions.push_back(action);
+    Ok(true)
+}
+
+// NOTE: deleting a cookie writes it again with an expiry in the past, so such writes are recorded
+// as deletions.
+pub(crate) fn set_cookie_action(cookie: &Cookie) -> DryRunAction {
+    let expired = !cookie.session
+        && cookie
+            .expires
+            .map_or(false, |expires| expires <= time::OffsetDateTime::now_utc());
+    if expired {
+        DryRunAction::DeleteCookie(cookie.clone())
+    } else {
+        DryRunAction::SetCookie(cookie.clone())
+    }
+}
+
+// NOTE: returns the cookies that would have been deleted when the window is in dry-run mode.
+pub(crate) async fn delete_cookies(window: &Window, url: Option<Url>) -> BoxResult<Option<Vec<Cookie>>> {
+    if !is_enabled(window)? {
+        return Ok(None);
+    }
+    let cookies = window.webview_get_cookies(url).await?;
+    for cookie in &cookies {
+        intercept(window, || DryRunAction::DeleteCookie(cookie.clone()))?;
+    }
+    Ok(Some(cookies))
+}
+
+pub(crate) async fn guard(
+    window: &Window,
+    action: impl FnOnce() -> DryRunAction,
+    operation: impl Future<Output = BoxResult<()>>,
+) -> BoxResult<()> {
+    if intercept(window, action)? {
+        return Ok(());
+    }
+    operation.await
+}
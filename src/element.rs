@@ -0,0 +1,187 @@
+use crate::{bridge, coords, init_script, state, stream, BoxResult, Error, Result};
+use futures::{stream::BoxStream, StreamExt};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    sync::atomic::{AtomicU64, Ordering},
+};
+use tauri::{PhysicalPosition, PhysicalSize, Window};
+
+const SCROLL_TO_SCRIPT: &str = include_str!("js/scroll_to.js");
+const FOCUS_ELEMENT_SCRIPT: &str = include_str!("js/focus_element.js");
+const ELEMENT_BOUNDS_SCRIPT: &str = include_str!("js/element_bounds.js");
+const WATCH_ELEMENT_RECT_SCRIPT: &str = include_str!("js/watch_element_rect.js");
+
+static NEXT_WATCHER: AtomicU64 = AtomicU64::new(1);
+
+// NOTE: the bounds of an element in CSS pixels, relative to the viewport. The webview fills the
+// inner area of its window, so the viewport starts at the inner position of the window.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Rect {
+    pub x: f64,
+    pub y: f64,
+    pub width: f64,
+    pub height: f64,
+    pub device_pixel_ratio: f64,
+}
+
+// NOTE: the device pixel ratio includes the zoom of the webview, so the conversion also holds for
+// zoomed pages. It does not while a device with another ratio is emulated.
+impl Rect {
+    pub fn window_position(&self) -> PhysicalPosition<f64> {
+        PhysicalPosition::new(self.x * self.device_pixel_ratio, self.y * self.device_pixel_ratio)
+    }
+
+    pub fn window_size(&self) -> PhysicalSize<f64> {
+        PhysicalSize::new(
+            self.width * self.device_pixel_ratio,
+            self.height * self.device_pixel_ratio,
+        )
+    }
+}
+
+#[derive(Default)]
+pub(crate) struct ElementRectState {
+    watchers: BTreeMap<u64, (String, stream::Sender<Option<Rect>>)>,
+}
+
+#[derive(Deserialize)]
+struct RectUpdate {
+    id: u64,
+    rect: Option<Rect>,
+}
+
+// NOTE: resolves once the element has stopped moving after being smoothly scrolled to the center of
+// the viewport. Elements are looked up in the main frame.
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn scroll_to(window: &Window, selector: String) -> BoxResult<()> {
+    let selector = serde_json::to_string(&selector)?;
+    let body = format!("return await ({SCROLL_TO_SCRIPT})({selector});");
+    match bridge::call(window, &body).await?.as_bool() {
+        Some(true) => Ok(()),
+        _ => Err(Error::Script(format!("no element matches the selector {selector}")).into()),
+    }
+}
+
+// NOTE: returns whether the element has focus afterwards, which it does not when it is disabled or
+// not focusable, like elements without a `tabindex` that are not controls or links.
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn focus(window: &Window, selector: String) -> BoxResult<bool> {
+    let selector = serde_json::to_string(&selector)?;
+    let body = format!("return ({FOCUS_ELEMENT_SCRIPT})({selector});");
+    bridge::call(window, &body)
+        .await?
+        .as_bool()
+        .ok_or_else(|| Error::Script(format!("no element matches the selector {selector}")).into())
+}
+
+// NOTE: returns `None` when no element matches the selector. Elements are looked up in the main
+// frame.
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn rect(window: &Window, selector: String) -> BoxResult<Option<Rect>> {
+    let selector = serde_json::to_string(&selector)?;
+    let body = format!("return ({ELEMENT_BOUNDS_SCRIPT})({selector});");
+    let rect = serde_json::from_value::<Option<Rect>>(bridge::call(window, &body).await?)?;
+    Ok(match rect {
+        Some(rect) => Some(with_zoom(window, rect).await?),
+        None => None,
+    })
+}
+
+async fn with_zoom(window: &Window, rect: Rect) -> BoxResult<Rect> {
+    let device_pixel_ratio = coords::device_pixel_ratio(window, rect.device_pixel_ratio).await?;
+    Ok(Rect {
+        device_pixel_ratio,
+        ..rect
+    })
+}
+
+// NOTE: the bounds are reported once when watching starts and then whenever the page scrolls,
+// resizes, or changes in a way that moves the element, and again for every new document. `None` is
+// reported while no element matches the selector.
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn watch_rect(
+    window: &Window,
+    selector: String,
+) -> BoxResult<BoxStream<'static, Result<Option<Rect>>>> {
+    let state = state::window_state(window)?;
+    if let Some(install) = bridge::install_once(&state, "element-rect").await? {
+        bridge::on_message(window, "element-rect", {
+            let window = window.clone();
+            move |payload| match serde_json::from_value::<RectUpdate>(payload) {
+                Ok(update) => publish(&window, update),
+                Err(err) => crate::report_error(window.label(), "malformed element rect", err),
+            }
+        })
+        .await?;
+        install.done();
+    }
+    let id = NEXT_WATCHER.fetch_add(1, Ordering::Relaxed);
+    let (rect_tx, rects) = stream::channel(stream::stream_config());
+    state.element_rects.lock()?.watchers.insert(id, (selector, rect_tx));
+    configure(window).await?;
+    let window = window.clone();
+    let rects = rects.then(move |rect| {
+        let window = window.clone();
+        async move {
+            match rect? {
+                Some(rect) => Ok::<_, Error>(Some(with_zoom(&window, rect).await?)),
+                None => Ok(None),
+            }
+        }
+    });
+    Ok(rects.boxed())
+}
+
+async fn configure(window: &Window) -> BoxResult<()> {
+    let state = state::window_state(window)?;
+    let watchers = state
+        .element_rects
+        .lock()?
+        .watchers
+        .iter()
+        .map(|(id, (selector, _))| (id.to_string(), selector.clone()))
+        .collect::<BTreeMap<_, _>>();
+    let watchers = serde_json::to_string(&watchers)?;
+    let source = format!("({WATCH_ELEMENT_RECT_SCRIPT})({watchers});");
+    init_script::replace(window, &state.element_rect_script, source).await
+}
+
+// NOTE: a watcher whose stream was dropped is only noticed at its next update, and is then removed
+// from the page as well.
+fn publish(window: &Window, update: RectUpdate) {
+    let Ok(state) = state::window_state(window) else {
+        return;
+    };
+    let Ok(mut element_rects) = state.element_rects.lock() else {
+        return;
+    };
+    let closed = match element_rects.watchers.get(&update.id) {
+        Some((_, rect_tx)) => rect_tx.send(update.rect).is_err(),
+        None => false,
+    };
+    if closed {
+        element_rects.watchers.remove(&update.id);
+        tauri::async_runtime::spawn({
+            let window = window.clone();
+            async move {
+                if let Err(err) = configure(&window).await {
+                    crate::report_error(window.label(), "failed to stop watching element", err);
+                }
+            }
+        });
+    }
+}
@@ -0,0 +1,92 @@
+use crate::{
+    scheme_handler::{self, SchemeHandler, SchemeRequest, SchemeResponse},
+    serve_directory,
+};
+use futures::{future, FutureExt};
+use rust_embed::RustEmbed;
+use std::sync::Arc;
+use tauri::{
+    http::{MimeType, Request, Response},
+    AppHandle,
+    Runtime,
+};
+use url::Url;
+
+const INDEX: &str = "index.html";
+
+/// Creates a protocol handler that serves the files embedded with `A`, for use with
+/// `tauri::Builder::register_uri_scheme_protocol`.
+///
+/// See [`crate::scheme_protocol`] for when this is needed instead of
+/// [`crate::WebviewExt::webview_serve_embedded`].
+pub fn embedded_protocol<A: RustEmbed + 'static, R: Runtime>(
+) -> impl Fn(&AppHandle<R>, &Request) -> Result<Response, Box<dyn std::error::Error>> + Send + Sync + 'static {
+    move |_app, request| {
+        let header = |name: &str| request.headers().get(name).and_then(|value| value.to_str().ok());
+        scheme_handler::respond(respond::<A>(request.uri(), header))
+    }
+}
+
+pub(crate) fn handler<A: RustEmbed + 'static>() -> SchemeHandler {
+    Arc::new(|request: SchemeRequest| future::ready(respond::<A>(&request.uri, |name| request.header(name))).boxed())
+}
+
+// NOTE: embedded files are tagged with the hash of their content, which is computed when they are
+// embedded, so unchanged files are answered with an empty `304` even across builds of the app.
+fn respond<'a, A: RustEmbed>(uri: &str, header: impl Fn(&str) -> Option<&'a str>) -> SchemeResponse {
+    let Some((path, file)) = resolve::<A>(uri) else {
+        return SchemeResponse::new(404);
+    };
+    let hash = file
+        .metadata
+        .sha256_hash()
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect::<String>();
+    let etag = format!("\"{hash}\"");
+    let response = SchemeResponse::new(200)
+        .header("ETag", etag.clone())
+        .header("Cache-Control", "no-cache");
+    if header("If-None-Match").map_or(false, |tags| serve_directory::etag_matches(tags, &etag)) {
+        return SchemeResponse {
+            status: 304,
+            ..response
+        };
+    }
+    let mime = MimeType::parse(&file.data, &path);
+    let response = response.header("Content-Type", mime).header("Accept-Ranges", "bytes");
+    let len = file.data.len() as u64;
+    let (response, start, length) = match serve_directory::byte_range(len, &etag, header) {
+        Ok(Some((start, length))) => {
+            let response = SchemeResponse {
+                status: 206,
+                ..response
+            };
+            (
+                response.header("Content-Range", serve_directory::content_range(start, length, len)),
+                start,
+                length,
+            )
+        },
+        Ok(None) => (response, 0, len),
+        Err(response) => return response,
+    };
+    let body = &file.data[start as usize .. (start + length) as usize];
+    response.header("Content-Length", body.len().to_string()).body(body)
+}
+
+// NOTE: like with directories, paths that end with `/` and paths of directories are answered with
+// their index file.
+fn resolve<A: RustEmbed>(uri: &str) -> Option<(String, rust_embed::EmbeddedFile)> {
+    let url = Url::parse(uri).ok()?;
+    let path = percent_encoding::percent_decode_str(url.path()).decode_utf8().ok()?;
+    let path = path.trim_start_matches('/');
+    let candidates = if path.is_empty() || path.ends_with('/') {
+        vec![format!("{path}{INDEX}")]
+    } else {
+        vec![path.to_string(), format!("{path}/{INDEX}")]
+    };
+    candidates
+        .into_iter()
+        .find_map(|candidate| A::get(&candidate).map(|file| (candidate, file)))
+}
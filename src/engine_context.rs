@@ -0,0 +1,56 @@
+use crate::{Result, WebviewExt};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use tauri::{AppHandle, Wry};
+
+/// The engine objects the webview of a window runs in, which decide what it shares with the
+/// webviews of other windows.
+///
+/// `context` identifies the process pool (WKWebView), web context (WebKitGTK), or environment
+/// (WebView2) of the webview. Webviews with the same context share their processes, and with
+/// WebKitGTK also their session cookies. `data_store` identifies the website data store
+/// (WKWebView), website data manager (WebKitGTK), or profile (WebView2), and webviews with the same
+/// data store see the same cookies and storage. `data_store` is `None` for a webview whose data
+/// store the engine does not report. The ids are only meant to be compared with each other while
+/// the windows are open.
+///
+/// None of the engines can move a webview to another context once it is created. Tauri gives every
+/// window a web context of its own with WebKitGTK, and windows share an environment with WebView2
+/// and a process pool with WKWebView. The data store of a window can be chosen when it is built with
+/// [`crate::window_builder_ext::WindowBuilderExt::webview_data_store`].
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct EngineContext {
+    pub context: String,
+    pub data_store: Option<String>,
+    pub browser_process_id: Option<u32>,
+}
+
+/// The labels of the windows of an app, grouped by the engine contexts and data stores they share.
+///
+/// Windows in the same group of `data_stores` see the same cookies. Windows whose data store is not
+/// known are left out of `data_stores`.
+#[derive(Clone, Debug, Default, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct SharingTopology {
+    pub contexts: Vec<Vec<String>>,
+    pub data_stores: Vec<Vec<String>>,
+}
+
+/// Reports which windows of the app share engine contexts and data stores.
+pub async fn sharing_topology(app: &AppHandle<Wry>) -> Result<SharingTopology> {
+    let mut contexts = BTreeMap::<_, Vec<_>>::new();
+    let mut data_stores = BTreeMap::<_, Vec<_>>::new();
+    let mut windows = app.windows().into_values().collect::<Vec<_>>();
+    windows.sort_by(|a, b| a.label().cmp(b.label()));
+    for window in windows {
+        let engine_context = window.webview_engine_context().await?;
+        let label = window.label().to_string();
+        contexts.entry(engine_context.context).or_default().push(label.clone());
+        if let Some(data_store) = engine_context.data_store {
+            data_stores.entry(data_store).or_default().push(label);
+        }
+    }
+    Ok(SharingTopology {
+        contexts: contexts.into_values().collect(),
+        data_stores: data_stores.into_values().collect(),
+    })
+}
@@ -0,0 +1,134 @@
+use crate::{BoxError, ScriptRejection};
+use tauri::Window;
+
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+#[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
+pub enum Error {
+    #[error("failed to {operation} for window `{label}`")]
+    Window {
+        label: String,
+        operation: &'static str,
+        #[source]
+        source: Box<Error>,
+    },
+    #[error("platform api call failed: {0}")]
+    PlatformApi(#[source] BoxError),
+    #[error("failed to convert cookie: {0}")]
+    CookieConversion(#[source] BoxError),
+    #[error("failed to build pattern: {0}")]
+    PatternBuild(#[from] regex::Error),
+    #[error("channel closed before a result was received")]
+    ChannelClosed,
+    #[error("operation timed out")]
+    Timeout,
+    #[error("operation was cancelled")]
+    Cancelled,
+    #[error("{0}")]
+    UnsupportedPlatform(&'static str),
+    #[error("script failed: {0}")]
+    Script(String),
+    #[error("script promise was rejected: {0}")]
+    ScriptRejected(ScriptRejection),
+    #[error("script result of {size} bytes exceeds the limit of {limit} bytes")]
+    ResultTooLarge { size: usize, limit: usize },
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error("failed to serialize or deserialize value: {0}")]
+    Serialization(#[from] serde_json::Error),
+    #[error("stream capacity exceeded")]
+    StreamOverflow,
+    #[error("state lock was poisoned")]
+    Poisoned,
+    #[error("the window is read-only")]
+    ReadOnly,
+    #[error("{0} is disabled in safe mode")]
+    SafeMode(&'static str),
+    #[error("cannot navigate to `{target}`: {reason}")]
+    InvalidNavigationTarget { target: String, reason: &'static str },
+    #[error("the data store of the webview is unavailable: {reason}")]
+    StoreUnavailable { reason: &'static str },
+    #[error(transparent)]
+    Other(BoxError),
+}
+
+impl Error {
+    // NOTE: errors of the webview methods are wrapped in `Error::Window` with the label of the window
+    // and the operation, so this is what should be matched on to tell the causes apart.
+    pub fn root(&self) -> &Error {
+        match self {
+            Self::Window { source, .. } => source.root(),
+            error => error,
+        }
+    }
+}
+
+// NOTE: internally errors are still passed around boxed, so the cause is recovered by downcasting
+// to the error types that have a dedicated variant.
+impl From<BoxError> for Error {
+    fn from(error: BoxError) -> Self {
+        let error = match error.downcast::<Error>() {
+            Ok(error) => return *error,
+            Err(error) => error,
+        };
+        if error.is::<oneshot::RecvError>() {
+            return Self::ChannelClosed;
+        }
+        if error.is::<tokio::time::error::Elapsed>() {
+            return Self::Timeout;
+        }
+        let error = match error.downcast::<regex::Error>() {
+            Ok(error) => return Self::PatternBuild(*error),
+            Err(error) => error,
+        };
+        let error = match error.downcast::<std::io::Error>() {
+            Ok(error) => return Self::Io(*error),
+            Err(error) => error,
+        };
+        let error = match error.downcast::<serde_json::Error>() {
+            Ok(error) => return Self::Serialization(*error),
+            Err(error) => error,
+        };
+        if error.is::<tauri::Error>() || error.is::<wry::Error>() {
+            return Self::PlatformApi(error);
+        }
+        Self::Other(error)
+    }
+}
+
+impl From<tauri::Error> for Error {
+    fn from(error: tauri::Error) -> Self {
+        Self::PlatformApi(error.into())
+    }
+}
+
+impl From<wry::Error> for Error {
+    fn from(error: wry::Error) -> Self {
+        Self::PlatformApi(error.into())
+    }
+}
+
+impl From<oneshot::RecvError> for Error {
+    fn from(_: oneshot::RecvError) -> Self {
+        Self::ChannelClosed
+    }
+}
+
+pub(crate) trait WindowContext<T> {
+    fn window_context(self, window: &Window, operation: &'static str) -> Result<T>;
+}
+
+impl<T, E: Into<Error>> WindowContext<T> for std::result::Result<T, E> {
+    fn window_context(self, window: &Window, operation: &'static str) -> Result<T> {
+        self.map_err(|source| {
+            let label = window.label().into();
+            let source = Box::new(source.into());
+            Error::Window {
+                label,
+                operation,
+                source,
+            }
+        })
+    }
+}
@@ -0,0 +1,139 @@
+use crate::{
+    bridge,
+    budget::BudgetExceeded,
+    cookie_watch::{self, CookieChangeEvent},
+    document,
+    downloads::{self, DownloadEvent},
+    error::WindowContext,
+    integrity::IntegrityFailure,
+    load_error::{self, LoadError},
+    navigation_events::{self, NavigationEvent},
+    platform,
+    request_inspection::OutgoingRequest,
+    response_capture::CapturedResponse,
+    route::RouteChange,
+    state,
+    stream::{self, StreamConfig},
+    ApiResult,
+    BoxResult,
+    Result,
+};
+use futures::{future::BoxFuture, prelude::*, stream::BoxStream};
+use std::sync::Arc;
+use tauri::Window;
+use url::Url;
+
+// NOTE: failed loads are reported both as `LoadFailed` and as a failed `Navigation`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum WebviewEvent {
+    BudgetExceeded(BudgetExceeded),
+    CookieChanged(CookieChangeEvent),
+    DocumentReady { url: Url },
+    DocumentUnload,
+    Download(DownloadEvent),
+    IntegrityFailed(IntegrityFailure),
+    LoadFailed(LoadError),
+    Navigation(NavigationEvent),
+    PdfDisplayed { url: Url },
+    PrintRequested { url: Url },
+    // NOTE: the web process of the window exited, unresponsive processes are not reported.
+    ProcessCrashed,
+    RequestSent(OutgoingRequest),
+    ResponseCaptured(CapturedResponse),
+    RouteChanged(RouteChange),
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct LabeledEvent {
+    pub label: String,
+    pub event: WebviewEvent,
+}
+
+#[derive(Clone, Default)]
+pub struct EventBus {
+    config: Option<StreamConfig>,
+    subscribers: ApiResult<Vec<stream::Sender<LabeledEvent>>>,
+}
+
+impl EventBus {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_config(config: StreamConfig) -> Self {
+        Self {
+            config: Some(config),
+            ..Self::default()
+        }
+    }
+
+    #[cfg_attr(feature = "tracing", tracing::instrument(
+        skip(self, window),
+        fields(
+            window.label = window.label(),
+            operation.id = crate::trace::operation_id(),
+            backend = crate::trace::BACKEND
+        )
+    ))]
+    pub fn register<'a>(&'a self, window: &'a Window) -> BoxFuture<'a, Result<()>> {
+        let future = async move {
+            let state = state::window_state(window)?;
+            {
+                let mut buses = state.event_buses.lock()?;
+                if !buses
+                    .iter()
+                    .any(|bus| Arc::ptr_eq(&bus.subscribers.0, &self.subscribers.0))
+                {
+                    buses.push(self.clone());
+                }
+            }
+            document::install(window).await?;
+            load_error::install(window).await?;
+            navigation_events::install(window).await?;
+            downloads::install(window).await?;
+            cookie_watch::install(window).await?;
+            if let Some(install) = bridge::install_once(&state, "process-crash").await? {
+                platform::add_process_crash_handler(window, {
+                    let window = window.clone();
+                    move || publish(&window, WebviewEvent::ProcessCrashed)
+                })
+                .await?;
+                install.done();
+            }
+            Ok(())
+        }
+        .map(move |result: BoxResult<_>| result.window_context(window, "register event bus"));
+        #[cfg(feature = "tracing")]
+        let future = tracing::Instrument::in_current_span(future);
+        future.boxed()
+    }
+
+    pub fn subscribe(&self) -> Result<BoxStream<'static, Result<LabeledEvent>>> {
+        let (event_tx, event_rx) = stream::channel(self.config.unwrap_or_else(stream::stream_config));
+        self.subscribers.lock()?.push(event_tx);
+        Ok(event_rx)
+    }
+
+    fn send(&self, event: &LabeledEvent) {
+        if let Ok(mut subscribers) = self.subscribers.lock() {
+            subscribers.retain(|subscriber| subscriber.send(event.clone()).is_ok());
+        }
+    }
+}
+
+pub(crate) fn publish(window: &Window, event: WebviewEvent) {
+    let buses = match state::window_state(window).and_then(|state| Ok(state.event_buses.lock()?.clone())) {
+        Ok(buses) => buses,
+        Err(err) => return crate::report_error(window.label(), "failed to publish webview event", err),
+    };
+    if !buses.is_empty() {
+        let event = LabeledEvent {
+            label: window.label().into(),
+            event,
+        };
+        for bus in buses {
+            bus.send(&event);
+        }
+    }
+}
@@ -0,0 +1,57 @@
+use crate::{bridge, state, stream, BoxResult, Result};
+use futures::stream::BoxStream;
+use serde::{Deserialize, Serialize};
+use tauri::Window;
+use url::Url;
+
+const EVENT_SOURCE_SCRIPT: &str = include_str!("js/event_source.js");
+
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum EventSourceState {
+    Connecting,
+    Open,
+    Closed,
+}
+
+// NOTE: `id` identifies a connection within the document that opened it, and `reconnects` counts
+// how often the user agent reconnected it after an error.
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct EventSourceEvent {
+    pub id: u64,
+    pub url: Url,
+    pub state: EventSourceState,
+    pub reconnects: u32,
+}
+
+// NOTE: connections are observed by wrapping `EventSource` in the top frame of every document, so
+// connections opened by workers are not observed.
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn events(window: &Window) -> BoxResult<BoxStream<'static, Result<EventSourceEvent>>> {
+    let state = state::window_state(window)?;
+    let (event_tx, event_rx) = stream::channel(stream::stream_config());
+    state.event_source_subscribers.lock()?.push(event_tx);
+    if let Some(install) = bridge::install_once(&state, "event-source").await? {
+        bridge::on_message(window, "event-source", {
+            let window = window.clone();
+            move |payload| {
+                let event = match serde_json::from_value::<EventSourceEvent>(payload) {
+                    Ok(event) => event,
+                    Err(err) => return crate::report_error(window.label(), "malformed event source event", err),
+                };
+                if let Ok(state) = state::window_state(&window) {
+                    if let Ok(mut subscribers) = state.event_source_subscribers.lock() {
+                        subscribers.retain(|subscriber| subscriber.send(event.clone()).is_ok());
+                    }
+                }
+            }
+        })
+        .await?;
+        bridge::add_script(window, EVENT_SOURCE_SCRIPT).await?;
+        install.done();
+    }
+    Ok(event_rx)
+}
@@ -0,0 +1,46 @@
+use crate::{bridge, BoxResult};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use serde::{Deserialize, Serialize};
+use tauri::Window;
+use url::Url;
+
+#[derive(Clone, Debug, Default, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct FetchedResource {
+    pub bytes: Vec<u8>,
+    pub mime: Option<String>,
+    pub headers: Vec<(String, String)>,
+}
+
+// NOTE: the resource is fetched by the current document with its credentials, so the webview's
+// cookies and HTTP cache are used, but cross-origin resources are only readable if they are served
+// with CORS headers. Headers the document may not read (like `Set-Cookie`) are not returned.
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn fetch_with_session(window: &Window, url: Url) -> BoxResult<FetchedResource> {
+    let url = serde_json::to_string(url.as_str())?;
+    let body = format!(
+        r#"
+        const response = await fetch({url}, {{ credentials: "include" }});
+        if (!response.ok) {{
+            throw new Error(`server responded with HTTP status ${{response.status}}`);
+        }}
+        const headers = [...response.headers];
+        const blob = await response.blob();
+        const data = await new Promise((resolve, reject) => {{
+            const reader = new FileReader();
+            reader.onload = () => resolve(reader.result.slice(reader.result.indexOf(",") + 1));
+            reader.onerror = () => reject(reader.error);
+            reader.readAsDataURL(blob);
+        }});
+        return {{ data, mime: blob.type || null, headers }};
+        "#
+    );
+    let result = bridge::call(window, &body).await?;
+    let data = result["data"].as_str().ok_or("resource was not returned as a string")?;
+    let bytes = STANDARD.decode(data)?;
+    let mime = result["mime"].as_str().map(Into::into);
+    let headers = serde_json::from_value(result["headers"].clone())?;
+    Ok(FetchedResource { bytes, mime, headers })
+}
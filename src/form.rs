@@ -0,0 +1,42 @@
+use crate::{bridge, BoxResult};
+use serde::{Deserialize, Serialize};
+use tauri::Window;
+
+const FILL_FORM_SCRIPT: &str = include_str!("js/fill_form.js");
+
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct FieldFill {
+    pub selector: String,
+    // NOTE: checkboxes and radio buttons are checked when the value is "true" or their own value.
+    pub value: String,
+    // NOTE: dispatched in order after the value is set, "input" then "change" by default.
+    pub events: Vec<String>,
+}
+
+impl FieldFill {
+    pub fn new(selector: impl Into<String>, value: impl Into<String>) -> Self {
+        let selector = selector.into();
+        let value = value.into();
+        let events = vec!["input".into(), "change".into()];
+        Self {
+            selector,
+            value,
+            events,
+        }
+    }
+}
+
+// NOTE: returns the number of fields that were filled. Fields are looked up in the main frame only,
+// and fields whose selector matches nothing are skipped.
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window, fields),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn fill(window: &Window, fields: Vec<FieldFill>) -> BoxResult<u64> {
+    let fields = serde_json::to_string(&fields)?;
+    let body = format!("return ({FILL_FORM_SCRIPT})({fields});");
+    let filled = bridge::call(window, &body).await?;
+    filled
+        .as_u64()
+        .ok_or_else(|| "unexpected reply when filling form".into())
+}
@@ -0,0 +1,48 @@
+use crate::{bridge, BoxResult};
+use serde::{Deserialize, Serialize};
+use tauri::Window;
+use url::Url;
+
+const FRAMES_SCRIPT: &str = include_str!("js/frames.js");
+
+// NOTE: the frames are listed depth-first in document order, starting with the main frame at a
+// depth of 0. The frames inside of a cross-origin frame can not be seen from the main frame, so they
+// are missing, and the URL of a cross-origin frame is the one it was loaded with. The cookies that
+// a frame can see are those of `webview_get_cookies` for its URL.
+#[serde(rename_all = "camelCase")]
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+pub struct FrameInfo {
+    pub url: Option<Url>,
+    pub name: Option<String>,
+    pub depth: u32,
+    pub cross_origin: bool,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct FrameEntry {
+    url: Option<String>,
+    name: Option<String>,
+    depth: u32,
+    cross_origin: bool,
+}
+
+// NOTE: the frames are read from the document of the main frame. The frame events of the platforms
+// only report frames as they are created, and not the frames that a page already has.
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn list(window: &Window) -> BoxResult<Vec<FrameInfo>> {
+    let body = format!("return ({FRAMES_SCRIPT})();");
+    let entries = serde_json::from_value::<Vec<FrameEntry>>(bridge::call(window, &body).await?)?;
+    Ok(entries
+        .into_iter()
+        .map(|entry| FrameInfo {
+            url: entry.url.and_then(|url| Url::parse(&url).ok()),
+            name: entry.name,
+            depth: entry.depth,
+            cross_origin: entry.cross_origin,
+        })
+        .collect())
+}
@@ -0,0 +1,77 @@
+// NOTE: globs match whole URLs, where `*` matches any sequence of characters and everything else
+// matches literally.
+pub(crate) fn matches(glob: &str, text: &str) -> bool {
+    let mut parts = glob.split('*');
+    let mut rest = match text.strip_prefix(parts.next().unwrap_or_default()) {
+        Some(rest) => rest,
+        None => return false,
+    };
+    let parts = parts.collect::<Vec<_>>();
+    match parts.split_last() {
+        None => rest.is_empty(),
+        Some((last, middle)) => {
+            for part in middle {
+                match rest.find(part) {
+                    Some(index) => rest = &rest[index + part.len() ..],
+                    None => return false,
+                }
+            }
+            rest.ends_with(last)
+        },
+    }
+}
+
+// NOTE: the regular expressions of WebKit content blockers only support a subset of the usual
+// syntax, which is still enough for globs.
+#[cfg(not(target_os = "windows"))]
+pub(crate) fn to_regex(glob: &str) -> String {
+    let pattern = glob
+        .split('*')
+        .map(|literal| {
+            let mut escaped = String::with_capacity(literal.len());
+            for char in literal.chars() {
+                if "\\^$.|?+()[]{}".contains(char) {
+                    escaped.push('\\');
+                }
+                escaped.push(char);
+            }
+            escaped
+        })
+        .collect::<Vec<_>>()
+        .join(".*");
+    format!("^{pattern}$")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn globs_match_whole_text() {
+        assert!(matches("https://example.com/", "https://example.com/"));
+        assert!(!matches("https://example.com", "https://example.com/"));
+        assert!(matches("https://*.example.com/*", "https://a.b.example.com/path"));
+        assert!(!matches("https://*.example.com/*", "https://example.com/path"));
+        assert!(matches("*", ""));
+        assert!(matches("a*b*c", "abc"));
+        assert!(!matches("a*bc*c", "abc"));
+    }
+
+    #[test]
+    fn only_stars_are_wildcards() {
+        assert!(matches("a?b.c", "a?b.c"));
+        assert!(!matches("a?b.c", "axbxc"));
+        assert!(!matches("[ab]", "a"));
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    #[test]
+    fn regexes_escape_everything_but_stars() {
+        let regex = to_regex("https://*.example.com/a?b=(c)|[d]");
+        assert_eq!(regex, r"^https://.*\.example\.com/a\?b=\(c\)\|\[d\]$");
+        let regex = regex::Regex::new(&regex).unwrap();
+        assert!(regex.is_match("https://www.example.com/a?b=(c)|[d]"));
+        assert!(!regex.is_match("https://www.example.com/ab=c"));
+        assert!(!regex.is_match("https://wwwxexample.com/a?b=(c)|[d]"));
+    }
+}
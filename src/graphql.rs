@@ -0,0 +1,169 @@
+use crate::{ChangeOrigin, Cookie, CookieChange, CookieChangeEvent, CookiePattern, WebviewExt};
+use async_graphql::{Enum, InputObject, Object, SimpleObject, Subscription};
+use futures::{Stream, StreamExt};
+use regex::Regex;
+use tauri::Window;
+
+/// A pattern for the cookies that a field applies to, where every predicate that is set has to
+/// match, like for [`CookiePattern`].
+#[derive(Clone, Debug, Default, InputObject)]
+pub struct CookiePatternInput {
+    pub domain: Option<String>,
+    pub name: Option<String>,
+    pub names: Option<Vec<String>>,
+    pub name_regex: Option<String>,
+    pub path_prefix: Option<String>,
+    pub hosts: Option<Vec<String>>,
+    #[graphql(default)]
+    pub include_subdomains: bool,
+    pub schemes: Option<Vec<String>>,
+    #[graphql(default)]
+    pub expired_only: bool,
+}
+
+impl TryFrom<CookiePatternInput> for CookiePattern {
+    type Error = crate::Error;
+
+    fn try_from(input: CookiePatternInput) -> Result<Self, Self::Error> {
+        let mut builder = CookiePattern::builder();
+        if let Some(domain) = input.domain {
+            builder = builder.match_domain(domain);
+        }
+        if let Some(name) = input.name {
+            builder = builder.match_name(name);
+        }
+        if let Some(names) = input.names {
+            builder = builder.match_names(names);
+        }
+        if let Some(name_regex) = input.name_regex {
+            builder = builder.match_name_regex(Regex::new(&name_regex)?);
+        }
+        if let Some(prefix) = input.path_prefix {
+            builder = builder.match_path_prefix(prefix);
+        }
+        if let Some(hosts) = input.hosts {
+            builder = builder.match_hosts(hosts, input.include_subdomains);
+        }
+        if let Some(schemes) = input.schemes {
+            builder = builder.match_schemes(schemes);
+        }
+        if input.expired_only {
+            builder = builder.expired_only();
+        }
+        Ok(builder.build())
+    }
+}
+
+#[derive(Clone, Copy, Debug, Enum, Eq, PartialEq)]
+pub enum CookieChangeKind {
+    Added,
+    Deleted,
+    Updated,
+}
+
+// NOTE: the flattened form of `CookieChangeEvent`, since GraphQL unions can not hold the same type
+// in more than one variant. `previous` is only set for updates.
+#[derive(Clone, Debug, SimpleObject)]
+pub struct CookieChangePayload {
+    pub kind: CookieChangeKind,
+    pub origin: ChangeOrigin,
+    pub cookie: Cookie,
+    pub previous: Option<Cookie>,
+}
+
+impl From<CookieChangeEvent> for CookieChangePayload {
+    fn from(event: CookieChangeEvent) -> Self {
+        let origin = event.origin;
+        match event.change {
+            CookieChange::Added(cookie) => Self {
+                kind: CookieChangeKind::Added,
+                origin,
+                cookie,
+                previous: None,
+            },
+            CookieChange::Deleted(cookie) => Self {
+                kind: CookieChangeKind::Deleted,
+                origin,
+                cookie,
+                previous: None,
+            },
+            CookieChange::Updated { previous, current } => Self {
+                kind: CookieChangeKind::Updated,
+                origin,
+                cookie: current,
+                previous: Some(previous),
+            },
+        }
+    }
+}
+
+fn pattern(input: Option<CookiePatternInput>) -> async_graphql::Result<CookiePattern> {
+    Ok(input.unwrap_or_default().try_into()?)
+}
+
+/// The `cookies` query for the webview of a window, to be merged into the query root of a schema
+/// with `MergedObject`.
+pub struct CookieQuery {
+    window: Window,
+}
+
+impl CookieQuery {
+    pub fn new(window: Window) -> Self {
+        Self { window }
+    }
+}
+
+#[Object]
+impl CookieQuery {
+    /// The cookies of the webview that match the pattern, or all of them without one.
+    async fn cookies(&self, pattern: Option<CookiePatternInput>) -> async_graphql::Result<Vec<Cookie>> {
+        let pattern = self::pattern(pattern)?;
+        Ok(self.window.webview_collect_cookies(pattern).await?)
+    }
+}
+
+/// The `deleteCookies` mutation for the webview of a window, to be merged into the mutation root of
+/// a schema with `MergedObject`.
+pub struct CookieMutation {
+    window: Window,
+}
+
+impl CookieMutation {
+    pub fn new(window: Window) -> Self {
+        Self { window }
+    }
+}
+
+#[Object]
+impl CookieMutation {
+    /// Deletes the cookies of the webview that match the pattern and returns them.
+    async fn delete_cookies(&self, pattern: CookiePatternInput) -> async_graphql::Result<Vec<Cookie>> {
+        let pattern = self::pattern(Some(pattern))?;
+        Ok(self.window.webview_delete_matching_cookies(pattern).await?)
+    }
+}
+
+/// The `cookieChanges` subscription for the webview of a window, to be merged into the subscription
+/// root of a schema with `MergedSubscription`.
+pub struct CookieSubscription {
+    window: Window,
+}
+
+impl CookieSubscription {
+    pub fn new(window: Window) -> Self {
+        Self { window }
+    }
+}
+
+#[Subscription]
+impl CookieSubscription {
+    /// The changes to the cookies of the webview that match the pattern.
+    async fn cookie_changes(
+        &self,
+        pattern: Option<CookiePatternInput>,
+    ) -> async_graphql::Result<impl Stream<Item = async_graphql::Result<CookieChangePayload>>> {
+        let pattern = self::pattern(pattern)?;
+        let changes = self.window.webview_watch_cookies(pattern).await?;
+        Ok(changes.map(|change| Ok(change?.into())))
+    }
+}
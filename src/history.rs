@@ -0,0 +1,11 @@
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+// NOTE: entries are listed from the oldest to the newest, and exactly one of them is the current
+// entry, unless the webview has not navigated yet.
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct HistoryEntry {
+    pub url: Url,
+    pub title: Option<String>,
+    pub current: bool,
+}
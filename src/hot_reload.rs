@@ -0,0 +1,68 @@
+use crate::{bridge, state, BoxResult};
+use notify::{RecursiveMode, Watcher};
+use std::{path::PathBuf, sync::mpsc, time::Duration};
+use tauri::Window;
+
+const SCROLL_RESTORE_SCRIPT: &str = include_str!("js/scroll_restore.js");
+
+const RELOAD_PRESERVING_SCROLL: &str = r#"
+sessionStorage.setItem("__tauri_webview_util_scroll__", JSON.stringify([window.scrollX, window.scrollY]));
+location.reload();
+"#;
+
+// NOTE: the watcher is kept in the window state, so watching stops when the window is destroyed or
+// when this is called again (with no paths to stop watching entirely). Changes are debounced on a
+// thread of their own, which exits once the watcher is dropped.
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn watch(
+    window: &Window,
+    paths: Vec<PathBuf>,
+    debounce: Duration,
+    preserve_scroll: bool,
+) -> BoxResult<()> {
+    let state = state::window_state(window)?;
+    state.reload_watcher.lock()?.take();
+    if paths.is_empty() {
+        return Ok(());
+    }
+    if preserve_scroll {
+        if let Some(install) = bridge::install_once(&state, "scroll-restore").await? {
+            bridge::add_script(window, SCROLL_RESTORE_SCRIPT).await?;
+            install.done();
+        }
+    }
+    let (event_tx, event_rx) = mpsc::channel();
+    let mut watcher = notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+        if event.map_or(false, |event| !event.kind.is_access()) {
+            event_tx.send(()).ok();
+        }
+    })?;
+    for path in &paths {
+        watcher.watch(path, RecursiveMode::Recursive)?;
+    }
+    *state.reload_watcher.lock()? = Some(watcher);
+    let window = window.clone();
+    std::thread::spawn(move || {
+        while event_rx.recv().is_ok() {
+            loop {
+                match event_rx.recv_timeout(debounce) {
+                    Ok(()) => continue,
+                    Err(mpsc::RecvTimeoutError::Timeout) => break,
+                    Err(mpsc::RecvTimeoutError::Disconnected) => return,
+                }
+            }
+            let script = if preserve_scroll {
+                RELOAD_PRESERVING_SCROLL
+            } else {
+                "location.reload();"
+            };
+            if let Err(err) = window.eval(script) {
+                crate::report_error(window.label(), "failed to reload webview", err);
+            }
+        }
+    });
+    Ok(())
+}
@@ -0,0 +1,66 @@
+use crate::{platform, state, ApiResult, BoxResult, Error, ScriptScope};
+use tauri::Window;
+
+pub(crate) struct InitScript {
+    handle: platform::UserScriptHandle,
+}
+
+// NOTE: persistent init scripts are added as native user scripts, so they run at document start in
+// every document loaded after they are set, but not in the current one.
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window, source),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn set(window: &Window, id: &str, source: &str, scope: ScriptScope) -> BoxResult<()> {
+    let source = scope.wrap(source)?;
+    remove(window, id).await?;
+    let handle = platform::add_init_script(window, source, !scope.main_frame_only).await?;
+    let state = state::window_state(window)?;
+    state.init_scripts.lock()?.insert(id.into(), InitScript { handle });
+    Ok(())
+}
+
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn remove(window: &Window, id: &str) -> BoxResult<bool> {
+    let state = state::window_state(window)?;
+    let script = state.init_scripts.lock()?.remove(id);
+    match script {
+        Some(script) => {
+            platform::remove_init_script(window, script.handle).await?;
+            Ok(true)
+        },
+        None => Ok(false),
+    }
+}
+
+// NOTE: replaces an internal script of the crate that is kept in `slot` rather than by id. The
+// script runs in the top frame of every new document, and is evaluated in the current one as well.
+// Where user scripts can not be removed (webkit2gtk without the `webkit2gtk-v2_32` feature) the
+// previous script is kept, and is overridden by the new one, which runs after it.
+pub(crate) async fn replace(
+    window: &Window,
+    slot: &ApiResult<Option<platform::UserScriptHandle>>,
+    source: String,
+) -> BoxResult<()> {
+    let previous = slot.lock()?.take();
+    if let Some(handle) = previous {
+        match platform::remove_init_script(window, handle).await {
+            Ok(()) => {},
+            Err(err) if matches!(err.downcast_ref::<Error>(), Some(Error::UnsupportedPlatform(_))) => {},
+            Err(err) => return Err(err),
+        }
+    }
+    let handle = platform::add_init_script(window, source.clone(), false).await?;
+    *slot.lock()? = Some(handle);
+    window.eval(&source)?;
+    Ok(())
+}
+
+pub(crate) fn list(window: &Window) -> BoxResult<Vec<String>> {
+    let state = state::window_state(window)?;
+    let ids = state.init_scripts.lock()?.keys().cloned().collect();
+    Ok(ids)
+}
@@ -0,0 +1,54 @@
+use crate::{bridge, event_bus, event_bus::WebviewEvent, platform, state, BoxResult};
+use serde::{Deserialize, Serialize};
+use tauri::Window;
+
+const INTEGRITY_SCRIPT: &str = include_str!("js/integrity.js");
+
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum IntegrityFailureKind {
+    Subresource,
+    CertificateTransparency,
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct IntegrityFailure {
+    pub kind: IntegrityFailureKind,
+    pub url: Option<String>,
+    // NOTE: the `integrity` attribute of the element for subresource integrity failures.
+    pub integrity: Option<String>,
+}
+
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn install(window: &Window) -> BoxResult<()> {
+    let state = state::window_state(window)?;
+    let Some(install) = bridge::install_once(&state, "integrity").await? else {
+        return Ok(());
+    };
+    bridge::on_message(window, "integrity-failure", {
+        let window = window.clone();
+        move |payload| match serde_json::from_value::<IntegrityFailure>(payload) {
+            Ok(failure) => event_bus::publish(&window, WebviewEvent::IntegrityFailed(failure)),
+            Err(err) => crate::report_error(window.label(), "malformed integrity failure", err),
+        }
+    })
+    .await?;
+    bridge::add_script(window, INTEGRITY_SCRIPT).await?;
+    platform::add_certificate_transparency_handler(window, {
+        let window = window.clone();
+        move |url| {
+            let failure = IntegrityFailure {
+                kind: IntegrityFailureKind::CertificateTransparency,
+                url,
+                integrity: None,
+            };
+            event_bus::publish(&window, WebviewEvent::IntegrityFailed(failure));
+        }
+    })
+    .await?;
+    install.done();
+    Ok(())
+}
@@ -0,0 +1,28 @@
+(function () {
+  const bridge = window.__TAURI_WEBVIEW_UTIL__;
+  if (!bridge || navigator.setAppBadge && navigator.setAppBadge.__tauriWebviewUtil) {
+    return;
+  }
+
+  const setAppBadge = function (contents) {
+    if (contents === undefined) {
+      bridge.post("badge", { count: null });
+      return Promise.resolve();
+    }
+    const count = Number(contents);
+    if (!Number.isFinite(count) || count < 0) {
+      return Promise.reject(new TypeError("badge contents must be a non-negative number"));
+    }
+    bridge.post("badge", { count: Math.floor(count) });
+    return Promise.resolve();
+  };
+
+  const clearAppBadge = function () {
+    bridge.post("badge", { count: 0 });
+    return Promise.resolve();
+  };
+
+  Object.defineProperty(setAppBadge, "__tauriWebviewUtil", { value: true });
+  navigator.setAppBadge = setAppBadge;
+  navigator.clearAppBadge = clearAppBadge;
+})();
@@ -0,0 +1,38 @@
+(function () {
+  if (window.__TAURI_WEBVIEW_UTIL_BANDWIDTH__ || typeof PerformanceObserver === "undefined") {
+    return;
+  }
+
+  let stats = {};
+  const record = (entry) => {
+    let origin;
+    try {
+      origin = new URL(entry.name).origin;
+    } catch (_) {
+      return;
+    }
+    const traffic = (stats[origin] = stats[origin] || {
+      requests: 0,
+      transferredBytes: 0,
+      encodedBodyBytes: 0,
+      decodedBodyBytes: 0,
+    });
+    traffic.requests += 1;
+    traffic.transferredBytes += entry.transferSize || 0;
+    traffic.encodedBodyBytes += entry.encodedBodySize || 0;
+    traffic.decodedBodyBytes += entry.decodedBodySize || 0;
+  };
+
+  for (const type of ["navigation", "resource"]) {
+    new PerformanceObserver((list) => list.getEntries().forEach(record)).observe({ type, buffered: true });
+  }
+
+  Object.defineProperty(window, "__TAURI_WEBVIEW_UTIL_BANDWIDTH__", {
+    value: Object.freeze({
+      stats: () => stats,
+      reset: () => {
+        stats = {};
+      },
+    }),
+  });
+})();
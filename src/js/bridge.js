@@ -0,0 +1,219 @@
+(function (token) {
+  if (window.__TAURI_WEBVIEW_UTIL__) {
+    return;
+  }
+
+  const listeners = new Map();
+  const requests = new Map();
+  let nextRequest = 0;
+
+  const send =
+    window.chrome && window.chrome.webview
+      ? (message) => window.chrome.webview.postMessage({ tauriWebviewUtil: message })
+      : (message) => window.webkit.messageHandlers.tauriWebviewUtil.postMessage(message);
+
+  // NOTE: structured clone values are sent as JSON, with the values that have no JSON form tagged by
+  // `$type`. The decoder in `structured_clone.rs` must be kept in sync.
+  const typedArrays = {
+    Int8Array,
+    Uint8Array,
+    Uint8ClampedArray,
+    Int16Array,
+    Uint16Array,
+    Int32Array,
+    Uint32Array,
+    Float32Array,
+    Float64Array,
+    BigInt64Array,
+    BigUint64Array,
+    DataView,
+  };
+  const toBase64 = (bytes) => {
+    let binary = "";
+    for (let index = 0; index < bytes.length; index += 0x8000) {
+      binary += String.fromCharCode.apply(null, bytes.subarray(index, index + 0x8000));
+    }
+    return btoa(binary);
+  };
+  const fromBase64 = (base64) => {
+    const binary = atob(base64);
+    const bytes = new Uint8Array(binary.length);
+    for (let index = 0; index < binary.length; index += 1) {
+      bytes[index] = binary.charCodeAt(index);
+    }
+    return bytes.buffer;
+  };
+  const encode = (value, seen = new Set()) => {
+    switch (typeof value) {
+      case "undefined":
+        return { $type: "undefined" };
+      case "number":
+        if (Number.isFinite(value) && !Object.is(value, -0)) {
+          return value;
+        }
+        return { $type: "number", value: Object.is(value, -0) ? "-0" : String(value) };
+      case "bigint":
+        return { $type: "bigint", value: value.toString() };
+      case "boolean":
+      case "string":
+        return value;
+      case "function":
+      case "symbol":
+        throw new TypeError(`${typeof value} values can not be cloned`);
+    }
+    if (value === null) {
+      return null;
+    }
+    if (seen.has(value)) {
+      throw new TypeError("cyclic values can not be cloned");
+    }
+    seen.add(value);
+    try {
+      const tag = Object.prototype.toString.call(value).slice(8, -1);
+      if (value instanceof Date) {
+        return { $type: "date", value: Number.isNaN(value.getTime()) ? null : value.getTime() };
+      }
+      if (value instanceof RegExp) {
+        return { $type: "regexp", source: value.source, flags: value.flags };
+      }
+      if (value instanceof ArrayBuffer) {
+        return { $type: "array-buffer", value: toBase64(new Uint8Array(value)) };
+      }
+      if (ArrayBuffer.isView(value) && tag in typedArrays) {
+        const bytes = new Uint8Array(value.buffer, value.byteOffset, value.byteLength);
+        return { $type: "typed-array", kind: tag, value: toBase64(bytes) };
+      }
+      if (value instanceof Map) {
+        return { $type: "map", value: Array.from(value, ([key, item]) => [encode(key, seen), encode(item, seen)]) };
+      }
+      if (value instanceof Set) {
+        return { $type: "set", value: Array.from(value, (item) => encode(item, seen)) };
+      }
+      if (Array.isArray(value)) {
+        return Array.from(value, (item) => encode(item, seen));
+      }
+      const object = {};
+      for (const [key, item] of Object.entries(value)) {
+        object[key] = encode(item, seen);
+      }
+      return Object.prototype.hasOwnProperty.call(object, "$type") ? { $type: "object", value: object } : object;
+    } finally {
+      seen.delete(value);
+    }
+  };
+  const decode = (value) => {
+    if (Array.isArray(value)) {
+      return value.map(decode);
+    }
+    if (value === null || typeof value !== "object") {
+      return value;
+    }
+    const entries = (object) => Object.fromEntries(Object.entries(object).map(([key, item]) => [key, decode(item)]));
+    switch (value.$type) {
+      case undefined:
+        return entries(value);
+      case "undefined":
+        return undefined;
+      case "number":
+        return value.value === "-0" ? -0 : Number(value.value);
+      case "bigint":
+        return BigInt(value.value);
+      case "date":
+        return new Date(value.value === null ? NaN : value.value);
+      case "regexp":
+        return new RegExp(value.source, value.flags);
+      case "array-buffer":
+        return fromBase64(value.value);
+      case "typed-array": {
+        const buffer = fromBase64(value.value);
+        const View = typedArrays[value.kind];
+        return View === DataView ? new DataView(buffer) : new View(buffer);
+      }
+      case "object":
+        return entries(value.value);
+      case "map":
+        return new Map(value.value.map(([key, item]) => [decode(key), decode(item)]));
+      case "set":
+        return new Set(value.value.map(decode));
+      default:
+        throw new TypeError(`unknown structured clone type \`${value.$type}\``);
+    }
+  };
+
+  Object.defineProperty(window, "__TAURI_WEBVIEW_UTIL__", {
+    value: Object.freeze({
+      post(name, payload) {
+        send(JSON.stringify({ token, name, payload: payload === undefined ? null : payload }));
+      },
+      on(name, listener) {
+        if (!listeners.has(name)) {
+          listeners.set(name, new Set());
+        }
+        listeners.get(name).add(listener);
+        return () => listeners.get(name).delete(listener);
+      },
+      reply(id, run) {
+        Promise.resolve()
+          .then(run)
+          .then(
+            (value) => this.post("reply", { id, value: value === undefined ? null : value }),
+            (error) => this.post("reply", { id, error: String(error) }),
+          );
+      },
+      stream(id, run, chunkSize, maxBytes = null) {
+        Promise.resolve()
+          .then(run)
+          .then((value) => {
+            const json = JSON.stringify(value === undefined ? null : value) ?? "null";
+            const size = new TextEncoder().encode(json).length;
+            if (maxBytes !== null && size > maxBytes) {
+              this.post("result-chunk", { id, tooLarge: { size, limit: maxBytes } });
+              return;
+            }
+            let start = 0;
+            while (start < json.length) {
+              let end = Math.min(start + chunkSize, json.length);
+              // NOTE: surrogate pairs are kept in one chunk, since each chunk is decoded on its own.
+              const last = json.charCodeAt(end - 1);
+              if (end < json.length && end - 1 > start && last >= 0xd800 && last <= 0xdbff) {
+                end -= 1;
+              }
+              this.post("result-chunk", { id, data: json.slice(start, end) });
+              start = end;
+            }
+            this.post("result-chunk", { id, done: true });
+          })
+          .catch((error) => this.post("result-chunk", { id, error: String(error) }));
+      },
+      request(name, payload, options = {}) {
+        const id = ++nextRequest;
+        return new Promise((resolve, reject) => {
+          const body = options.clone ? encode(payload) : payload;
+          requests.set(id, { resolve, reject });
+          this.post("request", { id, name, payload: body });
+        });
+      },
+      settle(id, value, error, clone = false) {
+        const request = requests.get(id);
+        if (!request) {
+          return;
+        }
+        requests.delete(id);
+        if (error === null) {
+          try {
+            request.resolve(clone ? decode(value) : value);
+          } catch (error) {
+            request.reject(error);
+          }
+        } else {
+          request.reject(new Error(error));
+        }
+      },
+      receive(name, payload) {
+        for (const listener of listeners.get(name) || []) {
+          listener(payload);
+        }
+      },
+    }),
+  });
+})
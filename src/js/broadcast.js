@@ -0,0 +1,43 @@
+(function (config) {
+  const bridge = window.__TAURI_WEBVIEW_UTIL__;
+  if (!bridge || typeof BroadcastChannel === "undefined") {
+    return;
+  }
+
+  if (!window.__TAURI_WEBVIEW_UTIL_BROADCAST__) {
+    let names = new Set();
+    const senders = new Map();
+    const NativeBroadcastChannel = window.BroadcastChannel;
+    // NOTE: a channel never receives its own messages, so messages the page posts are reported when
+    // they are posted instead of with a listening channel, which would also receive the messages
+    // posted from native code.
+    class BroadcastChannel extends NativeBroadcastChannel {
+      postMessage(message) {
+        super.postMessage(message);
+        if (names.has(this.name)) {
+          try {
+            bridge.post("broadcast", { name: this.name, data: message });
+          } catch (_) {}
+        }
+      }
+    }
+    Object.defineProperty(window, "BroadcastChannel", { value: BroadcastChannel, writable: true, configurable: true });
+
+    Object.defineProperty(window, "__TAURI_WEBVIEW_UTIL_BROADCAST__", {
+      value: Object.freeze({
+        configure(config) {
+          names = new Set(config.names);
+        },
+        post(name, data) {
+          let sender = senders.get(name);
+          if (!sender) {
+            sender = new NativeBroadcastChannel(name);
+            senders.set(name, sender);
+          }
+          sender.postMessage(data);
+        },
+      }),
+    });
+  }
+  window.__TAURI_WEBVIEW_UTIL_BROADCAST__.configure(config);
+})
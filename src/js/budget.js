@@ -0,0 +1,54 @@
+(function (budget) {
+  const bridge = window.__TAURI_WEBVIEW_UTIL__;
+  if (!bridge || typeof PerformanceObserver === "undefined") {
+    return;
+  }
+
+  if (!window.__TAURI_WEBVIEW_UTIL_BUDGET__) {
+    let current = { maxBytes: null, maxRequests: null, block: false };
+    let bytes = 0;
+    let requests = 0;
+    let exceeded = false;
+
+    const block = () => {
+      window.stop();
+      const reject = () => Promise.reject(new TypeError("page budget exceeded"));
+      window.fetch = reject;
+      XMLHttpRequest.prototype.send = function () {
+        this.dispatchEvent(new ProgressEvent("error"));
+      };
+    };
+    const check = () => {
+      const overBytes = current.maxBytes !== null && bytes > current.maxBytes;
+      const overRequests = current.maxRequests !== null && requests > current.maxRequests;
+      if (exceeded || !(overBytes || overRequests)) {
+        return;
+      }
+      exceeded = true;
+      if (current.block) {
+        block();
+      }
+      bridge.post("budget-exceeded", { url: location.href, bytes, requests });
+    };
+
+    for (const type of ["navigation", "resource"]) {
+      new PerformanceObserver((list) => {
+        for (const entry of list.getEntries()) {
+          requests += 1;
+          bytes += entry.transferSize || entry.encodedBodySize || 0;
+        }
+        check();
+      }).observe({ type, buffered: true });
+    }
+
+    Object.defineProperty(window, "__TAURI_WEBVIEW_UTIL_BUDGET__", {
+      value: Object.freeze({
+        configure(budget) {
+          current = budget;
+          check();
+        },
+      }),
+    });
+  }
+  window.__TAURI_WEBVIEW_UTIL_BUDGET__.configure(budget);
+})
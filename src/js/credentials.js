@@ -0,0 +1,51 @@
+(function (origins) {
+  const bridge = window.__TAURI_WEBVIEW_UTIL__;
+  if (!bridge) {
+    return;
+  }
+
+  if (!window.__TAURI_WEBVIEW_UTIL_CREDENTIALS__) {
+    let allowed = [];
+
+    const username = (form, password) => {
+      const candidates = Array.from(form.querySelectorAll("input")).filter(
+        (input) =>
+          input.autocomplete === "username" ||
+          input.type === "email" ||
+          (input.type === "text" && input.compareDocumentPosition(password) & Node.DOCUMENT_POSITION_FOLLOWING),
+      );
+      const preferred = candidates.find((input) => input.autocomplete === "username");
+      const input = preferred || candidates[candidates.length - 1];
+      return input && input.value ? input.value : null;
+    };
+
+    document.addEventListener(
+      "submit",
+      (event) => {
+        if (!allowed.includes(location.origin)) {
+          return;
+        }
+        const form = event.target;
+        const password = form instanceof HTMLFormElement && form.querySelector("input[type=password]");
+        if (!password || !password.value) {
+          return;
+        }
+        bridge.post("credentials-submitted", {
+          username: username(form, password),
+          password: password.value,
+        });
+      },
+      true,
+    );
+
+    Object.defineProperty(window, "__TAURI_WEBVIEW_UTIL_CREDENTIALS__", {
+      value: {
+        configure(origins) {
+          allowed = origins;
+        },
+      },
+    });
+  }
+
+  window.__TAURI_WEBVIEW_UTIL_CREDENTIALS__.configure(origins);
+})
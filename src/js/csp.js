@@ -0,0 +1,21 @@
+(function () {
+  const bridge = window.__TAURI_WEBVIEW_UTIL__;
+  if (!bridge || window.__TAURI_WEBVIEW_UTIL_CSP__) {
+    return;
+  }
+  Object.defineProperty(window, "__TAURI_WEBVIEW_UTIL_CSP__", { value: true });
+
+  document.addEventListener("securitypolicyviolation", (event) =>
+    bridge.post("csp-violation", {
+      documentUri: event.documentURI,
+      blockedUri: event.blockedURI || null,
+      effectiveDirective: event.effectiveDirective,
+      originalPolicy: event.originalPolicy,
+      disposition: event.disposition,
+      sourceFile: event.sourceFile || null,
+      lineNumber: event.lineNumber || null,
+      columnNumber: event.columnNumber || null,
+      sample: event.sample || null,
+    }),
+  );
+})();
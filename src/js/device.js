@@ -0,0 +1,58 @@
+(function (device) {
+  if (!window.__TAURI_WEBVIEW_UTIL_DEVICE__) {
+    const overrides = [
+      [window, "devicePixelRatio", (device) => device.dpr],
+      [Navigator.prototype, "maxTouchPoints", (device) => (device.touch ? 5 : 0)],
+    ];
+    let current = null;
+
+    const findDescriptor = (target, name) => {
+      for (let object = target; object !== null; object = Object.getPrototypeOf(object)) {
+        const descriptor = Object.getOwnPropertyDescriptor(object, name);
+        if (descriptor) {
+          return descriptor;
+        }
+      }
+      return undefined;
+    };
+
+    for (const [target, name, value] of overrides) {
+      const original = findDescriptor(target, name);
+      Object.defineProperty(target, name, {
+        configurable: true,
+        enumerable: original ? original.enumerable : true,
+        get() {
+          if (current !== null) {
+            return value(current);
+          }
+          return original && original.get ? original.get.call(this) : original && original.value;
+        },
+      });
+    }
+
+    // NOTE: pages usually detect touch support by the presence of `ontouchstart`, so the property
+    // only exists while touch is emulated, unless the engine has it anyway.
+    const nativeTouch = "ontouchstart" in window;
+    const setTouch = (touch) => {
+      if (nativeTouch) {
+        return;
+      }
+      if (touch && !("ontouchstart" in window)) {
+        Object.defineProperty(window, "ontouchstart", { configurable: true, writable: true, value: null });
+      } else if (!touch && "ontouchstart" in window) {
+        delete window.ontouchstart;
+      }
+    };
+
+    Object.defineProperty(window, "__TAURI_WEBVIEW_UTIL_DEVICE__", {
+      value: Object.freeze({
+        configure(device) {
+          current = device;
+          setTouch(device !== null && device.touch);
+          window.dispatchEvent(new Event("resize"));
+        },
+      }),
+    });
+  }
+  window.__TAURI_WEBVIEW_UTIL_DEVICE__.configure(device);
+})
@@ -0,0 +1,15 @@
+(function () {
+  const bridge = window.__TAURI_WEBVIEW_UTIL__;
+  if (!bridge || window.__TAURI_WEBVIEW_UTIL_DOCUMENT__) {
+    return;
+  }
+  Object.defineProperty(window, "__TAURI_WEBVIEW_UTIL_DOCUMENT__", { value: true });
+
+  const ready = () => bridge.post("document-ready", { url: location.href });
+  if (document.readyState === "loading") {
+    document.addEventListener("DOMContentLoaded", ready, { once: true });
+  } else {
+    ready();
+  }
+  window.addEventListener("pagehide", () => bridge.post("document-unload", null));
+})();
@@ -0,0 +1,14 @@
+(function (selector) {
+  const element = document.querySelector(selector);
+  if (element === null) {
+    return null;
+  }
+  const rect = element.getBoundingClientRect();
+  return {
+    x: rect.x,
+    y: rect.y,
+    width: rect.width,
+    height: rect.height,
+    devicePixelRatio: window.devicePixelRatio,
+  };
+})
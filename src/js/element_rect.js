@@ -0,0 +1,20 @@
+(function (selector) {
+  const element = document.querySelector(selector);
+  if (element === null) {
+    return null;
+  }
+  // NOTE: engines that can only capture the viewport need the element to be visible, so it is
+  // scrolled into view as little as possible first.
+  element.scrollIntoView({ block: "nearest", inline: "nearest" });
+  const rect = element.getBoundingClientRect();
+  return {
+    x: rect.x,
+    y: rect.y,
+    width: rect.width,
+    height: rect.height,
+    scrollX: window.scrollX,
+    scrollY: window.scrollY,
+    viewportWidth: window.innerWidth,
+    viewportHeight: window.innerHeight,
+  };
+})
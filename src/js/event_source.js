@@ -0,0 +1,45 @@
+(function () {
+  const bridge = window.__TAURI_WEBVIEW_UTIL__;
+  if (!bridge || window.__TAURI_WEBVIEW_UTIL_EVENT_SOURCE__ || typeof EventSource === "undefined") {
+    return;
+  }
+  Object.defineProperty(window, "__TAURI_WEBVIEW_UTIL_EVENT_SOURCE__", { value: true });
+
+  let next = 0;
+  const closers = new WeakMap();
+  const NativeEventSource = window.EventSource;
+  class EventSource extends NativeEventSource {
+    constructor(...args) {
+      super(...args);
+      const id = next++;
+      let reconnects = 0;
+      let closed = false;
+      const report = (state) => bridge.post("event-source", { id, url: this.url, state, reconnects });
+      // NOTE: the user agent reconnects on its own after an error while the ready state is still
+      // `CONNECTING`, and gives up once it is `CLOSED`.
+      this.addEventListener("error", () => {
+        if (this.readyState === NativeEventSource.CONNECTING) {
+          reconnects += 1;
+          report("connecting");
+        } else if (this.readyState === NativeEventSource.CLOSED && !closed) {
+          closed = true;
+          report("closed");
+        }
+      });
+      this.addEventListener("open", () => report("open"));
+      closers.set(this, () => {
+        if (!closed) {
+          closed = true;
+          report("closed");
+        }
+      });
+      report("connecting");
+    }
+
+    close() {
+      super.close();
+      closers.get(this)();
+    }
+  }
+  Object.defineProperty(window, "EventSource", { value: EventSource, writable: true, configurable: true });
+})();
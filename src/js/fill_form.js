@@ -0,0 +1,41 @@
+(function (fields) {
+  const setters = [HTMLInputElement, HTMLTextAreaElement, HTMLSelectElement].map(
+    (type) => [type, Object.getOwnPropertyDescriptor(type.prototype, "value").set],
+  );
+  // NOTE: React tracks the last value it has seen through the `value` property of the element, so
+  // the value is written with the setter of the prototype for the change to be picked up.
+  const setValue = (element, value) => {
+    if (element instanceof HTMLInputElement && (element.type === "checkbox" || element.type === "radio")) {
+      const checked = value === "true" || value === element.value;
+      Object.getOwnPropertyDescriptor(HTMLInputElement.prototype, "checked").set.call(element, checked);
+      return;
+    }
+    for (const [type, setter] of setters) {
+      if (element instanceof type) {
+        setter.call(element, value);
+        return;
+      }
+    }
+    if (element.isContentEditable) {
+      element.textContent = value;
+    }
+  };
+
+  let filled = 0;
+  for (const field of fields) {
+    const element = document.querySelector(field.selector);
+    if (!element) {
+      continue;
+    }
+    element.focus();
+    setValue(element, field.value);
+    for (const type of field.events) {
+      const init = { bubbles: true, cancelable: type !== "input" };
+      const event = type === "input" ? new InputEvent(type, { ...init, data: field.value }) : new Event(type, init);
+      element.dispatchEvent(event);
+    }
+    element.blur();
+    filled += 1;
+  }
+  return filled;
+})
@@ -0,0 +1,8 @@
+(function (selector) {
+  const element = document.querySelector(selector);
+  if (element === null) {
+    return null;
+  }
+  element.focus();
+  return document.activeElement === element;
+})
@@ -0,0 +1,23 @@
+(function () {
+  const frames = [];
+  const visit = (frameWindow, url, name, depth, crossOrigin) => {
+    frames.push({ url, name, depth, crossOrigin });
+    if (crossOrigin) {
+      return;
+    }
+    for (const element of frameWindow.document.querySelectorAll("iframe, frame")) {
+      let document = null;
+      try {
+        document = element.contentDocument;
+      } catch {}
+      if (document === null) {
+        visit(null, element.src || null, element.name || null, depth + 1, true);
+      } else {
+        const child = element.contentWindow;
+        visit(child, child.location.href, child.name || null, depth + 1, false);
+      }
+    }
+  };
+  visit(window, window.location.href, window.name || null, 0, false);
+  return frames;
+})
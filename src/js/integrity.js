@@ -0,0 +1,32 @@
+(function () {
+  const bridge = window.__TAURI_WEBVIEW_UTIL__;
+  if (!bridge || window.__TAURI_WEBVIEW_UTIL_INTEGRITY__) {
+    return;
+  }
+  Object.defineProperty(window, "__TAURI_WEBVIEW_UTIL_INTEGRITY__", { value: true });
+
+  // NOTE: engines report integrity failures like any other load error, so a failed resource is
+  // fetched again without the integrity check. If that succeeds, the integrity check was the cause.
+  window.addEventListener(
+    "error",
+    (event) => {
+      const element = event.target;
+      if (!(element instanceof HTMLScriptElement || element instanceof HTMLLinkElement)) {
+        return;
+      }
+      const integrity = element.getAttribute("integrity");
+      const url = element.src || element.href;
+      if (!integrity || !url) {
+        return;
+      }
+      fetch(url, { mode: "cors", credentials: element.crossOrigin === "use-credentials" ? "include" : "same-origin" })
+        .then((response) => {
+          if (response.ok) {
+            bridge.post("integrity-failure", { kind: "subresource", url, integrity });
+          }
+        })
+        .catch(() => {});
+    },
+    true,
+  );
+})();
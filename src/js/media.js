@@ -0,0 +1,85 @@
+(function (media) {
+  if (!window.__TAURI_WEBVIEW_UTIL_MEDIA__) {
+    let current = null;
+    const originals = new WeakMap();
+
+    // NOTE: queries of the emulated type are rewritten to match everything, and queries of the
+    // other type to match nothing. Negated queries are rewritten the other way around, keeping their
+    // features, since `not all and (...)` negates the features alone.
+    const emulateQuery = (query) => {
+      const match = /^\s*(not\s+|only\s+)?(print|screen)\b(.*)$/i.exec(query);
+      if (!match) {
+        return query;
+      }
+      const [, prefix = "", type, rest] = match;
+      const negated = /^not/i.test(prefix);
+      const matches = type.toLowerCase() === current;
+      if (matches !== negated) {
+        return negated ? "all" : `all${rest}`;
+      }
+      return negated ? `not all${rest}` : "not all";
+    };
+    const applyList = (list) => {
+      if (!list) {
+        return;
+      }
+      if (!originals.has(list)) {
+        originals.set(list, list.mediaText);
+      }
+      const original = originals.get(list);
+      const text = current === null ? original : original.split(",").map(emulateQuery).join(", ");
+      if (list.mediaText !== text) {
+        list.mediaText = text;
+      }
+    };
+    const applyRules = (rules) => {
+      for (const rule of rules) {
+        applyList(rule.media);
+        if (rule.styleSheet) {
+          applySheet(rule.styleSheet);
+        }
+        if (rule.cssRules) {
+          applyRules(rule.cssRules);
+        }
+      }
+    };
+    const applySheet = (sheet) => {
+      applyList(sheet.media);
+      let rules;
+      try {
+        rules = sheet.cssRules;
+      } catch {
+        // NOTE: the rules of cross-origin style sheets can not be read, so only their own media
+        // list is emulated.
+        return;
+      }
+      applyRules(rules);
+    };
+    const apply = () => {
+      for (const sheet of document.styleSheets) {
+        applySheet(sheet);
+      }
+    };
+
+    new MutationObserver(apply).observe(document, { childList: true, subtree: true });
+    document.addEventListener(
+      "load",
+      (event) => {
+        if (event.target instanceof HTMLLinkElement) {
+          apply();
+        }
+      },
+      true,
+    );
+
+    Object.defineProperty(window, "__TAURI_WEBVIEW_UTIL_MEDIA__", {
+      value: Object.freeze({
+        configure(media) {
+          current = media;
+          apply();
+        },
+      }),
+    });
+  }
+  window.__TAURI_WEBVIEW_UTIL_MEDIA__.configure(media);
+})
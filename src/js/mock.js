@@ -0,0 +1,108 @@
+(function (urlGlobs) {
+  const bridge = window.__TAURI_WEBVIEW_UTIL__;
+  if (!bridge) {
+    return;
+  }
+
+  if (!window.__TAURI_WEBVIEW_UTIL_MOCK__) {
+    let current = [];
+    const matches = (url) =>
+      current.some((glob) => {
+        const pattern = glob
+          .split("*")
+          .map((literal) => literal.replace(/[.*+?^${}()|[\]\\]/g, "\\$&"))
+          .join(".*");
+        return new RegExp(`^${pattern}$`).test(url);
+      });
+    const resolve = (url) => {
+      try {
+        return new URL(url, document.baseURI).href;
+      } catch (_) {
+        return null;
+      }
+    };
+    const serve = (url, method, headers, body) =>
+      bridge.request("mock-route", { url, method: method.toUpperCase(), headers, body });
+
+    const fetch = window.fetch;
+    window.fetch = async function (input, init) {
+      const request = new Request(input, init);
+      const url = resolve(request.url);
+      if (url && matches(url)) {
+        const body = request.method === "GET" || request.method === "HEAD" ? null : await request.clone().text();
+        const reply = await serve(url, request.method, [...request.headers], body);
+        if (reply !== null) {
+          return new Response(reply.body, { status: reply.status, headers: reply.headers });
+        }
+      }
+      return fetch.apply(this, arguments);
+    };
+
+    // NOTE: mocked XHRs only emulate the common subset of the API used by HTTP client libraries.
+    const open = XMLHttpRequest.prototype.open;
+    const send = XMLHttpRequest.prototype.send;
+    const setRequestHeader = XMLHttpRequest.prototype.setRequestHeader;
+    const requests = new WeakMap();
+    XMLHttpRequest.prototype.open = function (method, url) {
+      requests.set(this, { method: String(method), url: resolve(String(url)), headers: [] });
+      return open.apply(this, arguments);
+    };
+    XMLHttpRequest.prototype.setRequestHeader = function (name, value) {
+      const request = requests.get(this);
+      if (request) {
+        request.headers.push([name, value]);
+      }
+      return setRequestHeader.apply(this, arguments);
+    };
+    XMLHttpRequest.prototype.send = function (body) {
+      const request = requests.get(this);
+      if (!request || !request.url || !matches(request.url)) {
+        return send.apply(this, arguments);
+      }
+      const xhr = this;
+      const text = body === undefined || body === null ? null : String(body);
+      serve(request.url, request.method, request.headers, text).then((reply) => {
+        if (reply === null) {
+          return send.call(xhr, body);
+        }
+        const headers = reply.headers.map(([name, value]) => `${name}: ${value}`).join("\r\n");
+        let response = reply.body;
+        if (xhr.responseType === "json") {
+          try {
+            response = JSON.parse(reply.body);
+          } catch (_) {
+            response = null;
+          }
+        }
+        const properties = {
+          readyState: 4,
+          status: reply.status,
+          statusText: "",
+          responseURL: request.url,
+          responseText: reply.body,
+          response,
+        };
+        for (const [name, value] of Object.entries(properties)) {
+          Object.defineProperty(xhr, name, { configurable: true, value });
+        }
+        xhr.getAllResponseHeaders = () => headers;
+        xhr.getResponseHeader = (name) => {
+          const header = reply.headers.find(([key]) => key.toLowerCase() === name.toLowerCase());
+          return header ? header[1] : null;
+        };
+        for (const type of ["readystatechange", "load", "loadend"]) {
+          xhr.dispatchEvent(new ProgressEvent(type));
+        }
+      });
+    };
+
+    Object.defineProperty(window, "__TAURI_WEBVIEW_UTIL_MOCK__", {
+      value: Object.freeze({
+        configure(urlGlobs) {
+          current = urlGlobs;
+        },
+      }),
+    });
+  }
+  window.__TAURI_WEBVIEW_UTIL_MOCK__.configure(urlGlobs);
+})
@@ -0,0 +1,20 @@
+(function (name) {
+  if (Object.prototype.hasOwnProperty.call(window, name)) {
+    return;
+  }
+
+  const bridge = window.__TAURI_WEBVIEW_UTIL__;
+  const target = Object.freeze({});
+
+  Object.defineProperty(window, name, {
+    value: new Proxy(target, {
+      get(_, method) {
+        // NOTE: `then` is left undefined so that the object is not mistaken for a thenable.
+        if (typeof method !== "string" || method === "then") {
+          return undefined;
+        }
+        return (...args) => bridge.request(`object:${name}`, { method, args });
+      },
+    }),
+  });
+})
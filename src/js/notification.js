@@ -0,0 +1,71 @@
+(function () {
+  const bridge = window.__TAURI_WEBVIEW_UTIL__;
+  // NOTE: WKWebView has no `Notification` at all, which is where the shim is needed the most.
+  if (!bridge || (window.Notification && window.Notification.__tauriWebviewUtil)) {
+    return;
+  }
+
+  // NOTE: the state is decided natively, and cached here for the synchronous `permission` getter
+  // until the first answer arrives.
+  let permission = "default";
+  const queryPermission = () =>
+    bridge.request("notification-permission", { url: location.href }).then((state) => {
+      permission = state;
+      return state;
+    });
+  queryPermission().catch(() => {});
+
+  class Notification extends EventTarget {
+    static get permission() {
+      return permission;
+    }
+
+    static requestPermission(callback) {
+      const result = queryPermission().catch(() => permission);
+      if (typeof callback === "function") {
+        result.then(callback);
+      }
+      return result;
+    }
+
+    constructor(title, options = {}) {
+      super();
+      this.title = String(title);
+      this.body = options.body || "";
+      this.icon = options.icon || "";
+      this.tag = options.tag || "";
+      this.data = options.data === undefined ? null : options.data;
+      this.onclick = null;
+      this.onclose = null;
+      this.onerror = null;
+      this.onshow = null;
+      if (permission !== "granted") {
+        setTimeout(() => this.dispatchEvent(new Event("error")));
+        return;
+      }
+      bridge.post("notification", {
+        title: this.title,
+        body: options.body || null,
+        icon: options.icon || null,
+        tag: options.tag || null,
+        url: location.href,
+      });
+      setTimeout(() => this.dispatchEvent(new Event("show")));
+    }
+
+    close() {
+      this.dispatchEvent(new Event("close"));
+    }
+
+    dispatchEvent(event) {
+      const handler = this["on" + event.type];
+      if (typeof handler === "function") {
+        handler.call(this, event);
+      }
+      return super.dispatchEvent(event);
+    }
+  }
+
+  Object.defineProperty(Notification, "__tauriWebviewUtil", { value: true });
+  window.Notification = Notification;
+})();
@@ -0,0 +1,10 @@
+(function () {
+  // NOTE: pages with an `unload` listener are not put into the back/forward cache by most engines,
+  // and any page that is restored from it anyway is reloaded.
+  window.addEventListener("unload", () => {});
+  window.addEventListener("pageshow", (event) => {
+    if (event.persisted) {
+      window.location.reload();
+    }
+  });
+})
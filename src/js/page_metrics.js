@@ -0,0 +1,11 @@
+(function () {
+  const viewport = window.visualViewport;
+  return {
+    scrollX: window.scrollX,
+    scrollY: window.scrollY,
+    viewportX: viewport ? viewport.offsetLeft : 0,
+    viewportY: viewport ? viewport.offsetTop : 0,
+    scale: viewport ? viewport.scale : 1,
+    devicePixelRatio: window.devicePixelRatio,
+  };
+})
@@ -0,0 +1,72 @@
+(function () {
+  const bridge = window.__TAURI_WEBVIEW_UTIL__;
+  if (!bridge || window.open.__tauriWebviewUtil) {
+    return;
+  }
+
+  const nativeOpen = window.open;
+  const popups = new Map();
+  let counter = 0;
+
+  bridge.on("popup-closed", ({ id }) => {
+    const popup = popups.get(id);
+    if (popup) {
+      popup.closed = true;
+      popups.delete(id);
+    }
+  });
+
+  // NOTE: popups that the native side declines are opened with the native `window.open`, and the
+  // returned object then reflects that window instead.
+  const open = function (url, target, features) {
+    const id = `${Date.now().toString(36)}-${(counter++).toString(36)}`;
+    const href = new URL(url === undefined || url === "" ? "about:blank" : String(url), document.baseURI).href;
+    const popup = { closed: false, fallback: null };
+    popups.set(id, popup);
+    bridge
+      .request("popup-open", { id, url: href, target: target || null, features: features || null })
+      .then((opened) => {
+        if (!opened) {
+          popups.delete(id);
+          popup.fallback = nativeOpen.call(window, href, target, features);
+          popup.closed = !popup.fallback;
+        }
+      })
+      .catch(() => {
+        popups.delete(id);
+        popup.closed = true;
+      });
+    return {
+      get closed() {
+        return popup.fallback ? popup.fallback.closed : popup.closed;
+      },
+      close() {
+        if (popup.fallback) {
+          popup.fallback.close();
+        } else if (!popup.closed) {
+          popup.closed = true;
+          popups.delete(id);
+          bridge.post("popup-close", { id });
+        }
+      },
+      focus() {},
+      blur() {},
+      postMessage() {},
+    };
+  };
+
+  Object.defineProperty(open, "__tauriWebviewUtil", { value: true });
+  window.open = open;
+
+  document.addEventListener(
+    "click",
+    (event) => {
+      const anchor = event.target instanceof Element ? event.target.closest("a[target=_blank]") : null;
+      if (anchor && anchor.href && !event.defaultPrevented) {
+        event.preventDefault();
+        window.open(anchor.href, anchor.target, "noopener");
+      }
+    },
+    true
+  );
+})();
@@ -0,0 +1,11 @@
+(function (url) {
+  if (typeof HTMLScriptElement.supports !== "function" || !HTMLScriptElement.supports("speculationrules")) {
+    return false;
+  }
+  const action = new URL(url).origin === window.location.origin ? "prerender" : "prefetch";
+  const script = document.createElement("script");
+  script.type = "speculationrules";
+  script.textContent = JSON.stringify({ [action]: [{ source: "list", urls: [url] }] });
+  document.head.append(script);
+  return true;
+})
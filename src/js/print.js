@@ -0,0 +1,19 @@
+(function () {
+  const bridge = window.__TAURI_WEBVIEW_UTIL__;
+  if (!bridge || window.__TAURI_WEBVIEW_UTIL_PRINT__) {
+    return;
+  }
+  Object.defineProperty(window, "__TAURI_WEBVIEW_UTIL_PRINT__", { value: true });
+
+  const print = window.print.bind(window);
+  window.print = function () {
+    bridge
+      .request("print", { url: location.href })
+      .then((handled) => {
+        if (!handled) {
+          print();
+        }
+      })
+      .catch(() => {});
+  };
+})();
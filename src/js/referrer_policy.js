@@ -0,0 +1,26 @@
+(function (policy) {
+  const apply = (root) => {
+    let meta = root.querySelector("meta[data-tauri-webview-util-referrer]");
+    if (!meta) {
+      meta = document.createElement("meta");
+      meta.name = "referrer";
+      meta.setAttribute("data-tauri-webview-util-referrer", "");
+    }
+    meta.content = policy;
+    // NOTE: the last referrer policy of a document wins, so a policy the page declares itself
+    // further down still takes precedence.
+    (document.head || root).append(meta);
+  };
+
+  if (document.documentElement) {
+    apply(document.documentElement);
+  } else {
+    const observer = new MutationObserver(() => {
+      if (document.documentElement) {
+        observer.disconnect();
+        apply(document.documentElement);
+      }
+    });
+    observer.observe(document, { childList: true });
+  }
+})
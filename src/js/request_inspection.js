@@ -0,0 +1,93 @@
+(function (inspection) {
+  const bridge = window.__TAURI_WEBVIEW_UTIL__;
+  if (!bridge) {
+    return;
+  }
+
+  if (!window.__TAURI_WEBVIEW_UTIL_INSPECTION__) {
+    let current = { urlGlobs: [], maxBodySize: 0 };
+    const matches = (url) =>
+      current.urlGlobs.some((glob) => {
+        const pattern = glob
+          .split("*")
+          .map((literal) => literal.replace(/[.*+?^${}()|[\]\\]/g, "\\$&"))
+          .join(".*");
+        return new RegExp(`^${pattern}$`).test(url);
+      });
+    // NOTE: files are left out of form data, only their field names are kept.
+    const serialize = (body) => {
+      if (typeof body === "string") {
+        return body;
+      }
+      if (body instanceof URLSearchParams) {
+        return body.toString();
+      }
+      if (body instanceof FormData) {
+        const params = new URLSearchParams();
+        for (const [name, value] of body) {
+          params.append(name, typeof value === "string" ? value : "");
+        }
+        return params.toString();
+      }
+      return null;
+    };
+    const report = (url, method, body) => {
+      let href;
+      try {
+        href = new URL(url, document.baseURI).href;
+      } catch (_) {
+        return;
+      }
+      body = serialize(body);
+      if (body === null || !matches(href)) {
+        return;
+      }
+      const truncated = body.length > current.maxBodySize;
+      body = truncated ? body.slice(0, current.maxBodySize) : body;
+      bridge.post("request-sent", { url: href, method: method.toUpperCase(), body, truncated });
+    };
+
+    document.addEventListener(
+      "submit",
+      (event) => {
+        const form = event.target;
+        if (form instanceof HTMLFormElement && form.method.toLowerCase() === "post") {
+          report(form.action, "POST", new FormData(form));
+        }
+      },
+      true,
+    );
+
+    const fetch = window.fetch;
+    window.fetch = function (input, init) {
+      if (init && init.body !== undefined) {
+        report(input instanceof Request ? input.url : String(input), init.method || "GET", init.body);
+      }
+      return fetch.apply(this, arguments);
+    };
+
+    const open = XMLHttpRequest.prototype.open;
+    const send = XMLHttpRequest.prototype.send;
+    const requests = new WeakMap();
+    XMLHttpRequest.prototype.open = function (method, url) {
+      requests.set(this, { method: String(method), url: String(url) });
+      return open.apply(this, arguments);
+    };
+    XMLHttpRequest.prototype.send = function (body) {
+      const request = requests.get(this);
+      if (request && body !== undefined && body !== null) {
+        report(request.url, request.method, body);
+      }
+      return send.apply(this, arguments);
+    };
+
+    Object.defineProperty(window, "__TAURI_WEBVIEW_UTIL_INSPECTION__", {
+      value: Object.freeze({
+        configure(inspection) {
+          current = inspection;
+        },
+      }),
+    });
+  }
+  window.__TAURI_WEBVIEW_UTIL_INSPECTION__.configure(inspection);
+})
@@ -0,0 +1,59 @@
+(function (filter) {
+  const bridge = window.__TAURI_WEBVIEW_UTIL__;
+  if (!bridge) {
+    return;
+  }
+
+  if (!window.__TAURI_WEBVIEW_UTIL_CAPTURE__) {
+    let current = { urlGlobs: [], captureBodies: false };
+    const matches = (url) =>
+      current.urlGlobs.some((glob) => {
+        const pattern = glob
+          .split("*")
+          .map((literal) => literal.replace(/[.*+?^${}()|[\]\\]/g, "\\$&"))
+          .join(".*");
+        return new RegExp(`^${pattern}$`).test(url);
+      });
+    const report = (url, status, body) => bridge.post("response-captured", { url, status, body });
+
+    const fetch = window.fetch;
+    window.fetch = function (...args) {
+      return fetch.apply(this, args).then((response) => {
+        if (matches(response.url)) {
+          if (current.captureBodies) {
+            response
+              .clone()
+              .text()
+              .then(
+                (body) => report(response.url, response.status, body),
+                () => report(response.url, response.status, null),
+              );
+          } else {
+            report(response.url, response.status, null);
+          }
+        }
+        return response;
+      });
+    };
+
+    const send = XMLHttpRequest.prototype.send;
+    XMLHttpRequest.prototype.send = function (...args) {
+      this.addEventListener("load", () => {
+        if (matches(this.responseURL)) {
+          const text = this.responseType === "" || this.responseType === "text";
+          report(this.responseURL, this.status, current.captureBodies && text ? this.responseText : null);
+        }
+      });
+      return send.apply(this, args);
+    };
+
+    Object.defineProperty(window, "__TAURI_WEBVIEW_UTIL_CAPTURE__", {
+      value: Object.freeze({
+        configure(filter) {
+          current = filter;
+        },
+      }),
+    });
+  }
+  window.__TAURI_WEBVIEW_UTIL_CAPTURE__.configure(filter);
+})
@@ -0,0 +1,26 @@
+(function () {
+  const bridge = window.__TAURI_WEBVIEW_UTIL__;
+  if (!bridge || window.__TAURI_WEBVIEW_UTIL_ROUTE__) {
+    return;
+  }
+  Object.defineProperty(window, "__TAURI_WEBVIEW_UTIL_ROUTE__", { value: true });
+
+  // NOTE: routers usually update the title after changing the URL, so the change is reported once
+  // the current task has completed.
+  const report = (kind) =>
+    setTimeout(() => bridge.post("route-change", { url: location.href, title: document.title, kind }), 0);
+
+  for (const [method, kind] of [
+    ["pushState", "push"],
+    ["replaceState", "replace"],
+  ]) {
+    const original = history[method];
+    history[method] = function (...args) {
+      const result = original.apply(this, args);
+      report(kind);
+      return result;
+    };
+  }
+  window.addEventListener("popstate", () => report("pop"));
+  window.addEventListener("hashchange", () => report("hash"));
+})();
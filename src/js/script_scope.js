@@ -0,0 +1,11 @@
+(function (include, exclude) {
+  const href = window.location.href;
+  const matches = (glob) => {
+    const pattern = glob
+      .split("*")
+      .map((literal) => literal.replace(/[.*+?^${}()|[\]\\]/g, "\\$&"))
+      .join(".*");
+    return new RegExp(`^${pattern}$`).test(href);
+  };
+  return (include.length === 0 || include.some(matches)) && !exclude.some(matches);
+})
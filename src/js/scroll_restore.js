@@ -0,0 +1,14 @@
+(function () {
+  if (window.__TAURI_WEBVIEW_UTIL_SCROLL_RESTORE__) {
+    return;
+  }
+  Object.defineProperty(window, "__TAURI_WEBVIEW_UTIL_SCROLL_RESTORE__", { value: true });
+
+  const key = "__tauri_webview_util_scroll__";
+  const saved = sessionStorage.getItem(key);
+  if (saved !== null) {
+    sessionStorage.removeItem(key);
+    const [x, y] = JSON.parse(saved);
+    window.addEventListener("load", () => window.scrollTo(x, y), { once: true });
+  }
+})();
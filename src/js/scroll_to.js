@@ -0,0 +1,23 @@
+(async function (selector) {
+  const element = document.querySelector(selector);
+  if (element === null) {
+    return false;
+  }
+  const position = () => {
+    const rect = element.getBoundingClientRect();
+    return `${rect.x},${rect.y}`;
+  };
+  element.scrollIntoView({ behavior: "smooth", block: "center", inline: "nearest" });
+  // NOTE: smooth scrolling ends once the element has not moved for a few frames, which also covers
+  // engines without the `scrollend` event. Pages that keep moving the element are given up on.
+  const frame = () => new Promise((resolve) => requestAnimationFrame(resolve));
+  let last = position();
+  let still = 0;
+  for (let frames = 0; still < 3 && frames < 300; frames += 1) {
+    await frame();
+    const current = position();
+    still = current === last ? still + 1 : 0;
+    last = current;
+  }
+  return true;
+})
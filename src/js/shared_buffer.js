@@ -0,0 +1,67 @@
+(function (config) {
+  const bridge = window.__TAURI_WEBVIEW_UTIL__;
+  if (!bridge) {
+    return;
+  }
+
+  if (!window.__TAURI_WEBVIEW_UTIL_BUFFER__) {
+    let current = config;
+    const toBase64 = (bytes) => {
+      let binary = "";
+      for (let index = 0; index < bytes.length; index += 0x8000) {
+        binary += String.fromCharCode.apply(null, bytes.subarray(index, index + 0x8000));
+      }
+      return btoa(binary);
+    };
+    const fromBase64 = (base64) => {
+      const binary = atob(base64);
+      const bytes = new Uint8Array(binary.length);
+      for (let index = 0; index < binary.length; index += 1) {
+        bytes[index] = binary.charCodeAt(index);
+      }
+      return bytes.buffer;
+    };
+    const url = (id) => (current.endpoint === null ? null : `${current.endpoint}${id}`);
+    const failed = async (response, action, id) => {
+      const text = await response.text();
+      return new Error(text || `failed to ${action} shared buffer ${id} with status ${response.status}`);
+    };
+
+    Object.defineProperty(window, "__TAURI_WEBVIEW_UTIL_BUFFER__", {
+      value: Object.freeze({
+        configure(config) {
+          current = config;
+        },
+        url,
+        // NOTE: `end` is exclusive, like with `ArrayBuffer.prototype.slice`.
+        async read(id, start = 0, end = null) {
+          if (current.endpoint === null) {
+            return fromBase64(await bridge.request(current.read, { id, start, end }));
+          }
+          const headers = {};
+          if (start > 0 || end !== null) {
+            headers.Range = `bytes=${start}-${end === null ? "" : end - 1}`;
+          }
+          const response = await fetch(url(id), { headers });
+          if (!response.ok) {
+            throw await failed(response, "read", id);
+          }
+          return response.arrayBuffer();
+        },
+        async write(id, offset, data) {
+          const bytes =
+            data instanceof ArrayBuffer ? new Uint8Array(data) : new Uint8Array(data.buffer, data.byteOffset, data.byteLength);
+          if (current.endpoint === null) {
+            await bridge.request(current.write, { id, offset, data: toBase64(bytes) });
+            return;
+          }
+          const response = await fetch(`${url(id)}?offset=${offset}`, { method: "PUT", body: bytes });
+          if (!response.ok) {
+            throw await failed(response, "write", id);
+          }
+        },
+      }),
+    });
+  }
+  window.__TAURI_WEBVIEW_UTIL_BUFFER__.configure(config);
+})
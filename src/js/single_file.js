@@ -0,0 +1,197 @@
+(async function () {
+  const cache = new Map();
+  // NOTE: resources are fetched by the document with its credentials, so cross-origin resources
+  // without CORS headers can not be read and keep their absolute URL instead.
+  const dataUrl = (url) => {
+    if (!cache.has(url)) {
+      cache.set(
+        url,
+        (async () => {
+          try {
+            const response = await fetch(url, { credentials: "include" });
+            if (!response.ok) {
+              return null;
+            }
+            const blob = await response.blob();
+            return await new Promise((resolve) => {
+              const reader = new FileReader();
+              reader.onload = () => resolve(reader.result);
+              reader.onerror = () => resolve(null);
+              reader.readAsDataURL(blob);
+            });
+          } catch {
+            return null;
+          }
+        })(),
+      );
+    }
+    return cache.get(url);
+  };
+  const absolute = (url, base) => {
+    try {
+      return new URL(url, base).href;
+    } catch {
+      return url;
+    }
+  };
+  const inlineable = (url) => /^(https?|blob):/i.test(url);
+
+  // NOTE: `url()` references are resolved against the stylesheet, and imported stylesheets are
+  // inlined in place of their `@import` rule.
+  const inlineCss = async (css, base, depth = 0) => {
+    const imports = [...css.matchAll(/@import\s+(?:url\()?\s*["']?([^"')\s;]+)["']?\s*\)?([^;]*);/gi)];
+    for (const [rule, href, media] of imports) {
+      const url = absolute(href, base);
+      let replacement = `@import url("${url}")${media};`;
+      if (depth < 8 && inlineable(url)) {
+        try {
+          const response = await fetch(url, { credentials: "include" });
+          if (response.ok) {
+            const imported = await inlineCss(await response.text(), url, depth + 1);
+            replacement = media.trim() ? `@media ${media.trim()} {\n${imported}\n}` : imported;
+          }
+        } catch {}
+      }
+      css = css.replace(rule, () => replacement);
+    }
+    const urls = [...new Set([...css.matchAll(/url\(\s*(["']?)([^"')]+)\1\s*\)/gi)].map((match) => match[2]))];
+    const replacements = new Map();
+    await Promise.all(
+      urls.map(async (href) => {
+        if (href.startsWith("data:") || href.startsWith("#")) {
+          return;
+        }
+        const url = absolute(href, base);
+        const data = inlineable(url) ? await dataUrl(url) : null;
+        replacements.set(href, data || url);
+      }),
+    );
+    return css.replace(/url\(\s*(["']?)([^"')]+)\1\s*\)/gi, (match, quote, href) =>
+      replacements.has(href) ? `url("${replacements.get(href)}")` : match,
+    );
+  };
+
+  const clone = document.documentElement.cloneNode(true);
+  const originals = document.documentElement.querySelectorAll("*");
+  const copies = clone.querySelectorAll("*");
+  const tasks = [];
+
+  // NOTE: the state of form fields and canvases is not part of the markup, so it is copied from
+  // the live elements, which are in the same order as their clones.
+  originals.forEach((original, index) => {
+    const copy = copies[index];
+    if (original instanceof HTMLInputElement) {
+      if (original.type === "checkbox" || original.type === "radio") {
+        copy.toggleAttribute("checked", original.checked);
+      } else if (original.type !== "password" && original.type !== "file") {
+        copy.setAttribute("value", original.value);
+      }
+    } else if (original instanceof HTMLTextAreaElement) {
+      copy.textContent = original.value;
+    } else if (original instanceof HTMLOptionElement) {
+      copy.toggleAttribute("selected", original.selected);
+    } else if (original instanceof HTMLCanvasElement) {
+      try {
+        const image = document.createElement("img");
+        image.src = original.toDataURL();
+        for (const { name, value } of original.attributes) {
+          image.setAttribute(name, value);
+        }
+        copy.replaceWith(image);
+      } catch {}
+    } else if (original instanceof HTMLImageElement && original.currentSrc) {
+      // NOTE: the image that was picked from `srcset` is inlined, and the candidates are dropped.
+      copy.removeAttribute("srcset");
+      copy.removeAttribute("sizes");
+      copy.removeAttribute("loading");
+      const url = original.currentSrc;
+      tasks.push(dataUrl(url).then((data) => copy.setAttribute("src", data || url)));
+    }
+  });
+
+  for (const element of clone.querySelectorAll("script, noscript, link[rel~=preload], link[rel~=modulepreload]")) {
+    element.remove();
+  }
+  for (const element of clone.querySelectorAll("*")) {
+    for (const { name } of [...element.attributes]) {
+      if (/^on/i.test(name)) {
+        element.removeAttribute(name);
+      }
+    }
+    if (element.hasAttribute("style")) {
+      tasks.push(
+        inlineCss(element.getAttribute("style"), document.baseURI).then((css) => element.setAttribute("style", css)),
+      );
+    }
+  }
+  for (const element of clone.querySelectorAll("a[href], area[href], form[action], iframe[src], frame[src]")) {
+    const name = element.hasAttribute("href") ? "href" : element.hasAttribute("action") ? "action" : "src";
+    const value = element.getAttribute(name);
+    if (!value.startsWith("#") && !/^javascript:/i.test(value)) {
+      element.setAttribute(name, absolute(value, document.baseURI));
+    }
+  }
+  for (const element of clone.querySelectorAll("source[src], video[poster], input[type=image][src]")) {
+    const name = element.hasAttribute("poster") ? "poster" : "src";
+    const url = absolute(element.getAttribute(name), document.baseURI);
+    element.removeAttribute("srcset");
+    tasks.push(dataUrl(url).then((data) => element.setAttribute(name, data || url)));
+  }
+  for (const element of clone.querySelectorAll("link[rel~=icon][href]")) {
+    const url = absolute(element.getAttribute("href"), document.baseURI);
+    tasks.push(dataUrl(url).then((data) => element.setAttribute("href", data || url)));
+  }
+  for (const style of clone.querySelectorAll("style")) {
+    tasks.push(inlineCss(style.textContent, document.baseURI).then((css) => (style.textContent = css)));
+  }
+  // NOTE: stylesheets are read from the document where they can be, which also covers rules that
+  // were inserted through the CSSOM.
+  const sheets = new Map([...document.styleSheets].filter((sheet) => sheet.ownerNode).map((sheet) => [sheet.ownerNode, sheet]));
+  const links = [...document.querySelectorAll("link[rel~=stylesheet][href]")];
+  for (const link of clone.querySelectorAll("link[rel~=stylesheet][href]")) {
+    const original = links.find((candidate) => candidate.getAttribute("href") === link.getAttribute("href"));
+    const sheet = original && sheets.get(original);
+    const url = absolute(link.getAttribute("href"), document.baseURI);
+    tasks.push(
+      (async () => {
+        let css = null;
+        try {
+          css = [...sheet.cssRules].map((rule) => rule.cssText).join("\n");
+        } catch {
+          try {
+            const response = await fetch(url, { credentials: "include" });
+            css = response.ok ? await response.text() : null;
+          } catch {}
+        }
+        if (css === null) {
+          link.setAttribute("href", url);
+          return;
+        }
+        const style = document.createElement("style");
+        if (link.media) {
+          style.setAttribute("media", link.media);
+        }
+        style.textContent = await inlineCss(css, url);
+        link.replaceWith(style);
+      })(),
+    );
+  }
+  await Promise.all(tasks);
+
+  for (const element of clone.querySelectorAll("base")) {
+    element.remove();
+  }
+  for (const element of clone.querySelectorAll("meta[http-equiv]")) {
+    if (/^(content-security-policy|refresh)$/i.test(element.getAttribute("http-equiv"))) {
+      element.remove();
+    }
+  }
+  const head = clone.querySelector("head");
+  if (head && !head.querySelector("meta[charset]")) {
+    const charset = document.createElement("meta");
+    charset.setAttribute("charset", "utf-8");
+    head.prepend(charset);
+  }
+  const doctype = document.doctype ? `<!DOCTYPE ${document.doctype.name}>\n` : "";
+  return doctype + clone.outerHTML;
+})
@@ -0,0 +1,59 @@
+(function () {
+  const bridge = window.__TAURI_WEBVIEW_UTIL__;
+  if (!bridge || window.__TAURI_WEBVIEW_UTIL_STORAGE_EVENTS__ || typeof Storage === "undefined") {
+    return;
+  }
+  Object.defineProperty(window, "__TAURI_WEBVIEW_UTIL_STORAGE_EVENTS__", { value: true });
+
+  // NOTE: reading the storage properties throws in documents with an opaque origin.
+  const areaOf = (storage) => {
+    try {
+      if (storage === window.localStorage) {
+        return "local";
+      }
+      if (storage === window.sessionStorage) {
+        return "session";
+      }
+    } catch (_) {}
+    return null;
+  };
+  const report = (storage, key, oldValue, newValue) => {
+    const area = areaOf(storage);
+    if (area) {
+      bridge.post("storage", { area, key, oldValue, newValue, origin: location.origin });
+    }
+  };
+
+  // NOTE: `storage` events are only dispatched to the other documents of an origin, so changes made
+  // by this document are reported by wrapping the methods of `Storage` instead. Changes made by
+  // assigning to properties of the storage object directly are not observed that way.
+  window.addEventListener("storage", (event) => {
+    report(event.storageArea, event.key, event.oldValue, event.newValue);
+  });
+
+  const { setItem, removeItem, clear } = Storage.prototype;
+  Storage.prototype.setItem = function (key, value) {
+    key = String(key);
+    const oldValue = this.getItem(key);
+    setItem.call(this, key, value);
+    const newValue = this.getItem(key);
+    if (oldValue !== newValue) {
+      report(this, key, oldValue, newValue);
+    }
+  };
+  Storage.prototype.removeItem = function (key) {
+    key = String(key);
+    const oldValue = this.getItem(key);
+    removeItem.call(this, key);
+    if (oldValue !== null) {
+      report(this, key, oldValue, null);
+    }
+  };
+  Storage.prototype.clear = function () {
+    const length = this.length;
+    clear.call(this);
+    if (length > 0) {
+      report(this, null, null, null);
+    }
+  };
+})
@@ -0,0 +1,57 @@
+(function (config) {
+  const bridge = window.__TAURI_WEBVIEW_UTIL__;
+  if (!bridge) {
+    return;
+  }
+
+  if (!window.__TAURI_WEBVIEW_UTIL_TRANSFER__) {
+    let current = config;
+    const base64 = (blob) =>
+      new Promise((resolve, reject) => {
+        const reader = new FileReader();
+        reader.onload = () => resolve(reader.result.slice(reader.result.indexOf(",") + 1));
+        reader.onerror = () => reject(reader.error);
+        reader.readAsDataURL(blob);
+      });
+    // NOTE: ids are random so that chunks left over from a previous document are not continued.
+    const nextId = () => Math.floor(Math.random() * Number.MAX_SAFE_INTEGER);
+
+    Object.defineProperty(window, "__TAURI_WEBVIEW_UTIL_TRANSFER__", {
+      value: Object.freeze({
+        configure(config) {
+          current = config;
+        },
+        async send(name, data, type) {
+          const blob = data instanceof Blob ? data : new Blob([data]);
+          const contentType = type || blob.type || null;
+          const { endpoint } = current;
+          const chunkSize = endpoint === null ? current.bridgeChunkSize : current.chunkSize;
+          const id = nextId();
+          let offset = 0;
+          do {
+            // NOTE: the chunk has no type, so that posting it is a simple request without a preflight.
+            const chunk = blob.slice(offset, offset + chunkSize);
+            const last = offset + chunk.size >= blob.size;
+            if (endpoint === null) {
+              const data = await base64(chunk);
+              await bridge.request(current.request, { name, id, offset, last, type: contentType, data });
+            } else {
+              const query = new URLSearchParams({ offset, last });
+              if (contentType !== null) {
+                query.set("type", contentType);
+              }
+              const url = `${endpoint}${encodeURIComponent(name)}/${id}?${query}`;
+              const response = await fetch(url, { method: "POST", body: chunk });
+              if (!response.ok) {
+                throw new Error((await response.text()) || `transfer failed with status ${response.status}`);
+              }
+            }
+            offset += chunk.size;
+          } while (offset < blob.size);
+          return blob.size;
+        },
+      }),
+    });
+  }
+  window.__TAURI_WEBVIEW_UTIL_TRANSFER__.configure(config);
+})
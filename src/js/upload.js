@@ -0,0 +1,76 @@
+(function () {
+  const bridge = window.__TAURI_WEBVIEW_UTIL__;
+  if (!bridge || window.__TAURI_WEBVIEW_UTIL_UPLOAD__) {
+    return;
+  }
+  Object.defineProperty(window, "__TAURI_WEBVIEW_UTIL_UPLOAD__", { value: true });
+
+  let next = 0;
+  const size = (body) => {
+    if (typeof body === "string" || body instanceof URLSearchParams) {
+      return new Blob([String(body)]).size;
+    }
+    if (body instanceof Blob) {
+      return body.size;
+    }
+    if (body instanceof ArrayBuffer || ArrayBuffer.isView(body)) {
+      return body.byteLength;
+    }
+    // NOTE: the encoded size of form data is only known once it is sent.
+    return null;
+  };
+  const resolve = (url) => {
+    try {
+      return new URL(url, document.baseURI).href;
+    } catch (_) {
+      return null;
+    }
+  };
+  const report = (upload, phase, sent, total) =>
+    bridge.post("upload", { ...upload, phase, sent, total: total === undefined ? upload.total : total });
+
+  // NOTE: `fetch` has no upload progress, so fetch uploads are only reported when they start and
+  // once the response (or failure) arrives.
+  const fetch = window.fetch;
+  window.fetch = function (input, init) {
+    const body = init && init.body;
+    const url = resolve(input instanceof Request ? input.url : String(input));
+    if (body === undefined || body === null || url === null) {
+      return fetch.apply(this, arguments);
+    }
+    const upload = { id: next++, url, method: (init.method || "GET").toUpperCase(), total: size(body) };
+    report(upload, "started", 0);
+    const response = fetch.apply(this, arguments);
+    response.then(
+      () => report(upload, "completed", upload.total || 0),
+      () => report(upload, "failed", 0),
+    );
+    return response;
+  };
+
+  const open = XMLHttpRequest.prototype.open;
+  const send = XMLHttpRequest.prototype.send;
+  const requests = new WeakMap();
+  XMLHttpRequest.prototype.open = function (method, url) {
+    requests.set(this, { method: String(method).toUpperCase(), url: resolve(String(url)) });
+    return open.apply(this, arguments);
+  };
+  XMLHttpRequest.prototype.send = function (body) {
+    const request = requests.get(this);
+    if (request && request.url !== null && body !== undefined && body !== null) {
+      const upload = { id: next++, url: request.url, method: request.method, total: size(body) };
+      let sent = 0;
+      const progress = (phase) => (event) => {
+        sent = event.loaded;
+        report(upload, phase, event.loaded, event.lengthComputable ? event.total : upload.total);
+      };
+      this.upload.addEventListener("loadstart", progress("started"));
+      this.upload.addEventListener("progress", progress("progress"));
+      this.upload.addEventListener("load", progress("completed"));
+      for (const type of ["error", "abort", "timeout"]) {
+        this.upload.addEventListener(type, () => report(upload, "failed", sent));
+      }
+    }
+    return send.apply(this, arguments);
+  };
+})();
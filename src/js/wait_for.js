@@ -0,0 +1,52 @@
+(function (check, timeout) {
+  return new Promise((resolve, reject) => {
+    const cleanup = [];
+    let done = false;
+    let checking = false;
+    let pending = false;
+    const finish = (callback) => {
+      done = true;
+      cleanup.forEach((dispose) => dispose());
+      callback();
+    };
+    // NOTE: checks do not overlap, and changes that happen during a check trigger another one once
+    // it is done.
+    const run = async () => {
+      if (done) {
+        return;
+      }
+      if (checking) {
+        pending = true;
+        return;
+      }
+      checking = true;
+      try {
+        if (await check()) {
+          finish(() => resolve(true));
+        }
+      } catch (error) {
+        finish(() => reject(error));
+      } finally {
+        checking = false;
+      }
+      if (pending && !done) {
+        pending = false;
+        run();
+      }
+    };
+    const observer = new MutationObserver(run);
+    observer.observe(document, { attributes: true, characterData: true, childList: true, subtree: true });
+    cleanup.push(() => observer.disconnect());
+    for (const type of ["hashchange", "load", "popstate"]) {
+      window.addEventListener(type, run);
+      cleanup.push(() => window.removeEventListener(type, run));
+    }
+    // NOTE: state outside of the DOM (like `history.pushState` or globals) does not notify
+    // anything, so it is also checked on a slow interval.
+    const interval = setInterval(run, 250);
+    cleanup.push(() => clearInterval(interval));
+    const deadline = setTimeout(() => finish(() => resolve(false)), timeout);
+    cleanup.push(() => clearTimeout(deadline));
+    run();
+  });
+})
@@ -0,0 +1,68 @@
+(function (watchers) {
+  const bridge = window.__TAURI_WEBVIEW_UTIL__;
+  if (!bridge) {
+    return;
+  }
+
+  if (!window.__TAURI_WEBVIEW_UTIL_ELEMENT_RECT__) {
+    let current = {};
+    let scheduled = false;
+    const reported = new Map();
+    const measure = (selector) => {
+      const element = document.querySelector(selector);
+      if (element === null) {
+        return null;
+      }
+      const rect = element.getBoundingClientRect();
+      return {
+        x: rect.x,
+        y: rect.y,
+        width: rect.width,
+        height: rect.height,
+        devicePixelRatio: window.devicePixelRatio,
+      };
+    };
+    const update = () => {
+      scheduled = false;
+      for (const [id, selector] of Object.entries(current)) {
+        const rect = measure(selector);
+        const json = JSON.stringify(rect);
+        if (reported.get(id) !== json) {
+          reported.set(id, json);
+          bridge.post("element-rect", { id: Number(id), rect });
+        }
+      }
+    };
+    // NOTE: bounds are measured at most once per frame, however many changes happened during it.
+    const schedule = () => {
+      if (!scheduled) {
+        scheduled = true;
+        requestAnimationFrame(update);
+      }
+    };
+    window.addEventListener("scroll", schedule, { capture: true, passive: true });
+    window.addEventListener("resize", schedule);
+    // NOTE: layout changes that neither scroll nor resize the viewport, like elements being added or
+    // restyled, are noticed through the mutations of the document.
+    new MutationObserver(schedule).observe(document, {
+      attributes: true,
+      characterData: true,
+      childList: true,
+      subtree: true,
+    });
+    Object.defineProperty(window, "__TAURI_WEBVIEW_UTIL_ELEMENT_RECT__", {
+      value: Object.freeze({
+        configure(watchers) {
+          current = watchers;
+          for (const id of reported.keys()) {
+            if (!(id in watchers)) {
+              reported.delete(id);
+            }
+          }
+          schedule();
+        },
+      }),
+    });
+  }
+  window.__TAURI_WEBVIEW_UTIL_ELEMENT_RECT__.configure(watchers);
+})
@@ -0,0 +1,61 @@
+(function (config) {
+  const bridge = window.__TAURI_WEBVIEW_UTIL__;
+  if (!bridge) {
+    return;
+  }
+
+  if (!window.__TAURI_WEBVIEW_UTIL_WEBSOCKET__) {
+    let current = { capturePayloads: false };
+    const encoder = new TextEncoder();
+    const base64 = (bytes) => {
+      let binary = "";
+      for (let index = 0; index < bytes.length; index += 1) {
+        binary += String.fromCharCode(bytes[index]);
+      }
+      return btoa(binary);
+    };
+    // NOTE: opcodes are those of the WebSocket protocol, 1 for text and 2 for binary frames. Blobs
+    // can only be read asynchronously, so their payload is never captured.
+    const report = (url, direction, data) => {
+      let frame;
+      if (typeof data === "string") {
+        frame = { opcode: 1, size: encoder.encode(data).length, payload: data };
+      } else if (data instanceof Blob) {
+        frame = { opcode: 2, size: data.size, payload: null };
+      } else if (data instanceof ArrayBuffer || ArrayBuffer.isView(data)) {
+        const bytes =
+          data instanceof ArrayBuffer ? new Uint8Array(data) : new Uint8Array(data.buffer, data.byteOffset, data.byteLength);
+        frame = { opcode: 2, size: bytes.length, payload: current.capturePayloads ? base64(bytes) : null };
+      } else {
+        return;
+      }
+      if (!current.capturePayloads) {
+        frame.payload = null;
+      }
+      bridge.post("websocket-frame", { url, direction, ...frame });
+    };
+
+    const NativeWebSocket = window.WebSocket;
+    class WebSocket extends NativeWebSocket {
+      constructor(...args) {
+        super(...args);
+        this.addEventListener("message", (event) => report(this.url, "received", event.data));
+      }
+
+      send(data) {
+        report(this.url, "sent", data);
+        return super.send(data);
+      }
+    }
+    Object.defineProperty(window, "WebSocket", { value: WebSocket, writable: true, configurable: true });
+
+    Object.defineProperty(window, "__TAURI_WEBVIEW_UTIL_WEBSOCKET__", {
+      value: Object.freeze({
+        configure(config) {
+          current = config;
+        },
+      }),
+    });
+  }
+  window.__TAURI_WEBVIEW_UTIL_WEBSOCKET__.configure(config);
+})
@@ -11,31 +11,1510 @@ mod webkit2gtk;
 mod wkwebview;
 
 #[cfg(target_os = "windows")]
-mod webview2;
+pub mod webview2;
 
+#[cfg(any(
+    target_os = "linux",
+    target_os = "dragonfly",
+    target_os = "freebsd",
+    target_os = "openbsd",
+    target_os = "netbsd"
+))]
+use self::webkit2gtk as platform;
+
+#[cfg(target_os = "macos")]
+use self::wkwebview as platform;
+
+#[cfg(target_os = "windows")]
+use self::webview2 as platform;
+
+mod async_js;
+mod auth;
+mod backend;
+mod badge;
+mod bandwidth;
+pub mod blocking;
+mod bridge;
+mod broadcast;
+#[cfg(feature = "browser-import")]
+mod browser_import;
+mod budget;
+mod cache_usage;
+mod cancel;
+mod certificate;
+mod color_scheme;
+mod content_rules;
+mod context_action;
 mod cookie;
+#[cfg(any(feature = "browser-import", feature = "profile-migration"))]
+mod cookie_db;
+#[cfg(feature = "debug-tools")]
+mod cookie_debug;
+mod cookie_diagnostics;
+mod cookie_host;
+#[cfg(feature = "cookie-store")]
+mod cookie_interop;
+mod cookie_jar;
+mod cookie_pattern;
+mod cookie_policy;
+mod cookie_query;
+#[cfg(feature = "cookie-snapshot")]
+mod cookie_snapshot;
+mod cookie_stats;
+mod cookie_watch;
+mod coords;
+mod credentials;
+mod csp;
+#[cfg(feature = "cli")]
+mod debug_socket;
+mod device;
+mod document;
+mod downloads;
+mod dry_run;
+mod element;
+#[cfg(feature = "rust-embed")]
+mod embedded;
+mod engine_context;
+mod error;
+mod event_bus;
+mod event_source;
+mod fetch;
+mod form;
+mod frame;
+mod glob;
+#[cfg(feature = "async-graphql")]
+pub mod graphql;
+mod history;
+#[cfg(feature = "hot-reload")]
+mod hot_reload;
+mod init_script;
+mod integrity;
+mod load_error;
+mod local_url;
+mod media;
+mod mock;
+#[cfg(feature = "mock")]
+mod mock_webview;
+mod native_object;
+mod navigation_events;
+mod navigation_policy;
+mod navigation_queue;
+#[cfg(feature = "notification")]
+mod notification;
+mod options;
+mod page_cache;
+mod partition;
+mod pdf;
+mod permission;
+#[cfg(feature = "plugin")]
+pub mod plugin;
+mod popup;
+mod preload;
+mod prerender;
+mod print;
+mod profile;
+mod profile_health;
+#[cfg(feature = "profile-migration")]
+mod profile_migration;
+mod proxy;
+mod read_only;
+mod recording;
+mod referrer_policy;
+mod request;
+mod request_inspection;
+mod response_capture;
+mod route;
+mod safe_mode;
+mod scheme_handler;
+mod screenshot;
+mod script_result;
+mod script_scope;
+mod self_events;
+mod serve_directory;
+#[cfg(feature = "tower")]
+mod service;
+mod session;
+mod shared_buffer;
+mod single_file;
+mod speech;
+mod state;
+mod storage;
+mod storage_events;
+mod stream;
+mod structured_clone;
+mod text_nodes;
+#[cfg(feature = "tracing")]
+mod trace;
+mod transfer;
+mod upload;
+mod url_filter;
+mod user_agent;
+mod user_script;
+mod virtual_host;
+mod wait;
+mod website_data;
+mod websocket;
+pub mod window_builder_ext;
+mod zoom;
+
+pub use async_js::ScriptRejection;
+pub use auth::{AuthChallenge, Credentials};
+pub use backend::WebviewBackend;
+pub use badge::Badge;
+pub use bandwidth::TrafficStats;
+pub use broadcast::BroadcastSender;
+#[cfg(feature = "browser-import")]
+pub use browser_import::{import_from_browser, Browser};
+pub use budget::{BudgetAction, BudgetExceeded, PageBudget};
+pub use cache_usage::OriginUsage;
+pub use cancel::{CancellationToken, WebviewFutureExt, WebviewStreamExt};
+pub use certificate::{CertDecision, CertificateErrorInfo, CertificateErrorKind};
+pub use color_scheme::ColorScheme;
+pub use content_rules::ContentRuleList;
 pub use cookie::Cookie;
+#[cfg(feature = "debug-tools")]
+pub use cookie_debug::RawCookieDebug;
+pub use cookie_diagnostics::{CookieWriteDiagnostic, CookieWriteProbe, CookieWriteStatus};
+pub use cookie_host::{CookieHost, CookieHostScheme};
+#[cfg(feature = "cookie-store")]
+pub use cookie_interop::{sync_cookies_from_store, sync_cookies_to_store};
+pub use cookie_jar::{deserialize_cookies, serialize_cookies, CookieJarFormat};
+pub use cookie_pattern::{CookieParty, CookiePattern, CookiePatternBuilder, CookiePatternSpec};
+pub use cookie_policy::CookiePolicyInfo;
+pub use cookie_query::{CookiePage, CookieQuery, CookieSortKey};
+#[cfg(feature = "cookie-snapshot")]
+pub use cookie_snapshot::CookieSnapshotter;
+pub use cookie_stats::{CookieStats, DomainCookieStats};
+pub use cookie_watch::{
+    cookie_polling,
+    set_cookie_polling,
+    ChangeOrigin,
+    CookieChange,
+    CookieChangeEvent,
+    CookiePollMode,
+    CookiePolling,
+};
+pub use credentials::SubmittedCredentials;
+pub use csp::{CspOptions, CspViolation};
+#[cfg(feature = "cli")]
+pub use debug_socket::{DEBUG_ADDR_VAR, DEFAULT_DEBUG_ADDR};
+pub use device::DeviceProfile;
+pub use downloads::{DownloadEvent, DownloadHandle};
+pub use dry_run::DryRunAction;
+pub use element::Rect;
+#[cfg(feature = "rust-embed")]
+pub use embedded::embedded_protocol;
+pub use engine_context::{sharing_topology, EngineContext, SharingTopology};
+pub use error::{Error, Result};
+pub use event_bus::{EventBus, LabeledEvent, WebviewEvent};
+pub use event_source::{EventSourceEvent, EventSourceState};
+pub use fetch::FetchedResource;
+pub use form::FieldFill;
+pub use frame::FrameInfo;
+pub use history::HistoryEntry;
+pub use integrity::{IntegrityFailure, IntegrityFailureKind};
+pub use load_error::{LoadError, LoadErrorKind};
+pub use media::MediaType;
+pub use mock::{MockHandler, MockReply, MockRequest, MockResponse, MockRoute};
+#[cfg(feature = "mock")]
+pub use mock_webview::MockWebView;
+pub use native_object::NativeObject;
+pub use navigation_events::{NavigationEvent, NavigationResult};
+pub use navigation_policy::{HttpsOnlyMode, RewriteRule};
+pub use options::{configure, options, Options, OptionsBuilder, RetryPolicy};
+pub use partition::{remove_storage_partition, storage_partition_directory};
+pub use pdf::PdfViewerSettings;
+pub use permission::{PermissionDecision, PermissionKind, PermissionRequest};
+pub use preload::{preload_engine, PreloadConfig, PreloadTimings};
+pub use print::{PdfOptions, PrintAction};
+pub use profile::{ProfileInfo, SessionKind};
+pub use profile_health::{profile_health_check, repair_profile, ProfileHealth, ProfileIssue};
+#[cfg(feature = "profile-migration")]
+pub use profile_migration::{migrate_profile, MigrationOptions, MigrationReport};
+pub use proxy::{ProxyConfig, ProxyCredentials, ProxyScheme};
+pub use recording::{RecordOptions, RecordingHandle, VideoFrame};
+pub use referrer_policy::ReferrerPolicy;
+pub use request_inspection::{OutgoingRequest, RequestInspection};
+pub use response_capture::{CaptureFilter, CapturedResponse};
+pub use route::{RouteChange, RouteChangeKind};
+pub use safe_mode::{safe_mode, SafeMode, SafeModeReport};
+pub use scheme_handler::{
+    scheme_protocol,
+    scheme_protocol_with_options,
+    SchemeOptions,
+    SchemeRequest,
+    SchemeResponse,
+    SchemeStreamResponse,
+};
+pub use screenshot::{ScreenshotFormat, ScreenshotOptions, ScreenshotRegion};
+pub use script_scope::ScriptScope;
+pub use serve_directory::{directory_protocol, directory_protocol_with_options, ServeDirectoryOptions};
+#[cfg(feature = "tower")]
+pub use service::{WebviewRequest, WebviewResponse, WebviewService};
+pub use session::{LocalStorageSnapshot, SessionSnapshot};
+pub use shared_buffer::{buffer_protocol, SharedBuffer, BUFFER_SCHEME};
+pub use storage::StorageArea;
+pub use storage_events::StorageChange;
+pub use stream::{set_stream_config, stream_config, Overflow, StreamConfig};
+pub use structured_clone::{CloneValue, TypedArrayKind};
+pub use text_nodes::TextNode;
+pub use transfer::{transfer_protocol, ByteTransfer, TRANSFER_SCHEME};
+pub use upload::{UploadEvent, UploadPhase};
+pub use user_script::{InjectionTime, ScriptHandle, UserScript};
+pub use virtual_host::CorsPolicy;
+pub use wait::WaitCondition;
+pub use website_data::{DataKinds, DataRecord};
+pub use websocket::{WsDirection, WsEvent};
 
-use futures::future::BoxFuture;
-use std::sync::{Arc, Mutex, MutexGuard};
+use error::WindowContext;
+use futures::{future::BoxFuture, prelude::*, stream::BoxStream};
+use std::{
+    collections::BTreeMap,
+    path::PathBuf,
+    sync::{Arc, Mutex, MutexGuard},
+    time::Duration,
+};
 use url::Url;
 
 pub type BoxError = Box<dyn std::error::Error + Send + Sync + 'static>;
 pub type BoxResult<T> = Result<T, BoxError>;
 
 pub trait WebviewExt: private::WebviewExtSealed {
-    fn webview_clear_cache(&self) -> BoxFuture<BoxResult<()>>;
-    fn webview_delete_cookies(&self, url: Option<Url>) -> BoxFuture<BoxResult<Vec<Cookie>>>;
-    fn webview_get_cookies(&self, url: Option<Url>) -> BoxFuture<BoxResult<Vec<Cookie>>>;
-    fn webview_navigate(&self, url: Url) -> BoxResult<()>;
+    fn webview_clear_cache(&self) -> BoxFuture<Result<()>>;
+    fn webview_delete_cookies(&self, url: Option<Url>) -> BoxFuture<Result<Vec<Cookie>>>;
+    fn webview_get_cookies(&self, url: Option<Url>) -> BoxFuture<Result<Vec<Cookie>>>;
+    fn webview_navigate(&self, url: Url) -> Result<()>;
+
+    fn webview_bandwidth_stats(&self) -> BoxFuture<Result<BTreeMap<String, TrafficStats>>> {
+        let window = self.window();
+        bandwidth::stats(window)
+            .map(move |result| result.window_context(window, "read bandwidth stats"))
+            .boxed()
+    }
+
+    fn webview_reset_bandwidth_stats(&self) -> BoxFuture<Result<()>> {
+        let window = self.window();
+        bandwidth::reset(window)
+            .map(move |result| result.window_context(window, "reset bandwidth stats"))
+            .boxed()
+    }
+
+    fn webview_block_urls(&self, patterns: Vec<String>) -> BoxFuture<Result<()>> {
+        let window = self.window();
+        url_filter::block(window, patterns)
+            .map(move |result| result.window_context(window, "block urls"))
+            .boxed()
+    }
+
+    fn webview_unblock_urls(&self, patterns: Vec<String>) -> BoxFuture<Result<()>> {
+        let window = self.window();
+        url_filter::unblock(window, patterns)
+            .map(move |result| result.window_context(window, "unblock urls"))
+            .boxed()
+    }
+
+    fn webview_set_content_rules(&self, rules: ContentRuleList) -> BoxFuture<Result<()>> {
+        let window = self.window();
+        content_rules::set(window, rules)
+            .map(move |result| result.window_context(window, "set content rules"))
+            .boxed()
+    }
+
+    fn webview_broadcast_channel(
+        &self,
+        name: String,
+    ) -> BoxFuture<Result<(BroadcastSender, BoxStream<'static, Result<serde_json::Value>>)>> {
+        let window = self.window();
+        broadcast::channel(window, name)
+            .map(move |result| result.window_context(window, "open broadcast channel"))
+            .boxed()
+    }
+
+    fn webview_bridge_badging(&self, handler: impl Fn(Badge) + Send + Sync + 'static) -> BoxFuture<Result<()>> {
+        let window = self.window();
+        badge::install(window, handler)
+            .map(move |result| result.window_context(window, "bridge badging"))
+            .boxed()
+    }
+
+    #[cfg(feature = "notification")]
+    fn webview_bridge_notifications(&self) -> BoxFuture<Result<()>> {
+        let window = self.window();
+        notification::install(window)
+            .map(move |result| result.window_context(window, "bridge notifications"))
+            .boxed()
+    }
+
+    fn webview_serve_directory(
+        &self,
+        scheme: &str,
+        root: PathBuf,
+        options: ServeDirectoryOptions,
+    ) -> BoxFuture<Result<()>> {
+        let window = self.window();
+        let handler = serve_directory::handler(root, options);
+        let handler = scheme_handler::with_options(window, scheme.into(), handler);
+        platform::serve_scheme(window, scheme.into(), handler)
+            .map(move |result| result.window_context(window, "serve directory"))
+            .boxed()
+    }
+
+    #[cfg(feature = "rust-embed")]
+    fn webview_serve_embedded<A: rust_embed::RustEmbed + 'static>(&self, scheme: &str) -> BoxFuture<Result<()>> {
+        let window = self.window();
+        let handler = embedded::handler::<A>();
+        let handler = scheme_handler::with_options(window, scheme.into(), handler);
+        platform::serve_scheme(window, scheme.into(), handler)
+            .map(move |result| result.window_context(window, "serve embedded files"))
+            .boxed()
+    }
+
+    fn webview_map_virtual_host(&self, host: &str, folder: PathBuf, cors: CorsPolicy) -> BoxFuture<Result<Url>> {
+        let window = self.window();
+        let host = host.to_ascii_lowercase();
+        async move {
+            virtual_host::validate_host(&host)?;
+            platform::map_virtual_host(window, host, folder, cors).await
+        }
+        .map(move |result: BoxResult<_>| result.window_context(window, "map virtual host"))
+        .boxed()
+    }
+
+    fn webview_register_scheme_handler(
+        &self,
+        scheme: &str,
+        handler: impl Fn(SchemeRequest) -> BoxFuture<'static, SchemeResponse> + Send + Sync + 'static,
+    ) -> BoxFuture<Result<()>> {
+        let window = self.window();
+        let handler: scheme_handler::SchemeHandler = Arc::new(handler);
+        let handler = scheme_handler::with_options(window, scheme.into(), handler);
+        platform::serve_scheme(window, scheme.into(), handler)
+            .map(move |result| result.window_context(window, "register scheme handler"))
+            .boxed()
+    }
+
+    fn webview_register_streaming_scheme_handler(
+        &self,
+        scheme: &str,
+        handler: impl Fn(SchemeRequest) -> BoxFuture<'static, SchemeStreamResponse> + Send + Sync + 'static,
+    ) -> BoxFuture<Result<()>> {
+        let window = self.window();
+        let handler: scheme_handler::SchemeStreamHandler = Arc::new(handler);
+        let handler = scheme_handler::with_options(window, scheme.into(), handler);
+        platform::serve_scheme_stream(window, scheme.into(), handler)
+            .map(move |result| result.window_context(window, "register streaming scheme handler"))
+            .boxed()
+    }
+
+    fn webview_set_scheme_options(&self, scheme: &str, options: SchemeOptions) -> BoxFuture<Result<()>> {
+        let window = self.window();
+        scheme_handler::set_options(window, scheme.into(), options)
+            .map(move |result| result.window_context(window, "set scheme options"))
+            .boxed()
+    }
+
+    #[cfg(feature = "hot-reload")]
+    fn webview_watch_and_reload(
+        &self,
+        paths: Vec<PathBuf>,
+        debounce: Duration,
+        preserve_scroll: bool,
+    ) -> BoxFuture<Result<()>> {
+        let window = self.window();
+        hot_reload::watch(window, paths, debounce, preserve_scroll)
+            .map(move |result| result.window_context(window, "watch and reload"))
+            .boxed()
+    }
+
+    fn webview_set_error_page(
+        &self,
+        renderer: impl Fn(&LoadError) -> String + Send + Sync + 'static,
+    ) -> BoxFuture<Result<()>> {
+        let window = self.window();
+        load_error::set_error_page(window, renderer)
+            .map(move |result| result.window_context(window, "set error page"))
+            .boxed()
+    }
+
+    fn webview_capture_submitted_credentials(
+        &self,
+        origins: Vec<Url>,
+        handler: impl Fn(SubmittedCredentials) + Send + Sync + 'static,
+    ) -> BoxFuture<Result<()>> {
+        let window = self.window();
+        credentials::capture(window, origins, handler)
+            .map(move |result| result.window_context(window, "capture submitted credentials"))
+            .boxed()
+    }
+
+    fn webview_capture_responses(&self, filter: CaptureFilter) -> BoxFuture<Result<()>> {
+        let window = self.window();
+        response_capture::capture(window, filter)
+            .map(move |result| result.window_context(window, "capture responses"))
+            .boxed()
+    }
+
+    #[cfg(feature = "clipboard")]
+    fn webview_copy_link(&self, url: Url) -> Result<()> {
+        let window = self.window();
+        context_action::copy_link(window, url).window_context(window, "copy link")
+    }
+
+    fn webview_set_read_only(&self, enabled: bool) -> Result<()> {
+        let window = self.window();
+        read_only::set(window, enabled).window_context(window, "set read-only mode")
+    }
+
+    fn webview_is_read_only(&self) -> Result<bool> {
+        let window = self.window();
+        read_only::is_enabled(window).window_context(window, "read read-only mode")
+    }
+
+    fn webview_set_dry_run(&self, enabled: bool) -> Result<()> {
+        let window = self.window();
+        dry_run::set(window, enabled).window_context(window, "set dry-run mode")
+    }
+
+    fn webview_is_dry_run(&self) -> Result<bool> {
+        let window = self.window();
+        dry_run::is_enabled(window).window_context(window, "read dry-run mode")
+    }
+
+    fn webview_take_dry_run_actions(&self) -> Result<Vec<DryRunAction>> {
+        let window = self.window();
+        dry_run::take_actions(window).window_context(window, "take dry-run actions")
+    }
+
+    fn webview_clear_data(&self, kinds: DataKinds) -> BoxFuture<Result<()>> {
+        let window = self.window();
+        read_only::guard(
+            window,
+            dry_run::guard(
+                window,
+                || DryRunAction::ClearData(kinds),
+                platform::clear_data(window, kinds),
+            ),
+        )
+        .map(move |result| result.window_context(window, "clear data"))
+        .boxed()
+    }
+
+    fn webview_profile_info(&self) -> BoxFuture<Result<ProfileInfo>> {
+        let window = self.window();
+        platform::profile_info(window)
+            .map(move |result| result.window_context(window, "read profile info"))
+            .boxed()
+    }
+
+    fn webview_engine_context(&self) -> BoxFuture<Result<EngineContext>> {
+        let window = self.window();
+        platform::engine_context(window)
+            .map(move |result| result.window_context(window, "read engine context"))
+            .boxed()
+    }
+
+    fn webview_list_data_records(&self, kinds: DataKinds) -> BoxFuture<Result<Vec<DataRecord>>> {
+        let window = self.window();
+        platform::list_data_records(window, kinds)
+            .map(move |result| result.window_context(window, "list data records"))
+            .boxed()
+    }
+
+    fn webview_remove_data_records(&self, records: Vec<DataRecord>) -> BoxFuture<Result<()>> {
+        let window = self.window();
+        let action = DryRunAction::RemoveDataRecords(records.clone());
+        read_only::guard(
+            window,
+            dry_run::guard(window, || action, platform::remove_data_records(window, records)),
+        )
+        .map(move |result| result.window_context(window, "remove data records"))
+        .boxed()
+    }
+
+    fn webview_get_cache_usage(&self) -> BoxFuture<Result<Vec<OriginUsage>>> {
+        let window = self.window();
+        platform::cache_usage(window)
+            .map(move |result| result.window_context(window, "get cache usage"))
+            .boxed()
+    }
+
+    fn webview_clear_cache_for_origins(&self, origins: Vec<Url>) -> BoxFuture<Result<()>> {
+        let window = self.window();
+        let action = DryRunAction::ClearCacheForOrigins(origins.clone());
+        read_only::guard(
+            window,
+            dry_run::guard(window, || action, cache_usage::clear_for_origins(window, origins)),
+        )
+        .map(move |result| result.window_context(window, "clear cache for origins"))
+        .boxed()
+    }
+
+    fn webview_disable_bfcache(&self) -> BoxFuture<Result<()>> {
+        let window = self.window();
+        page_cache::disable(window)
+            .map(move |result| result.window_context(window, "disable bfcache"))
+            .boxed()
+    }
+
+    fn webview_set_bypass_service_worker(&self, bypass: bool) -> BoxFuture<Result<()>> {
+        let window = self.window();
+        platform::set_bypass_service_worker(window, bypass)
+            .map(move |result| result.window_context(window, "set bypass service worker"))
+            .boxed()
+    }
+
+    fn webview_load_html(&self, html: String, base_url: Option<Url>) -> BoxFuture<Result<()>> {
+        let window = self.window();
+        read_only::guard(window, platform::load_html(window, html, base_url))
+            .map(move |result| result.window_context(window, "load html"))
+            .boxed()
+    }
+
+    fn webview_reload(&self, ignore_cache: bool) -> BoxFuture<Result<()>> {
+        let window = self.window();
+        navigation_events::tag(window);
+        platform::reload(window, ignore_cache)
+            .map(move |result| result.window_context(window, "reload"))
+            .boxed()
+    }
+
+    fn webview_stop_loading(&self) -> BoxFuture<Result<()>> {
+        let window = self.window();
+        platform::stop_loading(window)
+            .map(move |result| result.window_context(window, "stop loading"))
+            .boxed()
+    }
+
+    fn webview_open_devtools(&self) -> BoxFuture<Result<()>> {
+        let window = self.window();
+        platform::open_devtools(window)
+            .map(move |result| result.window_context(window, "open devtools"))
+            .boxed()
+    }
+
+    fn webview_close_devtools(&self) -> BoxFuture<Result<()>> {
+        let window = self.window();
+        platform::close_devtools(window)
+            .map(move |result| result.window_context(window, "close devtools"))
+            .boxed()
+    }
+
+    fn webview_set_devtools_enabled(&self, enabled: bool) -> BoxFuture<Result<()>> {
+        let window = self.window();
+        platform::set_devtools_enabled(window, enabled)
+            .map(move |result| result.window_context(window, "set devtools enabled"))
+            .boxed()
+    }
+
+    fn webview_set_muted(&self, muted: bool) -> BoxFuture<Result<()>> {
+        let window = self.window();
+        platform::set_muted(window, muted)
+            .map(move |result| result.window_context(window, "set muted"))
+            .boxed()
+    }
+
+    fn webview_is_muted(&self) -> BoxFuture<Result<bool>> {
+        let window = self.window();
+        platform::is_muted(window)
+            .map(move |result| result.window_context(window, "read muted state"))
+            .boxed()
+    }
+
+    fn webview_is_playing_audio(&self) -> BoxFuture<Result<bool>> {
+        let window = self.window();
+        platform::is_playing_audio(window)
+            .map(move |result| result.window_context(window, "read audio state"))
+            .boxed()
+    }
+
+    fn webview_set_color_scheme(&self, scheme: ColorScheme) -> BoxFuture<Result<()>> {
+        let window = self.window();
+        platform::set_color_scheme(window, scheme)
+            .map(move |result| result.window_context(window, "set color scheme"))
+            .boxed()
+    }
+
+    fn webview_emulate_device(&self, profile: DeviceProfile) -> BoxFuture<Result<()>> {
+        let window = self.window();
+        device::emulate(window, Some(profile))
+            .map(move |result| result.window_context(window, "emulate device"))
+            .boxed()
+    }
+
+    fn webview_clear_device_emulation(&self) -> BoxFuture<Result<()>> {
+        let window = self.window();
+        device::emulate(window, None)
+            .map(move |result| result.window_context(window, "clear device emulation"))
+            .boxed()
+    }
+
+    fn webview_emulate_media(&self, media: Option<MediaType>) -> BoxFuture<Result<()>> {
+        let window = self.window();
+        media::emulate(window, media)
+            .map(move |result| result.window_context(window, "emulate media"))
+            .boxed()
+    }
+
+    fn webview_on_permission_request(
+        &self,
+        handler: impl Fn(PermissionRequest) -> PermissionDecision + Send + Sync + 'static,
+    ) -> BoxFuture<Result<()>> {
+        let window = self.window();
+        permission::on_request(window, handler)
+            .map(move |result| result.window_context(window, "handle permission requests"))
+            .boxed()
+    }
+
+    fn webview_on_certificate_error(
+        &self,
+        handler: impl Fn(CertificateErrorInfo) -> CertDecision + Send + Sync + 'static,
+    ) -> BoxFuture<Result<()>> {
+        let window = self.window();
+        certificate::on_error(window, handler)
+            .map(move |result| result.window_context(window, "handle certificate errors"))
+            .boxed()
+    }
+
+    fn webview_on_auth_challenge(
+        &self,
+        handler: impl Fn(AuthChallenge) -> BoxFuture<'static, Option<Credentials>> + Send + Sync + 'static,
+    ) -> BoxFuture<Result<()>> {
+        let window = self.window();
+        auth::on_challenge(window, handler)
+            .map(move |result| result.window_context(window, "handle authentication challenges"))
+            .boxed()
+    }
+
+    fn webview_csp_reports(&self) -> BoxFuture<Result<BoxStream<'static, Result<CspViolation>>>> {
+        let window = self.window();
+        csp::reports(window)
+            .map(move |result| result.window_context(window, "subscribe to csp reports"))
+            .boxed()
+    }
+
+    fn webview_downloads(&self) -> BoxFuture<Result<BoxStream<'static, Result<DownloadEvent>>>> {
+        let window = self.window();
+        downloads::subscribe(window)
+            .map(move |result| result.window_context(window, "subscribe to downloads"))
+            .boxed()
+    }
+
+    fn webview_event_source_events(&self) -> BoxFuture<Result<BoxStream<'static, Result<EventSourceEvent>>>> {
+        let window = self.window();
+        event_source::events(window)
+            .map(move |result| result.window_context(window, "subscribe to event source events"))
+            .boxed()
+    }
+
+    fn webview_storage_events(&self) -> BoxFuture<Result<BoxStream<'static, Result<StorageChange>>>> {
+        let window = self.window();
+        storage_events::events(window)
+            .map(move |result| result.window_context(window, "subscribe to storage events"))
+            .boxed()
+    }
+
+    fn webview_upload_events(&self) -> BoxFuture<Result<BoxStream<'static, Result<UploadEvent>>>> {
+        let window = self.window();
+        upload::events(window)
+            .map(move |result| result.window_context(window, "subscribe to upload events"))
+            .boxed()
+    }
+
+    fn webview_websocket_events(
+        &self,
+        capture_payloads: bool,
+    ) -> BoxFuture<Result<BoxStream<'static, Result<WsEvent>>>> {
+        let window = self.window();
+        websocket::events(window, capture_payloads)
+            .map(move |result| result.window_context(window, "subscribe to websocket events"))
+            .boxed()
+    }
+
+    fn webview_watch_cookies(
+        &self,
+        pattern: CookiePattern,
+    ) -> BoxFuture<Result<BoxStream<'static, Result<CookieChangeEvent>>>> {
+        let window = self.window();
+        cookie_watch::watch(window, pattern)
+            .map(move |result| result.window_context(window, "watch cookies"))
+            .boxed()
+    }
+
+    fn webview_cookie_policy_info(&self) -> BoxFuture<Result<CookiePolicyInfo>> {
+        let window = self.window();
+        cookie_policy::info(window)
+            .map(move |result| result.window_context(window, "read cookie policy info"))
+            .boxed()
+    }
+
+    fn webview_cookie_write_diagnostics(&self, origins: Vec<Url>) -> BoxFuture<Result<Vec<CookieWriteDiagnostic>>> {
+        let window = self.window();
+        cookie_diagnostics::diagnose(window, origins)
+            .map(move |result| result.window_context(window, "diagnose cookie writes"))
+            .boxed()
+    }
+
+    fn webview_enforce_budget(&self, budget: PageBudget) -> BoxFuture<Result<()>> {
+        let window = self.window();
+        budget::enforce(window, budget)
+            .map(move |result| result.window_context(window, "enforce page budget"))
+            .boxed()
+    }
+
+    fn webview_expose_object(&self, name: &str, object: impl NativeObject) -> BoxFuture<Result<()>> {
+        let window = self.window();
+        native_object::expose(window, name, object)
+            .map(move |result| result.window_context(window, "expose object"))
+            .boxed()
+    }
+
+    fn webview_eval_js(&self, script: &str) -> BoxFuture<Result<serde_json::Value>> {
+        let window = self.window();
+        read_only::guard(window, script_result::eval(window, script.into()))
+            .map(move |result| result.window_context(window, "evaluate javascript"))
+            .boxed()
+    }
+
+    // NOTE: the result is sent through the bridge in chunks, so results of several megabytes stay
+    // below the message size limits of the platforms.
+    fn webview_eval_js_chunked(&self, script: &str) -> BoxFuture<Result<serde_json::Value>> {
+        let window = self.window();
+        read_only::guard(window, script_result::eval_chunked(window, script.into()))
+            .map(move |result| result.window_context(window, "evaluate chunked javascript"))
+            .boxed()
+    }
+
+    // NOTE: yields the JSON text of the result in chunks as they arrive, which can be written out
+    // without holding the whole result in memory.
+    fn webview_eval_js_stream(&self, script: &str) -> BoxFuture<Result<BoxStream<'static, Result<String>>>> {
+        let window = self.window();
+        read_only::guard(window, script_result::stream(window, script.into()))
+            .map(move |result| result.window_context(window, "stream javascript result"))
+            .boxed()
+    }
+
+    // NOTE: unlike `webview_eval_js`, a promise that the script evaluates to is awaited, and its
+    // rejection is reported as `Error::ScriptRejected`.
+    fn webview_eval_async_js(&self, script: &str) -> BoxFuture<Result<serde_json::Value>> {
+        let window = self.window();
+        read_only::guard(window, async_js::eval(window, script.into()))
+            .map(move |result| result.window_context(window, "evaluate async javascript"))
+            .boxed()
+    }
+
+    fn webview_export_single_file(&self) -> BoxFuture<Result<String>> {
+        let window = self.window();
+        single_file::export(window)
+            .map(move |result| result.window_context(window, "export single file"))
+            .boxed()
+    }
+
+    fn webview_extract_text_nodes(&self) -> BoxFuture<Result<Vec<TextNode>>> {
+        let window = self.window();
+        text_nodes::extract(window)
+            .map(move |result| result.window_context(window, "extract text nodes"))
+            .boxed()
+    }
+
+    fn webview_fetch_with_session(&self, url: Url) -> BoxFuture<Result<FetchedResource>> {
+        let window = self.window();
+        fetch::fetch_with_session(window, url)
+            .map(move |result| result.window_context(window, "fetch with session"))
+            .boxed()
+    }
+
+    fn webview_fill_form(&self, fields: Vec<FieldFill>) -> BoxFuture<Result<u64>> {
+        let window = self.window();
+        read_only::guard(window, form::fill(window, fields))
+            .map(move |result| result.window_context(window, "fill form"))
+            .boxed()
+    }
+
+    fn webview_find_oversized_cookies(&self) -> BoxFuture<Result<Vec<Cookie>>> {
+        let window = self.window();
+        async move {
+            let mut cookies = window.webview_get_cookies(None).await?;
+            cookies.retain(Cookie::is_oversized);
+            Ok(cookies)
+        }
+        .boxed()
+    }
+
+    fn webview_cookie_stats(&self, pattern: CookiePattern) -> BoxFuture<Result<CookieStats>> {
+        let window = self.window();
+        async move {
+            let cookies = window.webview_get_cookies(None).await?;
+            let cookies = cookies.iter().filter(|cookie| pattern.matches(cookie));
+            Ok(CookieStats::from_cookies(cookies))
+        }
+        .boxed()
+    }
+
+    // NOTE: matching cookies are deleted by writing them again with an expiry in the past, which
+    // (unlike `webview_delete_cookies`) leaves other cookies for the same URL alone.
+    fn webview_delete_matching_cookies(&self, pattern: CookiePattern) -> BoxFuture<Result<Vec<Cookie>>> {
+        let window = self.window();
+        async move {
+            let mut cookies = window.webview_get_cookies(None).await?;
+            cookies.retain(|cookie| pattern.matches(cookie));
+            for cookie in cookies.iter().cloned() {
+                let cookie = Cookie {
+                    expires: Some(time::OffsetDateTime::UNIX_EPOCH),
+                    session: false,
+                    ..cookie
+                };
+                platform::set_cookie(window, cookie).await?;
+            }
+            Ok(cookies)
+        }
+        .map(move |result: BoxResult<_>| result.window_context(window, "delete matching cookies"))
+        .boxed()
+    }
+
+    // NOTE: matching persistent cookies are written again to expire `extend_by` from now, unless they
+    // already expire later. Session cookies have no expiry to extend and are left alone. Engines may
+    // still cap the expiry of cookies that were set by scripts, which only a server response avoids.
+    fn webview_touch_cookies(&self, pattern: CookiePattern, extend_by: Duration) -> BoxFuture<Result<Vec<Cookie>>> {
+        let window = self.window();
+        async move {
+            let extend_by = time::Duration::try_from(extend_by)?;
+            let expires = time::OffsetDateTime::now_utc()
+                .checked_add(extend_by)
+                .ok_or_else(|| Error::Other("the extended expiry is out of range".into()))?;
+            let mut touched = vec![];
+            for cookie in window.webview_get_cookies(None).await? {
+                if cookie.session || !pattern.matches(&cookie) {
+                    continue;
+                }
+                if cookie.expires.map_or(true, |current| current >= expires) {
+                    continue;
+                }
+                let cookie = Cookie {
+                    expires: Some(expires),
+                    ..cookie
+                };
+                platform::set_cookie(window, cookie.clone()).await?;
+                touched.push(cookie);
+            }
+            Ok(touched)
+        }
+        .map(move |result: BoxResult<_>| result.window_context(window, "touch cookies"))
+        .boxed()
+    }
+
+    // NOTE: the future owns a handle to the window, so it can be spawned or stored without borrowing
+    // it. Dropping it before it completes leaves the platform callbacks without a receiver, which
+    // they ignore.
+    fn webview_collect_cookies(&self, pattern: CookiePattern) -> BoxFuture<'static, Result<Vec<Cookie>>> {
+        let window = self.window().clone();
+        async move {
+            let mut cookies = window.webview_get_cookies(None).await?;
+            cookies.retain(|cookie| pattern.matches(cookie));
+            Ok(cookies)
+        }
+        .boxed()
+    }
+
+    #[cfg(feature = "debug-tools")]
+    fn webview_dump_raw_cookie_debug(&self, pattern: CookiePattern) -> BoxFuture<Result<Vec<RawCookieDebug>>> {
+        let window = self.window();
+        platform::raw_cookie_debug(window)
+            .map(move |result| {
+                result
+                    .map(|cookies| cookie_debug::filter(cookies, &pattern))
+                    .window_context(window, "dump raw cookie debug")
+            })
+            .boxed()
+    }
+
+    fn webview_query_cookies(&self, query: CookieQuery) -> BoxFuture<Result<CookiePage>> {
+        let window = self.window();
+        async move {
+            let cookies = window.webview_get_cookies(None).await?;
+            Ok(query.apply(cookies))
+        }
+        .boxed()
+    }
+
+    fn webview_set_cookie(&self, cookie: Cookie) -> BoxFuture<Result<()>> {
+        let window = self.window();
+        platform::set_cookie(window, cookie)
+            .map(move |result| result.window_context(window, "set cookie"))
+            .boxed()
+    }
+
+    // NOTE: cookies are written one after another, and writing stops at the first failure.
+    fn webview_set_cookies(&self, cookies: Vec<Cookie>) -> BoxFuture<Result<()>> {
+        let window = self.window();
+        async move {
+            for cookie in cookies {
+                platform::set_cookie(window, cookie).await?;
+            }
+            Ok(())
+        }
+        .map(move |result: BoxResult<()>| result.window_context(window, "set cookies"))
+        .boxed()
+    }
+
+    fn webview_export_session(&self, pattern: CookiePattern) -> BoxFuture<Result<SessionSnapshot>> {
+        let window = self.window();
+        session::export(window, pattern)
+            .map(move |result| result.window_context(window, "export session"))
+            .boxed()
+    }
+
+    fn webview_import_session(&self, snapshot: SessionSnapshot) -> BoxFuture<Result<()>> {
+        let window = self.window();
+        session::import(window, snapshot)
+            .map(move |result| result.window_context(window, "import session"))
+            .boxed()
+    }
+
+    fn webview_adopt_session_from<'a>(
+        &'a self,
+        other: &'a tauri::Window,
+        pattern: CookiePattern,
+        kinds: DataKinds,
+    ) -> BoxFuture<'a, Result<()>> {
+        let window = self.window();
+        session::adopt(window, other, pattern, kinds)
+            .map(move |result| result.window_context(window, "adopt session"))
+            .boxed()
+    }
+
+    fn webview_copy_session_storage_from<'a>(&'a self, other: &'a tauri::Window) -> BoxFuture<'a, Result<usize>> {
+        let window = self.window();
+        session::copy_session_storage(window, other)
+            .map(move |result| result.window_context(window, "copy session storage"))
+            .boxed()
+    }
+
+    #[cfg(feature = "browser-import")]
+    fn webview_import_from_browser(
+        &self,
+        browser: Browser,
+        profile: Option<String>,
+        pattern: CookiePattern,
+    ) -> BoxFuture<Result<Vec<Cookie>>> {
+        let window = self.window();
+        browser_import::install(window, browser, profile, pattern)
+            .map(move |result| result.window_context(window, "import cookies from browser"))
+            .boxed()
+    }
+
+    fn webview_get_local_storage(&self, origin: Option<Url>) -> BoxFuture<Result<BTreeMap<String, String>>> {
+        let window = self.window();
+        storage::items(window, storage::StorageArea::Local, origin)
+            .map(move |result| result.window_context(window, "get local storage"))
+            .boxed()
+    }
+
+    fn webview_set_local_storage_item(
+        &self,
+        origin: Option<Url>,
+        key: &str,
+        value: Option<&str>,
+    ) -> BoxFuture<Result<()>> {
+        let window = self.window();
+        let key = key.into();
+        let value = value.map(Into::into);
+        storage::set_item(window, storage::StorageArea::Local, origin, key, value)
+            .map(move |result| result.window_context(window, "set local storage item"))
+            .boxed()
+    }
+
+    fn webview_clear_local_storage(&self, origin: Option<Url>) -> BoxFuture<Result<()>> {
+        let window = self.window();
+        storage::clear(window, storage::StorageArea::Local, origin)
+            .map(move |result| result.window_context(window, "clear local storage"))
+            .boxed()
+    }
+
+    fn webview_get_session_storage(&self, origin: Option<Url>) -> BoxFuture<Result<BTreeMap<String, String>>> {
+        let window = self.window();
+        storage::items(window, storage::StorageArea::Session, origin)
+            .map(move |result| result.window_context(window, "get session storage"))
+            .boxed()
+    }
+
+    fn webview_set_session_storage_item(
+        &self,
+        origin: Option<Url>,
+        key: &str,
+        value: Option<&str>,
+    ) -> BoxFuture<Result<()>> {
+        let window = self.window();
+        let key = key.into();
+        let value = value.map(Into::into);
+        storage::set_item(window, storage::StorageArea::Session, origin, key, value)
+            .map(move |result| result.window_context(window, "set session storage item"))
+            .boxed()
+    }
+
+    fn webview_clear_session_storage(&self, origin: Option<Url>) -> BoxFuture<Result<()>> {
+        let window = self.window();
+        storage::clear(window, storage::StorageArea::Session, origin)
+            .map(move |result| result.window_context(window, "clear session storage"))
+            .boxed()
+    }
+
+    fn webview_handle_js_clone_requests(
+        &self,
+        name: &str,
+        handler: impl Fn(CloneValue) -> BoxFuture<'static, BoxResult<CloneValue>> + Send + Sync + 'static,
+    ) -> BoxFuture<Result<()>> {
+        let window = self.window();
+        request::on_clone_request(window, name, handler)
+            .map(move |result| result.window_context(window, "handle js clone requests"))
+            .boxed()
+    }
+
+    fn webview_handle_js_requests(
+        &self,
+        name: &str,
+        handler: impl Fn(serde_json::Value) -> BoxFuture<'static, BoxResult<serde_json::Value>> + Send + Sync + 'static,
+    ) -> BoxFuture<Result<()>> {
+        let window = self.window();
+        request::on_request(window, name, handler)
+            .map(move |result| result.window_context(window, "handle js requests"))
+            .boxed()
+    }
+
+    fn webview_transfer_bytes(&self, name: &str) -> BoxFuture<Result<BoxStream<'static, Result<ByteTransfer>>>> {
+        let window = self.window();
+        transfer::receive(window, name)
+            .map(move |result| result.window_context(window, "receive transferred bytes"))
+            .boxed()
+    }
+
+    fn webview_share_buffer(&self, bytes: Vec<u8>, readonly: bool) -> BoxFuture<Result<SharedBuffer>> {
+        let window = self.window();
+        shared_buffer::share(window, bytes, readonly)
+            .map(move |result| result.window_context(window, "share buffer"))
+            .boxed()
+    }
+
+    fn webview_unshare_buffer(&self, buffer: &SharedBuffer) -> BoxFuture<Result<bool>> {
+        let window = self.window();
+        shared_buffer::unshare(window, buffer.id())
+            .map(move |result| result.window_context(window, "unshare buffer"))
+            .boxed()
+    }
+
+    fn webview_inspect_request_bodies(
+        &self,
+        inspection: RequestInspection,
+        redactor: impl Fn(OutgoingRequest) -> Option<OutgoingRequest> + Send + Sync + 'static,
+    ) -> BoxFuture<Result<()>> {
+        let window = self.window();
+        request_inspection::inspect(window, inspection, redactor)
+            .map(move |result| result.window_context(window, "inspect request bodies"))
+            .boxed()
+    }
+
+    fn webview_integrity_events(&self) -> BoxFuture<Result<()>> {
+        let window = self.window();
+        integrity::install(window)
+            .map(move |result| result.window_context(window, "install integrity events"))
+            .boxed()
+    }
+
+    fn webview_intercept_print(&self, action: PrintAction) -> BoxFuture<Result<()>> {
+        let window = self.window();
+        print::intercept(window, action)
+            .map(move |result| result.window_context(window, "intercept print"))
+            .boxed()
+    }
+
+    fn webview_print(&self) -> BoxFuture<Result<()>> {
+        let window = self.window();
+        print::print(window)
+            .map(move |result| result.window_context(window, "print"))
+            .boxed()
+    }
+
+    fn webview_print_to_pdf(&self, options: PdfOptions) -> BoxFuture<Result<Vec<u8>>> {
+        let window = self.window();
+        print::print_to_pdf(window, options)
+            .map(move |result| result.window_context(window, "print to pdf"))
+            .boxed()
+    }
+
+    fn webview_capture_screenshot(&self, options: ScreenshotOptions) -> BoxFuture<Result<Vec<u8>>> {
+        let window = self.window();
+        platform::capture_screenshot(window, options)
+            .map(move |result| result.window_context(window, "capture screenshot"))
+            .boxed()
+    }
+
+    fn webview_scroll_to(&self, selector: &str) -> BoxFuture<Result<()>> {
+        let window = self.window();
+        element::scroll_to(window, selector.into())
+            .map(move |result| result.window_context(window, "scroll to element"))
+            .boxed()
+    }
+
+    fn webview_focus_element(&self, selector: &str) -> BoxFuture<Result<bool>> {
+        let window = self.window();
+        element::focus(window, selector.into())
+            .map(move |result| result.window_context(window, "focus element"))
+            .boxed()
+    }
+
+    fn webview_element_rect(&self, selector: &str) -> BoxFuture<Result<Option<Rect>>> {
+        let window = self.window();
+        element::rect(window, selector.into())
+            .map(move |result| result.window_context(window, "read element rect"))
+            .boxed()
+    }
+
+    fn webview_watch_element_rect(
+        &self,
+        selector: &str,
+    ) -> BoxFuture<Result<BoxStream<'static, Result<Option<Rect>>>>> {
+        let window = self.window();
+        element::watch_rect(window, selector.into())
+            .map(move |result| result.window_context(window, "watch element rect"))
+            .boxed()
+    }
+
+    fn webview_page_to_window_coords(
+        &self,
+        point: tauri::LogicalPosition<f64>,
+    ) -> BoxFuture<Result<tauri::PhysicalPosition<f64>>> {
+        let window = self.window();
+        coords::page_to_window(window, point)
+            .map(move |result| result.window_context(window, "convert page to window coordinates"))
+            .boxed()
+    }
+
+    fn webview_window_to_page_coords(
+        &self,
+        point: tauri::PhysicalPosition<f64>,
+    ) -> BoxFuture<Result<tauri::LogicalPosition<f64>>> {
+        let window = self.window();
+        coords::window_to_page(window, point)
+            .map(move |result| result.window_context(window, "convert window to page coordinates"))
+            .boxed()
+    }
+
+    fn webview_snapshot_element(&self, selector: &str) -> BoxFuture<Result<Vec<u8>>> {
+        let window = self.window();
+        screenshot::snapshot_element(window, selector.into())
+            .map(move |result| result.window_context(window, "snapshot element"))
+            .boxed()
+    }
+
+    fn webview_record(&self, options: RecordOptions) -> BoxFuture<Result<RecordingHandle>> {
+        let window = self.window();
+        recording::record(window, options)
+            .map(move |result| result.window_context(window, "record"))
+            .boxed()
+    }
+
+    fn webview_load_extension(&self, path: PathBuf) -> BoxFuture<Result<()>> {
+        let window = self.window();
+        // NOTE: extensions are rejected for windows that were relaunched in safe mode.
+        if safe_mode::is_enabled(window) {
+            let result: Result<()> = Err(Error::SafeMode("loading extensions"));
+            return future::ready(result.window_context(window, "load extension")).boxed();
+        }
+        platform::load_extension(window, path)
+            .map(move |result| result.window_context(window, "load extension"))
+            .boxed()
+    }
+
+    fn webview_mock_routes(&self, routes: Vec<MockRoute>) -> BoxFuture<Result<()>> {
+        let window = self.window();
+        mock::set_routes(window, routes)
+            .map(move |result| result.window_context(window, "mock routes"))
+            .boxed()
+    }
+
+    fn webview_navigation_events(&self) -> BoxFuture<Result<BoxStream<'static, Result<NavigationEvent>>>> {
+        let window = self.window();
+        navigation_events::subscribe(window, self_events::ignore_self())
+            .map(move |result| result.window_context(window, "subscribe to navigation events"))
+            .boxed()
+    }
+
+    fn webview_wait_for(&self, condition: WaitCondition, timeout: Duration) -> BoxFuture<Result<()>> {
+        let window = self.window();
+        wait::wait_for(window, condition, timeout)
+            .map(move |result| result.window_context(window, "wait for condition"))
+            .boxed()
+    }
+
+    fn webview_go_back(&self) -> BoxFuture<Result<()>> {
+        let window = self.window();
+        navigation_events::tag(window);
+        platform::go_back(window)
+            .map(move |result| result.window_context(window, "go back"))
+            .boxed()
+    }
+
+    fn webview_go_forward(&self) -> BoxFuture<Result<()>> {
+        let window = self.window();
+        navigation_events::tag(window);
+        platform::go_forward(window)
+            .map(move |result| result.window_context(window, "go forward"))
+            .boxed()
+    }
+
+    fn webview_can_go_back(&self) -> BoxFuture<Result<bool>> {
+        let window = self.window();
+        platform::can_go_back(window)
+            .map(move |result| result.window_context(window, "check back navigation"))
+            .boxed()
+    }
+
+    fn webview_can_go_forward(&self) -> BoxFuture<Result<bool>> {
+        let window = self.window();
+        platform::can_go_forward(window)
+            .map(move |result| result.window_context(window, "check forward navigation"))
+            .boxed()
+    }
+
+    fn webview_back_forward_list(&self) -> BoxFuture<Result<Vec<HistoryEntry>>> {
+        let window = self.window();
+        platform::back_forward_list(window)
+            .map(move |result| result.window_context(window, "read back forward list"))
+            .boxed()
+    }
+
+    fn webview_navigate_and_wait(&self, url: Url) -> BoxFuture<Result<NavigationResult>> {
+        let window = self.window();
+        navigation_events::navigate_and_wait(window, url)
+            .map(move |result| result.window_context(window, "navigate and wait"))
+            .boxed()
+    }
+
+    fn webview_navigate_file(&self, file: PathBuf, read_access: Option<PathBuf>) -> Result<()> {
+        let window = self.window();
+        local_url::file_target(file, read_access)
+            .and_then(|target| platform::navigate(window, target))
+            .window_context(window, "navigate to file")
+    }
+
+    fn webview_navigate_debounced(&self, url: Url, delay: Duration) -> BoxFuture<Result<()>> {
+        let window = self.window();
+        navigation_queue::debounce(window, url, delay)
+            .map(move |result| result.window_context(window, "navigate debounced"))
+            .boxed()
+    }
+
+    fn webview_pending_navigation(&self) -> Result<Option<Url>> {
+        let window = self.window();
+        navigation_queue::pending(window).window_context(window, "read pending navigation")
+    }
+
+    fn webview_cancel_pending_navigation(&self) -> Result<bool> {
+        let window = self.window();
+        navigation_queue::cancel(window).window_context(window, "cancel pending navigation")
+    }
+
+    fn webview_queue_navigation(&self, url: Url) -> BoxFuture<Result<()>> {
+        let window = self.window();
+        navigation_queue::queue(window, url)
+            .map(move |result| result.window_context(window, "queue navigation"))
+            .boxed()
+    }
+
+    fn webview_prerender(&self, url: Url) -> BoxFuture<Result<()>> {
+        let window = self.window();
+        prerender::prerender(window, url)
+            .map(move |result| result.window_context(window, "prerender page"))
+            .boxed()
+    }
+
+    fn webview_list_frames(&self) -> BoxFuture<Result<Vec<FrameInfo>>> {
+        let window = self.window();
+        frame::list(window)
+            .map(move |result| result.window_context(window, "list frames"))
+            .boxed()
+    }
+
+    fn webview_replace_text_nodes(&self, texts: BTreeMap<u64, String>) -> BoxFuture<Result<u64>> {
+        let window = self.window();
+        read_only::guard(window, text_nodes::replace(window, texts))
+            .map(move |result| result.window_context(window, "replace text nodes"))
+            .boxed()
+    }
+
+    fn webview_save_resource(&self, url: Url, path: PathBuf) -> BoxFuture<Result<()>> {
+        let window = self.window();
+        context_action::save_resource(window, url, path)
+            .map(move |result| result.window_context(window, "save resource"))
+            .boxed()
+    }
+
+    fn webview_pdf_settings(&self, settings: PdfViewerSettings) -> BoxFuture<Result<()>> {
+        let window = self.window();
+        pdf::set_settings(window, settings)
+            .map(move |result| result.window_context(window, "set pdf settings"))
+            .boxed()
+    }
+
+    fn webview_set_referrer_policy(&self, policy: ReferrerPolicy) -> BoxFuture<Result<()>> {
+        let window = self.window();
+        referrer_policy::set(window, policy)
+            .map(move |result| result.window_context(window, "set referrer policy"))
+            .boxed()
+    }
+
+    fn webview_set_https_only(&self, mode: HttpsOnlyMode) -> BoxFuture<Result<()>> {
+        let window = self.window();
+        navigation_policy::set_https_only(window, mode)
+            .map(move |result| result.window_context(window, "set https only"))
+            .boxed()
+    }
+
+    fn webview_set_url_rewrites(&self, rules: Vec<RewriteRule>) -> BoxFuture<Result<()>> {
+        let window = self.window();
+        navigation_policy::set_url_rewrites(window, rules)
+            .map(move |result| result.window_context(window, "set url rewrites"))
+            .boxed()
+    }
+
+    fn webview_set_user_agent(&self, user_agent: Option<String>) -> BoxFuture<Result<()>> {
+        let window = self.window();
+        read_only::guard(window, user_agent::set(window, user_agent))
+            .map(move |result| result.window_context(window, "set user agent"))
+            .boxed()
+    }
+
+    fn webview_get_user_agent(&self) -> BoxFuture<Result<String>> {
+        let window = self.window();
+        platform::user_agent(window)
+            .map(move |result| result.window_context(window, "get user agent"))
+            .boxed()
+    }
+
+    fn webview_set_proxy(&self, config: ProxyConfig) -> BoxFuture<Result<()>> {
+        let window = self.window();
+        read_only::guard(window, proxy::set(window, config))
+            .map(move |result| result.window_context(window, "set proxy"))
+            .boxed()
+    }
+
+    fn webview_set_zoom(&self, factor: f64) -> BoxFuture<Result<()>> {
+        let window = self.window();
+        zoom::set(window, factor)
+            .map(move |result| result.window_context(window, "set zoom"))
+            .boxed()
+    }
+
+    fn webview_get_zoom(&self) -> BoxFuture<Result<f64>> {
+        let window = self.window();
+        platform::zoom(window)
+            .map(move |result| result.window_context(window, "get zoom"))
+            .boxed()
+    }
+
+    fn webview_profile_health_check(&self) -> BoxFuture<Result<ProfileHealth>> {
+        let window = self.window();
+        profile_health::health_check(window)
+            .map(move |result| result.window_context(window, "check profile health"))
+            .boxed()
+    }
+
+    fn webview_repair_profile(&self) -> BoxFuture<Result<ProfileHealth>> {
+        let window = self.window();
+        profile_health::repair(window)
+            .map(move |result| result.window_context(window, "repair profile"))
+            .boxed()
+    }
+
+    fn webview_set_user_agent_rules(&self, rules: Vec<(String, String)>) -> BoxFuture<Result<()>> {
+        let window = self.window();
+        read_only::guard(window, user_agent::set_rules(window, rules))
+            .map(move |result| result.window_context(window, "set user agent rules"))
+            .boxed()
+    }
+
+    fn webview_speak_selection(&self) -> BoxFuture<Result<()>> {
+        let window = self.window();
+        speech::speak_selection(window)
+            .map(move |result| result.window_context(window, "speak selection"))
+            .boxed()
+    }
+
+    fn webview_stop_speaking(&self) -> BoxFuture<Result<()>> {
+        let window = self.window();
+        speech::stop_speaking(window)
+            .map(move |result| result.window_context(window, "stop speaking"))
+            .boxed()
+    }
+
+    fn webview_spa_route_events(&self) -> BoxFuture<Result<()>> {
+        let window = self.window();
+        route::install(window)
+            .map(move |result| result.window_context(window, "install route events"))
+            .boxed()
+    }
+
+    fn webview_set_persistent_init_script(&self, id: &str, source: &str, scope: ScriptScope) -> BoxFuture<Result<()>> {
+        let window = self.window();
+        read_only::guard(window, init_script::set(window, id, source, scope))
+            .map(move |result| result.window_context(window, "set persistent init script"))
+            .boxed()
+    }
+
+    fn webview_remove_persistent_init_script(&self, id: &str) -> BoxFuture<Result<bool>> {
+        let window = self.window();
+        read_only::guard(window, init_script::remove(window, id))
+            .map(move |result| result.window_context(window, "remove persistent init script"))
+            .boxed()
+    }
+
+    fn webview_persistent_init_scripts(&self) -> Result<Vec<String>> {
+        let window = self.window();
+        init_script::list(window).window_context(window, "list persistent init scripts")
+    }
+
+    fn webview_add_user_script(&self, script: UserScript) -> BoxFuture<Result<ScriptHandle>> {
+        let window = self.window();
+        read_only::guard(window, user_script::add(window, script))
+            .map(move |result| result.window_context(window, "add user script"))
+            .boxed()
+    }
+
+    fn webview_remove_user_script(&self, handle: ScriptHandle) -> BoxFuture<Result<bool>> {
+        let window = self.window();
+        read_only::guard(window, user_script::remove(window, handle))
+            .map(move |result| result.window_context(window, "remove user script"))
+            .boxed()
+    }
+
+    fn webview_open_popups_as_tauri_windows(
+        &self,
+        builder: impl Fn(&Url) -> Option<tauri::utils::config::WindowConfig> + Send + Sync + 'static,
+    ) -> BoxFuture<Result<()>> {
+        let window = self.window();
+        popup::install(window, builder)
+            .map(move |result| result.window_context(window, "install popup handler"))
+            .boxed()
+    }
 }
 
 mod private {
-    pub trait WebviewExtSealed {}
+    pub trait WebviewExtSealed {
+        fn window(&self) -> &tauri::Window;
+    }
+
     impl WebviewExtSealed for tauri::Window {
+        fn window(&self) -> &tauri::Window {
+            self
+        }
     }
 }
 
+#[allow(unused_variables)]
+fn report_error(label: &str, context: &str, err: impl std::fmt::Display) {
+    #[cfg(feature = "tracing")]
+    tracing::warn!(window.label = label, %err, "{}", context);
+}
+
 #[derive(Debug)]
 struct ApiResult<T>(Arc<Mutex<T>>);
 
@@ -51,7 +1530,13 @@ impl<T> ApiResult<T> {
     }
 
     fn lock(&self) -> BoxResult<MutexGuard<T>> {
-        self.0.lock().map_err(|err| err.to_string().into())
+        self.0.lock().map_err(|_| Error::Poisoned.into())
+    }
+}
+
+impl<T: Default> Default for ApiResult<T> {
+    fn default() -> Self {
+        Self::new(T::default())
     }
 }
 
@@ -0,0 +1,142 @@
+use crate::{
+    bridge,
+    event_bus,
+    event_bus::WebviewEvent,
+    navigation_events::{self, NavigationEvent},
+    platform,
+    state,
+    BoxResult,
+};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tauri::Window;
+use url::Url;
+
+pub(crate) type ErrorPageRenderer = Arc<dyn Fn(&LoadError) -> String + Send + Sync + 'static>;
+
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[non_exhaustive]
+pub enum LoadErrorKind {
+    DnsFailure,
+    Tls,
+    Cancelled,
+    BlockedByPolicy,
+    Http(u16),
+    Network,
+    Unknown,
+}
+
+impl std::fmt::Display for LoadErrorKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Self::DnsFailure => write!(f, "host name could not be resolved"),
+            Self::Tls => write!(f, "secure connection failed"),
+            Self::Cancelled => write!(f, "load was cancelled"),
+            Self::BlockedByPolicy => write!(f, "load was blocked by policy"),
+            Self::Http(status) => write!(f, "server responded with HTTP status {status}"),
+            Self::Network => write!(f, "network error"),
+            Self::Unknown => write!(f, "unknown error"),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[non_exhaustive]
+pub struct LoadError {
+    pub url: Option<Url>,
+    pub kind: LoadErrorKind,
+    // NOTE: the engine specific error code (NSError code, `COREWEBVIEW2_WEB_ERROR_STATUS`, or
+    // GError code), kept for diagnostics since `kind` is only a normalized approximation.
+    pub code: i64,
+    pub description: String,
+}
+
+impl LoadError {
+    pub(crate) fn new(url: Option<&str>, kind: LoadErrorKind, code: i64, description: impl Into<String>) -> Self {
+        let url = url.and_then(|url| Url::parse(url).ok());
+        let description = description.into();
+        Self {
+            url,
+            kind,
+            code,
+            description,
+        }
+    }
+}
+
+impl std::fmt::Display for LoadError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match &self.url {
+            Some(url) => write!(f, "failed to load `{}`: {}", url, self.kind),
+            None => write!(f, "failed to load: {}", self.kind),
+        }
+    }
+}
+
+impl std::error::Error for LoadError {
+}
+
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn install(window: &Window) -> BoxResult<()> {
+    let state = state::window_state(window)?;
+    let Some(install) = bridge::install_once(&state, "load-error").await? else {
+        return Ok(());
+    };
+    platform::add_load_error_handler(window, {
+        let window = window.clone();
+        move |error| {
+            #[cfg(feature = "tracing")]
+            tracing::info!(window.label = window.label(), %error);
+            let handled = show_error_page(&window, &error);
+            navigation_events::publish(&window, NavigationEvent::Failed(error.clone()));
+            event_bus::publish(&window, WebviewEvent::LoadFailed(error));
+            handled
+        }
+    })
+    .await?;
+    install.done();
+    Ok(())
+}
+
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window, renderer),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn set_error_page(
+    window: &Window,
+    renderer: impl Fn(&LoadError) -> String + Send + Sync + 'static,
+) -> BoxResult<()> {
+    let state = state::window_state(window)?;
+    *state.error_page.lock()? = Some(Arc::new(renderer));
+    install(window).await
+}
+
+// NOTE: cancelled loads are superseded by another navigation or turned into a download, and HTTP
+// errors already come with a document from the server, so neither is replaced by the error page.
+fn show_error_page(window: &Window, error: &LoadError) -> bool {
+    if matches!(error.kind, LoadErrorKind::Cancelled | LoadErrorKind::Http(_)) {
+        return false;
+    }
+    let renderer = match state::window_state(window).and_then(|state| Ok(state.error_page.lock()?.clone())) {
+        Ok(Some(renderer)) => renderer,
+        Ok(None) => return false,
+        Err(err) => {
+            crate::report_error(window.label(), "failed to read error page renderer", err);
+            return false;
+        },
+    };
+    let html = renderer(error);
+    let window = window.clone();
+    // NOTE: load errors are reported on the main thread, where waiting for `with_webview` to
+    // complete would deadlock, so the page is loaded from the async runtime instead. The page is
+    // loaded without a base URL so that it never runs with the origin of the failed site.
+    tauri::async_runtime::spawn(async move {
+        if let Err(err) = platform::load_html(&window, html, None).await {
+            crate::report_error(window.label(), "failed to load error page", err);
+        }
+    });
+    true
+}
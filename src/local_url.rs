@@ -0,0 +1,95 @@
+use crate::{BoxResult, Error};
+use base64::Engine;
+use std::path::{Component, Path, PathBuf};
+use url::Url;
+
+pub(crate) enum NavigationTarget {
+    Remote(Url),
+    Data(Url),
+    File {
+        url: Url,
+        file: PathBuf,
+        read_access: PathBuf,
+    },
+}
+
+impl NavigationTarget {
+    pub(crate) fn into_url(self) -> Url {
+        match self {
+            Self::Remote(url) | Self::Data(url) | Self::File { url, .. } => url,
+        }
+    }
+}
+
+fn invalid(target: impl Into<String>, reason: &'static str) -> Error {
+    Error::InvalidNavigationTarget {
+        target: target.into(),
+        reason,
+    }
+}
+
+// NOTE: `data:` and `file:` URLs are checked before they are handed to the webview, since the
+// webviews fail to load or silently ignore malformed ones instead of reporting an error.
+pub(crate) fn target(url: Url) -> BoxResult<NavigationTarget> {
+    match url.scheme() {
+        "data" => {
+            validate_data(&url)?;
+            Ok(NavigationTarget::Data(url))
+        },
+        "file" => {
+            let file = url
+                .to_file_path()
+                .map_err(|()| invalid(url.as_str(), "the URL is not a local file path"))?;
+            let read_access = check_file(&file, None)?;
+            Ok(NavigationTarget::File { url, file, read_access })
+        },
+        _ => Ok(NavigationTarget::Remote(url)),
+    }
+}
+
+// NOTE: the document of a file may only read files under the read access directory, which is the
+// directory of the file unless a directory that contains it is given. Paths with `..` components
+// are rejected, so that the file can not escape that directory.
+pub(crate) fn file_target(file: PathBuf, read_access: Option<PathBuf>) -> BoxResult<NavigationTarget> {
+    let read_access = check_file(&file, read_access)?;
+    let url =
+        Url::from_file_path(&file).map_err(|()| invalid(file.display().to_string(), "the path can not be a URL"))?;
+    Ok(NavigationTarget::File { url, file, read_access })
+}
+
+fn check_file(file: &Path, read_access: Option<PathBuf>) -> BoxResult<PathBuf> {
+    let display = file.display().to_string();
+    if !file.is_absolute() {
+        return Err(invalid(display, "the path is not absolute").into());
+    }
+    if file.components().any(|component| component == Component::ParentDir) {
+        return Err(invalid(display, "the path contains `..`").into());
+    }
+    if !std::fs::metadata(file)?.is_file() {
+        return Err(invalid(display, "the path is not a file").into());
+    }
+    let read_access = match read_access {
+        Some(read_access) => read_access,
+        None => file
+            .parent()
+            .map(Into::into)
+            .ok_or_else(|| invalid(display.clone(), "the file has no parent directory"))?,
+    };
+    if !file.starts_with(&read_access) || !std::fs::metadata(&read_access)?.is_dir() {
+        return Err(invalid(display, "the file is not in the read access directory").into());
+    }
+    Ok(read_access)
+}
+
+// NOTE: a data URL has the form `data:[<media type>][;base64],<data>`.
+fn validate_data(url: &Url) -> BoxResult<()> {
+    let Some((header, data)) = url.path().split_once(',') else {
+        return Err(invalid(url.as_str(), "the data URL has no `,` before its data").into());
+    };
+    if header.to_ascii_lowercase().ends_with(";base64") {
+        base64::engine::general_purpose::STANDARD
+            .decode(data)
+            .map_err(|_| invalid(url.as_str(), "the data URL has invalid base64 data"))?;
+    }
+    Ok(())
+}
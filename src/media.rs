@@ -0,0 +1,36 @@
+use crate::{init_script, platform, state, BoxResult};
+use serde::{Deserialize, Serialize};
+use tauri::Window;
+
+const MEDIA_SCRIPT: &str = include_str!("js/media.js");
+
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub enum MediaType {
+    Screen,
+    Print,
+}
+
+impl MediaType {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Screen => "screen",
+            Self::Print => "print",
+        }
+    }
+}
+
+// NOTE: `None` stops emulating. Where the engine can not emulate media itself, the media queries of
+// the style sheets in the top frame are rewritten instead, which `matchMedia` does not see.
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn emulate(window: &Window, media: Option<MediaType>) -> BoxResult<()> {
+    if platform::emulate_media(window, media).await? {
+        return Ok(());
+    }
+    let state = state::window_state(window)?;
+    let media = serde_json::to_string(&media.map(MediaType::as_str))?;
+    let source = format!("({MEDIA_SCRIPT})({media});");
+    init_script::replace(window, &state.media_script, source).await
+}
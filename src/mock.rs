@@ -0,0 +1,85 @@
+use crate::{glob, init_script, request, state, BoxResult};
+use futures::future::{BoxFuture, FutureExt};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tauri::Window;
+use url::Url;
+
+const MOCK_SCRIPT: &str = include_str!("js/mock.js");
+
+pub type MockHandler = Arc<dyn Fn(MockRequest) -> BoxFuture<'static, BoxResult<MockReply>> + Send + Sync + 'static>;
+
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq)]
+pub struct MockRequest {
+    pub url: Url,
+    pub method: String,
+    pub headers: Vec<(String, String)>,
+    pub body: Option<String>,
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct MockReply {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: String,
+}
+
+#[derive(Clone)]
+pub enum MockResponse {
+    Fixed(MockReply),
+    Handler(MockHandler),
+}
+
+/// A canned response for requests made by page scripts.
+///
+/// The pattern is a URL glob where `*` matches any sequence of characters, and the route only
+/// applies to requests with the given method when one is set. Routes are matched in order.
+#[derive(Clone)]
+pub struct MockRoute {
+    pub pattern: String,
+    pub method: Option<String>,
+    pub response: MockResponse,
+}
+
+// NOTE: custom schemes can only be registered when the app is built, and WebKit can not intercept
+// `http(s)` requests, so requests are served from `fetch` and `XMLHttpRequest` in the page instead.
+// Requests that match a pattern but none of the routes are passed through to the network.
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window, routes),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn set_routes(window: &Window, routes: Vec<MockRoute>) -> BoxResult<()> {
+    let state = state::window_state(window)?;
+    let patterns = routes.iter().map(|route| route.pattern.clone()).collect::<Vec<_>>();
+    *state.mock_routes.lock()? = routes;
+    request::on_request(window, "mock-route", {
+        let window = window.clone();
+        move |payload| serve(window.clone(), payload).boxed()
+    })
+    .await?;
+    let patterns = serde_json::to_string(&patterns)?;
+    let source = format!("({MOCK_SCRIPT})({patterns});");
+    init_script::replace(window, &state.mock_script, source).await
+}
+
+async fn serve(window: Window, payload: serde_json::Value) -> BoxResult<serde_json::Value> {
+    let request = serde_json::from_value::<MockRequest>(payload)?;
+    let route = state::window_state(&window)?
+        .mock_routes
+        .lock()?
+        .iter()
+        .find(|route| {
+            let method = route
+                .method
+                .as_deref()
+                .map_or(true, |method| method.eq_ignore_ascii_case(&request.method));
+            method && glob::matches(&route.pattern, request.url.as_str())
+        })
+        .cloned();
+    let reply = match route.map(|route| route.response) {
+        Some(MockResponse::Fixed(reply)) => reply,
+        Some(MockResponse::Handler(handler)) => handler(request).await?,
+        None => return Ok(serde_json::Value::Null),
+    };
+    Ok(serde_json::to_value(reply)?)
+}
@@ -0,0 +1,188 @@
+use crate::{Cookie, Error, OriginUsage, Result, WebviewBackend};
+use futures::future::{self, BoxFuture, FutureExt};
+use std::{
+    collections::BTreeMap,
+    sync::{Arc, Mutex, MutexGuard},
+};
+use url::Url;
+
+/// An in-memory webview for testing code written against [`WebviewBackend`] without a window.
+///
+/// Cookies are kept in a jar that follows the matching rules of browsers for domains, paths,
+/// secure cookies, and expiry, and the cache is a list of entries with their size. Clones share
+/// the same jar, cache, and history.
+#[derive(Clone, Debug, Default)]
+pub struct MockWebView {
+    state: Arc<Mutex<MockState>>,
+}
+
+#[derive(Debug, Default)]
+struct MockState {
+    cookies: Vec<Cookie>,
+    cache: BTreeMap<Url, u64>,
+    history: Vec<Url>,
+    store_unavailable: bool,
+}
+
+impl MockWebView {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_cookies(cookies: impl IntoIterator<Item = Cookie>) -> Self {
+        let webview = Self::new();
+        if let Ok(mut state) = webview.state() {
+            for cookie in cookies {
+                state.store(cookie);
+            }
+        }
+        webview
+    }
+
+    // NOTE: like a window whose ephemeral store has not been set up yet, every cookie and cache
+    // operation fails with `Error::StoreUnavailable`, while navigation still works.
+    pub fn without_store() -> Self {
+        let webview = Self::new();
+        if let Ok(mut state) = webview.state() {
+            state.store_unavailable = true;
+        }
+        webview
+    }
+
+    pub fn url(&self) -> Option<Url> {
+        self.state().ok()?.history.last().cloned()
+    }
+
+    pub fn history(&self) -> Vec<Url> {
+        self.state().map(|state| state.history.clone()).unwrap_or_default()
+    }
+
+    pub fn insert_cache_entry(&self, url: Url, size: u64) {
+        if let Ok(mut state) = self.state() {
+            state.cache.insert(url, size);
+        }
+    }
+
+    pub fn cache_entries(&self) -> Vec<Url> {
+        self.state()
+            .map(|state| state.cache.keys().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    pub fn cache_usage(&self) -> Vec<OriginUsage> {
+        let mut usage = BTreeMap::<String, u64>::new();
+        if let Ok(state) = self.state() {
+            for (url, size) in &state.cache {
+                *usage.entry(url.origin().ascii_serialization()).or_default() += size;
+            }
+        }
+        usage
+            .into_iter()
+            .map(|(origin, size)| OriginUsage {
+                origin,
+                disk_cache: Some(size),
+                memory_cache: None,
+            })
+            .collect()
+    }
+
+    pub fn clear_cache_for_origins(&self, origins: &[Url]) {
+        if let Ok(mut state) = self.state() {
+            state
+                .cache
+                .retain(|url, _| !origins.iter().any(|origin| origin.origin() == url.origin()));
+        }
+    }
+
+    fn state(&self) -> Result<MutexGuard<MockState>> {
+        self.state.lock().map_err(|_| Error::Poisoned)
+    }
+
+    fn data_store(&self) -> Result<MutexGuard<MockState>> {
+        let state = self.state()?;
+        if state.store_unavailable {
+            return Err(Error::StoreUnavailable {
+                reason: "mock webview has no data store",
+            });
+        }
+        Ok(state)
+    }
+}
+
+impl MockState {
+    // NOTE: a cookie replaces the one with the same name, domain, and path, and a cookie that has
+    // already expired only removes it, like a `Set-Cookie` header would.
+    fn store(&mut self, cookie: Cookie) {
+        self.cookies.retain(|stored| {
+            (stored.name.as_str(), stored.domain.as_str(), stored.path.as_str())
+                != (cookie.name.as_str(), cookie.domain.as_str(), cookie.path.as_str())
+        });
+        if !is_expired(&cookie) {
+            self.cookies.push(cookie);
+        }
+    }
+
+    fn matching(&self, url: Option<&Url>) -> Vec<Cookie> {
+        self.cookies
+            .iter()
+            .filter(|cookie| !is_expired(cookie) && url.map_or(true, |url| sent_to(cookie, url)))
+            .cloned()
+            .collect()
+    }
+}
+
+impl WebviewBackend for MockWebView {
+    fn get_cookies(&self, url: Option<Url>) -> BoxFuture<Result<Vec<Cookie>>> {
+        let result = self.data_store().map(|state| state.matching(url.as_ref()));
+        future::ready(result).boxed()
+    }
+
+    fn set_cookie(&self, cookie: Cookie) -> BoxFuture<Result<()>> {
+        let result = self.data_store().map(|mut state| state.store(cookie));
+        future::ready(result).boxed()
+    }
+
+    fn delete_cookies(&self, url: Option<Url>) -> BoxFuture<Result<Vec<Cookie>>> {
+        let result = self.data_store().map(|mut state| {
+            let deleted = state.matching(url.as_ref());
+            state.cookies.retain(|cookie| !deleted.contains(cookie));
+            deleted
+        });
+        future::ready(result).boxed()
+    }
+
+    fn clear_cache(&self) -> BoxFuture<Result<()>> {
+        let result = self.data_store().map(|mut state| state.cache.clear());
+        future::ready(result).boxed()
+    }
+
+    fn navigate(&self, url: Url) -> Result<()> {
+        self.state()?.history.push(url);
+        Ok(())
+    }
+}
+
+fn is_expired(cookie: &Cookie) -> bool {
+    !cookie.session
+        && cookie
+            .expires
+            .map_or(false, |expires| expires <= time::OffsetDateTime::now_utc())
+}
+
+// NOTE: cookies with a leading dot in their domain are sent to subdomains as well, the others only
+// to their own host.
+fn sent_to(cookie: &Cookie, url: &Url) -> bool {
+    let Some(host) = url.host_str() else {
+        return false;
+    };
+    let domain = cookie.domain.trim_start_matches('.');
+    let domain_matches = host.eq_ignore_ascii_case(domain)
+        || (cookie.domain.starts_with('.') && host.strip_suffix(domain).map_or(false, |prefix| prefix.ends_with('.')));
+    let path = url.path();
+    let path_matches = cookie.path.is_empty()
+        || path == cookie.path
+        || (path.starts_with(&cookie.path)
+            && (cookie.path.ends_with('/') || path[cookie.path.len() ..].starts_with('/')));
+    let secure_matches = !cookie.secure || url.scheme() == "https";
+    domain_matches && path_matches && secure_matches
+}
@@ -0,0 +1,44 @@
+use crate::{bridge, request, BoxResult};
+use futures::future::{BoxFuture, FutureExt};
+use serde::Deserialize;
+use std::sync::Arc;
+use tauri::Window;
+
+const NATIVE_OBJECT_SCRIPT: &str = include_str!("js/native_object.js");
+
+/// An object whose methods can be called from page scripts.
+///
+/// Every method call from the page is forwarded with the method name and the JSON arguments, and
+/// returns a promise for the result in the page.
+pub trait NativeObject: Send + Sync + 'static {
+    fn call(&self, method: &str, args: Vec<serde_json::Value>) -> BoxFuture<'static, BoxResult<serde_json::Value>>;
+}
+
+#[derive(Deserialize)]
+struct MethodCall {
+    method: String,
+    #[serde(default)]
+    args: Vec<serde_json::Value>,
+}
+
+// NOTE: WebView2 host objects need an `IDispatch` implementation and are only reachable
+// asynchronously from the page anyway, so all engines use the same proxy shim on top of the request
+// channel instead, which also keeps the calling convention identical across platforms.
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window, object),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn expose(window: &Window, name: &str, object: impl NativeObject) -> BoxResult<()> {
+    let object = Arc::new(object);
+    request::on_request(
+        window,
+        &format!("object:{name}"),
+        move |payload| match serde_json::from_value::<MethodCall>(payload) {
+            Ok(call) => object.call(&call.method, call.args),
+            Err(err) => futures::future::ready(Err(err.into())).boxed(),
+        },
+    )
+    .await?;
+    let name = serde_json::to_string(name)?;
+    bridge::add_script(window, &format!("({NATIVE_OBJECT_SCRIPT})({name});")).await
+}
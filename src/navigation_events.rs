@@ -0,0 +1,166 @@
+use crate::{
+    bridge,
+    event_bus::{self, WebviewEvent},
+    load_error,
+    platform,
+    self_events::SelfTags,
+    state,
+    stream,
+    BoxResult,
+    Error,
+    LoadError,
+    LoadErrorKind,
+    Result,
+    WebviewExt,
+};
+use futures::{stream::BoxStream, StreamExt};
+use serde::{Deserialize, Serialize};
+use tauri::Window;
+use url::Url;
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[non_exhaustive]
+pub enum NavigationEvent {
+    Started { url: Url },
+    Committed { url: Url },
+    Finished { url: Url },
+    Failed(LoadError),
+}
+
+// NOTE: `status` is the HTTP status of the document where the engine reports it, and `error` is why
+// the navigation failed, which for HTTP error responses is set along with `status`.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[non_exhaustive]
+pub struct NavigationResult {
+    pub url: Url,
+    pub success: bool,
+    pub status: Option<u16>,
+    pub error: Option<LoadError>,
+}
+
+// NOTE: navigations started through this crate are tagged, and the events from the start of such a
+// navigation up to the start of the next one are its events.
+#[derive(Default)]
+pub(crate) struct SelfNavigation {
+    tags: SelfTags<()>,
+    current: bool,
+}
+
+pub(crate) fn tag(window: &Window) {
+    if let Ok(state) = state::window_state(window) {
+        if let Ok(mut navigation) = state.self_navigation.lock() {
+            navigation.tags.tag(());
+        }
+    }
+}
+
+// NOTE: events are reported for main frame navigations. A navigation ends with either `Finished` or
+// `Failed`, except for HTTP error responses, which still load a document and are reported by both.
+// Failures are the ones reported to the error page and the event bus.
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn subscribe(
+    window: &Window,
+    ignore_self: bool,
+) -> BoxResult<BoxStream<'static, Result<NavigationEvent>>> {
+    let state = state::window_state(window)?;
+    let (event_tx, event_rx) = stream::channel(stream::stream_config());
+    state.navigation_subscribers.lock()?.push((ignore_self, event_tx));
+    install(window).await?;
+    Ok(event_rx)
+}
+
+pub(crate) async fn install(window: &Window) -> BoxResult<()> {
+    let state = state::window_state(window)?;
+    if let Some(install) = bridge::install_once(&state, "navigation-events").await? {
+        platform::add_navigation_observer(window, {
+            let window = window.clone();
+            move |event| publish(&window, event)
+        })
+        .await?;
+        load_error::install(window).await?;
+        install.done();
+    }
+    Ok(())
+}
+
+pub(crate) fn publish(window: &Window, event: NavigationEvent) {
+    // NOTE: the calls of the previous document are also cancelled natively, since its
+    // "document-unload" message is rejected when it arrives after the window changed origin.
+    if let NavigationEvent::Committed { .. } = event {
+        bridge::cancel_calls(window);
+    }
+    let Ok(state) = state::window_state(window) else {
+        return;
+    };
+    let from_self = match state.self_navigation.lock() {
+        Ok(mut navigation) => {
+            if let NavigationEvent::Started { .. } = event {
+                navigation.current = navigation.tags.take(&());
+            }
+            navigation.current
+        },
+        Err(_) => false,
+    };
+    if let Ok(mut subscribers) = state.navigation_subscribers.lock() {
+        subscribers
+            .retain(|(ignore_self, subscriber)| (from_self && *ignore_self) || subscriber.send(event.clone()).is_ok());
+    }
+    event_bus::publish(window, WebviewEvent::Navigation(event));
+}
+
+// NOTE: resolves with the first navigation that ends after this one was started, so navigations the
+// page starts itself before this one is reported may take its place. Statuses of successful
+// navigations are read from the navigation timing of the document, which WebKit does not report.
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn navigate_and_wait(window: &Window, url: Url) -> BoxResult<NavigationResult> {
+    let mut events = subscribe(window, false).await?;
+    window.webview_navigate(url.clone())?;
+    let mut started = false;
+    let mut http_error = None;
+    while let Some(event) = events.next().await {
+        match event? {
+            NavigationEvent::Started { .. } => started = true,
+            NavigationEvent::Committed { .. } => {},
+            NavigationEvent::Failed(error) if started => match error.kind {
+                LoadErrorKind::Http(_) => http_error = Some(error),
+                // NOTE: the navigation that was in progress is cancelled by this one.
+                LoadErrorKind::Cancelled if error.url.as_ref() != Some(&url) => {},
+                _ => {
+                    return Ok(NavigationResult {
+                        url: error.url.clone().unwrap_or(url),
+                        success: false,
+                        status: None,
+                        error: Some(error),
+                    });
+                },
+            },
+            NavigationEvent::Finished { url } if started => {
+                let status = match http_error.as_ref().map(|error| error.kind) {
+                    Some(LoadErrorKind::Http(status)) => Some(status),
+                    _ => response_status(window).await,
+                };
+                let success = http_error.is_none() && status.map_or(true, |status| status < 400);
+                return Ok(NavigationResult {
+                    url,
+                    success,
+                    status,
+                    error: http_error,
+                });
+            },
+            _ => {},
+        }
+    }
+    Err(Error::ChannelClosed.into())
+}
+
+async fn response_status(window: &Window) -> Option<u16> {
+    let body = "return performance.getEntriesByType(\"navigation\")[0]?.responseStatus || null;";
+    let status = bridge::call(window, body).await.ok()?;
+    status.as_u64().and_then(|status| u16::try_from(status).ok())
+}
@@ -0,0 +1,295 @@
+use crate::{bridge, glob, local_url, platform, state, BoxResult, Error, Result};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use tauri::Window;
+use url::{Host, Url};
+
+#[derive(Clone, Debug)]
+pub struct RewriteRule {
+    pattern: Regex,
+    replacement: String,
+}
+
+impl RewriteRule {
+    // NOTE: the replacement may refer to capture groups of the pattern, like `$1` or `${name}`.
+    pub fn new(pattern: &str, replacement: impl Into<String>) -> Result<Self> {
+        let pattern = Regex::new(pattern)?;
+        let replacement = replacement.into();
+        Ok(Self { pattern, replacement })
+    }
+
+    fn rewrite(&self, url: &str) -> Option<String> {
+        if self.pattern.is_match(url) {
+            Some(self.pattern.replace(url, self.replacement.as_str()).into_owned())
+        } else {
+            None
+        }
+    }
+}
+
+const MAX_REWRITES: usize = 16;
+
+// NOTE: the first rule that matches a URL rewrites it, and the rules are applied again to the result
+// until the first matching rule leaves the URL as it is or no rule matches, so the engine finds
+// nothing left to rewrite when the redirected navigation passes through the policy. Rewriting to a
+// URL that was already seen is a cycle, and every target is validated like a navigation would be.
+pub(crate) fn rewrite(rules: &[RewriteRule], url: &str) -> BoxResult<Option<String>> {
+    let mut seen = vec![];
+    let mut current = url.to_owned();
+    while let Some(rewritten) = rules.iter().find_map(|rule| rule.rewrite(&current)) {
+        if rewritten == current {
+            break;
+        }
+        let invalid = |reason| Error::InvalidNavigationTarget {
+            target: rewritten.clone(),
+            reason,
+        };
+        if seen.contains(&rewritten) {
+            return Err(invalid("the rewrite rules form a cycle").into());
+        }
+        if seen.len() == MAX_REWRITES {
+            return Err(invalid("the URL was rewritten too many times").into());
+        }
+        let target = Url::parse(&rewritten).map_err(|_| invalid("the rewritten URL is not a valid URL"))?;
+        if target.scheme() == "javascript" {
+            return Err(invalid("URLs can not be rewritten to scripts").into());
+        }
+        local_url::target(target)?;
+        seen.push(std::mem::replace(&mut current, rewritten));
+    }
+    Ok(Some(current).filter(|current| current != url))
+}
+
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct HttpsOnlyMode {
+    pub upgrade: bool,
+    pub block_http: bool,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub(crate) enum NavigationPolicy {
+    Allow,
+    Redirect(String),
+    Cancel,
+}
+
+// NOTE: a user agent of `None` restores the one the webview had when the policy was added.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub(crate) struct NavigationDecision {
+    pub(crate) policy: NavigationPolicy,
+    pub(crate) user_agent: Option<String>,
+}
+
+// NOTE: the policy is decided for main frame navigations only, before their request is sent. Every
+// navigation (including redirected ones) passes through it again, so a navigation is only
+// redirected if the rewritten URL differs from the original one. Navigations whose rewrite fails are
+// cancelled and reported.
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn install(window: &Window) -> BoxResult<()> {
+    let state = state::window_state(window)?;
+    let Some(install) = bridge::install_once(&state, "navigation-policy").await? else {
+        return Ok(());
+    };
+    platform::add_navigation_policy(window, {
+        let window = window.clone();
+        move |url| match decide(&window, url) {
+            Ok(decision) => decision,
+            Err(err) if matches!(err.downcast_ref::<Error>(), Some(Error::InvalidNavigationTarget { .. })) => {
+                crate::report_error(window.label(), "failed to rewrite navigation", err);
+                NavigationDecision {
+                    policy: NavigationPolicy::Cancel,
+                    user_agent: None,
+                }
+            },
+            Err(err) => {
+                crate::report_error(window.label(), "failed to decide navigation policy", err);
+                NavigationDecision {
+                    policy: NavigationPolicy::Allow,
+                    user_agent: None,
+                }
+            },
+        }
+    })
+    .await?;
+    install.done();
+    Ok(())
+}
+
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn set_url_rewrites(window: &Window, rules: Vec<RewriteRule>) -> BoxResult<()> {
+    let state = state::window_state(window)?;
+    *state.url_rewrites.lock()? = rules;
+    install(window).await
+}
+
+// NOTE: none of the engines has a public HTTPS-only setting, so only main frame navigations are
+// upgraded or blocked, and subresources are left to the mixed content checks of the engine.
+// Navigations to loopback hosts are exempt, since local development servers rarely serve HTTPS.
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn set_https_only(window: &Window, mode: HttpsOnlyMode) -> BoxResult<()> {
+    let state = state::window_state(window)?;
+    *state.https_only.lock()? = mode;
+    install(window).await
+}
+
+fn decide(window: &Window, url: &str) -> BoxResult<NavigationDecision> {
+    let state = state::window_state(window)?;
+    let rewritten = rewrite(&state.url_rewrites.lock()?, url)?;
+    if let Some(rewritten) = rewritten {
+        return Ok(NavigationDecision {
+            policy: NavigationPolicy::Redirect(rewritten),
+            user_agent: None,
+        });
+    }
+    let https_only = *state.https_only.lock()?;
+    if https_only.upgrade || https_only.block_http {
+        if let Some(url) = Url::parse(url).ok().filter(is_insecure) {
+            let policy = match upgrade(url) {
+                Some(url) if https_only.upgrade => NavigationPolicy::Redirect(url.into()),
+                _ => NavigationPolicy::Cancel,
+            };
+            return Ok(NavigationDecision {
+                policy,
+                user_agent: None,
+            });
+        }
+    }
+    let user_agent = state
+        .user_agent_rules
+        .lock()?
+        .iter()
+        .find(|(pattern, _)| glob::matches(pattern, url))
+        .map(|(_, user_agent)| user_agent.clone());
+    // NOTE: navigations without a rule use the user agent set with `webview_set_user_agent`, if any.
+    let user_agent = match user_agent {
+        Some(user_agent) => Some(user_agent),
+        None => state.user_agent.lock()?.clone(),
+    };
+    Ok(NavigationDecision {
+        policy: NavigationPolicy::Allow,
+        user_agent,
+    })
+}
+
+fn is_insecure(url: &Url) -> bool {
+    let loopback = match url.host() {
+        Some(Host::Domain(domain)) => domain == "localhost" || domain.ends_with(".localhost"),
+        Some(Host::Ipv4(address)) => address.is_loopback(),
+        Some(Host::Ipv6(address)) => address.is_loopback(),
+        None => false,
+    };
+    url.scheme() == "http" && !loopback
+}
+
+fn upgrade(mut url: Url) -> Option<Url> {
+    url.set_scheme("https").ok()?;
+    // NOTE: an explicit port 80 would now be the wrong one, while any other port is kept as is.
+    if url.port() == Some(80) {
+        url.set_port(None).ok()?;
+    }
+    Some(url)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rules(rules: &[(&str, &str)]) -> Vec<RewriteRule> {
+        rules
+            .iter()
+            .map(|(pattern, replacement)| RewriteRule::new(pattern, *replacement).unwrap())
+            .collect()
+    }
+
+    fn invalid_reason(rules: &[RewriteRule], url: &str) -> &'static str {
+        let err = rewrite(rules, url).unwrap_err();
+        match err.downcast_ref::<Error>() {
+            Some(Error::InvalidNavigationTarget { reason, .. }) => *reason,
+            _ => panic!("unexpected error: {err}"),
+        }
+    }
+
+    #[test]
+    fn the_first_matching_rule_wins() {
+        let rules = rules(&[
+            ("^https://old\\.test/(.*)$", "https://new.test/$1"),
+            ("^https://old\\.test/.*$", "https://other.test/"),
+        ]);
+        assert_eq!(
+            rewrite(&rules, "https://old.test/page").unwrap().as_deref(),
+            Some("https://new.test/page")
+        );
+        assert_eq!(rewrite(&rules, "https://unrelated.test/").unwrap(), None);
+    }
+
+    #[test]
+    fn rules_are_applied_to_rewritten_urls() {
+        let rules = rules(&[
+            ("^http://a\\.test/", "http://b.test/"),
+            ("^http://b\\.test/", "https://c.test/"),
+        ]);
+        assert_eq!(
+            rewrite(&rules, "http://a.test/").unwrap().as_deref(),
+            Some("https://c.test/")
+        );
+        // NOTE: the redirected navigation passes through the policy again and is left alone.
+        assert_eq!(rewrite(&rules, "https://c.test/").unwrap(), None);
+    }
+
+    #[test]
+    fn rewrites_to_the_same_url_are_not_redirects() {
+        let rules = rules(&[
+            ("^(https://a\\.test/)$", "$1"),
+            ("^https://a\\.test/$", "https://b.test/"),
+        ]);
+        assert_eq!(rewrite(&rules, "https://a.test/").unwrap(), None);
+    }
+
+    #[test]
+    fn rewrite_cycles_are_rejected() {
+        let rules = rules(&[
+            ("^https://a\\.test/$", "https://b.test/"),
+            ("^https://b\\.test/$", "https://a.test/"),
+        ]);
+        assert_eq!(
+            invalid_reason(&rules, "https://a.test/"),
+            "the rewrite rules form a cycle"
+        );
+    }
+
+    #[test]
+    fn endless_rewrites_are_rejected() {
+        let rules = rules(&[("^https://a\\.test/(.*)$", "https://a.test/x$1")]);
+        assert_eq!(
+            invalid_reason(&rules, "https://a.test/"),
+            "the URL was rewritten too many times"
+        );
+    }
+
+    #[test]
+    fn invalid_targets_are_rejected() {
+        let rules = rules(&[
+            ("^https://a\\.test/$", "not a url"),
+            ("^https://b\\.test/$", "javascript:alert(1)"),
+            ("^https://c\\.test/$", "data:text/html;base64,%%%"),
+        ]);
+        assert_eq!(
+            invalid_reason(&rules, "https://a.test/"),
+            "the rewritten URL is not a valid URL"
+        );
+        assert_eq!(
+            invalid_reason(&rules, "https://b.test/"),
+            "URLs can not be rewritten to scripts"
+        );
+        assert!(rewrite(&rules, "https://c.test/").is_err());
+    }
+}
@@ -0,0 +1,123 @@
+use crate::{document, state, BoxResult, WebviewExt};
+use std::{collections::VecDeque, time::Duration};
+use tauri::Window;
+use url::Url;
+
+#[derive(Default)]
+pub(crate) struct NavigationQueueState {
+    ready: bool,
+    pending: VecDeque<Url>,
+    debounced: Option<Url>,
+    generation: u64,
+}
+
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn queue(window: &Window, url: Url) -> BoxResult<()> {
+    document::install(window).await?;
+    let state = state::window_state(window)?;
+    let navigate_now = {
+        let mut queue = state.navigation_queue.lock()?;
+        if queue.ready && queue.pending.is_empty() {
+            queue.ready = false;
+            Some(url)
+        } else {
+            queue.pending.push_back(url);
+            None
+        }
+    };
+    if let Some(url) = navigate_now {
+        window.webview_navigate(url)?;
+    }
+    Ok(())
+}
+
+// NOTE: only the last of the URLs requested within `delay` of each other is navigated to, once no
+// further request has arrived for `delay`. Requests for the URL that is already pending are
+// dropped without restarting the delay, so a storm of identical requests can not hold it back.
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn debounce(window: &Window, url: Url, delay: Duration) -> BoxResult<()> {
+    let state = state::window_state(window)?;
+    let generation = {
+        let mut queue = state.navigation_queue.lock()?;
+        if queue.debounced.as_ref() == Some(&url) {
+            return Ok(());
+        }
+        queue.debounced = Some(url);
+        queue.generation += 1;
+        queue.generation
+    };
+    let window = window.clone();
+    tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(delay).await;
+        let url = state.navigation_queue.lock().ok().and_then(|mut queue| {
+            if queue.generation == generation {
+                queue.debounced.take()
+            } else {
+                None
+            }
+        });
+        if let Some(url) = url {
+            if let Err(err) = queue(&window, url).await {
+                crate::report_error(window.label(), "failed to navigate after debounce", err);
+            }
+        }
+    });
+    Ok(())
+}
+
+pub(crate) fn pending(window: &Window) -> BoxResult<Option<Url>> {
+    let state = state::window_state(window)?;
+    let queue = state.navigation_queue.lock()?;
+    Ok(queue.debounced.clone().or_else(|| queue.pending.back().cloned()))
+}
+
+// NOTE: cancels the debounced navigation as well as any navigations waiting for the current
+// document, and returns whether there was anything to cancel.
+pub(crate) fn cancel(window: &Window) -> BoxResult<bool> {
+    let state = state::window_state(window)?;
+    let mut queue = state.navigation_queue.lock()?;
+    let cancelled = queue.debounced.is_some() || !queue.pending.is_empty();
+    queue.debounced = None;
+    queue.generation += 1;
+    queue.pending.clear();
+    Ok(cancelled)
+}
+
+pub(crate) fn on_document_ready(window: &Window) {
+    let next = state::window_state(window).and_then(|state| {
+        let mut queue = state.navigation_queue.lock()?;
+        let next = queue.pending.pop_front();
+        queue.ready = next.is_none();
+        Ok(next)
+    });
+    match next {
+        Ok(Some(url)) => navigate(window, url),
+        Ok(None) => {},
+        Err(err) => crate::report_error(window.label(), "failed to update navigation queue", err),
+    }
+}
+
+pub(crate) fn on_document_unload(window: &Window) {
+    if let Ok(state) = state::window_state(window) {
+        if let Ok(mut queue) = state.navigation_queue.lock() {
+            queue.ready = false;
+        }
+    }
+}
+
+// NOTE: bridge messages are delivered on the main thread, where waiting for `with_webview` to
+// complete would deadlock, so the navigation is issued from the async runtime instead.
+fn navigate(window: &Window, url: Url) {
+    let window = window.clone();
+    tauri::async_runtime::spawn(async move {
+        if let Err(err) = window.webview_navigate(url) {
+            crate::report_error(window.label(), "failed to replay queued navigation", err);
+        }
+    });
+}
@@ -0,0 +1,80 @@
+use crate::{
+    bridge,
+    permission::{self, PermissionDecision, PermissionKind, PermissionRequest},
+    request,
+    BoxResult,
+};
+use futures::{future, FutureExt};
+use serde::Deserialize;
+use tauri::{api::notification::Notification, Manager, Window};
+
+const NOTIFICATION_SCRIPT: &str = include_str!("js/notification.js");
+
+#[derive(Debug, Deserialize)]
+struct NotificationRequest {
+    title: String,
+    body: Option<String>,
+    icon: Option<String>,
+    url: Option<String>,
+}
+
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn install(window: &Window) -> BoxResult<()> {
+    let identifier = window.config().tauri.bundle.identifier.clone();
+    let label = String::from(window.label());
+    request::on_request(window, "notification-permission", {
+        let window = window.clone();
+        move |payload| {
+            let state = permission_state(&window, payload["url"].as_str());
+            future::ready(Ok(state.into())).boxed()
+        }
+    })
+    .await?;
+    bridge::on_message(window, "notification", {
+        let window = window.clone();
+        move |payload| {
+            let request = match serde_json::from_value::<NotificationRequest>(payload) {
+                Ok(request) => request,
+                Err(err) => return crate::report_error(&label, "malformed notification request", err),
+            };
+            if permission_state(&window, request.url.as_deref()) != "granted" {
+                return;
+            }
+            #[cfg(all(feature = "tracing", not(feature = "tracing-verbose")))]
+            tracing::debug!(window.label = %label, "notification requested");
+            #[cfg(feature = "tracing-verbose")]
+            tracing::debug!(window.label = %label, ?request, "notification requested");
+            let mut notification = Notification::new(&identifier).title(request.title);
+            if let Some(body) = request.body {
+                notification = notification.body(body);
+            }
+            if let Some(icon) = request.icon {
+                notification = notification.icon(icon);
+            }
+            if let Err(err) = notification.show() {
+                crate::report_error(&label, "failed to show notification", err);
+            }
+        }
+    })
+    .await?;
+    bridge::add_script(window, NOTIFICATION_SCRIPT).await
+}
+
+// NOTE: the state the page sees is decided by the permission handler of the window, see
+// `WebviewExt::webview_on_permission_request`. Without a decision, notifications are granted when
+// the notification API of tauri is allowed for the app, which is what tauri reports for it itself.
+fn permission_state(window: &Window, url: Option<&str>) -> &'static str {
+    let request = PermissionRequest {
+        kind: PermissionKind::Notifications,
+        origin: url.and_then(permission::origin),
+    };
+    match permission::decide(window, request) {
+        PermissionDecision::Allow => "granted",
+        PermissionDecision::Deny => "denied",
+        PermissionDecision::Default if window.config().tauri.allowlist.notification.all => "granted",
+        PermissionDecision::Default => "denied",
+    }
+}
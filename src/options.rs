@@ -0,0 +1,125 @@
+use crate::StreamConfig;
+use std::{sync::RwLock, time::Duration};
+
+static OPTIONS: RwLock<Options> = RwLock::new(Options::new());
+
+// NOTE: the retry policy applies to operations that are safe to repeat, like `webview_wait_for`,
+// when the document they run in is unloaded before they complete.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct RetryPolicy {
+    pub max_attempts: Option<u32>,
+    pub delay: Duration,
+}
+
+impl RetryPolicy {
+    pub const fn new() -> Self {
+        Self {
+            max_attempts: None,
+            delay: Duration::from_millis(50),
+        }
+    }
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// NOTE: `default_timeout` limits how long scripts evaluated in the page may take to reply, except for
+// operations that take a timeout of their own. `max_result_bytes` limits the size of script results
+// as JSON text, which chunked evaluation checks in the page before anything is sent. The target
+// of tracing spans is fixed at compile time, so it is not an option. With `ignore_self`, the cookie,
+// navigation, and storage watchers leave out the changes made through this crate, so that features
+// which mirror those changes elsewhere do not end up in a loop.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[non_exhaustive]
+pub struct Options {
+    pub default_timeout: Option<Duration>,
+    pub stream: StreamConfig,
+    pub redact_cookie_values: bool,
+    pub retry: RetryPolicy,
+    pub max_result_bytes: Option<usize>,
+    pub ignore_self: bool,
+}
+
+impl Options {
+    pub const fn new() -> Self {
+        Self {
+            default_timeout: None,
+            stream: StreamConfig::unbounded(),
+            redact_cookie_values: true,
+            retry: RetryPolicy::new(),
+            max_result_bytes: None,
+            ignore_self: false,
+        }
+    }
+
+    pub fn builder() -> OptionsBuilder {
+        OptionsBuilder::default()
+    }
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct OptionsBuilder {
+    options: Options,
+}
+
+impl OptionsBuilder {
+    pub fn default_timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.options.default_timeout = timeout;
+        self
+    }
+
+    pub fn stream(mut self, config: StreamConfig) -> Self {
+        self.options.stream = config;
+        self
+    }
+
+    pub fn redact_cookie_values(mut self, redact: bool) -> Self {
+        self.options.redact_cookie_values = redact;
+        self
+    }
+
+    pub fn retry(mut self, retry: RetryPolicy) -> Self {
+        self.options.retry = retry;
+        self
+    }
+
+    pub fn max_result_bytes(mut self, max: Option<usize>) -> Self {
+        self.options.max_result_bytes = max;
+        self
+    }
+
+    pub fn ignore_self(mut self, ignore: bool) -> Self {
+        self.options.ignore_self = ignore;
+        self
+    }
+
+    pub fn build(self) -> Options {
+        self.options
+    }
+}
+
+// NOTE: options apply to operations started afterwards, in every window.
+pub fn configure(options: Options) {
+    if let Ok(mut current) = OPTIONS.write() {
+        *current = options;
+    }
+}
+
+pub fn options() -> Options {
+    OPTIONS.read().map(|options| *options).unwrap_or_default()
+}
+
+pub(crate) fn update(update: impl FnOnce(&mut Options)) {
+    if let Ok(mut current) = OPTIONS.write() {
+        update(&mut current);
+    }
+}
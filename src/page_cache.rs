@@ -0,0 +1,17 @@
+use crate::{init_script, platform, state, BoxResult};
+use tauri::Window;
+
+const PAGE_CACHE_SCRIPT: &str = include_str!("js/page_cache.js");
+
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn disable(window: &Window) -> BoxResult<()> {
+    if platform::disable_page_cache(window).await? {
+        return Ok(());
+    }
+    let state = state::window_state(window)?;
+    let source = format!("({PAGE_CACHE_SCRIPT})();");
+    init_script::replace(window, &state.page_cache_script, source).await
+}
@@ -0,0 +1,54 @@
+use crate::{Error, Result};
+use std::path::PathBuf;
+use tauri::{AppHandle, Runtime};
+
+/// Returns the webview data directory for the named storage partition.
+///
+/// None of the engines can move an existing webview to a different data store, so partitions have
+/// to be selected when the window is created:
+///
+/// ```no_run
+/// # fn example(app: &tauri::AppHandle) -> tauri_webview_util::BoxResult<()> {
+/// use tauri::{WindowBuilder, WindowUrl};
+/// let directory = tauri_webview_util::storage_partition_directory(app, "account-2")?;
+/// WindowBuilder::new(app, "account-2", WindowUrl::default())
+///     .data_directory(directory)
+///     .build()?;
+/// # Ok(())
+/// # }
+/// ```
+///
+/// Windows with different partitions do not share cookies or storage. This is supported with
+/// WebView2 and webkit2gtk. WKWebView always uses the default data store and returns an error.
+pub fn storage_partition_directory<R: Runtime>(app: &AppHandle<R>, partition: &str) -> Result<PathBuf> {
+    if cfg!(target_os = "macos") {
+        return Err(Error::UnsupportedPlatform(
+            "storage partitions are not supported with WKWebView",
+        ));
+    }
+    let is_valid = |c: char| c.is_ascii_alphanumeric() || c == '-' || c == '_';
+    if partition.is_empty() || !partition.chars().all(is_valid) {
+        let msg = format!(r#""{partition}" is not a valid storage partition name"#);
+        return Err(Error::Other(msg.into()));
+    }
+    let directory = app
+        .path_resolver()
+        .app_data_dir()
+        .ok_or_else(|| Error::PlatformApi("failed to resolve the app data directory".into()))?
+        .join("webview-partitions")
+        .join(partition);
+    Ok(directory)
+}
+
+/// Removes the data of the named storage partition from disk.
+///
+/// The engines keep the files of a partition open while a window uses it, so this should only be
+/// called once all windows of the partition are closed. Removing a partition that has no data is
+/// not an error.
+pub fn remove_storage_partition<R: Runtime>(app: &AppHandle<R>, partition: &str) -> Result<()> {
+    let directory = storage_partition_directory(app, partition)?;
+    match std::fs::remove_dir_all(directory) {
+        Err(err) if err.kind() != std::io::ErrorKind::NotFound => Err(err.into()),
+        _ => Ok(()),
+    }
+}
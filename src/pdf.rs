@@ -0,0 +1,58 @@
+use crate::{
+    bridge,
+    event_bus,
+    event_bus::WebviewEvent,
+    navigation_events::{self, NavigationEvent},
+    platform,
+    state,
+    BoxResult,
+};
+use futures::prelude::*;
+use serde::{Deserialize, Serialize};
+use tauri::Window;
+
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct PdfViewerSettings {
+    pub toolbar: bool,
+    pub download_allowed: bool,
+}
+
+impl Default for PdfViewerSettings {
+    fn default() -> Self {
+        Self {
+            toolbar: true,
+            download_allowed: true,
+        }
+    }
+}
+
+// NOTE: only WebView2 lets the toolbar of its PDF viewer be configured. Every engine reports the
+// PDF documents it displays, which are recognized by their content type once they finish loading.
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn set_settings(window: &Window, settings: PdfViewerSettings) -> BoxResult<()> {
+    platform::set_pdf_settings(window, settings).await?;
+    let state = state::window_state(window)?;
+    let Some(install) = bridge::install_once(&state, "pdf").await? else {
+        return Ok(());
+    };
+    let mut events = navigation_events::subscribe(window, false).await?;
+    let window = window.clone();
+    tauri::async_runtime::spawn(async move {
+        while let Some(event) = events.next().await {
+            if let Ok(NavigationEvent::Finished { url }) = event {
+                match platform::eval_js(&window, "document.contentType".into()).await {
+                    Ok(content_type) if content_type == "application/pdf" => {
+                        event_bus::publish(&window, WebviewEvent::PdfDisplayed { url });
+                    },
+                    Ok(_) => {},
+                    Err(err) => crate::report_error(window.label(), "failed to read document content type", err),
+                }
+            }
+        }
+    });
+    install.done();
+    Ok(())
+}
@@ -0,0 +1,75 @@
+use crate::{bridge, platform, state, BoxResult};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tauri::Window;
+use url::Url;
+
+pub(crate) type PermissionHandler = Arc<dyn Fn(PermissionRequest) -> PermissionDecision + Send + Sync + 'static>;
+
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[non_exhaustive]
+pub enum PermissionKind {
+    Camera,
+    Microphone,
+    CameraAndMicrophone,
+    Geolocation,
+    Notifications,
+    Other,
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[non_exhaustive]
+pub struct PermissionRequest {
+    pub kind: PermissionKind,
+    pub origin: Option<Url>,
+}
+
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub enum PermissionDecision {
+    // NOTE: leaves the request to the platform, which prompts or denies it like it would without a
+    // handler.
+    #[default]
+    Default,
+    Allow,
+    Deny,
+}
+
+// NOTE: the handler is called on the main thread while the page waits for the decision, so it
+// should not block. Later calls replace the handler.
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window, handler),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn on_request(
+    window: &Window,
+    handler: impl Fn(PermissionRequest) -> PermissionDecision + Send + Sync + 'static,
+) -> BoxResult<()> {
+    let state = state::window_state(window)?;
+    *state.permission_handler.lock()? = Some(Arc::new(handler));
+    if let Some(install) = bridge::install_once(&state, "permission-request").await? {
+        platform::add_permission_handler(window, {
+            let window = window.clone();
+            move |request| decide(&window, request)
+        })
+        .await?;
+        install.done();
+    }
+    Ok(())
+}
+
+pub(crate) fn decide(window: &Window, request: PermissionRequest) -> PermissionDecision {
+    state::window_state(window)
+        .and_then(|state| Ok(state.permission_handler.lock()?.clone()))
+        .ok()
+        .flatten()
+        .map(|handler| handler(request))
+        .unwrap_or_default()
+}
+
+pub(crate) fn origin(url: &str) -> Option<Url> {
+    let origin = Url::parse(url).ok()?.origin();
+    origin
+        .is_tuple()
+        .then(|| Url::parse(&origin.ascii_serialization()).ok())
+        .flatten()
+}
@@ -0,0 +1,62 @@
+use crate::{Cookie, CookiePattern, Error, Result, WebviewExt};
+use tauri::{
+    plugin::{Builder, TauriPlugin},
+    Window,
+    Wry,
+};
+use url::Url;
+
+// NOTE: the commands are invoked from the frontend as `plugin:webview-util|<command>`, and act on
+// the webview of the window that invoked them. With the `cli` feature, debug builds also listen on
+// the debug socket for the `webview-util` command-line utility.
+pub fn init() -> TauriPlugin<Wry> {
+    let builder = Builder::new("webview-util").invoke_handler(tauri::generate_handler![
+        get_cookies,
+        delete_cookies,
+        delete_matching_cookies,
+        clear_cache,
+        navigate,
+    ]);
+    #[cfg(feature = "cli")]
+    let builder = builder.setup(|app| {
+        crate::debug_socket::serve(app.clone());
+        Ok(())
+    });
+    builder.build()
+}
+
+#[tauri::command]
+async fn get_cookies(window: Window, url: Option<Url>, pattern: Option<CookiePattern>) -> Result<Vec<Cookie>> {
+    let mut cookies = window.webview_get_cookies(url).await?;
+    if let Some(pattern) = pattern {
+        cookies.retain(|cookie| pattern.matches(cookie));
+    }
+    Ok(cookies)
+}
+
+#[tauri::command]
+async fn delete_cookies(window: Window, url: Option<Url>) -> Result<Vec<Cookie>> {
+    window.webview_delete_cookies(url).await
+}
+
+#[tauri::command]
+async fn delete_matching_cookies(window: Window, pattern: CookiePattern) -> Result<Vec<Cookie>> {
+    window.webview_delete_matching_cookies(pattern).await
+}
+
+#[tauri::command]
+async fn clear_cache(window: Window) -> Result<()> {
+    window.webview_clear_cache().await
+}
+
+#[tauri::command]
+fn navigate(window: Window, url: Url) -> Result<()> {
+    window.webview_navigate(url)
+}
+
+// NOTE: errors reach the frontend as their message, with the causes that are part of it.
+impl serde::Serialize for Error {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_string())
+    }
+}
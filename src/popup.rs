@@ -0,0 +1,88 @@
+use crate::{bridge, request, BoxResult};
+use futures::FutureExt;
+use serde::Deserialize;
+use std::sync::Arc;
+use tauri::{utils::config::WindowConfig, Manager, Window, WindowBuilder, WindowEvent, WindowUrl};
+use url::Url;
+
+const POPUP_SCRIPT: &str = include_str!("js/popup.js");
+
+#[derive(Debug, Deserialize)]
+struct PopupOpenRequest {
+    id: String,
+    url: Url,
+}
+
+#[derive(Debug, Deserialize)]
+struct PopupCloseRequest {
+    id: String,
+}
+
+fn popup_label(window: &Window, id: &str) -> String {
+    format!("{}-popup-{}", window.label(), id)
+}
+
+// NOTE: popups are created as ordinary tauri windows, so they share the default data store (and
+// therefore the cookies and session) of the opening window. There is however no `window.opener`
+// relationship between the two, so flows that rely on `opener.postMessage` need to communicate
+// through the native side instead. The page is told when a popup window is destroyed, so that flows
+// which poll `closed` still see the popup close. Popups that `builder` declines are opened as they
+// would be without this crate.
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window, builder),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn install(
+    window: &Window,
+    builder: impl Fn(&Url) -> Option<WindowConfig> + Send + Sync + 'static,
+) -> BoxResult<()> {
+    let builder = Arc::new(builder);
+    request::on_request(window, "popup-open", {
+        let window = window.clone();
+        move |payload| {
+            let window = window.clone();
+            let builder = builder.clone();
+            async move {
+                let request = serde_json::from_value::<PopupOpenRequest>(payload)?;
+                #[cfg(feature = "tracing")]
+                tracing::debug!(window.label = window.label(), url = %request.url, "popup requested");
+                let Some(mut config) = builder(&request.url) else {
+                    return Ok(false.into());
+                };
+                config.label = popup_label(&window, &request.id);
+                config.url = WindowUrl::External(request.url);
+                // NOTE: requests are answered on the async runtime, so building the window does not
+                // block the main thread, where it would deadlock on some platforms.
+                let popup = WindowBuilder::from_config(&window.app_handle(), config).build()?;
+                popup.on_window_event(move |event| {
+                    if let WindowEvent::Destroyed = event {
+                        let payload = serde_json::json!({ "id": request.id });
+                        let script = format!("window.__TAURI_WEBVIEW_UTIL__.receive(\"popup-closed\", {payload});");
+                        if let Err(err) = window.eval(&script) {
+                            crate::report_error(window.label(), "failed to report closed popup window", err);
+                        }
+                    }
+                });
+                Ok(true.into())
+            }
+            .boxed()
+        }
+    })
+    .await?;
+    bridge::on_message(window, "popup-close", {
+        let window = window.clone();
+        move |payload| {
+            let request = match serde_json::from_value::<PopupCloseRequest>(payload) {
+                Ok(request) => request,
+                Err(err) => return crate::report_error(window.label(), "malformed popup request", err),
+            };
+            if let Some(popup) = window.get_window(&popup_label(&window, &request.id)) {
+                if let Err(err) = popup.close() {
+                    crate::report_error(window.label(), "failed to close popup window", err);
+                }
+            }
+        }
+    })
+    .await?;
+    bridge::add_script(window, POPUP_SCRIPT).await
+}
@@ -0,0 +1,72 @@
+use crate::{platform, storage_partition_directory, window_builder_ext::WebviewDataStore, BoxResult, Error, Result};
+use serde::Serialize;
+use std::{
+    path::PathBuf,
+    time::{Duration, Instant},
+};
+use tauri::{AppHandle, Runtime};
+
+/// What [`preload_engine`] prepares the engine for, which should match the windows created after it.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct PreloadConfig {
+    pub data_store: WebviewDataStore,
+    #[cfg(target_os = "windows")]
+    pub environment: crate::webview2::EnvironmentOptions,
+}
+
+/// How long each step of [`preload_engine`] took, so that the time it saves the first window can be
+/// compared with what it costs.
+#[derive(Clone, Debug, Default, Eq, PartialEq, Serialize)]
+pub struct PreloadTimings {
+    pub steps: Vec<(String, Duration)>,
+    pub total: Duration,
+}
+
+impl PreloadTimings {
+    pub(crate) fn time<T>(&mut self, step: &str, run: impl FnOnce() -> T) -> T {
+        let started = Instant::now();
+        let value = run();
+        let elapsed = started.elapsed();
+        self.steps.push((step.into(), elapsed));
+        self.total += elapsed;
+        value
+    }
+}
+
+/// Starts the webview engine before the first window is created, e.g. in the `setup` hook of the
+/// app, so that the window does not have to wait for it.
+///
+/// With WebView2 the browser process of the data store is started and kept running, which windows
+/// created with the same data store and environment options attach to. With WKWebView a hidden
+/// webview starts the processes WebKit shares between all webviews of the app, and is kept. With
+/// WebKitGTK, where tauri gives every window a web context of its own, a hidden webview of an
+/// ephemeral context loads WebKit and its helper programs once, and is dropped afterwards.
+///
+/// This has to be called on the main thread.
+pub fn preload_engine<R: Runtime>(app: &AppHandle<R>, config: PreloadConfig) -> Result<PreloadTimings> {
+    if !crate::blocking::on_main_thread() {
+        return Err(Error::UnsupportedPlatform(
+            "the engine can only be preloaded on the main thread",
+        ));
+    }
+    let mut timings = PreloadTimings::default();
+    let data_directory = data_directory(app, &config.data_store)?;
+    platform::preload_engine(data_directory, &config, &mut timings)?;
+    #[cfg(feature = "tracing")]
+    tracing::info!(?timings, "preloaded the webview engine");
+    Ok(timings)
+}
+
+// NOTE: like tauri, the default data store is the local data directory of the app on Linux and
+// Windows. WKWebView only has the default data store.
+fn data_directory<R: Runtime>(app: &AppHandle<R>, store: &WebviewDataStore) -> BoxResult<Option<PathBuf>> {
+    match store {
+        WebviewDataStore::Default if cfg!(target_os = "macos") => Ok(None),
+        WebviewDataStore::Default => Ok(app.path_resolver().app_local_data_dir()),
+        _ if cfg!(target_os = "macos") => {
+            Err(Error::UnsupportedPlatform("custom data stores are not supported with WKWebView").into())
+        },
+        WebviewDataStore::Directory(directory) => Ok(Some(directory.clone())),
+        WebviewDataStore::Partition(partition) => Ok(Some(storage_partition_directory(app, partition)?)),
+    }
+}
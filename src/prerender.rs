@@ -0,0 +1,21 @@
+use crate::{bridge, BoxResult, Error};
+use tauri::Window;
+use url::Url;
+
+const PRERENDER_SCRIPT: &str = include_str!("js/prerender.js");
+
+// NOTE: speculation rules are added to the current document, so the page is only prerendered for a
+// navigation away from it. Browsers only prerender pages of the same origin, so pages of other
+// origins are prefetched instead. WebKit does not support speculation rules.
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn prerender(window: &Window, url: Url) -> BoxResult<()> {
+    let url = serde_json::to_string(url.as_str())?;
+    let body = format!("return ({PRERENDER_SCRIPT})({url});");
+    match bridge::call(window, &body).await?.as_bool() {
+        Some(true) => Ok(()),
+        _ => Err(Error::UnsupportedPlatform("prerendering is not supported by this webview").into()),
+    }
+}
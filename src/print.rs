@@ -0,0 +1,129 @@
+use crate::{bridge, event_bus, event_bus::WebviewEvent, platform, request, state, BoxResult, Error};
+use futures::prelude::*;
+use serde::{Deserialize, Serialize};
+use tauri::Window;
+use url::Url;
+
+const PRINT_SCRIPT: &str = include_str!("js/print.js");
+
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub enum PrintAction {
+    #[default]
+    Native,
+    Suppress,
+    Event,
+}
+
+// NOTE: sizes are in inches, and the defaults are a US Letter page with the default margins of
+// Chromium.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+pub struct PdfOptions {
+    pub landscape: bool,
+    pub print_background: bool,
+    pub scale: f64,
+    pub page_width: f64,
+    pub page_height: f64,
+    pub margin_top: f64,
+    pub margin_bottom: f64,
+    pub margin_left: f64,
+    pub margin_right: f64,
+}
+
+impl Default for PdfOptions {
+    fn default() -> Self {
+        Self {
+            landscape: false,
+            print_background: false,
+            scale: 1.0,
+            page_width: 8.5,
+            page_height: 11.0,
+            margin_top: 0.4,
+            margin_bottom: 0.4,
+            margin_left: 0.4,
+            margin_right: 0.4,
+        }
+    }
+}
+
+// NOTE: `window.print()` returns immediately once intercepted instead of blocking until the dialog
+// is closed. Native printing uses the print operation of the engine where one is available, and
+// otherwise falls back to the original `window.print()` of the document.
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn intercept(window: &Window, action: PrintAction) -> BoxResult<()> {
+    let state = state::window_state(window)?;
+    *state.print.lock()? = action;
+    let Some(install) = bridge::install_once(&state, "print").await? else {
+        return Ok(());
+    };
+    request::on_request(window, "print", {
+        let window = window.clone();
+        move |payload| {
+            let window = window.clone();
+            async move {
+                let action = state::window_state(&window)
+                    .and_then(|state| Ok(*state.print.lock()?))
+                    .unwrap_or_default();
+                let handled = match action {
+                    PrintAction::Native => platform::print(&window).await?,
+                    PrintAction::Suppress => true,
+                    PrintAction::Event => {
+                        let url = payload["url"].as_str().and_then(|url| Url::parse(url).ok());
+                        let url = url.unwrap_or_else(|| window.url());
+                        event_bus::publish(&window, WebviewEvent::PrintRequested { url });
+                        true
+                    },
+                };
+                Ok(handled.into())
+            }
+            .boxed()
+        }
+    })
+    .await?;
+    bridge::add_script(window, PRINT_SCRIPT).await?;
+    install.done();
+    Ok(())
+}
+
+// NOTE: engines without a print operation of their own print with `window.print()`, which goes
+// through `webview_intercept_print` when it is installed.
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn print(window: &Window) -> BoxResult<()> {
+    if !platform::print(window).await? {
+        platform::eval_js(window, "window.print()".into()).await?;
+    }
+    Ok(())
+}
+
+// NOTE: the engines write the document to a file, which is removed again once it has been read.
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn print_to_pdf(window: &Window, options: PdfOptions) -> BoxResult<Vec<u8>> {
+    let sizes = [options.scale, options.page_width, options.page_height];
+    let margins = [
+        options.margin_top,
+        options.margin_bottom,
+        options.margin_left,
+        options.margin_right,
+    ];
+    let valid_sizes = sizes.iter().all(|size| size.is_finite() && *size > 0.0);
+    let valid_margins = margins.iter().all(|margin| margin.is_finite() && *margin >= 0.0);
+    if !valid_sizes || !valid_margins {
+        return Err(Error::Other("invalid PDF page options".into()).into());
+    }
+    let directory = std::env::temp_dir().join("tauri-webview-util").join("pdf");
+    std::fs::create_dir_all(&directory)?;
+    let nanos = time::OffsetDateTime::now_utc().unix_timestamp_nanos();
+    let path = directory.join(format!("{}-{nanos}.pdf", window.label()));
+    let result = platform::print_to_pdf(window, path.clone(), options).await;
+    let bytes = result.and_then(|()| Ok(std::fs::read(&path)?));
+    std::fs::remove_file(&path).ok();
+    bytes
+}
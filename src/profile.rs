@@ -0,0 +1,20 @@
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+// NOTE: an ephemeral session keeps all of its website data in memory, so nothing of it is written
+// to disk and it is gone once the engine is done with it.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub enum SessionKind {
+    #[default]
+    Persistent,
+    Ephemeral,
+}
+
+// NOTE: only WebView2 has named profiles, and InPrivate profiles are reported as ephemeral there.
+// `directory` is where the data of a persistent session is kept, and `None` for ephemeral sessions.
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct ProfileInfo {
+    pub name: Option<String>,
+    pub session: SessionKind,
+    pub directory: Option<PathBuf>,
+}
@@ -0,0 +1,187 @@
+use crate::{platform, website_data::DataKinds, BoxResult, Result};
+use serde::{Deserialize, Serialize};
+use std::{
+    io::Read,
+    path::{Path, PathBuf},
+};
+use tauri::Window;
+
+const SQLITE_HEADER: &[u8; 16] = b"SQLite format 3\0";
+
+// NOTE: Chromium (and so WebView2) keeps its databases in files without an extension, while
+// WebKit uses these extensions for its own.
+const DATABASE_NAMES: &[&str] = &["Cookies", "Favicons", "History", "Login Data", "Top Sites", "Web Data"];
+const DATABASE_EXTENSIONS: &[&str] = &["db", "localstorage", "sqlite", "sqlite3"];
+const PREFERENCES_NAMES: &[&str] = &["Local State", "Preferences", "Secure Preferences"];
+const LOCK_NAMES: &[&str] = &["lockfile"];
+
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[non_exhaustive]
+pub enum ProfileIssue {
+    EmptyDatabase(PathBuf),
+    CorruptDatabase(PathBuf),
+    EmptyPreferences(PathBuf),
+    // NOTE: a lock file that no process holds anymore, which is left behind by a crashed engine.
+    StaleLock(PathBuf),
+    // NOTE: the profile is in use by another process, which usually is another instance of the app.
+    // This can not be repaired other than by closing that process.
+    Locked(PathBuf),
+}
+
+impl ProfileIssue {
+    pub fn path(&self) -> &Path {
+        match self {
+            Self::EmptyDatabase(path)
+            | Self::CorruptDatabase(path)
+            | Self::EmptyPreferences(path)
+            | Self::StaleLock(path)
+            | Self::Locked(path) => path,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[non_exhaustive]
+pub struct ProfileHealth {
+    // NOTE: `None` when the engine does not persist the profile, in which case there is nothing to
+    // check.
+    pub directory: Option<PathBuf>,
+    pub issues: Vec<ProfileIssue>,
+}
+
+impl ProfileHealth {
+    pub fn is_healthy(&self) -> bool {
+        self.issues.is_empty()
+    }
+}
+
+// NOTE: meant to be run on the data directory before the window that uses it is created, since
+// that is the only time the lock files tell anything and the files can be removed safely.
+pub fn profile_health_check(directory: &Path) -> Result<ProfileHealth> {
+    let issues = if directory.exists() {
+        check(directory, true)?
+    } else {
+        vec![]
+    };
+    let directory = Some(directory.to_path_buf());
+    Ok(ProfileHealth { directory, issues })
+}
+
+// NOTE: removes the offending files so that the engine recreates them, and returns their paths.
+// The data in a corrupt database is lost either way, since the engine would not open it.
+pub fn repair_profile(health: &ProfileHealth) -> Result<Vec<PathBuf>> {
+    let mut removed = vec![];
+    for issue in &health.issues {
+        if let ProfileIssue::Locked(_) = issue {
+            continue;
+        }
+        let path = issue.path();
+        match std::fs::remove_file(path) {
+            Ok(()) => removed.push(path.to_path_buf()),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => {},
+            Err(err) => return Err(err.into()),
+        }
+    }
+    Ok(removed)
+}
+
+// NOTE: the engine of the window holds the locks of its own profile, so those are not checked.
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn health_check(window: &Window) -> BoxResult<ProfileHealth> {
+    let directory = platform::profile_directory(window).await?;
+    let issues = match &directory {
+        Some(directory) if directory.exists() => check(directory, false)?,
+        _ => vec![],
+    };
+    Ok(ProfileHealth { directory, issues })
+}
+
+// NOTE: the files of a running engine can not be removed, so the website data is cleared instead,
+// which makes the engine rewrite its databases.
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn repair(window: &Window) -> BoxResult<ProfileHealth> {
+    let health = health_check(window).await?;
+    if health.is_healthy() {
+        return Ok(health);
+    }
+    platform::clear_data(window, DataKinds::all()).await?;
+    health_check(window).await
+}
+
+fn check(directory: &Path, locks: bool) -> std::io::Result<Vec<ProfileIssue>> {
+    let mut issues = vec![];
+    let mut directories = vec![directory.to_path_buf()];
+    while let Some(directory) = directories.pop() {
+        for entry in std::fs::read_dir(&directory)? {
+            let entry = entry?;
+            let path = entry.path();
+            let name = entry.file_name();
+            let name = name.to_string_lossy();
+            let file_type = entry.file_type()?;
+            // NOTE: caches are skipped since they are large and the engines discard broken entries
+            // on their own.
+            if file_type.is_dir() {
+                if !name.contains("Cache") {
+                    directories.push(path);
+                }
+                continue;
+            }
+            if !file_type.is_file() {
+                continue;
+            }
+            if let Some(issue) = check_file(path, &name, locks)? {
+                issues.push(issue);
+            }
+        }
+    }
+    issues.sort_by(|lhs, rhs| lhs.path().cmp(rhs.path()));
+    Ok(issues)
+}
+
+fn check_file(path: PathBuf, name: &str, locks: bool) -> std::io::Result<Option<ProfileIssue>> {
+    if LOCK_NAMES.contains(&name) {
+        if !locks {
+            return Ok(None);
+        }
+        // NOTE: the engine holds its lock file open without sharing it, so being able to open it
+        // means that the process which created it is gone.
+        let issue = match std::fs::OpenOptions::new().write(true).open(&path) {
+            Ok(_) => ProfileIssue::StaleLock(path),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(_) => ProfileIssue::Locked(path),
+        };
+        return Ok(Some(issue));
+    }
+    let is_database = DATABASE_NAMES.contains(&name)
+        || path
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .map_or(false, |extension| DATABASE_EXTENSIONS.contains(&extension));
+    let is_preferences = PREFERENCES_NAMES.contains(&name);
+    if !is_database && !is_preferences {
+        return Ok(None);
+    }
+    let mut file = std::fs::File::open(&path)?;
+    if file.metadata()?.len() == 0 {
+        let issue = if is_database {
+            ProfileIssue::EmptyDatabase(path)
+        } else {
+            ProfileIssue::EmptyPreferences(path)
+        };
+        return Ok(Some(issue));
+    }
+    if is_database {
+        let mut header = [0; SQLITE_HEADER.len()];
+        let is_sqlite = file.read_exact(&mut header).is_ok() && &header == SQLITE_HEADER;
+        if !is_sqlite {
+            return Ok(Some(ProfileIssue::CorruptDatabase(path)));
+        }
+    }
+    Ok(None)
+}
@@ -0,0 +1,215 @@
+use crate::{
+    cookie_db,
+    cookie_jar::{deserialize_cookies, CookieJarFormat},
+    error::WindowContext,
+    platform,
+    storage::{self, StorageArea},
+    BoxResult,
+    Cookie,
+    CookiePattern,
+    LocalStorageSnapshot,
+    Result,
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+};
+use tauri::Window;
+use time::OffsetDateTime;
+use url::Url;
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct MigrationOptions {
+    pub pattern: CookiePattern,
+    pub cookies: bool,
+    pub local_storage: bool,
+}
+
+impl Default for MigrationOptions {
+    fn default() -> Self {
+        Self {
+            pattern: CookiePattern::default(),
+            cookies: true,
+            local_storage: true,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[non_exhaustive]
+pub struct MigrationReport {
+    pub sources: Vec<PathBuf>,
+    pub cookies_imported: usize,
+    // NOTE: Chromium (and so WebView2) encrypts cookie values with a key that is bound to the user
+    // account and the browser, so these can not be imported.
+    pub cookies_encrypted: usize,
+    pub local_storage_imported: usize,
+    // NOTE: local storage can only be written through a document of its origin, so the local storage
+    // of other origins is returned to be imported with `webview_import_session` once they are loaded.
+    pub pending_local_storage: Vec<LocalStorageSnapshot>,
+}
+
+enum Source {
+    // NOTE: the text file of libsoup, which wry uses with webkit2gtk.
+    CookieText,
+    // NOTE: the SQLite database of libsoup.
+    CookieSqlite,
+    // NOTE: the SQLite database of Chromium, which WebView2 uses.
+    ChromiumCookies,
+    // NOTE: the SQLite database per origin of (older) WebKit.
+    WebKitLocalStorage,
+}
+
+// NOTE: reads the cookies and local storage of an engine profile that is no longer in use, such as
+// the data directory of a previous release, and imports them into the webview. Expired cookies are
+// skipped, and when the same cookie is found more than once the last one read wins. The binary
+// cookies of WebKit on macOS and the LevelDB local storage of Chromium are not supported.
+pub async fn migrate_profile(
+    from_dir: &Path,
+    to_window: &Window,
+    options: MigrationOptions,
+) -> Result<MigrationReport> {
+    migrate(from_dir, to_window, options)
+        .await
+        .window_context(to_window, "migrate profile")
+}
+
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window, options),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+async fn migrate(from_dir: &Path, window: &Window, options: MigrationOptions) -> BoxResult<MigrationReport> {
+    let mut report = MigrationReport::default();
+    let mut cookies = BTreeMap::new();
+    let mut local_storage = vec![];
+    for (path, source) in sources(from_dir)? {
+        match source {
+            Source::CookieText if options.cookies => {
+                let text = std::fs::read_to_string(&path)?;
+                for cookie in deserialize_cookies(&text, CookieJarFormat::Netscape)? {
+                    insert(&mut cookies, cookie);
+                }
+            },
+            Source::CookieSqlite if options.cookies => {
+                for cookie in cookie_db::read_moz_cookies(&path)? {
+                    insert(&mut cookies, cookie);
+                }
+            },
+            Source::ChromiumCookies if options.cookies => {
+                let (read, encrypted) = cookie_db::read_chromium_cookies(&path, |_, _| None)?;
+                report.cookies_encrypted += encrypted;
+                for cookie in read {
+                    insert(&mut cookies, cookie);
+                }
+            },
+            Source::WebKitLocalStorage if options.local_storage => {
+                if let Some(snapshot) = read_webkit_local_storage(&path)? {
+                    local_storage.push(snapshot);
+                }
+            },
+            _ => continue,
+        }
+        report.sources.push(path);
+    }
+    let now = OffsetDateTime::now_utc();
+    for cookie in cookies.into_values() {
+        if cookie.expires.map_or(false, |expires| expires <= now) || !options.pattern.matches(&cookie) {
+            continue;
+        }
+        platform::set_cookie(window, cookie).await?;
+        report.cookies_imported += 1;
+    }
+    let current = window.url().origin();
+    for snapshot in local_storage {
+        if snapshot.origin.origin() != current {
+            report.pending_local_storage.push(snapshot);
+            continue;
+        }
+        for (key, value) in snapshot.items {
+            let origin = Some(snapshot.origin.clone());
+            storage::set_item(window, StorageArea::Local, origin, key, Some(value)).await?;
+            report.local_storage_imported += 1;
+        }
+    }
+    Ok(report)
+}
+
+fn insert(cookies: &mut BTreeMap<(String, String, String), Cookie>, cookie: Cookie) {
+    let key = (cookie.domain.clone(), cookie.path.clone(), cookie.name.clone());
+    cookies.insert(key, cookie);
+}
+
+// NOTE: caches are skipped like in the health check, and the sources are sorted by path so that
+// the result does not depend on the order of the directory entries.
+fn sources(directory: &Path) -> std::io::Result<Vec<(PathBuf, Source)>> {
+    let mut sources = vec![];
+    if !directory.exists() {
+        return Ok(sources);
+    }
+    let mut directories = vec![directory.to_path_buf()];
+    while let Some(directory) = directories.pop() {
+        for entry in std::fs::read_dir(&directory)? {
+            let entry = entry?;
+            let path = entry.path();
+            let name = entry.file_name();
+            let name = name.to_string_lossy();
+            let file_type = entry.file_type()?;
+            if file_type.is_dir() {
+                if !name.contains("Cache") {
+                    directories.push(path);
+                }
+                continue;
+            }
+            if !file_type.is_file() {
+                continue;
+            }
+            let source = match &*name {
+                "cookies" => Source::CookieText,
+                "cookies.sqlite" => Source::CookieSqlite,
+                "Cookies" => Source::ChromiumCookies,
+                _ if name.ends_with(".localstorage") => Source::WebKitLocalStorage,
+                _ => continue,
+            };
+            sources.push((path, source));
+        }
+    }
+    sources.sort_by(|lhs, rhs| lhs.0.cmp(&rhs.0));
+    Ok(sources)
+}
+
+// NOTE: the origin is encoded in the file name as `<scheme>_<host>_<port>.localstorage`, with a port
+// of zero for the default port, and the values are stored as UTF-16.
+fn read_webkit_local_storage(path: &Path) -> BoxResult<Option<LocalStorageSnapshot>> {
+    let origin = match path.file_stem().and_then(|stem| stem.to_str()).and_then(webkit_origin) {
+        Some(origin) => origin,
+        None => return Ok(None),
+    };
+    let connection = cookie_db::open(path)?;
+    let mut statement = connection.prepare("SELECT key, value FROM ItemTable")?;
+    let mut rows = statement.query([])?;
+    let mut items = BTreeMap::new();
+    while let Some(row) = rows.next()? {
+        let key: String = row.get(0)?;
+        let value: Vec<u8> = row.get(1)?;
+        let value = value
+            .chunks_exact(2)
+            .map(|bytes| u16::from_le_bytes([bytes[0], bytes[1]]))
+            .collect::<Vec<_>>();
+        items.insert(key, String::from_utf16_lossy(&value));
+    }
+    if items.is_empty() {
+        return Ok(None);
+    }
+    Ok(Some(LocalStorageSnapshot { origin, items }))
+}
+
+fn webkit_origin(stem: &str) -> Option<Url> {
+    let (scheme, rest) = stem.split_once('_')?;
+    let (host, port) = rest.rsplit_once('_')?;
+    let port = match port.parse::<u16>().ok()? {
+        0 => String::new(),
+        port => format!(":{port}"),
+    };
+    Url::parse(&format!("{scheme}://{host}{port}/")).ok()
+}
@@ -0,0 +1,81 @@
+use crate::{platform, BoxResult, Error};
+use serde::{Deserialize, Serialize};
+use tauri::Window;
+
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub enum ProxyScheme {
+    Http,
+    Https,
+    Socks5,
+}
+
+impl ProxyScheme {
+    pub(crate) fn as_str(&self) -> &'static str {
+        match self {
+            Self::Http => "http",
+            Self::Https => "https",
+            Self::Socks5 => "socks5",
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct ProxyCredentials {
+    pub username: String,
+    pub password: String,
+}
+
+// NOTE: `bypass` lists the hosts that are connected to directly, which may start with a `*.`
+// wildcard for their subdomains.
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct ProxyConfig {
+    pub scheme: ProxyScheme,
+    pub host: String,
+    pub port: u16,
+    pub credentials: Option<ProxyCredentials>,
+    pub bypass: Vec<String>,
+}
+
+impl ProxyConfig {
+    pub fn new(scheme: ProxyScheme, host: impl Into<String>, port: u16) -> Self {
+        Self {
+            scheme,
+            host: host.into(),
+            port,
+            credentials: None,
+            bypass: vec![],
+        }
+    }
+
+    pub fn credentials(mut self, username: impl Into<String>, password: impl Into<String>) -> Self {
+        let username = username.into();
+        let password = password.into();
+        self.credentials = Some(ProxyCredentials { username, password });
+        self
+    }
+
+    pub fn bypass(mut self, host: impl Into<String>) -> Self {
+        self.bypass.push(host.into());
+        self
+    }
+
+    pub(crate) fn uri(&self) -> String {
+        format!("{}://{}:{}", self.scheme.as_str(), self.host, self.port)
+    }
+
+    pub(crate) fn validate(&self) -> BoxResult<()> {
+        if self.host.is_empty() || self.host.contains(&['/', '@', ' '][..]) {
+            return Err(Error::Other(format!("`{}` is not a valid proxy host", self.host).into()).into());
+        }
+        Ok(())
+    }
+}
+
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window, config),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn set(window: &Window, config: ProxyConfig) -> BoxResult<()> {
+    config.validate()?;
+    platform::set_proxy(window, config).await
+}
@@ -0,0 +1,29 @@
+use crate::{state, BoxResult, Error};
+use std::future::Future;
+use tauri::Window;
+
+// NOTE: a read-only window rejects the operations of this crate that change its data or content or
+// run scripts of the caller, which are writing and deleting cookies, clearing data and storage,
+// evaluating, injecting, and removing scripts, loading HTML, filling forms, replacing text, and
+// changing the user agent or proxy. Scripts that this crate runs itself to read from the page are
+// still allowed, as are navigation and display settings like zoom, HTTPS-only mode, and rewrites.
+pub(crate) fn set(window: &Window, enabled: bool) -> BoxResult<()> {
+    *state::window_state(window)?.read_only.lock()? = enabled;
+    Ok(())
+}
+
+pub(crate) fn is_enabled(window: &Window) -> BoxResult<bool> {
+    Ok(*state::window_state(window)?.read_only.lock()?)
+}
+
+pub(crate) fn check(window: &Window) -> BoxResult<()> {
+    if is_enabled(window)? {
+        return Err(Error::ReadOnly.into());
+    }
+    Ok(())
+}
+
+pub(crate) async fn guard<T>(window: &Window, operation: impl Future<Output = BoxResult<T>>) -> BoxResult<T> {
+    check(window)?;
+    operation.await
+}
@@ -0,0 +1,251 @@
+use crate::{
+    bridge,
+    platform,
+    state,
+    stream,
+    BoxResult,
+    CancellationToken,
+    Error,
+    Result,
+    ScreenshotFormat,
+    ScreenshotOptions,
+};
+use futures::{
+    future::{self, Either},
+    prelude::*,
+    stream::BoxStream,
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    pin::Pin,
+    sync::atomic::{AtomicU64, Ordering},
+    task::{Context, Poll},
+    time::{Duration, Instant},
+};
+use tauri::Window;
+
+static NEXT_RECORDING: AtomicU64 = AtomicU64::new(1);
+
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct RecordOptions {
+    pub frame_rate: u32,
+    pub format: ScreenshotFormat,
+}
+
+impl Default for RecordOptions {
+    fn default() -> Self {
+        Self {
+            frame_rate: 30,
+            format: ScreenshotFormat::Jpeg,
+        }
+    }
+}
+
+// NOTE: `timestamp` is the time since the recording started, at which the frame was captured.
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct VideoFrame {
+    pub timestamp: Duration,
+    pub format: ScreenshotFormat,
+    pub data: Vec<u8>,
+}
+
+// NOTE: the frames of the recording, which ends when it is stopped or dropped, or when another
+// recording of the same window is started. Frames are encoded images, so that they can be written
+// out or passed to an encoder as they are.
+pub struct RecordingHandle {
+    frames: BoxStream<'static, Result<VideoFrame>>,
+    token: CancellationToken,
+}
+
+impl RecordingHandle {
+    pub fn stop(&self) {
+        self.token.cancel();
+    }
+
+    pub fn is_stopped(&self) -> bool {
+        self.token.is_cancelled()
+    }
+}
+
+impl Stream for RecordingHandle {
+    type Item = Result<VideoFrame>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.frames.poll_next_unpin(cx)
+    }
+}
+
+impl Drop for RecordingHandle {
+    fn drop(&mut self) {
+        self.token.cancel();
+    }
+}
+
+impl std::fmt::Debug for RecordingHandle {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.debug_struct("RecordingHandle")
+            .field("stopped", &self.is_stopped())
+            .finish()
+    }
+}
+
+#[derive(Default)]
+pub(crate) struct RecordingState {
+    current: Option<Recording>,
+    native: bool,
+}
+
+struct Recording {
+    id: u64,
+    started: Instant,
+    format: ScreenshotFormat,
+    frames: stream::Sender<Result<VideoFrame>>,
+    token: CancellationToken,
+}
+
+// NOTE: with WebView2 frames are pushed by the compositor through the DevTools screencast, so they
+// only arrive when the page changes. Elsewhere a snapshot of the viewport is taken on every tick of
+// the frame rate, and ticks are skipped while a snapshot takes longer than a frame.
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn record(window: &Window, options: RecordOptions) -> BoxResult<RecordingHandle> {
+    let state = state::window_state(window)?;
+    if let Some(install) = bridge::install_once(&state, "screencast").await? {
+        let native = platform::add_screencast_observer(window, {
+            let window = window.clone();
+            move |data| {
+                publish(&window, None, Ok(data));
+            }
+        })
+        .await?;
+        state.recording.lock()?.native = native;
+        install.done();
+    }
+    let id = NEXT_RECORDING.fetch_add(1, Ordering::Relaxed);
+    let token = CancellationToken::new();
+    let (frame_tx, frames) = stream::channel(stream::stream_config());
+    let (native, previous) = {
+        let mut recording = state.recording.lock()?;
+        let previous = recording.current.replace(Recording {
+            id,
+            started: Instant::now(),
+            format: options.format,
+            frames: frame_tx,
+            token: token.clone(),
+        });
+        (recording.native, previous)
+    };
+    if let Some(previous) = previous {
+        previous.token.cancel();
+    }
+    if native {
+        // NOTE: the compositor produces frames at up to 60 per second.
+        let every_nth_frame = (60 / options.frame_rate.clamp(1, 60)).max(1);
+        if let Err(err) = platform::start_screencast(window, options.format, every_nth_frame).await {
+            finish(window, id);
+            return Err(err);
+        }
+    }
+    tauri::async_runtime::spawn({
+        let window = window.clone();
+        let token = token.clone();
+        async move {
+            if native {
+                token.cancelled().await;
+            } else {
+                capture(&window, id, options, &token).await;
+            }
+            if finish(&window, id) && native {
+                if let Err(err) = platform::stop_screencast(&window).await {
+                    crate::report_error(window.label(), "failed to stop screencast", err);
+                }
+            }
+        }
+    });
+    Ok(RecordingHandle {
+        frames: frames.map(|frame| frame.and_then(std::convert::identity)).boxed(),
+        token,
+    })
+}
+
+async fn capture(window: &Window, id: u64, options: RecordOptions, token: &CancellationToken) {
+    let period = Duration::from_secs_f64(1.0 / f64::from(options.frame_rate.max(1)));
+    let mut interval = tokio::time::interval(period);
+    interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+    loop {
+        if let Either::Right(_) = future::select(interval.tick().boxed(), token.cancelled().boxed()).await {
+            return;
+        }
+        // NOTE: snapshots are only taken once the stream has space for them, rather than queued
+        // beyond its capacity when frames are read slower than they are recorded.
+        let Some(frames) = frames(window, id) else {
+            return;
+        };
+        match future::select(frames.ready().boxed(), token.cancelled().boxed()).await {
+            Either::Left((Ok(()), _)) => {},
+            _ => return,
+        }
+        drop(frames);
+        let snapshot = platform::capture_screenshot(window, ScreenshotOptions {
+            format: options.format,
+            ..ScreenshotOptions::default()
+        })
+        .await;
+        let failed = snapshot.is_err();
+        if !publish(window, Some(id), snapshot) || failed {
+            return;
+        }
+    }
+}
+
+// NOTE: frames are published to the current recording when `id` is not given. Returns whether the
+// recording goes on, which it does not once its stream was dropped or an error was published.
+fn publish(window: &Window, id: Option<u64>, data: BoxResult<Vec<u8>>) -> bool {
+    let Ok(state) = state::window_state(window) else {
+        return false;
+    };
+    let Ok(recording) = state.recording.lock() else {
+        return false;
+    };
+    let Some(recording) = recording
+        .current
+        .as_ref()
+        .filter(|recording| id.map_or(true, |id| id == recording.id))
+    else {
+        return false;
+    };
+    let failed = data.is_err();
+    let frame = data.map_err(Error::from).map(|data| VideoFrame {
+        timestamp: recording.started.elapsed(),
+        format: recording.format,
+        data,
+    });
+    if recording.frames.send(frame).is_err() || failed {
+        recording.token.cancel();
+        return false;
+    }
+    true
+}
+
+fn frames(window: &Window, id: u64) -> Option<stream::Sender<Result<VideoFrame>>> {
+    let state = state::window_state(window).ok()?;
+    let recording = state.recording.lock().ok()?;
+    let recording = recording.current.as_ref().filter(|recording| recording.id == id)?;
+    Some(recording.frames.clone())
+}
+
+// NOTE: removes the recording unless another one was started since, which ends its stream.
+fn finish(window: &Window, id: u64) -> bool {
+    state::window_state(window)
+        .and_then(|state| {
+            let mut recording = state.recording.lock()?;
+            let current = recording.current.as_ref().map_or(false, |recording| recording.id == id);
+            if current {
+                recording.current.take();
+            }
+            Ok(current)
+        })
+        .unwrap_or(false)
+}
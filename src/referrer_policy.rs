@@ -0,0 +1,48 @@
+use crate::{init_script, state, BoxResult};
+use serde::{Deserialize, Serialize};
+use tauri::Window;
+
+const REFERRER_POLICY_SCRIPT: &str = include_str!("js/referrer_policy.js");
+
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub enum ReferrerPolicy {
+    NoReferrer,
+    NoReferrerWhenDowngrade,
+    Origin,
+    OriginWhenCrossOrigin,
+    SameOrigin,
+    StrictOrigin,
+    #[default]
+    StrictOriginWhenCrossOrigin,
+    UnsafeUrl,
+}
+
+impl ReferrerPolicy {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::NoReferrer => "no-referrer",
+            Self::NoReferrerWhenDowngrade => "no-referrer-when-downgrade",
+            Self::Origin => "origin",
+            Self::OriginWhenCrossOrigin => "origin-when-cross-origin",
+            Self::SameOrigin => "same-origin",
+            Self::StrictOrigin => "strict-origin",
+            Self::StrictOriginWhenCrossOrigin => "strict-origin-when-cross-origin",
+            Self::UnsafeUrl => "unsafe-url",
+        }
+    }
+}
+
+// NOTE: none of the engines has a setting for the default referrer policy, so the policy is set
+// with a `<meta name="referrer">` element in every document of the top frame. It applies to
+// requests of the document and to navigations away from it, but elements with their own
+// `referrerpolicy` attribute and documents in frames are not affected.
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn set(window: &Window, policy: ReferrerPolicy) -> BoxResult<()> {
+    let state = state::window_state(window)?;
+    let policy = serde_json::to_string(policy.as_str())?;
+    let source = format!("({REFERRER_POLICY_SCRIPT})({policy});");
+    init_script::replace(window, &state.referrer_policy_script, source).await
+}
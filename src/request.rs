@@ -0,0 +1,113 @@
+use crate::{
+    bridge,
+    state,
+    structured_clone::{self, CloneValue},
+    BoxResult,
+};
+use futures::{future::BoxFuture, prelude::*};
+use serde::Deserialize;
+use std::sync::Arc;
+use tauri::Window;
+
+pub(crate) type RequestHandler =
+    Arc<dyn Fn(serde_json::Value) -> BoxFuture<'static, BoxResult<serde_json::Value>> + Send + Sync + 'static>;
+
+// NOTE: how payloads and results are encoded, which the page is told when the request settles.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(crate) enum RequestEncoding {
+    Json,
+    StructuredClone,
+}
+
+#[derive(Deserialize)]
+struct Request {
+    id: u64,
+    name: String,
+    #[serde(default)]
+    payload: serde_json::Value,
+}
+
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window, handler),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn on_request(
+    window: &Window,
+    name: &str,
+    handler: impl Fn(serde_json::Value) -> BoxFuture<'static, BoxResult<serde_json::Value>> + Send + Sync + 'static,
+) -> BoxResult<()> {
+    register(window, name, Arc::new(handler), RequestEncoding::Json).await
+}
+
+// NOTE: the page sends structured clone payloads with `request(name, payload, { clone: true })`.
+// Plain JSON payloads decode unchanged, so the option is only needed for values without a JSON form.
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window, handler),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn on_clone_request(
+    window: &Window,
+    name: &str,
+    handler: impl Fn(CloneValue) -> BoxFuture<'static, BoxResult<CloneValue>> + Send + Sync + 'static,
+) -> BoxResult<()> {
+    let handler: RequestHandler = Arc::new(move |payload| match structured_clone::decode(payload) {
+        Ok(payload) => handler(payload)
+            .map_ok(|value| structured_clone::encode(&value))
+            .boxed(),
+        Err(err) => future::ready(Err(err.into())).boxed(),
+    });
+    register(window, name, handler, RequestEncoding::StructuredClone).await
+}
+
+async fn register(window: &Window, name: &str, handler: RequestHandler, encoding: RequestEncoding) -> BoxResult<()> {
+    let state = state::window_state(window)?;
+    state.requests.lock()?.insert(name.into(), (handler, encoding));
+    let Some(install) = bridge::install_once(&state, "request").await? else {
+        return Ok(());
+    };
+    bridge::on_message(window, "request", {
+        let window = window.clone();
+        move |payload| dispatch(&window, payload)
+    })
+    .await?;
+    install.done();
+    Ok(())
+}
+
+fn dispatch(window: &Window, payload: serde_json::Value) {
+    let request = match serde_json::from_value::<Request>(payload) {
+        Ok(request) => request,
+        Err(err) => return crate::report_error(window.label(), "malformed js request", err),
+    };
+    let handler = state::window_state(window)
+        .and_then(|state| Ok(state.requests.lock()?.get(&request.name).cloned()))
+        .ok()
+        .flatten();
+    let (handler, encoding) = match handler {
+        Some(handler) => handler,
+        None => {
+            let error = format!("no handler for request `{}`", request.name);
+            return settle(window, request.id, Err(error.into()), RequestEncoding::Json);
+        },
+    };
+    let window = window.clone();
+    tauri::async_runtime::spawn(async move {
+        let result = handler(request.payload).await;
+        settle(&window, request.id, result, encoding);
+    });
+}
+
+// NOTE: page scripts call `window.__TAURI_WEBVIEW_UTIL__.request(name, payload)`, which returns a
+// promise that is settled here once the handler completes. Replies for a document that has since
+// been unloaded are ignored by the bridge of the new document.
+fn settle(window: &Window, id: u64, result: BoxResult<serde_json::Value>, encoding: RequestEncoding) {
+    let (value, error) = match result {
+        Ok(value) => (value, serde_json::Value::Null),
+        Err(err) => (serde_json::Value::Null, serde_json::Value::String(err.to_string())),
+    };
+    let clone = encoding == RequestEncoding::StructuredClone;
+    let script = format!("window.__TAURI_WEBVIEW_UTIL__.settle({id}, {value}, {error}, {clone});");
+    if let Err(err) = window.eval(&script) {
+        crate::report_error(window.label(), "failed to settle js request", err);
+    }
+}
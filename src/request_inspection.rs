@@ -0,0 +1,72 @@
+use crate::{bridge, event_bus, event_bus::WebviewEvent, init_script, state, BoxResult};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tauri::Window;
+use url::Url;
+
+const REQUEST_INSPECTION_SCRIPT: &str = include_str!("js/request_inspection.js");
+
+pub(crate) type RequestRedactor = Arc<dyn Fn(OutgoingRequest) -> Option<OutgoingRequest> + Send + Sync + 'static>;
+
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RequestInspection {
+    pub url_globs: Vec<String>,
+    pub max_body_size: usize,
+}
+
+impl Default for RequestInspection {
+    fn default() -> Self {
+        Self {
+            url_globs: vec![],
+            max_body_size: 64 * 1024,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct OutgoingRequest {
+    pub url: Url,
+    pub method: String,
+    pub body: String,
+    pub truncated: bool,
+}
+
+// NOTE: request bodies are only observed for URLs matching the configured globs, and every request
+// passes through the redactor before it is published, which can rewrite it or drop it entirely.
+// Bodies are limited to `max_body_size` characters in the page already.
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window, redactor),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn inspect(
+    window: &Window,
+    inspection: RequestInspection,
+    redactor: impl Fn(OutgoingRequest) -> Option<OutgoingRequest> + Send + Sync + 'static,
+) -> BoxResult<()> {
+    let state = state::window_state(window)?;
+    *state.request_redactor.lock()? = Some(Arc::new(redactor));
+    if let Some(install) = bridge::install_once(&state, "request-inspection").await? {
+        bridge::on_message(window, "request-sent", {
+            let window = window.clone();
+            move |payload| {
+                let request = match serde_json::from_value::<OutgoingRequest>(payload) {
+                    Ok(request) => request,
+                    Err(err) => return crate::report_error(window.label(), "malformed outgoing request", err),
+                };
+                let redactor = state::window_state(&window)
+                    .and_then(|state| Ok(state.request_redactor.lock()?.clone()))
+                    .ok()
+                    .flatten();
+                if let Some(request) = redactor.and_then(|redactor| redactor(request)) {
+                    event_bus::publish(&window, WebviewEvent::RequestSent(request));
+                }
+            }
+        })
+        .await?;
+        install.done();
+    }
+    let inspection = serde_json::to_string(&inspection)?;
+    let source = format!("({REQUEST_INSPECTION_SCRIPT})({inspection});");
+    init_script::replace(window, &state.request_inspection, source).await
+}
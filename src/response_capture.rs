@@ -0,0 +1,45 @@
+use crate::{bridge, event_bus, event_bus::WebviewEvent, init_script, state, BoxResult};
+use serde::{Deserialize, Serialize};
+use tauri::Window;
+use url::Url;
+
+const RESPONSE_CAPTURE_SCRIPT: &str = include_str!("js/response_capture.js");
+
+#[derive(Clone, Debug, Default, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CaptureFilter {
+    pub url_globs: Vec<String>,
+    pub capture_bodies: bool,
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct CapturedResponse {
+    pub url: Url,
+    pub status: u16,
+    pub body: Option<String>,
+}
+
+// NOTE: responses are observed by wrapping `fetch` and `XMLHttpRequest` in the page, which works the
+// same with every engine, but only sees requests made by page scripts (and only text bodies). The
+// filter is kept in a user script of its own so that it applies from the start of every document.
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn capture(window: &Window, filter: CaptureFilter) -> BoxResult<()> {
+    let state = state::window_state(window)?;
+    if let Some(install) = bridge::install_once(&state, "response-capture").await? {
+        bridge::on_message(window, "response-captured", {
+            let window = window.clone();
+            move |payload| match serde_json::from_value::<CapturedResponse>(payload) {
+                Ok(response) => event_bus::publish(&window, WebviewEvent::ResponseCaptured(response)),
+                Err(err) => crate::report_error(window.label(), "malformed captured response", err),
+            }
+        })
+        .await?;
+        install.done();
+    }
+    let filter = serde_json::to_string(&filter)?;
+    let source = format!("({RESPONSE_CAPTURE_SCRIPT})({filter});");
+    init_script::replace(window, &state.response_capture, source).await
+}
@@ -0,0 +1,46 @@
+use crate::{bridge, event_bus, event_bus::WebviewEvent, state, BoxResult};
+use serde::{Deserialize, Serialize};
+use tauri::Window;
+use url::Url;
+
+const ROUTE_SCRIPT: &str = include_str!("js/route.js");
+
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RouteChangeKind {
+    Push,
+    Replace,
+    Pop,
+    Hash,
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct RouteChange {
+    pub url: Url,
+    pub title: String,
+    pub kind: RouteChangeKind,
+}
+
+// NOTE: history API navigations within a document do not reach the navigation delegates of the
+// engines, so they are observed from the page instead.
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn install(window: &Window) -> BoxResult<()> {
+    let state = state::window_state(window)?;
+    let Some(install) = bridge::install_once(&state, "route").await? else {
+        return Ok(());
+    };
+    bridge::on_message(window, "route-change", {
+        let window = window.clone();
+        move |payload| match serde_json::from_value::<RouteChange>(payload) {
+            Ok(change) => event_bus::publish(&window, WebviewEvent::RouteChanged(change)),
+            Err(err) => crate::report_error(window.label(), "malformed route change", err),
+        }
+    })
+    .await?;
+    bridge::add_script(window, ROUTE_SCRIPT).await?;
+    install.done();
+    Ok(())
+}
@@ -0,0 +1,147 @@
+use crate::{platform, state, website_data::DataKinds, BoxResult, Result, WebviewExt};
+use serde::{Deserialize, Serialize};
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+};
+use tauri::{utils::config::WindowConfig, AppHandle, Window, WindowBuilder, WindowEvent};
+
+#[derive(Clone, Debug, Default, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[non_exhaustive]
+pub struct SafeModeReport {
+    pub label: String,
+    pub crashes: usize,
+    pub caches_cleared: bool,
+    pub extensions_disabled: bool,
+    pub hardware_acceleration_disabled: bool,
+}
+
+type RelaunchHandler = Arc<dyn Fn(&Window, &SafeModeReport) + Send + Sync + 'static>;
+
+#[derive(Clone)]
+pub struct SafeMode {
+    app: AppHandle,
+    config: WindowConfig,
+    crash_threshold: usize,
+    on_relaunch: Option<RelaunchHandler>,
+}
+
+pub fn safe_mode(app: &AppHandle, config: WindowConfig) -> SafeMode {
+    SafeMode {
+        app: app.clone(),
+        config,
+        crash_threshold: 3,
+        on_relaunch: None,
+    }
+}
+
+impl SafeMode {
+    pub fn crash_threshold(mut self, crash_threshold: usize) -> Self {
+        self.crash_threshold = crash_threshold.max(1);
+        self
+    }
+
+    pub fn on_relaunch(mut self, handler: impl Fn(&Window, &SafeModeReport) + Send + Sync + 'static) -> Self {
+        self.on_relaunch = Some(Arc::new(handler));
+        self
+    }
+
+    // NOTE: the page is reloaded after crashes below the threshold. Once the threshold is reached,
+    // the window is closed and built again from its config in safe mode, where it stays.
+    pub fn build(&self) -> Result<Window> {
+        let window = WindowBuilder::from_config(&self.app, self.config.clone()).build()?;
+        self.watch(&window);
+        Ok(window)
+    }
+
+    fn watch(&self, window: &Window) {
+        let safe_mode = self.clone();
+        let window = window.clone();
+        tauri::async_runtime::spawn(async move {
+            let crashes = AtomicUsize::new(0);
+            let handler = {
+                let safe_mode = safe_mode.clone();
+                let window = window.clone();
+                move || safe_mode.crashed(&window, &crashes)
+            };
+            if let Err(err) = platform::add_process_crash_handler(&window, handler).await {
+                crate::report_error(window.label(), "failed to watch for crashes", err);
+            }
+        });
+    }
+
+    fn crashed(&self, window: &Window, crashes: &AtomicUsize) {
+        let crashes = crashes.fetch_add(1, Ordering::Relaxed) + 1;
+        #[cfg(feature = "tracing")]
+        tracing::warn!(window.label = window.label(), crashes, "web process crashed");
+        if crashes < self.crash_threshold || is_enabled(window) {
+            if let Err(err) = window.webview_navigate(window.url()) {
+                crate::report_error(window.label(), "failed to reload after crash", err);
+            }
+            return;
+        }
+        // NOTE: the label is only free again once the window is destroyed.
+        window.on_window_event({
+            let safe_mode = self.clone();
+            let label = String::from(window.label());
+            move |event| {
+                if let WindowEvent::Destroyed = event {
+                    let safe_mode = safe_mode.clone();
+                    let label = label.clone();
+                    tauri::async_runtime::spawn(async move {
+                        if let Err(err) = safe_mode.relaunch(crashes).await {
+                            crate::report_error(&label, "failed to relaunch in safe mode", err);
+                        }
+                    });
+                }
+            }
+        });
+        if let Err(err) = window.close() {
+            crate::report_error(window.label(), "failed to close crashed window", err);
+        }
+    }
+
+    // NOTE: with WebView2 the browser arguments only apply when no other window shares the
+    // environment of the relaunched window, since an environment can not be created again with
+    // different arguments while it is in use.
+    async fn relaunch(&self, crashes: usize) -> BoxResult<()> {
+        let builder = WindowBuilder::from_config(&self.app, self.config.clone());
+        #[cfg(target_os = "windows")]
+        let builder = crate::webview2::EnvironmentOptions {
+            additional_browser_arguments: vec!["--disable-gpu".into(), "--disable-gpu-compositing".into()],
+            ..Default::default()
+        }
+        .apply(builder);
+        let window = builder.build()?;
+        *state::window_state(&window)?.safe_mode.lock()? = true;
+        let hardware_acceleration_disabled =
+            cfg!(target_os = "windows") || platform::disable_hardware_acceleration(&window).await?;
+        let caches_cleared = match platform::clear_data(&window, DataKinds::CACHES).await {
+            Ok(()) => true,
+            Err(err) => {
+                crate::report_error(window.label(), "failed to clear caches in safe mode", err);
+                false
+            },
+        };
+        let report = SafeModeReport {
+            label: window.label().into(),
+            crashes,
+            caches_cleared,
+            extensions_disabled: true,
+            hardware_acceleration_disabled,
+        };
+        #[cfg(feature = "tracing")]
+        tracing::warn!(window.label = window.label(), ?report, "relaunched in safe mode");
+        self.watch(&window);
+        if let Some(on_relaunch) = &self.on_relaunch {
+            on_relaunch(&window, &report);
+        }
+        Ok(())
+    }
+}
+
+pub(crate) fn is_enabled(window: &Window) -> bool {
+    state::window_state(window)
+        .and_then(|state| Ok(*state.safe_mode.lock()?))
+        .unwrap_or_default()
+}
@@ -0,0 +1,393 @@
+use crate::{platform, state, BoxResult, CorsPolicy, CspOptions};
+use futures::{
+    future::{self, BoxFuture, FutureExt},
+    stream::{self, BoxStream, StreamExt},
+};
+use serde::{Deserialize, Serialize};
+use std::{fmt, io, sync::Arc};
+use tauri::{
+    http::{Request, Response, ResponseBuilder},
+    AppHandle,
+    Runtime,
+    Window,
+};
+
+pub(crate) type Handler<R> = Arc<dyn Fn(SchemeRequest) -> BoxFuture<'static, R> + Send + Sync + 'static>;
+
+pub(crate) type SchemeHandler = Handler<SchemeResponse>;
+
+pub(crate) type SchemeStreamHandler = Handler<SchemeStreamResponse>;
+
+// NOTE: webkit2gtk only exposes the method, headers and body of requests since 2.36 and 2.40, so
+// requests there are always reported as `GET` requests without headers or a body.
+#[derive(Clone, Debug, Default, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct SchemeRequest {
+    pub uri: String,
+    pub method: String,
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+impl SchemeRequest {
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(header, _)| header.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct SchemeResponse {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+impl SchemeResponse {
+    pub fn new(status: u16) -> Self {
+        Self {
+            status,
+            ..Self::default()
+        }
+    }
+
+    pub fn header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.headers.push((name.into(), value.into()));
+        self
+    }
+
+    pub fn body(mut self, body: impl Into<Vec<u8>>) -> Self {
+        self.body = body.into();
+        self
+    }
+
+    pub(crate) fn content_type(&self) -> Option<&str> {
+        find_header(&self.headers, "Content-Type")
+    }
+}
+
+/// A response of a custom scheme whose body is sent while it is produced, e.g. a large video file,
+/// so that it does not have to be held in memory as a whole.
+///
+/// The body is only polled for its next chunk once the webview has taken the previous ones, so a
+/// slow reader slows down the stream instead of the chunks piling up. A `Content-Length` header is
+/// passed on as the length of the body where the webview needs one up front.
+pub struct SchemeStreamResponse {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: BoxStream<'static, io::Result<Vec<u8>>>,
+}
+
+impl SchemeStreamResponse {
+    pub fn new(status: u16, body: impl futures::Stream<Item = io::Result<Vec<u8>>> + Send + 'static) -> Self {
+        Self {
+            status,
+            headers: vec![],
+            body: body.boxed(),
+        }
+    }
+
+    pub fn header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.headers.push((name.into(), value.into()));
+        self
+    }
+
+    pub(crate) fn content_type(&self) -> Option<&str> {
+        find_header(&self.headers, "Content-Type")
+    }
+
+    pub(crate) fn content_length(&self) -> Option<u64> {
+        find_header(&self.headers, "Content-Length")?.trim().parse().ok()
+    }
+}
+
+impl fmt::Debug for SchemeStreamResponse {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SchemeStreamResponse")
+            .field("status", &self.status)
+            .field("headers", &self.headers)
+            .finish_non_exhaustive()
+    }
+}
+
+impl From<SchemeResponse> for SchemeStreamResponse {
+    fn from(response: SchemeResponse) -> Self {
+        Self {
+            status: response.status,
+            headers: response.headers,
+            body: stream::once(future::ready(Ok(response.body))).boxed(),
+        }
+    }
+}
+
+// NOTE: lets the options of a scheme be applied to buffered and streamed responses alike.
+pub(crate) trait ResponseParts: From<SchemeResponse> + Send + 'static {
+    fn status(&self) -> u16;
+
+    fn headers(&self) -> &[(String, String)];
+
+    fn headers_mut(&mut self) -> &mut Vec<(String, String)>;
+
+    fn with_header(mut self, name: &str, value: String) -> Self {
+        self.headers_mut().push((name.into(), value));
+        self
+    }
+
+    fn has_header(&self, name: &str) -> bool {
+        find_header(self.headers(), name).is_some()
+    }
+
+    fn remove_header(&mut self, name: &str) {
+        self.headers_mut()
+            .retain(|(header, _)| !header.eq_ignore_ascii_case(name));
+    }
+
+    fn is_html(&self) -> bool {
+        find_header(self.headers(), "Content-Type").map_or(false, |content_type| content_type.starts_with("text/html"))
+    }
+
+    // NOTE: the body of a streamed response is not known in advance, so it is left as it is.
+    fn add_script_nonce(&mut self, _nonce: &str) -> bool {
+        false
+    }
+}
+
+impl ResponseParts for SchemeResponse {
+    fn status(&self) -> u16 {
+        self.status
+    }
+
+    fn headers(&self) -> &[(String, String)] {
+        &self.headers
+    }
+
+    fn headers_mut(&mut self) -> &mut Vec<(String, String)> {
+        &mut self.headers
+    }
+
+    // NOTE: an encoded body can not be rewritten, but it is never served to a scheme with a nonce,
+    // see `CspOptions::with_handler`.
+    fn add_script_nonce(&mut self, nonce: &str) -> bool {
+        if self.status != 200 || !self.is_html() || self.has_header("Content-Encoding") {
+            return false;
+        }
+        let Some(body) = crate::csp::add_script_nonce(&self.body, nonce) else {
+            return false;
+        };
+        self.body = body;
+        for (name, value) in &mut self.headers {
+            if name.eq_ignore_ascii_case("Content-Length") {
+                *value = self.body.len().to_string();
+            }
+        }
+        true
+    }
+}
+
+impl ResponseParts for SchemeStreamResponse {
+    fn status(&self) -> u16 {
+        self.status
+    }
+
+    fn headers(&self) -> &[(String, String)] {
+        &self.headers
+    }
+
+    fn headers_mut(&mut self) -> &mut Vec<(String, String)> {
+        &mut self.headers
+    }
+}
+
+fn find_header<'a>(headers: &'a [(String, String)], name: &str) -> Option<&'a str> {
+    headers
+        .iter()
+        .find(|(header, _)| header.eq_ignore_ascii_case(name))
+        .map(|(_, value)| value.as_str())
+}
+
+// NOTE: the body is polled on a blocking thread of the async runtime, and each chunk is handed to
+// `write`, which blocks until the webview has room for it. The body is dropped once `write` fails,
+// e.g. because the webview stopped reading it. A body that fails ends early, which the webview sees
+// as a truncated response.
+pub(crate) fn pump_body(
+    mut body: BoxStream<'static, io::Result<Vec<u8>>>,
+    mut write: impl FnMut(Vec<u8>) -> bool + Send + 'static,
+) {
+    tauri::async_runtime::spawn_blocking(move || {
+        while let Some(chunk) = tauri::async_runtime::block_on(body.next()) {
+            match chunk {
+                Ok(chunk) => {
+                    if !write(chunk) {
+                        break;
+                    }
+                },
+                #[allow(unused_variables)]
+                Err(err) => {
+                    #[cfg(feature = "tracing")]
+                    tracing::warn!(%err, "failed to stream the body of a scheme response");
+                    break;
+                },
+            }
+        }
+    });
+}
+
+/// How the pages of a window treat a custom scheme.
+///
+/// With `secure`, documents of the scheme are secure contexts, which e.g. storage, service workers,
+/// and `crypto.subtle` need. With `CorsPolicy::Allow`, other origins may read responses of the
+/// scheme with `fetch`, only the origins in `allowed_origins` if it is not empty, and only with the
+/// methods in `allowed_methods`.
+///
+/// With `csp`, a `Content-Security-Policy` header is attached to responses that do not have one.
+///
+/// WebKitGTK applies `secure` and the CORS policy to the scheme for the whole web context, and can
+/// not revert them. WebView2 serves custom schemes over `https`, so they are always secure there,
+/// and WKWebView decides itself for schemes registered when the app is built.
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct SchemeOptions {
+    pub secure: bool,
+    pub cors: CorsPolicy,
+    pub allowed_origins: Vec<String>,
+    pub allowed_methods: Vec<String>,
+    pub csp: Option<CspOptions>,
+}
+
+impl Default for SchemeOptions {
+    fn default() -> Self {
+        Self {
+            secure: false,
+            cors: CorsPolicy::DenyCors,
+            allowed_origins: vec![],
+            allowed_methods: vec!["GET".into(), "HEAD".into()],
+            csp: None,
+        }
+    }
+}
+
+impl SchemeOptions {
+    // NOTE: WebKitGTK does not report the headers of requests, so without an `Origin` header only
+    // a list of allowed origins that is empty can be applied.
+    fn allowed_origin(&self, request: &SchemeRequest) -> Option<String> {
+        if self.cors != CorsPolicy::Allow {
+            return None;
+        }
+        if self.allowed_origins.is_empty() {
+            return Some("*".into());
+        }
+        let origin = request.header("Origin")?;
+        self.allowed_origins
+            .iter()
+            .any(|allowed| allowed == origin)
+            .then(|| origin.into())
+    }
+
+    fn apply<R: ResponseParts>(&self, handler: &Handler<R>, request: SchemeRequest) -> BoxFuture<'static, R> {
+        match self.csp.clone() {
+            Some(csp) => self.apply_cors(&csp.with_handler(handler.clone()), request),
+            None => self.apply_cors(handler, request),
+        }
+    }
+
+    fn apply_cors<R: ResponseParts>(&self, handler: &Handler<R>, request: SchemeRequest) -> BoxFuture<'static, R> {
+        if self.cors != CorsPolicy::Allow {
+            return handler(request);
+        }
+        let origin = self.allowed_origin(&request);
+        // NOTE: preflight requests are answered here, so that handlers do not have to know about
+        // `OPTIONS` requests.
+        if request.method.eq_ignore_ascii_case("OPTIONS") && request.header("Access-Control-Request-Method").is_some() {
+            let response = match origin {
+                Some(origin) => SchemeResponse::new(204)
+                    .header("Access-Control-Allow-Origin", origin)
+                    .header("Access-Control-Allow-Methods", self.allowed_methods.join(", "))
+                    .header(
+                        "Access-Control-Allow-Headers",
+                        request.header("Access-Control-Request-Headers").unwrap_or_default(),
+                    )
+                    .header("Vary", "Origin"),
+                None => SchemeResponse::new(403),
+            };
+            return future::ready(response.into()).boxed();
+        }
+        let response = handler(request);
+        match origin {
+            Some(origin) => response
+                .map(move |response| {
+                    response
+                        .with_header("Access-Control-Allow-Origin", origin)
+                        .with_header("Vary", "Origin".into())
+                })
+                .boxed(),
+            None => response,
+        }
+    }
+}
+
+pub(crate) async fn set_options(window: &Window, scheme: String, options: SchemeOptions) -> BoxResult<()> {
+    platform::set_scheme_security(window, scheme.clone(), options.secure, options.cors).await?;
+    state::window_state(window)?
+        .scheme_options
+        .lock()?
+        .insert(scheme, options);
+    Ok(())
+}
+
+// NOTE: the options are looked up for every request, so that they can still be changed after the
+// handler was registered.
+pub(crate) fn with_options<R: ResponseParts>(window: &Window, scheme: String, handler: Handler<R>) -> Handler<R> {
+    let window = window.clone();
+    Arc::new(move |request: SchemeRequest| {
+        let options = state::window_state(&window)
+            .ok()
+            .and_then(|state| state.scheme_options.lock().ok()?.get(&scheme).cloned())
+            .unwrap_or_default();
+        options.apply(&handler, request)
+    })
+}
+
+/// Creates a protocol handler that answers requests with `handler`, for use with
+/// `tauri::Builder::register_uri_scheme_protocol`.
+///
+/// This is needed with WKWebView, where schemes can only be registered before a webview is
+/// created. Elsewhere [`crate::WebviewExt::webview_register_scheme_handler`] can register the scheme
+/// for an existing window. The protocol handlers of tauri are synchronous, so the thread that runs
+/// them is blocked until the response is ready.
+pub fn scheme_protocol<R: Runtime>(
+    handler: impl Fn(SchemeRequest) -> BoxFuture<'static, SchemeResponse> + Send + Sync + 'static,
+) -> impl Fn(&AppHandle<R>, &Request) -> Result<Response, Box<dyn std::error::Error>> + Send + Sync + 'static {
+    scheme_protocol_with_options(SchemeOptions::default(), handler)
+}
+
+/// Like [`scheme_protocol`], but answers requests of other origins according to the CORS policy of
+/// `options`, and attaches its CSP. The `secure` option has no effect here.
+pub fn scheme_protocol_with_options<R: Runtime>(
+    options: SchemeOptions,
+    handler: impl Fn(SchemeRequest) -> BoxFuture<'static, SchemeResponse> + Send + Sync + 'static,
+) -> impl Fn(&AppHandle<R>, &Request) -> Result<Response, Box<dyn std::error::Error>> + Send + Sync + 'static {
+    let handler: SchemeHandler = Arc::new(handler);
+    move |_app, request| {
+        let headers = request
+            .headers()
+            .iter()
+            .filter_map(|(name, value)| Some((name.to_string(), value.to_str().ok()?.to_string())))
+            .collect();
+        let request = SchemeRequest {
+            uri: request.uri().into(),
+            method: request.method().to_string(),
+            headers,
+            body: request.body().clone(),
+        };
+        let response = tauri::async_runtime::block_on(options.apply(&handler, request));
+        respond(response)
+    }
+}
+
+pub(crate) fn respond(response: SchemeResponse) -> Result<Response, Box<dyn std::error::Error>> {
+    let mut builder = ResponseBuilder::new().status(response.status);
+    for (name, value) in &response.headers {
+        builder = builder.header(name.as_str(), value.as_str());
+    }
+    builder.body(response.body)
+}
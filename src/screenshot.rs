@@ -0,0 +1,77 @@
+use crate::{bridge, platform, BoxResult, Error};
+use serde::{Deserialize, Serialize};
+use tauri::Window;
+
+const ELEMENT_RECT_SCRIPT: &str = include_str!("js/element_rect.js");
+
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub enum ScreenshotRegion {
+    #[default]
+    Viewport,
+    FullPage,
+}
+
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub enum ScreenshotFormat {
+    #[default]
+    Png,
+    Jpeg,
+}
+
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct ScreenshotOptions {
+    pub region: ScreenshotRegion,
+    pub format: ScreenshotFormat,
+}
+
+// NOTE: the bounds of an element in CSS pixels, relative to the viewport after it was scrolled into
+// view, along with the scroll offset and size of the viewport.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ClipRect {
+    pub(crate) x: f64,
+    pub(crate) y: f64,
+    pub(crate) width: f64,
+    pub(crate) height: f64,
+    pub(crate) scroll_x: f64,
+    pub(crate) scroll_y: f64,
+    pub(crate) viewport_width: f64,
+    pub(crate) viewport_height: f64,
+}
+
+impl ClipRect {
+    // NOTE: the part of the bounds that is within the viewport, for engines that can only capture the
+    // viewport.
+    #[cfg(not(target_os = "windows"))]
+    pub(crate) fn visible(self) -> Self {
+        let x = self.x.max(0.0);
+        let y = self.y.max(0.0);
+        Self {
+            x,
+            y,
+            width: ((self.x + self.width).min(self.viewport_width) - x).max(0.0),
+            height: ((self.y + self.height).min(self.viewport_height) - y).max(0.0),
+            ..self
+        }
+    }
+}
+
+// NOTE: elements are looked up in the main frame. WebView2 captures the whole element, while the
+// other engines only capture the part of it that fits in the viewport.
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn snapshot_element(window: &Window, selector: String) -> BoxResult<Vec<u8>> {
+    let selector = serde_json::to_string(&selector)?;
+    let body = format!("return ({ELEMENT_RECT_SCRIPT})({selector});");
+    let rect = bridge::call(window, &body).await?;
+    if rect.is_null() {
+        return Err(Error::Script(format!("no element matches the selector {selector}")).into());
+    }
+    let clip = serde_json::from_value::<ClipRect>(rect)?;
+    if clip.width <= 0.0 || clip.height <= 0.0 {
+        return Err(Error::Script(format!("the element matching {selector} has no area")).into());
+    }
+    platform::capture_clip(window, clip, ScreenshotFormat::Png).await
+}
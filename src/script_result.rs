@@ -0,0 +1,45 @@
+use crate::{bridge, options, platform, BoxResult, Error, Result};
+use futures::stream::{BoxStream, TryStreamExt};
+use tauri::Window;
+
+// NOTE: chunks are measured in UTF-16 code units of the JSON text, which keeps each bridge message
+// well below the message size limits of the platforms.
+const CHUNK_SIZE: usize = 256 * 1024;
+
+pub(crate) fn check_size(value: serde_json::Value) -> BoxResult<serde_json::Value> {
+    if let Some(limit) = options::options().max_result_bytes {
+        let size = serde_json::to_string(&value)?.len();
+        if size > limit {
+            return Err(Error::ResultTooLarge { size, limit }.into());
+        }
+    }
+    Ok(value)
+}
+
+// NOTE: the platforms return the result as a whole, so the limit can only be checked once it has
+// arrived. Chunked evaluation checks it in the page instead.
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window, script),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn eval(window: &Window, script: String) -> BoxResult<serde_json::Value> {
+    check_size(platform::eval_js(window, script).await?)
+}
+
+// NOTE: the script is awaited as an expression, like with `webview_eval_async_js`.
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window, script),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn stream(window: &Window, script: String) -> BoxResult<BoxStream<'static, Result<String>>> {
+    bridge::call_chunked(window, &format!("return await ({script}\n);"), CHUNK_SIZE).await
+}
+
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window, script),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn eval_chunked(window: &Window, script: String) -> BoxResult<serde_json::Value> {
+    let json = stream(window, script).await?.try_collect::<String>().await?;
+    Ok(serde_json::from_str(&json)?)
+}
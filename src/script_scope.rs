@@ -0,0 +1,50 @@
+use crate::BoxResult;
+use serde::{Deserialize, Serialize};
+
+const SCRIPT_SCOPE_SCRIPT: &str = include_str!("js/script_scope.js");
+
+/// Limits the frames an injected script runs in.
+///
+/// URL globs are matched against the full URL of each frame, where `*` matches any sequence of
+/// characters. A script runs in a frame when it matches any of the include globs (or there are
+/// none) and none of the exclude globs.
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct ScriptScope {
+    pub main_frame_only: bool,
+    pub include_url_globs: Vec<String>,
+    pub exclude_url_globs: Vec<String>,
+}
+
+impl Default for ScriptScope {
+    fn default() -> Self {
+        Self {
+            main_frame_only: true,
+            include_url_globs: vec![],
+            exclude_url_globs: vec![],
+        }
+    }
+}
+
+impl ScriptScope {
+    pub fn all_frames() -> Self {
+        Self {
+            main_frame_only: false,
+            ..Self::default()
+        }
+    }
+
+    // NOTE: the URL checks run in the frame itself, since only webkit2gtk supports URL patterns for
+    // user scripts natively, and its pattern syntax is more restrictive than globs. The source is
+    // only wrapped when there are globs to check, in which case its top-level declarations are
+    // scoped to the wrapping block.
+    pub(crate) fn wrap(&self, source: &str) -> BoxResult<String> {
+        if self.include_url_globs.is_empty() && self.exclude_url_globs.is_empty() {
+            return Ok(source.into());
+        }
+        let include = serde_json::to_string(&self.include_url_globs)?;
+        let exclude = serde_json::to_string(&self.exclude_url_globs)?;
+        Ok(format!(
+            "if (({SCRIPT_SCOPE_SCRIPT})({include}, {exclude})) {{\n{source}\n}}"
+        ))
+    }
+}
@@ -0,0 +1,52 @@
+use crate::options;
+use std::time::{Duration, Instant};
+
+// NOTE: how long a change made through this crate is expected to take to be reported. A change that
+// is not reported by then (like setting an item to the value it already had) is forgotten, rather
+// than being mistaken for a later change of the page.
+const TAG_LIFETIME: Duration = Duration::from_secs(5);
+
+// NOTE: changes made through this crate are tagged before they are made, and the events they cause
+// take the tag again, so that the watchers can tell them apart from changes made by the page. The
+// same key can be tagged more than once, for changes made in quick succession.
+pub(crate) struct SelfTags<K> {
+    tags: Vec<(K, Instant)>,
+}
+
+impl<K: PartialEq> SelfTags<K> {
+    pub(crate) fn tag(&mut self, key: K) {
+        self.prune();
+        self.tags.push((key, Instant::now()));
+    }
+
+    pub(crate) fn take(&mut self, key: &K) -> bool {
+        self.prune();
+        match self.tags.iter().position(|(tagged, _)| tagged == key) {
+            Some(index) => {
+                self.tags.remove(index);
+                true
+            },
+            None => false,
+        }
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.tags.clear();
+    }
+
+    fn prune(&mut self) {
+        let now = Instant::now();
+        self.tags
+            .retain(|(_, tagged)| now.duration_since(*tagged) < TAG_LIFETIME);
+    }
+}
+
+impl<K> Default for SelfTags<K> {
+    fn default() -> Self {
+        Self { tags: Vec::new() }
+    }
+}
+
+pub(crate) fn ignore_self() -> bool {
+    options::options().ignore_self
+}
@@ -0,0 +1,482 @@
+use crate::{
+    scheme_handler::{self, SchemeHandler, SchemeOptions, SchemeRequest, SchemeResponse},
+    BoxResult,
+};
+use futures::{future, FutureExt};
+use std::{
+    fs::{File, Metadata},
+    io::{Read, Seek, SeekFrom},
+    path::{Component, Path, PathBuf},
+    sync::Arc,
+};
+use tauri::{
+    http::{HttpRange, MimeType, Request, Response},
+    AppHandle,
+    Runtime,
+};
+use time::{format_description::FormatItem, OffsetDateTime, PrimitiveDateTime};
+use url::Url;
+
+/// How files are served from a directory.
+///
+/// With `precompressed`, a `.br` or `.gz` file next to a requested file is served in its place to
+/// webviews that accept that encoding. With `compress`, text files without such a file are
+/// compressed when they are served, which needs the `compression` feature.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct ServeDirectoryOptions {
+    pub index: String,
+    pub spa_fallback: bool,
+    pub precompressed: bool,
+    pub compress: bool,
+}
+
+impl Default for ServeDirectoryOptions {
+    fn default() -> Self {
+        Self {
+            index: "index.html".into(),
+            spa_fallback: false,
+            precompressed: true,
+            compress: false,
+        }
+    }
+}
+
+/// Creates a protocol handler that serves files from `root`, for use with
+/// `tauri::Builder::register_uri_scheme_protocol`.
+///
+/// See [`crate::scheme_protocol`] for when this is needed instead of
+/// [`crate::WebviewExt::webview_serve_directory`].
+pub fn directory_protocol<R: Runtime>(
+    root: PathBuf,
+    options: ServeDirectoryOptions,
+) -> impl Fn(&AppHandle<R>, &Request) -> Result<Response, Box<dyn std::error::Error>> + Send + Sync + 'static {
+    move |_app, request| {
+        let header = |name: &str| request.headers().get(name).and_then(|value| value.to_str().ok());
+        scheme_handler::respond(respond(&root, &options, request.uri(), header))
+    }
+}
+
+/// Like [`directory_protocol`], but answers requests according to the CORS policy and CSP of
+/// `scheme_options`.
+pub fn directory_protocol_with_options<R: Runtime>(
+    root: PathBuf,
+    options: ServeDirectoryOptions,
+    scheme_options: SchemeOptions,
+) -> impl Fn(&AppHandle<R>, &Request) -> Result<Response, Box<dyn std::error::Error>> + Send + Sync + 'static {
+    let handler = handler(root, options);
+    scheme_handler::scheme_protocol_with_options(scheme_options, move |request| handler(request))
+}
+
+pub(crate) fn handler(root: PathBuf, options: ServeDirectoryOptions) -> SchemeHandler {
+    Arc::new(move |request: SchemeRequest| {
+        future::ready(respond(&root, &options, &request.uri, |name| request.header(name))).boxed()
+    })
+}
+
+fn respond<'a>(
+    root: &Path,
+    options: &ServeDirectoryOptions,
+    uri: &str,
+    header: impl Fn(&str) -> Option<&'a str>,
+) -> SchemeResponse {
+    let path = match resolve(root, options, uri) {
+        Some(path) => path,
+        None => return SchemeResponse::new(404),
+    };
+    match read(&path, options, header) {
+        Ok(response) => response,
+        #[allow(unused_variables)]
+        Err(err) => {
+            #[cfg(feature = "tracing")]
+            tracing::warn!(path = %path.display(), %err, "failed to serve file");
+            SchemeResponse::new(500)
+        },
+    }
+}
+
+// NOTE: the path is built from the decoded segments of the URL, and any segment that is not a plain
+// file name is rejected rather than normalized. Checking for absolute paths is not enough, since on
+// Windows a segment like `C:` has a prefix but no root, and pushing it replaces the whole path.
+fn resolve(root: &Path, options: &ServeDirectoryOptions, uri: &str) -> Option<PathBuf> {
+    let url = Url::parse(uri).ok()?;
+    let mut path = root.to_path_buf();
+    for segment in url.path_segments().into_iter().flatten() {
+        let segment = percent_encoding::percent_decode_str(segment).decode_utf8().ok()?;
+        if segment.is_empty() || segment == "." {
+            continue;
+        }
+        if segment.contains(['/', '\\']) {
+            return None;
+        }
+        let mut components = Path::new(&*segment).components();
+        match (components.next(), components.next()) {
+            (Some(Component::Normal(name)), None) => path.push(name),
+            _ => return None,
+        }
+    }
+    if !path.starts_with(root) {
+        return None;
+    }
+    if path.is_dir() {
+        path.push(&options.index);
+    }
+    if path.is_file() {
+        return Some(path);
+    }
+    // NOTE: only paths without an extension fall back to the index, so that missing assets are
+    // still reported as such instead of being answered with the application document.
+    if options.spa_fallback && path.extension().is_none() {
+        let index = root.join(&options.index);
+        return index.is_file().then_some(index);
+    }
+    None
+}
+
+// NOTE: files are validated with their `ETag` and `Last-Modified` headers, and `Cache-Control:
+// no-cache` makes the webview revalidate them on every load, so that a rebuilt file is never served
+// from the cache while unchanged files are answered with an empty `304`. WebKitGTK does not report
+// the headers of requests, so files are always served in full and uncompressed there.
+fn read<'a>(
+    path: &Path,
+    options: &ServeDirectoryOptions,
+    header: impl Fn(&str) -> Option<&'a str>,
+) -> BoxResult<SchemeResponse> {
+    let mut file = File::open(path)?;
+    let metadata = file.metadata()?;
+    let len = metadata.len();
+    // NOTE: ranges refer to the bytes of the file itself, so they are always served uncompressed.
+    let encoding = header("Range")
+        .is_none()
+        .then(|| negotiate_encoding(path, options, len, header("Accept-Encoding")))
+        .flatten();
+    let validators = Validators::new(&metadata, encoding.as_ref().map(|(encoding, _)| *encoding));
+    let mut headers = validators.headers();
+    if options.precompressed || options.compress {
+        headers.push(("Vary".into(), "Accept-Encoding".into()));
+    }
+    if validators.not_modified(header("If-None-Match"), header("If-Modified-Since")) {
+        return Ok(SchemeResponse {
+            status: 304,
+            headers,
+            body: vec![],
+        });
+    }
+    let mut magic = Vec::with_capacity(len.min(8192) as usize);
+    (&mut file).take(8192).read_to_end(&mut magic)?;
+    let mime = MimeType::parse(&magic, &path.to_string_lossy());
+    headers.push(("Content-Type".to_string(), mime));
+    headers.push(("Accept-Ranges".to_string(), "bytes".to_string()));
+    if let Some((encoding, source)) = encoding {
+        let body = match source {
+            EncodedSource::Sidecar(sidecar) => std::fs::read(sidecar)?,
+            #[cfg(feature = "compression")]
+            EncodedSource::Compressed => {
+                let mut data = magic;
+                file.read_to_end(&mut data)?;
+                compress(encoding, &data)?
+            },
+        };
+        headers.push(("Content-Encoding".into(), encoding.name().into()));
+        headers.push(("Content-Length".into(), body.len().to_string()));
+        return Ok(SchemeResponse {
+            status: 200,
+            headers,
+            body,
+        });
+    }
+    let (status, start, length) = match byte_range(len, &validators.etag, header) {
+        Ok(Some((start, length))) => {
+            headers.push(("Content-Range".into(), content_range(start, length, len)));
+            (206, start, length)
+        },
+        Ok(None) => (200, 0, len),
+        Err(response) => return Ok(response),
+    };
+    let mut body = Vec::with_capacity(length as usize);
+    file.seek(SeekFrom::Start(start))?;
+    file.take(length).read_to_end(&mut body)?;
+    headers.push(("Content-Length".into(), body.len().to_string()));
+    Ok(SchemeResponse { status, headers, body })
+}
+
+// NOTE: returns the start and length of the requested range, or `None` for the whole body. A range
+// is ignored when `If-Range` names another version of the body, in which case the whole body is
+// served. Only single ranges are served partially, requests for multiple ranges get the whole body,
+// which is allowed for servers that do not support multipart responses.
+pub(crate) fn byte_range<'a>(
+    len: u64,
+    etag: &str,
+    header: impl Fn(&str) -> Option<&'a str>,
+) -> Result<Option<(u64, u64)>, SchemeResponse> {
+    let range = header("Range").filter(|_| header("If-Range").map_or(true, |tag| tag == etag));
+    match range.map(|range| HttpRange::parse(range, len)) {
+        Some(Ok(ranges)) if ranges.len() == 1 => Ok(Some((ranges[0].start, ranges[0].length))),
+        Some(Err(_)) => Err(SchemeResponse::new(416).header("Content-Range", format!("bytes */{len}"))),
+        _ => Ok(None),
+    }
+}
+
+pub(crate) fn content_range(start: u64, length: u64, len: u64) -> String {
+    format!("bytes {start}-{}/{len}", start + length - 1)
+}
+
+// NOTE: the order of the variants is the order of preference among encodings of the same quality.
+#[derive(Clone, Copy, Debug, Eq, Ord, PartialEq, PartialOrd)]
+enum Encoding {
+    Brotli,
+    Gzip,
+}
+
+impl Encoding {
+    fn name(self) -> &'static str {
+        match self {
+            Self::Brotli => "br",
+            Self::Gzip => "gzip",
+        }
+    }
+
+    fn extension(self) -> &'static str {
+        match self {
+            Self::Brotli => "br",
+            Self::Gzip => "gz",
+        }
+    }
+}
+
+enum EncodedSource {
+    Sidecar(PathBuf),
+    #[cfg(feature = "compression")]
+    Compressed,
+}
+
+// NOTE: files smaller than this are not compressed when they are served, since the saved bytes are
+// not worth the time it takes.
+#[cfg(feature = "compression")]
+const MIN_COMPRESS_SIZE: u64 = 1024;
+
+#[cfg(feature = "compression")]
+const COMPRESSIBLE_EXTENSIONS: &[&str] = &[
+    "css", "csv", "htm", "html", "js", "json", "map", "mjs", "svg", "txt", "wasm", "xml",
+];
+
+#[allow(unused_variables)]
+fn negotiate_encoding(
+    path: &Path,
+    options: &ServeDirectoryOptions,
+    len: u64,
+    accept_encoding: Option<&str>,
+) -> Option<(Encoding, EncodedSource)> {
+    let accepted = accepted_encodings(accept_encoding?);
+    if options.precompressed {
+        for &encoding in &accepted {
+            let mut sidecar = path.as_os_str().to_owned();
+            sidecar.push(".");
+            sidecar.push(encoding.extension());
+            let sidecar = PathBuf::from(sidecar);
+            if sidecar.is_file() {
+                return Some((encoding, EncodedSource::Sidecar(sidecar)));
+            }
+        }
+    }
+    #[cfg(feature = "compression")]
+    if options.compress && len >= MIN_COMPRESS_SIZE {
+        let compressible = path
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .map_or(false, |extension| {
+                COMPRESSIBLE_EXTENSIONS.contains(&extension.to_ascii_lowercase().as_str())
+            });
+        if compressible {
+            return accepted.first().map(|&encoding| (encoding, EncodedSource::Compressed));
+        }
+    }
+    None
+}
+
+// NOTE: encodings are ordered by their quality, and those with a quality of 0 are not acceptable.
+// The `*` wildcard is ignored, so that only encodings the webview names are used.
+fn accepted_encodings(accept_encoding: &str) -> Vec<Encoding> {
+    let mut accepted = accept_encoding
+        .split(',')
+        .filter_map(|item| {
+            let mut parts = item.split(';');
+            let encoding = match parts.next()?.trim().to_ascii_lowercase().as_str() {
+                "br" => Encoding::Brotli,
+                "gzip" | "x-gzip" => Encoding::Gzip,
+                _ => return None,
+            };
+            let quality = parts
+                .find_map(|part| part.trim().strip_prefix("q=")?.parse::<f32>().ok())
+                .unwrap_or(1.0);
+            (quality > 0.0).then_some((encoding, quality))
+        })
+        .collect::<Vec<_>>();
+    accepted.sort_by(|(a, a_quality), (b, b_quality)| b_quality.total_cmp(a_quality).then(a.cmp(b)));
+    accepted.into_iter().map(|(encoding, _)| encoding).collect()
+}
+
+#[cfg(feature = "compression")]
+fn compress(encoding: Encoding, data: &[u8]) -> std::io::Result<Vec<u8>> {
+    use std::io::Write;
+
+    match encoding {
+        Encoding::Brotli => {
+            // NOTE: a middle quality, since the highest ones take far too long for files that are
+            // compressed on every request.
+            let mut writer = brotli::CompressorWriter::new(Vec::new(), 4096, 5, 22);
+            writer.write_all(data)?;
+            Ok(writer.into_inner())
+        },
+        Encoding::Gzip => {
+            let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+            encoder.write_all(data)?;
+            encoder.finish()
+        },
+    }
+}
+
+struct Validators {
+    etag: String,
+    modified: Option<OffsetDateTime>,
+}
+
+impl Validators {
+    // NOTE: the tag is derived from the size and modification time of the file, which change with
+    // every write, so that the file does not have to be hashed for every request. Each encoding of a
+    // file gets a tag of its own, since it is a different representation of the file.
+    fn new(metadata: &Metadata, encoding: Option<Encoding>) -> Self {
+        let modified = metadata.modified().ok().map(OffsetDateTime::from);
+        let nanos = modified.map_or(0, OffsetDateTime::unix_timestamp_nanos);
+        let suffix = encoding
+            .map(|encoding| format!("-{}", encoding.extension()))
+            .unwrap_or_default();
+        Self {
+            etag: format!("\"{:x}-{nanos:x}{suffix}\"", metadata.len()),
+            modified: modified.and_then(|modified| modified.replace_nanosecond(0).ok()),
+        }
+    }
+
+    fn headers(&self) -> Vec<(String, String)> {
+        let mut headers = vec![
+            ("ETag".to_string(), self.etag.clone()),
+            ("Cache-Control".to_string(), "no-cache".to_string()),
+        ];
+        if let Some(modified) = self.modified.and_then(|modified| modified.format(&http_date()).ok()) {
+            headers.push(("Last-Modified".into(), modified));
+        }
+        headers
+    }
+
+    // NOTE: `If-Modified-Since` is only used without `If-None-Match`, and tags are compared weakly,
+    // as RFC 9110 requires for both.
+    fn not_modified(&self, if_none_match: Option<&str>, if_modified_since: Option<&str>) -> bool {
+        if let Some(tags) = if_none_match {
+            return etag_matches(tags, &self.etag);
+        }
+        let since = if_modified_since
+            .and_then(|since| PrimitiveDateTime::parse(since.trim(), &http_date()).ok())
+            .map(PrimitiveDateTime::assume_utc);
+        matches!((self.modified, since), (Some(modified), Some(since)) if modified <= since)
+    }
+}
+
+pub(crate) fn etag_matches(if_none_match: &str, etag: &str) -> bool {
+    if_none_match
+        .split(',')
+        .map(|tag| tag.trim().trim_start_matches("W/"))
+        .any(|tag| tag == "*" || tag == etag)
+}
+
+// NOTE: the IMF-fixdate format of HTTP, which is always in GMT.
+fn http_date() -> Vec<FormatItem<'static>> {
+    time::format_description::parse(
+        "[weekday repr:short], [day] [month repr:short] [year] [hour]:[minute]:[second] GMT",
+    )
+    .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn validators() -> Validators {
+        Validators {
+            etag: "\"tag\"".into(),
+            modified: Some(OffsetDateTime::from_unix_timestamp(784111777).unwrap()),
+        }
+    }
+
+    #[test]
+    fn last_modified_is_an_http_date() {
+        let headers = validators().headers();
+        assert!(headers.contains(&("ETag".into(), "\"tag\"".into())));
+        assert!(headers.contains(&("Last-Modified".into(), "Sun, 06 Nov 1994 08:49:37 GMT".into())));
+    }
+
+    #[test]
+    fn etags_are_matched_weakly_in_lists() {
+        let validators = validators();
+        assert!(validators.not_modified(Some("\"tag\""), None));
+        assert!(validators.not_modified(Some("W/\"tag\""), None));
+        assert!(validators.not_modified(Some("\"other\", W/\"tag\""), None));
+        assert!(validators.not_modified(Some("*"), None));
+        assert!(!validators.not_modified(Some("\"other\""), None));
+        assert!(!validators.not_modified(Some("tag"), None));
+    }
+
+    #[test]
+    fn if_none_match_takes_precedence_over_if_modified_since() {
+        let validators = validators();
+        assert!(!validators.not_modified(Some("\"other\""), Some("Mon, 07 Nov 1994 08:49:37 GMT")));
+        assert!(validators.not_modified(Some("\"tag\""), Some("Sat, 05 Nov 1994 08:49:37 GMT")));
+    }
+
+    #[test]
+    fn if_modified_since_compares_dates() {
+        let validators = validators();
+        assert!(validators.not_modified(None, Some("Sun, 06 Nov 1994 08:49:37 GMT")));
+        assert!(validators.not_modified(None, Some("Mon, 07 Nov 1994 08:49:37 GMT")));
+        assert!(!validators.not_modified(None, Some("Sun, 06 Nov 1994 08:49:36 GMT")));
+        assert!(!validators.not_modified(None, Some("yesterday")));
+        assert!(!validators.not_modified(None, None));
+    }
+
+    #[test]
+    fn encodings_are_ordered_by_quality() {
+        assert_eq!(accepted_encodings("gzip, br"), [Encoding::Brotli, Encoding::Gzip]);
+        assert_eq!(accepted_encodings("br;q=0.5, gzip"), [Encoding::Gzip, Encoding::Brotli]);
+        assert_eq!(accepted_encodings("gzip;q=0.8, br;q=0.9"), [
+            Encoding::Brotli,
+            Encoding::Gzip
+        ]);
+    }
+
+    #[test]
+    fn unacceptable_and_unknown_encodings_are_ignored() {
+        assert_eq!(accepted_encodings("br;q=0, gzip"), [Encoding::Gzip]);
+        assert!(accepted_encodings("*, deflate, identity").is_empty());
+        assert_eq!(accepted_encodings("X-GZIP"), [Encoding::Gzip]);
+        assert!(accepted_encodings("").is_empty());
+    }
+
+    #[test]
+    fn if_range_only_serves_ranges_of_the_same_version() {
+        let range = |headers: &'static [(&'static str, &'static str)]| {
+            byte_range(100, "\"tag\"", |name| {
+                headers.iter().find(|(key, _)| *key == name).map(|(_, value)| *value)
+            })
+        };
+        assert_eq!(range(&[("Range", "bytes=0-9")]).unwrap(), Some((0, 10)));
+        assert_eq!(
+            range(&[("Range", "bytes=0-9"), ("If-Range", "\"tag\"")]).unwrap(),
+            Some((0, 10))
+        );
+        assert_eq!(
+            range(&[("Range", "bytes=0-9"), ("If-Range", "\"other\"")]).unwrap(),
+            None
+        );
+        assert_eq!(range(&[("Range", "bytes=0-9, 20-29")]).unwrap(), None);
+        assert_eq!(range(&[]).unwrap(), None);
+        assert_eq!(range(&[("Range", "bytes=200-300")]).unwrap_err().status, 416);
+    }
+}
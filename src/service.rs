@@ -0,0 +1,78 @@
+use crate::{Cookie, Error, WebviewExt};
+use futures::{
+    future::BoxFuture,
+    task::{Context, Poll},
+    FutureExt,
+};
+use tauri::Window;
+use tower_service::Service;
+use url::Url;
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum WebviewRequest {
+    GetCookies { url: Option<Url> },
+    DeleteCookies { url: Option<Url> },
+    Navigate { url: Url },
+    EvalJs { script: String },
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[non_exhaustive]
+pub enum WebviewResponse {
+    Cookies(Vec<Cookie>),
+    Navigated,
+    Value(serde_json::Value),
+}
+
+// NOTE: the service is always ready, since the webview queues operations itself, so limits on
+// concurrency or rate are left to the layers around it.
+#[derive(Clone)]
+pub struct WebviewService {
+    window: Window,
+}
+
+impl WebviewService {
+    pub fn new(window: Window) -> Self {
+        Self { window }
+    }
+
+    pub fn window(&self) -> &Window {
+        &self.window
+    }
+}
+
+impl std::fmt::Debug for WebviewService {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("WebviewService")
+            .field("window", &self.window.label())
+            .finish()
+    }
+}
+
+impl Service<WebviewRequest> for WebviewService {
+    type Error = Error;
+    type Future = BoxFuture<'static, Result<WebviewResponse, Error>>;
+    type Response = WebviewResponse;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, request: WebviewRequest) -> Self::Future {
+        let window = self.window.clone();
+        async move {
+            match request {
+                WebviewRequest::GetCookies { url } => {
+                    window.webview_get_cookies(url).await.map(WebviewResponse::Cookies)
+                },
+                WebviewRequest::DeleteCookies { url } => {
+                    window.webview_delete_cookies(url).await.map(WebviewResponse::Cookies)
+                },
+                WebviewRequest::Navigate { url } => window.webview_navigate(url).map(|()| WebviewResponse::Navigated),
+                WebviewRequest::EvalJs { script } => window.webview_eval_js(&script).await.map(WebviewResponse::Value),
+            }
+        }
+        .boxed()
+    }
+}
@@ -0,0 +1,123 @@
+use crate::{
+    platform,
+    storage::{self, StorageArea},
+    BoxResult,
+    Cookie,
+    CookiePattern,
+    DataKinds,
+    Error,
+    WebviewExt,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use tauri::Window;
+use url::Url;
+
+#[derive(Clone, Debug, Default, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct SessionSnapshot {
+    pub cookies: Vec<Cookie>,
+    pub local_storage: Option<LocalStorageSnapshot>,
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct LocalStorageSnapshot {
+    pub origin: Url,
+    pub items: BTreeMap<String, String>,
+}
+
+// NOTE: local storage can only be read through a document of the same origin, so only the local
+// storage of the current document is included, and only if it has a (non-opaque) origin.
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn export(window: &Window, pattern: CookiePattern) -> BoxResult<SessionSnapshot> {
+    let mut cookies = window.webview_get_cookies(None).await?;
+    cookies.retain(|cookie| pattern.matches(cookie));
+    let url = window.url();
+    let local_storage = if url.origin().is_tuple() {
+        let items = storage::items(window, StorageArea::Local, Some(url.clone())).await?;
+        Some(LocalStorageSnapshot { origin: url, items })
+    } else {
+        None
+    };
+    Ok(SessionSnapshot { cookies, local_storage })
+}
+
+// NOTE: local storage is only restored when the current document has the origin it was exported
+// from, so it should be imported again after that origin is loaded. Cookies are always restored.
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window, snapshot),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn import(window: &Window, snapshot: SessionSnapshot) -> BoxResult<()> {
+    for cookie in snapshot.cookies {
+        platform::set_cookie(window, cookie).await?;
+    }
+    if let Some(local_storage) = snapshot.local_storage {
+        if window.url().origin() == local_storage.origin.origin() {
+            for (key, value) in local_storage.items {
+                let origin = Some(local_storage.origin.clone());
+                storage::set_item(window, StorageArea::Local, origin, key, Some(value)).await?;
+            }
+        }
+    }
+    Ok(())
+}
+
+// NOTE: cookies are copied for `DataKinds::COOKIES`, and local storage for its kind, which (like with
+// `import`) only works when both documents have the same origin, since storage can only be accessed
+// through a document of its origin. Session storage belongs to a single tab, so it is never copied
+// here, even for `DataKinds::SESSION_STORAGE`, but only by `copy_session_storage`. Other kinds are
+// ignored.
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window, other),
+    fields(
+        window.label = window.label(),
+        operation.id = crate::trace::operation_id(),
+        backend = crate::trace::BACKEND,
+        other.label = other.label()
+    )
+))]
+pub(crate) async fn adopt(window: &Window, other: &Window, pattern: CookiePattern, kinds: DataKinds) -> BoxResult<()> {
+    if kinds.contains(DataKinds::COOKIES) {
+        let cookies = other.webview_get_cookies(None).await?;
+        for cookie in cookies.into_iter().filter(|cookie| pattern.matches(cookie)) {
+            platform::set_cookie(window, cookie).await?;
+        }
+    }
+    let url = other.url();
+    if !kinds.contains(DataKinds::LOCAL_STORAGE) || !url.origin().is_tuple() || window.url().origin() != url.origin() {
+        return Ok(());
+    }
+    for (key, value) in storage::items(other, StorageArea::Local, Some(url.clone())).await? {
+        storage::set_item(window, StorageArea::Local, Some(url.clone()), key, Some(value)).await?;
+    }
+    Ok(())
+}
+
+// NOTE: copies the session storage of the current document of the other window, for apps that
+// really want a new window to continue the tab of another one. Both documents need to have the same
+// origin. Returns how many items were copied.
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window, other),
+    fields(
+        window.label = window.label(),
+        operation.id = crate::trace::operation_id(),
+        backend = crate::trace::BACKEND,
+        other.label = other.label()
+    )
+))]
+pub(crate) async fn copy_session_storage(window: &Window, other: &Window) -> BoxResult<usize> {
+    let url = other.url();
+    if !url.origin().is_tuple() || window.url().origin() != url.origin() {
+        let message = "session storage can only be copied between documents of the same origin";
+        return Err(Error::Other(message.into()).into());
+    }
+    let items = storage::items(other, StorageArea::Session, Some(url.clone())).await?;
+    let count = items.len();
+    for (key, value) in items {
+        storage::set_item(window, StorageArea::Session, Some(url.clone()), key, Some(value)).await?;
+    }
+    Ok(count)
+}
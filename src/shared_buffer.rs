@@ -0,0 +1,337 @@
+use crate::{
+    bridge,
+    init_script,
+    request,
+    scheme_handler::{self, SchemeRequest, SchemeResponse},
+    state,
+    BoxResult,
+    Error,
+    Result,
+};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use futures::{future, prelude::*};
+use serde::Deserialize;
+#[cfg(any(target_os = "ios", target_os = "macos"))]
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::{
+    collections::HashMap,
+    sync::{Arc, RwLock},
+};
+use tauri::{
+    http::{HttpRange, Request, Response},
+    AppHandle,
+    Manager,
+    Window,
+};
+use url::Url;
+
+const BUFFER_SCRIPT: &str = include_str!("js/shared_buffer.js");
+
+const READ_REQUEST: &str = "tauri-webview-util:buffer-read";
+
+const WRITE_REQUEST: &str = "tauri-webview-util:buffer-write";
+
+/// The scheme that [`buffer_protocol`] must be registered for.
+pub const BUFFER_SCHEME: &str = "webview-util-buffer";
+
+#[cfg(any(target_os = "ios", target_os = "macos"))]
+static PROTOCOL_REGISTERED: AtomicBool = AtomicBool::new(false);
+
+// NOTE: the bytes are shared with the page, so writes of the page are seen through the buffer, and
+// writes through the buffer are seen by later reads of the page. Buffers keep their size.
+#[derive(Clone)]
+pub struct SharedBuffer {
+    id: u64,
+    url: Option<String>,
+    readonly: bool,
+    bytes: Arc<RwLock<Vec<u8>>>,
+}
+
+impl SharedBuffer {
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    // NOTE: the URL the page can fetch the bytes from, when the platform can serve them.
+    pub fn url(&self) -> Option<&str> {
+        self.url.as_deref()
+    }
+
+    pub fn is_readonly(&self) -> bool {
+        self.readonly
+    }
+
+    pub fn len(&self) -> usize {
+        self.bytes.read().map_or(0, |bytes| bytes.len())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn to_vec(&self) -> Result<Vec<u8>> {
+        Ok(self.bytes.read().map_err(|_| Error::Poisoned)?.clone())
+    }
+
+    pub fn write(&self, offset: usize, data: &[u8]) -> Result<()> {
+        let mut bytes = self.bytes.write().map_err(|_| Error::Poisoned)?;
+        let target = offset
+            .checked_add(data.len())
+            .and_then(|end| bytes.get_mut(offset .. end))
+            .ok_or_else(|| {
+                Error::Other(format!("write of {} bytes at {offset} is out of bounds", data.len()).into())
+            })?;
+        target.copy_from_slice(data);
+        Ok(())
+    }
+}
+
+impl std::fmt::Debug for SharedBuffer {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.debug_struct("SharedBuffer")
+            .field("id", &self.id)
+            .field("url", &self.url)
+            .field("readonly", &self.readonly)
+            .field("len", &self.len())
+            .finish()
+    }
+}
+
+#[derive(Default)]
+pub(crate) struct SharedBuffers {
+    next_id: u64,
+    endpoint: Option<String>,
+    buffers: HashMap<u64, SharedBuffer>,
+}
+
+#[derive(Deserialize)]
+struct ReadRequest {
+    id: u64,
+    #[serde(default)]
+    start: usize,
+    end: Option<usize>,
+}
+
+#[derive(Deserialize)]
+struct WriteRequest {
+    id: u64,
+    offset: usize,
+    data: String,
+}
+
+/// Creates a protocol handler that serves shared buffers to page scripts, for use with
+/// `tauri::Builder::register_uri_scheme_protocol` and [`BUFFER_SCHEME`].
+///
+/// This is only needed with WKWebView, where schemes can only be registered before a webview is
+/// created. Without it, buffers are read and written through the script message handler as base64.
+pub fn buffer_protocol(
+) -> impl Fn(&AppHandle, &Request) -> Result<Response, Box<dyn std::error::Error>> + Send + Sync + 'static {
+    #[cfg(any(target_os = "ios", target_os = "macos"))]
+    PROTOCOL_REGISTERED.store(true, Ordering::Relaxed);
+    move |app, request| {
+        let headers = request
+            .headers()
+            .iter()
+            .filter_map(|(name, value)| Some((name.to_string(), value.to_str().ok()?.to_string())))
+            .collect();
+        let request = SchemeRequest {
+            uri: request.uri().into(),
+            method: request.method().to_string(),
+            headers,
+            body: request.body().clone(),
+        };
+        let response = match parse(&request.uri).and_then(|(label, _)| app.get_window(&label)) {
+            Some(window) => respond(&window, request),
+            None => SchemeResponse::new(404),
+        };
+        scheme_handler::respond(response)
+    }
+}
+
+// NOTE: page scripts read the bytes with `window.__TAURI_WEBVIEW_UTIL_BUFFER__.read(id, start, end)`,
+// which resolves to an `ArrayBuffer`, and write them with `write(id, offset, data)` unless the buffer
+// is read-only. Bytes are fetched from a custom scheme that serves ranges where responses can have
+// headers, which is with WebView2, and with WKWebView when `buffer_protocol` is registered.
+// Elsewhere they are sent through the bridge as base64, since webkit2gtk can only set the headers of
+// scheme responses since 2.36. WebView2 shared buffers would avoid the copies, but need
+// `ICoreWebView2Environment12`, which the bindings used here do not have yet.
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window, bytes),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn share(window: &Window, bytes: Vec<u8>, readonly: bool) -> BoxResult<SharedBuffer> {
+    let state = state::window_state(window)?;
+    if let Some(install) = bridge::install_once(&state, "shared-buffer").await? {
+        request::on_request(window, READ_REQUEST, {
+            let window = window.clone();
+            move |payload| future::ready(read_request(&window, payload).map_err(Into::into)).boxed()
+        })
+        .await?;
+        request::on_request(window, WRITE_REQUEST, {
+            let window = window.clone();
+            move |payload| future::ready(write_request(&window, payload).map_err(Into::into)).boxed()
+        })
+        .await?;
+        let endpoint = endpoint(window).await?;
+        state.shared_buffers.lock()?.endpoint = endpoint.clone();
+        let config = serde_json::json!({
+            "endpoint": endpoint,
+            "read": READ_REQUEST,
+            "write": WRITE_REQUEST,
+        });
+        let source = format!("({BUFFER_SCRIPT})({config});");
+        init_script::replace(window, &state.shared_buffer_script, source).await?;
+        install.done();
+    }
+    let mut buffers = state.shared_buffers.lock()?;
+    buffers.next_id += 1;
+    let id = buffers.next_id;
+    let buffer = SharedBuffer {
+        id,
+        url: buffers.endpoint.as_ref().map(|endpoint| format!("{endpoint}{id}")),
+        readonly,
+        bytes: Arc::new(RwLock::new(bytes)),
+    };
+    buffers.buffers.insert(id, buffer.clone());
+    Ok(buffer)
+}
+
+// NOTE: the page can no longer read or write the buffer, but the bytes stay available through the
+// handle.
+pub(crate) async fn unshare(window: &Window, id: u64) -> BoxResult<bool> {
+    let state = state::window_state(window)?;
+    let removed = state.shared_buffers.lock()?.buffers.remove(&id).is_some();
+    Ok(removed)
+}
+
+#[cfg(target_os = "windows")]
+async fn endpoint(window: &Window) -> BoxResult<Option<String>> {
+    let handler = Arc::new({
+        let window = window.clone();
+        move |request: SchemeRequest| future::ready(respond(&window, request)).boxed()
+    });
+    crate::platform::serve_scheme(window, BUFFER_SCHEME.into(), handler).await?;
+    Ok(Some(format!(
+        "https://{BUFFER_SCHEME}.localhost/{}/",
+        encode_label(window)
+    )))
+}
+
+#[cfg(any(target_os = "ios", target_os = "macos"))]
+async fn endpoint(window: &Window) -> BoxResult<Option<String>> {
+    Ok(PROTOCOL_REGISTERED
+        .load(Ordering::Relaxed)
+        .then(|| format!("{BUFFER_SCHEME}://localhost/{}/", encode_label(window))))
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "ios", target_os = "macos")))]
+async fn endpoint(_window: &Window) -> BoxResult<Option<String>> {
+    Ok(None)
+}
+
+#[cfg(any(target_os = "windows", target_os = "ios", target_os = "macos"))]
+fn encode_label(window: &Window) -> String {
+    percent_encoding::utf8_percent_encode(window.label(), percent_encoding::NON_ALPHANUMERIC).to_string()
+}
+
+fn buffer(window: &Window, id: u64) -> Result<SharedBuffer> {
+    let state = state::window_state(window).map_err(Error::from)?;
+    let buffers = state.shared_buffers.lock()?;
+    buffers
+        .buffers
+        .get(&id)
+        .cloned()
+        .ok_or_else(|| Error::Other(format!("no shared buffer with id {id}").into()))
+}
+
+fn read_request(window: &Window, payload: serde_json::Value) -> Result<serde_json::Value> {
+    let ReadRequest { id, start, end } = serde_json::from_value(payload)?;
+    let buffer = buffer(window, id)?;
+    let bytes = buffer.bytes.read().map_err(|_| Error::Poisoned)?;
+    let end = end.unwrap_or(bytes.len());
+    let bytes = bytes
+        .get(start .. end)
+        .ok_or_else(|| Error::Other(format!("range {start}..{end} is out of bounds").into()))?;
+    Ok(serde_json::Value::String(STANDARD.encode(bytes)))
+}
+
+fn write_request(window: &Window, payload: serde_json::Value) -> Result<serde_json::Value> {
+    let WriteRequest { id, offset, data } = serde_json::from_value(payload)?;
+    let buffer = buffer(window, id)?;
+    if buffer.readonly {
+        return Err(Error::Other(format!("shared buffer {id} is read-only").into()));
+    }
+    let data = STANDARD.decode(data).map_err(|err| Error::Other(err.into()))?;
+    buffer.write(offset, &data)?;
+    Ok(serde_json::Value::Null)
+}
+
+// NOTE: the URL is `<endpoint>/<label>/<id>`, with `?offset=<offset>` for writes.
+fn parse(uri: &str) -> Option<(String, u64)> {
+    let url = Url::parse(uri).ok()?;
+    let mut segments = url.path_segments()?;
+    let label = percent_encoding::percent_decode_str(segments.next()?)
+        .decode_utf8()
+        .ok()?
+        .into_owned();
+    let id = segments.next()?.parse().ok()?;
+    Some((label, id))
+}
+
+fn write_offset(uri: &str) -> Option<usize> {
+    let url = Url::parse(uri).ok()?;
+    let (_, offset) = url.query_pairs().find(|(name, _)| name == "offset")?;
+    offset.parse().ok()
+}
+
+// NOTE: responses allow any origin, since the scheme is never the origin of the page that reads the
+// buffer. Only single ranges are served partially, like with `serve_directory`.
+fn respond(window: &Window, request: SchemeRequest) -> SchemeResponse {
+    let response = match (request.method.as_str(), parse(&request.uri)) {
+        ("OPTIONS", _) => SchemeResponse::new(204)
+            .header("Access-Control-Allow-Methods", "GET, PUT")
+            .header("Access-Control-Allow-Headers", "Range"),
+        (_, None) => SchemeResponse::new(404),
+        (method, Some((_, id))) => match buffer(window, id) {
+            Ok(buffer) if method == "GET" => serve(&buffer, request.header("Range")),
+            Ok(buffer) if method == "PUT" && buffer.readonly => SchemeResponse::new(403),
+            Ok(buffer) if method == "PUT" => match write_offset(&request.uri) {
+                Some(offset) => match buffer.write(offset, &request.body) {
+                    Ok(()) => SchemeResponse::new(204),
+                    Err(err) => SchemeResponse::new(416).body(err.to_string()),
+                },
+                None => SchemeResponse::new(400),
+            },
+            Ok(_) => SchemeResponse::new(405),
+            Err(_) => SchemeResponse::new(404),
+        },
+    };
+    response.header("Access-Control-Allow-Origin", "*").header(
+        "Access-Control-Expose-Headers",
+        "Accept-Ranges, Content-Length, Content-Range",
+    )
+}
+
+fn serve(buffer: &SharedBuffer, range: Option<&str>) -> SchemeResponse {
+    let bytes = match buffer.bytes.read() {
+        Ok(bytes) => bytes,
+        Err(_) => return SchemeResponse::new(500),
+    };
+    let len = bytes.len() as u64;
+    let response = SchemeResponse::new(200)
+        .header("Content-Type", "application/octet-stream")
+        .header("Accept-Ranges", "bytes");
+    match range.map(|range| HttpRange::parse(range, len)) {
+        Some(Ok(ranges)) if ranges.len() == 1 => {
+            let range = &ranges[0];
+            let (start, end) = (range.start as usize, (range.start + range.length) as usize);
+            let mut response = response
+                .header("Content-Range", format!("bytes {start}-{}/{len}", end - 1))
+                .body(&bytes[start .. end]);
+            response.status = 206;
+            response
+        },
+        Some(Err(_)) => SchemeResponse::new(416).header("Content-Range", format!("bytes */{len}")),
+        _ => response.body(bytes.as_slice()),
+    }
+}
@@ -0,0 +1,19 @@
+use crate::{bridge, BoxResult};
+use tauri::Window;
+
+const SINGLE_FILE_SCRIPT: &str = include_str!("js/single_file.js");
+
+// NOTE: the page is serialized from its current DOM, with stylesheets, images, icons and media
+// posters inlined as data URLs, and scripts and event handler attributes removed so that the
+// snapshot does not change once opened. Links are made absolute, and frames are not inlined.
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn export(window: &Window) -> BoxResult<String> {
+    let body = format!("return await ({SINGLE_FILE_SCRIPT})();");
+    let html = bridge::call(window, &body).await?;
+    html.as_str()
+        .map(Into::into)
+        .ok_or_else(|| "unexpected reply when exporting page".into())
+}
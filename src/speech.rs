@@ -0,0 +1,48 @@
+use crate::{bridge, platform, BoxResult};
+use serde::Deserialize;
+use tauri::Window;
+
+#[derive(Deserialize)]
+struct Selection {
+    text: String,
+    spoken: bool,
+}
+
+// NOTE: the selection is spoken with `speechSynthesis` where the engine provides voices for it,
+// which WebKitGTK often does not, and otherwise with the speech service of the platform.
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn speak_selection(window: &Window) -> BoxResult<()> {
+    let body = r#"
+        const text = String(window.getSelection() || "").trim();
+        const synthesis = window.speechSynthesis;
+        if (!text || !synthesis || synthesis.getVoices().length === 0) {
+            return { text, spoken: false };
+        }
+        synthesis.cancel();
+        synthesis.speak(new SpeechSynthesisUtterance(text));
+        return { text, spoken: true };
+    "#;
+    let selection = serde_json::from_value::<Selection>(bridge::call(window, body).await?)?;
+    if selection.spoken || selection.text.is_empty() {
+        return Ok(());
+    }
+    platform::speak(window, selection.text).await
+}
+
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn stop_speaking(window: &Window) -> BoxResult<()> {
+    let body = r#"
+        if (window.speechSynthesis) {
+            window.speechSynthesis.cancel();
+        }
+        return null;
+    "#;
+    bridge::call(window, body).await?;
+    platform::stop_speaking(window).await
+}
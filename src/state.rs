@@ -0,0 +1,138 @@
+use crate::{
+    auth::AuthHandler,
+    bridge::BridgeState,
+    broadcast::BroadcastState,
+    budget::PageBudget,
+    certificate::CertificateErrorHandler,
+    content_rules::ContentRuleList,
+    cookie_watch::CookieWatchState,
+    credentials::CredentialCapture,
+    csp::CspViolation,
+    device::DeviceState,
+    downloads::DownloadEvent,
+    dry_run::DryRunState,
+    element::ElementRectState,
+    event_bus::EventBus,
+    event_source::EventSourceEvent,
+    init_script::InitScript,
+    load_error::ErrorPageRenderer,
+    mock::MockRoute,
+    navigation_events::{NavigationEvent, SelfNavigation},
+    navigation_policy::{HttpsOnlyMode, RewriteRule},
+    navigation_queue::NavigationQueueState,
+    permission::PermissionHandler,
+    platform::{NativeDownload, UserScriptHandle},
+    print::PrintAction,
+    proxy::ProxyCredentials,
+    recording::RecordingState,
+    request::{RequestEncoding, RequestHandler},
+    request_inspection::RequestRedactor,
+    scheme_handler::SchemeOptions,
+    self_events::SelfTags,
+    shared_buffer::SharedBuffers,
+    storage::StorageArea,
+    storage_events::StorageChange,
+    stream,
+    transfer::TransferState,
+    upload::UploadEvent,
+    user_script::UserScripts,
+    websocket::WebSocketState,
+    ApiResult,
+    BoxResult,
+};
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    sync::Arc,
+};
+use tauri::{Manager, Window, WindowEvent};
+
+#[derive(Default)]
+pub(crate) struct WindowState {
+    pub(crate) auth_handler: ApiResult<Option<AuthHandler>>,
+    pub(crate) blocked_urls: ApiResult<BTreeSet<String>>,
+    pub(crate) bridge: ApiResult<BridgeState>,
+    pub(crate) broadcast: ApiResult<BroadcastState>,
+    pub(crate) broadcast_script: ApiResult<Option<UserScriptHandle>>,
+    pub(crate) budget: ApiResult<PageBudget>,
+    pub(crate) budget_script: ApiResult<Option<UserScriptHandle>>,
+    pub(crate) certificate_error_handler: ApiResult<Option<CertificateErrorHandler>>,
+    pub(crate) content_rules: ApiResult<Option<Arc<ContentRuleList>>>,
+    pub(crate) cookie_watch: ApiResult<CookieWatchState>,
+    pub(crate) credential_capture: ApiResult<Option<CredentialCapture>>,
+    pub(crate) credential_script: ApiResult<Option<UserScriptHandle>>,
+    #[cfg(target_os = "windows")]
+    pub(crate) default_user_agent: ApiResult<Option<String>>,
+    pub(crate) csp_subscribers: ApiResult<Vec<stream::Sender<CspViolation>>>,
+    pub(crate) download_subscribers: ApiResult<Vec<stream::Sender<DownloadEvent>>>,
+    pub(crate) device: ApiResult<DeviceState>,
+    pub(crate) device_script: ApiResult<Option<UserScriptHandle>>,
+    pub(crate) downloads: ApiResult<HashMap<u64, NativeDownload>>,
+    pub(crate) dry_run: ApiResult<DryRunState>,
+    pub(crate) element_rect_script: ApiResult<Option<UserScriptHandle>>,
+    pub(crate) element_rects: ApiResult<ElementRectState>,
+    pub(crate) error_page: ApiResult<Option<ErrorPageRenderer>>,
+    pub(crate) event_buses: ApiResult<Vec<EventBus>>,
+    pub(crate) event_source_subscribers: ApiResult<Vec<stream::Sender<EventSourceEvent>>>,
+    pub(crate) https_only: ApiResult<HttpsOnlyMode>,
+    pub(crate) init_scripts: ApiResult<BTreeMap<String, InitScript>>,
+    pub(crate) media_script: ApiResult<Option<UserScriptHandle>>,
+    pub(crate) mock_routes: ApiResult<Vec<MockRoute>>,
+    pub(crate) mock_script: ApiResult<Option<UserScriptHandle>>,
+    pub(crate) navigation_queue: ApiResult<NavigationQueueState>,
+    pub(crate) navigation_subscribers: ApiResult<Vec<(bool, stream::Sender<NavigationEvent>)>>,
+    pub(crate) page_cache_script: ApiResult<Option<UserScriptHandle>>,
+    pub(crate) permission_handler: ApiResult<Option<PermissionHandler>>,
+    pub(crate) print: ApiResult<PrintAction>,
+    pub(crate) proxy_credentials: ApiResult<Option<ProxyCredentials>>,
+    pub(crate) read_only: ApiResult<bool>,
+    pub(crate) recording: ApiResult<RecordingState>,
+    #[cfg(feature = "hot-reload")]
+    pub(crate) reload_watcher: ApiResult<Option<notify::RecommendedWatcher>>,
+    pub(crate) referrer_policy_script: ApiResult<Option<UserScriptHandle>>,
+    pub(crate) request_inspection: ApiResult<Option<UserScriptHandle>>,
+    pub(crate) request_redactor: ApiResult<Option<RequestRedactor>>,
+    pub(crate) requests: ApiResult<HashMap<String, (RequestHandler, RequestEncoding)>>,
+    pub(crate) response_capture: ApiResult<Option<UserScriptHandle>>,
+    pub(crate) safe_mode: ApiResult<bool>,
+    pub(crate) scheme_options: ApiResult<HashMap<String, SchemeOptions>>,
+    pub(crate) self_navigation: ApiResult<SelfNavigation>,
+    pub(crate) shared_buffer_script: ApiResult<Option<UserScriptHandle>>,
+    pub(crate) shared_buffers: ApiResult<SharedBuffers>,
+    pub(crate) storage_subscribers: ApiResult<Vec<(bool, stream::Sender<StorageChange>)>>,
+    pub(crate) storage_tags: ApiResult<SelfTags<(StorageArea, Option<String>)>>,
+    pub(crate) transfer_script: ApiResult<Option<UserScriptHandle>>,
+    pub(crate) transfers: ApiResult<TransferState>,
+    pub(crate) upload_subscribers: ApiResult<Vec<stream::Sender<UploadEvent>>>,
+    pub(crate) url_rewrites: ApiResult<Vec<RewriteRule>>,
+    pub(crate) user_agent: ApiResult<Option<String>>,
+    pub(crate) user_agent_rules: ApiResult<Vec<(String, String)>>,
+    pub(crate) user_scripts: ApiResult<UserScripts>,
+    pub(crate) websocket: ApiResult<WebSocketState>,
+    pub(crate) websocket_script: ApiResult<Option<UserScriptHandle>>,
+}
+
+#[derive(Default)]
+struct Registry(ApiResult<HashMap<String, Arc<WindowState>>>);
+
+pub(crate) fn window_state(window: &Window) -> BoxResult<Arc<WindowState>> {
+    window.manage(Registry::default());
+    let registry = window.state::<Registry>();
+    let mut windows = registry.0.lock()?;
+    if let Some(state) = windows.get(window.label()) {
+        return Ok(state.clone());
+    }
+    let state = Arc::new(WindowState::default());
+    windows.insert(window.label().into(), state.clone());
+    window.on_window_event({
+        let registry = registry.0.clone();
+        let label = String::from(window.label());
+        move |event| {
+            if let WindowEvent::Destroyed = event {
+                if let Ok(mut windows) = registry.lock() {
+                    windows.remove(&label);
+                }
+            }
+        }
+    });
+    Ok(state)
+}
@@ -0,0 +1,116 @@
+use crate::{
+    bridge,
+    dry_run::{self, DryRunAction},
+    read_only,
+    storage_events,
+    BoxResult,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use tauri::Window;
+use url::Url;
+
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum StorageArea {
+    Local,
+    Session,
+}
+
+impl StorageArea {
+    fn property(self) -> &'static str {
+        match self {
+            Self::Local => "localStorage",
+            Self::Session => "sessionStorage",
+        }
+    }
+}
+
+// NOTE: web storage can only be reached through a document of the same origin, so every operation
+// runs in the current document. When an origin is given, the operation fails unless it matches the
+// origin of the current document, so that storage of an unexpected site is never touched.
+fn script(area: StorageArea, origin: Option<&Url>, operation: &str) -> BoxResult<String> {
+    let origin = match origin {
+        Some(origin) => serde_json::to_string(&origin.origin().ascii_serialization())?,
+        None => "null".into(),
+    };
+    let property = area.property();
+    Ok(format!(
+        r#"
+        const origin = {origin};
+        if (origin !== null && origin !== location.origin) {{
+            throw new Error(`current document has origin ${{location.origin}} instead of ${{origin}}`);
+        }}
+        const storage = window.{property};
+        {operation}
+        "#
+    ))
+}
+
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn items(
+    window: &Window,
+    area: StorageArea,
+    origin: Option<Url>,
+) -> BoxResult<BTreeMap<String, String>> {
+    let operation = "return Object.fromEntries(Object.keys(storage).map((key) => [key, storage.getItem(key)]));";
+    let body = script(area, origin.as_ref(), operation)?;
+    Ok(serde_json::from_value(bridge::call(window, &body).await?)?)
+}
+
+// NOTE: an item without a value is removed.
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window, value),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn set_item(
+    window: &Window,
+    area: StorageArea,
+    origin: Option<Url>,
+    key: String,
+    value: Option<String>,
+) -> BoxResult<()> {
+    read_only::check(window)?;
+    let skip = dry_run::intercept(window, || DryRunAction::SetStorageItem {
+        area,
+        origin: origin.clone(),
+        key: key.clone(),
+        value: value.clone(),
+    })?;
+    if skip {
+        return Ok(());
+    }
+    storage_events::tag(window, area, Some(&key));
+    let key = serde_json::to_string(&key)?;
+    let operation = match value {
+        Some(value) => format!(
+            "storage.setItem({key}, {}); return null;",
+            serde_json::to_string(&value)?
+        ),
+        None => format!("storage.removeItem({key}); return null;"),
+    };
+    let body = script(area, origin.as_ref(), &operation)?;
+    bridge::call(window, &body).await?;
+    Ok(())
+}
+
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn clear(window: &Window, area: StorageArea, origin: Option<Url>) -> BoxResult<()> {
+    read_only::check(window)?;
+    if dry_run::intercept(window, || DryRunAction::ClearStorage {
+        area,
+        origin: origin.clone(),
+    })? {
+        return Ok(());
+    }
+    let body = script(area, origin.as_ref(), "storage.clear(); return null;")?;
+    storage_events::tag(window, area, None);
+    bridge::call(window, &body).await?;
+    Ok(())
+}
@@ -0,0 +1,75 @@
+use crate::{bridge, self_events, state, storage::StorageArea, stream, BoxResult, Result};
+use futures::stream::BoxStream;
+use serde::{Deserialize, Serialize};
+use tauri::Window;
+
+const STORAGE_EVENTS_SCRIPT: &str = include_str!("js/storage_events.js");
+
+// NOTE: `key` is `None` when the whole storage area was cleared, and the values are `None` when
+// the item did not exist before or after the change.
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StorageChange {
+    pub area: StorageArea,
+    pub key: Option<String>,
+    pub old_value: Option<String>,
+    pub new_value: Option<String>,
+    pub origin: String,
+}
+
+// NOTE: called before this crate changes storage, with no key when a whole area is cleared. Nothing
+// is recorded while no one is watching.
+pub(crate) fn tag(window: &Window, area: StorageArea, key: Option<&str>) {
+    let Ok(state) = state::window_state(window) else {
+        return;
+    };
+    if !state
+        .storage_subscribers
+        .lock()
+        .map_or(true, |subscribers| subscribers.is_empty())
+    {
+        if let Ok(mut tags) = state.storage_tags.lock() {
+            tags.tag((area, key.map(Into::into)));
+        }
+    }
+}
+
+// NOTE: changes are observed in the top frame of every document, so changes made by documents in
+// frames are only reported when they also reach the top frame as `storage` events.
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn events(window: &Window) -> BoxResult<BoxStream<'static, Result<StorageChange>>> {
+    let state = state::window_state(window)?;
+    let (event_tx, event_rx) = stream::channel(stream::stream_config());
+    state
+        .storage_subscribers
+        .lock()?
+        .push((self_events::ignore_self(), event_tx));
+    if let Some(install) = bridge::install_once(&state, "storage-events").await? {
+        bridge::on_message(window, "storage", {
+            let window = window.clone();
+            move |payload| {
+                let change = match serde_json::from_value::<StorageChange>(payload) {
+                    Ok(change) => change,
+                    Err(err) => return crate::report_error(window.label(), "malformed storage event", err),
+                };
+                let Ok(state) = state::window_state(&window) else {
+                    return;
+                };
+                let key = (change.area, change.key.clone());
+                let from_self = state.storage_tags.lock().map_or(false, |mut tags| tags.take(&key));
+                if let Ok(mut subscribers) = state.storage_subscribers.lock() {
+                    subscribers.retain(|(ignore_self, subscriber)| {
+                        (from_self && *ignore_self) || subscriber.send(change.clone()).is_ok()
+                    });
+                }
+            }
+        })
+        .await?;
+        bridge::add_script(window, STORAGE_EVENTS_SCRIPT).await?;
+        install.done();
+    }
+    Ok(event_rx)
+}
@@ -0,0 +1,271 @@
+use crate::{options, Error, Result};
+use futures::{
+    stream::{BoxStream, Stream},
+    task::{Context, Poll, Waker},
+    StreamExt,
+};
+use std::{
+    collections::VecDeque,
+    pin::Pin,
+    sync::{Arc, Mutex},
+};
+use tokio::sync::Notify;
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct StreamConfig {
+    pub capacity: Option<usize>,
+    pub overflow: Overflow,
+}
+
+impl StreamConfig {
+    pub const fn unbounded() -> Self {
+        Self {
+            capacity: None,
+            overflow: Overflow::Block,
+        }
+    }
+
+    /// # Panics
+    ///
+    /// Panics if `capacity` is 0, since nothing could ever be sent on such a stream.
+    pub const fn bounded(capacity: usize, overflow: Overflow) -> Self {
+        assert!(capacity > 0, "the capacity of a bounded stream must be greater than 0");
+        Self {
+            capacity: Some(capacity),
+            overflow,
+        }
+    }
+}
+
+impl Default for StreamConfig {
+    fn default() -> Self {
+        Self::unbounded()
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub enum Overflow {
+    // NOTE: producers are usually platform callbacks, which must not block, so items are never
+    // dropped but queued beyond the capacity instead. Producers that can wait do so asynchronously
+    // with `Sender::ready`, which resolves once the stream has space again.
+    #[default]
+    Block,
+    DropOldest,
+    Error,
+}
+
+pub fn set_stream_config(config: StreamConfig) {
+    options::update(|options| options.stream = config);
+}
+
+pub fn stream_config() -> StreamConfig {
+    options::options().stream
+}
+
+struct Shared<T> {
+    queue: VecDeque<T>,
+    senders: usize,
+    closed: bool,
+    overflowed: bool,
+    waker: Option<Waker>,
+}
+
+struct Channel<T> {
+    config: StreamConfig,
+    shared: Mutex<Shared<T>>,
+    space: Notify,
+}
+
+pub(crate) struct Sender<T>(Arc<Channel<T>>);
+
+pub(crate) struct Closed;
+
+// NOTE: a capacity of 0 can still be set through the field of the config, which is treated as a
+// capacity of 1.
+pub(crate) fn channel<T: Send + 'static>(mut config: StreamConfig) -> (Sender<T>, BoxStream<'static, Result<T>>) {
+    config.capacity = config.capacity.map(|capacity| capacity.max(1));
+    let channel = Arc::new(Channel {
+        config,
+        shared: Mutex::new(Shared {
+            queue: VecDeque::new(),
+            senders: 1,
+            closed: false,
+            overflowed: false,
+            waker: None,
+        }),
+        space: Notify::new(),
+    });
+    let receiver = Receiver {
+        channel: channel.clone(),
+        poisoned: false,
+    };
+    (Sender(channel), receiver.boxed())
+}
+
+impl<T> Sender<T> {
+    pub(crate) fn send(&self, item: T) -> Result<(), Closed> {
+        let mut shared = self.0.shared.lock().map_err(|_| Closed)?;
+        if shared.closed || shared.overflowed {
+            return Err(Closed);
+        }
+        match self.0.config.capacity {
+            Some(capacity) if shared.queue.len() >= capacity => match self.0.config.overflow {
+                Overflow::Block => {},
+                Overflow::DropOldest => {
+                    shared.queue.pop_front();
+                },
+                Overflow::Error => {
+                    shared.overflowed = true;
+                    wake(&mut shared);
+                    return Err(Closed);
+                },
+            },
+            _ => {},
+        }
+        shared.queue.push_back(item);
+        wake(&mut shared);
+        Ok(())
+    }
+
+    // NOTE: resolves once a blocking stream has space for another item, or with `Closed` once the
+    // stream is closed. Streams that drop or fail on overflow are always ready.
+    pub(crate) async fn ready(&self) -> Result<(), Closed> {
+        loop {
+            {
+                let shared = self.0.shared.lock().map_err(|_| Closed)?;
+                if shared.closed || shared.overflowed {
+                    return Err(Closed);
+                }
+                match (self.0.config.capacity, self.0.config.overflow) {
+                    (Some(capacity), Overflow::Block) if shared.queue.len() >= capacity => {},
+                    _ => return Ok(()),
+                }
+            }
+            // NOTE: a notification sent in between is stored as a permit, so it is not missed.
+            self.0.space.notified().await;
+        }
+    }
+}
+
+impl<T> Clone for Sender<T> {
+    fn clone(&self) -> Self {
+        if let Ok(mut shared) = self.0.shared.lock() {
+            shared.senders += 1;
+        }
+        Self(self.0.clone())
+    }
+}
+
+impl<T> Drop for Sender<T> {
+    fn drop(&mut self) {
+        if let Ok(mut shared) = self.0.shared.lock() {
+            shared.senders -= 1;
+            if shared.senders == 0 {
+                wake(&mut shared);
+            }
+        }
+    }
+}
+
+struct Receiver<T> {
+    channel: Arc<Channel<T>>,
+    poisoned: bool,
+}
+
+impl<T> Stream for Receiver<T> {
+    type Item = Result<T>;
+
+    // NOTE: a poisoned channel ends the stream after a single `Poisoned` error.
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        if this.poisoned {
+            return Poll::Ready(None);
+        }
+        let mut shared = match this.channel.shared.lock() {
+            Ok(shared) => shared,
+            Err(_) => {
+                this.poisoned = true;
+                return Poll::Ready(Some(Err(Error::Poisoned)));
+            },
+        };
+        if let Some(item) = shared.queue.pop_front() {
+            this.channel.space.notify_one();
+            return Poll::Ready(Some(Ok(item)));
+        }
+        if shared.overflowed {
+            shared.overflowed = false;
+            shared.closed = true;
+            return Poll::Ready(Some(Err(Error::StreamOverflow)));
+        }
+        if shared.closed || shared.senders == 0 {
+            return Poll::Ready(None);
+        }
+        shared.waker = Some(cx.waker().clone());
+        Poll::Pending
+    }
+}
+
+impl<T> Drop for Receiver<T> {
+    fn drop(&mut self) {
+        if let Ok(mut shared) = self.channel.shared.lock() {
+            shared.closed = true;
+            shared.queue.clear();
+        }
+        // NOTE: a permit is also stored for a producer that is about to wait for space.
+        self.channel.space.notify_waiters();
+        self.channel.space.notify_one();
+    }
+}
+
+fn wake<T>(shared: &mut Shared<T>) {
+    if let Some(waker) = shared.waker.take() {
+        waker.wake();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::FutureExt;
+
+    #[test]
+    fn blocking_streams_queue_beyond_capacity() {
+        let (tx, mut rx) = channel(StreamConfig::bounded(1, Overflow::Block));
+        assert!(tx.send(1).is_ok());
+        assert!(tx.send(2).is_ok());
+        drop(tx);
+        tauri::async_runtime::block_on(async {
+            assert!(matches!(rx.next().await, Some(Ok(1))));
+            assert!(matches!(rx.next().await, Some(Ok(2))));
+            assert!(rx.next().await.is_none());
+        });
+    }
+
+    #[test]
+    fn ready_waits_for_space() {
+        let (tx, mut rx) = channel(StreamConfig::bounded(1, Overflow::Block));
+        assert!(matches!(tx.ready().now_or_never(), Some(Ok(()))));
+        assert!(tx.send(1).is_ok());
+        assert!(tx.ready().now_or_never().is_none());
+        tauri::async_runtime::block_on(async {
+            assert!(matches!(rx.next().await, Some(Ok(1))));
+        });
+        assert!(matches!(tx.ready().now_or_never(), Some(Ok(()))));
+        drop(rx);
+        assert!(matches!(tx.ready().now_or_never(), Some(Err(Closed))));
+    }
+
+    #[test]
+    fn poisoned_streams_end_after_one_error() {
+        let (tx, mut rx) = channel::<u8>(StreamConfig::unbounded());
+        let poison = std::panic::AssertUnwindSafe(|| {
+            let _shared = tx.0.shared.lock();
+            panic!("poisoning the channel");
+        });
+        assert!(std::panic::catch_unwind(poison).is_err());
+        tauri::async_runtime::block_on(async {
+            assert!(matches!(rx.next().await, Some(Err(Error::Poisoned))));
+            assert!(rx.next().await.is_none());
+        });
+    }
+}
@@ -0,0 +1,296 @@
+use crate::{Error, Result};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use std::collections::BTreeMap;
+use time::OffsetDateTime;
+
+const TAG: &str = "$type";
+
+// NOTE: a value of the types the structured clone algorithm can copy, other than the ones with
+// platform resources (like blobs or image bitmaps). Cyclic and shared references are not kept, and
+// cyclic values are rejected by the page.
+#[derive(Clone, Debug, PartialEq)]
+#[non_exhaustive]
+pub enum CloneValue {
+    Undefined,
+    Null,
+    Bool(bool),
+    Number(f64),
+    // NOTE: the decimal digits of the value, with a leading `-` when negative.
+    BigInt(String),
+    String(String),
+    // NOTE: milliseconds since the Unix epoch, which is NaN for invalid dates.
+    Date(f64),
+    RegExp { source: String, flags: String },
+    ArrayBuffer(Vec<u8>),
+    // NOTE: the bytes viewed by the array, in the byte order of the page.
+    TypedArray { kind: TypedArrayKind, bytes: Vec<u8> },
+    Array(Vec<CloneValue>),
+    Object(BTreeMap<String, CloneValue>),
+    Map(Vec<(CloneValue, CloneValue)>),
+    Set(Vec<CloneValue>),
+}
+
+impl CloneValue {
+    pub fn as_bytes(&self) -> Option<&[u8]> {
+        match self {
+            Self::ArrayBuffer(bytes) | Self::TypedArray { bytes, .. } => Some(bytes),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Self::String(string) => Some(string),
+            _ => None,
+        }
+    }
+
+    pub fn as_date_time(&self) -> Option<OffsetDateTime> {
+        match self {
+            Self::Date(millis) if millis.is_finite() => {
+                OffsetDateTime::from_unix_timestamp_nanos(*millis as i128 * 1_000_000).ok()
+            },
+            _ => None,
+        }
+    }
+}
+
+impl From<bool> for CloneValue {
+    fn from(value: bool) -> Self {
+        Self::Bool(value)
+    }
+}
+
+impl From<f64> for CloneValue {
+    fn from(value: f64) -> Self {
+        Self::Number(value)
+    }
+}
+
+impl From<&str> for CloneValue {
+    fn from(value: &str) -> Self {
+        Self::String(value.into())
+    }
+}
+
+impl From<String> for CloneValue {
+    fn from(value: String) -> Self {
+        Self::String(value)
+    }
+}
+
+// NOTE: bytes become a `Uint8Array`, which is what page scripts usually expect for binary data.
+impl From<Vec<u8>> for CloneValue {
+    fn from(bytes: Vec<u8>) -> Self {
+        Self::TypedArray {
+            kind: TypedArrayKind::Uint8,
+            bytes,
+        }
+    }
+}
+
+impl From<OffsetDateTime> for CloneValue {
+    fn from(value: OffsetDateTime) -> Self {
+        Self::Date((value.unix_timestamp_nanos() / 1_000_000) as f64)
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum TypedArrayKind {
+    Int8,
+    Uint8,
+    Uint8Clamped,
+    Int16,
+    Uint16,
+    Int32,
+    Uint32,
+    Float32,
+    Float64,
+    BigInt64,
+    BigUint64,
+    DataView,
+}
+
+impl TypedArrayKind {
+    fn name(self) -> &'static str {
+        match self {
+            Self::Int8 => "Int8Array",
+            Self::Uint8 => "Uint8Array",
+            Self::Uint8Clamped => "Uint8ClampedArray",
+            Self::Int16 => "Int16Array",
+            Self::Uint16 => "Uint16Array",
+            Self::Int32 => "Int32Array",
+            Self::Uint32 => "Uint32Array",
+            Self::Float32 => "Float32Array",
+            Self::Float64 => "Float64Array",
+            Self::BigInt64 => "BigInt64Array",
+            Self::BigUint64 => "BigUint64Array",
+            Self::DataView => "DataView",
+        }
+    }
+
+    fn from_name(name: &str) -> Option<Self> {
+        Some(match name {
+            "Int8Array" => Self::Int8,
+            "Uint8Array" => Self::Uint8,
+            "Uint8ClampedArray" => Self::Uint8Clamped,
+            "Int16Array" => Self::Int16,
+            "Uint16Array" => Self::Uint16,
+            "Int32Array" => Self::Int32,
+            "Uint32Array" => Self::Uint32,
+            "Float32Array" => Self::Float32,
+            "Float64Array" => Self::Float64,
+            "BigInt64Array" => Self::BigInt64,
+            "BigUint64Array" => Self::BigUint64,
+            "DataView" => Self::DataView,
+            _ => return None,
+        })
+    }
+}
+
+// NOTE: the wire format is JSON, so that it passes through the same message handlers as the rest of
+// the bridge. Values without a JSON form are objects tagged with `$type`, and plain objects which
+// have a `$type` key of their own are tagged as `object`, so that plain JSON decodes unchanged.
+pub(crate) fn encode(value: &CloneValue) -> serde_json::Value {
+    use serde_json::{json, Value};
+
+    let tagged = |tag: &str, value: Value| json!({ TAG: tag, "value": value });
+    match value {
+        CloneValue::Undefined => json!({ TAG: "undefined" }),
+        CloneValue::Null => Value::Null,
+        CloneValue::Bool(value) => Value::Bool(*value),
+        CloneValue::Number(number) => match serde_json::Number::from_f64(*number) {
+            Some(json) if *number != 0.0 || number.is_sign_positive() => Value::Number(json),
+            _ => tagged("number", Value::String(js_number(*number))),
+        },
+        CloneValue::BigInt(digits) => tagged("bigint", Value::String(digits.clone())),
+        CloneValue::String(string) => Value::String(string.clone()),
+        CloneValue::Date(millis) => tagged(
+            "date",
+            serde_json::Number::from_f64(*millis).map_or(Value::Null, Value::Number),
+        ),
+        CloneValue::RegExp { source, flags } => json!({ TAG: "regexp", "source": source, "flags": flags }),
+        CloneValue::ArrayBuffer(bytes) => tagged("array-buffer", Value::String(STANDARD.encode(bytes))),
+        CloneValue::TypedArray { kind, bytes } => {
+            json!({ TAG: "typed-array", "kind": kind.name(), "value": STANDARD.encode(bytes) })
+        },
+        CloneValue::Array(values) => Value::Array(values.iter().map(encode).collect()),
+        CloneValue::Object(entries) => {
+            let object = entries
+                .iter()
+                .map(|(key, value)| (key.clone(), encode(value)))
+                .collect::<serde_json::Map<_, _>>();
+            if object.contains_key(TAG) {
+                tagged("object", Value::Object(object))
+            } else {
+                Value::Object(object)
+            }
+        },
+        CloneValue::Map(entries) => tagged(
+            "map",
+            entries
+                .iter()
+                .map(|(key, value)| Value::Array(vec![encode(key), encode(value)]))
+                .collect(),
+        ),
+        CloneValue::Set(values) => tagged("set", values.iter().map(encode).collect()),
+    }
+}
+
+pub(crate) fn decode(value: serde_json::Value) -> Result<CloneValue> {
+    use serde_json::Value;
+
+    let invalid = |reason: &str| Error::Other(format!("invalid structured clone value: {reason}").into());
+    Ok(match value {
+        Value::Null => CloneValue::Null,
+        Value::Bool(value) => CloneValue::Bool(value),
+        Value::Number(number) => CloneValue::Number(number.as_f64().ok_or_else(|| invalid("number out of range"))?),
+        Value::String(string) => CloneValue::String(string),
+        Value::Array(values) => CloneValue::Array(values.into_iter().map(decode).collect::<Result<_>>()?),
+        Value::Object(mut object) => {
+            let tag = match object.remove(TAG) {
+                Some(Value::String(tag)) => tag,
+                Some(_) => return Err(invalid("`$type` is not a string")),
+                None => {
+                    return Ok(CloneValue::Object(
+                        object
+                            .into_iter()
+                            .map(|(key, value)| Ok((key, decode(value)?)))
+                            .collect::<Result<_>>()?,
+                    ))
+                },
+            };
+            let mut field = |name: &str| object.remove(name).unwrap_or(Value::Null);
+            let string = |value: Value| match value {
+                Value::String(string) => Ok(string),
+                _ => Err(invalid(&format!("expected a string in `{tag}`"))),
+            };
+            let bytes = |value: Value| {
+                STANDARD
+                    .decode(string(value)?)
+                    .map_err(|err| invalid(&format!("bad base64 in `{tag}`: {err}")))
+            };
+            let array = |value: Value| match value {
+                Value::Array(values) => Ok(values),
+                _ => Err(invalid(&format!("expected an array in `{tag}`"))),
+            };
+            match tag.as_str() {
+                "undefined" => CloneValue::Undefined,
+                "number" => CloneValue::Number(match string(field("value"))?.as_str() {
+                    "NaN" => f64::NAN,
+                    "Infinity" => f64::INFINITY,
+                    "-Infinity" => f64::NEG_INFINITY,
+                    "-0" => -0.0,
+                    other => return Err(invalid(&format!("unknown number `{other}`"))),
+                }),
+                "bigint" => CloneValue::BigInt(string(field("value"))?),
+                "date" => CloneValue::Date(field("value").as_f64().unwrap_or(f64::NAN)),
+                "regexp" => CloneValue::RegExp {
+                    source: string(field("source"))?,
+                    flags: string(field("flags"))?,
+                },
+                "array-buffer" => CloneValue::ArrayBuffer(bytes(field("value"))?),
+                "typed-array" => {
+                    let kind = string(field("kind"))?;
+                    CloneValue::TypedArray {
+                        kind: TypedArrayKind::from_name(&kind)
+                            .ok_or_else(|| invalid(&format!("unknown typed array `{kind}`")))?,
+                        bytes: bytes(field("value"))?,
+                    }
+                },
+                "object" => match field("value") {
+                    Value::Object(object) => CloneValue::Object(
+                        object
+                            .into_iter()
+                            .map(|(key, value)| Ok((key, decode(value)?)))
+                            .collect::<Result<_>>()?,
+                    ),
+                    _ => return Err(invalid("expected an object in `object`")),
+                },
+                "map" => CloneValue::Map(
+                    array(field("value"))?
+                        .into_iter()
+                        .map(|entry| match array(entry)?.as_mut_slice() {
+                            [key, value] => Ok((decode(key.take())?, decode(value.take())?)),
+                            _ => Err(invalid("expected a key and value in `map`")),
+                        })
+                        .collect::<Result<_>>()?,
+                ),
+                "set" => CloneValue::Set(array(field("value"))?.into_iter().map(decode).collect::<Result<_>>()?),
+                other => return Err(invalid(&format!("unknown type `{other}`"))),
+            }
+        },
+    })
+}
+
+fn js_number(number: f64) -> String {
+    if number.is_nan() {
+        "NaN".into()
+    } else if number == f64::INFINITY {
+        "Infinity".into()
+    } else if number == f64::NEG_INFINITY {
+        "-Infinity".into()
+    } else {
+        "-0".into()
+    }
+}
@@ -0,0 +1,71 @@
+use crate::{bridge, BoxResult};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use tauri::Window;
+
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct TextNode {
+    pub id: u64,
+    pub text: String,
+}
+
+// NOTE: node ids index into a list kept by the current document, which is replaced by every
+// extraction, so ids are only valid until the next extraction or navigation. Text inside elements
+// that are not rendered as text (scripts, styles, and form controls) is skipped.
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn extract(window: &Window) -> BoxResult<Vec<TextNode>> {
+    let body = r#"
+        const skipped = new Set(["SCRIPT", "STYLE", "NOSCRIPT", "TEMPLATE", "TEXTAREA", "CODE", "PRE"]);
+        const walker = document.createTreeWalker(document.body || document.documentElement, NodeFilter.SHOW_TEXT, {
+            acceptNode(node) {
+                if (!node.nodeValue.trim()) {
+                    return NodeFilter.FILTER_REJECT;
+                }
+                for (let parent = node.parentElement; parent; parent = parent.parentElement) {
+                    if (skipped.has(parent.tagName) || parent.isContentEditable) {
+                        return NodeFilter.FILTER_REJECT;
+                    }
+                }
+                return NodeFilter.FILTER_ACCEPT;
+            },
+        });
+        const nodes = [];
+        while (walker.nextNode()) {
+            nodes.push(walker.currentNode);
+        }
+        Object.defineProperty(window, "__TAURI_WEBVIEW_UTIL_TEXT_NODES__", { value: nodes, configurable: true });
+        return nodes.map((node, id) => ({ id, text: node.nodeValue }));
+    "#;
+    Ok(serde_json::from_value(bridge::call(window, body).await?)?)
+}
+
+// NOTE: returns the number of nodes that were replaced. Ids of nodes that have since been removed
+// from the document are ignored.
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window, texts),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn replace(window: &Window, texts: BTreeMap<u64, String>) -> BoxResult<u64> {
+    let texts = serde_json::to_string(&texts)?;
+    let body = format!(
+        r#"
+        const nodes = window.__TAURI_WEBVIEW_UTIL_TEXT_NODES__ || [];
+        let replaced = 0;
+        for (const [id, text] of Object.entries({texts})) {{
+            const node = nodes[Number(id)];
+            if (node && node.isConnected) {{
+                node.nodeValue = text;
+                replaced += 1;
+            }}
+        }}
+        return replaced;
+        "#
+    );
+    let replaced = bridge::call(window, &body).await?;
+    replaced
+        .as_u64()
+        .ok_or_else(|| "unexpected reply when replacing text nodes".into())
+}
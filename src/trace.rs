@@ -0,0 +1,31 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+
+static NEXT_OPERATION: AtomicU64 = AtomicU64::new(1);
+
+#[cfg(any(
+    target_os = "linux",
+    target_os = "dragonfly",
+    target_os = "freebsd",
+    target_os = "openbsd",
+    target_os = "netbsd"
+))]
+pub(crate) const BACKEND: &str = "webkit2gtk";
+
+#[cfg(target_os = "macos")]
+pub(crate) const BACKEND: &str = "wkwebview";
+
+#[cfg(target_os = "windows")]
+pub(crate) const BACKEND: &str = "webview2";
+
+// NOTE: every operation span gets an id of its own, so that the events of concurrent operations on
+// the same window can be told apart. How long an operation took is reported by the subscriber when
+// its span closes, e.g. with `FmtSpan::CLOSE` of `tracing-subscriber`.
+pub(crate) fn operation_id() -> u64 {
+    NEXT_OPERATION.fetch_add(1, Ordering::Relaxed)
+}
+
+// NOTE: platform callbacks run on the main thread, outside of the span of the operation that
+// started them, so their events carry the backend to be attributed.
+pub(crate) fn callback(name: &'static str) {
+    tracing::debug!(backend = BACKEND, callback = name, "platform callback");
+}
@@ -0,0 +1,252 @@
+use crate::{
+    bridge,
+    init_script,
+    request,
+    scheme_handler::{self, SchemeRequest, SchemeResponse},
+    state,
+    stream,
+    BoxResult,
+    Error,
+    Result,
+};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use futures::{future, prelude::*, stream::BoxStream};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+#[cfg(any(target_os = "ios", target_os = "macos"))]
+use std::sync::atomic::{AtomicBool, Ordering};
+use tauri::{
+    http::{Request, Response},
+    AppHandle,
+    Manager,
+    Window,
+};
+use url::Url;
+
+const TRANSFER_SCRIPT: &str = include_str!("js/transfer.js");
+
+const TRANSFER_REQUEST: &str = "tauri-webview-util:transfer";
+
+// NOTE: chunks are posted one at a time, so that the page never holds more than one copy of a chunk
+// in memory. Chunks sent through the bridge are smaller, since they are encoded as base64.
+const CHUNK_SIZE: usize = 8 * 1024 * 1024;
+
+const BRIDGE_CHUNK_SIZE: usize = 1024 * 1024;
+
+/// The scheme that [`transfer_protocol`] must be registered for.
+pub const TRANSFER_SCHEME: &str = "webview-util-transfer";
+
+#[cfg(any(target_os = "ios", target_os = "macos"))]
+static PROTOCOL_REGISTERED: AtomicBool = AtomicBool::new(false);
+
+#[derive(Clone, Debug, Default, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct ByteTransfer {
+    pub content_type: Option<String>,
+    pub bytes: Vec<u8>,
+}
+
+#[derive(Default)]
+pub(crate) struct TransferState {
+    receivers: HashMap<String, stream::Sender<ByteTransfer>>,
+    pending: HashMap<(String, u64), ByteTransfer>,
+}
+
+#[derive(Deserialize)]
+struct Chunk {
+    name: String,
+    id: u64,
+    offset: usize,
+    last: bool,
+    #[serde(rename = "type")]
+    content_type: Option<String>,
+    #[serde(default)]
+    data: String,
+}
+
+/// Creates a protocol handler that receives the bytes sent by page scripts, for use with
+/// `tauri::Builder::register_uri_scheme_protocol` and [`TRANSFER_SCHEME`].
+///
+/// This is only needed with WKWebView, where schemes can only be registered before a webview is
+/// created. Without it, bytes are sent through the script message handler as base64.
+pub fn transfer_protocol(
+) -> impl Fn(&AppHandle, &Request) -> Result<Response, Box<dyn std::error::Error>> + Send + Sync + 'static {
+    #[cfg(any(target_os = "ios", target_os = "macos"))]
+    PROTOCOL_REGISTERED.store(true, Ordering::Relaxed);
+    move |app, request| {
+        let request = SchemeRequest {
+            uri: request.uri().into(),
+            method: request.method().to_string(),
+            body: request.body().clone(),
+            ..SchemeRequest::default()
+        };
+        let response = match parse(&request.uri).and_then(|(label, _)| app.get_window(&label)) {
+            Some(window) => respond(&window, request),
+            None => SchemeResponse::new(404),
+        };
+        scheme_handler::respond(response)
+    }
+}
+
+// NOTE: page scripts send bytes with `window.__TAURI_WEBVIEW_UTIL_TRANSFER__.send(name, data, type)`,
+// where `data` is a `Blob`, an `ArrayBuffer`, or a typed array, and the promise it returns resolves
+// once every byte was received. Bytes are posted to a custom scheme where the request body is
+// available, which is with WebView2, and with WKWebView when `transfer_protocol` is registered.
+// Elsewhere they are sent through the bridge as base64, since webkit2gtk only exposes the body of
+// scheme requests since 2.40. WebView2 shared buffers would avoid copying the bytes, but need
+// `ICoreWebView2Environment12`, which the bindings used here do not have yet. Later calls for the
+// same name replace the previous receiver.
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn receive(window: &Window, name: &str) -> BoxResult<BoxStream<'static, Result<ByteTransfer>>> {
+    let state = state::window_state(window)?;
+    let (transfer_tx, transfers) = stream::channel(stream::stream_config());
+    state.transfers.lock()?.receivers.insert(name.into(), transfer_tx);
+    if let Some(install) = bridge::install_once(&state, "transfer").await? {
+        request::on_request(window, TRANSFER_REQUEST, {
+            let window = window.clone();
+            move |payload| {
+                let result = serde_json::from_value::<Chunk>(payload)
+                    .map_err(Error::from)
+                    .and_then(|chunk| {
+                        let bytes = STANDARD.decode(&chunk.data).map_err(|err| Error::Other(err.into()))?;
+                        append(&window, chunk, bytes)
+                    })
+                    .map(|()| serde_json::Value::Null)
+                    .map_err(Into::into);
+                future::ready(result).boxed()
+            }
+        })
+        .await?;
+        let config = serde_json::json!({
+            "endpoint": endpoint(window).await?,
+            "chunkSize": CHUNK_SIZE,
+            "bridgeChunkSize": BRIDGE_CHUNK_SIZE,
+            "request": TRANSFER_REQUEST,
+        });
+        let source = format!("({TRANSFER_SCRIPT})({config});");
+        init_script::replace(window, &state.transfer_script, source).await?;
+        install.done();
+    }
+    Ok(transfers)
+}
+
+// NOTE: the label of the window is part of the URL, so that the protocol handler registered with
+// tauri can find the window the bytes are for.
+#[cfg(target_os = "windows")]
+async fn endpoint(window: &Window) -> BoxResult<Option<String>> {
+    let handler = std::sync::Arc::new({
+        let window = window.clone();
+        move |request: SchemeRequest| future::ready(respond(&window, request)).boxed()
+    });
+    crate::platform::serve_scheme(window, TRANSFER_SCHEME.into(), handler).await?;
+    Ok(Some(format!(
+        "https://{TRANSFER_SCHEME}.localhost/{}/",
+        encode_label(window)
+    )))
+}
+
+#[cfg(any(target_os = "ios", target_os = "macos"))]
+async fn endpoint(window: &Window) -> BoxResult<Option<String>> {
+    Ok(PROTOCOL_REGISTERED
+        .load(Ordering::Relaxed)
+        .then(|| format!("{TRANSFER_SCHEME}://localhost/{}/", encode_label(window))))
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "ios", target_os = "macos")))]
+async fn endpoint(_window: &Window) -> BoxResult<Option<String>> {
+    Ok(None)
+}
+
+#[cfg(any(target_os = "windows", target_os = "ios", target_os = "macos"))]
+fn encode_label(window: &Window) -> String {
+    percent_encoding::utf8_percent_encode(window.label(), percent_encoding::NON_ALPHANUMERIC).to_string()
+}
+
+// NOTE: the URL is `<endpoint>/<label>/<name>/<id>?offset=<offset>&last=<last>&type=<type>`.
+fn parse(uri: &str) -> Option<(String, Chunk)> {
+    let url = Url::parse(uri).ok()?;
+    let decode = |segment: &str| {
+        Some(
+            percent_encoding::percent_decode_str(segment)
+                .decode_utf8()
+                .ok()?
+                .into_owned(),
+        )
+    };
+    let mut segments = url.path_segments()?;
+    let (label, name, id) = (segments.next()?, segments.next()?, segments.next()?);
+    let query = url.query_pairs().collect::<HashMap<_, _>>();
+    let chunk = Chunk {
+        name: decode(name)?,
+        id: id.parse().ok()?,
+        offset: query.get("offset")?.parse().ok()?,
+        last: query.get("last")?.parse().ok()?,
+        content_type: query.get("type").map(|content_type| content_type.to_string()),
+        data: String::new(),
+    };
+    Some((decode(label)?, chunk))
+}
+
+// NOTE: responses allow any origin, since the scheme is never the origin of the page that sends the
+// bytes.
+fn respond(window: &Window, request: SchemeRequest) -> SchemeResponse {
+    let response = match request.method.as_str() {
+        "OPTIONS" => SchemeResponse::new(204)
+            .header("Access-Control-Allow-Methods", "POST")
+            .header("Access-Control-Allow-Headers", "Content-Type"),
+        "POST" => match parse(&request.uri) {
+            Some((_, chunk)) => match append(window, chunk, request.body) {
+                Ok(()) => SchemeResponse::new(200),
+                Err(err) => SchemeResponse::new(400).body(err.to_string()),
+            },
+            None => SchemeResponse::new(404),
+        },
+        _ => SchemeResponse::new(405),
+    };
+    response.header("Access-Control-Allow-Origin", "*")
+}
+
+fn append(window: &Window, chunk: Chunk, bytes: Vec<u8>) -> Result<()> {
+    let state = state::window_state(window).map_err(Error::from)?;
+    let mut transfers = state.transfers.lock()?;
+    if !transfers.receivers.contains_key(&chunk.name) {
+        return Err(Error::Other(
+            format!("no receiver for transfer `{}`", chunk.name).into(),
+        ));
+    }
+    let key = (chunk.name, chunk.id);
+    // NOTE: a first chunk replaces whatever was left of a transfer with the same id, which can only
+    // be one that a previous document did not finish.
+    if chunk.offset == 0 {
+        transfers.pending.insert(key.clone(), ByteTransfer {
+            content_type: chunk.content_type,
+            bytes: vec![],
+        });
+    }
+    let transfer = match transfers.pending.get_mut(&key) {
+        Some(transfer) if transfer.bytes.len() == chunk.offset => transfer,
+        _ => {
+            transfers.pending.remove(&key);
+            return Err(Error::Other(
+                format!("unexpected chunk at offset {}", chunk.offset).into(),
+            ));
+        },
+    };
+    transfer.bytes.extend_from_slice(&bytes);
+    if chunk.last {
+        if let Some(transfer) = transfers.pending.remove(&key) {
+            let (name, _) = key;
+            let sent = transfers
+                .receivers
+                .get(&name)
+                .map_or(false, |receiver| receiver.send(transfer).is_ok());
+            if !sent {
+                transfers.receivers.remove(&name);
+                return Err(Error::ChannelClosed);
+            }
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,60 @@
+use crate::{bridge, state, stream, BoxResult, Result};
+use futures::stream::BoxStream;
+use serde::{Deserialize, Serialize};
+use tauri::Window;
+use url::Url;
+
+const UPLOAD_SCRIPT: &str = include_str!("js/upload.js");
+
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum UploadPhase {
+    Started,
+    Progress,
+    Completed,
+    Failed,
+}
+
+// NOTE: `id` identifies an upload within the document that started it, and `total` is missing when
+// the size of the body can not be known in advance (like form data sent with `fetch`).
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct UploadEvent {
+    pub id: u64,
+    pub url: Url,
+    pub method: String,
+    pub phase: UploadPhase,
+    pub sent: u64,
+    pub total: Option<u64>,
+}
+
+// NOTE: uploads are observed by wrapping `fetch` and `XMLHttpRequest` in the top frame of every
+// document. Only `XMLHttpRequest` reports progress while the body is sent.
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn events(window: &Window) -> BoxResult<BoxStream<'static, Result<UploadEvent>>> {
+    let state = state::window_state(window)?;
+    let (event_tx, event_rx) = stream::channel(stream::stream_config());
+    state.upload_subscribers.lock()?.push(event_tx);
+    if let Some(install) = bridge::install_once(&state, "upload").await? {
+        bridge::on_message(window, "upload", {
+            let window = window.clone();
+            move |payload| {
+                let event = match serde_json::from_value::<UploadEvent>(payload) {
+                    Ok(event) => event,
+                    Err(err) => return crate::report_error(window.label(), "malformed upload event", err),
+                };
+                if let Ok(state) = state::window_state(&window) {
+                    if let Ok(mut subscribers) = state.upload_subscribers.lock() {
+                        subscribers.retain(|subscriber| subscriber.send(event.clone()).is_ok());
+                    }
+                }
+            }
+        })
+        .await?;
+        bridge::add_script(window, UPLOAD_SCRIPT).await?;
+        install.done();
+    }
+    Ok(event_rx)
+}
@@ -0,0 +1,63 @@
+use crate::{glob, platform, state, BoxResult};
+use tauri::Window;
+
+#[cfg(not(target_os = "windows"))]
+pub(crate) const CONTENT_FILTER_ID: &str = "tauri-webview-util-blocked-urls";
+
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn block(window: &Window, patterns: Vec<String>) -> BoxResult<()> {
+    let state = state::window_state(window)?;
+    let patterns = {
+        let mut blocked = state.blocked_urls.lock()?;
+        blocked.extend(patterns);
+        blocked.iter().cloned().collect()
+    };
+    platform::set_blocked_urls(window, patterns).await
+}
+
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn unblock(window: &Window, patterns: Vec<String>) -> BoxResult<()> {
+    let state = state::window_state(window)?;
+    let patterns = {
+        let mut blocked = state.blocked_urls.lock()?;
+        for pattern in &patterns {
+            blocked.remove(pattern);
+        }
+        blocked.iter().cloned().collect()
+    };
+    platform::set_blocked_urls(window, patterns).await
+}
+
+#[cfg(target_os = "windows")]
+pub(crate) fn is_blocked(window: &Window, url: &str) -> bool {
+    state::window_state(window)
+        .and_then(|state| {
+            Ok(state
+                .blocked_urls
+                .lock()?
+                .iter()
+                .any(|pattern| glob::matches(pattern, url)))
+        })
+        .unwrap_or_default()
+}
+
+// NOTE: WebKit content blockers use the same JSON rule format on both WebKit engines.
+#[cfg(not(target_os = "windows"))]
+pub(crate) fn content_rules(patterns: &[String]) -> BoxResult<String> {
+    let rules = patterns
+        .iter()
+        .map(|pattern| {
+            serde_json::json!({
+                "trigger": { "url-filter": glob::to_regex(pattern) },
+                "action": { "type": "block" },
+            })
+        })
+        .collect::<Vec<_>>();
+    Ok(serde_json::to_string(&rules)?)
+}
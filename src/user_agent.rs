@@ -0,0 +1,25 @@
+use crate::{navigation_policy, platform, state, BoxResult};
+use tauri::Window;
+
+// NOTE: rules are `(url glob, user agent)` pairs, and the first rule matching the URL of a main
+// frame navigation decides the user agent it is sent with. Subresources and requests made by the
+// document use whatever user agent its navigation was sent with.
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn set_rules(window: &Window, rules: Vec<(String, String)>) -> BoxResult<()> {
+    let state = state::window_state(window)?;
+    *state.user_agent_rules.lock()? = rules;
+    navigation_policy::install(window).await
+}
+
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn set(window: &Window, user_agent: Option<String>) -> BoxResult<()> {
+    let state = state::window_state(window)?;
+    *state.user_agent.lock()? = user_agent.clone();
+    platform::set_user_agent(window, user_agent).await
+}
@@ -0,0 +1,75 @@
+use crate::{platform, state, BoxResult};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use tauri::Window;
+
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub enum InjectionTime {
+    // NOTE: before any of the content of the document has been parsed.
+    #[default]
+    DocumentStart,
+    // NOTE: once the document has been parsed, before its subresources have finished loading.
+    DocumentEnd,
+}
+
+// NOTE: scripts in an isolated world share the DOM with the page but not its JavaScript globals, and
+// scripts in the same world (by name) share their globals with each other.
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct UserScript {
+    pub source: String,
+    pub injection_time: InjectionTime,
+    pub main_frame_only: bool,
+    pub isolated_world: Option<String>,
+}
+
+impl UserScript {
+    pub fn new(source: impl Into<String>) -> Self {
+        Self {
+            source: source.into(),
+            injection_time: InjectionTime::default(),
+            main_frame_only: true,
+            isolated_world: None,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
+pub struct ScriptHandle(u64);
+
+#[derive(Default)]
+pub(crate) struct UserScripts {
+    next_id: u64,
+    handles: HashMap<u64, platform::UserScriptHandle>,
+}
+
+// NOTE: user scripts run in documents loaded after they are added, but not in the current one.
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window, script),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn add(window: &Window, script: UserScript) -> BoxResult<ScriptHandle> {
+    let handle = platform::add_scoped_user_script(window, script).await?;
+    let state = state::window_state(window)?;
+    let mut scripts = state.user_scripts.lock()?;
+    scripts.next_id += 1;
+    let id = scripts.next_id;
+    scripts.handles.insert(id, handle);
+    Ok(ScriptHandle(id))
+}
+
+// NOTE: returns whether the script was still added, so removing a script twice is not an error.
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn remove(window: &Window, handle: ScriptHandle) -> BoxResult<bool> {
+    let state = state::window_state(window)?;
+    let handle = state.user_scripts.lock()?.handles.remove(&handle.0);
+    match handle {
+        Some(handle) => {
+            platform::remove_init_script(window, handle).await?;
+            Ok(true)
+        },
+        None => Ok(false),
+    }
+}
@@ -0,0 +1,35 @@
+use crate::{BoxResult, Error};
+use serde::{Deserialize, Serialize};
+
+/// Which other origins may use the files of a virtual host, like the access kinds of WebView2.
+///
+/// `Deny` only lets documents of the virtual host itself load its files, `DenyCors` also lets other
+/// origins embed them (e.g. as images or scripts) without reading them, and `Allow` also lets other
+/// origins read them with `fetch`. WebKitGTK can not restrict embedding, so there `Deny` behaves
+/// like `DenyCors`.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub enum CorsPolicy {
+    Deny,
+    #[default]
+    DenyCors,
+    Allow,
+}
+
+// NOTE: WebKitGTK can not serve `https` URLs itself, so the host is served as a custom scheme of the
+// same name, which is registered as secure so that documents of it are secure contexts with
+// storage. The host therefore has to be a valid scheme as well.
+pub(crate) fn validate_host(host: &str) -> BoxResult<()> {
+    let valid = host.starts_with(|c: char| c.is_ascii_lowercase())
+        && host
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' || c == '.')
+        && !host.split('.').any(str::is_empty);
+    if !valid {
+        return Err(Error::InvalidNavigationTarget {
+            target: host.into(),
+            reason: "a virtual host must be a lowercase host name that starts with a letter",
+        }
+        .into());
+    }
+    Ok(())
+}
@@ -0,0 +1,74 @@
+use crate::{bridge, glob, options, BoxResult, Error};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use tauri::Window;
+use tokio::time::Instant;
+
+const WAIT_FOR_SCRIPT: &str = include_str!("js/wait_for.js");
+
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[non_exhaustive]
+pub enum WaitCondition {
+    // NOTE: a CSS selector that is matched against the main frame.
+    SelectorPresent(String),
+    // NOTE: a glob that is matched against the whole URL of the main frame.
+    UrlMatches(String),
+    // NOTE: a JavaScript expression, which may evaluate to a promise.
+    JsTruthy(String),
+}
+
+impl WaitCondition {
+    fn check(&self) -> BoxResult<String> {
+        Ok(match self {
+            Self::SelectorPresent(selector) => {
+                let selector = serde_json::to_string(selector)?;
+                format!("() => document.querySelector({selector}) !== null")
+            },
+            Self::UrlMatches(pattern) => {
+                let pattern = serde_json::to_string(pattern)?;
+                format!(
+                    r#"() => new RegExp("^" + {pattern}.split("*").map((part) => part.replace(/[.*+?^${{}}()|[\]\\]/g, "\\$&")).join(".*") + "$").test(location.href)"#
+                )
+            },
+            Self::JsTruthy(expression) => format!("async () => Boolean(await ({expression}\n))"),
+        })
+    }
+}
+
+// NOTE: the condition is checked in the document whenever it changes, and checked again in the next
+// document when the current one is unloaded before the condition is met, as the retry policy of the
+// options allows. Scripts that fail to evaluate end the wait with their error.
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn wait_for(window: &Window, condition: WaitCondition, timeout: Duration) -> BoxResult<()> {
+    let deadline = Instant::now() + timeout;
+    let check = condition.check()?;
+    let retry = options::options().retry;
+    let wait = async {
+        let mut attempts = 0;
+        loop {
+            if let WaitCondition::UrlMatches(pattern) = &condition {
+                if glob::matches(pattern, window.url().as_str()) {
+                    return Ok(());
+                }
+            }
+            let remaining = deadline.saturating_duration_since(Instant::now()).as_millis();
+            let body = format!("return await ({WAIT_FOR_SCRIPT})({check}, {remaining});");
+            attempts += 1;
+            match bridge::call_with_timeout(window, &body, None).await {
+                Ok(serde_json::Value::Bool(true)) => return Ok(()),
+                Ok(_) => return Err(Error::Timeout.into()),
+                Err(err)
+                    if matches!(err.downcast_ref::<Error>(), Some(Error::ChannelClosed))
+                        && retry.max_attempts.map_or(true, |max_attempts| attempts < max_attempts) =>
+                {
+                    tokio::time::sleep(retry.delay).await;
+                },
+                Err(err) => return Err(err),
+            }
+        }
+    };
+    tokio::time::timeout_at(deadline, wait).await?
+}
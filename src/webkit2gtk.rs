@@ -1,127 +1,758 @@
-use crate::{ApiResult, BoxError, BoxResult, Cookie};
+use crate::{
+    auth::AuthHandler,
+    bridge::{self, ScriptMessage},
+    downloads::{self, DownloadUpdate},
+    error::WindowContext,
+    local_url::NavigationTarget,
+    navigation_policy::{NavigationDecision, NavigationPolicy},
+    scheme_handler::{
+        SchemeHandler,
+        SchemeOptions,
+        SchemeRequest,
+        SchemeResponse,
+        SchemeStreamHandler,
+        SchemeStreamResponse,
+    },
+    screenshot::ClipRect,
+    state,
+    ApiResult,
+    AuthChallenge,
+    BoxError,
+    BoxResult,
+    CertDecision,
+    CertificateErrorInfo,
+    CertificateErrorKind,
+    ColorScheme,
+    ContentRuleList,
+    Cookie,
+    CookiePolicyInfo,
+    CorsPolicy,
+    Credentials,
+    DataKinds,
+    DataRecord,
+    DeviceProfile,
+    DownloadEvent,
+    Error,
+    HistoryEntry,
+    InjectionTime,
+    LoadError,
+    LoadErrorKind,
+    MediaType,
+    NavigationEvent,
+    OriginUsage,
+    PdfOptions,
+    PdfViewerSettings,
+    PermissionDecision,
+    PermissionKind,
+    PermissionRequest,
+    ProfileInfo,
+    ProxyConfig,
+    ProxyCredentials,
+    Result,
+    ScreenshotFormat,
+    ScreenshotOptions,
+    ScreenshotRegion,
+    SessionKind,
+    WsEvent,
+};
 use futures::{future::BoxFuture, prelude::*};
+use std::{path::PathBuf, sync::Arc};
 use tauri::Window;
 use url::Url;
-use webkit2gtk::{gio::Cancellable, CookieManager, CookieManagerExt, WebContextExt, WebViewExt, WebsiteDataManagerExt};
+use webkit2gtk::{
+    gio::{Cancellable, MemoryInputStream, UnixInputStream},
+    AuthenticationRequestExt,
+    BackForwardListExt,
+    BackForwardListItemExt,
+    CookieAcceptPolicy,
+    CookieManager,
+    CookieManagerExt,
+    Credential,
+    CredentialPersistence,
+    DownloadError,
+    DownloadExt,
+    HardwareAccelerationPolicy,
+    LoadEvent,
+    NavigationPolicyDecision,
+    NavigationPolicyDecisionExt,
+    NetworkError,
+    NetworkProxyMode,
+    NetworkProxySettings,
+    PolicyDecisionExt,
+    PolicyDecisionType,
+    PolicyError,
+    PrintOperation,
+    PrintOperationExt,
+    SecurityManagerExt,
+    SettingsExt,
+    URIRequestExt,
+    URIResponseExt,
+    URISchemeRequest,
+    URISchemeRequestExt,
+    UserContentInjectedFrames,
+    UserContentManager,
+    UserContentManagerExt,
+    UserScript,
+    UserScriptInjectionTime,
+    WebContextExt,
+    WebContextExtManual,
+    WebInspectorExt,
+    WebProcessTerminationReason,
+    WebResourceExt,
+    WebViewExt,
+    WebsiteDataManagerExt,
+    WebsiteDataManagerExtManual,
+    WebsiteDataTypes,
+};
 
 impl crate::WebviewExt for Window {
-    #[cfg_attr(feature = "tracing", tracing::instrument)]
-    fn webview_clear_cache(&self) -> BoxFuture<BoxResult<()>> {
+    #[cfg_attr(feature = "tracing", tracing::instrument(
+        skip(self),
+        fields(
+            window.label = self.label(),
+            operation.id = crate::trace::operation_id(),
+            backend = crate::trace::BACKEND
+        )
+    ))]
+    fn webview_clear_cache(&self) -> BoxFuture<Result<()>> {
+        // NOTE: the span of the attribute is only entered while the future is created, so the future
+        // is instrumented with it as well.
         let window = self.clone();
-        async move {
-            let (done_tx, done_rx) = oneshot::channel();
+        let future = async move {
+            crate::read_only::check(&window)?;
+            if crate::dry_run::intercept(&window, || crate::dry_run::DryRunAction::ClearCache)? {
+                return Ok(());
+            }
+            let (done_tx, done_rx) = oneshot::channel::<BoxResult<()>>();
             window.with_webview(move |webview| {
                 let webview = webview.inner();
-                if let Some(context) = webview.context() {
-                    context.clear_cache();
-                }
-                done_tx.send(()).unwrap();
+                let result = match webview.context() {
+                    Some(context) => {
+                        context.clear_cache();
+                        Ok(())
+                    },
+                    None => Err(Error::StoreUnavailable {
+                        reason: "webview has no web context",
+                    }
+                    .into()),
+                };
+                done_tx.send(result).ok();
             })?;
-            done_rx.await?;
-            Ok(())
+            done_rx.await?
         }
-        .boxed()
+        .map(move |result: BoxResult<_>| result.window_context(self, "clear cache"));
+        #[cfg(feature = "tracing")]
+        let future = tracing::Instrument::in_current_span(future);
+        future.boxed()
     }
 
-    #[cfg_attr(feature = "tracing", tracing::instrument)]
-    fn webview_delete_cookies(&self, url: Option<Url>) -> BoxFuture<BoxResult<Vec<Cookie>>> {
-        async move {
+    #[cfg_attr(feature = "tracing", tracing::instrument(
+        skip(self),
+        fields(
+            window.label = self.label(),
+            operation.id = crate::trace::operation_id(),
+            backend = crate::trace::BACKEND
+        )
+    ))]
+    fn webview_delete_cookies(&self, url: Option<Url>) -> BoxFuture<Result<Vec<Cookie>>> {
+        let future = async move {
+            crate::read_only::check(self)?;
+            if let Some(cookies) = crate::dry_run::delete_cookies(self, url.clone()).await? {
+                return Ok(cookies);
+            }
             let mut cookies = vec![];
-            if let Some(cookie_manager) = webview_get_cookie_manager(self).await? {
-                let raw_cookies = webview_get_raw_cookies(self, url).await?;
-                let raw_cookies = raw_cookies.lock()?;
-                let cookie_manager = cookie_manager.lock()?;
-                for mut raw_cookie in raw_cookies.iter().cloned() {
-                    let cancellable = Cancellable::current();
-                    let (done_tx, done_rx) = oneshot::channel();
-                    cookie_manager.delete_cookie(&mut raw_cookie, cancellable.as_ref(), |result| {
-                        done_tx.send(result).unwrap();
-                    });
-                    done_rx.recv()??;
-                    cookies.push(raw_cookie.try_into()?);
-                }
+            let cookie_manager = webview_get_cookie_manager(self).await?;
+            let raw_cookies = webview_get_raw_cookies(self, url).await?;
+            let raw_cookies = raw_cookies.lock()?;
+            let cookie_manager = cookie_manager.lock()?;
+            for mut raw_cookie in raw_cookies.iter().cloned() {
+                // NOTE: the cookie is tagged before it is deleted, so that the change is attributed to
+                // this crate even if it is observed before the deletion completes.
+                let cookie: Cookie = raw_cookie.clone().try_into()?;
+                crate::cookie_watch::tag(self, &cookie);
+                let cancellable = Cancellable::current();
+                let (done_tx, done_rx) = oneshot::channel();
+                cookie_manager.delete_cookie(&mut raw_cookie, cancellable.as_ref(), |result| {
+                    #[cfg(feature = "tracing")]
+                    crate::trace::callback("delete_cookie");
+                    done_tx.send(result).ok();
+                });
+                done_rx.recv()??;
+                cookies.push(cookie);
             }
             Ok(cookies)
         }
-        .boxed()
+        .map(move |result: BoxResult<_>| result.window_context(self, "delete cookies"));
+        #[cfg(feature = "tracing")]
+        let future = tracing::Instrument::in_current_span(future);
+        future.boxed()
     }
 
-    #[cfg_attr(feature = "tracing", tracing::instrument)]
-    fn webview_get_cookies(&self, url: Option<Url>) -> BoxFuture<BoxResult<Vec<Cookie>>> {
-        async move {
+    #[cfg_attr(feature = "tracing", tracing::instrument(
+        skip(self),
+        fields(
+            window.label = self.label(),
+            operation.id = crate::trace::operation_id(),
+            backend = crate::trace::BACKEND
+        )
+    ))]
+    fn webview_get_cookies(&self, url: Option<Url>) -> BoxFuture<Result<Vec<Cookie>>> {
+        let future = async move {
             let cookies = webview_get_raw_cookies(self, url)
                 .await?
                 .lock()?
                 .iter()
                 .cloned()
                 .map(TryInto::try_into)
-                .collect::<BoxResult<Vec<_>>>()?;
+                .collect::<Result<Vec<_>>>()?;
+            #[cfg(feature = "tracing-verbose")]
+            for cookie in &cookies {
+                tracing::trace!(%cookie, "fetched cookie");
+            }
             Ok(cookies)
         }
-        .boxed()
+        .map(move |result: BoxResult<_>| result.window_context(self, "fetch cookies"));
+        #[cfg(feature = "tracing")]
+        let future = tracing::Instrument::in_current_span(future);
+        future.boxed()
     }
 
-    #[cfg_attr(feature = "tracing", tracing::instrument)]
-    fn webview_navigate(&self, url: Url) -> BoxResult<()> {
-        self.with_webview(move |webview| {
-            let webview = webview.inner();
-            webview.load_uri(url.as_str());
-        })?;
-        Ok(())
+    #[cfg_attr(feature = "tracing", tracing::instrument(
+        skip(self),
+        fields(
+            window.label = self.label(),
+            operation.id = crate::trace::operation_id(),
+            backend = crate::trace::BACKEND
+        )
+    ))]
+    fn webview_navigate(&self, url: Url) -> Result<()> {
+        crate::local_url::target(url)
+            .and_then(|target| navigate(self, target))
+            .window_context(self, "navigate")
     }
 }
 
+// NOTE: WebKitGTK loads `data:` and `file:` URLs like any other, and has no read access directory for
+// the document of a file.
+pub(crate) fn navigate(window: &Window, target: NavigationTarget) -> BoxResult<()> {
+    crate::navigation_events::tag(window);
+    let url = target.into_url();
+    window.with_webview(move |webview| {
+        let webview = webview.inner();
+        webview.load_uri(url.as_str());
+    })?;
+    Ok(())
+}
+
 impl TryFrom<soup::Cookie> for Cookie {
-    type Error = BoxError;
-
-    fn try_from(mut cookie: soup::Cookie) -> Result<Self, Self::Error> {
-        let name = cookie.name().map(Into::into);
-        let value = cookie.value().map(Into::into);
-        let domain = cookie.domain().map(Into::into);
-        let path = cookie.path().map(Into::into);
-        let expires = cookie
-            .expires()
-            .and_then(|mut date| {
-                let format = soup::DateFormat::Iso8601Full;
-                date.to_string(format).map(Into::<String>::into)
-            })
-            .map(|s| {
-                let description = time::format_description::well_known::Iso8601::PARSING;
-                time::OffsetDateTime::parse(&s, &description)
-            })
-            .transpose()?;
-        let is_http_only = None;
-        let same_site = None;
-        let is_secure = None;
-        let is_session = None;
-        Ok(Self {
-            name,
-            value,
-            domain,
-            path,
-            expires,
-            is_http_only,
-            same_site,
-            is_secure,
-            is_session,
+    type Error = Error;
+
+    fn try_from(cookie: soup::Cookie) -> Result<Self, Self::Error> {
+        cookie_from_soup(cookie).map_err(Error::CookieConversion)
+    }
+}
+
+fn cookie_from_soup(mut cookie: soup::Cookie) -> BoxResult<Cookie> {
+    let name = cookie.name().map(Into::into).unwrap_or_default();
+    let value = cookie.value().map(Into::into).unwrap_or_default();
+    let domain = cookie.domain().map(Into::into).unwrap_or_default();
+    let path = cookie.path().map(Into::into).unwrap_or_default();
+    let port_list = None;
+    let expires = cookie
+        .expires()
+        .and_then(|mut date| {
+            let format = soup::DateFormat::Iso8601Full;
+            date.to_string(format).map(Into::<String>::into)
+        })
+        .map(|s| {
+            let description = time::format_description::well_known::Iso8601::PARSING;
+            time::OffsetDateTime::parse(&s, &description)
+        })
+        .transpose()?;
+    let http_only = cookie.is_http_only();
+    // NOTE: the same-site policy is only exposed by libsoup 2.70 and later.
+    let same_site = None;
+    let secure = cookie.is_secure();
+    let session = expires.is_none();
+    let comment = None;
+    let comment_url = None;
+    Ok(Cookie {
+        name,
+        value,
+        domain,
+        path,
+        port_list,
+        expires,
+        http_only,
+        same_site,
+        secure,
+        session,
+        comment,
+        comment_url,
+    })
+}
+
+#[cfg(feature = "debug-tools")]
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn raw_cookie_debug(window: &Window) -> BoxResult<Vec<crate::RawCookieDebug>> {
+    let raw_cookies = webview_get_raw_cookies(window, None).await?;
+    let raw_cookies = raw_cookies.lock()?;
+    let cookies = raw_cookies
+        .iter()
+        .cloned()
+        .map(|mut cookie| {
+            let value = cookie.value();
+            let raw = vec![
+                ("name", format!("{:?}", cookie.name())),
+                (
+                    "value",
+                    crate::cookie_debug::redact(value.as_deref().unwrap_or_default()),
+                ),
+                ("domain", format!("{:?}", cookie.domain())),
+                ("path", format!("{:?}", cookie.path())),
+                (
+                    "expires",
+                    format!(
+                        "{:?}",
+                        cookie
+                            .expires()
+                            .map(|mut date| (date.to_string(soup::DateFormat::Iso8601Full), date.to_time_t()))
+                    ),
+                ),
+                ("http_only", cookie.is_http_only().to_string()),
+                ("secure", cookie.is_secure().to_string()),
+            ];
+            crate::RawCookieDebug::new(raw, cookie.try_into())
         })
+        .collect();
+    Ok(cookies)
+}
+
+impl From<&Cookie> for soup::Cookie {
+    fn from(cookie: &Cookie) -> Self {
+        // NOTE: a max age of -1 creates a session cookie, the expiry (if any) is set afterwards.
+        let mut raw_cookie = soup::Cookie::new(&cookie.name, &cookie.value, &cookie.domain, &cookie.path, -1);
+        if let Some(expires) = cookie.expires {
+            raw_cookie.set_expires(&mut soup::Date::from_time_t(expires.unix_timestamp() as _));
+        }
+        raw_cookie.set_http_only(cookie.http_only);
+        raw_cookie.set_secure(cookie.secure);
+        raw_cookie
     }
 }
 
-#[cfg_attr(feature = "tracing", tracing::instrument)]
-async fn webview_get_cookie_manager(window: &Window) -> BoxResult<Option<ApiResult<CookieManager>>> {
-    let (call_tx, call_rx) = oneshot::channel();
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn clear_data(window: &Window, kinds: DataKinds) -> BoxResult<()> {
+    let types = website_data_types(kinds);
+    let (call_tx, call_rx) = oneshot::channel::<BoxResult<()>>();
+    window.with_webview(move |webview| {
+        let webview = webview.inner();
+        match webview.context().and_then(|context| context.website_data_manager()) {
+            Some(website_data_manager) => {
+                let cancellable = Cancellable::current();
+                // NOTE: a time span of zero clears data regardless of when it was modified.
+                website_data_manager.clear(types, glib::TimeSpan(0), cancellable.as_ref(), |result| {
+                    #[cfg(feature = "tracing")]
+                    crate::trace::callback("clear");
+                    call_tx.send(result.map_err(Into::into)).ok();
+                });
+            },
+            None => {
+                let err = Error::StoreUnavailable {
+                    reason: "webview has no website data manager",
+                };
+                call_tx.send(Err(err.into())).ok();
+            },
+        }
+    })?;
+    call_rx.await?
+}
+
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn list_data_records(window: &Window, kinds: DataKinds) -> BoxResult<Vec<DataRecord>> {
+    let (call_tx, call_rx) = oneshot::channel::<BoxResult<Vec<DataRecord>>>();
+    window.with_webview(move |webview| {
+        let webview = webview.inner();
+        match webview.context().and_then(|context| context.website_data_manager()) {
+            Some(website_data_manager) => {
+                let cancellable = Cancellable::current();
+                website_data_manager.fetch(website_data_types(kinds), cancellable.as_ref(), move |result| {
+                    #[cfg(feature = "tracing")]
+                    crate::trace::callback("fetch");
+                    let result = result.map_err(Into::into).map(|website_data| {
+                        website_data
+                            .iter()
+                            .filter_map(|data| {
+                                let name = data.name()?.to_string();
+                                let kinds = data_kinds(data.types()) & kinds;
+                                Some(DataRecord { name, kinds }).filter(|record| !record.kinds.is_empty())
+                            })
+                            .collect()
+                    });
+                    call_tx.send(result).ok();
+                });
+            },
+            None => {
+                let err = Error::StoreUnavailable {
+                    reason: "webview has no website data manager",
+                };
+                call_tx.send(Err(err.into())).ok();
+            },
+        }
+    })?;
+    call_rx.await?
+}
+
+// NOTE: records are matched by name against what the data manager holds now, and each one is removed
+// for its own kinds only.
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn remove_data_records(window: &Window, records: Vec<DataRecord>) -> BoxResult<()> {
+    let kinds = records
+        .iter()
+        .fold(DataKinds::empty(), |kinds, record| kinds | record.kinds);
+    if kinds.is_empty() {
+        return Ok(());
+    }
+    let (call_tx, call_rx) = oneshot::channel::<BoxResult<()>>();
+    window.with_webview(move |webview| {
+        let webview = webview.inner();
+        let website_data_manager = match webview.context().and_then(|context| context.website_data_manager()) {
+            Some(website_data_manager) => website_data_manager,
+            None => {
+                let err = Error::StoreUnavailable {
+                    reason: "webview has no website data manager",
+                };
+                call_tx.send(Err(err.into())).ok();
+                return;
+            },
+        };
+        glib::MainContext::default().spawn_local(async move {
+            let result = async {
+                let website_data = website_data_manager.fetch_future(website_data_types(kinds)).await?;
+                for record in records {
+                    let matching = website_data
+                        .iter()
+                        .filter(|data| data.name().as_deref() == Some(record.name.as_str()))
+                        .collect::<Vec<_>>();
+                    if matching.is_empty() || record.kinds.is_empty() {
+                        continue;
+                    }
+                    let (done_tx, done_rx) = oneshot::channel();
+                    let cancellable = Cancellable::current();
+                    website_data_manager.remove(
+                        website_data_types(record.kinds),
+                        &matching,
+                        cancellable.as_ref(),
+                        move |result| {
+                            #[cfg(feature = "tracing")]
+                            crate::trace::callback("remove");
+                            done_tx.send(result).ok();
+                        },
+                    );
+                    done_rx.await??;
+                }
+                BoxResult::Ok(())
+            }
+            .await;
+            call_tx.send(result).ok();
+        });
+    })?;
+    call_rx.await?
+}
+
+// NOTE: WebKitGTK only knows the size of the disk cache.
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn cache_usage(window: &Window) -> BoxResult<Vec<OriginUsage>> {
+    let (call_tx, call_rx) = oneshot::channel::<BoxResult<Vec<OriginUsage>>>();
     window.with_webview(move |webview| {
         let webview = webview.inner();
-        if let Some(context) = webview.context() {
-            let cookie_manager = context.cookie_manager().map(ApiResult::new);
-            call_tx.send(cookie_manager).unwrap();
+        match webview.context().and_then(|context| context.website_data_manager()) {
+            Some(website_data_manager) => {
+                let cancellable = Cancellable::current();
+                let types = website_data_types(DataKinds::CACHES);
+                website_data_manager.fetch(types, cancellable.as_ref(), move |result| {
+                    let result = result.map_err(Into::into).map(|website_data| {
+                        website_data
+                            .iter()
+                            .filter_map(|data| {
+                                let origin = data.name()?.to_string();
+                                let disk_cache = data.size(WebsiteDataTypes::DISK_CACHE);
+                                Some(OriginUsage {
+                                    origin,
+                                    disk_cache: Some(disk_cache),
+                                    memory_cache: None,
+                                })
+                            })
+                            .collect()
+                    });
+                    call_tx.send(result).ok();
+                });
+            },
+            None => {
+                let err = Error::StoreUnavailable {
+                    reason: "webview has no website data manager",
+                };
+                call_tx.send(Err(err.into())).ok();
+            },
         }
     })?;
+    call_rx.await?
+}
+
+fn website_data_types(kinds: DataKinds) -> WebsiteDataTypes {
+    WEBSITE_DATA_TYPES
+        .iter()
+        .filter(|(kind, _)| kinds.contains(*kind))
+        .fold(WebsiteDataTypes::empty(), |types, (_, data_types)| types | *data_types)
+}
+
+fn data_kinds(types: WebsiteDataTypes) -> DataKinds {
+    WEBSITE_DATA_TYPES
+        .iter()
+        .filter(|(_, data_types)| types.intersects(*data_types))
+        .fold(DataKinds::empty(), |kinds, (kind, _)| kinds | *kind)
+}
+
+const WEBSITE_DATA_TYPES: [(DataKinds, WebsiteDataTypes); 9] = [
+    (DataKinds::DISK_CACHE, WebsiteDataTypes::DISK_CACHE),
+    (DataKinds::MEMORY_CACHE, WebsiteDataTypes::MEMORY_CACHE),
+    (DataKinds::CACHE_STORAGE, WebsiteDataTypes::DOM_CACHE),
+    (DataKinds::COOKIES, WebsiteDataTypes::COOKIES),
+    (DataKinds::INDEXED_DB, WebsiteDataTypes::INDEXEDDB_DATABASES),
+    (DataKinds::LOCAL_STORAGE, WebsiteDataTypes::LOCAL_STORAGE),
+    (DataKinds::SESSION_STORAGE, WebsiteDataTypes::SESSION_STORAGE),
+    (DataKinds::WEB_SQL, WebsiteDataTypes::WEBSQL_DATABASES),
+    (
+        DataKinds::SERVICE_WORKERS,
+        WebsiteDataTypes::SERVICE_WORKER_REGISTRATIONS,
+    ),
+];
+
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn profile_directory(window: &Window) -> BoxResult<Option<PathBuf>> {
+    let (call_tx, call_rx) = oneshot::channel();
+    window.with_webview(move |webview| {
+        let webview = webview.inner();
+        let directory = webview
+            .context()
+            .and_then(|context| context.website_data_manager())
+            .and_then(|manager| manager.base_data_directory())
+            .map(|directory| PathBuf::from(directory.as_str()));
+        call_tx.send(directory).ok();
+    })?;
+    Ok(call_rx.await?)
+}
+
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn profile_info(window: &Window) -> BoxResult<ProfileInfo> {
+    let (call_tx, call_rx) = oneshot::channel();
+    window.with_webview(move |webview| {
+        let webview = webview.inner();
+        let info = if webview.is_ephemeral() {
+            ProfileInfo {
+                name: None,
+                session: SessionKind::Ephemeral,
+                directory: None,
+            }
+        } else {
+            let directory = webview
+                .context()
+                .and_then(|context| context.website_data_manager())
+                .and_then(|manager| manager.base_data_directory())
+                .map(|directory| PathBuf::from(directory.as_str()));
+            ProfileInfo {
+                name: None,
+                session: SessionKind::Persistent,
+                directory,
+            }
+        };
+        call_tx.send(info).ok();
+    })?;
     Ok(call_rx.await?)
 }
 
-#[cfg_attr(feature = "tracing", tracing::instrument)]
+// NOTE: the ids are the addresses of the web context and its website data manager, which stay the
+// same while the webview is alive. WebKitGTK does not report the processes of a web context.
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn engine_context(window: &Window) -> BoxResult<crate::EngineContext> {
+    use glib::ObjectType;
+
+    let (call_tx, call_rx) = oneshot::channel::<BoxResult<crate::EngineContext>>();
+    window.with_webview(move |webview| {
+        let webview = webview.inner();
+        let result = match webview.context() {
+            Some(context) => {
+                let data_store = context
+                    .website_data_manager()
+                    .map(|manager| format!("{:p}", manager.as_ptr()));
+                Ok(crate::EngineContext {
+                    context: format!("{:p}", context.as_ptr()),
+                    data_store,
+                    browser_process_id: None,
+                })
+            },
+            None => Err(Error::StoreUnavailable {
+                reason: "the webview has no web context",
+            }
+            .into()),
+        };
+        call_tx.send(result).ok();
+    })?;
+    call_rx.await?
+}
+
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window, cookie),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn set_cookie(window: &Window, cookie: Cookie) -> BoxResult<()> {
+    crate::read_only::check(window)?;
+    if crate::dry_run::intercept(window, || crate::dry_run::set_cookie_action(&cookie))? {
+        return Ok(());
+    }
+    crate::cookie_watch::tag(window, &cookie);
+    let (call_tx, call_rx) = oneshot::channel::<BoxResult<()>>();
+    window.with_webview(move |webview| {
+        let webview = webview.inner();
+        match webview.context().and_then(|context| context.cookie_manager()) {
+            Some(cookie_manager) => {
+                let mut raw_cookie = soup::Cookie::from(&cookie);
+                let cancellable = Cancellable::current();
+                cookie_manager.add_cookie(&mut raw_cookie, cancellable.as_ref(), |result| {
+                    #[cfg(feature = "tracing")]
+                    crate::trace::callback("add_cookie");
+                    call_tx.send(result.map_err(Into::into)).ok();
+                });
+            },
+            None => {
+                let err = Error::StoreUnavailable {
+                    reason: "webview has no cookie manager",
+                };
+                call_tx.send(Err(err.into())).ok();
+            },
+        }
+    })?;
+    call_rx.await?
+}
+
+// NOTE: libsoup keeps no limit on the number or the lifetime of cookies, and third-party cookies
+// are blocked according to the accept policy of the cookie manager.
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn cookie_policy(window: &Window) -> BoxResult<CookiePolicyInfo> {
+    let (call_tx, call_rx) = oneshot::channel::<BoxResult<CookieAcceptPolicy>>();
+    window.with_webview(move |webview| {
+        let webview = webview.inner();
+        match webview.context().and_then(|context| context.cookie_manager()) {
+            Some(cookie_manager) => {
+                let cancellable = Cancellable::current();
+                cookie_manager.accept_policy(cancellable.as_ref(), move |result| {
+                    call_tx.send(result.map_err(Into::into)).ok();
+                });
+            },
+            None => {
+                let err = Error::StoreUnavailable {
+                    reason: "webview has no cookie manager",
+                };
+                call_tx.send(Err(err.into())).ok();
+            },
+        }
+    })?;
+    let blocks_third_party_cookies = match call_rx.await?? {
+        CookieAcceptPolicy::Always => Some(false),
+        CookieAcceptPolicy::Never | CookieAcceptPolicy::NoThirdParty => Some(true),
+        _ => None,
+    };
+    Ok(CookiePolicyInfo {
+        engine: "webkit2gtk".into(),
+        engine_version: wry::webview::webview_version()?,
+        blocks_third_party_cookies,
+        script_cookie_max_age: None,
+        script_cookie_max_age_probed: false,
+        max_cookies_per_domain: None,
+    })
+}
+
+// NOTE: the cookie manager is shared by every webview of the web context, so changes made by other
+// webviews are reported as well.
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window, observer),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn add_cookie_observer(window: &Window, observer: impl Fn() + Send + Sync + 'static) -> BoxResult<()> {
+    let (call_tx, call_rx) = oneshot::channel::<BoxResult<()>>();
+    window.with_webview(move |webview| {
+        let webview = webview.inner();
+        match webview.context().and_then(|context| context.cookie_manager()) {
+            Some(cookie_manager) => {
+                cookie_manager.connect_changed(move |_cookie_manager| observer());
+                call_tx.send(Ok(())).ok();
+            },
+            None => {
+                let err = Error::StoreUnavailable {
+                    reason: "webview has no cookie manager",
+                };
+                call_tx.send(Err(err.into())).ok();
+            },
+        }
+    })?;
+    call_rx.await?
+}
+
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+async fn webview_get_cookie_manager(window: &Window) -> BoxResult<ApiResult<CookieManager>> {
+    let (call_tx, call_rx) = oneshot::channel();
+    window.with_webview(move |webview| {
+        let webview = webview.inner();
+        let cookie_manager = webview
+            .context()
+            .and_then(|context| context.cookie_manager())
+            .map(ApiResult::new);
+        call_tx.send(cookie_manager).ok();
+    })?;
+    let cookie_manager = call_rx.await?.ok_or(Error::StoreUnavailable {
+        reason: "webview has no cookie manager",
+    })?;
+    Ok(cookie_manager)
+}
+
+// NOTE: at most this many cookie queries are in flight at once when the cookies of every domain are
+// read, so that jars with hundreds of domains are read quickly without flooding the main loop.
+const COOKIE_QUERY_CONCURRENCY: usize = 16;
+
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
 async fn webview_get_raw_cookies(window: &Window, url: Option<Url>) -> BoxResult<ApiResult<Vec<soup::Cookie>>> {
     if let Some(url) = url {
         webview_get_raw_cookies_for_one_urls(window, url).await.map(Into::into)
@@ -130,13 +761,16 @@ async fn webview_get_raw_cookies(window: &Window, url: Option<Url>) -> BoxResult
     }
 }
 
-#[cfg_attr(feature = "tracing", tracing::instrument)]
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
 async fn webview_get_raw_cookies_for_one_urls(window: &Window, url: Url) -> BoxResult<Vec<soup::Cookie>> {
     let (call_tx, call_rx) = oneshot::channel::<ApiResult<_>>();
     window.with_webview(move |webview| {
         let webview = webview.inner();
-        if let Some(context) = webview.context() {
-            if let Some(cookie_manager) = context.cookie_manager() {
+        match webview.context().and_then(|context| context.cookie_manager()) {
+            Some(cookie_manager) => {
                 let url = url.as_str();
                 let cancellable = Cancellable::current();
                 // NOTE: this function appears to not return cookies for some domains reported as
@@ -145,17 +779,31 @@ async fn webview_get_raw_cookies_for_one_urls(window: &Window, url: Url) -> BoxR
                 // webkit2gtk or if something else is going on. Currently this means that getting
                 // all cookies with web2gtk is unreliable compared to the other platforms.
                 cookie_manager.cookies(url, cancellable.as_ref(), |result| {
-                    call_tx.send(result.into()).unwrap();
+                    #[cfg(feature = "tracing")]
+                    crate::trace::callback("cookies");
+                    call_tx.send(Some(result).into()).ok();
                 });
-            }
+            },
+            None => {
+                call_tx.send(None.into()).ok();
+            },
         }
     })?;
-    Ok(call_rx.await?.lock()?.clone()?)
+    let cookies = call_rx.await?.lock()?.clone().ok_or(Error::StoreUnavailable {
+        reason: "webview has no cookie manager",
+    })??;
+    Ok(cookies)
 }
 
-#[cfg_attr(feature = "tracing", tracing::instrument)]
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
 async fn webview_get_raw_cookies_for_all_urls(window: &Window) -> BoxResult<ApiResult<Vec<soup::Cookie>>> {
     use itertools::Itertools;
+    if let Some(cookies) = webview_get_all_raw_cookies(window).await? {
+        return Ok(cookies.into());
+    }
     let urls = webview_get_all_domains_with_cookies(window)
         .await?
         .iter()
@@ -166,35 +814,1826 @@ async fn webview_get_raw_cookies_for_all_urls(window: &Window) -> BoxResult<ApiR
         })
         .flatten_ok()
         .collect::<BoxResult<Vec<_>>>()?;
-    let cookies = ApiResult::new(vec![]);
-    for url in urls {
-        let data = &mut webview_get_raw_cookies_for_one_urls(window, url).await?;
-        cookies.lock()?.append(data);
+    // NOTE: a cookie that is sent over both schemes is returned for both of its URLs, so cookies are
+    // only kept the first time their name, domain, and path are seen.
+    let mut seen = std::collections::BTreeSet::new();
+    let mut cookies = vec![];
+    let mut results = futures::stream::iter(urls)
+        .map(|url| webview_get_raw_cookies_for_one_urls(window, url))
+        .buffer_unordered(COOKIE_QUERY_CONCURRENCY);
+    while let Some(data) = results.next().await {
+        for mut cookie in data? {
+            if seen.insert((cookie.name(), cookie.domain(), cookie.path())) {
+                cookies.push(cookie);
+            }
+        }
     }
-    Ok(cookies)
+    Ok(cookies.into())
+}
+
+// NOTE: `webkit_cookie_manager_get_all_cookies` was added in WebKitGTK 2.42, which is newer than the
+// bindings, so it is looked up when it is called and only used when the installed WebKitGTK has it.
+async fn webview_get_all_raw_cookies(window: &Window) -> BoxResult<Option<Vec<soup::Cookie>>> {
+    use glib::translate::{from_glib_full, FromGlibPtrContainer, ToGlibPtr};
+    use webkit2gtk::{ffi, gio};
+
+    type GetAllCookies = unsafe extern fn(
+        *mut ffi::WebKitCookieManager,
+        *mut gio::ffi::GCancellable,
+        gio::ffi::GAsyncReadyCallback,
+        glib::ffi::gpointer,
+    );
+    type GetAllCookiesFinish = unsafe extern fn(
+        *mut ffi::WebKitCookieManager,
+        *mut gio::ffi::GAsyncResult,
+        *mut *mut glib::ffi::GError,
+    ) -> *mut glib::ffi::GList;
+    type Fetched = (GetAllCookiesFinish, oneshot::Sender<BoxResult<Vec<soup::Cookie>>>);
+
+    unsafe extern fn fetched(
+        manager: *mut glib::gobject_ffi::GObject,
+        result: *mut gio::ffi::GAsyncResult,
+        data: glib::ffi::gpointer,
+    ) {
+        #[cfg(feature = "tracing")]
+        crate::trace::callback("get_all_cookies");
+        let (finish, call_tx) = *Box::from_raw(data as *mut Fetched);
+        let mut error = std::ptr::null_mut();
+        let list = finish(manager as *mut _, result, &mut error);
+        let result = if error.is_null() {
+            Ok(FromGlibPtrContainer::from_glib_full(list))
+        } else {
+            Err(from_glib_full::<_, glib::Error>(error).into())
+        };
+        call_tx.send(result).ok();
+    }
+
+    let (get_all_cookies, finish) = unsafe {
+        let get_all_cookies = libc::dlsym(
+            libc::RTLD_DEFAULT,
+            b"webkit_cookie_manager_get_all_cookies\0".as_ptr().cast(),
+        );
+        let finish = libc::dlsym(
+            libc::RTLD_DEFAULT,
+            b"webkit_cookie_manager_get_all_cookies_finish\0".as_ptr().cast(),
+        );
+        if get_all_cookies.is_null() || finish.is_null() {
+            return Ok(None);
+        }
+        (
+            std::mem::transmute::<_, GetAllCookies>(get_all_cookies),
+            std::mem::transmute::<_, GetAllCookiesFinish>(finish),
+        )
+    };
+    let (call_tx, call_rx) = oneshot::channel::<BoxResult<Vec<soup::Cookie>>>();
+    window.with_webview(move |webview| unsafe {
+        let webview = webview.inner();
+        match webview.context().and_then(|context| context.cookie_manager()) {
+            Some(cookie_manager) => {
+                let data = Box::into_raw(Box::new((finish, call_tx)));
+                get_all_cookies(
+                    cookie_manager.to_glib_none().0,
+                    std::ptr::null_mut(),
+                    Some(fetched),
+                    data as glib::ffi::gpointer,
+                );
+            },
+            None => {
+                let err = Error::StoreUnavailable {
+                    reason: "webview has no cookie manager",
+                };
+                call_tx.send(Err(err.into())).ok();
+            },
+        }
+    })?;
+    Ok(Some(call_rx.await??))
 }
 
 async fn webview_get_all_domains_with_cookies(window: &Window) -> BoxResult<Vec<String>> {
     let (call_tx, call_rx) = oneshot::channel::<ApiResult<_>>();
     window.with_webview(move |webview| {
         let webview = webview.inner();
-        if let Some(context) = webview.context() {
-            if let Some(website_data_manager) = context.website_data_manager() {
+        match webview.context().and_then(|context| context.website_data_manager()) {
+            Some(website_data_manager) => {
                 let types = webkit2gtk::WebsiteDataTypes::COOKIES;
                 let cancellable = Cancellable::current();
                 website_data_manager.fetch(types, cancellable.as_ref(), |result| {
-                    call_tx.send(result.into()).unwrap();
+                    #[cfg(feature = "tracing")]
+                    crate::trace::callback("fetch");
+                    call_tx.send(Some(result).into()).ok();
                 })
-            }
+            },
+            None => {
+                call_tx.send(None.into()).ok();
+            },
         }
     })?;
     let domains = call_rx.await?;
     let domains = match &*domains.lock()? {
-        Ok(domains) => domains
+        Some(Ok(domains)) => domains
             .iter()
             .filter_map(|domain| domain.name().map(Into::into))
             .collect(),
-        Err(err) => return Err(err.clone().into()),
+        Some(Err(err)) => return Err(err.clone().into()),
+        None => {
+            let err = Error::StoreUnavailable {
+                reason: "webview has no website data manager",
+            };
+            return Err(err.into());
+        },
     };
     Ok(domains)
 }
+
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window, source),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn add_user_script(window: &Window, source: String) -> BoxResult<()> {
+    let (call_tx, call_rx) = oneshot::channel();
+    window.with_webview(move |webview| {
+        let webview = webview.inner();
+        let result = webview_get_user_content_manager(&webview).map(|manager| {
+            let frames = UserContentInjectedFrames::TopFrame;
+            let time = UserScriptInjectionTime::Start;
+            let script = UserScript::new(&source, frames, time, &[], &[]);
+            manager.add_script(&script);
+        });
+        call_tx.send(result).ok();
+    })?;
+    call_rx.await?
+}
+
+// NOTE: the result is converted like `JSON.stringify` would, so `undefined` (and anything else that
+// has no JSON representation) is reported as `null`.
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window, script),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn eval_js(window: &Window, script: String) -> BoxResult<serde_json::Value> {
+    use javascriptcore::ValueExt;
+
+    #[cfg(feature = "tracing-verbose")]
+    tracing::trace!(%script, "evaluating script");
+    let (call_tx, call_rx) = oneshot::channel::<BoxResult<Option<String>>>();
+    window.with_webview(move |webview| {
+        let webview = webview.inner();
+        let cancellable = Cancellable::current();
+        webview.run_javascript(&script, cancellable.as_ref(), |result| {
+            #[cfg(feature = "tracing")]
+            crate::trace::callback("run_javascript");
+            let result = result
+                .map(|result| result.js_value().and_then(|value| value.to_json(0)).map(Into::into))
+                .map_err(|err| Error::Script(err.to_string()).into());
+            call_tx.send(result).ok();
+        });
+    })?;
+    match call_rx.await?? {
+        Some(json) => Ok(serde_json::from_str(&json)?),
+        None => Ok(serde_json::Value::Null),
+    }
+}
+
+// NOTE: webkit2gtk only calls async functions itself from 2.40, so the body is run through the
+// bridge instead.
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window, body),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn eval_async_js(window: &Window, body: String) -> BoxResult<serde_json::Value> {
+    #[cfg(feature = "tracing-verbose")]
+    tracing::trace!(%body, "evaluating script");
+    bridge::call(window, &body).await
+}
+
+// NOTE: downloads are only accessed from `with_webview` on the main thread, the handle is merely
+// kept in the window state until the download ends.
+#[derive(Clone)]
+pub(crate) struct NativeDownload(webkit2gtk::Download);
+
+unsafe impl Send for NativeDownload {
+}
+
+unsafe impl Sync for NativeDownload {
+}
+
+// NOTE: user scripts are only accessed from `with_webview` on the main thread, the handle is merely
+// kept in the window state until the script is removed again.
+pub(crate) struct UserScriptHandle(UserScript);
+
+unsafe impl Send for UserScriptHandle {
+}
+
+unsafe impl Sync for UserScriptHandle {
+}
+
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window, source),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn add_init_script(window: &Window, source: String, all_frames: bool) -> BoxResult<UserScriptHandle> {
+    let (call_tx, call_rx) = oneshot::channel();
+    window.with_webview(move |webview| {
+        let webview = webview.inner();
+        let result = webview_get_user_content_manager(&webview).map(|manager| {
+            let frames = if all_frames {
+                UserContentInjectedFrames::AllFrames
+            } else {
+                UserContentInjectedFrames::TopFrame
+            };
+            let time = UserScriptInjectionTime::Start;
+            let script = UserScript::new(&source, frames, time, &[], &[]);
+            manager.add_script(&script);
+            UserScriptHandle(script)
+        });
+        call_tx.send(result).ok();
+    })?;
+    call_rx.await?
+}
+
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window, script),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn add_scoped_user_script(window: &Window, script: crate::UserScript) -> BoxResult<UserScriptHandle> {
+    let (call_tx, call_rx) = oneshot::channel();
+    window.with_webview(move |webview| {
+        let webview = webview.inner();
+        let result = webview_get_user_content_manager(&webview).map(|manager| {
+            let frames = if script.main_frame_only {
+                UserContentInjectedFrames::TopFrame
+            } else {
+                UserContentInjectedFrames::AllFrames
+            };
+            let time = match script.injection_time {
+                InjectionTime::DocumentStart => UserScriptInjectionTime::Start,
+                InjectionTime::DocumentEnd => UserScriptInjectionTime::End,
+            };
+            let source = &script.source;
+            let script = match &script.isolated_world {
+                Some(world) => UserScript::for_world(source, frames, time, world, &[], &[]),
+                None => UserScript::new(source, frames, time, &[], &[]),
+            };
+            manager.add_script(&script);
+            UserScriptHandle(script)
+        });
+        call_tx.send(result).ok();
+    })?;
+    call_rx.await?
+}
+
+// NOTE: user scripts can only be removed one by one since WebKitGTK 2.32, so removing them needs the
+// `webkit2gtk-v2_32` feature. Without the feature (and its newer APIs) WebKitGTK 2.22 is enough.
+#[cfg(feature = "webkit2gtk-v2_32")]
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window, handle),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn remove_init_script(window: &Window, handle: UserScriptHandle) -> BoxResult<()> {
+    let (call_tx, call_rx) = oneshot::channel();
+    window.with_webview(move |webview| {
+        let webview = webview.inner();
+        let result = webview_get_user_content_manager(&webview).map(|manager| {
+            manager.remove_script(&handle.0);
+        });
+        call_tx.send(result).ok();
+    })?;
+    call_rx.await?
+}
+
+#[cfg(not(feature = "webkit2gtk-v2_32"))]
+#[allow(unused_variables)]
+pub(crate) async fn remove_init_script(window: &Window, handle: UserScriptHandle) -> BoxResult<()> {
+    let error = "removing user scripts requires WebKitGTK 2.32 and the webkit2gtk-v2_32 feature";
+    Err(Error::UnsupportedPlatform(error).into())
+}
+
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window, handler),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn add_script_message_handler(
+    window: &Window,
+    name: &'static str,
+    handler: impl Fn(ScriptMessage) + Send + Sync + 'static,
+) -> BoxResult<()> {
+    use glib::ObjectExt;
+
+    let (call_tx, call_rx) = oneshot::channel();
+    window.with_webview(move |webview| {
+        let webview = webview.inner();
+        let result = webview_get_user_content_manager(&webview).and_then(|manager| {
+            let webview = webview.downgrade();
+            // NOTE: webkit2gtk does not report the frame a message was posted from, and the bridge
+            // script is only added to the main frame, so messages are taken to be from the main frame.
+            manager.connect_script_message_received(Some(name), move |_manager, result| {
+                if let Some(value) = result.js_value() {
+                    let url = webview
+                        .upgrade()
+                        .and_then(|webview| webview.uri())
+                        .and_then(|uri| Url::parse(&uri).ok());
+                    handler(ScriptMessage {
+                        body: value.to_string(),
+                        main_frame: true,
+                        url,
+                    });
+                }
+            });
+            if manager.register_script_message_handler(name) {
+                Ok(())
+            } else {
+                let msg = format!(r#"failed to register script message handler "{name}""#);
+                Err(msg.into())
+            }
+        });
+        call_tx.send(result).ok();
+    })?;
+    call_rx.await?
+}
+
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window, handler),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn add_load_error_handler(
+    window: &Window,
+    handler: impl Fn(LoadError) -> bool + Send + Sync + 'static,
+) -> BoxResult<()> {
+    window.with_webview(move |webview| {
+        let webview = webview.inner();
+        let handler = std::rc::Rc::new(handler);
+        // NOTE: certificate failures are reported through `load-failed` as well (after
+        // `load-failed-with-tls-errors` goes unhandled), so that signal is not connected here.
+        // NOTE: returning `true` from the handler suppresses the default error page.
+        webview.connect_load_failed({
+            let handler = handler.clone();
+            move |_webview, _event, uri, error| handler(load_error_from_glib(uri, error))
+        });
+        // NOTE: webkit2gtk does not treat HTTP error responses as failed loads, so the status of
+        // the main resource is checked once the load finishes.
+        webview.connect_load_changed(move |webview, event| {
+            if event == LoadEvent::Finished {
+                let status = webview
+                    .main_resource()
+                    .and_then(|resource| resource.response())
+                    .map(|response| response.status_code())
+                    .unwrap_or_default();
+                if status >= 400 {
+                    let uri = webview.uri();
+                    let kind = LoadErrorKind::Http(status as u16);
+                    handler(LoadError::new(uri.as_deref(), kind, status.into(), kind.to_string()));
+                }
+            }
+        });
+    })?;
+    Ok(())
+}
+
+// NOTE: without a user agent, webkit2gtk goes back to its default one.
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn set_user_agent(window: &Window, user_agent: Option<String>) -> BoxResult<()> {
+    window.with_webview(move |webview| {
+        let webview = webview.inner();
+        if let Some(settings) = webview.settings() {
+            settings.set_user_agent(user_agent.as_deref());
+        }
+    })?;
+    Ok(())
+}
+
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn user_agent(window: &Window) -> BoxResult<String> {
+    let (call_tx, call_rx) = oneshot::channel();
+    window.with_webview(move |webview| {
+        let webview = webview.inner();
+        let user_agent = webview.settings().and_then(|settings| settings.user_agent());
+        call_tx.send(user_agent.map(|user_agent| user_agent.to_string())).ok();
+    })?;
+    call_rx
+        .await?
+        .ok_or_else(|| Error::PlatformApi("webview has no settings".into()).into())
+}
+
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn set_zoom(window: &Window, factor: f64) -> BoxResult<()> {
+    window.with_webview(move |webview| {
+        let webview = webview.inner();
+        webview.set_zoom_level(factor);
+    })?;
+    Ok(())
+}
+
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn zoom(window: &Window) -> BoxResult<f64> {
+    let (call_tx, call_rx) = oneshot::channel();
+    window.with_webview(move |webview| {
+        let webview = webview.inner();
+        call_tx.send(webview.zoom_level()).ok();
+    })?;
+    Ok(call_rx.await?)
+}
+
+// NOTE: the policy is decided for main frame navigations before their request is sent, which is
+// also when the user agent is switched. The user agent the webview had when this was called is
+// restored for navigations without one.
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window, policy),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn add_navigation_policy(
+    window: &Window,
+    policy: impl Fn(&str) -> NavigationDecision + Send + Sync + 'static,
+) -> BoxResult<()> {
+    use glib::Cast;
+
+    window.with_webview(move |webview| {
+        let webview = webview.inner();
+        let default = webview.settings().and_then(|settings| settings.user_agent());
+        webview.connect_decide_policy(move |webview, decision, decision_type| {
+            if decision_type != PolicyDecisionType::NavigationAction {
+                return false;
+            }
+            let uri = decision
+                .downcast_ref::<NavigationPolicyDecision>()
+                .filter(|decision| decision.frame_name().is_none())
+                .and_then(|decision| decision.navigation_action())
+                .and_then(|action| action.request())
+                .and_then(|request| request.uri());
+            let uri = match uri {
+                Some(uri) => uri,
+                None => return false,
+            };
+            let NavigationDecision { policy, user_agent } = policy(&uri);
+            match policy {
+                NavigationPolicy::Allow => {
+                    if let Some(settings) = webview.settings() {
+                        let user_agent = user_agent.or_else(|| default.as_ref().map(ToString::to_string));
+                        settings.set_user_agent(user_agent.as_deref());
+                    }
+                    false
+                },
+                NavigationPolicy::Redirect(url) => {
+                    decision.ignore();
+                    webview.load_uri(&url);
+                    true
+                },
+                NavigationPolicy::Cancel => {
+                    decision.ignore();
+                    true
+                },
+            }
+        });
+    })?;
+    Ok(())
+}
+
+// NOTE: `load-changed` reports `Finished` after failed loads as well, so it is only reported as
+// finished when `load-failed` was not raised since the load started.
+// NOTE: downloads are shared by the web context, so they are filtered by their webview.
+// webkit2gtk before 2.40 decides the destination while `decide-destination` is emitted, so downloads
+// are saved to the downloads directory right away and their destination can not be set.
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window, observer),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn add_download_observer(
+    window: &Window,
+    observer: impl Fn(DownloadUpdate) + Send + Sync + 'static,
+) -> BoxResult<()> {
+    use glib::ObjectExt;
+    use std::{cell::Cell, rc::Rc};
+
+    let (call_tx, call_rx) = oneshot::channel::<BoxResult<()>>();
+    window.with_webview(move |webview| {
+        let webview = webview.inner();
+        let result = match webview.context() {
+            Some(context) => {
+                let observer = Rc::new(observer);
+                let webview = webview.downgrade();
+                context.connect_download_started(move |_context, download| {
+                    if download.web_view() != webview.upgrade() {
+                        return;
+                    }
+                    let id = downloads::next_id();
+                    let failed = Rc::new(Cell::new(false));
+                    download.connect_decide_destination({
+                        let observer = observer.clone();
+                        move |download, suggested_filename| {
+                            let url = download
+                                .request()
+                                .and_then(|request| request.uri())
+                                .and_then(|uri| Url::parse(&uri).ok());
+                            if let Some(url) = url {
+                                let (decision_tx, _) = oneshot::channel();
+                                observer(DownloadUpdate::Started {
+                                    id,
+                                    download: NativeDownload(download.clone()),
+                                    url,
+                                    suggested_filename: (!suggested_filename.is_empty())
+                                        .then(|| suggested_filename.into()),
+                                    decision: decision_tx,
+                                });
+                            }
+                            false
+                        }
+                    });
+                    download.connect_received_data({
+                        let observer = observer.clone();
+                        move |download, _length| {
+                            let total_bytes = download
+                                .response()
+                                .map(|response| response.content_length())
+                                .filter(|length| *length > 0);
+                            observer(DownloadUpdate::Event(DownloadEvent::Progress {
+                                id,
+                                received_bytes: download.received_data_length(),
+                                total_bytes,
+                            }));
+                        }
+                    });
+                    download.connect_failed({
+                        let observer = observer.clone();
+                        let failed = failed.clone();
+                        move |_download, error| {
+                            failed.set(true);
+                            let event = match error.kind::<DownloadError>() {
+                                Some(DownloadError::CancelledByUser) => DownloadEvent::Cancelled { id },
+                                _ => DownloadEvent::Failed {
+                                    id,
+                                    reason: error.message().into(),
+                                },
+                            };
+                            observer(DownloadUpdate::Event(event));
+                        }
+                    });
+                    download.connect_finished({
+                        let observer = observer.clone();
+                        move |download| {
+                            if failed.get() {
+                                return;
+                            }
+                            let destination = download
+                                .destination()
+                                .and_then(|uri| Url::parse(&uri).ok())
+                                .and_then(|url| url.to_file_path().ok());
+                            observer(DownloadUpdate::Event(DownloadEvent::Finished { id, destination }));
+                        }
+                    });
+                });
+                Ok(())
+            },
+            None => Err(Error::PlatformApi("webview has no web context".into()).into()),
+        };
+        call_tx.send(result).ok();
+    })?;
+    call_rx.await?
+}
+
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window, download),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn cancel_download(window: &Window, download: NativeDownload) -> BoxResult<()> {
+    window.with_webview(move |_webview| {
+        download.0.cancel();
+    })?;
+    Ok(())
+}
+
+#[allow(unused_variables)]
+pub(crate) async fn pause_download(window: &Window, download: NativeDownload) -> BoxResult<()> {
+    Err(Error::UnsupportedPlatform("pausing downloads is not supported with webkit2gtk").into())
+}
+
+#[allow(unused_variables)]
+pub(crate) async fn resume_download(window: &Window, download: NativeDownload) -> BoxResult<()> {
+    Err(Error::UnsupportedPlatform("resuming downloads is not supported with webkit2gtk").into())
+}
+
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window, observer),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn add_navigation_observer(
+    window: &Window,
+    observer: impl Fn(NavigationEvent) + Send + Sync + 'static,
+) -> BoxResult<()> {
+    use std::{cell::Cell, rc::Rc};
+
+    window.with_webview(move |webview| {
+        let webview = webview.inner();
+        let failed = Rc::new(Cell::new(false));
+        webview.connect_load_failed({
+            let failed = failed.clone();
+            move |_webview, _event, _uri, _error| {
+                failed.set(true);
+                false
+            }
+        });
+        webview.connect_load_changed(move |webview, event| {
+            let url = match webview.uri().and_then(|uri| Url::parse(&uri).ok()) {
+                Some(url) => url,
+                None => return,
+            };
+            match event {
+                LoadEvent::Started => {
+                    failed.set(false);
+                    observer(NavigationEvent::Started { url });
+                },
+                LoadEvent::Committed => observer(NavigationEvent::Committed { url }),
+                LoadEvent::Finished if !failed.get() => observer(NavigationEvent::Finished { url }),
+                _ => {},
+            }
+        });
+    })?;
+    Ok(())
+}
+
+// NOTE: web processes that were terminated through the API were not crashed, so they are ignored.
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window, handler),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn add_process_crash_handler(
+    window: &Window,
+    handler: impl Fn() + Send + Sync + 'static,
+) -> BoxResult<()> {
+    window.with_webview(move |webview| {
+        let webview = webview.inner();
+        webview.connect_web_process_terminated(move |_webview, reason| {
+            if reason != WebProcessTerminationReason::TerminatedByApi {
+                handler();
+            }
+        });
+    })?;
+    Ok(())
+}
+
+// NOTE: webkit2gtk has no prompt of its own, so requests that are left to it are denied.
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window, handler),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn add_permission_handler(
+    window: &Window,
+    handler: impl Fn(PermissionRequest) -> PermissionDecision + Send + Sync + 'static,
+) -> BoxResult<()> {
+    use glib::Cast;
+    use webkit2gtk::{
+        GeolocationPermissionRequest,
+        NotificationPermissionRequest,
+        PermissionRequestExt,
+        UserMediaPermissionRequest,
+        UserMediaPermissionRequestExt,
+    };
+
+    window.with_webview(move |webview| {
+        let webview = webview.inner();
+        webview.connect_permission_request(move |webview, request| {
+            let kind = if let Some(request) = request.downcast_ref::<UserMediaPermissionRequest>() {
+                match (request.is_for_video_device(), request.is_for_audio_device()) {
+                    (true, true) => PermissionKind::CameraAndMicrophone,
+                    (true, false) => PermissionKind::Camera,
+                    (false, true) => PermissionKind::Microphone,
+                    (false, false) => PermissionKind::Other,
+                }
+            } else if request.is::<GeolocationPermissionRequest>() {
+                PermissionKind::Geolocation
+            } else if request.is::<NotificationPermissionRequest>() {
+                PermissionKind::Notifications
+            } else {
+                PermissionKind::Other
+            };
+            let origin = webview.uri().and_then(|uri| crate::permission::origin(&uri));
+            match handler(PermissionRequest { kind, origin }) {
+                PermissionDecision::Default => false,
+                PermissionDecision::Allow => {
+                    request.allow();
+                    true
+                },
+                PermissionDecision::Deny => {
+                    request.deny();
+                    true
+                },
+            }
+        });
+    })?;
+    Ok(())
+}
+
+// NOTE: an accepted certificate is allowed for the host, and the failed load is started again.
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window, handler),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn add_certificate_error_handler(
+    window: &Window,
+    handler: impl Fn(CertificateErrorInfo) -> CertDecision + Send + Sync + 'static,
+) -> BoxResult<()> {
+    use webkit2gtk::gio::TlsCertificateFlags;
+
+    window.with_webview(move |webview| {
+        let webview = webview.inner();
+        webview.connect_load_failed_with_tls_errors(move |webview, failing_uri, certificate, errors| {
+            let url = match Url::parse(failing_uri) {
+                Ok(url) => url,
+                Err(_) => return false,
+            };
+            let kind = if errors.intersects(TlsCertificateFlags::EXPIRED | TlsCertificateFlags::NOT_ACTIVATED) {
+                CertificateErrorKind::Expired
+            } else if errors.contains(TlsCertificateFlags::UNKNOWN_CA) {
+                CertificateErrorKind::UnknownAuthority
+            } else if errors.contains(TlsCertificateFlags::BAD_IDENTITY) {
+                CertificateErrorKind::HostMismatch
+            } else if errors.contains(TlsCertificateFlags::REVOKED) {
+                CertificateErrorKind::Revoked
+            } else if errors.intersects(TlsCertificateFlags::INSECURE | TlsCertificateFlags::GENERIC_ERROR) {
+                CertificateErrorKind::Invalid
+            } else {
+                CertificateErrorKind::Other
+            };
+            let host = url.host_str().map(String::from);
+            match (handler(CertificateErrorInfo { url, kind }), host, webview.context()) {
+                (CertDecision::Proceed, Some(host), Some(context)) => {
+                    context.allow_tls_certificate_for_host(certificate, &host);
+                    webview.load_uri(failing_uri);
+                    true
+                },
+                _ => false,
+            }
+        });
+    })?;
+    Ok(())
+}
+
+// NOTE: proxy challenges are left to the credentials of the proxy configuration, when it has them.
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window, handler),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn add_auth_handler(window: &Window, handler: AuthHandler) -> BoxResult<()> {
+    use glib::translate::{ToGlibPtr, ToGlibPtrMut};
+
+    let proxy_credentials = state::window_state(window)?.proxy_credentials.clone();
+    window.with_webview(move |webview| {
+        let webview = webview.inner();
+        webview.connect_authenticate(move |_webview, request| {
+            let has_proxy_credentials = proxy_credentials
+                .lock()
+                .map_or(false, |credentials| credentials.is_some());
+            if request.is_for_proxy() && has_proxy_credentials {
+                return false;
+            }
+            let challenge = AuthChallenge {
+                url: None,
+                host: request.host().map(String::from).unwrap_or_default(),
+                port: request.port() as u16,
+                realm: request.realm().map(String::from).filter(|realm| !realm.is_empty()),
+                is_proxy: request.is_for_proxy(),
+            };
+            let credentials = tauri::async_runtime::spawn(handler(challenge));
+            let request = request.clone();
+            glib::MainContext::default().spawn_local(async move {
+                let mut credential = credentials
+                    .await
+                    .ok()
+                    .flatten()
+                    .map(|Credentials { username, password }| {
+                        Credential::new(&username, &password, CredentialPersistence::ForSession)
+                    });
+                // NOTE: without a credential, the request goes on without authentication.
+                let credential_ptr = match &mut credential {
+                    Some(credential) => credential.to_glib_none_mut().0,
+                    None => std::ptr::null_mut(),
+                };
+                unsafe {
+                    webkit2gtk::ffi::webkit_authentication_request_authenticate(
+                        request.to_glib_none().0,
+                        credential_ptr,
+                    );
+                }
+            });
+            true
+        });
+    })?;
+    Ok(())
+}
+
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window, html),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn load_html(window: &Window, html: String, base_url: Option<Url>) -> BoxResult<()> {
+    window.with_webview(move |webview| {
+        let webview = webview.inner();
+        webview.load_html(&html, base_url.as_ref().map(Url::as_str));
+    })?;
+    Ok(())
+}
+
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn reload(window: &Window, ignore_cache: bool) -> BoxResult<()> {
+    window.with_webview(move |webview| {
+        let webview = webview.inner();
+        if ignore_cache {
+            webview.reload_bypass_cache();
+        } else {
+            webview.reload();
+        }
+    })?;
+    Ok(())
+}
+
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn stop_loading(window: &Window) -> BoxResult<()> {
+    window.with_webview(move |webview| {
+        let webview = webview.inner();
+        webview.stop_loading();
+    })?;
+    Ok(())
+}
+
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn open_devtools(window: &Window) -> BoxResult<()> {
+    let (call_tx, call_rx) = oneshot::channel::<BoxResult<()>>();
+    window.with_webview(move |webview| {
+        let webview = webview.inner();
+        let enabled = webview
+            .settings()
+            .map_or(false, |settings| settings.enables_developer_extras());
+        let result = match webview.inspector() {
+            Some(inspector) if enabled => {
+                inspector.show();
+                Ok(())
+            },
+            Some(_) => Err(Error::Other("devtools are disabled for the webview".into()).into()),
+            None => Err(Error::PlatformApi("webview has no inspector".into()).into()),
+        };
+        call_tx.send(result).ok();
+    })?;
+    call_rx.await?
+}
+
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn close_devtools(window: &Window) -> BoxResult<()> {
+    window.with_webview(move |webview| {
+        let webview = webview.inner();
+        if let Some(inspector) = webview.inspector() {
+            inspector.close();
+        }
+    })?;
+    Ok(())
+}
+
+// NOTE: disabling the developer extras also closes the inspector, which can not be opened again
+// until they are enabled.
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn set_devtools_enabled(window: &Window, enabled: bool) -> BoxResult<()> {
+    window.with_webview(move |webview| {
+        let webview = webview.inner();
+        if let Some(settings) = webview.settings() {
+            settings.set_enable_developer_extras(enabled);
+        }
+        if !enabled {
+            if let Some(inspector) = webview.inspector() {
+                inspector.close();
+            }
+        }
+    })?;
+    Ok(())
+}
+
+#[cfg(feature = "webkit2gtk-v2_32")]
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn set_muted(window: &Window, muted: bool) -> BoxResult<()> {
+    window.with_webview(move |webview| {
+        let webview = webview.inner();
+        webview.set_is_muted(muted);
+    })?;
+    Ok(())
+}
+
+#[cfg(not(feature = "webkit2gtk-v2_32"))]
+#[allow(unused_variables)]
+pub(crate) async fn set_muted(window: &Window, muted: bool) -> BoxResult<()> {
+    Err(Error::UnsupportedPlatform("muting requires WebKitGTK 2.30 and the webkit2gtk-v2_32 feature").into())
+}
+
+#[cfg(feature = "webkit2gtk-v2_32")]
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn is_muted(window: &Window) -> BoxResult<bool> {
+    let (call_tx, call_rx) = oneshot::channel();
+    window.with_webview(move |webview| {
+        let webview = webview.inner();
+        call_tx.send(webview.is_muted()).ok();
+    })?;
+    Ok(call_rx.await?)
+}
+
+#[cfg(not(feature = "webkit2gtk-v2_32"))]
+#[allow(unused_variables)]
+pub(crate) async fn is_muted(window: &Window) -> BoxResult<bool> {
+    Err(Error::UnsupportedPlatform("muting requires WebKitGTK 2.30 and the webkit2gtk-v2_32 feature").into())
+}
+
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn is_playing_audio(window: &Window) -> BoxResult<bool> {
+    let (call_tx, call_rx) = oneshot::channel();
+    window.with_webview(move |webview| {
+        let webview = webview.inner();
+        call_tx.send(webview.is_playing_audio()).ok();
+    })?;
+    Ok(call_rx.await?)
+}
+
+// NOTE: webkit2gtk has no API for emulating devices, so they are emulated by resizing the window and
+// in the page.
+#[allow(unused_variables)]
+pub(crate) async fn emulate_device(window: &Window, profile: Option<&DeviceProfile>) -> BoxResult<bool> {
+    Ok(false)
+}
+
+// NOTE: webkit2gtk has no API for emulating media, so it is emulated in the page.
+#[allow(unused_variables)]
+pub(crate) async fn emulate_media(window: &Window, media: Option<MediaType>) -> BoxResult<bool> {
+    Ok(false)
+}
+
+// NOTE: webkit2gtk follows the dark variant of the GTK theme, which is preferred for the whole
+// application, so the scheme applies to every webview.
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn set_color_scheme(window: &Window, scheme: ColorScheme) -> BoxResult<()> {
+    window.with_webview(move |_webview| {
+        if let Some(settings) = gtk::Settings::default() {
+            match scheme {
+                ColorScheme::Auto => settings.reset_property("gtk-application-prefer-dark-theme"),
+                ColorScheme::Light => settings.set_gtk_application_prefer_dark_theme(false),
+                ColorScheme::Dark => settings.set_gtk_application_prefer_dark_theme(true),
+            }
+        }
+    })?;
+    Ok(())
+}
+
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn go_back(window: &Window) -> BoxResult<()> {
+    window.with_webview(move |webview| {
+        let webview = webview.inner();
+        webview.go_back();
+    })?;
+    Ok(())
+}
+
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn go_forward(window: &Window) -> BoxResult<()> {
+    window.with_webview(move |webview| {
+        let webview = webview.inner();
+        webview.go_forward();
+    })?;
+    Ok(())
+}
+
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn can_go_back(window: &Window) -> BoxResult<bool> {
+    let (call_tx, call_rx) = oneshot::channel();
+    window.with_webview(move |webview| {
+        let webview = webview.inner();
+        call_tx.send(webview.can_go_back()).ok();
+    })?;
+    Ok(call_rx.await?)
+}
+
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn can_go_forward(window: &Window) -> BoxResult<bool> {
+    let (call_tx, call_rx) = oneshot::channel();
+    window.with_webview(move |webview| {
+        let webview = webview.inner();
+        call_tx.send(webview.can_go_forward()).ok();
+    })?;
+    Ok(call_rx.await?)
+}
+
+// NOTE: items are looked up by their offset from the current item, since the order of the back and
+// forward lists is not documented.
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn back_forward_list(window: &Window) -> BoxResult<Vec<HistoryEntry>> {
+    let (call_tx, call_rx) = oneshot::channel();
+    window.with_webview(move |webview| {
+        let webview = webview.inner();
+        let mut entries = vec![];
+        if let Some(list) = webview.back_forward_list() {
+            let back = list.back_list().len() as i32;
+            let forward = list.forward_list().len() as i32;
+            for index in -back ..= forward {
+                let item = match list.nth_item(index) {
+                    Some(item) => item,
+                    None => continue,
+                };
+                if let Some(url) = item.uri().and_then(|uri| Url::parse(&uri).ok()) {
+                    let title = item.title().map(String::from).filter(|title| !title.is_empty());
+                    entries.push(HistoryEntry {
+                        url,
+                        title,
+                        current: index == 0,
+                    });
+                }
+            }
+        }
+        call_tx.send(entries).ok();
+    })?;
+    Ok(call_rx.await?)
+}
+
+fn load_error_from_glib(uri: &str, error: &webkit2gtk::Error) -> LoadError {
+    use webkit2gtk::gio::{ResolverError, TlsError};
+    // NOTE: webkit2gtk reports libsoup transport failures using the soup status code as the error
+    // code in this domain.
+    const SOUP_HTTP_ERROR: &str = "soup_http_error_quark";
+    const SOUP_STATUS_CANCELLED: i32 = 1;
+    const SOUP_STATUS_CANT_RESOLVE: i32 = 2;
+    const SOUP_STATUS_CANT_RESOLVE_PROXY: i32 = 3;
+    const SOUP_STATUS_SSL_FAILED: i32 = 6;
+    let code = unsafe { (*error.as_ptr()).code };
+    let kind = if let Some(error) = error.kind::<NetworkError>() {
+        match error {
+            NetworkError::Cancelled => LoadErrorKind::Cancelled,
+            _ => LoadErrorKind::Network,
+        }
+    } else if let Some(error) = error.kind::<PolicyError>() {
+        match error {
+            // NOTE: reported whenever a navigation turns into a download or is cancelled by a
+            // navigation policy, so it is not a failure of the load.
+            PolicyError::FrameLoadInterruptedByPolicyChange => LoadErrorKind::Cancelled,
+            _ => LoadErrorKind::BlockedByPolicy,
+        }
+    } else if error.is::<ResolverError>() {
+        LoadErrorKind::DnsFailure
+    } else if error.is::<TlsError>() {
+        LoadErrorKind::Tls
+    } else if error.domain().as_str() == SOUP_HTTP_ERROR {
+        match code {
+            SOUP_STATUS_CANCELLED => LoadErrorKind::Cancelled,
+            SOUP_STATUS_CANT_RESOLVE | SOUP_STATUS_CANT_RESOLVE_PROXY => LoadErrorKind::DnsFailure,
+            SOUP_STATUS_SSL_FAILED => LoadErrorKind::Tls,
+            _ => LoadErrorKind::Network,
+        }
+    } else {
+        LoadErrorKind::Unknown
+    };
+    LoadError::new(Some(uri), kind, code.into(), error.message())
+}
+
+fn webview_get_user_content_manager(webview: &webkit2gtk::WebView) -> BoxResult<UserContentManager> {
+    webview
+        .user_content_manager()
+        .ok_or_else(|| Error::PlatformApi("webview has no user content manager".into()).into())
+}
+
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn set_blocked_urls(window: &Window, patterns: Vec<String>) -> BoxResult<()> {
+    let rules = if patterns.is_empty() {
+        None
+    } else {
+        Some(crate::url_filter::content_rules(&patterns)?)
+    };
+    set_content_filter(window, crate::url_filter::CONTENT_FILTER_ID, rules).await
+}
+
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window, rules),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn set_content_rules(window: &Window, rules: Arc<ContentRuleList>) -> BoxResult<()> {
+    let rules = (!rules.is_empty()).then(|| rules.as_json().to_string());
+    set_content_filter(window, crate::content_rules::CONTENT_RULES_ID, rules).await
+}
+
+// NOTE: the content filter is compiled into a directory below the system temporary directory, since
+// the compiled form is only a cache and is replaced whenever the rules change.
+#[cfg(feature = "webkit2gtk-v2_32")]
+async fn set_content_filter(window: &Window, identifier: &'static str, rules: Option<String>) -> BoxResult<()> {
+    use glib::translate::{from_glib_full, ToGlibPtr};
+    use webkit2gtk::ffi;
+
+    type Saved = (UserContentManager, oneshot::Sender<BoxResult<()>>);
+
+    unsafe extern fn saved(
+        store: *mut glib::gobject_ffi::GObject,
+        result: *mut webkit2gtk::gio::ffi::GAsyncResult,
+        data: glib::ffi::gpointer,
+    ) {
+        let (manager, call_tx) = *Box::from_raw(data as *mut Saved);
+        let mut error = std::ptr::null_mut();
+        let filter = ffi::webkit_user_content_filter_store_save_finish(store as *mut _, result, &mut error);
+        let result = if error.is_null() {
+            ffi::webkit_user_content_manager_add_filter(manager.to_glib_none().0, filter);
+            ffi::webkit_user_content_filter_unref(filter);
+            Ok(())
+        } else {
+            Err(from_glib_full::<_, glib::Error>(error).into())
+        };
+        call_tx.send(result).ok();
+    }
+
+    let directory = std::env::temp_dir().join("tauri-webview-util").join("content-filters");
+    let directory = directory
+        .to_str()
+        .ok_or("temporary directory is not valid UTF-8")?
+        .to_string();
+    let (call_tx, call_rx) = oneshot::channel::<BoxResult<()>>();
+    window.with_webview(move |webview| unsafe {
+        let webview = webview.inner();
+        let manager = match webview_get_user_content_manager(&webview) {
+            Ok(manager) => manager,
+            Err(err) => {
+                call_tx.send(Err(err)).ok();
+                return;
+            },
+        };
+        manager.remove_filter_by_id(identifier);
+        match rules {
+            Some(rules) => {
+                let store = ffi::webkit_user_content_filter_store_new(directory.to_glib_none().0);
+                let bytes = glib::Bytes::from_owned(rules.into_bytes());
+                let data = Box::into_raw(Box::new((manager, call_tx)));
+                ffi::webkit_user_content_filter_store_save(
+                    store,
+                    identifier.to_glib_none().0,
+                    bytes.to_glib_none().0 as *mut _,
+                    std::ptr::null_mut(),
+                    Some(saved),
+                    data as glib::ffi::gpointer,
+                );
+                glib::gobject_ffi::g_object_unref(store as *mut _);
+            },
+            None => {
+                call_tx.send(Ok(())).ok();
+            },
+        }
+    })?;
+    call_rx.await?
+}
+
+#[cfg(not(feature = "webkit2gtk-v2_32"))]
+#[allow(unused_variables)]
+async fn set_content_filter(window: &Window, identifier: &'static str, rules: Option<String>) -> BoxResult<()> {
+    let error = "content filters require WebKitGTK 2.26 and the webkit2gtk-v2_32 feature";
+    Err(Error::UnsupportedPlatform(error).into())
+}
+
+// NOTE: responses for custom schemes can not carry a status or headers before WebKitGTK 2.36, so
+// ranges are always answered with the whole file, and errors finish the request with an error.
+// Responses that are not ready right away are awaited on the async runtime, and the request is
+// finished from the main loop afterwards.
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window, handler),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn serve_scheme(window: &Window, scheme: String, handler: SchemeHandler) -> BoxResult<()> {
+    let (call_tx, call_rx) = oneshot::channel::<BoxResult<()>>();
+    window.with_webview(move |webview| {
+        let webview = webview.inner();
+        let result = match webview.context() {
+            Some(context) => {
+                context.register_uri_scheme(&scheme, move |request| {
+                    let uri = request.uri().map(|uri| uri.to_string()).unwrap_or_default();
+                    let mut response = handler(SchemeRequest {
+                        uri: uri.clone(),
+                        method: "GET".into(),
+                        ..Default::default()
+                    });
+                    if let Some(response) = (&mut response).now_or_never() {
+                        finish_scheme_request(request, &uri, response);
+                        return;
+                    }
+                    let request = request.clone();
+                    let response = tauri::async_runtime::spawn(response);
+                    glib::MainContext::default().spawn_local(async move {
+                        let response = response.await.unwrap_or_else(|_| SchemeResponse::new(500));
+                        finish_scheme_request(&request, &uri, response);
+                    });
+                });
+                Ok(())
+            },
+            None => Err(Error::PlatformApi("webview has no web context".into()).into()),
+        };
+        call_tx.send(result).ok();
+    })?;
+    call_rx.await?
+}
+
+// NOTE: tauri gives every window a web context of its own, so the preloaded context can not be used
+// by a window itself. Loading a page in it loads and initializes WebKit, and brings its helper
+// programs into the page cache, which is most of what makes the first window slow. The context is
+// ephemeral, so that it does not lock the data directory of a window.
+#[allow(unused_variables)]
+pub(crate) fn preload_engine(
+    data_directory: Option<PathBuf>,
+    config: &crate::PreloadConfig,
+    timings: &mut crate::PreloadTimings,
+) -> BoxResult<()> {
+    use std::{cell::Cell, rc::Rc};
+
+    const LOAD_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(5);
+
+    let context = timings.time("create web context", webkit2gtk::WebContext::new_ephemeral);
+    let webview = timings.time("create webview", || webkit2gtk::WebView::with_context(&context));
+    let loaded = Rc::new(Cell::new(false));
+    webview.connect_load_changed({
+        let loaded = loaded.clone();
+        move |_, event| {
+            if event == LoadEvent::Finished {
+                loaded.set(true);
+            }
+        }
+    });
+    let timed_out = Rc::new(Cell::new(false));
+    let timeout = glib::timeout_add_local_once(LOAD_TIMEOUT, {
+        let timed_out = timed_out.clone();
+        move || timed_out.set(true)
+    });
+    timings.time("load blank page", || {
+        webview.load_html("", None);
+        let main_context = glib::MainContext::default();
+        while !loaded.get() && !timed_out.get() {
+            main_context.iteration(true);
+        }
+    });
+    if timed_out.get() {
+        return Err(Error::Timeout.into());
+    }
+    timeout.remove();
+    Ok(())
+}
+
+// NOTE: streamed bodies are written to a pipe, whose other end WebKit reads the response from. The
+// writes block once the pipe is full, so the body is only produced as fast as WebKit reads it.
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window, handler),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn serve_scheme_stream(
+    window: &Window,
+    scheme: String,
+    handler: SchemeStreamHandler,
+) -> BoxResult<()> {
+    let (call_tx, call_rx) = oneshot::channel::<BoxResult<()>>();
+    window.with_webview(move |webview| {
+        let webview = webview.inner();
+        let result = match webview.context() {
+            Some(context) => {
+                context.register_uri_scheme(&scheme, move |request| {
+                    let uri = request.uri().map(|uri| uri.to_string()).unwrap_or_default();
+                    let response = tauri::async_runtime::spawn(handler(SchemeRequest {
+                        uri: uri.clone(),
+                        method: "GET".into(),
+                        ..Default::default()
+                    }));
+                    let request = request.clone();
+                    glib::MainContext::default().spawn_local(async move {
+                        match response.await {
+                            Ok(response) => finish_scheme_stream(&request, &uri, response),
+                            Err(_) => finish_scheme_request(&request, &uri, SchemeResponse::new(500)),
+                        }
+                    });
+                });
+                Ok(())
+            },
+            None => Err(Error::PlatformApi("webview has no web context".into()).into()),
+        };
+        call_tx.send(result).ok();
+    })?;
+    call_rx.await?
+}
+
+fn finish_scheme_stream(request: &URISchemeRequest, uri: &str, response: SchemeStreamResponse) {
+    if response.status >= 400 {
+        let response = SchemeResponse {
+            status: response.status,
+            headers: response.headers,
+            body: vec![],
+        };
+        return finish_scheme_request(request, uri, response);
+    }
+    let length = response
+        .content_length()
+        .and_then(|length| i64::try_from(length).ok())
+        .unwrap_or(-1);
+    let content_type = response.content_type().map(String::from);
+    match body_pipe(response) {
+        Ok(stream) => request.finish(&stream, length, content_type.as_deref()),
+        Err(err) => {
+            let message = format!("failed to stream `{uri}`: {err}");
+            request.finish_error(&mut glib::Error::new(NetworkError::Failed, &message));
+        },
+    }
+}
+
+fn body_pipe(response: SchemeStreamResponse) -> BoxResult<UnixInputStream> {
+    use std::{
+        fs::File,
+        io::Write,
+        os::fd::{FromRawFd, OwnedFd},
+    };
+
+    let mut fds = [0; 2];
+    if unsafe { libc::pipe2(fds.as_mut_ptr(), libc::O_CLOEXEC) } != 0 {
+        return Err(std::io::Error::last_os_error().into());
+    }
+    let (reader, mut writer) = unsafe { (OwnedFd::from_raw_fd(fds[0]), File::from_raw_fd(fds[1])) };
+    crate::scheme_handler::pump_body(response.body, move |chunk| writer.write_all(&chunk).is_ok());
+    Ok(unsafe { UnixInputStream::take_fd(reader) })
+}
+
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn map_virtual_host(
+    window: &Window,
+    host: String,
+    folder: PathBuf,
+    cors: CorsPolicy,
+) -> BoxResult<Url> {
+    let options = SchemeOptions {
+        secure: true,
+        cors,
+        ..Default::default()
+    };
+    crate::scheme_handler::set_options(window, host.clone(), options).await?;
+    let handler = crate::serve_directory::handler(folder, Default::default());
+    let handler = crate::scheme_handler::with_options(window, host.clone(), handler);
+    serve_scheme(window, host.clone(), handler).await?;
+    Ok(Url::parse(&format!("{host}://{host}/"))?)
+}
+
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn set_scheme_security(
+    window: &Window,
+    scheme: String,
+    secure: bool,
+    cors: CorsPolicy,
+) -> BoxResult<()> {
+    let (call_tx, call_rx) = oneshot::channel::<BoxResult<()>>();
+    window.with_webview(move |webview| {
+        let webview = webview.inner();
+        let result = match webview.context().and_then(|context| context.security_manager()) {
+            Some(security_manager) => {
+                if secure {
+                    security_manager.register_uri_scheme_as_secure(&scheme);
+                }
+                if cors == CorsPolicy::Allow {
+                    security_manager.register_uri_scheme_as_cors_enabled(&scheme);
+                }
+                Ok(())
+            },
+            None => Err(Error::PlatformApi("webview has no security manager".into()).into()),
+        };
+        call_tx.send(result).ok();
+    })?;
+    call_rx.await?
+}
+
+fn finish_scheme_request(request: &URISchemeRequest, uri: &str, response: SchemeResponse) {
+    if response.status >= 400 {
+        let kind = match response.status {
+            404 => NetworkError::FileDoesNotExist,
+            _ => NetworkError::Failed,
+        };
+        let message = format!("failed to load `{uri}` with status {}", response.status);
+        request.finish_error(&mut glib::Error::new(kind, &message));
+    } else {
+        let length = response.body.len() as i64;
+        let content_type = response.content_type().map(String::from);
+        let stream = MemoryInputStream::from_bytes(&glib::Bytes::from_owned(response.body));
+        request.finish(&stream, length, content_type.as_deref());
+    }
+}
+
+// NOTE: webkit2gtk has no PDF viewer of its own before 2.40, and none of its settings are exposed.
+#[allow(unused_variables)]
+pub(crate) async fn set_pdf_settings(window: &Window, settings: PdfViewerSettings) -> BoxResult<()> {
+    if settings == PdfViewerSettings::default() {
+        Ok(())
+    } else {
+        Err(Error::UnsupportedPlatform("pdf viewer settings are not supported with webkit2gtk").into())
+    }
+}
+
+// NOTE: the print dialog is run without a parent, since the GTK window of a tauri window can only
+// be looked up through the event loop, which is blocked while the dialog is running.
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn print(window: &Window) -> BoxResult<bool> {
+    use glib::translate::ToGlibPtr;
+
+    let (call_tx, call_rx) = oneshot::channel();
+    window.with_webview(move |webview| unsafe {
+        let webview = webview.inner();
+        let operation = PrintOperation::new(&webview);
+        webkit2gtk::ffi::webkit_print_operation_run_dialog(operation.to_glib_none().0, std::ptr::null_mut());
+        call_tx.send(true).ok();
+    })?;
+    Ok(call_rx.await?)
+}
+
+// NOTE: the page is printed with the "Print to File" printer of GTK. Backgrounds are a setting of
+// the webview rather than of the print operation, so the setting is kept afterwards.
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn print_to_pdf(window: &Window, path: PathBuf, options: PdfOptions) -> BoxResult<()> {
+    use gtk::{PageOrientation, PageSetup, PaperSize, PrintSettings, Unit};
+    use std::{cell::RefCell, rc::Rc};
+
+    let uri = Url::from_file_path(&path)
+        .map_err(|()| Error::Other(format!("`{}` is not an absolute path", path.display()).into()))?;
+    let (call_tx, call_rx) = oneshot::channel::<BoxResult<()>>();
+    window.with_webview(move |webview| {
+        let webview = webview.inner();
+        if let Some(settings) = webview.settings() {
+            settings.set_print_backgrounds(options.print_background);
+        }
+        let orientation = if options.landscape {
+            PageOrientation::Landscape
+        } else {
+            PageOrientation::Portrait
+        };
+        let print_settings = PrintSettings::new();
+        print_settings.set("printer", Some("Print to File"));
+        print_settings.set("output-file-format", Some("pdf"));
+        print_settings.set("output-uri", Some(uri.as_str()));
+        print_settings.set_orientation(orientation);
+        print_settings.set_scale(options.scale * 100.0);
+        let paper_size = PaperSize::new_custom("custom", "custom", options.page_width, options.page_height, Unit::Inch);
+        let page_setup = PageSetup::new();
+        page_setup.set_paper_size(&paper_size);
+        page_setup.set_orientation(orientation);
+        page_setup.set_top_margin(options.margin_top, Unit::Inch);
+        page_setup.set_bottom_margin(options.margin_bottom, Unit::Inch);
+        page_setup.set_left_margin(options.margin_left, Unit::Inch);
+        page_setup.set_right_margin(options.margin_right, Unit::Inch);
+        let operation = PrintOperation::new(&webview);
+        operation.set_print_settings(&print_settings);
+        operation.set_page_setup(&page_setup);
+        // NOTE: `failed` is emitted before `finished`, so whichever is emitted first is reported.
+        let call_tx = Rc::new(RefCell::new(Some(call_tx)));
+        operation.connect_failed({
+            let call_tx = call_tx.clone();
+            move |_operation, error| {
+                if let Some(call_tx) = call_tx.borrow_mut().take() {
+                    call_tx
+                        .send(Err(Error::PlatformApi(error.to_string().into()).into()))
+                        .ok();
+                }
+            }
+        });
+        operation.connect_finished(move |_operation| {
+            if let Some(call_tx) = call_tx.borrow_mut().take() {
+                call_tx.send(Ok(())).ok();
+            }
+        });
+        operation.print();
+    })?;
+    call_rx.await?
+}
+
+// NOTE: snapshots are encoded with gdk-pixbuf, which supports both formats.
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn capture_screenshot(window: &Window, options: ScreenshotOptions) -> BoxResult<Vec<u8>> {
+    use webkit2gtk::{SnapshotOptions, SnapshotRegion};
+
+    let (call_tx, call_rx) = oneshot::channel::<BoxResult<Vec<u8>>>();
+    window.with_webview(move |webview| {
+        let webview = webview.inner();
+        let region = match options.region {
+            ScreenshotRegion::Viewport => SnapshotRegion::Visible,
+            ScreenshotRegion::FullPage => SnapshotRegion::FullDocument,
+        };
+        let cancellable = Cancellable::current();
+        webview.snapshot(region, SnapshotOptions::NONE, cancellable.as_ref(), move |result| {
+            let result = result
+                .map_err(|err| Error::PlatformApi(err.to_string().into()).into())
+                .and_then(|surface| encode_snapshot(surface, options.format));
+            call_tx.send(result).ok();
+        });
+    })?;
+    call_rx.await?
+}
+
+// NOTE: the visible part of the element is cropped from a snapshot of the viewport, which is scaled
+// to device pixels.
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn capture_clip(window: &Window, clip: ClipRect, format: ScreenshotFormat) -> BoxResult<Vec<u8>> {
+    use webkit2gtk::{SnapshotOptions, SnapshotRegion};
+
+    let clip = clip.visible();
+    if clip.width <= 0.0 || clip.height <= 0.0 {
+        return Err(Error::Script("the element is not within the viewport".into()).into());
+    }
+    let (call_tx, call_rx) = oneshot::channel::<BoxResult<Vec<u8>>>();
+    window.with_webview(move |webview| {
+        let webview = webview.inner();
+        let cancellable = Cancellable::current();
+        let region = SnapshotRegion::Visible;
+        webview.snapshot(region, SnapshotOptions::NONE, cancellable.as_ref(), move |result| {
+            let result = result
+                .map_err(|err| Error::PlatformApi(err.to_string().into()).into())
+                .and_then(|surface| crop_snapshot(surface, clip))
+                .and_then(|surface| encode_snapshot(surface, format));
+            call_tx.send(result).ok();
+        });
+    })?;
+    call_rx.await?
+}
+
+fn crop_snapshot(surface: gtk::cairo::Surface, clip: ClipRect) -> BoxResult<gtk::cairo::Surface> {
+    let surface = gtk::cairo::ImageSurface::try_from(surface)
+        .map_err(|_| Error::PlatformApi("snapshot is not an image surface".into()))?;
+    let scale = f64::from(surface.width()) / clip.viewport_width;
+    let width = (clip.width * scale).round().max(1.0) as i32;
+    let height = (clip.height * scale).round().max(1.0) as i32;
+    let cropped = gtk::cairo::ImageSurface::create(gtk::cairo::Format::ARgb32, width, height)?;
+    let context = gtk::cairo::Context::new(&cropped)?;
+    context.set_source_surface(&surface, -(clip.x * scale).round(), -(clip.y * scale).round())?;
+    context.paint()?;
+    drop(context);
+    Ok((*cropped).clone())
+}
+
+fn encode_snapshot(surface: gtk::cairo::Surface, format: ScreenshotFormat) -> BoxResult<Vec<u8>> {
+    let surface = gtk::cairo::ImageSurface::try_from(surface)
+        .map_err(|_| Error::PlatformApi("snapshot is not an image surface".into()))?;
+    let pixbuf = gtk::gdk::pixbuf_get_from_surface(&surface, 0, 0, surface.width(), surface.height())
+        .ok_or_else(|| Error::PlatformApi("failed to read snapshot".into()))?;
+    let format = match format {
+        ScreenshotFormat::Png => "png",
+        ScreenshotFormat::Jpeg => "jpeg",
+    };
+    let bytes = pixbuf
+        .save_to_bufferv(format, &[])
+        .map_err(|err| Error::PlatformApi(err.to_string().into()))?;
+    Ok(bytes)
+}
+
+// NOTE: speech goes through speech-dispatcher with its `spd-say` client, which returns once the
+// text is queued. Cancelling stops whatever the client is currently saying.
+#[allow(unused_variables)]
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window, text),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn speak(window: &Window, text: String) -> BoxResult<()> {
+    spd_say(&["--cancel"])?;
+    spd_say(&["--", &text])
+}
+
+#[allow(unused_variables)]
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn stop_speaking(window: &Window) -> BoxResult<()> {
+    spd_say(&["--cancel"])
+}
+
+fn spd_say(args: &[&str]) -> BoxResult<()> {
+    let status = std::process::Command::new("spd-say").args(args).status()?;
+    if !status.success() {
+        return Err(format!("spd-say exited with {status}").into());
+    }
+    Ok(())
+}
+
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn disable_page_cache(window: &Window) -> BoxResult<bool> {
+    window.with_webview(move |webview| {
+        let webview = webview.inner();
+        if let Some(settings) = webview.settings() {
+            settings.set_enable_page_cache(false);
+        }
+    })?;
+    Ok(true)
+}
+
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn disable_hardware_acceleration(window: &Window) -> BoxResult<bool> {
+    let (call_tx, call_rx) = oneshot::channel();
+    window.with_webview(move |webview| {
+        let webview = webview.inner();
+        let settings = webview.settings();
+        if let Some(settings) = &settings {
+            settings.set_hardware_acceleration_policy(HardwareAccelerationPolicy::Never);
+        }
+        call_tx.send(settings.is_some()).ok();
+    })?;
+    Ok(call_rx.await?)
+}
+
+// NOTE: webkit2gtk has no setting for bypassing service workers. Their registrations can be removed with
+// `DataKinds::SERVICE_WORKERS` instead.
+#[allow(unused_variables)]
+pub(crate) async fn set_bypass_service_worker(window: &Window, bypass: bool) -> BoxResult<()> {
+    if bypass {
+        Err(Error::UnsupportedPlatform("bypassing service workers is not supported with webkit2gtk").into())
+    } else {
+        Ok(())
+    }
+}
+
+// NOTE: the proxy settings belong to the web context, so they apply to every webview that shares
+// it. Credentials are answered from the window state whenever the proxy asks for them.
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window, config),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn set_proxy(window: &Window, config: ProxyConfig) -> BoxResult<()> {
+    use glib::translate::{ToGlibPtr, ToGlibPtrMut};
+
+    let state = state::window_state(window)?;
+    *state.proxy_credentials.lock()? = config.credentials.clone();
+    let install = bridge::install_once(&state, "proxy-authentication").await?;
+    let credentials = install.is_some().then(|| state.proxy_credentials.clone());
+    let (call_tx, call_rx) = oneshot::channel();
+    window.with_webview(move |webview| {
+        let webview = webview.inner();
+        let context = match webview.context() {
+            Some(context) => context,
+            None => {
+                call_tx.send(false).ok();
+                return;
+            },
+        };
+        let uri = config.uri();
+        let bypass = config.bypass.iter().map(String::as_str).collect::<Vec<_>>();
+        let mut settings = NetworkProxySettings::new(Some(&uri), &bypass);
+        context.set_network_proxy_settings(NetworkProxyMode::Custom, Some(&mut settings));
+        if let Some(credentials) = credentials {
+            webview.connect_authenticate(move |_webview, request| {
+                if !request.is_for_proxy() {
+                    return false;
+                }
+                let credentials = match credentials.lock() {
+                    Ok(credentials) => credentials.clone(),
+                    Err(_) => None,
+                };
+                match credentials {
+                    Some(ProxyCredentials { username, password }) => unsafe {
+                        let mut credential = Credential::new(&username, &password, CredentialPersistence::ForSession);
+                        webkit2gtk::ffi::webkit_authentication_request_authenticate(
+                            request.to_glib_none().0,
+                            credential.to_glib_none_mut().0,
+                        );
+                        true
+                    },
+                    None => false,
+                }
+            });
+        }
+        call_tx.send(true).ok();
+    })?;
+    if !call_rx.await? {
+        return Err(Error::PlatformApi("webview has no web context".into()).into());
+    }
+    if let Some(install) = install {
+        install.done();
+    }
+    Ok(())
+}
+
+// NOTE: webkit2gtk has no API for observing WebSocket frames, so they are observed in the page.
+#[allow(unused_variables)]
+pub(crate) async fn add_websocket_observer(
+    window: &Window,
+    observer: impl Fn(WsEvent) + Send + Sync + 'static,
+) -> BoxResult<bool> {
+    Ok(false)
+}
+
+// NOTE: webkit2gtk has no screencast, so recordings are made of snapshots instead.
+#[allow(unused_variables)]
+pub(crate) async fn add_screencast_observer(
+    window: &Window,
+    observer: impl Fn(Vec<u8>) + Send + Sync + 'static,
+) -> BoxResult<bool> {
+    Ok(false)
+}
+
+#[allow(unused_variables)]
+pub(crate) async fn start_screencast(window: &Window, format: ScreenshotFormat, every_nth_frame: u32) -> BoxResult<()> {
+    Err(Error::UnsupportedPlatform("screencasts are not supported with webkit2gtk").into())
+}
+
+#[allow(unused_variables)]
+pub(crate) async fn stop_screencast(window: &Window) -> BoxResult<()> {
+    Ok(())
+}
+
+// NOTE: certificate transparency failures are not reported separately by WebKitGTK, they surface
+// as TLS load errors instead.
+#[allow(unused_variables)]
+pub(crate) async fn add_certificate_transparency_handler(
+    window: &Window,
+    handler: impl Fn(Option<String>) + Send + Sync + 'static,
+) -> BoxResult<()> {
+    Ok(())
+}
+
+// NOTE: `path` is a directory of web process extension modules. They are loaded by web processes
+// launched after this call, so extensions should be loaded before the first navigation of the
+// window (or be followed by a navigation that starts a new web process).
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn load_extension(window: &Window, path: PathBuf) -> BoxResult<()> {
+    let directory = path.to_str().ok_or("extension path is not valid UTF-8")?.to_string();
+    let (call_tx, call_rx) = oneshot::channel::<BoxResult<()>>();
+    window.with_webview(move |webview| {
+        let webview = webview.inner();
+        let result = match webview.context() {
+            Some(context) => {
+                context.set_web_extensions_directory(&directory);
+                Ok(())
+            },
+            None => Err(Error::PlatformApi("webview has no web context".into()).into()),
+        };
+        call_tx.send(result).ok();
+    })?;
+    call_rx.await?
+}
@@ -0,0 +1,29 @@
+bitflags::bitflags! {
+    // NOTE: kinds without a counterpart in an engine are ignored by it. WebKit keeps no history,
+    // autofill, or password data for a webview, and WebView2 can not clear the memory cache,
+    // session storage, or service workers on their own.
+    #[derive(Default)]
+    pub struct DataKinds: u32 {
+        const DISK_CACHE = 1 << 0;
+        const MEMORY_CACHE = 1 << 1;
+        const CACHE_STORAGE = 1 << 2;
+        const COOKIES = 1 << 3;
+        const INDEXED_DB = 1 << 4;
+        const LOCAL_STORAGE = 1 << 5;
+        const SESSION_STORAGE = 1 << 6;
+        const WEB_SQL = 1 << 7;
+        const SERVICE_WORKERS = 1 << 8;
+        const HISTORY = 1 << 9;
+        const AUTOFILL = 1 << 10;
+        const PASSWORDS = 1 << 11;
+        const CACHES = Self::DISK_CACHE.bits | Self::MEMORY_CACHE.bits | Self::CACHE_STORAGE.bits;
+    }
+}
+
+// NOTE: the name is what the engine groups the data under, which is a domain with WebKit and an
+// origin with WebView2, and the kinds are those of the requested kinds that it holds data for.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct DataRecord {
+    pub name: String,
+    pub kinds: DataKinds,
+}
@@ -0,0 +1,100 @@
+use crate::{bridge, init_script, platform, state, stream, BoxResult, Result};
+use futures::stream::BoxStream;
+use serde::{Deserialize, Serialize};
+use tauri::Window;
+use url::Url;
+
+const WEBSOCKET_SCRIPT: &str = include_str!("js/websocket.js");
+
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum WsDirection {
+    Sent,
+    Received,
+}
+
+// NOTE: binary payloads are captured as base64.
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct WsEvent {
+    pub url: Url,
+    pub direction: WsDirection,
+    pub opcode: u8,
+    pub size: u64,
+    pub payload: Option<String>,
+}
+
+#[derive(Default)]
+pub(crate) struct WebSocketState {
+    subscribers: Vec<(bool, stream::Sender<WsEvent>)>,
+    capture_payloads: bool,
+    native: bool,
+}
+
+// NOTE: frames are observed through the DevTools protocol where the engine supports it, which also
+// covers sockets of workers. Otherwise `WebSocket` is wrapped in the top frame of every document,
+// so only sockets the document opens itself are observed. Payloads are only captured once a
+// subscriber asks for them, and are left out for the other subscribers.
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn events(window: &Window, capture_payloads: bool) -> BoxResult<BoxStream<'static, Result<WsEvent>>> {
+    let state = state::window_state(window)?;
+    let (event_tx, event_rx) = stream::channel(stream::stream_config());
+    let capture_changed = {
+        let mut websocket = state.websocket.lock()?;
+        websocket.subscribers.push((capture_payloads, event_tx));
+        let changed = capture_payloads && !websocket.capture_payloads;
+        websocket.capture_payloads |= capture_payloads;
+        changed
+    };
+    let install = bridge::install_once(&state, "websocket").await?;
+    let installed = install.is_some();
+    if let Some(install) = install {
+        let native = platform::add_websocket_observer(window, {
+            let window = window.clone();
+            move |event| publish(&window, event)
+        })
+        .await?;
+        state.websocket.lock()?.native = native;
+        if !native {
+            bridge::on_message(window, "websocket-frame", {
+                let window = window.clone();
+                move |payload| match serde_json::from_value::<WsEvent>(payload) {
+                    Ok(event) => publish(&window, event),
+                    Err(err) => crate::report_error(window.label(), "malformed websocket frame", err),
+                }
+            })
+            .await?;
+        }
+        install.done();
+    }
+    let (native, capture_payloads) = {
+        let websocket = state.websocket.lock()?;
+        (websocket.native, websocket.capture_payloads)
+    };
+    if !native && (installed || capture_changed) {
+        let config = serde_json::json!({ "capturePayloads": capture_payloads });
+        let source = format!("({WEBSOCKET_SCRIPT})({config});");
+        init_script::replace(window, &state.websocket_script, source).await?;
+    }
+    Ok(event_rx)
+}
+
+fn publish(window: &Window, event: WsEvent) {
+    if let Ok(state) = state::window_state(window) {
+        if let Ok(mut websocket) = state.websocket.lock() {
+            websocket.subscribers.retain(|(capture_payloads, subscriber)| {
+                let event = if *capture_payloads {
+                    event.clone()
+                } else {
+                    WsEvent {
+                        payload: None,
+                        ..event.clone()
+                    }
+                };
+                subscriber.send(event).is_ok()
+            });
+        }
+    }
+}
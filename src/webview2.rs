@@ -1,23 +1,120 @@
-use crate::{ApiResult, BoxError, BoxResult, Cookie};
+use crate::{
+    auth::AuthHandler,
+    bridge::{self, ScriptMessage},
+    content_rules::{self, ResourceType},
+    downloads::{self, DownloadDecision, DownloadUpdate},
+    error::WindowContext,
+    local_url::NavigationTarget,
+    navigation_policy::{NavigationDecision, NavigationPolicy},
+    scheme_handler::{
+        Handler,
+        SchemeHandler,
+        SchemeRequest,
+        SchemeResponse,
+        SchemeStreamHandler,
+        SchemeStreamResponse,
+    },
+    screenshot::ClipRect,
+    state,
+    url_filter,
+    ApiResult,
+    AuthChallenge,
+    BoxError,
+    BoxResult,
+    CertDecision,
+    CertificateErrorInfo,
+    CertificateErrorKind,
+    ColorScheme,
+    ContentRuleList,
+    Cookie,
+    CookiePolicyInfo,
+    CorsPolicy,
+    Credentials,
+    DataKinds,
+    DataRecord,
+    DeviceProfile,
+    DownloadEvent,
+    Error,
+    HistoryEntry,
+    InjectionTime,
+    LoadError,
+    LoadErrorKind,
+    MediaType,
+    NavigationEvent,
+    OriginUsage,
+    PdfOptions,
+    PdfViewerSettings,
+    PermissionDecision,
+    PermissionKind,
+    PermissionRequest,
+    ProfileInfo,
+    ProxyConfig,
+    Result,
+    ScreenshotFormat,
+    ScreenshotOptions,
+    ScreenshotRegion,
+    SessionKind,
+    UserScript,
+    WsDirection,
+    WsEvent,
+};
 use futures::{future::BoxFuture, prelude::*};
-use tauri::{window::PlatformWebview, Window};
+use std::{
+    cell::RefCell,
+    collections::{BTreeMap, HashMap},
+    path::{Path, PathBuf},
+    rc::Rc,
+    sync::{mpsc, Arc, Mutex},
+};
+use tauri::{http::status::StatusCode, window::PlatformWebview, Runtime, Window, WindowBuilder};
 use url::Url;
 use webview2_com::{
+    AddScriptToExecuteOnDocumentCreatedCompletedHandler,
+    BasicAuthenticationRequestedEventHandler,
+    BytesReceivedChangedEventHandler,
+    CallDevToolsProtocolMethodCompletedHandler,
     ClearBrowsingDataCompletedHandler,
+    ContentLoadingEventHandler,
+    CoreWebView2EnvironmentOptions,
+    CreateCoreWebView2EnvironmentCompletedHandler,
+    DevToolsProtocolEventReceivedEventHandler,
+    DownloadStartingEventHandler,
     Error::WindowsError,
+    ExecuteScriptCompletedHandler,
     GetCookiesCompletedHandler,
     Microsoft::Web::WebView2::Win32::{
+        CreateCoreWebView2EnvironmentWithOptions,
+        ICoreWebView2,
         ICoreWebView2Cookie,
         ICoreWebView2CookieList,
         ICoreWebView2CookieManager,
+        ICoreWebView2DownloadOperation,
+        ICoreWebView2Environment,
+        ICoreWebView2Environment6,
+        ICoreWebView2Environment7,
+        ICoreWebView2EnvironmentOptions,
+        ICoreWebView2NavigationCompletedEventArgs,
+        ICoreWebView2NavigationCompletedEventArgs2,
         ICoreWebView2Profile2,
+        ICoreWebView2Settings2,
+        ICoreWebView2Settings7,
+        ICoreWebView2WebResourceRequest,
+        ICoreWebView2WebResourceRequestedEventArgs,
+        ICoreWebView2_10,
         ICoreWebView2_13,
+        ICoreWebView2_14,
         ICoreWebView2_2,
+        ICoreWebView2_3,
+        ICoreWebView2_4,
+        ICoreWebView2_7,
+        ICoreWebView2_8,
+        COREWEBVIEW2_BROWSING_DATA_KINDS,
         COREWEBVIEW2_BROWSING_DATA_KINDS_ALL_DOM_STORAGE,
         COREWEBVIEW2_BROWSING_DATA_KINDS_ALL_PROFILE,
         COREWEBVIEW2_BROWSING_DATA_KINDS_ALL_SITE,
         COREWEBVIEW2_BROWSING_DATA_KINDS_BROWSING_HISTORY,
         COREWEBVIEW2_BROWSING_DATA_KINDS_CACHE_STORAGE,
+        COREWEBVIEW2_BROWSING_DATA_KINDS_COOKIES,
         COREWEBVIEW2_BROWSING_DATA_KINDS_DISK_CACHE,
         COREWEBVIEW2_BROWSING_DATA_KINDS_DOWNLOAD_HISTORY,
         COREWEBVIEW2_BROWSING_DATA_KINDS_FILE_SYSTEMS,
@@ -31,16 +128,248 @@ use webview2_com::{
         COREWEBVIEW2_COOKIE_SAME_SITE_KIND_LAX,
         COREWEBVIEW2_COOKIE_SAME_SITE_KIND_NONE,
         COREWEBVIEW2_COOKIE_SAME_SITE_KIND_STRICT,
+        COREWEBVIEW2_DOWNLOAD_INTERRUPT_REASON,
+        COREWEBVIEW2_DOWNLOAD_INTERRUPT_REASON_USER_CANCELED,
+        COREWEBVIEW2_DOWNLOAD_INTERRUPT_REASON_USER_PAUSED,
+        COREWEBVIEW2_DOWNLOAD_STATE,
+        COREWEBVIEW2_DOWNLOAD_STATE_COMPLETED,
+        COREWEBVIEW2_DOWNLOAD_STATE_INTERRUPTED,
+        COREWEBVIEW2_HOST_RESOURCE_ACCESS_KIND_ALLOW,
+        COREWEBVIEW2_HOST_RESOURCE_ACCESS_KIND_DENY,
+        COREWEBVIEW2_HOST_RESOURCE_ACCESS_KIND_DENY_CORS,
+        COREWEBVIEW2_PDF_TOOLBAR_ITEMS_BOOKMARKS,
+        COREWEBVIEW2_PDF_TOOLBAR_ITEMS_FIT_PAGE,
+        COREWEBVIEW2_PDF_TOOLBAR_ITEMS_NONE,
+        COREWEBVIEW2_PDF_TOOLBAR_ITEMS_PAGE_LAYOUT,
+        COREWEBVIEW2_PDF_TOOLBAR_ITEMS_PAGE_SELECTOR,
+        COREWEBVIEW2_PDF_TOOLBAR_ITEMS_PRINT,
+        COREWEBVIEW2_PDF_TOOLBAR_ITEMS_ROTATE,
+        COREWEBVIEW2_PDF_TOOLBAR_ITEMS_SAVE,
+        COREWEBVIEW2_PDF_TOOLBAR_ITEMS_SAVE_AS,
+        COREWEBVIEW2_PDF_TOOLBAR_ITEMS_SEARCH,
+        COREWEBVIEW2_PDF_TOOLBAR_ITEMS_ZOOM_IN,
+        COREWEBVIEW2_PDF_TOOLBAR_ITEMS_ZOOM_OUT,
+        COREWEBVIEW2_PERMISSION_KIND,
+        COREWEBVIEW2_PERMISSION_KIND_CAMERA,
+        COREWEBVIEW2_PERMISSION_KIND_GEOLOCATION,
+        COREWEBVIEW2_PERMISSION_KIND_MICROPHONE,
+        COREWEBVIEW2_PERMISSION_KIND_NOTIFICATIONS,
+        COREWEBVIEW2_PERMISSION_STATE_ALLOW,
+        COREWEBVIEW2_PERMISSION_STATE_DENY,
+        COREWEBVIEW2_PREFERRED_COLOR_SCHEME_AUTO,
+        COREWEBVIEW2_PREFERRED_COLOR_SCHEME_DARK,
+        COREWEBVIEW2_PREFERRED_COLOR_SCHEME_LIGHT,
+        COREWEBVIEW2_PRINT_ORIENTATION_LANDSCAPE,
+        COREWEBVIEW2_PRINT_ORIENTATION_PORTRAIT,
+        COREWEBVIEW2_PROCESS_FAILED_KIND,
+        COREWEBVIEW2_PROCESS_FAILED_KIND_RENDER_PROCESS_UNRESPONSIVE,
+        COREWEBVIEW2_SERVER_CERTIFICATE_ERROR_ACTION_ALWAYS_ALLOW,
+        COREWEBVIEW2_WEB_ERROR_STATUS,
+        COREWEBVIEW2_WEB_ERROR_STATUS_CANNOT_CONNECT,
+        COREWEBVIEW2_WEB_ERROR_STATUS_CERTIFICATE_COMMON_NAME_IS_INCORRECT,
+        COREWEBVIEW2_WEB_ERROR_STATUS_CERTIFICATE_EXPIRED,
+        COREWEBVIEW2_WEB_ERROR_STATUS_CERTIFICATE_IS_INVALID,
+        COREWEBVIEW2_WEB_ERROR_STATUS_CERTIFICATE_REVOKED,
+        COREWEBVIEW2_WEB_ERROR_STATUS_CLIENT_CERTIFICATE_CONTAINS_ERRORS,
+        COREWEBVIEW2_WEB_ERROR_STATUS_CONNECTION_ABORTED,
+        COREWEBVIEW2_WEB_ERROR_STATUS_CONNECTION_RESET,
+        COREWEBVIEW2_WEB_ERROR_STATUS_DISCONNECTED,
+        COREWEBVIEW2_WEB_ERROR_STATUS_HOST_NAME_NOT_RESOLVED,
+        COREWEBVIEW2_WEB_ERROR_STATUS_OPERATION_CANCELED,
+        COREWEBVIEW2_WEB_ERROR_STATUS_SERVER_UNREACHABLE,
+        COREWEBVIEW2_WEB_ERROR_STATUS_TIMEOUT,
+        COREWEBVIEW2_WEB_ERROR_STATUS_VALID_AUTHENTICATION_CREDENTIALS_REQUIRED,
+        COREWEBVIEW2_WEB_ERROR_STATUS_VALID_PROXY_AUTHENTICATION_REQUIRED,
+        COREWEBVIEW2_WEB_RESOURCE_CONTEXT,
+        COREWEBVIEW2_WEB_RESOURCE_CONTEXT_ALL,
+        COREWEBVIEW2_WEB_RESOURCE_CONTEXT_DOCUMENT,
+        COREWEBVIEW2_WEB_RESOURCE_CONTEXT_EVENT_SOURCE,
+        COREWEBVIEW2_WEB_RESOURCE_CONTEXT_FETCH,
+        COREWEBVIEW2_WEB_RESOURCE_CONTEXT_FONT,
+        COREWEBVIEW2_WEB_RESOURCE_CONTEXT_IMAGE,
+        COREWEBVIEW2_WEB_RESOURCE_CONTEXT_MEDIA,
+        COREWEBVIEW2_WEB_RESOURCE_CONTEXT_PING,
+        COREWEBVIEW2_WEB_RESOURCE_CONTEXT_SCRIPT,
+        COREWEBVIEW2_WEB_RESOURCE_CONTEXT_STYLESHEET,
+        COREWEBVIEW2_WEB_RESOURCE_CONTEXT_TEXT_TRACK,
+        COREWEBVIEW2_WEB_RESOURCE_CONTEXT_WEBSOCKET,
+        COREWEBVIEW2_WEB_RESOURCE_CONTEXT_XML_HTTP_REQUEST,
     },
+    NavigationCompletedEventHandler,
+    NavigationStartingEventHandler,
+    PermissionRequestedEventHandler,
+    PrintToPdfCompletedHandler,
+    ProcessFailedEventHandler,
+    ServerCertificateErrorDetectedEventHandler,
+    StateChangedEventHandler,
+    WebMessageReceivedEventHandler,
+    WebResourceRequestedEventHandler,
 };
 use windows::{
-    core::{Interface, HSTRING, PWSTR},
-    Win32::Foundation::BOOL,
+    core::{implement, IUnknown, InParam, Interface, HRESULT, HSTRING, PCWSTR, PWSTR},
+    Win32::{
+        Foundation::{BOOL, E_NOTIMPL, STG_E_ACCESSDENIED, S_FALSE, S_OK},
+        Globalization::{GetUserDefaultUILanguage, LCIDToLocaleName, LOCALE_ALLOW_NEUTRAL_NAMES, MAX_LOCALE_NAME},
+        Media::Speech::{ISpVoice, SpVoice, SPF_ASYNC, SPF_PURGEBEFORESPEAK},
+        System::{
+            Com::{
+                CoCreateInstance,
+                ISequentialStream_Impl,
+                IStream,
+                IStream_Impl,
+                CLSCTX_ALL,
+                LOCKTYPE,
+                STATFLAG,
+                STATSTG,
+                STGC,
+                STREAM_SEEK,
+            },
+            WinRT::EventRegistrationToken,
+        },
+        UI::Shell::SHCreateMemStream,
+    },
 };
 
+// NOTE: these are the arguments wry passes when none are given, which disable the "mini menu" and
+// SmartScreen. They are kept when additional arguments are supplied.
+const DEFAULT_DISABLED_FEATURES: &[&str] = &["msWebOOUI", "msPdfOOUI", "msSmartScreenProtection"];
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum ReleaseChannelPreference {
+    Stable,
+    Canary,
+}
+
+#[derive(Clone, Debug, Default, Eq, Hash, PartialEq)]
+pub struct EnvironmentOptions {
+    pub additional_browser_arguments: Vec<String>,
+    pub language: Option<String>,
+    pub release_channel_preference: Option<ReleaseChannelPreference>,
+    // NOTE: Chromium takes no proxy credentials as arguments, so it asks for them instead.
+    pub proxy: Option<ProxyConfig>,
+}
+
+impl EnvironmentOptions {
+    pub fn browser_arguments(&self) -> String {
+        let mut disabled_features = DEFAULT_DISABLED_FEATURES
+            .iter()
+            .map(|&feature| feature.to_string())
+            .collect::<Vec<_>>();
+        let mut arguments = vec![];
+        for argument in &self.additional_browser_arguments {
+            // NOTE: only the last `--disable-features` switch takes effect, so all of them are
+            // merged into one.
+            match argument.strip_prefix("--disable-features=") {
+                Some(features) => disabled_features.extend(features.split(',').map(String::from)),
+                None => arguments.push(argument.clone()),
+            }
+        }
+        if let Some(language) = &self.language {
+            arguments.push(format!("--lang={language}"));
+        }
+        if let Some(proxy) = &self.proxy {
+            arguments.push(format!("--proxy-server={}", proxy.uri()));
+            if !proxy.bypass.is_empty() {
+                arguments.push(format!("--proxy-bypass-list={}", proxy.bypass.join(";")));
+            }
+        }
+        arguments.insert(0, format!("--disable-features={}", disabled_features.join(",")));
+        arguments.join(" ")
+    }
+
+    /// Applies the options to a window that is being built.
+    ///
+    /// The environment of a WebView2 webview is fixed once it is created, so this has to be called
+    /// on the `WindowBuilder` before `build`. The release channel preference is read from the
+    /// process environment and therefore also applies to windows created afterwards.
+    pub fn apply<'a, R: Runtime>(&self, builder: WindowBuilder<'a, R>) -> WindowBuilder<'a, R> {
+        self.set_release_channel_preference();
+        builder.additional_browser_args(&self.browser_arguments())
+    }
+
+    fn set_release_channel_preference(&self) {
+        if let Some(preference) = self.release_channel_preference {
+            let value = match preference {
+                ReleaseChannelPreference::Stable => "0",
+                ReleaseChannelPreference::Canary => "1",
+            };
+            std::env::set_var("WEBVIEW2_RELEASE_CHANNEL_PREFERENCE", value);
+        }
+    }
+}
+
+thread_local! {
+    static PRELOADED_ENVIRONMENT: RefCell<Option<ICoreWebView2Environment>> = RefCell::new(None);
+}
+
+// NOTE: environments created for the same user data folder with the same options share one browser
+// process, so the environment created here starts the process the windows attach to, and is kept so
+// that it keeps running. The options have to be the ones wry creates environments with, the
+// language of the system and the browser arguments of the window, since creating an environment with
+// other options fails while the process runs.
+pub(crate) fn preload_engine(
+    data_directory: Option<PathBuf>,
+    config: &crate::PreloadConfig,
+    timings: &mut crate::PreloadTimings,
+) -> BoxResult<()> {
+    config.environment.set_release_channel_preference();
+    let arguments = config.environment.browser_arguments();
+    let environment = timings.time("create environment", || create_environment(data_directory, arguments))?;
+    PRELOADED_ENVIRONMENT.with(|preloaded| *preloaded.borrow_mut() = Some(environment));
+    Ok(())
+}
+
+fn create_environment(
+    data_directory: Option<PathBuf>,
+    arguments: String,
+) -> webview2_com::Result<ICoreWebView2Environment> {
+    let (environment_tx, environment_rx) = mpsc::channel();
+    CreateCoreWebView2EnvironmentCompletedHandler::wait_for_async_operation(
+        Box::new(move |handler| unsafe {
+            let options: ICoreWebView2EnvironmentOptions = CoreWebView2EnvironmentOptions::default().into();
+            let mut language = [0; MAX_LOCALE_NAME as usize];
+            LCIDToLocaleName(
+                u32::from(GetUserDefaultUILanguage()),
+                Some(&mut language),
+                LOCALE_ALLOW_NEUTRAL_NAMES,
+            );
+            options
+                .SetLanguage(PCWSTR::from_raw(language.as_ptr()))
+                .map_err(WindowsError)?;
+            options
+                .SetAdditionalBrowserArguments(&HSTRING::from(arguments))
+                .map_err(WindowsError)?;
+            let data_directory =
+                data_directory.map(|directory| HSTRING::from(directory.to_string_lossy().into_owned()));
+            let data_directory = data_directory
+                .as_ref()
+                .map_or(PCWSTR::null(), |directory| PCWSTR::from_raw(directory.as_ptr()));
+            CreateCoreWebView2EnvironmentWithOptions(PCWSTR::null(), data_directory, &options, &handler)
+                .map_err(WindowsError)
+        }),
+        Box::new(move |hresult, environment| {
+            hresult?;
+            environment_tx.send(environment).ok();
+            Ok(())
+        }),
+    )?;
+    environment_rx
+        .try_recv()
+        .ok()
+        .flatten()
+        .ok_or(webview2_com::Error::SendError)
+}
+
 impl crate::WebviewExt for Window {
-    #[cfg_attr(feature = "tracing", tracing::instrument)]
-    fn webview_clear_cache(&self) -> BoxFuture<BoxResult<()>> {
+    #[cfg_attr(feature = "tracing", tracing::instrument(
+        skip(self),
+        fields(
+            window.label = self.label(),
+            operation.id = crate::trace::operation_id(),
+            backend = crate::trace::BACKEND
+        )
+    ))]
+    fn webview_clear_cache(&self) -> BoxFuture<Result<()>> {
         unsafe fn run(webview: PlatformWebview, done_tx: oneshot::Sender<()>) -> Result<(), wry::Error> {
             let webview = webview.controller().CoreWebView2().map_err(WindowsError)?;
             let webview = Interface::cast::<ICoreWebView2_13>(&webview).map_err(WindowsError)?;
@@ -67,34 +396,56 @@ impl crate::WebviewExt for Window {
                     Ok(())
                 }),
                 Box::new(|hresult| {
+                    #[cfg(feature = "tracing")]
+                    crate::trace::callback("ClearBrowsingDataCompleted");
                     hresult?;
-                    done_tx.send(()).unwrap();
+                    done_tx.send(()).ok();
                     Ok(())
                 }),
             )?;
             Ok(())
         }
 
+        // NOTE: the span of the attribute is only entered while the future is created, so the future
+        // is instrumented with it as well.
         let window = self.clone();
-        async move {
+        let future = async move {
+            crate::read_only::check(&window)?;
+            if crate::dry_run::intercept(&window, || crate::dry_run::DryRunAction::ClearCache)? {
+                return Ok(());
+            }
             let (done_tx, done_rx) = oneshot::channel();
             let (call_tx, call_rx) = oneshot::channel();
             window
                 .with_webview(move |webview| unsafe {
                     let result = run(webview, done_tx).map_err(Into::into);
-                    call_tx.send(result).unwrap();
+                    call_tx.send(result).ok();
                 })
                 .map_err(Into::<BoxError>::into)
                 .and(call_rx.await?)?;
             Ok(done_rx.await?)
         }
-        .boxed()
+        .map(move |result: BoxResult<_>| result.window_context(self, "clear cache"));
+        #[cfg(feature = "tracing")]
+        let future = tracing::Instrument::in_current_span(future);
+        future.boxed()
     }
 
-    #[cfg_attr(feature = "tracing", tracing::instrument)]
-    fn webview_delete_cookies(&self, url: Option<Url>) -> BoxFuture<BoxResult<Vec<Cookie>>> {
+    #[cfg_attr(feature = "tracing", tracing::instrument(
+        skip(self),
+        fields(
+            window.label = self.label(),
+            operation.id = crate::trace::operation_id(),
+            backend = crate::trace::BACKEND
+        )
+    ))]
+    fn webview_delete_cookies(&self, url: Option<Url>) -> BoxFuture<Result<Vec<Cookie>>> {
         let window = self.clone();
-        async move {
+        let future = async move {
+            crate::read_only::check(&window)?;
+            if let Some(cookies) = crate::dry_run::delete_cookies(&window, url.clone()).await? {
+                return Ok(cookies);
+            }
             let mut cookies = vec![];
             if let Some(list) = unsafe { webview_get_raw_cookies(&window, url.clone()) }.await? {
                 let cookie_manager = unsafe { webview_get_cookie_manager(&window) }.await?;
@@ -104,21 +455,35 @@ impl crate::WebviewExt for Window {
                 unsafe {
                     list.Count(count)?;
                     for i in 0 .. *count {
-                        let cookie = list.GetValueAtIndex(i)?;
-                        cookie_manager.DeleteCookie(&cookie)?;
-                        cookies.push(cookie.try_into()?);
+                        let raw_cookie = list.GetValueAtIndex(i)?;
+                        // NOTE: the cookie is tagged before it is deleted, so that the change is
+                        // attributed to this crate even if it is observed before the deletion completes.
+                        let cookie: Cookie = raw_cookie.clone().try_into()?;
+                        crate::cookie_watch::tag(&window, &cookie);
+                        cookie_manager.DeleteCookie(&raw_cookie)?;
+                        cookies.push(cookie);
                     }
                 }
             }
             Ok(cookies)
         }
-        .boxed()
+        .map(move |result: BoxResult<_>| result.window_context(self, "delete cookies"));
+        #[cfg(feature = "tracing")]
+        let future = tracing::Instrument::in_current_span(future);
+        future.boxed()
     }
 
-    #[cfg_attr(feature = "tracing", tracing::instrument)]
-    fn webview_get_cookies(&self, url: Option<Url>) -> BoxFuture<BoxResult<Vec<Cookie>>> {
+    #[cfg_attr(feature = "tracing", tracing::instrument(
+        skip(self),
+        fields(
+            window.label = self.label(),
+            operation.id = crate::trace::operation_id(),
+            backend = crate::trace::BACKEND
+        )
+    ))]
+    fn webview_get_cookies(&self, url: Option<Url>) -> BoxFuture<Result<Vec<Cookie>>> {
         let window = self.clone();
-        async move {
+        let future = async move {
             if let Some(list) = unsafe { webview_get_raw_cookies(&window, url) }.await? {
                 let list = list.lock()?;
                 let mut cookies = Vec::<Cookie>::new();
@@ -129,94 +494,610 @@ impl crate::WebviewExt for Window {
                         cookies.push(list.GetValueAtIndex(i)?.try_into()?);
                     }
                 }
+                #[cfg(feature = "tracing-verbose")]
+                for cookie in &cookies {
+                    tracing::trace!(%cookie, "fetched cookie");
+                }
                 Ok(cookies)
             } else {
                 Ok(vec![])
             }
         }
-        .boxed()
+        .map(move |result: BoxResult<_>| result.window_context(self, "fetch cookies"));
+        #[cfg(feature = "tracing")]
+        let future = tracing::Instrument::in_current_span(future);
+        future.boxed()
     }
 
-    #[cfg_attr(feature = "tracing", tracing::instrument)]
-    fn webview_navigate(&self, url: Url) -> BoxResult<()> {
-        unsafe fn run(webview: PlatformWebview, url: Url) -> Result<(), wry::Error> {
-            let webview = webview.controller().CoreWebView2().map_err(WindowsError)?;
-            let url = &HSTRING::from(url.as_str());
-            webview.Navigate(url).map_err(WindowsError)?;
-            Ok(())
+    #[cfg_attr(feature = "tracing", tracing::instrument(
+        skip(self),
+        fields(
+            window.label = self.label(),
+            operation.id = crate::trace::operation_id(),
+            backend = crate::trace::BACKEND
+        )
+    ))]
+    fn webview_navigate(&self, url: Url) -> Result<()> {
+        crate::local_url::target(url)
+            .and_then(|target| navigate(self, target))
+            .window_context(self, "navigate")
+    }
+}
+
+// NOTE: the folder a file is given read access to is mapped to a virtual host, and the file is loaded
+// from it over HTTPS, so that the document can load the files next to it. `file:` documents can not.
+const FILE_HOST: &str = "files.tauri-webview-util.example";
+
+// NOTE: `Navigate` rejects data URLs larger than this.
+const MAX_DATA_URL_LENGTH: usize = 2 * 1024 * 1024;
+
+pub(crate) fn navigate(window: &Window, target: NavigationTarget) -> BoxResult<()> {
+    unsafe fn run(webview: PlatformWebview, url: Url, folder: Option<String>) -> Result<(), wry::Error> {
+        let webview = webview.controller().CoreWebView2().map_err(WindowsError)?;
+        if let Some(folder) = folder {
+            let webview = Interface::cast::<ICoreWebView2_3>(&webview).map_err(WindowsError)?;
+            let host = &HSTRING::from(FILE_HOST);
+            let folder = &HSTRING::from(folder);
+            let access = COREWEBVIEW2_HOST_RESOURCE_ACCESS_KIND_DENY_CORS;
+            webview
+                .SetVirtualHostNameToFolderMapping(host, folder, access)
+                .map_err(WindowsError)?;
         }
+        let url = &HSTRING::from(url.as_str());
+        webview.Navigate(url).map_err(WindowsError)?;
+        Ok(())
+    }
 
-        let (call_tx, call_rx) = oneshot::channel();
-        self.with_webview(move |webview| unsafe {
-            let result = run(webview, url).map_err(Into::into);
-            call_tx.send(result).unwrap();
+    let (url, folder) = match target {
+        NavigationTarget::File { url, file, read_access } => {
+            let url = mapped_file_url(&url, &file, &read_access)?;
+            (url, Some(read_access.to_string_lossy().into_owned()))
+        },
+        NavigationTarget::Data(url) if url.as_str().len() > MAX_DATA_URL_LENGTH => {
+            let err = Error::InvalidNavigationTarget {
+                target: format!("data URL of {} bytes", url.as_str().len()),
+                reason: "WebView2 can not navigate to data URLs larger than 2 MB",
+            };
+            return Err(err.into());
+        },
+        NavigationTarget::Remote(url) | NavigationTarget::Data(url) => (url, None),
+    };
+    crate::navigation_events::tag(window);
+    let (call_tx, call_rx) = oneshot::channel();
+    window
+        .with_webview(move |webview| unsafe {
+            let result = run(webview, url, folder).map_err(Into::into);
+            call_tx.send(result).ok();
         })
-        .map_err(Into::into)
-        .and(call_rx.recv().unwrap())
+        .map_err(Into::<BoxError>::into)
+        .and_then(|()| call_rx.recv()?)
+}
+
+fn mapped_file_url(url: &Url, file: &Path, read_access: &Path) -> BoxResult<Url> {
+    let mut mapped = Url::parse(&format!("https://{FILE_HOST}/"))?;
+    mapped
+        .path_segments_mut()
+        .map_err(|()| Error::UnsupportedPlatform("the virtual host URL can not have a path"))?
+        .extend(
+            file.strip_prefix(read_access)?
+                .components()
+                .map(|component| component.as_os_str().to_string_lossy().into_owned()),
+        );
+    mapped.set_query(url.query());
+    mapped.set_fragment(url.fragment());
+    Ok(mapped)
+}
+
+#[cfg(feature = "debug-tools")]
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn raw_cookie_debug(window: &Window) -> BoxResult<Vec<crate::RawCookieDebug>> {
+    let mut cookies = vec![];
+    if let Some(list) = unsafe { webview_get_raw_cookies(window, None) }.await? {
+        let list = list.lock()?;
+        unsafe {
+            let count = &mut u32::default();
+            list.Count(count)?;
+            for i in 0 .. *count {
+                let cookie = list.GetValueAtIndex(i)?;
+                let raw = raw_cookie_attributes(&cookie)?;
+                cookies.push(crate::RawCookieDebug::new(raw, cookie.try_into()));
+            }
+        }
     }
+    Ok(cookies)
+}
+
+#[cfg(feature = "debug-tools")]
+unsafe fn raw_cookie_attributes(cookie: &ICoreWebView2Cookie) -> BoxResult<Vec<(&'static str, String)>> {
+    let name = &mut PWSTR::null();
+    let value = &mut PWSTR::null();
+    let domain = &mut PWSTR::null();
+    let path = &mut PWSTR::null();
+    let expires = &mut f64::default();
+    let is_http_only = &mut BOOL::default();
+    let same_site = &mut COREWEBVIEW2_COOKIE_SAME_SITE_KIND::default();
+    let is_secure = &mut BOOL::default();
+    let is_session = &mut BOOL::default();
+    cookie.Name(name)?;
+    cookie.Value(value)?;
+    cookie.Domain(domain)?;
+    cookie.Path(path)?;
+    cookie.Expires(expires)?;
+    cookie.IsHttpOnly(is_http_only)?;
+    cookie.SameSite(same_site)?;
+    cookie.IsSecure(is_secure)?;
+    cookie.IsSession(is_session)?;
+    Ok(vec![
+        ("Name", name.to_string()?),
+        ("Value", crate::cookie_debug::redact(&value.to_string()?)),
+        ("Domain", domain.to_string()?),
+        ("Path", path.to_string()?),
+        ("Expires", expires.to_string()),
+        ("IsHttpOnly", is_http_only.as_bool().to_string()),
+        ("SameSite", same_site.0.to_string()),
+        ("IsSecure", is_secure.as_bool().to_string()),
+        ("IsSession", is_session.as_bool().to_string()),
+    ])
 }
 
 impl TryFrom<ICoreWebView2Cookie> for Cookie {
-    type Error = BoxError;
+    type Error = Error;
 
     fn try_from(cookie: ICoreWebView2Cookie) -> Result<Self, Self::Error> {
+        cookie_from_core_webview2(cookie).map_err(Error::CookieConversion)
+    }
+}
+
+fn cookie_from_core_webview2(cookie: ICoreWebView2Cookie) -> BoxResult<Cookie> {
+    let name = &mut PWSTR::null();
+    let value = &mut PWSTR::null();
+    let domain = &mut PWSTR::null();
+    let path = &mut PWSTR::null();
+    let expires = &mut f64::default();
+    let is_http_only = &mut BOOL::default();
+    let same_site = &mut COREWEBVIEW2_COOKIE_SAME_SITE_KIND::default();
+    let is_secure = &mut BOOL::default();
+    let is_session = &mut BOOL::default();
+
+    unsafe {
+        cookie.Name(name)?;
+        cookie.Value(value)?;
+        cookie.Domain(domain)?;
+        cookie.Path(path)?;
+        cookie.Expires(expires)?;
+        cookie.IsHttpOnly(is_http_only)?;
+        cookie.SameSite(same_site)?;
+        cookie.IsSecure(is_secure)?;
+        cookie.IsSession(is_session)?;
+
+        let name = name.to_string()?;
+        let value = value.to_string()?;
+        let domain = domain.to_string()?;
+        let path = path.to_string()?;
+        let port_list = None;
+        let session = is_session.as_bool();
+        // NOTE: session cookies report an expiry of -1.
+        let expires = if session {
+            None
+        } else {
+            let expires = expires.round() as i64;
+            Some(time::OffsetDateTime::from_unix_timestamp(expires)?)
+        };
+        let http_only = is_http_only.as_bool();
+        let same_site = match *same_site {
+            COREWEBVIEW2_COOKIE_SAME_SITE_KIND_NONE => String::from("none"),
+            COREWEBVIEW2_COOKIE_SAME_SITE_KIND_LAX => String::from("lax"),
+            COREWEBVIEW2_COOKIE_SAME_SITE_KIND_STRICT => String::from("strict"),
+            _ => unreachable!(),
+        }
+        .into();
+        let secure = is_secure.as_bool();
+        let comment = None;
+        let comment_url = None;
+
+        Ok(Cookie {
+            name,
+            value,
+            domain,
+            path,
+            port_list,
+            expires,
+            http_only,
+            same_site,
+            secure,
+            session,
+            comment,
+            comment_url,
+        })
+    }
+}
+
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn clear_data(window: &Window, kinds: DataKinds) -> BoxResult<()> {
+    unsafe fn run(
+        webview: PlatformWebview,
+        datakinds: COREWEBVIEW2_BROWSING_DATA_KINDS,
+        done_tx: oneshot::Sender<()>,
+    ) -> Result<(), wry::Error> {
+        let webview = webview.controller().CoreWebView2().map_err(WindowsError)?;
+        let webview = Interface::cast::<ICoreWebView2_13>(&webview).map_err(WindowsError)?;
+        let profile = webview.Profile().map_err(WindowsError)?;
+        let profile = Interface::cast::<ICoreWebView2Profile2>(&profile).map_err(WindowsError)?;
+        ClearBrowsingDataCompletedHandler::wait_for_async_operation(
+            Box::new(move |handler| {
+                profile.ClearBrowsingData(datakinds, &handler)?;
+                Ok(())
+            }),
+            Box::new(|hresult| {
+                #[cfg(feature = "tracing")]
+                crate::trace::callback("ClearBrowsingDataCompleted");
+                hresult?;
+                done_tx.send(()).ok();
+                Ok(())
+            }),
+        )?;
+        Ok(())
+    }
+
+    let mut datakinds = COREWEBVIEW2_BROWSING_DATA_KINDS::default();
+    for (kind, datakind) in [
+        (DataKinds::DISK_CACHE, COREWEBVIEW2_BROWSING_DATA_KINDS_DISK_CACHE),
+        (DataKinds::CACHE_STORAGE, COREWEBVIEW2_BROWSING_DATA_KINDS_CACHE_STORAGE),
+        (DataKinds::COOKIES, COREWEBVIEW2_BROWSING_DATA_KINDS_COOKIES),
+        (DataKinds::INDEXED_DB, COREWEBVIEW2_BROWSING_DATA_KINDS_INDEXED_DB),
+        (DataKinds::LOCAL_STORAGE, COREWEBVIEW2_BROWSING_DATA_KINDS_LOCAL_STORAGE),
+        (DataKinds::WEB_SQL, COREWEBVIEW2_BROWSING_DATA_KINDS_WEB_SQL),
+        (DataKinds::HISTORY, COREWEBVIEW2_BROWSING_DATA_KINDS_BROWSING_HISTORY),
+        (DataKinds::HISTORY, COREWEBVIEW2_BROWSING_DATA_KINDS_DOWNLOAD_HISTORY),
+        (DataKinds::AUTOFILL, COREWEBVIEW2_BROWSING_DATA_KINDS_GENERAL_AUTOFILL),
+        (DataKinds::PASSWORDS, COREWEBVIEW2_BROWSING_DATA_KINDS_PASSWORD_AUTOSAVE),
+    ] {
+        if kinds.contains(kind) {
+            datakinds = datakinds | datakind;
+        }
+    }
+    let (done_tx, done_rx) = oneshot::channel();
+    let (call_tx, call_rx) = oneshot::channel();
+    window
+        .with_webview(move |webview| unsafe {
+            let result = run(webview, datakinds, done_tx).map_err(Into::<BoxError>::into);
+            call_tx.send(result).ok();
+        })
+        .map_err(Into::<BoxError>::into)?;
+    call_rx.await??;
+    Ok(done_rx.await?)
+}
+
+// NOTE: WebView2 can only clear browsing data for the whole profile, so records are per origin
+// through the DevTools protocol. There is no way to enumerate the origins that hold data, so the
+// origins of the frames in the page and of the cookies in the profile are the ones that are listed.
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn list_data_records(window: &Window, kinds: DataKinds) -> BoxResult<Vec<DataRecord>> {
+    let mut records = vec![];
+    for (origin, mut origin_kinds) in data_origins(window).await? {
+        for usage in storage_usage(window, &origin).await? {
+            if usage.usage > 0.0 {
+                origin_kinds |= STORAGE_TYPES
+                    .iter()
+                    .filter(|(_, storage_type)| *storage_type == usage.storage_type)
+                    .fold(DataKinds::empty(), |kinds, (kind, _)| kinds | *kind);
+            }
+        }
+        let kinds = origin_kinds & kinds;
+        if !kinds.is_empty() {
+            records.push(DataRecord { name: origin, kinds });
+        }
+    }
+    Ok(records)
+}
+
+// NOTE: the HTTP cache is shared by all origins and the memory cache is not reported, so the disk
+// cache of an origin is what it holds in Cache Storage. Origins are enumerated like data records.
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn cache_usage(window: &Window) -> BoxResult<Vec<OriginUsage>> {
+    let mut usages = vec![];
+    for origin in data_origins(window).await?.into_keys() {
+        let disk_cache = storage_usage(window, &origin)
+            .await?
+            .into_iter()
+            .filter(|usage| usage.storage_type == "cache_storage")
+            .map(|usage| usage.usage as u64)
+            .sum::<u64>();
+        if disk_cache > 0 {
+            usages.push(OriginUsage {
+                origin,
+                disk_cache: Some(disk_cache),
+                memory_cache: None,
+            });
+        }
+    }
+    Ok(usages)
+}
+
+async fn data_origins(window: &Window) -> BoxResult<BTreeMap<String, DataKinds>> {
+    #[derive(serde::Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    struct FrameTree {
+        frame: Frame,
+        #[serde(default)]
+        child_frames: Vec<FrameTree>,
+    }
+
+    #[derive(serde::Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    struct Frame {
+        security_origin: String,
+    }
+
+    #[derive(serde::Deserialize)]
+    struct Cookies {
+        cookies: Vec<NetworkCookie>,
+    }
+
+    #[derive(serde::Deserialize)]
+    struct NetworkCookie {
+        domain: String,
+        secure: bool,
+    }
+
+    fn frame_origins(tree: FrameTree, origins: &mut BTreeMap<String, DataKinds>) {
+        if Url::parse(&tree.frame.security_origin).map_or(false, |url| url.has_host()) {
+            origins.entry(tree.frame.security_origin).or_default();
+        }
+        for child in tree.child_frames {
+            frame_origins(child, origins);
+        }
+    }
+
+    let mut origins = BTreeMap::<String, DataKinds>::new();
+    let result = devtools_call(window, "Page.getFrameTree", serde_json::json!({})).await?;
+    let tree = serde_json::from_str::<serde_json::Value>(&result)?["frameTree"].take();
+    frame_origins(serde_json::from_value(tree)?, &mut origins);
+    let result = devtools_call(window, "Network.getAllCookies", serde_json::json!({})).await?;
+    for cookie in serde_json::from_str::<Cookies>(&result)?.cookies {
+        let scheme = if cookie.secure { "https" } else { "http" };
+        let origin = format!("{scheme}://{}", cookie.domain.trim_start_matches('.'));
+        *origins.entry(origin).or_default() |= DataKinds::COOKIES;
+    }
+    Ok(origins)
+}
+
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct StorageUsage {
+    storage_type: String,
+    usage: f64,
+}
+
+async fn storage_usage(window: &Window, origin: &str) -> BoxResult<Vec<StorageUsage>> {
+    #[derive(serde::Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    struct Usage {
+        usage_breakdown: Vec<StorageUsage>,
+    }
+
+    let parameters = serde_json::json!({ "origin": origin });
+    let result = devtools_call(window, "Storage.getUsageAndQuota", parameters).await?;
+    Ok(serde_json::from_str::<Usage>(&result)?.usage_breakdown)
+}
+
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn remove_data_records(window: &Window, records: Vec<DataRecord>) -> BoxResult<()> {
+    for record in records {
+        let storage_types = STORAGE_TYPES
+            .iter()
+            .filter(|(kind, _)| record.kinds.contains(*kind))
+            .map(|(_, storage_type)| *storage_type)
+            .collect::<Vec<_>>();
+        if storage_types.is_empty() {
+            continue;
+        }
+        let parameters = serde_json::json!({ "origin": record.name, "storageTypes": storage_types.join(",") });
+        devtools_call(window, "Storage.clearDataForOrigin", parameters).await?;
+    }
+    Ok(())
+}
+
+const STORAGE_TYPES: [(DataKinds, &str); 6] = [
+    (DataKinds::CACHE_STORAGE, "cache_storage"),
+    (DataKinds::COOKIES, "cookies"),
+    (DataKinds::INDEXED_DB, "indexeddb"),
+    (DataKinds::LOCAL_STORAGE, "local_storage"),
+    (DataKinds::WEB_SQL, "websql"),
+    (DataKinds::SERVICE_WORKERS, "service_workers"),
+];
+
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn profile_directory(window: &Window) -> BoxResult<Option<PathBuf>> {
+    unsafe fn run(webview: PlatformWebview) -> Result<Option<PathBuf>, wry::Error> {
+        let webview = webview.controller().CoreWebView2().map_err(WindowsError)?;
+        let webview = Interface::cast::<ICoreWebView2_2>(&webview).map_err(WindowsError)?;
+        let environment = webview
+            .Environment()
+            .and_then(|environment| environment.cast::<ICoreWebView2Environment7>())
+            .map_err(WindowsError)?;
+        let directory = &mut PWSTR::null();
+        environment.UserDataFolder(directory).map_err(WindowsError)?;
+        Ok(directory.to_string().ok().map(PathBuf::from))
+    }
+
+    let (call_tx, call_rx) = oneshot::channel();
+    window
+        .with_webview(move |webview| unsafe {
+            let result = run(webview).map_err(Into::<BoxError>::into);
+            call_tx.send(result).ok();
+        })
+        .map_err(Into::<BoxError>::into)?;
+    call_rx.await?
+}
+
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn profile_info(window: &Window) -> BoxResult<ProfileInfo> {
+    unsafe fn run(webview: PlatformWebview) -> Result<ProfileInfo, wry::Error> {
+        let webview = webview.controller().CoreWebView2().map_err(WindowsError)?;
+        let webview = Interface::cast::<ICoreWebView2_13>(&webview).map_err(WindowsError)?;
+        let profile = webview.Profile().map_err(WindowsError)?;
         let name = &mut PWSTR::null();
-        let value = &mut PWSTR::null();
-        let domain = &mut PWSTR::null();
+        profile.ProfileName(name).map_err(WindowsError)?;
+        let in_private = &mut BOOL::default();
+        profile.IsInPrivateModeEnabled(in_private).map_err(WindowsError)?;
+        let path = &mut PWSTR::null();
+        profile.ProfilePath(path).map_err(WindowsError)?;
+        let (session, directory) = if in_private.as_bool() {
+            (SessionKind::Ephemeral, None)
+        } else {
+            (SessionKind::Persistent, path.to_string().ok().map(PathBuf::from))
+        };
+        Ok(ProfileInfo {
+            name: name.to_string().ok().filter(|name| !name.is_empty()),
+            session,
+            directory,
+        })
+    }
+
+    let (call_tx, call_rx) = oneshot::channel();
+    window
+        .with_webview(move |webview| unsafe {
+            let result = run(webview).map_err(Into::<BoxError>::into);
+            call_tx.send(result).ok();
+        })
+        .map_err(Into::<BoxError>::into)?;
+    call_rx.await?
+}
+
+// NOTE: COM objects are identified by their `IUnknown` pointer, so the environment is identified by
+// that. Profiles are identified by their path, which is unique among the profiles of a browser
+// process.
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn engine_context(window: &Window) -> BoxResult<crate::EngineContext> {
+    unsafe fn run(webview: PlatformWebview) -> Result<crate::EngineContext, wry::Error> {
+        let webview = webview.controller().CoreWebView2().map_err(WindowsError)?;
+        let process_id = &mut 0;
+        webview.BrowserProcessId(process_id).map_err(WindowsError)?;
+        let environment = Interface::cast::<ICoreWebView2_2>(&webview)
+            .and_then(|webview| webview.Environment())
+            .and_then(|environment| environment.cast::<IUnknown>())
+            .map_err(WindowsError)?;
+        let profile = Interface::cast::<ICoreWebView2_13>(&webview)
+            .and_then(|webview| webview.Profile())
+            .map_err(WindowsError)?;
         let path = &mut PWSTR::null();
-        let expires = &mut f64::default();
-        let is_http_only = &mut BOOL::default();
-        let same_site = &mut COREWEBVIEW2_COOKIE_SAME_SITE_KIND::default();
-        let is_secure = &mut BOOL::default();
-        let is_session = &mut BOOL::default();
+        profile.ProfilePath(path).map_err(WindowsError)?;
+        Ok(crate::EngineContext {
+            context: format!("{:p}", environment.as_raw()),
+            data_store: path.to_string().ok(),
+            browser_process_id: Some(*process_id),
+        })
+    }
 
-        unsafe {
-            cookie.Name(name)?;
-            cookie.Value(value)?;
-            cookie.Domain(domain)?;
-            cookie.Path(path)?;
-            cookie.Expires(expires)?;
-            cookie.IsHttpOnly(is_http_only)?;
-            cookie.SameSite(same_site)?;
-            cookie.IsSecure(is_secure)?;
-            cookie.IsSession(is_session)?;
-
-            let name = name.to_string()?.into();
-            let value = value.to_string()?.into();
-            let domain = domain.to_string()?.into();
-            let path = path.to_string()?.into();
-            let expires = {
-                let expires = expires.round() as i64;
-                time::OffsetDateTime::from_unix_timestamp(expires)?
-            }
-            .into();
-            let is_http_only = is_http_only.as_bool().into();
-            let same_site = match *same_site {
-                COREWEBVIEW2_COOKIE_SAME_SITE_KIND_NONE => String::from("none"),
-                COREWEBVIEW2_COOKIE_SAME_SITE_KIND_LAX => String::from("lax"),
-                COREWEBVIEW2_COOKIE_SAME_SITE_KIND_STRICT => String::from("strict"),
-                _ => unreachable!(),
-            }
-            .into();
-            let is_secure = is_secure.as_bool().into();
-            let is_session = is_session.as_bool().into();
-
-            Ok(Self {
-                name,
-                value,
-                domain,
-                path,
-                expires,
-                is_http_only,
-                same_site,
-                is_secure,
-                is_session,
-            })
+    let (call_tx, call_rx) = oneshot::channel();
+    window
+        .with_webview(move |webview| unsafe {
+            let result = run(webview).map_err(Into::<BoxError>::into);
+            call_tx.send(result).ok();
+        })
+        .map_err(Into::<BoxError>::into)?;
+    call_rx.await?
+}
+
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window, cookie),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn set_cookie(window: &Window, cookie: Cookie) -> BoxResult<()> {
+    crate::read_only::check(window)?;
+    if crate::dry_run::intercept(window, || crate::dry_run::set_cookie_action(&cookie))? {
+        return Ok(());
+    }
+    crate::cookie_watch::tag(window, &cookie);
+    unsafe fn run(webview: PlatformWebview, cookie: Cookie) -> Result<(), wry::Error> {
+        let webview = webview.controller().CoreWebView2().map_err(WindowsError)?;
+        let webview = Interface::cast::<ICoreWebView2_2>(&webview).map_err(WindowsError)?;
+        let manager = webview.CookieManager().map_err(WindowsError)?;
+        let name = &HSTRING::from(&cookie.name);
+        let value = &HSTRING::from(&cookie.value);
+        let domain = &HSTRING::from(&cookie.domain);
+        let path = &HSTRING::from(&cookie.path);
+        let raw_cookie = manager.CreateCookie(name, value, domain, path).map_err(WindowsError)?;
+        if let Some(expires) = cookie.expires {
+            raw_cookie
+                .SetExpires(expires.unix_timestamp() as f64)
+                .map_err(WindowsError)?;
+        }
+        raw_cookie.SetIsHttpOnly(cookie.http_only).map_err(WindowsError)?;
+        raw_cookie.SetIsSecure(cookie.secure).map_err(WindowsError)?;
+        let same_site = match cookie.same_site.as_deref().map(str::to_ascii_lowercase).as_deref() {
+            Some("none") => Some(COREWEBVIEW2_COOKIE_SAME_SITE_KIND_NONE),
+            Some("lax") => Some(COREWEBVIEW2_COOKIE_SAME_SITE_KIND_LAX),
+            Some("strict") => Some(COREWEBVIEW2_COOKIE_SAME_SITE_KIND_STRICT),
+            _ => None,
+        };
+        if let Some(same_site) = same_site {
+            raw_cookie.SetSameSite(same_site).map_err(WindowsError)?;
         }
+        manager.AddOrUpdateCookie(&raw_cookie).map_err(WindowsError)?;
+        Ok(())
     }
+
+    let (call_tx, call_rx) = oneshot::channel();
+    window
+        .with_webview(move |webview| unsafe {
+            let result = run(webview, cookie).map_err(Into::<BoxError>::into);
+            call_tx.send(result).ok();
+        })
+        .map_err(Into::<BoxError>::into)?;
+    call_rx.await?
+}
+
+// NOTE: Chromium allows third-party cookies by default, caps the lifetime of every cookie to 400
+// days, and keeps at most 180 cookies per domain.
+#[allow(unused_variables)]
+pub(crate) async fn cookie_policy(window: &Window) -> BoxResult<CookiePolicyInfo> {
+    Ok(CookiePolicyInfo {
+        engine: "WebView2".into(),
+        engine_version: wry::webview::webview_version()?,
+        blocks_third_party_cookies: Some(false),
+        script_cookie_max_age: Some(std::time::Duration::from_secs(400 * 24 * 60 * 60)),
+        script_cookie_max_age_probed: false,
+        max_cookies_per_domain: Some(180),
+    })
+}
+
+// NOTE: WebView2 has no event for cookie changes (not even through the DevTools protocol), so
+// watched cookies are polled instead.
+#[allow(unused_variables)]
+pub(crate) async fn add_cookie_observer(window: &Window, observer: impl Fn() + Send + Sync + 'static) -> BoxResult<()> {
+    Err(Error::UnsupportedPlatform("cookie change notifications are not supported with WebView2").into())
 }
 
-#[cfg_attr(feature = "tracing", tracing::instrument)]
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
 async unsafe fn webview_get_cookie_manager(window: &Window) -> BoxResult<ApiResult<ICoreWebView2CookieManager>> {
     unsafe fn run(webview: PlatformWebview) -> Result<ApiResult<ICoreWebView2CookieManager>, wry::Error> {
         let webview = webview.controller().CoreWebView2().map_err(WindowsError)?;
@@ -229,13 +1110,16 @@ async unsafe fn webview_get_cookie_manager(window: &Window) -> BoxResult<ApiResu
     window
         .with_webview(|webview| {
             let result = run(webview).map_err(Into::<BoxError>::into);
-            call_tx.send(result).unwrap();
+            call_tx.send(result).ok();
         })
         .map_err(Into::<BoxError>::into)?;
     Ok(call_rx.await??)
 }
 
-#[cfg_attr(feature = "tracing", tracing::instrument)]
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
 async unsafe fn webview_get_raw_cookies(
     window: &Window,
     url: Option<Url>,
@@ -255,10 +1139,10 @@ async unsafe fn webview_get_raw_cookies(
                 Ok(())
             }),
             Box::new(move |hresult, list| {
-                hresult?;
                 #[cfg(feature = "tracing")]
-                tracing::info!(?list);
-                done_tx.send(list.map(Into::into)).unwrap();
+                crate::trace::callback("GetCookiesCompleted");
+                hresult?;
+                done_tx.send(list.map(Into::into)).ok();
                 Ok(())
             }),
         )?;
@@ -270,9 +1154,2497 @@ async unsafe fn webview_get_raw_cookies(
     window
         .with_webview(move |webview| unsafe {
             let result = run(webview, url, done_tx).map_err(Into::<BoxError>::into);
-            call_tx.send(result).unwrap();
+            call_tx.send(result).ok();
         })
         .map_err(Into::<BoxError>::into)
         .and(call_rx.await?)?;
     Ok(done_rx.await?)
 }
+
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window, source),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn add_user_script(window: &Window, source: String) -> BoxResult<()> {
+    unsafe fn run(webview: PlatformWebview, source: String) -> Result<(), wry::Error> {
+        let webview = webview.controller().CoreWebView2().map_err(WindowsError)?;
+        AddScriptToExecuteOnDocumentCreatedCompletedHandler::wait_for_async_operation(
+            Box::new(move |handler| {
+                let source = &HSTRING::from(source);
+                webview.AddScriptToExecuteOnDocumentCreated(source, &handler)?;
+                Ok(())
+            }),
+            Box::new(|hresult, _id| {
+                #[cfg(feature = "tracing")]
+                crate::trace::callback("AddScriptToExecuteOnDocumentCreatedCompleted");
+                hresult?;
+                Ok(())
+            }),
+        )?;
+        Ok(())
+    }
+
+    let (call_tx, call_rx) = oneshot::channel();
+    window
+        .with_webview(move |webview| unsafe {
+            let result = run(webview, source).map_err(Into::<BoxError>::into);
+            call_tx.send(result).ok();
+        })
+        .map_err(Into::<BoxError>::into)?;
+    call_rx.await?
+}
+
+pub(crate) struct UserScriptHandle(String);
+
+// NOTE: `ExecuteScript` already serializes the result with `JSON.stringify`, reporting `undefined`
+// as `null`.
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window, script),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn eval_js(window: &Window, script: String) -> BoxResult<serde_json::Value> {
+    #[cfg(feature = "tracing-verbose")]
+    tracing::trace!(%script, "evaluating script");
+    unsafe fn run(webview: PlatformWebview, script: String) -> Result<String, wry::Error> {
+        let webview = webview.controller().CoreWebView2().map_err(WindowsError)?;
+        let (json_tx, json_rx) = oneshot::channel();
+        ExecuteScriptCompletedHandler::wait_for_async_operation(
+            Box::new(move |handler| {
+                let script = &HSTRING::from(script);
+                webview.ExecuteScript(script, &handler)?;
+                Ok(())
+            }),
+            Box::new(move |hresult, json| {
+                #[cfg(feature = "tracing")]
+                crate::trace::callback("ExecuteScriptCompleted");
+                hresult?;
+                json_tx.send(json).ok();
+                Ok(())
+            }),
+        )?;
+        let json = json_rx.try_recv().map_err(|_| webview2_com::Error::SendError)?;
+        Ok(json)
+    }
+
+    let (call_tx, call_rx) = oneshot::channel();
+    window
+        .with_webview(move |webview| unsafe {
+            let result = run(webview, script).map_err(Into::<BoxError>::into);
+            call_tx.send(result).ok();
+        })
+        .map_err(Into::<BoxError>::into)?;
+    let json = call_rx.await??;
+    Ok(serde_json::from_str(&json)?)
+}
+
+// NOTE: `ExecuteScript` does not await promises, and `ExecuteScriptWithResult` is newer than the
+// WebView2 SDK used here, so the body is run through the bridge instead.
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window, body),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn eval_async_js(window: &Window, body: String) -> BoxResult<serde_json::Value> {
+    #[cfg(feature = "tracing-verbose")]
+    tracing::trace!(%body, "evaluating script");
+    bridge::call(window, &body).await
+}
+
+// NOTE: scripts added with `AddScriptToExecuteOnDocumentCreated` run in every frame, so scripts that
+// are limited to the top frame check for it themselves.
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window, source),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn add_init_script(window: &Window, source: String, all_frames: bool) -> BoxResult<UserScriptHandle> {
+    unsafe fn run(webview: PlatformWebview, source: String) -> Result<UserScriptHandle, wry::Error> {
+        let webview = webview.controller().CoreWebView2().map_err(WindowsError)?;
+        let (id_tx, id_rx) = oneshot::channel();
+        AddScriptToExecuteOnDocumentCreatedCompletedHandler::wait_for_async_operation(
+            Box::new(move |handler| {
+                let source = &HSTRING::from(source);
+                webview.AddScriptToExecuteOnDocumentCreated(source, &handler)?;
+                Ok(())
+            }),
+            Box::new(move |hresult, id| {
+                #[cfg(feature = "tracing")]
+                crate::trace::callback("AddScriptToExecuteOnDocumentCreatedCompleted");
+                hresult?;
+                id_tx.send(id).ok();
+                Ok(())
+            }),
+        )?;
+        let id = id_rx.try_recv().map_err(|_| webview2_com::Error::SendError)?;
+        Ok(UserScriptHandle(id))
+    }
+
+    let source = if all_frames {
+        source
+    } else {
+        format!("if (window === window.top) {{\n{source}\n}}")
+    };
+    let (call_tx, call_rx) = oneshot::channel();
+    window
+        .with_webview(move |webview| unsafe {
+            let result = run(webview, source).map_err(Into::<BoxError>::into);
+            call_tx.send(result).ok();
+        })
+        .map_err(Into::<BoxError>::into)?;
+    call_rx.await?
+}
+
+// NOTE: WebView2 runs scripts from `AddScriptToExecuteOnDocumentCreated` before the document is
+// parsed, so scripts for the end of the document wait for `DOMContentLoaded` in a function (which
+// scopes their top-level declarations). Scripts can only be added to the world of the page.
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window, script),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn add_scoped_user_script(window: &Window, script: UserScript) -> BoxResult<UserScriptHandle> {
+    if script.isolated_world.is_some() {
+        return Err(Error::UnsupportedPlatform("isolated worlds are not supported with WebView2").into());
+    }
+    let source = match script.injection_time {
+        InjectionTime::DocumentStart => script.source,
+        InjectionTime::DocumentEnd => format!(
+            r#"(() => {{
+  const run = () => {{
+{}
+  }};
+  if (document.readyState === "loading") {{
+    document.addEventListener("DOMContentLoaded", run, {{ once: true }});
+  }} else {{
+    run();
+  }}
+}})();"#,
+            script.source
+        ),
+    };
+    add_init_script(window, source, !script.main_frame_only).await
+}
+
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window, handle),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn remove_init_script(window: &Window, handle: UserScriptHandle) -> BoxResult<()> {
+    unsafe fn run(webview: PlatformWebview, handle: UserScriptHandle) -> Result<(), wry::Error> {
+        let webview = webview.controller().CoreWebView2().map_err(WindowsError)?;
+        let id = &HSTRING::from(handle.0);
+        webview
+            .RemoveScriptToExecuteOnDocumentCreated(id)
+            .map_err(WindowsError)?;
+        Ok(())
+    }
+
+    let (call_tx, call_rx) = oneshot::channel();
+    window
+        .with_webview(move |webview| unsafe {
+            let result = run(webview, handle).map_err(Into::<BoxError>::into);
+            call_tx.send(result).ok();
+        })
+        .map_err(Into::<BoxError>::into)?;
+    call_rx.await?
+}
+
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window, handler),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn add_script_message_handler(
+    window: &Window,
+    name: &'static str,
+    handler: impl Fn(ScriptMessage) + Send + Sync + 'static,
+) -> BoxResult<()> {
+    unsafe fn run(
+        webview: PlatformWebview,
+        name: &'static str,
+        handler: impl Fn(ScriptMessage) + 'static,
+    ) -> Result<(), wry::Error> {
+        let webview = webview.controller().CoreWebView2().map_err(WindowsError)?;
+        let token = &mut EventRegistrationToken::default();
+        let handler = WebMessageReceivedEventHandler::create(Box::new(move |_webview, args| {
+            if let Some(args) = args {
+                // NOTE: bridge messages are posted as `{ [name]: message }` objects rather than
+                // strings so that they are ignored by the wry IPC handler, which only accepts
+                // string messages.
+                let json = &mut PWSTR::null();
+                args.WebMessageAsJson(json)?;
+                let object = json.to_string().ok().and_then(|json| serde_json::from_str(&json).ok());
+                if let Some(serde_json::Value::Object(mut object)) = object {
+                    if let Some(serde_json::Value::String(body)) = object.remove(name) {
+                        // NOTE: `WebMessageReceived` of the webview is only raised for messages of the
+                        // main frame, since subframes post theirs to their own `CoreWebView2Frame`.
+                        let source = &mut PWSTR::null();
+                        args.Source(source)?;
+                        let url = source.to_string().ok().and_then(|source| Url::parse(&source).ok());
+                        handler(ScriptMessage {
+                            body,
+                            main_frame: true,
+                            url,
+                        });
+                    }
+                }
+            }
+            Ok(())
+        }));
+        webview.add_WebMessageReceived(&handler, token).map_err(WindowsError)?;
+        Ok(())
+    }
+
+    let (call_tx, call_rx) = oneshot::channel();
+    window
+        .with_webview(move |webview| unsafe {
+            let result = run(webview, name, handler).map_err(Into::<BoxError>::into);
+            call_tx.send(result).ok();
+        })
+        .map_err(Into::<BoxError>::into)?;
+    call_rx.await?
+}
+
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window, handler),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn add_load_error_handler(
+    window: &Window,
+    handler: impl Fn(LoadError) -> bool + Send + Sync + 'static,
+) -> BoxResult<()> {
+    unsafe fn run(webview: PlatformWebview, handler: impl Fn(LoadError) -> bool + 'static) -> Result<(), wry::Error> {
+        let webview = webview.controller().CoreWebView2().map_err(WindowsError)?;
+        let token = &mut EventRegistrationToken::default();
+        let handler = NavigationCompletedEventHandler::create(Box::new(move |webview, args| {
+            if let (Some(webview), Some(args)) = (webview, args) {
+                let uri = &mut PWSTR::null();
+                webview.Source(uri)?;
+                let uri = uri.to_string().ok();
+                if let Some(error) = load_error_from_navigation(uri.as_deref(), &args)? {
+                    handler(error);
+                }
+            }
+            Ok(())
+        }));
+        webview.add_NavigationCompleted(&handler, token).map_err(WindowsError)?;
+        Ok(())
+    }
+
+    let (call_tx, call_rx) = oneshot::channel();
+    window
+        .with_webview(move |webview| unsafe {
+            let result = run(webview, handler).map_err(Into::<BoxError>::into);
+            call_tx.send(result).ok();
+        })
+        .map_err(Into::<BoxError>::into)?;
+    call_rx.await?
+}
+
+// NOTE: downloads are handled, so the download dialog of WebView2 is not shown, and they are deferred
+// until their destination is decided.
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window, observer),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn add_download_observer(
+    window: &Window,
+    observer: impl Fn(DownloadUpdate) + Send + Sync + 'static,
+) -> BoxResult<()> {
+    type Observer = std::sync::Arc<dyn Fn(DownloadUpdate) + Send + Sync>;
+
+    unsafe fn observe(
+        operation: &ICoreWebView2DownloadOperation,
+        id: u64,
+        observer: Observer,
+    ) -> windows::core::Result<()> {
+        let token = &mut EventRegistrationToken::default();
+        let handler = BytesReceivedChangedEventHandler::create(Box::new({
+            let observer = observer.clone();
+            move |operation, _args| {
+                if let Some(operation) = operation {
+                    let received_bytes = &mut 0;
+                    operation.BytesReceived(received_bytes)?;
+                    let total_bytes = &mut 0;
+                    operation.TotalBytesToReceive(total_bytes)?;
+                    observer(DownloadUpdate::Event(DownloadEvent::Progress {
+                        id,
+                        received_bytes: *received_bytes as u64,
+                        total_bytes: Some(*total_bytes as u64).filter(|_| *total_bytes > 0),
+                    }));
+                }
+                Ok(())
+            }
+        }));
+        operation.add_BytesReceivedChanged(&handler, token)?;
+        let handler = StateChangedEventHandler::create(Box::new(move |operation, _args| {
+            if let Some(operation) = operation {
+                let state = &mut COREWEBVIEW2_DOWNLOAD_STATE::default();
+                operation.State(state)?;
+                let event = if *state == COREWEBVIEW2_DOWNLOAD_STATE_COMPLETED {
+                    let path = &mut PWSTR::null();
+                    operation.ResultFilePath(path)?;
+                    let destination = path.to_string().ok().map(PathBuf::from);
+                    DownloadEvent::Finished { id, destination }
+                } else if *state == COREWEBVIEW2_DOWNLOAD_STATE_INTERRUPTED {
+                    let reason = &mut COREWEBVIEW2_DOWNLOAD_INTERRUPT_REASON::default();
+                    operation.InterruptReason(reason)?;
+                    if *reason == COREWEBVIEW2_DOWNLOAD_INTERRUPT_REASON_USER_PAUSED {
+                        return Ok(());
+                    } else if *reason == COREWEBVIEW2_DOWNLOAD_INTERRUPT_REASON_USER_CANCELED {
+                        DownloadEvent::Cancelled { id }
+                    } else {
+                        let reason = format!("download interrupted with reason {}", reason.0);
+                        DownloadEvent::Failed { id, reason }
+                    }
+                } else {
+                    return Ok(());
+                };
+                observer(DownloadUpdate::Event(event));
+            }
+            Ok(())
+        }));
+        operation.add_StateChanged(&handler, token)?;
+        Ok(())
+    }
+
+    unsafe fn run(window: Window, webview: PlatformWebview, observer: Observer) -> Result<(), wry::Error> {
+        let webview = webview.controller().CoreWebView2().map_err(WindowsError)?;
+        let webview = Interface::cast::<ICoreWebView2_4>(&webview).map_err(WindowsError)?;
+        let token = &mut EventRegistrationToken::default();
+        let handler = DownloadStartingEventHandler::create(Box::new(move |_webview, args| {
+            if let Some(args) = args {
+                let operation = args.DownloadOperation()?;
+                let uri = &mut PWSTR::null();
+                operation.Uri(uri)?;
+                let url = match uri.to_string().ok().and_then(|uri| Url::parse(&uri).ok()) {
+                    Some(url) => url,
+                    None => return Ok(()),
+                };
+                let path = &mut PWSTR::null();
+                args.ResultFilePath(path)?;
+                let suggested_filename = path
+                    .to_string()
+                    .ok()
+                    .and_then(|path| Some(PathBuf::from(path).file_name()?.to_string_lossy().into_owned()));
+                let id = downloads::next_id();
+                observe(&operation, id, observer.clone())?;
+                args.SetHandled(true)?;
+                let deferral = args.GetDeferral()?;
+                let (decision_tx, decision_rx) = oneshot::channel();
+                observer(DownloadUpdate::Started {
+                    id,
+                    download: NativeDownload(operation),
+                    url,
+                    suggested_filename,
+                    decision: decision_tx,
+                });
+                let pending = MainThreadBound((args, deferral));
+                let window = window.clone();
+                let observer = observer.clone();
+                tauri::async_runtime::spawn(async move {
+                    let decision = decision_rx.await.unwrap_or_default();
+                    window
+                        .run_on_main_thread(move || {
+                            let MainThreadBound((args, deferral)) = pending;
+                            match decision {
+                                DownloadDecision::Default => {},
+                                DownloadDecision::Destination(path) => {
+                                    let path = HSTRING::from(path.to_string_lossy().as_ref());
+                                    args.SetResultFilePath(&path).ok();
+                                },
+                                DownloadDecision::Cancel => {
+                                    args.SetCancel(true).ok();
+                                    observer(DownloadUpdate::Event(DownloadEvent::Cancelled { id }));
+                                },
+                            }
+                            deferral.Complete().ok();
+                        })
+                        .ok();
+                });
+            }
+            Ok(())
+        }));
+        webview.add_DownloadStarting(&handler, token).map_err(WindowsError)?;
+        Ok(())
+    }
+
+    let (call_tx, call_rx) = oneshot::channel();
+    window
+        .with_webview({
+            let window = window.clone();
+            move |webview| unsafe {
+                let result = run(window, webview, std::sync::Arc::new(observer)).map_err(Into::<BoxError>::into);
+                call_tx.send(result).ok();
+            }
+        })
+        .map_err(Into::<BoxError>::into)?;
+    call_rx.await?
+}
+
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window, download),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn cancel_download(window: &Window, download: NativeDownload) -> BoxResult<()> {
+    with_download(window, download, |operation| unsafe { operation.Cancel() }).await
+}
+
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window, download),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn pause_download(window: &Window, download: NativeDownload) -> BoxResult<()> {
+    with_download(window, download, |operation| unsafe { operation.Pause() }).await
+}
+
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window, download),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn resume_download(window: &Window, download: NativeDownload) -> BoxResult<()> {
+    with_download(window, download, |operation| unsafe { operation.Resume() }).await
+}
+
+async fn with_download(
+    window: &Window,
+    download: NativeDownload,
+    call: impl FnOnce(&ICoreWebView2DownloadOperation) -> windows::core::Result<()> + Send + 'static,
+) -> BoxResult<()> {
+    let (call_tx, call_rx) = oneshot::channel();
+    window
+        .run_on_main_thread(move || {
+            let result = call(&download.0).map_err(Into::<BoxError>::into);
+            call_tx.send(result).ok();
+        })
+        .map_err(Into::<BoxError>::into)?;
+    call_rx.await?
+}
+
+// NOTE: `ContentLoading` is raised once the response is committed and before the document loads,
+// which is the closest match to a committed navigation.
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window, observer),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn add_navigation_observer(
+    window: &Window,
+    observer: impl Fn(NavigationEvent) + Send + Sync + 'static,
+) -> BoxResult<()> {
+    unsafe fn run(webview: PlatformWebview, observer: impl Fn(NavigationEvent) + 'static) -> Result<(), wry::Error> {
+        unsafe fn source(webview: &ICoreWebView2) -> windows::core::Result<Option<Url>> {
+            let uri = &mut PWSTR::null();
+            webview.Source(uri)?;
+            Ok(uri.to_string().ok().and_then(|uri| Url::parse(&uri).ok()))
+        }
+
+        let webview = webview.controller().CoreWebView2().map_err(WindowsError)?;
+        let observer = Rc::new(observer);
+        let token = &mut EventRegistrationToken::default();
+        let handler = NavigationStartingEventHandler::create(Box::new({
+            let observer = observer.clone();
+            move |_webview, args| {
+                if let Some(args) = args {
+                    let uri = &mut PWSTR::null();
+                    args.Uri(uri)?;
+                    if let Some(url) = uri.to_string().ok().and_then(|uri| Url::parse(&uri).ok()) {
+                        observer(NavigationEvent::Started { url });
+                    }
+                }
+                Ok(())
+            }
+        }));
+        webview.add_NavigationStarting(&handler, token).map_err(WindowsError)?;
+        let handler = ContentLoadingEventHandler::create(Box::new({
+            let observer = observer.clone();
+            move |webview, _args| {
+                if let Some(url) = webview.map(|webview| source(&webview)).transpose()?.flatten() {
+                    observer(NavigationEvent::Committed { url });
+                }
+                Ok(())
+            }
+        }));
+        webview.add_ContentLoading(&handler, token).map_err(WindowsError)?;
+        let handler = NavigationCompletedEventHandler::create(Box::new(move |webview, args| {
+            if let (Some(webview), Some(args)) = (webview, args) {
+                let is_success = &mut BOOL::default();
+                args.IsSuccess(is_success)?;
+                if let Some(url) = source(&webview)?.filter(|_| is_success.as_bool()) {
+                    observer(NavigationEvent::Finished { url });
+                }
+            }
+            Ok(())
+        }));
+        webview.add_NavigationCompleted(&handler, token).map_err(WindowsError)?;
+        Ok(())
+    }
+
+    let (call_tx, call_rx) = oneshot::channel();
+    window
+        .with_webview(move |webview| unsafe {
+            let result = run(webview, observer).map_err(Into::<BoxError>::into);
+            call_tx.send(result).ok();
+        })
+        .map_err(Into::<BoxError>::into)?;
+    call_rx.await?
+}
+
+// NOTE: unresponsive render processes recover on their own, so only exited processes are reported.
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window, handler),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn add_process_crash_handler(
+    window: &Window,
+    handler: impl Fn() + Send + Sync + 'static,
+) -> BoxResult<()> {
+    unsafe fn run(webview: PlatformWebview, handler: impl Fn() + 'static) -> Result<(), wry::Error> {
+        let webview = webview.controller().CoreWebView2().map_err(WindowsError)?;
+        let token = &mut EventRegistrationToken::default();
+        let handler = ProcessFailedEventHandler::create(Box::new(move |_webview, args| {
+            if let Some(args) = args {
+                let kind = &mut COREWEBVIEW2_PROCESS_FAILED_KIND::default();
+                args.ProcessFailedKind(kind)?;
+                if *kind != COREWEBVIEW2_PROCESS_FAILED_KIND_RENDER_PROCESS_UNRESPONSIVE {
+                    handler();
+                }
+            }
+            Ok(())
+        }));
+        webview.add_ProcessFailed(&handler, token).map_err(WindowsError)?;
+        Ok(())
+    }
+
+    let (call_tx, call_rx) = oneshot::channel();
+    window
+        .with_webview(move |webview| unsafe {
+            let result = run(webview, handler).map_err(Into::<BoxError>::into);
+            call_tx.send(result).ok();
+        })
+        .map_err(Into::<BoxError>::into)?;
+    call_rx.await?
+}
+
+// NOTE: the camera and the microphone are requested separately, so `CameraAndMicrophone` is never
+// reported.
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window, handler),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn add_permission_handler(
+    window: &Window,
+    handler: impl Fn(PermissionRequest) -> PermissionDecision + Send + Sync + 'static,
+) -> BoxResult<()> {
+    unsafe fn run(
+        webview: PlatformWebview,
+        handler: impl Fn(PermissionRequest) -> PermissionDecision + 'static,
+    ) -> Result<(), wry::Error> {
+        let webview = webview.controller().CoreWebView2().map_err(WindowsError)?;
+        let token = &mut EventRegistrationToken::default();
+        let handler = PermissionRequestedEventHandler::create(Box::new(move |_webview, args| {
+            if let Some(args) = args {
+                let kind = &mut COREWEBVIEW2_PERMISSION_KIND::default();
+                args.PermissionKind(kind)?;
+                let kind = match *kind {
+                    COREWEBVIEW2_PERMISSION_KIND_CAMERA => PermissionKind::Camera,
+                    COREWEBVIEW2_PERMISSION_KIND_MICROPHONE => PermissionKind::Microphone,
+                    COREWEBVIEW2_PERMISSION_KIND_GEOLOCATION => PermissionKind::Geolocation,
+                    COREWEBVIEW2_PERMISSION_KIND_NOTIFICATIONS => PermissionKind::Notifications,
+                    _ => PermissionKind::Other,
+                };
+                let uri = &mut PWSTR::null();
+                args.Uri(uri)?;
+                let origin = uri.to_string().ok().and_then(|uri| crate::permission::origin(&uri));
+                match handler(PermissionRequest { kind, origin }) {
+                    PermissionDecision::Default => {},
+                    PermissionDecision::Allow => args.SetState(COREWEBVIEW2_PERMISSION_STATE_ALLOW)?,
+                    PermissionDecision::Deny => args.SetState(COREWEBVIEW2_PERMISSION_STATE_DENY)?,
+                }
+            }
+            Ok(())
+        }));
+        webview.add_PermissionRequested(&handler, token).map_err(WindowsError)?;
+        Ok(())
+    }
+
+    let (call_tx, call_rx) = oneshot::channel();
+    window
+        .with_webview(move |webview| unsafe {
+            let result = run(webview, handler).map_err(Into::<BoxError>::into);
+            call_tx.send(result).ok();
+        })
+        .map_err(Into::<BoxError>::into)?;
+    call_rx.await?
+}
+
+// NOTE: an accepted certificate is allowed for the host until the app exits.
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window, handler),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn add_certificate_error_handler(
+    window: &Window,
+    handler: impl Fn(CertificateErrorInfo) -> CertDecision + Send + Sync + 'static,
+) -> BoxResult<()> {
+    unsafe fn run(
+        webview: PlatformWebview,
+        handler: impl Fn(CertificateErrorInfo) -> CertDecision + 'static,
+    ) -> Result<(), wry::Error> {
+        let webview = webview.controller().CoreWebView2().map_err(WindowsError)?;
+        let webview = Interface::cast::<ICoreWebView2_14>(&webview).map_err(WindowsError)?;
+        let token = &mut EventRegistrationToken::default();
+        let handler = ServerCertificateErrorDetectedEventHandler::create(Box::new(move |_webview, args| {
+            if let Some(args) = args {
+                let uri = &mut PWSTR::null();
+                args.RequestUri(uri)?;
+                let url = match uri.to_string().ok().and_then(|uri| Url::parse(&uri).ok()) {
+                    Some(url) => url,
+                    None => return Ok(()),
+                };
+                let status = &mut COREWEBVIEW2_WEB_ERROR_STATUS::default();
+                args.ErrorStatus(status)?;
+                let kind = match *status {
+                    COREWEBVIEW2_WEB_ERROR_STATUS_CERTIFICATE_EXPIRED => CertificateErrorKind::Expired,
+                    COREWEBVIEW2_WEB_ERROR_STATUS_CERTIFICATE_COMMON_NAME_IS_INCORRECT => {
+                        CertificateErrorKind::HostMismatch
+                    },
+                    COREWEBVIEW2_WEB_ERROR_STATUS_CERTIFICATE_REVOKED => CertificateErrorKind::Revoked,
+                    COREWEBVIEW2_WEB_ERROR_STATUS_CERTIFICATE_IS_INVALID => CertificateErrorKind::Invalid,
+                    _ => CertificateErrorKind::Other,
+                };
+                if handler(CertificateErrorInfo { url, kind }) == CertDecision::Proceed {
+                    args.SetAction(COREWEBVIEW2_SERVER_CERTIFICATE_ERROR_ACTION_ALWAYS_ALLOW)?;
+                }
+            }
+            Ok(())
+        }));
+        webview
+            .add_ServerCertificateErrorDetected(&handler, token)
+            .map_err(WindowsError)?;
+        Ok(())
+    }
+
+    let (call_tx, call_rx) = oneshot::channel();
+    window
+        .with_webview(move |webview| unsafe {
+            let result = run(webview, handler).map_err(Into::<BoxError>::into);
+            call_tx.send(result).ok();
+        })
+        .map_err(Into::<BoxError>::into)?;
+    call_rx.await?
+}
+
+// NOTE: WebView2 only raises basic authentication for servers, proxies ask for credentials in a
+// dialog of their own. The request is deferred while the handler runs, and answered from the main
+// thread afterwards.
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window, handler),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn add_auth_handler(window: &Window, handler: AuthHandler) -> BoxResult<()> {
+    unsafe fn run(window: Window, webview: PlatformWebview, handler: AuthHandler) -> Result<(), wry::Error> {
+        let webview = webview.controller().CoreWebView2().map_err(WindowsError)?;
+        let webview = Interface::cast::<ICoreWebView2_10>(&webview).map_err(WindowsError)?;
+        let token = &mut EventRegistrationToken::default();
+        let handler = BasicAuthenticationRequestedEventHandler::create(Box::new(move |_webview, args| {
+            if let Some(args) = args {
+                let uri = &mut PWSTR::null();
+                args.Uri(uri)?;
+                let url = match uri.to_string().ok().and_then(|uri| Url::parse(&uri).ok()) {
+                    Some(url) => url,
+                    None => return Ok(()),
+                };
+                let challenge = &mut PWSTR::null();
+                args.Challenge(challenge)?;
+                let realm = challenge.to_string().ok().and_then(|challenge| {
+                    let (_, realm) = challenge.split_once("realm=")?;
+                    Some(realm.trim_start_matches('"').split('"').next()?.to_string())
+                });
+                let challenge = AuthChallenge {
+                    host: url.host_str().unwrap_or_default().into(),
+                    port: url.port_or_known_default().unwrap_or_default(),
+                    url: Some(url),
+                    realm,
+                    is_proxy: false,
+                };
+                let credentials = handler(challenge);
+                let deferral = args.GetDeferral()?;
+                let pending = MainThreadBound((args, deferral));
+                let window = window.clone();
+                tauri::async_runtime::spawn(async move {
+                    let credentials = credentials.await;
+                    window
+                        .run_on_main_thread(move || {
+                            let MainThreadBound((args, deferral)) = pending;
+                            match credentials {
+                                Some(Credentials { username, password }) => {
+                                    if let Ok(response) = args.Response() {
+                                        response.SetUserName(&HSTRING::from(username)).ok();
+                                        response.SetPassword(&HSTRING::from(password)).ok();
+                                    }
+                                },
+                                None => {
+                                    args.SetCancel(true).ok();
+                                },
+                            }
+                            deferral.Complete().ok();
+                        })
+                        .ok();
+                });
+            }
+            Ok(())
+        }));
+        webview
+            .add_BasicAuthenticationRequested(&handler, token)
+            .map_err(WindowsError)?;
+        Ok(())
+    }
+
+    let (call_tx, call_rx) = oneshot::channel();
+    window
+        .with_webview({
+            let window = window.clone();
+            move |webview| unsafe {
+                let result = run(window, webview, handler).map_err(Into::<BoxError>::into);
+                call_tx.send(result).ok();
+            }
+        })
+        .map_err(Into::<BoxError>::into)?;
+    call_rx.await?
+}
+
+// NOTE: WebView2 can not go back to its default user agent by itself, so the user agent it had
+// before the first override is restored instead.
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn set_user_agent(window: &Window, user_agent: Option<String>) -> BoxResult<()> {
+    unsafe fn run(webview: PlatformWebview, user_agent: String) -> Result<(), wry::Error> {
+        let webview = webview.controller().CoreWebView2().map_err(WindowsError)?;
+        let settings = webview
+            .Settings()
+            .and_then(|settings| settings.cast::<ICoreWebView2Settings2>())
+            .map_err(WindowsError)?;
+        settings
+            .SetUserAgent(&HSTRING::from(user_agent))
+            .map_err(WindowsError)?;
+        Ok(())
+    }
+
+    let state = state::window_state(window)?;
+    let default = state.default_user_agent.lock()?.clone();
+    let user_agent = match (user_agent, default) {
+        (Some(user_agent), Some(_)) => user_agent,
+        (Some(user_agent), None) => {
+            let default = self::user_agent(window).await?;
+            *state.default_user_agent.lock()? = Some(default);
+            user_agent
+        },
+        (None, Some(default)) => default,
+        (None, None) => return Ok(()),
+    };
+    let (call_tx, call_rx) = oneshot::channel();
+    window
+        .with_webview(move |webview| unsafe {
+            let result = run(webview, user_agent).map_err(Into::<BoxError>::into);
+            call_tx.send(result).ok();
+        })
+        .map_err(Into::<BoxError>::into)?;
+    call_rx.await?
+}
+
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn user_agent(window: &Window) -> BoxResult<String> {
+    unsafe fn run(webview: PlatformWebview) -> Result<Option<String>, wry::Error> {
+        let webview = webview.controller().CoreWebView2().map_err(WindowsError)?;
+        let settings = webview
+            .Settings()
+            .and_then(|settings| settings.cast::<ICoreWebView2Settings2>())
+            .map_err(WindowsError)?;
+        let user_agent = &mut PWSTR::null();
+        settings.UserAgent(user_agent).map_err(WindowsError)?;
+        Ok(user_agent.to_string().ok())
+    }
+
+    let (call_tx, call_rx) = oneshot::channel();
+    window
+        .with_webview(move |webview| unsafe {
+            let result = run(webview)
+                .map_err(Into::<BoxError>::into)
+                .and_then(|user_agent| user_agent.ok_or_else(|| "user agent is not valid UTF-16".into()));
+            call_tx.send(result).ok();
+        })
+        .map_err(Into::<BoxError>::into)?;
+    call_rx.await?
+}
+
+// NOTE: the zoom factor is kept by the controller across navigations.
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn set_zoom(window: &Window, factor: f64) -> BoxResult<()> {
+    unsafe fn run(webview: PlatformWebview, factor: f64) -> Result<(), wry::Error> {
+        webview.controller().SetZoomFactor(factor).map_err(WindowsError)?;
+        Ok(())
+    }
+
+    let (call_tx, call_rx) = oneshot::channel();
+    window
+        .with_webview(move |webview| unsafe {
+            let result = run(webview, factor).map_err(Into::<BoxError>::into);
+            call_tx.send(result).ok();
+        })
+        .map_err(Into::<BoxError>::into)?;
+    call_rx.await?
+}
+
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn zoom(window: &Window) -> BoxResult<f64> {
+    unsafe fn run(webview: PlatformWebview) -> Result<f64, wry::Error> {
+        let factor = &mut f64::default();
+        webview.controller().ZoomFactor(factor).map_err(WindowsError)?;
+        Ok(*factor)
+    }
+
+    let (call_tx, call_rx) = oneshot::channel();
+    window
+        .with_webview(move |webview| unsafe {
+            let result = run(webview).map_err(Into::<BoxError>::into);
+            call_tx.send(result).ok();
+        })
+        .map_err(Into::<BoxError>::into)?;
+    call_rx.await?
+}
+
+// NOTE: `NavigationStarting` is only raised for the main frame, before its request is sent. The
+// user agent the webview had when this was called is restored for navigations without one.
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window, policy),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn add_navigation_policy(
+    window: &Window,
+    policy: impl Fn(&str) -> NavigationDecision + Send + Sync + 'static,
+) -> BoxResult<()> {
+    unsafe fn run(
+        webview: PlatformWebview,
+        policy: impl Fn(&str) -> NavigationDecision + 'static,
+    ) -> Result<(), wry::Error> {
+        let webview = webview.controller().CoreWebView2().map_err(WindowsError)?;
+        let settings = webview
+            .Settings()
+            .and_then(|settings| settings.cast::<ICoreWebView2Settings2>())
+            .map_err(WindowsError)?;
+        let default = &mut PWSTR::null();
+        settings.UserAgent(default).map_err(WindowsError)?;
+        let default = default.to_string().ok();
+        let token = &mut EventRegistrationToken::default();
+        let handler = NavigationStartingEventHandler::create(Box::new(move |webview, args| {
+            if let (Some(webview), Some(args)) = (webview, args) {
+                let uri = &mut PWSTR::null();
+                args.Uri(uri)?;
+                if let Ok(uri) = uri.to_string() {
+                    let NavigationDecision { policy, user_agent } = policy(&uri);
+                    match policy {
+                        NavigationPolicy::Allow => {
+                            if let Some(user_agent) = user_agent.or_else(|| default.clone()) {
+                                settings.SetUserAgent(&HSTRING::from(user_agent))?;
+                            }
+                        },
+                        NavigationPolicy::Redirect(url) => {
+                            args.SetCancel(true)?;
+                            webview.Navigate(&HSTRING::from(url))?;
+                        },
+                        NavigationPolicy::Cancel => {
+                            args.SetCancel(true)?;
+                        },
+                    }
+                }
+            }
+            Ok(())
+        }));
+        webview.add_NavigationStarting(&handler, token).map_err(WindowsError)?;
+        Ok(())
+    }
+
+    let (call_tx, call_rx) = oneshot::channel();
+    window
+        .with_webview(move |webview| unsafe {
+            let result = run(webview, policy).map_err(Into::<BoxError>::into);
+            call_tx.send(result).ok();
+        })
+        .map_err(Into::<BoxError>::into)?;
+    call_rx.await?
+}
+
+// NOTE: requests are matched against the blocked URLs in the handler, so the filter and handler are
+// only added once per window and later changes to the blocked URLs apply immediately.
+#[allow(unused_variables)]
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn set_blocked_urls(window: &Window, patterns: Vec<String>) -> BoxResult<()> {
+    unsafe fn run(webview: PlatformWebview, window: Window) -> Result<(), wry::Error> {
+        let webview = webview.controller().CoreWebView2().map_err(WindowsError)?;
+        let environment = Interface::cast::<ICoreWebView2_2>(&webview)
+            .and_then(|webview| webview.Environment())
+            .map_err(WindowsError)?;
+        let token = &mut EventRegistrationToken::default();
+        let handler = WebResourceRequestedEventHandler::create(Box::new(move |_webview, args| {
+            if let Some(args) = args {
+                let uri = &mut PWSTR::null();
+                args.Request()?.Uri(uri)?;
+                let uri = uri.to_string().unwrap_or_default();
+                if url_filter::is_blocked(&window, &uri) {
+                    let reason = &HSTRING::from("Forbidden");
+                    let headers = &HSTRING::new();
+                    let response = environment.CreateWebResourceResponse(InParam::null(), 403, reason, headers)?;
+                    args.SetResponse(&response)?;
+                }
+            }
+            Ok(())
+        }));
+        let uri = &HSTRING::from("*");
+        webview
+            .AddWebResourceRequestedFilter(uri, COREWEBVIEW2_WEB_RESOURCE_CONTEXT_ALL)
+            .map_err(WindowsError)?;
+        webview
+            .add_WebResourceRequested(&handler, token)
+            .map_err(WindowsError)?;
+        Ok(())
+    }
+
+    let state = state::window_state(window)?;
+    let Some(install) = bridge::install_once(&state, "url-filter").await? else {
+        return Ok(());
+    };
+    let (call_tx, call_rx) = oneshot::channel();
+    window
+        .with_webview({
+            let window = window.clone();
+            move |webview| unsafe {
+                let result = run(webview, window).map_err(Into::<BoxError>::into);
+                call_tx.send(result).ok();
+            }
+        })
+        .map_err(Into::<BoxError>::into)?;
+    call_rx.await??;
+    install.done();
+    Ok(())
+}
+
+// NOTE: WebView2 has no content blockers, so requests are matched against the rules in a resource
+// handler, which is added once per window. Rules that hide elements or change requests are not
+// applied.
+#[allow(unused_variables)]
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window, rules),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn set_content_rules(window: &Window, rules: Arc<ContentRuleList>) -> BoxResult<()> {
+    unsafe fn run(webview: PlatformWebview, window: Window) -> Result<(), wry::Error> {
+        let webview = webview.controller().CoreWebView2().map_err(WindowsError)?;
+        let environment = Interface::cast::<ICoreWebView2_2>(&webview)
+            .and_then(|webview| webview.Environment())
+            .map_err(WindowsError)?;
+        let token = &mut EventRegistrationToken::default();
+        let handler = WebResourceRequestedEventHandler::create(Box::new(move |webview, args| {
+            if let (Some(webview), Some(args)) = (webview, args) {
+                let uri = &mut PWSTR::null();
+                args.Request()?.Uri(uri)?;
+                let uri = uri.to_string().unwrap_or_default();
+                let source = &mut PWSTR::null();
+                webview.Source(source)?;
+                let source = source.to_string().ok().and_then(|source| Url::parse(&source).ok());
+                let context = &mut COREWEBVIEW2_WEB_RESOURCE_CONTEXT::default();
+                args.ResourceContext(context)?;
+                let resource_type = content_resource_type(*context);
+                if content_rules::is_blocked(&window, &uri, source.as_ref(), resource_type) {
+                    let reason = &HSTRING::from("Forbidden");
+                    let headers = &HSTRING::new();
+                    let response = environment.CreateWebResourceResponse(InParam::null(), 403, reason, headers)?;
+                    args.SetResponse(&response)?;
+                }
+            }
+            Ok(())
+        }));
+        let uri = &HSTRING::from("*");
+        webview
+            .AddWebResourceRequestedFilter(uri, COREWEBVIEW2_WEB_RESOURCE_CONTEXT_ALL)
+            .map_err(WindowsError)?;
+        webview
+            .add_WebResourceRequested(&handler, token)
+            .map_err(WindowsError)?;
+        Ok(())
+    }
+
+    let state = state::window_state(window)?;
+    let Some(install) = bridge::install_once(&state, "content-rules").await? else {
+        return Ok(());
+    };
+    let (call_tx, call_rx) = oneshot::channel();
+    window
+        .with_webview({
+            let window = window.clone();
+            move |webview| unsafe {
+                let result = run(webview, window).map_err(Into::<BoxError>::into);
+                call_tx.send(result).ok();
+            }
+        })
+        .map_err(Into::<BoxError>::into)?;
+    call_rx.await??;
+    install.done();
+    Ok(())
+}
+
+// NOTE: requests made by scripts (`fetch`, XHR, WebSocket, and others) are what WebKit calls `raw`.
+fn content_resource_type(context: COREWEBVIEW2_WEB_RESOURCE_CONTEXT) -> Option<ResourceType> {
+    match context {
+        COREWEBVIEW2_WEB_RESOURCE_CONTEXT_DOCUMENT => Some(ResourceType::Document),
+        COREWEBVIEW2_WEB_RESOURCE_CONTEXT_STYLESHEET => Some(ResourceType::StyleSheet),
+        COREWEBVIEW2_WEB_RESOURCE_CONTEXT_IMAGE => Some(ResourceType::Image),
+        COREWEBVIEW2_WEB_RESOURCE_CONTEXT_MEDIA | COREWEBVIEW2_WEB_RESOURCE_CONTEXT_TEXT_TRACK => {
+            Some(ResourceType::Media)
+        },
+        COREWEBVIEW2_WEB_RESOURCE_CONTEXT_FONT => Some(ResourceType::Font),
+        COREWEBVIEW2_WEB_RESOURCE_CONTEXT_SCRIPT => Some(ResourceType::Script),
+        COREWEBVIEW2_WEB_RESOURCE_CONTEXT_XML_HTTP_REQUEST
+        | COREWEBVIEW2_WEB_RESOURCE_CONTEXT_FETCH
+        | COREWEBVIEW2_WEB_RESOURCE_CONTEXT_EVENT_SOURCE
+        | COREWEBVIEW2_WEB_RESOURCE_CONTEXT_WEBSOCKET
+        | COREWEBVIEW2_WEB_RESOURCE_CONTEXT_PING => Some(ResourceType::Raw),
+        _ => None,
+    }
+}
+
+// NOTE: failed requests are only reported with their id by the DevTools protocol, so the URLs of
+// requests are tracked from `Network.requestWillBeSent` until they finish or fail.
+// NOTE: hiding every item hides the toolbar as a whole.
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn set_pdf_settings(window: &Window, settings: PdfViewerSettings) -> BoxResult<()> {
+    unsafe fn run(webview: PlatformWebview, settings: PdfViewerSettings) -> Result<(), wry::Error> {
+        let webview = webview.controller().CoreWebView2().map_err(WindowsError)?;
+        let webview_settings = webview
+            .Settings()
+            .and_then(|settings| settings.cast::<ICoreWebView2Settings7>())
+            .map_err(WindowsError)?;
+        let mut items = COREWEBVIEW2_PDF_TOOLBAR_ITEMS_NONE;
+        if !settings.toolbar {
+            items |= COREWEBVIEW2_PDF_TOOLBAR_ITEMS_SAVE
+                | COREWEBVIEW2_PDF_TOOLBAR_ITEMS_PRINT
+                | COREWEBVIEW2_PDF_TOOLBAR_ITEMS_SAVE_AS
+                | COREWEBVIEW2_PDF_TOOLBAR_ITEMS_ZOOM_IN
+                | COREWEBVIEW2_PDF_TOOLBAR_ITEMS_ZOOM_OUT
+                | COREWEBVIEW2_PDF_TOOLBAR_ITEMS_ROTATE
+                | COREWEBVIEW2_PDF_TOOLBAR_ITEMS_FIT_PAGE
+                | COREWEBVIEW2_PDF_TOOLBAR_ITEMS_PAGE_LAYOUT
+                | COREWEBVIEW2_PDF_TOOLBAR_ITEMS_BOOKMARKS
+                | COREWEBVIEW2_PDF_TOOLBAR_ITEMS_PAGE_SELECTOR
+                | COREWEBVIEW2_PDF_TOOLBAR_ITEMS_SEARCH;
+        }
+        if !settings.download_allowed {
+            items |= COREWEBVIEW2_PDF_TOOLBAR_ITEMS_SAVE | COREWEBVIEW2_PDF_TOOLBAR_ITEMS_SAVE_AS;
+        }
+        webview_settings.SetHiddenPdfToolbarItems(items).map_err(WindowsError)?;
+        Ok(())
+    }
+
+    let (call_tx, call_rx) = oneshot::channel();
+    window
+        .with_webview(move |webview| unsafe {
+            let result = run(webview, settings).map_err(Into::<BoxError>::into);
+            call_tx.send(result).ok();
+        })
+        .map_err(Into::<BoxError>::into)?;
+    call_rx.await?
+}
+
+// NOTE: WebView2 prints documents itself, and `ShowPrintUI` is not available in the bindings used
+// by wry, so printing falls back to the original `window.print()` of the document.
+#[allow(unused_variables)]
+pub(crate) async fn print(window: &Window) -> BoxResult<bool> {
+    Ok(false)
+}
+
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn print_to_pdf(window: &Window, path: PathBuf, options: PdfOptions) -> BoxResult<()> {
+    unsafe fn run(
+        webview: PlatformWebview,
+        path: PathBuf,
+        options: PdfOptions,
+        done_tx: oneshot::Sender<bool>,
+    ) -> Result<(), wry::Error> {
+        let webview = webview.controller().CoreWebView2().map_err(WindowsError)?;
+        let environment = Interface::cast::<ICoreWebView2_2>(&webview)
+            .and_then(|webview| webview.Environment())
+            .and_then(|environment| environment.cast::<ICoreWebView2Environment6>())
+            .map_err(WindowsError)?;
+        let webview = Interface::cast::<ICoreWebView2_7>(&webview).map_err(WindowsError)?;
+        let settings = environment.CreatePrintSettings().map_err(WindowsError)?;
+        let orientation = if options.landscape {
+            COREWEBVIEW2_PRINT_ORIENTATION_LANDSCAPE
+        } else {
+            COREWEBVIEW2_PRINT_ORIENTATION_PORTRAIT
+        };
+        settings.SetOrientation(orientation).map_err(WindowsError)?;
+        settings.SetScaleFactor(options.scale).map_err(WindowsError)?;
+        settings.SetPageWidth(options.page_width).map_err(WindowsError)?;
+        settings.SetPageHeight(options.page_height).map_err(WindowsError)?;
+        settings.SetMarginTop(options.margin_top).map_err(WindowsError)?;
+        settings.SetMarginBottom(options.margin_bottom).map_err(WindowsError)?;
+        settings.SetMarginLeft(options.margin_left).map_err(WindowsError)?;
+        settings.SetMarginRight(options.margin_right).map_err(WindowsError)?;
+        settings
+            .SetShouldPrintBackgrounds(options.print_background)
+            .map_err(WindowsError)?;
+        PrintToPdfCompletedHandler::wait_for_async_operation(
+            Box::new(move |handler| {
+                let path = &HSTRING::from(path.as_os_str());
+                webview.PrintToPdf(path, &settings, &handler)?;
+                Ok(())
+            }),
+            Box::new(|hresult, success| {
+                #[cfg(feature = "tracing")]
+                crate::trace::callback("PrintToPdfCompleted");
+                hresult?;
+                done_tx.send(success.as_bool()).ok();
+                Ok(())
+            }),
+        )?;
+        Ok(())
+    }
+
+    let (done_tx, done_rx) = oneshot::channel();
+    let (call_tx, call_rx) = oneshot::channel();
+    window
+        .with_webview(move |webview| unsafe {
+            let result = run(webview, path, options, done_tx).map_err(Into::<BoxError>::into);
+            call_tx.send(result).ok();
+        })
+        .map_err(Into::<BoxError>::into)?;
+    call_rx.await??;
+    if done_rx.await? {
+        Ok(())
+    } else {
+        Err(Error::PlatformApi("failed to print the page to PDF".into()).into())
+    }
+}
+
+// NOTE: `CapturePreview` only captures the viewport, into a COM stream, so screenshots are taken
+// through the DevTools protocol instead, which can also capture beyond the viewport.
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn capture_screenshot(window: &Window, options: ScreenshotOptions) -> BoxResult<Vec<u8>> {
+    let mut parameters = serde_json::json!({ "format": screenshot_format(options.format) });
+    if options.region == ScreenshotRegion::FullPage {
+        let script = "[document.documentElement.scrollWidth, document.documentElement.scrollHeight]";
+        let (width, height) = serde_json::from_value::<(f64, f64)>(eval_js(window, script.into()).await?)?;
+        parameters["captureBeyondViewport"] = true.into();
+        parameters["clip"] = serde_json::json!({ "x": 0, "y": 0, "width": width, "height": height, "scale": 1 });
+    }
+    devtools_screenshot(window, parameters).await
+}
+
+// NOTE: the clip is in document coordinates, so elements are captured whole even when they extend
+// beyond the viewport.
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn capture_clip(window: &Window, clip: ClipRect, format: ScreenshotFormat) -> BoxResult<Vec<u8>> {
+    let parameters = serde_json::json!({
+        "format": screenshot_format(format),
+        "captureBeyondViewport": true,
+        "clip": {
+            "x": clip.x + clip.scroll_x,
+            "y": clip.y + clip.scroll_y,
+            "width": clip.width,
+            "height": clip.height,
+            "scale": 1,
+        },
+    });
+    devtools_screenshot(window, parameters).await
+}
+
+async fn devtools_screenshot(window: &Window, parameters: serde_json::Value) -> BoxResult<Vec<u8>> {
+    use base64::{engine::general_purpose::STANDARD, Engine};
+
+    let json = devtools_call(window, "Page.captureScreenshot", parameters).await?;
+    let result = serde_json::from_str::<serde_json::Value>(&json)?;
+    let data = result["data"]
+        .as_str()
+        .ok_or_else(|| Error::PlatformApi("no screenshot was captured".into()))?;
+    Ok(STANDARD.decode(data)?)
+}
+
+fn screenshot_format(format: ScreenshotFormat) -> &'static str {
+    match format {
+        ScreenshotFormat::Png => "png",
+        ScreenshotFormat::Jpeg => "jpeg",
+    }
+}
+
+thread_local! {
+    static VOICE: RefCell<Option<ISpVoice>> = RefCell::new(None);
+}
+
+// NOTE: a single SAPI voice is shared by all windows. It is created on the main thread, where COM
+// has already been initialized for WebView2.
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window, text),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn speak(window: &Window, text: String) -> BoxResult<()> {
+    unsafe fn run(text: String) -> windows::core::Result<()> {
+        VOICE.with(|voice| {
+            let mut voice = voice.borrow_mut();
+            if voice.is_none() {
+                *voice = Some(CoCreateInstance(&SpVoice, None, CLSCTX_ALL)?);
+            }
+            if let Some(voice) = &*voice {
+                let flags = SPF_ASYNC.0 | SPF_PURGEBEFORESPEAK.0;
+                voice.Speak(&HSTRING::from(text), flags as u32, None)?;
+            }
+            Ok(())
+        })
+    }
+
+    let (call_tx, call_rx) = oneshot::channel();
+    window
+        .run_on_main_thread(move || unsafe {
+            let result = run(text).map_err(Into::<BoxError>::into);
+            call_tx.send(result).ok();
+        })
+        .map_err(Into::<BoxError>::into)?;
+    call_rx.await?
+}
+
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn stop_speaking(window: &Window) -> BoxResult<()> {
+    unsafe fn run() -> windows::core::Result<()> {
+        VOICE.with(|voice| match &*voice.borrow() {
+            Some(voice) => voice.Speak(PCWSTR::null(), SPF_PURGEBEFORESPEAK.0 as u32, None),
+            None => Ok(()),
+        })
+    }
+
+    let (call_tx, call_rx) = oneshot::channel();
+    window
+        .run_on_main_thread(move || unsafe {
+            let result = run().map_err(Into::<BoxError>::into);
+            call_tx.send(result).ok();
+        })
+        .map_err(Into::<BoxError>::into)?;
+    call_rx.await?
+}
+
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window, handler),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn add_certificate_transparency_handler(
+    window: &Window,
+    handler: impl Fn(Option<String>) + Send + Sync + 'static,
+) -> BoxResult<()> {
+    unsafe fn run(webview: PlatformWebview, handler: impl Fn(Option<String>) + 'static) -> Result<(), wry::Error> {
+        let webview = webview.controller().CoreWebView2().map_err(WindowsError)?;
+        let requests = Rc::new(RefCell::new(HashMap::<String, String>::new()));
+        let receive =
+            |event: &str, callback: Box<dyn Fn(serde_json::Value)>| receive_devtools_event(&webview, event, callback);
+        receive("Network.requestWillBeSent", {
+            let requests = requests.clone();
+            Box::new(move |value| {
+                if let (Some(id), Some(url)) = (value["requestId"].as_str(), value["request"]["url"].as_str()) {
+                    requests.borrow_mut().insert(id.into(), url.into());
+                }
+            })
+        })?;
+        receive("Network.loadingFinished", {
+            let requests = requests.clone();
+            Box::new(move |value| {
+                if let Some(id) = value["requestId"].as_str() {
+                    requests.borrow_mut().remove(id);
+                }
+            })
+        })?;
+        receive(
+            "Network.loadingFailed",
+            Box::new(move |value| {
+                let url = value["requestId"]
+                    .as_str()
+                    .and_then(|id| requests.borrow_mut().remove(id));
+                if value["errorText"].as_str() == Some("net::ERR_CERTIFICATE_TRANSPARENCY_REQUIRED") {
+                    handler(url);
+                }
+            }),
+        )?;
+        enable_devtools_network(&webview)
+    }
+
+    let (call_tx, call_rx) = oneshot::channel();
+    window
+        .with_webview(move |webview| unsafe {
+            let result = run(webview, handler).map_err(Into::<BoxError>::into);
+            call_tx.send(result).ok();
+        })
+        .map_err(Into::<BoxError>::into)?;
+    call_rx.await?
+}
+
+// NOTE: WebView2 can only disable the back/forward cache with a browser argument when the
+// environment is created, so pages restored from it are reloaded instead.
+#[allow(unused_variables)]
+pub(crate) async fn disable_page_cache(window: &Window) -> BoxResult<bool> {
+    Ok(false)
+}
+
+// NOTE: WebView2 only disables the GPU through browser arguments, which are passed when its
+// environment is created.
+#[allow(unused_variables)]
+pub(crate) async fn disable_hardware_acceleration(window: &Window) -> BoxResult<bool> {
+    Ok(false)
+}
+
+// NOTE: the setting lasts for the lifetime of the webview, and applies to requests made after it.
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn set_bypass_service_worker(window: &Window, bypass: bool) -> BoxResult<()> {
+    unsafe fn run(webview: PlatformWebview, bypass: bool) -> Result<(), wry::Error> {
+        let webview = webview.controller().CoreWebView2().map_err(WindowsError)?;
+        enable_devtools_network(&webview)?;
+        call_devtools_method(
+            &webview,
+            "Network.setBypassServiceWorker",
+            serde_json::json!({ "bypass": bypass }),
+        )
+    }
+
+    let (call_tx, call_rx) = oneshot::channel();
+    window
+        .with_webview(move |webview| unsafe {
+            let result = run(webview, bypass).map_err(Into::<BoxError>::into);
+            call_tx.send(result).ok();
+        })
+        .map_err(Into::<BoxError>::into)?;
+    call_rx.await?
+}
+
+// NOTE: WebView2 only takes a proxy as a browser argument when its environment is created, which
+// is what `EnvironmentOptions::proxy` is for.
+#[allow(unused_variables)]
+pub(crate) async fn set_proxy(window: &Window, config: ProxyConfig) -> BoxResult<()> {
+    Err(
+        Error::UnsupportedPlatform("WebView2 only takes a proxy from `EnvironmentOptions` when the window is built")
+            .into(),
+    )
+}
+
+// NOTE: `Network.webSocketCreated` is reported before the frames of a connection, so frames of
+// connections opened before this was called are not reported.
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window, observer),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn add_websocket_observer(
+    window: &Window,
+    observer: impl Fn(WsEvent) + Send + Sync + 'static,
+) -> BoxResult<bool> {
+    unsafe fn run(webview: PlatformWebview, observer: impl Fn(WsEvent) + 'static) -> Result<(), wry::Error> {
+        use base64::{engine::general_purpose::STANDARD, Engine};
+
+        let webview = webview.controller().CoreWebView2().map_err(WindowsError)?;
+        let sockets = Rc::new(RefCell::new(HashMap::<String, Url>::new()));
+        let observer = Rc::new(observer);
+        receive_devtools_event(&webview, "Network.webSocketCreated", {
+            let sockets = sockets.clone();
+            Box::new(move |value| {
+                let url = value["url"].as_str().and_then(|url| Url::parse(url).ok());
+                if let (Some(id), Some(url)) = (value["requestId"].as_str(), url) {
+                    sockets.borrow_mut().insert(id.into(), url);
+                }
+            })
+        })?;
+        receive_devtools_event(&webview, "Network.webSocketClosed", {
+            let sockets = sockets.clone();
+            Box::new(move |value| {
+                if let Some(id) = value["requestId"].as_str() {
+                    sockets.borrow_mut().remove(id);
+                }
+            })
+        })?;
+        for (event, direction) in [
+            ("Network.webSocketFrameSent", WsDirection::Sent),
+            ("Network.webSocketFrameReceived", WsDirection::Received),
+        ] {
+            let sockets = sockets.clone();
+            let observer = observer.clone();
+            receive_devtools_event(
+                &webview,
+                event,
+                Box::new(move |value| {
+                    let url = value["requestId"]
+                        .as_str()
+                        .and_then(|id| sockets.borrow().get(id).cloned());
+                    let opcode = value["response"]["opcode"].as_u64().unwrap_or_default() as u8;
+                    let payload = value["response"]["payloadData"].as_str().unwrap_or_default();
+                    // NOTE: binary payloads are reported as base64 by the DevTools protocol.
+                    let size = match opcode {
+                        2 => STANDARD.decode(payload).map(|bytes| bytes.len()).unwrap_or_default(),
+                        _ => payload.len(),
+                    };
+                    if let Some(url) = url {
+                        observer(WsEvent {
+                            url,
+                            direction,
+                            opcode,
+                            size: size as u64,
+                            payload: Some(payload.into()),
+                        });
+                    }
+                }),
+            )?;
+        }
+        enable_devtools_network(&webview)
+    }
+
+    let (call_tx, call_rx) = oneshot::channel();
+    window
+        .with_webview(move |webview| unsafe {
+            let result = run(webview, observer).map_err(Into::<BoxError>::into);
+            call_tx.send(result).ok();
+        })
+        .map_err(Into::<BoxError>::into)?;
+    call_rx.await??;
+    Ok(true)
+}
+
+// NOTE: every frame has to be acknowledged before the next one is sent, so frames are dropped
+// rather than queued when the observer falls behind.
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window, observer),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn add_screencast_observer(
+    window: &Window,
+    observer: impl Fn(Vec<u8>) + Send + Sync + 'static,
+) -> BoxResult<bool> {
+    unsafe fn run(webview: PlatformWebview, observer: impl Fn(Vec<u8>) + 'static) -> Result<(), wry::Error> {
+        use base64::{engine::general_purpose::STANDARD, Engine};
+
+        let webview = webview.controller().CoreWebView2().map_err(WindowsError)?;
+        receive_devtools_event(&webview, "Page.screencastFrame", {
+            let webview = webview.clone();
+            Box::new(move |value| {
+                let session = serde_json::json!({ "sessionId": value["sessionId"] });
+                call_devtools_method(&webview, "Page.screencastFrameAck", session).ok();
+                if let Ok(data) = STANDARD.decode(value["data"].as_str().unwrap_or_default()) {
+                    observer(data);
+                }
+            })
+        })
+    }
+
+    let (call_tx, call_rx) = oneshot::channel();
+    window
+        .with_webview(move |webview| unsafe {
+            let result = run(webview, observer).map_err(Into::<BoxError>::into);
+            call_tx.send(result).ok();
+        })
+        .map_err(Into::<BoxError>::into)?;
+    call_rx.await??;
+    Ok(true)
+}
+
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn start_screencast(window: &Window, format: ScreenshotFormat, every_nth_frame: u32) -> BoxResult<()> {
+    let parameters = serde_json::json!({ "format": screenshot_format(format), "everyNthFrame": every_nth_frame });
+    devtools_call(window, "Page.startScreencast", parameters).await?;
+    Ok(())
+}
+
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn stop_screencast(window: &Window) -> BoxResult<()> {
+    devtools_call(window, "Page.stopScreencast", serde_json::json!({})).await?;
+    Ok(())
+}
+
+unsafe fn receive_devtools_event(
+    webview: &ICoreWebView2,
+    event: &str,
+    callback: Box<dyn Fn(serde_json::Value)>,
+) -> Result<(), wry::Error> {
+    let receiver = webview
+        .GetDevToolsProtocolEventReceiver(&HSTRING::from(event))
+        .map_err(WindowsError)?;
+    let handler = DevToolsProtocolEventReceivedEventHandler::create(Box::new(move |_webview, args| {
+        #[cfg(feature = "tracing")]
+        crate::trace::callback("DevToolsProtocolEventReceived");
+        if let Some(args) = args {
+            let json = &mut PWSTR::null();
+            args.ParameterObjectAsJson(json)?;
+            if let Ok(value) = serde_json::from_str(&json.to_string().unwrap_or_default()) {
+                callback(value);
+            }
+        }
+        Ok(())
+    }));
+    let token = &mut EventRegistrationToken::default();
+    receiver
+        .add_DevToolsProtocolEventReceived(&handler, token)
+        .map_err(WindowsError)?;
+    Ok(())
+}
+
+// NOTE: DevTools protocol network events are only reported once the domain is enabled, which is
+// harmless to repeat.
+unsafe fn enable_devtools_network(webview: &ICoreWebView2) -> Result<(), wry::Error> {
+    call_devtools_method(webview, "Network.enable", serde_json::json!({}))
+}
+
+// NOTE: like `call_devtools_method`, but waits for the result of the method, as JSON.
+async fn devtools_call(window: &Window, method: &'static str, parameters: serde_json::Value) -> BoxResult<String> {
+    unsafe fn run(webview: PlatformWebview, method: &str, parameters: serde_json::Value) -> Result<String, wry::Error> {
+        let webview = webview.controller().CoreWebView2().map_err(WindowsError)?;
+        let (json_tx, json_rx) = oneshot::channel();
+        CallDevToolsProtocolMethodCompletedHandler::wait_for_async_operation(
+            Box::new(move |handler| {
+                let method = &HSTRING::from(method);
+                let parameters = &HSTRING::from(parameters.to_string());
+                webview.CallDevToolsProtocolMethod(method, parameters, &handler)?;
+                Ok(())
+            }),
+            Box::new(move |hresult, json| {
+                #[cfg(feature = "tracing")]
+                crate::trace::callback("CallDevToolsProtocolMethodCompleted");
+                hresult?;
+                json_tx.send(json).ok();
+                Ok(())
+            }),
+        )?;
+        let json = json_rx.try_recv().map_err(|_| webview2_com::Error::SendError)?;
+        Ok(json)
+    }
+
+    let (call_tx, call_rx) = oneshot::channel();
+    window
+        .with_webview(move |webview| unsafe {
+            let result = run(webview, method, parameters).map_err(Into::<BoxError>::into);
+            call_tx.send(result).ok();
+        })
+        .map_err(Into::<BoxError>::into)?;
+    call_rx.await?
+}
+
+unsafe fn call_devtools_method(
+    webview: &ICoreWebView2,
+    method: &str,
+    parameters: serde_json::Value,
+) -> Result<(), wry::Error> {
+    let method = &HSTRING::from(method);
+    let parameters = &HSTRING::from(parameters.to_string());
+    webview
+        .CallDevToolsProtocolMethod(method, parameters, InParam::null())
+        .map_err(WindowsError)?;
+    Ok(())
+}
+
+// NOTE: like wry, custom schemes are served from `https://{scheme}.` URLs, since WebView2 only
+// supports non-standard schemes on newer runtimes. The handler receives the URL with the scheme
+// restored. Responses that are not ready right away are awaited on the async runtime while the
+// request is deferred, and set from the main thread afterwards.
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window, handler),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn serve_scheme(window: &Window, scheme: String, handler: SchemeHandler) -> BoxResult<()> {
+    register_scheme(window, scheme, handler).await
+}
+
+// NOTE: streamed bodies are read by WebView2 from an `IStream` on a thread of its own, which blocks
+// until the next chunk of the body arrives. Only a few chunks are buffered ahead of the reads, so
+// the body is only produced as fast as WebView2 reads it.
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window, handler),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn serve_scheme_stream(
+    window: &Window,
+    scheme: String,
+    handler: SchemeStreamHandler,
+) -> BoxResult<()> {
+    register_scheme(window, scheme, handler).await
+}
+
+async fn register_scheme<R: SchemeContent>(window: &Window, scheme: String, handler: Handler<R>) -> BoxResult<()> {
+    unsafe fn run<R: SchemeContent>(
+        window: Window,
+        webview: PlatformWebview,
+        scheme: String,
+        handler: Handler<R>,
+    ) -> Result<(), wry::Error> {
+        let webview = webview.controller().CoreWebView2().map_err(WindowsError)?;
+        let environment = Interface::cast::<ICoreWebView2_2>(&webview)
+            .and_then(|webview| webview.Environment())
+            .map_err(WindowsError)?;
+        let prefix = format!("https://{scheme}.");
+        let filter = &HSTRING::from(format!("{prefix}*"));
+        let token = &mut EventRegistrationToken::default();
+        let handler = WebResourceRequestedEventHandler::create(Box::new(move |_webview, args| {
+            if let Some(args) = args {
+                let request = args.Request()?;
+                let uri = &mut PWSTR::null();
+                request.Uri(uri)?;
+                let uri = uri.to_string().unwrap_or_default();
+                if let Some(rest) = uri.strip_prefix(&prefix) {
+                    let uri = format!("{scheme}://{rest}");
+                    let mut response = handler(scheme_request(&request, uri)?);
+                    if let Some(response) = (&mut response).now_or_never() {
+                        return set_scheme_response(&environment, &args, response);
+                    }
+                    let deferral = args.GetDeferral()?;
+                    let pending = MainThreadBound((environment.clone(), args, deferral));
+                    let window = window.clone();
+                    tauri::async_runtime::spawn(async move {
+                        let response = response.await;
+                        window
+                            .run_on_main_thread(move || {
+                                let MainThreadBound((environment, args, deferral)) = pending;
+                                set_scheme_response(&environment, &args, response).ok();
+                                deferral.Complete().ok();
+                            })
+                            .ok();
+                    });
+                }
+            }
+            Ok(())
+        }));
+        webview
+            .AddWebResourceRequestedFilter(filter, COREWEBVIEW2_WEB_RESOURCE_CONTEXT_ALL)
+            .map_err(WindowsError)?;
+        webview
+            .add_WebResourceRequested(&handler, token)
+            .map_err(WindowsError)?;
+        Ok(())
+    }
+
+    let (call_tx, call_rx) = oneshot::channel();
+    window
+        .with_webview({
+            let window = window.clone();
+            move |webview| unsafe {
+                let result = run(window, webview, scheme, handler).map_err(Into::<BoxError>::into);
+                call_tx.send(result).ok();
+            }
+        })
+        .map_err(Into::<BoxError>::into)?;
+    call_rx.await?
+}
+
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn map_virtual_host(
+    window: &Window,
+    host: String,
+    folder: PathBuf,
+    cors: CorsPolicy,
+) -> BoxResult<Url> {
+    unsafe fn run(webview: PlatformWebview, host: String, folder: String, cors: CorsPolicy) -> Result<(), wry::Error> {
+        let webview = webview.controller().CoreWebView2().map_err(WindowsError)?;
+        let webview = Interface::cast::<ICoreWebView2_3>(&webview).map_err(WindowsError)?;
+        let access = match cors {
+            CorsPolicy::Deny => COREWEBVIEW2_HOST_RESOURCE_ACCESS_KIND_DENY,
+            CorsPolicy::DenyCors => COREWEBVIEW2_HOST_RESOURCE_ACCESS_KIND_DENY_CORS,
+            CorsPolicy::Allow => COREWEBVIEW2_HOST_RESOURCE_ACCESS_KIND_ALLOW,
+        };
+        let host = &HSTRING::from(host);
+        let folder = &HSTRING::from(folder);
+        webview
+            .SetVirtualHostNameToFolderMapping(host, folder, access)
+            .map_err(WindowsError)?;
+        Ok(())
+    }
+
+    let url = Url::parse(&format!("https://{host}/"))?;
+    let folder = folder.to_string_lossy().into_owned();
+    let (call_tx, call_rx) = oneshot::channel();
+    window
+        .with_webview(move |webview| unsafe {
+            let result = run(webview, host, folder, cors).map_err(Into::<BoxError>::into);
+            call_tx.send(result).ok();
+        })
+        .map_err(Into::<BoxError>::into)?;
+    call_rx.await??;
+    Ok(url)
+}
+
+// NOTE: custom schemes are served over `https` with WebView2, so their documents are always secure
+// contexts, and CORS is handled by the responses.
+#[allow(unused_variables)]
+pub(crate) async fn set_scheme_security(
+    window: &Window,
+    scheme: String,
+    secure: bool,
+    cors: CorsPolicy,
+) -> BoxResult<()> {
+    Ok(())
+}
+
+// NOTE: download operations are only used on the main thread, the handle is merely kept in the
+// window state until the download ends.
+#[derive(Clone)]
+pub(crate) struct NativeDownload(ICoreWebView2DownloadOperation);
+
+unsafe impl Send for NativeDownload {
+}
+
+unsafe impl Sync for NativeDownload {
+}
+
+// NOTE: COM objects may only be used from the thread they were created on, so they are only moved
+// through other threads on their way back to the main thread.
+struct MainThreadBound<T>(T);
+
+unsafe impl<T> Send for MainThreadBound<T> {
+}
+
+unsafe fn scheme_request(
+    request: &ICoreWebView2WebResourceRequest,
+    uri: String,
+) -> windows::core::Result<SchemeRequest> {
+    let method = &mut PWSTR::null();
+    request.Method(method)?;
+    let method = method.to_string().unwrap_or_else(|_| "GET".into());
+    let mut headers = vec![];
+    let iterator = request.Headers()?.GetIterator()?;
+    let mut has_current = BOOL::default();
+    iterator.HasCurrentHeader(&mut has_current)?;
+    while has_current.as_bool() {
+        let name = &mut PWSTR::null();
+        let value = &mut PWSTR::null();
+        iterator.GetCurrentHeader(name, value)?;
+        headers.push((
+            name.to_string().unwrap_or_default(),
+            value.to_string().unwrap_or_default(),
+        ));
+        iterator.MoveNext(&mut has_current)?;
+    }
+    let mut body = vec![];
+    if let Ok(content) = request.Content() {
+        let mut chunk = [0u8; 8192];
+        loop {
+            let mut read = 0;
+            content
+                .Read(chunk.as_mut_ptr() as *mut _, chunk.len() as u32, Some(&mut read))
+                .ok()?;
+            if read == 0 {
+                break;
+            }
+            body.extend_from_slice(&chunk[.. read as usize]);
+        }
+    }
+    Ok(SchemeRequest {
+        uri,
+        method,
+        headers,
+        body,
+    })
+}
+
+trait SchemeContent: Send + 'static {
+    fn status(&self) -> u16;
+
+    fn headers(&self) -> &[(String, String)];
+
+    unsafe fn content(self) -> windows::core::Result<Option<IStream>>;
+}
+
+impl SchemeContent for SchemeResponse {
+    fn status(&self) -> u16 {
+        self.status
+    }
+
+    fn headers(&self) -> &[(String, String)] {
+        &self.headers
+    }
+
+    unsafe fn content(self) -> windows::core::Result<Option<IStream>> {
+        Ok(SHCreateMemStream(Some(&self.body)))
+    }
+}
+
+impl SchemeContent for SchemeStreamResponse {
+    fn status(&self) -> u16 {
+        self.status
+    }
+
+    fn headers(&self) -> &[(String, String)] {
+        &self.headers
+    }
+
+    unsafe fn content(self) -> windows::core::Result<Option<IStream>> {
+        Ok(Some(ChunkStream::new(self.body).into()))
+    }
+}
+
+unsafe fn set_scheme_response(
+    environment: &ICoreWebView2Environment,
+    args: &ICoreWebView2WebResourceRequestedEventArgs,
+    response: impl SchemeContent,
+) -> windows::core::Result<()> {
+    let status = response.status();
+    let reason = StatusCode::from_u16(status)
+        .ok()
+        .and_then(|status| status.canonical_reason())
+        .unwrap_or_default();
+    let headers = response
+        .headers()
+        .iter()
+        .map(|(name, value)| format!("{name}: {value}"))
+        .collect::<Vec<_>>()
+        .join("\r\n");
+    let content = response.content()?;
+    let response = environment.CreateWebResourceResponse(
+        content.as_ref(),
+        i32::from(status),
+        &HSTRING::from(reason),
+        &HSTRING::from(headers),
+    )?;
+    args.SetResponse(&response)
+}
+
+// NOTE: how many chunks of a streamed body are produced before WebView2 has read them.
+const STREAM_CHUNKS_AHEAD: usize = 4;
+
+#[implement(IStream)]
+struct ChunkStream {
+    reader: Mutex<ChunkReader>,
+}
+
+struct ChunkReader {
+    chunks: mpsc::Receiver<Vec<u8>>,
+    chunk: Vec<u8>,
+    offset: usize,
+}
+
+impl ChunkStream {
+    fn new(body: futures::stream::BoxStream<'static, std::io::Result<Vec<u8>>>) -> Self {
+        let (chunk_tx, chunk_rx) = mpsc::sync_channel(STREAM_CHUNKS_AHEAD);
+        crate::scheme_handler::pump_body(body, move |chunk| chunk_tx.send(chunk).is_ok());
+        Self {
+            reader: Mutex::new(ChunkReader {
+                chunks: chunk_rx,
+                chunk: vec![],
+                offset: 0,
+            }),
+        }
+    }
+}
+
+// NOTE: a read only waits for the next chunk when nothing was read yet, and reading less than was
+// asked for with `S_FALSE` ends the stream, which is what happens once the body is done.
+impl ISequentialStream_Impl for ChunkStream {
+    fn Read(&self, pv: *mut std::ffi::c_void, cb: u32, pcbread: *mut u32) -> HRESULT {
+        let Ok(mut reader) = self.reader.lock() else {
+            return S_FALSE;
+        };
+        let wanted = cb as usize;
+        let mut read = 0;
+        while read < wanted {
+            if reader.offset == reader.chunk.len() {
+                if read > 0 {
+                    break;
+                }
+                match reader.chunks.recv() {
+                    Ok(chunk) => {
+                        reader.chunk = chunk;
+                        reader.offset = 0;
+                        continue;
+                    },
+                    Err(_) => break,
+                }
+            }
+            let count = (wanted - read).min(reader.chunk.len() - reader.offset);
+            unsafe {
+                std::ptr::copy_nonoverlapping(
+                    reader.chunk.as_ptr().add(reader.offset),
+                    pv.cast::<u8>().add(read),
+                    count,
+                );
+            }
+            reader.offset += count;
+            read += count;
+        }
+        if !pcbread.is_null() {
+            unsafe { *pcbread = read as u32 };
+        }
+        if read == 0 && wanted > 0 {
+            S_FALSE
+        } else {
+            S_OK
+        }
+    }
+
+    fn Write(&self, _pv: *const std::ffi::c_void, _cb: u32, _pcbwritten: *mut u32) -> HRESULT {
+        STG_E_ACCESSDENIED
+    }
+}
+
+impl IStream_Impl for ChunkStream {
+    fn Seek(&self, _dlibmove: i64, _dworigin: STREAM_SEEK, _plibnewposition: *mut u64) -> windows::core::Result<()> {
+        Err(E_NOTIMPL.into())
+    }
+
+    fn SetSize(&self, _libnewsize: u64) -> windows::core::Result<()> {
+        Err(STG_E_ACCESSDENIED.into())
+    }
+
+    fn CopyTo(
+        &self,
+        _pstm: &Option<IStream>,
+        _cb: u64,
+        _pcbread: *mut u64,
+        _pcbwritten: *mut u64,
+    ) -> windows::core::Result<()> {
+        Err(E_NOTIMPL.into())
+    }
+
+    fn Commit(&self, _grfcommitflags: STGC) -> windows::core::Result<()> {
+        Ok(())
+    }
+
+    fn Revert(&self) -> windows::core::Result<()> {
+        Ok(())
+    }
+
+    fn LockRegion(&self, _liboffset: u64, _cb: u64, _dwlocktype: LOCKTYPE) -> windows::core::Result<()> {
+        Err(E_NOTIMPL.into())
+    }
+
+    fn UnlockRegion(&self, _liboffset: u64, _cb: u64, _dwlocktype: u32) -> windows::core::Result<()> {
+        Err(E_NOTIMPL.into())
+    }
+
+    fn Stat(&self, _pstatstg: *mut STATSTG, _grfstatflag: STATFLAG) -> windows::core::Result<()> {
+        Err(E_NOTIMPL.into())
+    }
+
+    fn Clone(&self) -> windows::core::Result<IStream> {
+        Err(E_NOTIMPL.into())
+    }
+}
+
+// NOTE: `NavigateToString` has no base URL, so a `<base>` element is inserted into the document
+// instead. It resolves relative URLs against the base URL, but the document still does not have its
+// origin.
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window, html),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn load_html(window: &Window, html: String, base_url: Option<Url>) -> BoxResult<()> {
+    unsafe fn run(webview: PlatformWebview, html: String) -> Result<(), wry::Error> {
+        let webview = webview.controller().CoreWebView2().map_err(WindowsError)?;
+        let html = &HSTRING::from(html);
+        webview.NavigateToString(html).map_err(WindowsError)?;
+        Ok(())
+    }
+
+    let (call_tx, call_rx) = oneshot::channel();
+    window
+        .with_webview(move |webview| unsafe {
+            let html = match base_url {
+                Some(base_url) => with_base_element(&html, &base_url),
+                None => html,
+            };
+            let result = run(webview, html).map_err(Into::<BoxError>::into);
+            call_tx.send(result).ok();
+        })
+        .map_err(Into::<BoxError>::into)?;
+    call_rx.await?
+}
+
+// NOTE: WebView2 has no reload that bypasses the cache, so the DevTools protocol is used for that.
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn reload(window: &Window, ignore_cache: bool) -> BoxResult<()> {
+    unsafe fn run(webview: PlatformWebview, ignore_cache: bool) -> Result<(), wry::Error> {
+        let webview = webview.controller().CoreWebView2().map_err(WindowsError)?;
+        if ignore_cache {
+            call_devtools_method(&webview, "Page.reload", serde_json::json!({ "ignoreCache": true }))?;
+        } else {
+            webview.Reload().map_err(WindowsError)?;
+        }
+        Ok(())
+    }
+
+    let (call_tx, call_rx) = oneshot::channel();
+    window
+        .with_webview(move |webview| unsafe {
+            let result = run(webview, ignore_cache).map_err(Into::<BoxError>::into);
+            call_tx.send(result).ok();
+        })
+        .map_err(Into::<BoxError>::into)?;
+    call_rx.await?
+}
+
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn stop_loading(window: &Window) -> BoxResult<()> {
+    unsafe fn run(webview: PlatformWebview) -> Result<(), wry::Error> {
+        let webview = webview.controller().CoreWebView2().map_err(WindowsError)?;
+        webview.Stop().map_err(WindowsError)?;
+        Ok(())
+    }
+
+    let (call_tx, call_rx) = oneshot::channel();
+    window
+        .with_webview(move |webview| unsafe {
+            let result = run(webview).map_err(Into::<BoxError>::into);
+            call_tx.send(result).ok();
+        })
+        .map_err(Into::<BoxError>::into)?;
+    call_rx.await?
+}
+
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn open_devtools(window: &Window) -> BoxResult<()> {
+    unsafe fn run(webview: PlatformWebview) -> Result<(), wry::Error> {
+        let webview = webview.controller().CoreWebView2().map_err(WindowsError)?;
+        webview.OpenDevToolsWindow().map_err(WindowsError)?;
+        Ok(())
+    }
+
+    let (call_tx, call_rx) = oneshot::channel();
+    window
+        .with_webview(move |webview| unsafe {
+            let result = run(webview).map_err(Into::<BoxError>::into);
+            call_tx.send(result).ok();
+        })
+        .map_err(Into::<BoxError>::into)?;
+    call_rx.await?
+}
+
+// NOTE: WebView2 has no API to close the DevTools window once it is open.
+#[allow(unused_variables)]
+pub(crate) async fn close_devtools(window: &Window) -> BoxResult<()> {
+    Err(Error::UnsupportedPlatform("closing devtools is not supported with WebView2").into())
+}
+
+// NOTE: disabling devtools keeps a DevTools window that is already open, but prevents opening it
+// again, through the context menu, shortcuts, or `open_devtools`.
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn set_devtools_enabled(window: &Window, enabled: bool) -> BoxResult<()> {
+    unsafe fn run(webview: PlatformWebview, enabled: bool) -> Result<(), wry::Error> {
+        let webview = webview.controller().CoreWebView2().map_err(WindowsError)?;
+        let settings = webview.Settings().map_err(WindowsError)?;
+        settings.SetAreDevToolsEnabled(enabled).map_err(WindowsError)?;
+        Ok(())
+    }
+
+    let (call_tx, call_rx) = oneshot::channel();
+    window
+        .with_webview(move |webview| unsafe {
+            let result = run(webview, enabled).map_err(Into::<BoxError>::into);
+            call_tx.send(result).ok();
+        })
+        .map_err(Into::<BoxError>::into)?;
+    call_rx.await?
+}
+
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn set_muted(window: &Window, muted: bool) -> BoxResult<()> {
+    unsafe fn run(webview: PlatformWebview, muted: bool) -> Result<(), wry::Error> {
+        let webview = webview.controller().CoreWebView2().map_err(WindowsError)?;
+        let webview = Interface::cast::<ICoreWebView2_8>(&webview).map_err(WindowsError)?;
+        webview.SetIsMuted(muted).map_err(WindowsError)?;
+        Ok(())
+    }
+
+    let (call_tx, call_rx) = oneshot::channel();
+    window
+        .with_webview(move |webview| unsafe {
+            let result = run(webview, muted).map_err(Into::<BoxError>::into);
+            call_tx.send(result).ok();
+        })
+        .map_err(Into::<BoxError>::into)?;
+    call_rx.await?
+}
+
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn is_muted(window: &Window) -> BoxResult<bool> {
+    unsafe fn run(webview: PlatformWebview) -> Result<bool, wry::Error> {
+        let webview = webview.controller().CoreWebView2().map_err(WindowsError)?;
+        let webview = Interface::cast::<ICoreWebView2_8>(&webview).map_err(WindowsError)?;
+        let muted = &mut BOOL::default();
+        webview.IsMuted(muted).map_err(WindowsError)?;
+        Ok(muted.as_bool())
+    }
+
+    let (call_tx, call_rx) = oneshot::channel();
+    window
+        .with_webview(move |webview| unsafe {
+            let result = run(webview).map_err(Into::<BoxError>::into);
+            call_tx.send(result).ok();
+        })
+        .map_err(Into::<BoxError>::into)?;
+    call_rx.await?
+}
+
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn is_playing_audio(window: &Window) -> BoxResult<bool> {
+    unsafe fn run(webview: PlatformWebview) -> Result<bool, wry::Error> {
+        let webview = webview.controller().CoreWebView2().map_err(WindowsError)?;
+        let webview = Interface::cast::<ICoreWebView2_8>(&webview).map_err(WindowsError)?;
+        let playing = &mut BOOL::default();
+        webview.IsDocumentPlayingAudio(playing).map_err(WindowsError)?;
+        Ok(playing.as_bool())
+    }
+
+    let (call_tx, call_rx) = oneshot::channel();
+    window
+        .with_webview(move |webview| unsafe {
+            let result = run(webview).map_err(Into::<BoxError>::into);
+            call_tx.send(result).ok();
+        })
+        .map_err(Into::<BoxError>::into)?;
+    call_rx.await?
+}
+
+// NOTE: touch devices are emulated as mobile devices, which also makes the page honor its viewport
+// meta tag. The emulation lasts across navigations.
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn emulate_device(window: &Window, profile: Option<&DeviceProfile>) -> BoxResult<bool> {
+    match profile {
+        Some(profile) => {
+            let parameters = serde_json::json!({
+                "width": profile.width,
+                "height": profile.height,
+                "deviceScaleFactor": profile.dpr,
+                "mobile": profile.touch,
+            });
+            devtools_call(window, "Emulation.setDeviceMetricsOverride", parameters).await?;
+            let parameters = match profile.touch {
+                true => serde_json::json!({ "enabled": true, "maxTouchPoints": 5 }),
+                false => serde_json::json!({ "enabled": false }),
+            };
+            devtools_call(window, "Emulation.setTouchEmulationEnabled", parameters).await?;
+        },
+        None => {
+            devtools_call(window, "Emulation.clearDeviceMetricsOverride", serde_json::json!({})).await?;
+            let parameters = serde_json::json!({ "enabled": false });
+            devtools_call(window, "Emulation.setTouchEmulationEnabled", parameters).await?;
+        },
+    }
+    Ok(true)
+}
+
+// NOTE: an empty media type stops emulating. The emulation lasts across navigations.
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn emulate_media(window: &Window, media: Option<MediaType>) -> BoxResult<bool> {
+    let media = media.map_or("", MediaType::as_str);
+    devtools_call(
+        window,
+        "Emulation.setEmulatedMedia",
+        serde_json::json!({ "media": media }),
+    )
+    .await?;
+    Ok(true)
+}
+
+// NOTE: the preferred color scheme belongs to the profile, so it applies to every webview that
+// shares it.
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn set_color_scheme(window: &Window, scheme: ColorScheme) -> BoxResult<()> {
+    unsafe fn run(webview: PlatformWebview, scheme: ColorScheme) -> Result<(), wry::Error> {
+        let webview = webview.controller().CoreWebView2().map_err(WindowsError)?;
+        let webview = Interface::cast::<ICoreWebView2_13>(&webview).map_err(WindowsError)?;
+        let profile = webview.Profile().map_err(WindowsError)?;
+        let scheme = match scheme {
+            ColorScheme::Auto => COREWEBVIEW2_PREFERRED_COLOR_SCHEME_AUTO,
+            ColorScheme::Light => COREWEBVIEW2_PREFERRED_COLOR_SCHEME_LIGHT,
+            ColorScheme::Dark => COREWEBVIEW2_PREFERRED_COLOR_SCHEME_DARK,
+        };
+        profile.SetPreferredColorScheme(scheme).map_err(WindowsError)?;
+        Ok(())
+    }
+
+    let (call_tx, call_rx) = oneshot::channel();
+    window
+        .with_webview(move |webview| unsafe {
+            let result = run(webview, scheme).map_err(Into::<BoxError>::into);
+            call_tx.send(result).ok();
+        })
+        .map_err(Into::<BoxError>::into)?;
+    call_rx.await?
+}
+
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn go_back(window: &Window) -> BoxResult<()> {
+    unsafe fn run(webview: PlatformWebview) -> Result<(), wry::Error> {
+        let webview = webview.controller().CoreWebView2().map_err(WindowsError)?;
+        webview.GoBack().map_err(WindowsError)?;
+        Ok(())
+    }
+
+    let (call_tx, call_rx) = oneshot::channel();
+    window
+        .with_webview(move |webview| unsafe {
+            let result = run(webview).map_err(Into::<BoxError>::into);
+            call_tx.send(result).ok();
+        })
+        .map_err(Into::<BoxError>::into)?;
+    call_rx.await?
+}
+
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn go_forward(window: &Window) -> BoxResult<()> {
+    unsafe fn run(webview: PlatformWebview) -> Result<(), wry::Error> {
+        let webview = webview.controller().CoreWebView2().map_err(WindowsError)?;
+        webview.GoForward().map_err(WindowsError)?;
+        Ok(())
+    }
+
+    let (call_tx, call_rx) = oneshot::channel();
+    window
+        .with_webview(move |webview| unsafe {
+            let result = run(webview).map_err(Into::<BoxError>::into);
+            call_tx.send(result).ok();
+        })
+        .map_err(Into::<BoxError>::into)?;
+    call_rx.await?
+}
+
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn can_go_back(window: &Window) -> BoxResult<bool> {
+    unsafe fn run(webview: PlatformWebview) -> Result<bool, wry::Error> {
+        let webview = webview.controller().CoreWebView2().map_err(WindowsError)?;
+        let can_go_back = &mut BOOL::default();
+        webview.CanGoBack(can_go_back).map_err(WindowsError)?;
+        Ok(can_go_back.as_bool())
+    }
+
+    let (call_tx, call_rx) = oneshot::channel();
+    window
+        .with_webview(move |webview| unsafe {
+            let result = run(webview).map_err(Into::<BoxError>::into);
+            call_tx.send(result).ok();
+        })
+        .map_err(Into::<BoxError>::into)?;
+    call_rx.await?
+}
+
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn can_go_forward(window: &Window) -> BoxResult<bool> {
+    unsafe fn run(webview: PlatformWebview) -> Result<bool, wry::Error> {
+        let webview = webview.controller().CoreWebView2().map_err(WindowsError)?;
+        let can_go_forward = &mut BOOL::default();
+        webview.CanGoForward(can_go_forward).map_err(WindowsError)?;
+        Ok(can_go_forward.as_bool())
+    }
+
+    let (call_tx, call_rx) = oneshot::channel();
+    window
+        .with_webview(move |webview| unsafe {
+            let result = run(webview).map_err(Into::<BoxError>::into);
+            call_tx.send(result).ok();
+        })
+        .map_err(Into::<BoxError>::into)?;
+    call_rx.await?
+}
+
+// NOTE: WebView2 does not expose its history, so it is read through the DevTools protocol.
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn back_forward_list(window: &Window) -> BoxResult<Vec<HistoryEntry>> {
+    #[derive(serde::Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    struct NavigationHistory {
+        current_index: usize,
+        entries: Vec<NavigationEntry>,
+    }
+
+    #[derive(serde::Deserialize)]
+    struct NavigationEntry {
+        url: String,
+        #[serde(default)]
+        title: String,
+    }
+
+    unsafe fn run(webview: PlatformWebview) -> Result<String, wry::Error> {
+        let webview = webview.controller().CoreWebView2().map_err(WindowsError)?;
+        let (json_tx, json_rx) = oneshot::channel();
+        CallDevToolsProtocolMethodCompletedHandler::wait_for_async_operation(
+            Box::new(move |handler| {
+                let method = &HSTRING::from("Page.getNavigationHistory");
+                let parameters = &HSTRING::from("{}");
+                webview.CallDevToolsProtocolMethod(method, parameters, &handler)?;
+                Ok(())
+            }),
+            Box::new(move |hresult, json| {
+                #[cfg(feature = "tracing")]
+                crate::trace::callback("CallDevToolsProtocolMethodCompleted");
+                hresult?;
+                json_tx.send(json).ok();
+                Ok(())
+            }),
+        )?;
+        let json = json_rx.try_recv().map_err(|_| webview2_com::Error::SendError)?;
+        Ok(json)
+    }
+
+    let (call_tx, call_rx) = oneshot::channel();
+    window
+        .with_webview(move |webview| unsafe {
+            let result = run(webview).map_err(Into::<BoxError>::into);
+            call_tx.send(result).ok();
+        })
+        .map_err(Into::<BoxError>::into)?;
+    let history = serde_json::from_str::<NavigationHistory>(&call_rx.await??)?;
+    let entries = history
+        .entries
+        .into_iter()
+        .enumerate()
+        .filter_map(|(index, entry)| {
+            let url = Url::parse(&entry.url).ok()?;
+            let title = Some(entry.title).filter(|title| !title.is_empty());
+            let current = index == history.current_index;
+            Some(HistoryEntry { url, title, current })
+        })
+        .collect();
+    Ok(entries)
+}
+
+// NOTE: the element is inserted after the doctype, since anything before it switches the document
+// to quirks mode.
+fn with_base_element(html: &str, base_url: &Url) -> String {
+    let href = base_url.as_str().replace('&', "&amp;").replace('"', "&quot;");
+    let base = format!(r#"<base href="{href}">"#);
+    let trimmed = html.trim_start();
+    let has_doctype = trimmed
+        .get(.. 9)
+        .map_or(false, |prefix| prefix.eq_ignore_ascii_case("<!doctype"));
+    if has_doctype {
+        if let Some(end) = trimmed.find('>') {
+            let (doctype, rest) = trimmed.split_at(end + 1);
+            return format!("{doctype}{base}{rest}");
+        }
+    }
+    format!("{base}{html}")
+}
+
+unsafe fn load_error_from_navigation(
+    uri: Option<&str>,
+    args: &ICoreWebView2NavigationCompletedEventArgs,
+) -> windows::core::Result<Option<LoadError>> {
+    let is_success = &mut BOOL::default();
+    args.IsSuccess(is_success)?;
+    // NOTE: the HTTP status is only available from newer runtimes, and only reported separately
+    // when the navigation otherwise succeeded with an error response.
+    let status = &mut i32::default();
+    if let Ok(args) = Interface::cast::<ICoreWebView2NavigationCompletedEventArgs2>(args) {
+        args.HttpStatusCode(status)?;
+    }
+    if is_success.as_bool() {
+        if *status >= 400 {
+            let kind = LoadErrorKind::Http(*status as u16);
+            return Ok(Some(LoadError::new(uri, kind, (*status).into(), kind.to_string())));
+        }
+        return Ok(None);
+    }
+    let web_error_status = &mut COREWEBVIEW2_WEB_ERROR_STATUS::default();
+    args.WebErrorStatus(web_error_status)?;
+    let kind = match *web_error_status {
+        COREWEBVIEW2_WEB_ERROR_STATUS_HOST_NAME_NOT_RESOLVED => LoadErrorKind::DnsFailure,
+        COREWEBVIEW2_WEB_ERROR_STATUS_CERTIFICATE_COMMON_NAME_IS_INCORRECT
+        | COREWEBVIEW2_WEB_ERROR_STATUS_CERTIFICATE_EXPIRED
+        | COREWEBVIEW2_WEB_ERROR_STATUS_CLIENT_CERTIFICATE_CONTAINS_ERRORS
+        | COREWEBVIEW2_WEB_ERROR_STATUS_CERTIFICATE_REVOKED
+        | COREWEBVIEW2_WEB_ERROR_STATUS_CERTIFICATE_IS_INVALID => LoadErrorKind::Tls,
+        COREWEBVIEW2_WEB_ERROR_STATUS_OPERATION_CANCELED => LoadErrorKind::Cancelled,
+        COREWEBVIEW2_WEB_ERROR_STATUS_VALID_AUTHENTICATION_CREDENTIALS_REQUIRED => LoadErrorKind::Http(401),
+        COREWEBVIEW2_WEB_ERROR_STATUS_VALID_PROXY_AUTHENTICATION_REQUIRED => LoadErrorKind::Http(407),
+        COREWEBVIEW2_WEB_ERROR_STATUS_SERVER_UNREACHABLE
+        | COREWEBVIEW2_WEB_ERROR_STATUS_TIMEOUT
+        | COREWEBVIEW2_WEB_ERROR_STATUS_CONNECTION_ABORTED
+        | COREWEBVIEW2_WEB_ERROR_STATUS_CONNECTION_RESET
+        | COREWEBVIEW2_WEB_ERROR_STATUS_DISCONNECTED
+        | COREWEBVIEW2_WEB_ERROR_STATUS_CANNOT_CONNECT => LoadErrorKind::Network,
+        _ => LoadErrorKind::Unknown,
+    };
+    let code = web_error_status.0.into();
+    Ok(Some(LoadError::new(uri, kind, code, kind.to_string())))
+}
+
+// NOTE: `ICoreWebView2Profile7::AddBrowserExtension` is newer than the WebView2 SDK used by wry,
+// which also does not enable browser extensions when it creates the environment.
+#[allow(unused_variables)]
+pub(crate) async fn load_extension(window: &Window, path: PathBuf) -> BoxResult<()> {
+    Err(Error::UnsupportedPlatform("browser extensions are not supported by the WebView2 SDK used by wry").into())
+}
@@ -0,0 +1,49 @@
+use crate::{storage_partition_directory, Error, Result};
+use std::path::PathBuf;
+use tauri::{AppHandle, Runtime, WindowBuilder};
+
+#[derive(Clone, Debug, Default, Eq, Hash, PartialEq)]
+pub enum WebviewDataStore {
+    #[default]
+    Default,
+    Directory(PathBuf),
+    Partition(String),
+}
+
+/// Selects the data store used by the webview of a window that is being built.
+///
+/// The directory is used as the user data folder with WebView2 and for the website data manager of
+/// the web context with webkit2gtk. WKWebView always uses the default data store, so anything but
+/// [`WebviewDataStore::Default`] returns an error there.
+///
+/// The webview runtime of Tauri always creates persistent sessions, so ephemeral ones (like
+/// InPrivate profiles of WebView2) can not be selected. A private window is best approximated by a
+/// partition of its own that is removed with [`crate::remove_storage_partition`] once the window
+/// is closed.
+pub trait WindowBuilderExt<R: Runtime>: Sized + private::WindowBuilderExtSealed {
+    fn webview_data_store(self, app: &AppHandle<R>, store: WebviewDataStore) -> Result<Self>;
+}
+
+impl<'a, R: Runtime> WindowBuilderExt<R> for WindowBuilder<'a, R> {
+    fn webview_data_store(self, app: &AppHandle<R>, store: WebviewDataStore) -> Result<Self> {
+        match store {
+            WebviewDataStore::Default => Ok(self),
+            WebviewDataStore::Directory(_) if cfg!(target_os = "macos") => Err(Error::UnsupportedPlatform(
+                "custom data stores are not supported with WKWebView",
+            )),
+            WebviewDataStore::Directory(directory) => Ok(self.data_directory(directory)),
+            WebviewDataStore::Partition(partition) => {
+                let directory = storage_partition_directory(app, &partition)?;
+                Ok(self.data_directory(directory))
+            },
+        }
+    }
+}
+
+mod private {
+    use tauri::{Runtime, WindowBuilder};
+
+    pub trait WindowBuilderExtSealed {}
+    impl<'a, R: Runtime> WindowBuilderExtSealed for WindowBuilder<'a, R> {
+    }
+}
@@ -1,29 +1,136 @@
-use crate::{ApiResult, BoxError, BoxResult, Cookie};
-use block2::ConcreteBlock;
+use crate::{
+    auth::AuthHandler,
+    bridge::ScriptMessage,
+    downloads::{self, DownloadDecision, DownloadUpdate},
+    error::WindowContext,
+    local_url::NavigationTarget,
+    navigation_policy::{NavigationDecision, NavigationPolicy},
+    scheme_handler::{SchemeHandler, SchemeStreamHandler},
+    screenshot::ClipRect,
+    ApiResult,
+    AuthChallenge,
+    BoxError,
+    BoxResult,
+    CertDecision,
+    CertificateErrorInfo,
+    CertificateErrorKind,
+    ColorScheme,
+    ContentRuleList,
+    Cookie,
+    CookiePolicyInfo,
+    CorsPolicy,
+    Credentials,
+    DataKinds,
+    DataRecord,
+    DeviceProfile,
+    DownloadEvent,
+    Error,
+    HistoryEntry,
+    InjectionTime,
+    LoadError,
+    LoadErrorKind,
+    MediaType,
+    NavigationEvent,
+    OriginUsage,
+    PdfOptions,
+    PdfViewerSettings,
+    PermissionDecision,
+    PermissionKind,
+    PermissionRequest,
+    ProfileInfo,
+    ProxyConfig,
+    ProxyCredentials,
+    ProxyScheme,
+    Result,
+    ScreenshotFormat,
+    ScreenshotOptions,
+    ScreenshotRegion,
+    SessionKind,
+    UserScript,
+    WsEvent,
+};
+use block2::{Block, ConcreteBlock, RcBlock};
 use futures::{future::BoxFuture, prelude::*};
 use icrate::{
     objc2::{
-        rc::{Id, Shared},
+        declare::{Ivar, IvarDrop},
+        ffi::{NSInteger, NSUInteger},
+        rc::{Id, Owned, Shared},
+        runtime::{Bool, Object, Sel},
         *,
     },
-    Foundation::{NSArray, NSDate, NSHTTPCookie, NSNumber, NSSet, NSString, NSURLRequest, NSURL},
+    Foundation::{
+        CGPoint,
+        CGRect,
+        CGSize,
+        NSArray,
+        NSData,
+        NSDate,
+        NSError,
+        NSHTTPCookie,
+        NSNumber,
+        NSObject,
+        NSSet,
+        NSString,
+        NSURLRequest,
+        NSURL,
+    },
     WebKit::{
+        WKContentRuleList,
+        WKContentRuleListStore,
         WKHTTPCookieStore,
+        WKScriptMessage,
+        WKSnapshotConfiguration,
+        WKUserContentController,
+        WKUserScript,
+        WKUserScriptInjectionTimeAtDocumentEnd,
+        WKUserScriptInjectionTimeAtDocumentStart,
         WKWebView,
+        WKWebViewConfiguration,
+        WKWebsiteDataTypeCookies,
         WKWebsiteDataTypeDiskCache,
+        WKWebsiteDataTypeFetchCache,
+        WKWebsiteDataTypeIndexedDBDatabases,
+        WKWebsiteDataTypeLocalStorage,
         WKWebsiteDataTypeMemoryCache,
         WKWebsiteDataTypeOfflineWebApplicationCache,
+        WKWebsiteDataTypeServiceWorkerRegistrations,
+        WKWebsiteDataTypeSessionStorage,
+        WKWebsiteDataTypeWebSQLDatabases,
+    },
+};
+use std::{
+    cell::RefCell,
+    collections::HashSet,
+    ffi::c_void,
+    path::PathBuf,
+    ptr::NonNull,
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Arc,
     },
 };
-use std::{collections::HashSet, ptr::NonNull};
 use tauri::{window::PlatformWebview, Window};
 use url::Url;
 
 impl crate::WebviewExt for Window {
-    #[cfg_attr(feature = "tracing", tracing::instrument)]
-    fn webview_clear_cache(&self) -> BoxFuture<BoxResult<()>> {
+    #[cfg_attr(feature = "tracing", tracing::instrument(
+        skip(self),
+        fields(
+            window.label = self.label(),
+            operation.id = crate::trace::operation_id(),
+            backend = crate::trace::BACKEND
+        )
+    ))]
+    fn webview_clear_cache(&self) -> BoxFuture<Result<()>> {
+        // NOTE: the span of the attribute is only entered while the future is created, so the future
+        // is instrumented with it as well.
         let window = self.clone();
-        async move {
+        let future = async move {
+            crate::read_only::check(&window)?;
+            if crate::dry_run::intercept(&window, || crate::dry_run::DryRunAction::ClearCache)? {
+                return Ok(());
+            }
             let done = dispatch::Semaphore::new(0);
             window
                 .with_webview({
@@ -39,6 +146,8 @@ impl crate::WebviewExt for Window {
                         ]);
                         let date = NSDate::distantPast();
                         let completion_handler = ConcreteBlock::new(move || {
+                            #[cfg(feature = "tracing")]
+                            crate::trace::callback("removeDataOfTypes");
                             done.signal();
                         })
                         .copy();
@@ -53,12 +162,26 @@ impl crate::WebviewExt for Window {
             done.future().await?;
             Ok(())
         }
-        .boxed()
+        .map(move |result: BoxResult<_>| result.window_context(self, "clear cache"));
+        #[cfg(feature = "tracing")]
+        let future = tracing::Instrument::in_current_span(future);
+        future.boxed()
     }
 
-    #[cfg_attr(feature = "tracing", tracing::instrument)]
-    fn webview_delete_cookies(&self, url: Option<Url>) -> BoxFuture<BoxResult<Vec<Cookie>>> {
-        async move {
+    #[cfg_attr(feature = "tracing", tracing::instrument(
+        skip(self),
+        fields(
+            window.label = self.label(),
+            operation.id = crate::trace::operation_id(),
+            backend = crate::trace::BACKEND
+        )
+    ))]
+    fn webview_delete_cookies(&self, url: Option<Url>) -> BoxFuture<Result<Vec<Cookie>>> {
+        let future = async move {
+            crate::read_only::check(self)?;
+            if let Some(cookies) = crate::dry_run::delete_cookies(self, url.clone()).await? {
+                return Ok(cookies);
+            }
             let mut result = vec![];
             let cookie_manager = webview_get_cookie_manager(self).await?;
             let cookies = {
@@ -69,23 +192,32 @@ impl crate::WebviewExt for Window {
                 let done = dispatch::Semaphore::new(0);
                 let (done_tx, done_rx) = oneshot::channel();
                 self.run_on_main_thread({
+                    let window = self.clone();
                     let manager = cookie_manager.clone();
                     let done = done.clone();
                     move || {
                         let manager = manager.lock().unwrap();
                         let cookie = cookie.lock().unwrap();
+                        // NOTE: the cookie is tagged before it is deleted, so that the change is
+                        // attributed to this crate even if it is observed before the deletion completes.
+                        let converted: Result<Cookie, _> = (&*cookie).try_into();
+                        if let Ok(converted) = &converted {
+                            crate::cookie_watch::tag(&window, converted);
+                        }
                         let _: () = unsafe {
                             manager.deleteCookie_completionHandler(
                                 &cookie,
                                 Some(
                                     &ConcreteBlock::new(move || {
+                                        #[cfg(feature = "tracing")]
+                                        crate::trace::callback("deleteCookie");
                                         done.signal();
                                     })
                                     .copy(),
                                 ),
                             )
                         };
-                        done_tx.send((&*cookie).try_into()).unwrap();
+                        done_tx.send(converted).ok();
                     }
                 })?;
                 done.future().await?;
@@ -93,84 +225,168 @@ impl crate::WebviewExt for Window {
             }
             Ok(result)
         }
-        .boxed()
+        .map(move |result: BoxResult<_>| result.window_context(self, "delete cookies"));
+        #[cfg(feature = "tracing")]
+        let future = tracing::Instrument::in_current_span(future);
+        future.boxed()
     }
 
-    #[cfg_attr(feature = "tracing", tracing::instrument)]
-    fn webview_get_cookies(&self, url: Option<Url>) -> BoxFuture<BoxResult<Vec<Cookie>>> {
-        async move {
-            webview_get_raw_cookies(self, url.as_ref())
+    #[cfg_attr(feature = "tracing", tracing::instrument(
+        skip(self),
+        fields(
+            window.label = self.label(),
+            operation.id = crate::trace::operation_id(),
+            backend = crate::trace::BACKEND
+        )
+    ))]
+    fn webview_get_cookies(&self, url: Option<Url>) -> BoxFuture<Result<Vec<Cookie>>> {
+        let future = async move {
+            let cookies = webview_get_raw_cookies(self, url.as_ref())
                 .await?
                 .map(|cookie| Cookie::try_from(&cookie))
-                .collect::<BoxResult<Vec<_>>>()
+                .collect::<Result<Vec<_>>>()?;
+            #[cfg(feature = "tracing-verbose")]
+            for cookie in &cookies {
+                tracing::trace!(%cookie, "fetched cookie");
+            }
+            Ok(cookies)
         }
-        .boxed()
+        .map(move |result: BoxResult<_>| result.window_context(self, "fetch cookies"));
+        #[cfg(feature = "tracing")]
+        let future = tracing::Instrument::in_current_span(future);
+        future.boxed()
     }
 
-    #[cfg_attr(feature = "tracing", tracing::instrument)]
-    fn webview_navigate(&self, url: Url) -> BoxResult<()> {
-        self.with_webview(move |webview| unsafe {
-            let webview = webview.WKWebView();
-            let string = NSString::from_str(url.as_str());
-            if let Some(url) = NSURL::URLWithString(&string) {
-                let request = NSURLRequest::requestWithURL(&url);
-                #[allow(unused_variables)]
-                webview.loadRequest(&request);
-                #[cfg(feature = "tracing")]
-                tracing::info!(?navigation);
-            }
-        })
-        .map_err(Into::into)
+    #[cfg_attr(feature = "tracing", tracing::instrument(
+        skip(self),
+        fields(
+            window.label = self.label(),
+            operation.id = crate::trace::operation_id(),
+            backend = crate::trace::BACKEND
+        )
+    ))]
+    fn webview_navigate(&self, url: Url) -> Result<()> {
+        crate::local_url::target(url)
+            .and_then(|target| navigate(self, target))
+            .window_context(self, "navigate")
     }
 }
 
+// NOTE: a file is loaded with `loadFileURL:allowingReadAccessToURL:`, since `loadRequest:` does not
+// grant the document access to the files it references.
+pub(crate) fn navigate(window: &Window, target: NavigationTarget) -> BoxResult<()> {
+    crate::navigation_events::tag(window);
+    window.with_webview(move |webview| unsafe {
+        let webview = webview.WKWebView();
+        match target {
+            NavigationTarget::File { url, read_access, .. } => {
+                let string = NSString::from_str(url.as_str());
+                if let Some(url) = NSURL::URLWithString(&string) {
+                    let read_access = NSString::from_str(&read_access.to_string_lossy());
+                    let read_access = NSURL::fileURLWithPath_isDirectory(&read_access, true);
+                    webview.loadFileURL_allowingReadAccessToURL(&url, &read_access);
+                }
+            },
+            NavigationTarget::Remote(url) | NavigationTarget::Data(url) => {
+                let string = NSString::from_str(url.as_str());
+                if let Some(url) = NSURL::URLWithString(&string) {
+                    let request = NSURLRequest::requestWithURL(&url);
+                    webview.loadRequest(&request);
+                }
+            },
+        }
+    })?;
+    Ok(())
+}
+
 impl TryFrom<&Id<NSHTTPCookie, Shared>> for Cookie {
-    type Error = BoxError;
+    type Error = Error;
 
     fn try_from(cookie: &Id<NSHTTPCookie, Shared>) -> Result<Self, Self::Error> {
-        unsafe {
-            let name = cookie.name().to_string().into();
-            let value = cookie.value().to_string().into();
-            let domain = cookie.domain().to_string().into();
-            let path = cookie.path().to_string().into();
-            let port_list = cookie
-                .portList()
-                .map(|list| list.into_iter().map(|port| u16::try_from(Number::from(port))).collect())
-                .transpose()?;
-            let expires = cookie
-                .expiresDate()
-                .map(|date| {
-                    let timestamp = date.timeIntervalSince1970().round() as i64;
-                    time::OffsetDateTime::from_unix_timestamp(timestamp)
-                })
-                .transpose()?;
-            let http_only = cookie.isHTTPOnly().into();
-            let same_site = cookie.sameSitePolicy().map(|policy| policy.to_string());
-            let secure = cookie.isSecure().into();
-            let session = cookie.isSessionOnly().into();
-            let comment = cookie.comment().map(|comment| comment.to_string());
-            let comment_url = cookie
-                .commentURL()
-                .and_then(|url| url.absoluteString().map(|url| Url::parse(&url.to_string())))
-                .transpose()?;
-            Ok(Self {
-                name,
-                value,
-                domain,
-                path,
-                port_list,
-                expires,
-                http_only,
-                same_site,
-                secure,
-                session,
-                comment,
-                comment_url,
+        cookie_from_ns_http_cookie(cookie).map_err(Error::CookieConversion)
+    }
+}
+
+fn cookie_from_ns_http_cookie(cookie: &Id<NSHTTPCookie, Shared>) -> BoxResult<Cookie> {
+    unsafe {
+        let name = cookie.name().to_string().into();
+        let value = cookie.value().to_string().into();
+        let domain = cookie.domain().to_string().into();
+        let path = cookie.path().to_string().into();
+        let port_list = cookie
+            .portList()
+            .map(|list| list.into_iter().map(|port| u16::try_from(Number::from(port))).collect())
+            .transpose()?;
+        let expires = cookie
+            .expiresDate()
+            .map(|date| {
+                let timestamp = date.timeIntervalSince1970().round() as i64;
+                time::OffsetDateTime::from_unix_timestamp(timestamp)
             })
-        }
+            .transpose()?;
+        let http_only = cookie.isHTTPOnly().into();
+        let same_site = cookie.sameSitePolicy().map(|policy| policy.to_string());
+        let secure = cookie.isSecure().into();
+        let session = cookie.isSessionOnly().into();
+        let comment = cookie.comment().map(|comment| comment.to_string());
+        let comment_url = cookie
+            .commentURL()
+            .and_then(|url| url.absoluteString().map(|url| Url::parse(&url.to_string())))
+            .transpose()?;
+        Ok(Cookie {
+            name,
+            value,
+            domain,
+            path,
+            port_list,
+            expires,
+            http_only,
+            same_site,
+            secure,
+            session,
+            comment,
+            comment_url,
+        })
     }
 }
 
+#[cfg(feature = "debug-tools")]
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn raw_cookie_debug(window: &Window) -> BoxResult<Vec<crate::RawCookieDebug>> {
+    let cookies = webview_get_raw_cookies(window, None)
+        .await?
+        .map(|cookie| unsafe {
+            let port_list = cookie
+                .portList()
+                .map(|list| list.into_iter().map(|port| port.as_f64()).collect::<Vec<_>>());
+            let raw = vec![
+                ("name", cookie.name().to_string()),
+                ("value", crate::cookie_debug::redact(&cookie.value().to_string())),
+                ("domain", cookie.domain().to_string()),
+                ("path", cookie.path().to_string()),
+                ("portList", format!("{port_list:?}")),
+                (
+                    "expiresDate",
+                    format!("{:?}", cookie.expiresDate().map(|date| date.timeIntervalSince1970())),
+                ),
+                ("isHTTPOnly", bool::from(cookie.isHTTPOnly()).to_string()),
+                (
+                    "sameSitePolicy",
+                    format!("{:?}", cookie.sameSitePolicy().map(|policy| policy.to_string())),
+                ),
+                ("isSecure", bool::from(cookie.isSecure()).to_string()),
+                ("isSessionOnly", bool::from(cookie.isSessionOnly()).to_string()),
+                ("version", cookie.version().to_string()),
+            ];
+            crate::RawCookieDebug::new(raw, Cookie::try_from(&cookie))
+        })
+        .collect();
+    Ok(cookies)
+}
+
 enum Number {
     Signed(i64),
     Unsigned(u64),
@@ -205,7 +421,423 @@ impl TryFrom<Number> for u16 {
     }
 }
 
-#[cfg_attr(feature = "tracing", tracing::instrument)]
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn clear_data(window: &Window, kinds: DataKinds) -> BoxResult<()> {
+    let done = dispatch::Semaphore::new(0);
+    window.with_webview({
+        let done = done.clone();
+        move |webview| unsafe {
+            let webview = webview.WKWebView();
+            let configuration = webview.configuration();
+            let data_store = configuration.websiteDataStore();
+            let data_types = website_data_types(kinds);
+            let data_types = NSSet::from_slice(&data_types);
+            let date = NSDate::distantPast();
+            let completion_handler = ConcreteBlock::new(move || {
+                #[cfg(feature = "tracing")]
+                crate::trace::callback("removeDataOfTypes");
+                done.signal();
+            })
+            .copy();
+            data_store.removeDataOfTypes_modifiedSince_completionHandler(&data_types, &date, &completion_handler);
+        }
+    })?;
+    done.future().await?;
+    Ok(())
+}
+
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn list_data_records(window: &Window, kinds: DataKinds) -> BoxResult<Vec<DataRecord>> {
+    let (call_tx, call_rx) = oneshot::channel();
+    window.with_webview(move |webview| unsafe {
+        let webview = webview.WKWebView();
+        let configuration = webview.configuration();
+        let data_store = configuration.websiteDataStore();
+        let data_types = NSSet::from_slice(&website_data_types(kinds));
+        let call_tx = std::sync::Mutex::new(Some(call_tx));
+        let completion_handler = ConcreteBlock::new(move |array: NonNull<NSArray<NSObject>>| {
+            #[cfg(feature = "tracing")]
+            crate::trace::callback("fetchDataRecordsOfTypes");
+            let records = array
+                .as_ref()
+                .to_shared_vec()
+                .iter()
+                .filter_map(|record| {
+                    let name: Id<NSString, Shared> = msg_send_id![record, displayName];
+                    let kinds = data_kinds(record) & kinds;
+                    let name = name.to_string();
+                    Some(DataRecord { name, kinds }).filter(|record| !record.kinds.is_empty())
+                })
+                .collect::<Vec<_>>();
+            if let Some(call_tx) = call_tx.lock().ok().and_then(|mut call_tx| call_tx.take()) {
+                call_tx.send(records).ok();
+            }
+        })
+        .copy();
+        let _: () = msg_send![
+            &data_store,
+            fetchDataRecordsOfTypes: &*data_types,
+            completionHandler: &*completion_handler,
+        ];
+    })?;
+    Ok(call_rx.await?)
+}
+
+// NOTE: records are matched by display name against what the data store holds now, and each one is
+// removed for its own kinds only.
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn remove_data_records(window: &Window, records: Vec<DataRecord>) -> BoxResult<()> {
+    let kinds = records
+        .iter()
+        .fold(DataKinds::empty(), |kinds, record| kinds | record.kinds);
+    if kinds.is_empty() {
+        return Ok(());
+    }
+    let (call_tx, call_rx) = oneshot::channel();
+    window.with_webview(move |webview| unsafe {
+        let webview = webview.WKWebView();
+        let configuration = webview.configuration();
+        let data_store = configuration.websiteDataStore();
+        let data_types = NSSet::from_slice(&website_data_types(kinds));
+        let call_tx = Arc::new(std::sync::Mutex::new(Some(call_tx)));
+        let completion_handler = ConcreteBlock::new({
+            let data_store = data_store.clone();
+            move |array: NonNull<NSArray<NSObject>>| {
+                #[cfg(feature = "tracing")]
+                crate::trace::callback("fetchDataRecordsOfTypes");
+                let native_records = array.as_ref().to_shared_vec();
+                let removals = records
+                    .iter()
+                    .flat_map(|record| {
+                        native_records
+                            .iter()
+                            .filter(|native_record| {
+                                let name: Id<NSString, Shared> = msg_send_id![*native_record, displayName];
+                                name.to_string() == record.name
+                            })
+                            .map(|native_record| (native_record.clone(), record.kinds))
+                    })
+                    .collect::<Vec<_>>();
+                let send = {
+                    let call_tx = call_tx.clone();
+                    move || {
+                        if let Some(call_tx) = call_tx.lock().ok().and_then(|mut call_tx| call_tx.take()) {
+                            call_tx.send(()).ok();
+                        }
+                    }
+                };
+                if removals.is_empty() {
+                    send();
+                    return;
+                }
+                let pending = Arc::new(AtomicUsize::new(removals.len()));
+                for (native_record, kinds) in removals {
+                    let data_types = NSSet::from_slice(&website_data_types(kinds));
+                    let native_records: Id<NSObject, Shared> =
+                        msg_send_id![class!(NSArray), arrayWithObject: &*native_record];
+                    let completion_handler = ConcreteBlock::new({
+                        let pending = pending.clone();
+                        let send = send.clone();
+                        move || {
+                            if pending.fetch_sub(1, Ordering::SeqCst) == 1 {
+                                send();
+                            }
+                        }
+                    })
+                    .copy();
+                    let _: () = msg_send![
+                        &data_store,
+                        removeDataOfTypes: &*data_types,
+                        forDataRecords: &*native_records,
+                        completionHandler: &*completion_handler,
+                    ];
+                }
+            }
+        })
+        .copy();
+        let _: () = msg_send![
+            &data_store,
+            fetchDataRecordsOfTypes: &*data_types,
+            completionHandler: &*completion_handler,
+        ];
+    })?;
+    Ok(call_rx.await?)
+}
+
+// NOTE: WKWebView does not report the size of website data, so only the origins that hold cached
+// data are listed.
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn cache_usage(window: &Window) -> BoxResult<Vec<OriginUsage>> {
+    let records = list_data_records(window, DataKinds::CACHES).await?;
+    let usage = records
+        .into_iter()
+        .map(|record| OriginUsage {
+            origin: record.name,
+            disk_cache: None,
+            memory_cache: None,
+        })
+        .collect();
+    Ok(usage)
+}
+
+unsafe fn website_data_types(kinds: DataKinds) -> Vec<Id<NSString, Shared>> {
+    website_data_type_kinds()
+        .into_iter()
+        .filter(|(kind, _)| kinds.contains(*kind))
+        .map(|(_, data_type)| data_type.to_owned())
+        .collect()
+}
+
+unsafe fn data_kinds(record: &NSObject) -> DataKinds {
+    let data_types: Id<NSObject, Shared> = msg_send_id![record, dataTypes];
+    website_data_type_kinds()
+        .into_iter()
+        .filter(|(_, data_type)| {
+            let contains: bool = msg_send![&data_types, containsObject: *data_type];
+            contains
+        })
+        .fold(DataKinds::empty(), |kinds, (kind, _)| kinds | kind)
+}
+
+unsafe fn website_data_type_kinds() -> [(DataKinds, &'static NSString); 9] {
+    [
+        (DataKinds::DISK_CACHE, WKWebsiteDataTypeDiskCache),
+        (DataKinds::MEMORY_CACHE, WKWebsiteDataTypeMemoryCache),
+        (DataKinds::CACHE_STORAGE, WKWebsiteDataTypeFetchCache),
+        (DataKinds::COOKIES, WKWebsiteDataTypeCookies),
+        (DataKinds::INDEXED_DB, WKWebsiteDataTypeIndexedDBDatabases),
+        (DataKinds::LOCAL_STORAGE, WKWebsiteDataTypeLocalStorage),
+        (DataKinds::SESSION_STORAGE, WKWebsiteDataTypeSessionStorage),
+        (DataKinds::WEB_SQL, WKWebsiteDataTypeWebSQLDatabases),
+        (DataKinds::SERVICE_WORKERS, WKWebsiteDataTypeServiceWorkerRegistrations),
+    ]
+}
+
+// NOTE: WKWebView has no API for the location of the default data store, which is the directory
+// that WebKit keeps for the bundle identifier of the app.
+pub(crate) async fn profile_directory(window: &Window) -> BoxResult<Option<PathBuf>> {
+    let identifier = window.config().tauri.bundle.identifier.clone();
+    let directory = std::env::var_os("HOME").map(|home| PathBuf::from(home).join("Library/WebKit").join(identifier));
+    Ok(directory)
+}
+
+// NOTE: the data store of a webview is either the default one or a non-persistent one, which keeps
+// everything in memory.
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn profile_info(window: &Window) -> BoxResult<ProfileInfo> {
+    let (call_tx, call_rx) = oneshot::channel();
+    window.with_webview(move |webview| unsafe {
+        let webview = webview.WKWebView();
+        let persistent = webview.configuration().websiteDataStore().isPersistent();
+        call_tx.send(persistent).ok();
+    })?;
+    if call_rx.await? {
+        Ok(ProfileInfo {
+            name: None,
+            session: SessionKind::Persistent,
+            directory: profile_directory(window).await?,
+        })
+    } else {
+        Ok(ProfileInfo {
+            name: None,
+            session: SessionKind::Ephemeral,
+            directory: None,
+        })
+    }
+}
+
+// NOTE: the ids are the addresses of the process pool and data store of the configuration of the
+// webview. WebKit starts web content processes for each webview as it needs them, so there is no
+// process id of a whole process pool to report.
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn engine_context(window: &Window) -> BoxResult<crate::EngineContext> {
+    let (call_tx, call_rx) = oneshot::channel();
+    window.with_webview(move |webview| unsafe {
+        let webview = webview.WKWebView();
+        let configuration = webview.configuration();
+        let context = crate::EngineContext {
+            context: format!("{:p}", Id::as_ptr(&configuration.processPool())),
+            data_store: Some(format!("{:p}", Id::as_ptr(&configuration.websiteDataStore()))),
+            browser_process_id: None,
+        };
+        call_tx.send(context).ok();
+    })?;
+    Ok(call_rx.await?)
+}
+
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window, cookie),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn set_cookie(window: &Window, cookie: Cookie) -> BoxResult<()> {
+    crate::read_only::check(window)?;
+    if crate::dry_run::intercept(window, || crate::dry_run::set_cookie_action(&cookie))? {
+        return Ok(());
+    }
+    crate::cookie_watch::tag(window, &cookie);
+    let cookie_manager = webview_get_cookie_manager(window).await?;
+    let done = dispatch::Semaphore::new(0);
+    let (call_tx, call_rx) = oneshot::channel::<BoxResult<()>>();
+    window.run_on_main_thread({
+        let done = done.clone();
+        move || unsafe {
+            let manager = match cookie_manager.lock() {
+                Ok(manager) => manager,
+                Err(_) => {
+                    done.signal();
+                    call_tx.send(Err(Error::Poisoned.into())).ok();
+                    return;
+                },
+            };
+            match ns_http_cookie_from(&cookie) {
+                Some(raw_cookie) => {
+                    let completion_handler = ConcreteBlock::new(move || {
+                        #[cfg(feature = "tracing")]
+                        crate::trace::callback("setCookie");
+                        done.signal();
+                    })
+                    .copy();
+                    manager.setCookie_completionHandler(&raw_cookie, Some(&completion_handler));
+                    call_tx.send(Ok(())).ok();
+                },
+                None => {
+                    done.signal();
+                    call_tx
+                        .send(Err(
+                            Error::CookieConversion("cookie could not be converted".into()).into()
+                        ))
+                        .ok();
+                },
+            }
+        }
+    })?;
+    done.future().await?;
+    call_rx.await?
+}
+
+// NOTE: the cookie store does not retain its observers, so the observer is kept alive by associating
+// it with the webview.
+// NOTE: WebKit caps the lifetime of cookies written by scripts to 7 days with intelligent tracking
+// prevention, and blocks third-party cookies by default. Neither is configurable for a webview, and
+// CFNetwork documents no limit on the number of cookies per domain.
+#[allow(unused_variables)]
+pub(crate) async fn cookie_policy(window: &Window) -> BoxResult<CookiePolicyInfo> {
+    Ok(CookiePolicyInfo {
+        engine: "WKWebView".into(),
+        engine_version: wry::webview::webview_version()?,
+        blocks_third_party_cookies: Some(true),
+        script_cookie_max_age: Some(std::time::Duration::from_secs(7 * 24 * 60 * 60)),
+        script_cookie_max_age_probed: false,
+        max_cookies_per_domain: None,
+    })
+}
+
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window, observer),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn add_cookie_observer(window: &Window, observer: impl Fn() + Send + Sync + 'static) -> BoxResult<()> {
+    window.with_webview(move |webview| unsafe {
+        static OBSERVER_KEY: u8 = 0;
+        let webview = webview.WKWebView();
+        let configuration = webview.configuration();
+        let data_store = configuration.websiteDataStore();
+        let http_cookie_store = data_store.httpCookieStore();
+        let observer = CookieStoreObserver::new(observer);
+        let _: () = msg_send![&http_cookie_store, addObserver: &*observer];
+        ffi::objc_setAssociatedObject(
+            Id::as_ptr(&webview) as *mut _,
+            &OBSERVER_KEY as *const u8 as *const _,
+            Id::as_ptr(&observer) as *mut _,
+            ffi::OBJC_ASSOCIATION_RETAIN_NONATOMIC,
+        );
+    })?;
+    Ok(())
+}
+
+declare_class!(
+    struct CookieStoreObserver {
+        observer: IvarDrop<Box<Box<dyn Fn()>>>,
+    }
+
+    unsafe impl ClassType for CookieStoreObserver {
+        type Super = NSObject;
+        const NAME: &'static str = "TauriWebviewUtilCookieStoreObserver";
+    }
+
+    unsafe impl CookieStoreObserver {
+        #[sel(cookiesDidChangeInCookieStore:)]
+        fn cookies_did_change_in_cookie_store(&self, _cookie_store: &WKHTTPCookieStore) {
+            (self.observer)();
+        }
+    }
+);
+
+impl CookieStoreObserver {
+    fn new(observer: impl Fn() + 'static) -> Id<Self, Shared> {
+        unsafe {
+            let this: Option<Id<Self, Owned>> = msg_send_id![msg_send_id![Self::class(), alloc], init];
+            match this {
+                None => unreachable!("initializer should never fail"),
+                Some(mut this) => {
+                    Ivar::write(&mut this.observer, Box::new(Box::new(observer)));
+                    this.into()
+                },
+            }
+        }
+    }
+}
+
+// NOTE: the property keys are the values of the `NSHTTPCookie*` property key constants.
+unsafe fn ns_http_cookie_from(cookie: &Cookie) -> Option<Id<NSHTTPCookie, Shared>> {
+    let properties: Id<NSObject, Shared> = msg_send_id![class!(NSMutableDictionary), new];
+    let set = |key: &str, value: &NSObject| {
+        let key = NSString::from_str(key);
+        let _: () = msg_send![&properties, setObject: value, forKey: &*key];
+    };
+    set("Name", &NSString::from_str(&cookie.name));
+    set("Value", &NSString::from_str(&cookie.value));
+    set("Domain", &NSString::from_str(&cookie.domain));
+    set("Path", &NSString::from_str(&cookie.path));
+    if let Some(expires) = cookie.expires {
+        let interval = expires.unix_timestamp() as f64;
+        let date: Id<NSObject, Shared> = msg_send_id![class!(NSDate), dateWithTimeIntervalSince1970: interval];
+        set("Expires", &date);
+    }
+    if cookie.http_only {
+        set("HttpOnly", &NSString::from_str("TRUE"));
+    }
+    if cookie.secure {
+        set("Secure", &NSString::from_str("TRUE"));
+    }
+    if let Some(same_site) = &cookie.same_site {
+        set("SameSite", &NSString::from_str(&same_site.to_ascii_lowercase()));
+    }
+    msg_send_id![class!(NSHTTPCookie), cookieWithProperties: &*properties]
+}
+
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
 async fn webview_get_cookie_manager(window: &Window) -> BoxResult<ApiResult<Id<WKHTTPCookieStore, Shared>>> {
     let (call_tx, call_rx) = oneshot::channel::<ApiResult<_>>();
     window.with_webview(move |webview| unsafe {
@@ -213,12 +845,15 @@ async fn webview_get_cookie_manager(window: &Window) -> BoxResult<ApiResult<Id<W
         let configuration = webview.configuration();
         let data_store = configuration.websiteDataStore();
         let http_cookie_store = data_store.httpCookieStore();
-        call_tx.send(http_cookie_store.into()).unwrap();
+        call_tx.send(http_cookie_store.into()).ok();
     })?;
     Ok(call_rx.await?)
 }
 
-#[cfg_attr(feature = "tracing", tracing::instrument)]
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
 async fn webview_get_raw_cookies<'a>(
     window: &Window,
     url: Option<&'a Url>,
@@ -235,7 +870,10 @@ async fn webview_get_raw_cookies<'a>(
     Ok(cookies)
 }
 
-#[cfg_attr(feature = "tracing", tracing::instrument)]
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
 async fn webview_get_raw_cookies_for_all_domains(
     window: &Window,
 ) -> BoxResult<impl Iterator<Item = Id<NSHTTPCookie, Shared>>> {
@@ -251,6 +889,8 @@ async fn webview_get_raw_cookies_for_all_domains(
             let http_cookie_store = data_store.httpCookieStore();
             http_cookie_store.getAllCookies(
                 &*ConcreteBlock::new(move |array: NonNull<NSArray<NSHTTPCookie>>| {
+                    #[cfg(feature = "tracing")]
+                    crate::trace::callback("getAllCookies");
                     *done_val.lock().unwrap() = array.as_ref().to_shared_vec();
                     done.signal();
                 })
@@ -292,6 +932,2428 @@ fn webview_cookie_filter<'a>(url: Option<&'a Url>) -> BoxResult<impl Fn(String,
     }
 }
 
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window, source),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn add_user_script(window: &Window, source: String) -> BoxResult<()> {
+    window.with_webview(move |webview| unsafe {
+        let webview = webview.WKWebView();
+        let configuration = webview.configuration();
+        let controller = configuration.userContentController();
+        let source = NSString::from_str(&source);
+        let time = WKUserScriptInjectionTimeAtDocumentStart;
+        let script =
+            WKUserScript::initWithSource_injectionTime_forMainFrameOnly(WKUserScript::alloc(), &source, time, true);
+        controller.addUserScript(&script);
+    })?;
+    Ok(())
+}
+
+// NOTE: user scripts are only accessed from `with_webview` on the main thread, the handle is merely
+// kept in the window state until the script is removed again.
+pub(crate) struct UserScriptHandle(Id<WKUserScript, Shared>);
+
+unsafe impl Send for UserScriptHandle {
+}
+
+unsafe impl Sync for UserScriptHandle {
+}
+
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window, script),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn eval_js(window: &Window, script: String) -> BoxResult<serde_json::Value> {
+    #[cfg(feature = "tracing-verbose")]
+    tracing::trace!(%script, "evaluating script");
+    let (call_tx, call_rx) = oneshot::channel::<BoxResult<serde_json::Value>>();
+    window.with_webview(move |webview| unsafe {
+        let webview = webview.WKWebView();
+        let call_tx = std::sync::Mutex::new(Some(call_tx));
+        let completion_handler = ConcreteBlock::new(move |result: *mut Object, error: *mut NSError| {
+            #[cfg(feature = "tracing")]
+            crate::trace::callback("evaluateJavaScript");
+            let result = match (result.as_ref(), error.as_ref()) {
+                (_, Some(error)) => Err(Error::Script(error.localizedDescription().to_string()).into()),
+                (Some(result), None) => json_from_object(result),
+                (None, None) => Ok(serde_json::Value::Null),
+            };
+            if let Some(call_tx) = call_tx.lock().ok().and_then(|mut call_tx| call_tx.take()) {
+                call_tx.send(result).ok();
+            }
+        })
+        .copy();
+        webview.evaluateJavaScript_completionHandler(&NSString::from_str(&script), Some(&completion_handler));
+    })?;
+    call_rx.await?
+}
+
+// NOTE: `callAsyncJavaScript` runs the body as an async function in the page world, and completes
+// once the promise it returns settles.
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window, body),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn eval_async_js(window: &Window, body: String) -> BoxResult<serde_json::Value> {
+    #[cfg(feature = "tracing-verbose")]
+    tracing::trace!(%body, "evaluating script");
+    let (call_tx, call_rx) = oneshot::channel::<BoxResult<serde_json::Value>>();
+    window.with_webview(move |webview| unsafe {
+        let webview = webview.WKWebView();
+        let call_tx = std::sync::Mutex::new(Some(call_tx));
+        let completion_handler = ConcreteBlock::new(move |result: *mut Object, error: *mut NSError| {
+            #[cfg(feature = "tracing")]
+            crate::trace::callback("callAsyncJavaScript");
+            let result = match (result.as_ref(), error.as_ref()) {
+                (_, Some(error)) => Err(Error::Script(error.localizedDescription().to_string()).into()),
+                (Some(result), None) => json_from_object(result),
+                (None, None) => Ok(serde_json::Value::Null),
+            };
+            if let Some(call_tx) = call_tx.lock().ok().and_then(|mut call_tx| call_tx.take()) {
+                call_tx.send(result).ok();
+            }
+        })
+        .copy();
+        let arguments: Id<NSObject, Shared> = msg_send_id![class!(NSDictionary), dictionary];
+        let world: Id<NSObject, Shared> = msg_send_id![class!(WKContentWorld), pageWorld];
+        let frame = std::ptr::null::<Object>();
+        let _: () = msg_send![
+            &webview,
+            callAsyncJavaScript: &*NSString::from_str(&body),
+            arguments: &*arguments,
+            inFrame: frame,
+            inContentWorld: &*world,
+            completionHandler: &*completion_handler,
+        ];
+    })?;
+    call_rx.await?
+}
+
+// NOTE: `NSJSONSerialization` raises an exception for objects it can not serialize, so the result is
+// validated first. Only collections are valid top-level objects, so it is wrapped in an array.
+unsafe fn json_from_object(object: &Object) -> BoxResult<serde_json::Value> {
+    let array: Id<NSObject, Shared> = msg_send_id![class!(NSArray), arrayWithObject: object];
+    let valid: bool = msg_send![class!(NSJSONSerialization), isValidJSONObject: &*array];
+    if !valid {
+        return Err("script result can not be represented as JSON".into());
+    }
+    let error = std::ptr::null_mut::<*mut NSError>();
+    let options: NSUInteger = 0;
+    let data: Option<Id<NSObject, Shared>> =
+        msg_send_id![class!(NSJSONSerialization), dataWithJSONObject: &*array, options: options, error: error];
+    let data = data.ok_or("failed to serialize script result")?;
+    let bytes: *const u8 = msg_send![&data, bytes];
+    let length: NSUInteger = msg_send![&data, length];
+    let mut values = serde_json::from_slice::<Vec<serde_json::Value>>(std::slice::from_raw_parts(bytes, length))?;
+    Ok(values.pop().unwrap_or_default())
+}
+
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window, source),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn add_init_script(window: &Window, source: String, all_frames: bool) -> BoxResult<UserScriptHandle> {
+    let (call_tx, call_rx) = oneshot::channel();
+    window.with_webview(move |webview| unsafe {
+        let webview = webview.WKWebView();
+        let configuration = webview.configuration();
+        let controller = configuration.userContentController();
+        let source = NSString::from_str(&source);
+        let time = WKUserScriptInjectionTimeAtDocumentStart;
+        let script = WKUserScript::initWithSource_injectionTime_forMainFrameOnly(
+            WKUserScript::alloc(),
+            &source,
+            time,
+            !all_frames,
+        );
+        controller.addUserScript(&script);
+        call_tx.send(UserScriptHandle(script)).ok();
+    })?;
+    Ok(call_rx.await?)
+}
+
+// NOTE: content worlds are only available from macOS 11 and iOS 14.
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window, script),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn add_scoped_user_script(window: &Window, script: UserScript) -> BoxResult<UserScriptHandle> {
+    let (call_tx, call_rx) = oneshot::channel();
+    window.with_webview(move |webview| unsafe {
+        let webview = webview.WKWebView();
+        let configuration = webview.configuration();
+        let controller = configuration.userContentController();
+        let source = NSString::from_str(&script.source);
+        let time = match script.injection_time {
+            InjectionTime::DocumentStart => WKUserScriptInjectionTimeAtDocumentStart,
+            InjectionTime::DocumentEnd => WKUserScriptInjectionTimeAtDocumentEnd,
+        };
+        let main_frame_only = Bool::new(script.main_frame_only);
+        let world: Id<NSObject, Shared> = match &script.isolated_world {
+            Some(name) => msg_send_id![class!(WKContentWorld), worldWithName: &*NSString::from_str(name)],
+            None => msg_send_id![class!(WKContentWorld), pageWorld],
+        };
+        let script: Id<WKUserScript, Shared> = msg_send_id![
+            msg_send_id![WKUserScript::class(), alloc],
+            initWithSource: &*source,
+            injectionTime: time,
+            forMainFrameOnly: main_frame_only,
+            inContentWorld: &*world,
+        ];
+        controller.addUserScript(&script);
+        call_tx.send(UserScriptHandle(script)).ok();
+    })?;
+    Ok(call_rx.await?)
+}
+
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window, handle),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn remove_init_script(window: &Window, handle: UserScriptHandle) -> BoxResult<()> {
+    let (call_tx, call_rx) = oneshot::channel();
+    window.with_webview(move |webview| unsafe {
+        let webview = webview.WKWebView();
+        let configuration = webview.configuration();
+        let controller = configuration.userContentController();
+        // NOTE: `WKUserContentController` can only remove all user scripts at once, so every other
+        // script (including those added by wry) is added back afterwards, in the same order.
+        let scripts = controller.userScripts();
+        controller.removeAllUserScripts();
+        for script in scripts.iter() {
+            if !std::ptr::eq(script, &*handle.0) {
+                controller.addUserScript(script);
+            }
+        }
+        call_tx.send(()).ok();
+    })?;
+    Ok(call_rx.await?)
+}
+
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window, handler),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn add_script_message_handler(
+    window: &Window,
+    name: &'static str,
+    handler: impl Fn(ScriptMessage) + Send + Sync + 'static,
+) -> BoxResult<()> {
+    window.with_webview(move |webview| unsafe {
+        let webview = webview.WKWebView();
+        let configuration = webview.configuration();
+        let controller = configuration.userContentController();
+        let handler = ScriptMessageHandler::new(handler);
+        controller.addScriptMessageHandler_name(&handler, &NSString::from_str(name));
+    })?;
+    Ok(())
+}
+
+declare_class!(
+    struct ScriptMessageHandler {
+        handler: IvarDrop<Box<Box<dyn Fn(ScriptMessage)>>>,
+    }
+
+    unsafe impl ClassType for ScriptMessageHandler {
+        type Super = NSObject;
+        const NAME: &'static str = "TauriWebviewUtilScriptMessageHandler";
+    }
+
+    unsafe impl ScriptMessageHandler {
+        #[sel(userContentController:didReceiveScriptMessage:)]
+        fn did_receive_script_message(&self, _controller: &WKUserContentController, message: &WKScriptMessage) {
+            unsafe {
+                let body = message.body();
+                let is_string: bool = msg_send![&body, isKindOfClass: NSString::class()];
+                if is_string {
+                    let body = Id::cast::<NSString>(body);
+                    let frame: Id<NSObject, Shared> = msg_send_id![message, frameInfo];
+                    let main_frame: bool = msg_send![&frame, isMainFrame];
+                    (self.handler)(ScriptMessage {
+                        body: body.to_string(),
+                        main_frame,
+                        url: security_origin_url(&frame),
+                    });
+                }
+            }
+        }
+    }
+);
+
+impl ScriptMessageHandler {
+    fn new(handler: impl Fn(ScriptMessage) + 'static) -> Id<Self, Shared> {
+        unsafe {
+            let this: Option<Id<Self, Owned>> = msg_send_id![msg_send_id![Self::class(), alloc], init];
+            match this {
+                None => unreachable!("initializer should never fail"),
+                Some(mut this) => {
+                    Ivar::write(&mut this.handler, Box::new(Box::new(handler)));
+                    this.into()
+                },
+            }
+        }
+    }
+}
+
+// NOTE: the security origin of a frame as a URL, where a port of 0 is the default port of the scheme.
+// Opaque origins have an empty protocol, and so no URL.
+unsafe fn security_origin_url(frame: &NSObject) -> Option<Url> {
+    let origin: Id<NSObject, Shared> = msg_send_id![frame, securityOrigin];
+    let protocol: Id<NSString, Shared> = msg_send_id![&origin, protocol];
+    let host: Id<NSString, Shared> = msg_send_id![&origin, host];
+    let port: NSInteger = msg_send![&origin, port];
+    let mut url = Url::parse(&format!("{}://{}", &*protocol, &*host)).ok()?;
+    if port != 0 {
+        url.set_port(u16::try_from(port).ok()).ok()?;
+    }
+    Some(url)
+}
+
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window, handler),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn add_load_error_handler(
+    window: &Window,
+    handler: impl Fn(LoadError) -> bool + Send + Sync + 'static,
+) -> BoxResult<()> {
+    window.with_webview(move |webview| unsafe {
+        static DELEGATE_KEY: u8 = 0;
+        let webview = webview.WKWebView();
+        // NOTE: wry installs its own navigation delegate, so it is wrapped by a proxy which
+        // observes failed loads and forwards every message to the original delegate.
+        let inner: Option<Id<NSObject, Shared>> = msg_send_id![&webview, navigationDelegate];
+        let delegate = NavigationDelegate::new(inner, handler, None, None, None, None, None);
+        let _: () = msg_send![&webview, setNavigationDelegate: &*delegate];
+        // NOTE: the navigation delegate property is weak, so the proxy is kept alive by
+        // associating it with the webview.
+        ffi::objc_setAssociatedObject(
+            Id::as_ptr(&webview) as *mut _,
+            &DELEGATE_KEY as *const u8 as *const _,
+            Id::as_ptr(&delegate) as *mut _,
+            ffi::OBJC_ASSOCIATION_RETAIN_NONATOMIC,
+        );
+    })?;
+    Ok(())
+}
+
+// NOTE: without a user agent, WKWebView goes back to its default one.
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn set_user_agent(window: &Window, user_agent: Option<String>) -> BoxResult<()> {
+    window.with_webview(move |webview| unsafe {
+        let webview = webview.WKWebView();
+        let user_agent = user_agent.map(|user_agent| NSString::from_str(&user_agent));
+        webview.setCustomUserAgent(user_agent.as_deref());
+    })?;
+    Ok(())
+}
+
+// NOTE: WKWebView has no public property for its default user agent, so it is read from the page
+// when no custom one is set.
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn user_agent(window: &Window) -> BoxResult<String> {
+    let (call_tx, call_rx) = oneshot::channel();
+    window.with_webview(move |webview| unsafe {
+        let webview = webview.WKWebView();
+        let user_agent = webview.customUserAgent().map(|user_agent| user_agent.to_string());
+        call_tx
+            .send(user_agent.filter(|user_agent| !user_agent.is_empty()))
+            .ok();
+    })?;
+    match call_rx.await? {
+        Some(user_agent) => Ok(user_agent),
+        None => eval_js(window, "navigator.userAgent".into())
+            .await?
+            .as_str()
+            .map(Into::into)
+            .ok_or_else(|| "user agent was not returned as a string".into()),
+    }
+}
+
+// NOTE: `pageZoom` is only available from macOS 11, before that only the magnification can be set,
+// which scales the rendered page like a pinch gesture instead of laying it out again.
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn set_zoom(window: &Window, factor: f64) -> BoxResult<()> {
+    let (call_tx, call_rx) = oneshot::channel();
+    window.with_webview(move |webview| unsafe {
+        let webview = webview.WKWebView();
+        let can_zoom: bool = msg_send![&webview, respondsToSelector: sel!(setPageZoom:)];
+        if can_zoom {
+            webview.setPageZoom(factor);
+        }
+        call_tx.send(can_zoom).ok();
+    })?;
+    if call_rx.await? {
+        Ok(())
+    } else {
+        Err(Error::UnsupportedPlatform("page zoom requires macOS 11 with WKWebView").into())
+    }
+}
+
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn zoom(window: &Window) -> BoxResult<f64> {
+    let (call_tx, call_rx) = oneshot::channel();
+    window.with_webview(move |webview| unsafe {
+        let webview = webview.WKWebView();
+        let can_zoom: bool = msg_send![&webview, respondsToSelector: sel!(pageZoom)];
+        call_tx.send(if can_zoom { webview.pageZoom() } else { 1.0 }).ok();
+    })?;
+    Ok(call_rx.await?)
+}
+
+// NOTE: the navigation delegate of the webview is wrapped by another proxy, like it is for load
+// errors. The custom user agent the webview had when this was called is restored for navigations
+// without one.
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window, policy),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn add_navigation_policy(
+    window: &Window,
+    policy: impl Fn(&str) -> NavigationDecision + Send + Sync + 'static,
+) -> BoxResult<()> {
+    window.with_webview(move |webview| unsafe {
+        static DELEGATE_KEY: u8 = 0;
+        let webview = webview.WKWebView();
+        let default = webview.customUserAgent().map(|user_agent| user_agent.to_string());
+        let policy = move |url: &str| {
+            let mut decision = policy(url);
+            decision.user_agent = decision.user_agent.or_else(|| default.clone());
+            decision
+        };
+        let inner: Option<Id<NSObject, Shared>> = msg_send_id![&webview, navigationDelegate];
+        let delegate = NavigationDelegate::new(
+            inner,
+            |_| false,
+            Some(Box::new(Box::new(policy))),
+            None,
+            None,
+            None,
+            None,
+        );
+        let _: () = msg_send![&webview, setNavigationDelegate: &*delegate];
+        ffi::objc_setAssociatedObject(
+            Id::as_ptr(&webview) as *mut _,
+            &DELEGATE_KEY as *const u8 as *const _,
+            Id::as_ptr(&delegate) as *mut _,
+            ffi::OBJC_ASSOCIATION_RETAIN_NONATOMIC,
+        );
+    })?;
+    Ok(())
+}
+
+// NOTE: WKWebView only downloads when the navigation delegate decides so, which the proxy does for
+// navigations that ask for it and for responses that are not shown (since macOS 11.3). Downloads
+// can only be cancelled, since resuming needs the data of a cancelled download, and their progress
+// is polled.
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window, observer),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn add_download_observer(
+    window: &Window,
+    observer: impl Fn(DownloadUpdate) + Send + Sync + 'static,
+) -> BoxResult<()> {
+    let observer: DownloadObserver = Arc::new(observer);
+    window.with_webview({
+        let window = window.clone();
+        move |webview| unsafe {
+            static DELEGATE_KEY: u8 = 0;
+            static DOWNLOAD_DELEGATE_KEY: u8 = 0;
+            let webview = webview.WKWebView();
+            let download = move |download: &Object| {
+                let delegate = DownloadDelegate::new(window.clone(), observer.clone());
+                let _: () = msg_send![download, setDelegate: &*delegate];
+                // NOTE: the download delegate property is weak as well.
+                ffi::objc_setAssociatedObject(
+                    download as *const Object as *mut _,
+                    &DOWNLOAD_DELEGATE_KEY as *const u8 as *const _,
+                    Id::as_ptr(&delegate) as *mut _,
+                    ffi::OBJC_ASSOCIATION_RETAIN_NONATOMIC,
+                );
+            };
+            let inner: Option<Id<NSObject, Shared>> = msg_send_id![&webview, navigationDelegate];
+            let delegate = NavigationDelegate::new(
+                inner,
+                |_| false,
+                None,
+                None,
+                None,
+                Some(Box::new(Box::new(download))),
+                None,
+            );
+            let _: () = msg_send![&webview, setNavigationDelegate: &*delegate];
+            ffi::objc_setAssociatedObject(
+                Id::as_ptr(&webview) as *mut _,
+                &DELEGATE_KEY as *const u8 as *const _,
+                Id::as_ptr(&delegate) as *mut _,
+                ffi::OBJC_ASSOCIATION_RETAIN_NONATOMIC,
+            );
+        }
+    })?;
+    Ok(())
+}
+
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window, download),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn cancel_download(window: &Window, download: NativeDownload) -> BoxResult<()> {
+    window.with_webview(move |_webview| unsafe {
+        let completion_handler = ConcreteBlock::new(|_resume_data: *mut NSData| {}).copy();
+        let _: () = msg_send![&download.0, cancel: &*completion_handler];
+    })?;
+    Ok(())
+}
+
+#[allow(unused_variables)]
+pub(crate) async fn pause_download(window: &Window, download: NativeDownload) -> BoxResult<()> {
+    Err(Error::UnsupportedPlatform("pausing downloads is not supported with WKWebView").into())
+}
+
+#[allow(unused_variables)]
+pub(crate) async fn resume_download(window: &Window, download: NativeDownload) -> BoxResult<()> {
+    Err(Error::UnsupportedPlatform("resuming downloads is not supported with WKWebView").into())
+}
+
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window, observer),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn add_navigation_observer(
+    window: &Window,
+    observer: impl Fn(NavigationEvent) + Send + Sync + 'static,
+) -> BoxResult<()> {
+    window.with_webview(move |webview| unsafe {
+        static DELEGATE_KEY: u8 = 0;
+        let webview = webview.WKWebView();
+        let inner: Option<Id<NSObject, Shared>> = msg_send_id![&webview, navigationDelegate];
+        let delegate = NavigationDelegate::new(
+            inner,
+            |_| false,
+            None,
+            Some(Box::new(Box::new(observer))),
+            None,
+            None,
+            None,
+        );
+        let _: () = msg_send![&webview, setNavigationDelegate: &*delegate];
+        ffi::objc_setAssociatedObject(
+            Id::as_ptr(&webview) as *mut _,
+            &DELEGATE_KEY as *const u8 as *const _,
+            Id::as_ptr(&delegate) as *mut _,
+            ffi::OBJC_ASSOCIATION_RETAIN_NONATOMIC,
+        );
+    })?;
+    Ok(())
+}
+
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window, handler),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn add_process_crash_handler(
+    window: &Window,
+    handler: impl Fn() + Send + Sync + 'static,
+) -> BoxResult<()> {
+    window.with_webview(move |webview| unsafe {
+        static DELEGATE_KEY: u8 = 0;
+        let webview = webview.WKWebView();
+        let inner: Option<Id<NSObject, Shared>> = msg_send_id![&webview, navigationDelegate];
+        let delegate = NavigationDelegate::new(
+            inner,
+            |_| false,
+            None,
+            None,
+            Some(Box::new(Box::new(handler))),
+            None,
+            None,
+        );
+        let _: () = msg_send![&webview, setNavigationDelegate: &*delegate];
+        ffi::objc_setAssociatedObject(
+            Id::as_ptr(&webview) as *mut _,
+            &DELEGATE_KEY as *const u8 as *const _,
+            Id::as_ptr(&delegate) as *mut _,
+            ffi::OBJC_ASSOCIATION_RETAIN_NONATOMIC,
+        );
+    })?;
+    Ok(())
+}
+
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window, handler),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn add_auth_handler(window: &Window, handler: AuthHandler) -> BoxResult<()> {
+    let challenge = ChallengeHandler::Auth(window.clone(), handler);
+    window.with_webview(move |webview| unsafe {
+        static DELEGATE_KEY: u8 = 0;
+        let webview = webview.WKWebView();
+        let inner: Option<Id<NSObject, Shared>> = msg_send_id![&webview, navigationDelegate];
+        let delegate = NavigationDelegate::new(inner, |_| false, None, None, None, None, Some(Box::new(challenge)));
+        let _: () = msg_send![&webview, setNavigationDelegate: &*delegate];
+        ffi::objc_setAssociatedObject(
+            Id::as_ptr(&webview) as *mut _,
+            &DELEGATE_KEY as *const u8 as *const _,
+            Id::as_ptr(&delegate) as *mut _,
+            ffi::OBJC_ASSOCIATION_RETAIN_NONATOMIC,
+        );
+    })?;
+    Ok(())
+}
+
+// NOTE: server trust is evaluated on the main thread, which can block it while the certificate
+// chain is being checked.
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window, handler),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn add_certificate_error_handler(
+    window: &Window,
+    handler: impl Fn(CertificateErrorInfo) -> CertDecision + Send + Sync + 'static,
+) -> BoxResult<()> {
+    window.with_webview(move |webview| unsafe {
+        static DELEGATE_KEY: u8 = 0;
+        let webview = webview.WKWebView();
+        let inner: Option<Id<NSObject, Shared>> = msg_send_id![&webview, navigationDelegate];
+        let delegate = NavigationDelegate::new(
+            inner,
+            |_| false,
+            None,
+            None,
+            None,
+            None,
+            Some(Box::new(ChallengeHandler::Certificate(Box::new(handler)))),
+        );
+        let _: () = msg_send![&webview, setNavigationDelegate: &*delegate];
+        ffi::objc_setAssociatedObject(
+            Id::as_ptr(&webview) as *mut _,
+            &DELEGATE_KEY as *const u8 as *const _,
+            Id::as_ptr(&delegate) as *mut _,
+            ffi::OBJC_ASSOCIATION_RETAIN_NONATOMIC,
+        );
+    })?;
+    Ok(())
+}
+
+// NOTE: media capture requests are only delegated since macOS 12, and geolocation and notification
+// requests only through private delegate methods.
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window, handler),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn add_permission_handler(
+    window: &Window,
+    handler: impl Fn(PermissionRequest) -> PermissionDecision + Send + Sync + 'static,
+) -> BoxResult<()> {
+    window.with_webview(move |webview| unsafe {
+        static DELEGATE_KEY: u8 = 0;
+        let webview = webview.WKWebView();
+        let inner: Option<Id<NSObject, Shared>> = msg_send_id![&webview, UIDelegate];
+        let delegate = UIDelegate::new(inner, handler);
+        let _: () = msg_send![&webview, setUIDelegate: &*delegate];
+        ffi::objc_setAssociatedObject(
+            Id::as_ptr(&webview) as *mut _,
+            &DELEGATE_KEY as *const u8 as *const _,
+            Id::as_ptr(&delegate) as *mut _,
+            ffi::OBJC_ASSOCIATION_RETAIN_NONATOMIC,
+        );
+    })?;
+    Ok(())
+}
+
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window, html),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn load_html(window: &Window, html: String, base_url: Option<Url>) -> BoxResult<()> {
+    window.with_webview(move |webview| unsafe {
+        let webview = webview.WKWebView();
+        let html = NSString::from_str(&html);
+        let base_url = base_url.and_then(|url| NSURL::URLWithString(&NSString::from_str(url.as_str())));
+        webview.loadHTMLString_baseURL(&html, base_url.as_deref());
+    })?;
+    Ok(())
+}
+
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn reload(window: &Window, ignore_cache: bool) -> BoxResult<()> {
+    window.with_webview(move |webview| unsafe {
+        let webview = webview.WKWebView();
+        if ignore_cache {
+            webview.reloadFromOrigin();
+        } else {
+            webview.reload();
+        }
+    })?;
+    Ok(())
+}
+
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn stop_loading(window: &Window) -> BoxResult<()> {
+    window.with_webview(move |webview| unsafe {
+        let webview = webview.WKWebView();
+        webview.stopLoading();
+    })?;
+    Ok(())
+}
+
+// NOTE: WKWebView only exposes the inspector through the private `_inspector` property, the same one
+// that wry uses for `open_devtools`.
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn open_devtools(window: &Window) -> BoxResult<()> {
+    let (call_tx, call_rx) = oneshot::channel::<BoxResult<()>>();
+    window.with_webview(move |webview| unsafe {
+        let webview = webview.WKWebView();
+        let result = match webview_inspector(&webview) {
+            Some(inspector) => {
+                let _: () = msg_send![&inspector, show];
+                Ok(())
+            },
+            None => Err(Error::PlatformApi("webview has no inspector".into()).into()),
+        };
+        call_tx.send(result).ok();
+    })?;
+    call_rx.await?
+}
+
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn close_devtools(window: &Window) -> BoxResult<()> {
+    window.with_webview(move |webview| unsafe {
+        let webview = webview.WKWebView();
+        if let Some(inspector) = webview_inspector(&webview) {
+            let _: () = msg_send![&inspector, close];
+        }
+    })?;
+    Ok(())
+}
+
+// NOTE: `inspectable` is only available from macOS 13.3, and before that the developer extras of the
+// preferences are all there is to enable the inspector.
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn set_devtools_enabled(window: &Window, enabled: bool) -> BoxResult<()> {
+    window.with_webview(move |webview| unsafe {
+        let webview = webview.WKWebView();
+        let can_set_inspectable: bool = msg_send![&webview, respondsToSelector: sel!(setInspectable:)];
+        if can_set_inspectable {
+            let _: () = msg_send![&webview, setInspectable: enabled];
+        }
+        let configuration = webview.configuration();
+        let preferences: Id<NSObject, Shared> = msg_send_id![&configuration, preferences];
+        let value = NSNumber::new_bool(enabled);
+        let key = NSString::from_str("developerExtrasEnabled");
+        let _: () = msg_send![&preferences, setValue: &*value, forKey: &*key];
+        if !enabled {
+            if let Some(inspector) = webview_inspector(&webview) {
+                let _: () = msg_send![&inspector, close];
+            }
+        }
+    })?;
+    Ok(())
+}
+
+unsafe fn webview_inspector(webview: &WKWebView) -> Option<Id<NSObject, Shared>> {
+    let has_inspector: bool = msg_send![webview, respondsToSelector: sel!(_inspector)];
+    if has_inspector {
+        msg_send_id![webview, _inspector]
+    } else {
+        None
+    }
+}
+
+const AUDIO_MUTED: NSUInteger = 1 << 0;
+
+// NOTE: WKWebView only exposes muting through the private `_setPageMuted:` and `_mediaMutedState`,
+// where the first bit of the state is for audio, and whether it plays audio through the private
+// `_isPlayingAudio`.
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn set_muted(window: &Window, muted: bool) -> BoxResult<()> {
+    let (call_tx, call_rx) = oneshot::channel::<BoxResult<()>>();
+    window.with_webview(move |webview| unsafe {
+        let webview = webview.WKWebView();
+        let can_mute: bool = msg_send![&webview, respondsToSelector: sel!(_setPageMuted:)];
+        let result = if can_mute {
+            let state: NSUInteger = msg_send![&webview, _mediaMutedState];
+            let state = if muted {
+                state | AUDIO_MUTED
+            } else {
+                state & !AUDIO_MUTED
+            };
+            let _: () = msg_send![&webview, _setPageMuted: state];
+            Ok(())
+        } else {
+            Err(Error::UnsupportedPlatform("muting is not supported by this version of WKWebView").into())
+        };
+        call_tx.send(result).ok();
+    })?;
+    call_rx.await?
+}
+
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn is_muted(window: &Window) -> BoxResult<bool> {
+    let (call_tx, call_rx) = oneshot::channel();
+    window.with_webview(move |webview| unsafe {
+        let webview = webview.WKWebView();
+        let has_state: bool = msg_send![&webview, respondsToSelector: sel!(_mediaMutedState)];
+        let muted = has_state && {
+            let state: NSUInteger = msg_send![&webview, _mediaMutedState];
+            state & AUDIO_MUTED != 0
+        };
+        call_tx.send(muted).ok();
+    })?;
+    Ok(call_rx.await?)
+}
+
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn is_playing_audio(window: &Window) -> BoxResult<bool> {
+    let (call_tx, call_rx) = oneshot::channel::<BoxResult<bool>>();
+    window.with_webview(move |webview| unsafe {
+        let webview = webview.WKWebView();
+        let has_state: bool = msg_send![&webview, respondsToSelector: sel!(_isPlayingAudio)];
+        let result = if has_state {
+            let playing: bool = msg_send![&webview, _isPlayingAudio];
+            Ok(playing)
+        } else {
+            Err(Error::UnsupportedPlatform("audio state is not supported by this version of WKWebView").into())
+        };
+        call_tx.send(result).ok();
+    })?;
+    call_rx.await?
+}
+
+// NOTE: WKWebView has no API for emulating devices, so they are emulated by resizing the window and
+// in the page.
+#[allow(unused_variables)]
+pub(crate) async fn emulate_device(window: &Window, profile: Option<&DeviceProfile>) -> BoxResult<bool> {
+    Ok(false)
+}
+
+// NOTE: WKWebView has no API for emulating media, so it is emulated in the page.
+#[allow(unused_variables)]
+pub(crate) async fn emulate_media(window: &Window, media: Option<MediaType>) -> BoxResult<bool> {
+    Ok(false)
+}
+
+// NOTE: `prefers-color-scheme` follows the appearance of the webview, which is inherited from the
+// window unless it is set. The default background under the page follows it as well.
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn set_color_scheme(window: &Window, scheme: ColorScheme) -> BoxResult<()> {
+    window.with_webview(move |webview| unsafe {
+        let webview = webview.WKWebView();
+        let name = match scheme {
+            ColorScheme::Auto => None,
+            ColorScheme::Light => Some("NSAppearanceNameAqua"),
+            ColorScheme::Dark => Some("NSAppearanceNameDarkAqua"),
+        };
+        let appearance: Option<Id<NSObject, Shared>> = match name {
+            Some(name) => msg_send_id![class!(NSAppearance), appearanceNamed: &*NSString::from_str(name)],
+            None => None,
+        };
+        let _: () = msg_send![&webview, setAppearance: appearance.as_deref()];
+    })?;
+    Ok(())
+}
+
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn go_back(window: &Window) -> BoxResult<()> {
+    window.with_webview(move |webview| unsafe {
+        let webview = webview.WKWebView();
+        webview.goBack();
+    })?;
+    Ok(())
+}
+
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn go_forward(window: &Window) -> BoxResult<()> {
+    window.with_webview(move |webview| unsafe {
+        let webview = webview.WKWebView();
+        webview.goForward();
+    })?;
+    Ok(())
+}
+
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn can_go_back(window: &Window) -> BoxResult<bool> {
+    let (call_tx, call_rx) = oneshot::channel();
+    window.with_webview(move |webview| unsafe {
+        let webview = webview.WKWebView();
+        call_tx.send(webview.canGoBack()).ok();
+    })?;
+    Ok(call_rx.await?)
+}
+
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn can_go_forward(window: &Window) -> BoxResult<bool> {
+    let (call_tx, call_rx) = oneshot::channel();
+    window.with_webview(move |webview| unsafe {
+        let webview = webview.WKWebView();
+        call_tx.send(webview.canGoForward()).ok();
+    })?;
+    Ok(call_rx.await?)
+}
+
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn back_forward_list(window: &Window) -> BoxResult<Vec<HistoryEntry>> {
+    let (call_tx, call_rx) = oneshot::channel();
+    window.with_webview(move |webview| unsafe {
+        let webview = webview.WKWebView();
+        let list: Id<NSObject, Shared> = msg_send_id![&webview, backForwardList];
+        let back_list: Id<NSObject, Shared> = msg_send_id![&list, backList];
+        let forward_list: Id<NSObject, Shared> = msg_send_id![&list, forwardList];
+        let back: NSUInteger = msg_send![&back_list, count];
+        let forward: NSUInteger = msg_send![&forward_list, count];
+        let mut entries = vec![];
+        for index in -(back as NSInteger) ..= forward as NSInteger {
+            let item: Option<Id<NSObject, Shared>> = msg_send_id![&list, itemAtIndex: index];
+            let item = match item {
+                Some(item) => item,
+                None => continue,
+            };
+            let url: Option<Id<NSURL, Shared>> = msg_send_id![&item, URL];
+            let url = url.and_then(|url| url.absoluteString());
+            if let Some(url) = url.and_then(|url| Url::parse(&url.to_string()).ok()) {
+                let title: Option<Id<NSString, Shared>> = msg_send_id![&item, title];
+                let title = title.map(|title| title.to_string()).filter(|title| !title.is_empty());
+                entries.push(HistoryEntry {
+                    url,
+                    title,
+                    current: index == 0,
+                });
+            }
+        }
+        call_tx.send(entries).ok();
+    })?;
+    Ok(call_rx.await?)
+}
+
+// NOTE: the PDF viewer of WKWebView has no public settings.
+#[allow(unused_variables)]
+pub(crate) async fn set_pdf_settings(window: &Window, settings: PdfViewerSettings) -> BoxResult<()> {
+    if settings == PdfViewerSettings::default() {
+        Ok(())
+    } else {
+        Err(Error::UnsupportedPlatform("pdf viewer settings are not supported with WKWebView").into())
+    }
+}
+
+// NOTE: WKWebView ignores `window.print()` before macOS 11, so the page is printed with a print
+// operation instead, in the same way as `tauri::Window::print`.
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn print(window: &Window) -> BoxResult<bool> {
+    let (call_tx, call_rx) = oneshot::channel();
+    window.with_webview(move |webview| unsafe {
+        let webview = webview.WKWebView();
+        let can_print: bool = msg_send![&webview, respondsToSelector: sel!(printOperationWithPrintInfo:)];
+        if can_print {
+            let print_info: *mut Object = msg_send![class!(NSPrintInfo), sharedPrintInfo];
+            let operation: *mut Object = msg_send![&webview, printOperationWithPrintInfo: print_info];
+            let _: () = msg_send![operation, setCanSpawnSeparateThread: true];
+            let ns_window: *mut Object = msg_send![&webview, window];
+            let null = std::ptr::null::<std::ffi::c_void>();
+            let _: () = msg_send![
+                operation,
+                runOperationModalForWindow: ns_window,
+                delegate: null,
+                didRunSelector: null,
+                contextInfo: null,
+            ];
+        }
+        call_tx.send(can_print).ok();
+    })?;
+    Ok(call_rx.await?)
+}
+
+// NOTE: `createPDFWithConfiguration:` renders the whole page onto a single page the size of its
+// content, with backgrounds, so the page options are ignored.
+#[allow(unused_variables)]
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn print_to_pdf(window: &Window, path: PathBuf, options: PdfOptions) -> BoxResult<()> {
+    let (call_tx, call_rx) = oneshot::channel::<BoxResult<Vec<u8>>>();
+    window.with_webview(move |webview| unsafe {
+        let webview = webview.WKWebView();
+        let can_create: bool =
+            msg_send![&webview, respondsToSelector: sel!(createPDFWithConfiguration:completionHandler:)];
+        if !can_create {
+            let error = Error::UnsupportedPlatform("printing to PDF requires macOS 11 with WKWebView");
+            call_tx.send(Err(error.into())).ok();
+            return;
+        }
+        let call_tx = std::sync::Mutex::new(Some(call_tx));
+        let completion_handler = ConcreteBlock::new(move |data: *mut NSData, error: *mut NSError| {
+            #[cfg(feature = "tracing")]
+            crate::trace::callback("createPDF");
+            let result = match (data.as_ref(), error.as_ref()) {
+                (_, Some(error)) => Err(Error::PlatformApi(error.localizedDescription().to_string().into()).into()),
+                (Some(data), None) => Ok(data.bytes().to_vec()),
+                (None, None) => Err(Error::PlatformApi("no PDF data was created".into()).into()),
+            };
+            if let Some(call_tx) = call_tx.lock().ok().and_then(|mut call_tx| call_tx.take()) {
+                call_tx.send(result).ok();
+            }
+        })
+        .copy();
+        webview.createPDFWithConfiguration_completionHandler(None, &completion_handler);
+    })?;
+    let bytes = call_rx.await??;
+    std::fs::write(path, bytes)?;
+    Ok(())
+}
+
+// NOTE: snapshots are limited to the bounds of the webview, so only the viewport can be captured.
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn capture_screenshot(window: &Window, options: ScreenshotOptions) -> BoxResult<Vec<u8>> {
+    if options.region == ScreenshotRegion::FullPage {
+        return Err(Error::UnsupportedPlatform("full page screenshots are not supported with WKWebView").into());
+    }
+    take_snapshot(window, None, options.format).await
+}
+
+// NOTE: the rect of a snapshot is in the coordinates of the view, so only the visible part of the
+// element is captured.
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn capture_clip(window: &Window, clip: ClipRect, format: ScreenshotFormat) -> BoxResult<Vec<u8>> {
+    let clip = clip.visible();
+    if clip.width <= 0.0 || clip.height <= 0.0 {
+        return Err(Error::Script("the element is not within the viewport".into()).into());
+    }
+    let rect = CGRect::new(CGPoint::new(clip.x, clip.y), CGSize::new(clip.width, clip.height));
+    take_snapshot(window, Some(rect), format).await
+}
+
+async fn take_snapshot(window: &Window, rect: Option<CGRect>, format: ScreenshotFormat) -> BoxResult<Vec<u8>> {
+    // NOTE: `NSBitmapImageFileTypeJPEG` and `NSBitmapImageFileTypePNG`.
+    let file_type: NSUInteger = match format {
+        ScreenshotFormat::Png => 4,
+        ScreenshotFormat::Jpeg => 3,
+    };
+    let (call_tx, call_rx) = oneshot::channel::<BoxResult<Vec<u8>>>();
+    window.with_webview(move |webview| unsafe {
+        let webview = webview.WKWebView();
+        let call_tx = std::sync::Mutex::new(Some(call_tx));
+        let completion_handler = ConcreteBlock::new(move |image: *mut Object, error: *mut NSError| {
+            #[cfg(feature = "tracing")]
+            crate::trace::callback("takeSnapshot");
+            let result = match (image.as_ref(), error.as_ref()) {
+                (_, Some(error)) => Err(Error::PlatformApi(error.localizedDescription().to_string().into()).into()),
+                (Some(image), None) => encode_image(image, file_type),
+                (None, None) => Err(Error::PlatformApi("no snapshot was taken".into()).into()),
+            };
+            if let Some(call_tx) = call_tx.lock().ok().and_then(|mut call_tx| call_tx.take()) {
+                call_tx.send(result).ok();
+            }
+        })
+        .copy();
+        let configuration = rect.map(|rect| {
+            let configuration = WKSnapshotConfiguration::new();
+            configuration.setRect(rect);
+            configuration
+        });
+        let configuration = configuration.as_deref().map_or(std::ptr::null(), |configuration| {
+            configuration as *const WKSnapshotConfiguration
+        });
+        let _: () = msg_send![
+            &webview,
+            takeSnapshotWithConfiguration: configuration,
+            completionHandler: &*completion_handler,
+        ];
+    })?;
+    call_rx.await?
+}
+
+unsafe fn encode_image(image: &Object, file_type: NSUInteger) -> BoxResult<Vec<u8>> {
+    let tiff: *mut Object = msg_send![image, TIFFRepresentation];
+    let representation: *mut Object = msg_send![class!(NSBitmapImageRep), imageRepWithData: tiff];
+    if representation.is_null() {
+        return Err(Error::PlatformApi("failed to read snapshot".into()).into());
+    }
+    let properties: *mut Object = msg_send![class!(NSDictionary), dictionary];
+    let data: *mut Object = msg_send![representation, representationUsingType: file_type, properties: properties];
+    if data.is_null() {
+        return Err(Error::PlatformApi("failed to encode snapshot".into()).into());
+    }
+    let bytes: *const u8 = msg_send![data, bytes];
+    let length: NSUInteger = msg_send![data, length];
+    Ok(std::slice::from_raw_parts(bytes, length).to_vec())
+}
+
+thread_local! {
+    static SYNTHESIZER: std::cell::RefCell<Option<Id<NSObject, Shared>>> = std::cell::RefCell::new(None);
+}
+
+// NOTE: a single `NSSpeechSynthesizer` is shared by all windows, and kept on the main thread.
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window, text),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn speak(window: &Window, text: String) -> BoxResult<()> {
+    let (call_tx, call_rx) = oneshot::channel::<BoxResult<()>>();
+    window.run_on_main_thread(move || unsafe {
+        let result = SYNTHESIZER.with(|synthesizer| {
+            let mut synthesizer = synthesizer.borrow_mut();
+            if synthesizer.is_none() {
+                *synthesizer = msg_send_id![msg_send_id![class!(NSSpeechSynthesizer), alloc], init];
+            }
+            let synthesizer = synthesizer.as_ref().ok_or("failed to create speech synthesizer")?;
+            let _: () = msg_send![synthesizer, stopSpeaking];
+            let text = NSString::from_str(&text);
+            let started: bool = msg_send![synthesizer, startSpeakingString: &*text];
+            if started {
+                Ok(())
+            } else {
+                Err("speech synthesizer did not start speaking".into())
+            }
+        });
+        call_tx.send(result).ok();
+    })?;
+    call_rx.await?
+}
+
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn stop_speaking(window: &Window) -> BoxResult<()> {
+    window.run_on_main_thread(move || unsafe {
+        SYNTHESIZER.with(|synthesizer| {
+            if let Some(synthesizer) = &*synthesizer.borrow() {
+                let _: () = msg_send![synthesizer, stopSpeaking];
+            }
+        });
+    })?;
+    Ok(())
+}
+
+// NOTE: WKWebView only has a private preference for the page cache, so pages restored from it are reloaded instead.
+#[allow(unused_variables)]
+pub(crate) async fn disable_page_cache(window: &Window) -> BoxResult<bool> {
+    Ok(false)
+}
+
+// NOTE: WKWebView always composites with the GPU.
+#[allow(unused_variables)]
+pub(crate) async fn disable_hardware_acceleration(window: &Window) -> BoxResult<bool> {
+    Ok(false)
+}
+
+// NOTE: WKWebView has no setting for bypassing service workers. Their registrations can be removed with
+// `DataKinds::SERVICE_WORKERS` instead.
+#[allow(unused_variables)]
+pub(crate) async fn set_bypass_service_worker(window: &Window, bypass: bool) -> BoxResult<()> {
+    if bypass {
+        Err(Error::UnsupportedPlatform("bypassing service workers is not supported with WKWebView").into())
+    } else {
+        Ok(())
+    }
+}
+
+// NOTE: the proxy configurations of `WKWebsiteDataStore` were added in macOS 14, and are built with
+// functions of Network.framework from the same release. Those are looked up when they are used, so
+// that the app still launches on older releases. The data store is shared by every webview of the
+// app.
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window, config),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn set_proxy(window: &Window, config: ProxyConfig) -> BoxResult<()> {
+    let (call_tx, call_rx) = oneshot::channel();
+    window.with_webview(move |webview| unsafe {
+        let webview = webview.WKWebView();
+        let data_store = webview.configuration().websiteDataStore();
+        let can_proxy: bool = msg_send![&data_store, respondsToSelector: sel!(setProxyConfigurations:)];
+        let result = match proxy_configuration(&config) {
+            Some(proxy) if can_proxy => {
+                let proxies: Id<NSObject, Shared> = msg_send_id![class!(NSArray), arrayWithObject: proxy];
+                network::nw_release(proxy);
+                let _: () = msg_send![&data_store, setProxyConfigurations: &*proxies];
+                Ok(())
+            },
+            _ => Err(Error::UnsupportedPlatform("proxies require macOS 14 with WKWebView").into()),
+        };
+        call_tx.send(result).ok();
+    })?;
+    call_rx.await?
+}
+
+unsafe fn proxy_configuration(config: &ProxyConfig) -> Option<*mut Object> {
+    use std::ffi::{c_char, CString};
+
+    type CreateHttpConnect = unsafe extern fn(*mut Object, *mut Object) -> *mut Object;
+    type CreateSocksV5 = unsafe extern fn(*mut Object) -> *mut Object;
+    type SetUsernameAndPassword = unsafe extern fn(*mut Object, *const c_char, *const c_char);
+    type AddExcludedDomain = unsafe extern fn(*mut Object, *const c_char);
+
+    let host = CString::new(config.host.as_str()).ok()?;
+    let port = CString::new(config.port.to_string()).ok()?;
+    let endpoint = network::nw_endpoint_create_host(host.as_ptr(), port.as_ptr());
+    if endpoint.is_null() {
+        return None;
+    }
+    let proxy = match config.scheme {
+        ProxyScheme::Http | ProxyScheme::Https => {
+            let create = network::symbol::<CreateHttpConnect>("nw_proxy_config_create_http_connect");
+            let tls_options = match config.scheme {
+                ProxyScheme::Https => network::nw_tls_create_options(),
+                _ => std::ptr::null_mut(),
+            };
+            let proxy = create.map(|create| create(endpoint, tls_options));
+            if !tls_options.is_null() {
+                network::nw_release(tls_options);
+            }
+            proxy
+        },
+        ProxyScheme::Socks5 => {
+            let create = network::symbol::<CreateSocksV5>("nw_proxy_config_create_socksv5");
+            create.map(|create| create(endpoint))
+        },
+    };
+    network::nw_release(endpoint);
+    let proxy = proxy.filter(|proxy| !proxy.is_null())?;
+    if let Some(ProxyCredentials { username, password }) = &config.credentials {
+        let set = network::symbol::<SetUsernameAndPassword>("nw_proxy_config_set_username_and_password");
+        match (set, CString::new(username.as_str()), CString::new(password.as_str())) {
+            (Some(set), Ok(username), Ok(password)) => set(proxy, username.as_ptr(), password.as_ptr()),
+            _ => {
+                network::nw_release(proxy);
+                return None;
+            },
+        }
+    }
+    if let Some(add) = network::symbol::<AddExcludedDomain>("nw_proxy_config_add_excluded_domain") {
+        for domain in &config.bypass {
+            if let Ok(domain) = CString::new(domain.trim_start_matches("*.")) {
+                add(proxy, domain.as_ptr());
+            }
+        }
+    }
+    Some(proxy)
+}
+
+mod network {
+    use icrate::objc2::runtime::Object;
+    use std::ffi::{c_char, c_void};
+
+    #[link(name = "Network", kind = "framework")]
+    extern {
+        pub(super) fn nw_endpoint_create_host(hostname: *const c_char, port: *const c_char) -> *mut Object;
+        pub(super) fn nw_tls_create_options() -> *mut Object;
+        pub(super) fn nw_release(object: *mut Object);
+    }
+
+    extern {
+        fn dlsym(handle: *mut c_void, symbol: *const c_char) -> *mut c_void;
+    }
+
+    // NOTE: `RTLD_DEFAULT` searches every image that is loaded into the process.
+    const RTLD_DEFAULT: *mut c_void = -2isize as *mut c_void;
+
+    pub(super) unsafe fn symbol<F: Copy>(name: &str) -> Option<F> {
+        let name = std::ffi::CString::new(name).ok()?;
+        let symbol = dlsym(RTLD_DEFAULT, name.as_ptr());
+        if symbol.is_null() {
+            None
+        } else {
+            Some(std::mem::transmute_copy::<*mut c_void, F>(&symbol))
+        }
+    }
+}
+
+// NOTE: WKWebView has no API for observing WebSocket frames, so they are observed in the page.
+#[allow(unused_variables)]
+pub(crate) async fn add_websocket_observer(
+    window: &Window,
+    observer: impl Fn(WsEvent) + Send + Sync + 'static,
+) -> BoxResult<bool> {
+    Ok(false)
+}
+
+// NOTE: WKWebView has no screencast, so recordings are made of snapshots instead.
+#[allow(unused_variables)]
+pub(crate) async fn add_screencast_observer(
+    window: &Window,
+    observer: impl Fn(Vec<u8>) + Send + Sync + 'static,
+) -> BoxResult<bool> {
+    Ok(false)
+}
+
+#[allow(unused_variables)]
+pub(crate) async fn start_screencast(window: &Window, format: ScreenshotFormat, every_nth_frame: u32) -> BoxResult<()> {
+    Err(Error::UnsupportedPlatform("screencasts are not supported with WKWebView").into())
+}
+
+#[allow(unused_variables)]
+pub(crate) async fn stop_screencast(window: &Window) -> BoxResult<()> {
+    Ok(())
+}
+
+// NOTE: certificate transparency failures are not reported separately by WKWebView, they surface
+// as TLS load errors instead.
+#[allow(unused_variables)]
+pub(crate) async fn add_certificate_transparency_handler(
+    window: &Window,
+    handler: impl Fn(Option<String>) + Send + Sync + 'static,
+) -> BoxResult<()> {
+    Ok(())
+}
+
+// NOTE: a `WKURLSchemeHandler` can only be set on the configuration a webview is created with, so
+// schemes have to be registered with `tauri::Builder::register_uri_scheme_protocol` instead, for
+// which `directory_protocol` and `scheme_protocol` create the handlers.
+#[allow(unused_variables)]
+pub(crate) async fn serve_scheme(window: &Window, scheme: String, handler: SchemeHandler) -> BoxResult<()> {
+    Err(
+        Error::UnsupportedPlatform("custom schemes can not be registered for an existing webview with WKWebView")
+            .into(),
+    )
+}
+
+thread_local! {
+    static PRELOADED: std::cell::RefCell<Option<Id<WKWebView, Shared>>> = std::cell::RefCell::new(None);
+}
+
+// NOTE: WebKit shares its web content and networking processes between the webviews of an app, so
+// a hidden webview that loads a page starts them before the first window. The webview is kept, so
+// that the processes keep running until the windows use them.
+#[allow(unused_variables)]
+pub(crate) fn preload_engine(
+    data_directory: Option<PathBuf>,
+    config: &crate::PreloadConfig,
+    timings: &mut crate::PreloadTimings,
+) -> BoxResult<()> {
+    unsafe {
+        let configuration = timings.time("create configuration", || WKWebViewConfiguration::new());
+        let webview = timings.time("create webview", || {
+            let frame = CGRect::new(CGPoint::new(0.0, 0.0), CGSize::new(0.0, 0.0));
+            WKWebView::initWithFrame_configuration(WKWebView::alloc(), frame, &configuration)
+        });
+        timings.time("load blank page", || {
+            webview.loadHTMLString_baseURL(&NSString::from_str(""), None);
+        });
+        PRELOADED.with(|preloaded| *preloaded.borrow_mut() = Some(webview));
+    }
+    Ok(())
+}
+
+// NOTE: tauri buffers the responses of the protocol handlers it registers for WKWebView, so there is
+// no `WKURLSchemeTask` to stream a body to.
+#[allow(unused_variables)]
+pub(crate) async fn serve_scheme_stream(
+    window: &Window,
+    scheme: String,
+    handler: SchemeStreamHandler,
+) -> BoxResult<()> {
+    Err(Error::UnsupportedPlatform("streamed scheme responses are not supported with WKWebView").into())
+}
+
+#[allow(unused_variables)]
+pub(crate) async fn map_virtual_host(
+    window: &Window,
+    host: String,
+    folder: PathBuf,
+    cors: CorsPolicy,
+) -> BoxResult<Url> {
+    Err(Error::UnsupportedPlatform(
+        "virtual hosts can not be mapped for an existing webview with WKWebView, use `directory_protocol` instead",
+    )
+    .into())
+}
+
+// NOTE: schemes can only be registered when the app is built with WKWebView, where WebKit decides
+// itself whether their documents are secure contexts, and CORS is handled by the responses.
+#[allow(unused_variables)]
+pub(crate) async fn set_scheme_security(
+    window: &Window,
+    scheme: String,
+    secure: bool,
+    cors: CorsPolicy,
+) -> BoxResult<()> {
+    Ok(())
+}
+
+#[allow(unused_variables)]
+pub(crate) async fn load_extension(window: &Window, path: PathBuf) -> BoxResult<()> {
+    Err(Error::UnsupportedPlatform("browser extensions are not supported with WKWebView").into())
+}
+
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn set_blocked_urls(window: &Window, patterns: Vec<String>) -> BoxResult<()> {
+    static LIST_KEY: u8 = 0;
+    let rules = if patterns.is_empty() {
+        None
+    } else {
+        Some(crate::url_filter::content_rules(&patterns)?)
+    };
+    set_content_rule_list(window, crate::url_filter::CONTENT_FILTER_ID, &LIST_KEY, rules).await
+}
+
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window, rules),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn set_content_rules(window: &Window, rules: Arc<ContentRuleList>) -> BoxResult<()> {
+    static LIST_KEY: u8 = 0;
+    let rules = (!rules.is_empty()).then(|| rules.as_json().to_string());
+    set_content_rule_list(window, crate::content_rules::CONTENT_RULES_ID, &LIST_KEY, rules).await
+}
+
+// NOTE: the compiled list is kept on the user content controller under the key, so that it can be
+// replaced later without removing the lists added for other features.
+async fn set_content_rule_list(
+    window: &Window,
+    identifier: &'static str,
+    key: &'static u8,
+    rules: Option<String>,
+) -> BoxResult<()> {
+    let (call_tx, call_rx) = oneshot::channel::<BoxResult<()>>();
+    window.with_webview(move |webview| unsafe {
+        let webview = webview.WKWebView();
+        let configuration = webview.configuration();
+        let controller = configuration.userContentController();
+        let key = key as *const u8 as *const c_void;
+        let previous =
+            ffi::objc_getAssociatedObject(Id::as_ptr(&controller) as *const _, key) as *const WKContentRuleList;
+        if let Some(previous) = previous.as_ref() {
+            controller.removeContentRuleList(previous);
+            ffi::objc_setAssociatedObject(
+                Id::as_ptr(&controller) as *mut _,
+                key,
+                std::ptr::null_mut(),
+                ffi::OBJC_ASSOCIATION_RETAIN_NONATOMIC,
+            );
+        }
+        let (rules, store) = match (rules, WKContentRuleListStore::defaultStore()) {
+            (Some(rules), Some(store)) => (rules, store),
+            (Some(_), None) => {
+                let err = Error::PlatformApi("no content rule list store".into());
+                call_tx.send(Err(err.into())).ok();
+                return;
+            },
+            (None, _) => {
+                call_tx.send(Ok(())).ok();
+                return;
+            },
+        };
+        let call_tx = std::sync::Mutex::new(Some(call_tx));
+        let completion_handler = ConcreteBlock::new(move |list: *mut WKContentRuleList, error: *mut NSError| {
+            #[cfg(feature = "tracing")]
+            crate::trace::callback("compileContentRuleList");
+            let result = match (list.as_ref(), error.as_ref()) {
+                (Some(list), _) => {
+                    controller.addContentRuleList(list);
+                    ffi::objc_setAssociatedObject(
+                        Id::as_ptr(&controller) as *mut _,
+                        key,
+                        list as *const WKContentRuleList as *mut _,
+                        ffi::OBJC_ASSOCIATION_RETAIN_NONATOMIC,
+                    );
+                    Ok(())
+                },
+                (None, Some(error)) => Err(error.localizedDescription().to_string().into()),
+                (None, None) => Err(Error::PlatformApi("failed to compile content rule list".into()).into()),
+            };
+            if let Some(call_tx) = call_tx.lock().ok().and_then(|mut call_tx| call_tx.take()) {
+                call_tx.send(result).ok();
+            }
+        })
+        .copy();
+        store.compileContentRuleListForIdentifier_encodedContentRuleList_completionHandler(
+            Some(&NSString::from_str(identifier)),
+            Some(&NSString::from_str(&rules)),
+            Some(&completion_handler),
+        );
+    })?;
+    call_rx.await?
+}
+
+const NSURL_SESSION_AUTH_CHALLENGE_USE_CREDENTIAL: NSInteger = 0;
+const NSURL_SESSION_AUTH_CHALLENGE_PERFORM_DEFAULT_HANDLING: NSInteger = 1;
+
+enum ChallengeHandler {
+    Certificate(Box<dyn Fn(CertificateErrorInfo) -> CertDecision>),
+    Auth(Window, AuthHandler),
+}
+
+declare_class!(
+    struct NavigationDelegate {
+        inner: IvarDrop<Option<Id<NSObject, Shared>>>,
+        handler: IvarDrop<Box<Box<dyn Fn(LoadError) -> bool>>>,
+        policy: IvarDrop<Option<Box<Box<dyn Fn(&str) -> NavigationDecision>>>>,
+        observer: IvarDrop<Option<Box<Box<dyn Fn(NavigationEvent)>>>>,
+        terminated: IvarDrop<Option<Box<Box<dyn Fn()>>>>,
+        download: IvarDrop<Option<Box<Box<dyn Fn(&Object)>>>>,
+        challenge: IvarDrop<Option<Box<ChallengeHandler>>>,
+    }
+
+    unsafe impl ClassType for NavigationDelegate {
+        type Super = NSObject;
+        const NAME: &'static str = "TauriWebviewUtilNavigationDelegate";
+    }
+
+    unsafe impl NavigationDelegate {
+        #[sel(webView:didStartProvisionalNavigation:)]
+        fn did_start_provisional_navigation(&self, webview: &WKWebView, navigation: *mut Object) {
+            unsafe {
+                self.observe(webview, |url| NavigationEvent::Started { url });
+                if let Some(inner) = self.forward_target(sel!(webView:didStartProvisionalNavigation:)) {
+                    let _: () = msg_send![&inner, webView: webview, didStartProvisionalNavigation: navigation];
+                }
+            }
+        }
+
+        #[sel(webView:didCommitNavigation:)]
+        fn did_commit_navigation(&self, webview: &WKWebView, navigation: *mut Object) {
+            unsafe {
+                self.observe(webview, |url| NavigationEvent::Committed { url });
+                if let Some(inner) = self.forward_target(sel!(webView:didCommitNavigation:)) {
+                    let _: () = msg_send![&inner, webView: webview, didCommitNavigation: navigation];
+                }
+            }
+        }
+
+        #[sel(webView:didFinishNavigation:)]
+        fn did_finish_navigation(&self, webview: &WKWebView, navigation: *mut Object) {
+            unsafe {
+                self.observe(webview, |url| NavigationEvent::Finished { url });
+                if let Some(inner) = self.forward_target(sel!(webView:didFinishNavigation:)) {
+                    let _: () = msg_send![&inner, webView: webview, didFinishNavigation: navigation];
+                }
+            }
+        }
+
+        #[sel(webView:didFailProvisionalNavigation:withError:)]
+        fn did_fail_provisional_navigation(&self, webview: &WKWebView, navigation: *mut Object, error: &NSError) {
+            unsafe {
+                (self.handler)(load_error_from_ns_error(error));
+                if let Some(inner) = self.forward_target(sel!(webView:didFailProvisionalNavigation:withError:)) {
+                    let _: () = msg_send![
+                        &inner,
+                        webView: webview,
+                        didFailProvisionalNavigation: navigation,
+                        withError: error,
+                    ];
+                }
+            }
+        }
+
+        #[sel(webView:didFailNavigation:withError:)]
+        fn did_fail_navigation(&self, webview: &WKWebView, navigation: *mut Object, error: &NSError) {
+            unsafe {
+                (self.handler)(load_error_from_ns_error(error));
+                if let Some(inner) = self.forward_target(sel!(webView:didFailNavigation:withError:)) {
+                    let _: () = msg_send![&inner, webView: webview, didFailNavigation: navigation, withError: error];
+                }
+            }
+        }
+
+        // NOTE: the policy for main frame navigations is decided before their request is sent,
+        // which is also when the user agent is switched. Redirected and cancelled navigations are
+        // not forwarded to the inner delegate.
+        #[sel(webView:decidePolicyForNavigationAction:decisionHandler:)]
+        fn decide_policy_for_navigation_action(
+            &self,
+            webview: &WKWebView,
+            navigation_action: &Object,
+            decision_handler: &Block<(NSInteger,), ()>,
+        ) {
+            const WK_NAVIGATION_ACTION_POLICY_CANCEL: NSInteger = 0;
+            unsafe {
+                let url = main_frame_url_from_navigation_action(navigation_action);
+                if let (Some(policy), Some(url)) = (&*self.policy, url) {
+                    let NavigationDecision { policy, user_agent } = policy(&url);
+                    match policy {
+                        NavigationPolicy::Allow => {
+                            let user_agent = user_agent.map(|user_agent| NSString::from_str(&user_agent));
+                            webview.setCustomUserAgent(user_agent.as_deref());
+                        },
+                        NavigationPolicy::Redirect(url) => {
+                            decision_handler.call((WK_NAVIGATION_ACTION_POLICY_CANCEL,));
+                            if let Some(url) = NSURL::URLWithString(&NSString::from_str(&url)) {
+                                webview.loadRequest(&NSURLRequest::requestWithURL(&url));
+                            }
+                            return;
+                        },
+                        NavigationPolicy::Cancel => {
+                            decision_handler.call((WK_NAVIGATION_ACTION_POLICY_CANCEL,));
+                            return;
+                        },
+                    }
+                }
+                if self.download.is_some() && should_perform_download(navigation_action) {
+                    const WK_NAVIGATION_ACTION_POLICY_DOWNLOAD: NSInteger = 2;
+                    decision_handler.call((WK_NAVIGATION_ACTION_POLICY_DOWNLOAD,));
+                    return;
+                }
+                let selector = sel!(webView:decidePolicyForNavigationAction:decisionHandler:);
+                if let Some(inner) = self.forward_target(selector) {
+                    let _: () = msg_send![
+                        &inner,
+                        webView: webview,
+                        decidePolicyForNavigationAction: navigation_action,
+                        decisionHandler: decision_handler,
+                    ];
+                } else {
+                    const WK_NAVIGATION_ACTION_POLICY_ALLOW: NSInteger = 1;
+                    decision_handler.call((WK_NAVIGATION_ACTION_POLICY_ALLOW,));
+                }
+            }
+        }
+
+        // NOTE: WebKit does not treat HTTP error responses as failed loads, so the status of
+        // main frame responses is checked before the navigation policy is decided.
+        #[sel(webView:decidePolicyForNavigationResponse:decisionHandler:)]
+        fn decide_policy_for_navigation_response(
+            &self,
+            webview: &WKWebView,
+            navigation_response: &Object,
+            decision_handler: &Block<(NSInteger,), ()>,
+        ) {
+            unsafe {
+                if let Some(error) = load_error_from_navigation_response(navigation_response) {
+                    (self.handler)(error);
+                }
+                if self.download.is_some() && is_download_response(navigation_response) {
+                    const WK_NAVIGATION_RESPONSE_POLICY_DOWNLOAD: NSInteger = 2;
+                    decision_handler.call((WK_NAVIGATION_RESPONSE_POLICY_DOWNLOAD,));
+                    return;
+                }
+                let selector = sel!(webView:decidePolicyForNavigationResponse:decisionHandler:);
+                if let Some(inner) = self.forward_target(selector) {
+                    let _: () = msg_send![
+                        &inner,
+                        webView: webview,
+                        decidePolicyForNavigationResponse: navigation_response,
+                        decisionHandler: decision_handler,
+                    ];
+                } else {
+                    const WK_NAVIGATION_RESPONSE_POLICY_ALLOW: NSInteger = 1;
+                    decision_handler.call((WK_NAVIGATION_RESPONSE_POLICY_ALLOW,));
+                }
+            }
+        }
+
+        #[sel(webView:navigationAction:didBecomeDownload:)]
+        fn navigation_action_did_become_download(
+            &self,
+            webview: &WKWebView,
+            navigation_action: &Object,
+            download: &Object,
+        ) {
+            unsafe {
+                if let Some(observe) = &*self.download {
+                    observe(download);
+                }
+                if let Some(inner) = self.forward_target(sel!(webView:navigationAction:didBecomeDownload:)) {
+                    let _: () = msg_send![
+                        &inner,
+                        webView: webview,
+                        navigationAction: navigation_action,
+                        didBecomeDownload: download,
+                    ];
+                }
+            }
+        }
+
+        #[sel(webView:navigationResponse:didBecomeDownload:)]
+        fn navigation_response_did_become_download(
+            &self,
+            webview: &WKWebView,
+            navigation_response: &Object,
+            download: &Object,
+        ) {
+            unsafe {
+                if let Some(observe) = &*self.download {
+                    observe(download);
+                }
+                if let Some(inner) = self.forward_target(sel!(webView:navigationResponse:didBecomeDownload:)) {
+                    let _: () = msg_send![
+                        &inner,
+                        webView: webview,
+                        navigationResponse: navigation_response,
+                        didBecomeDownload: download,
+                    ];
+                }
+            }
+        }
+
+        // NOTE: server trust is evaluated here only when there is a certificate error handler, and
+        // certificates that are trusted are handled like before. Authentication challenges wait
+        // for the credentials of the handler, and the completion handler is called from the main
+        // thread once they are known.
+        #[sel(webView:didReceiveAuthenticationChallenge:completionHandler:)]
+        fn did_receive_authentication_challenge(
+            &self,
+            webview: &WKWebView,
+            challenge: &Object,
+            completion_handler: &Block<(NSInteger, *mut Object), ()>,
+        ) {
+            unsafe {
+                match self.challenge.as_deref() {
+                    Some(ChallengeHandler::Certificate(certificate)) => {
+                        if let Some((trust, info)) = server_trust_error(challenge) {
+                            if certificate(info) == CertDecision::Proceed {
+                                let credential: Id<NSObject, Shared> =
+                                    msg_send_id![class!(NSURLCredential), credentialForTrust: trust];
+                                completion_handler.call((
+                                    NSURL_SESSION_AUTH_CHALLENGE_USE_CREDENTIAL,
+                                    Id::as_ptr(&credential) as *mut Object,
+                                ));
+                                return;
+                            }
+                        }
+                    },
+                    Some(ChallengeHandler::Auth(window, handler)) => {
+                        if let Some(auth_challenge) = auth_challenge(challenge) {
+                            let credentials = handler(auth_challenge);
+                            let pending = MainThreadBound(RcBlock::copy(completion_handler as *const _ as *mut _));
+                            let window = window.clone();
+                            tauri::async_runtime::spawn(async move {
+                                let credentials = credentials.await;
+                                window
+                                    .run_on_main_thread(move || {
+                                        let MainThreadBound(completion_handler) = pending;
+                                        complete_auth_challenge(&completion_handler, credentials);
+                                    })
+                                    .ok();
+                            });
+                            return;
+                        }
+                    },
+                    None => {},
+                }
+                let selector = sel!(webView:didReceiveAuthenticationChallenge:completionHandler:);
+                if let Some(inner) = self.forward_target(selector) {
+                    let _: () = msg_send![
+                        &inner,
+                        webView: webview,
+                        didReceiveAuthenticationChallenge: challenge,
+                        completionHandler: completion_handler,
+                    ];
+                } else {
+                    completion_handler.call((NSURL_SESSION_AUTH_CHALLENGE_PERFORM_DEFAULT_HANDLING, std::ptr::null_mut()));
+                }
+            }
+        }
+
+        #[sel(webViewWebContentProcessDidTerminate:)]
+        fn web_content_process_did_terminate(&self, webview: &WKWebView) {
+            unsafe {
+                if let Some(terminated) = &*self.terminated {
+                    terminated();
+                }
+                if let Some(inner) = self.forward_target(sel!(webViewWebContentProcessDidTerminate:)) {
+                    let _: () = msg_send![&inner, webViewWebContentProcessDidTerminate: webview];
+                }
+            }
+        }
+
+        #[sel(respondsToSelector:)]
+        fn responds_to_selector(&self, selector: Sel) -> bool {
+            let responds: bool = unsafe { msg_send![super(self), respondsToSelector: selector] };
+            responds || self.forward_target(selector).is_some()
+        }
+
+        #[sel(forwardingTargetForSelector:)]
+        fn forwarding_target_for_selector(&self, selector: Sel) -> *mut Object {
+            match self.forward_target(selector) {
+                Some(inner) => Id::as_ptr(&inner) as *mut Object,
+                None => std::ptr::null_mut(),
+            }
+        }
+    }
+);
+
+impl NavigationDelegate {
+    fn new(
+        inner: Option<Id<NSObject, Shared>>,
+        handler: impl Fn(LoadError) -> bool + 'static,
+        policy: Option<Box<Box<dyn Fn(&str) -> NavigationDecision>>>,
+        observer: Option<Box<Box<dyn Fn(NavigationEvent)>>>,
+        terminated: Option<Box<Box<dyn Fn()>>>,
+        download: Option<Box<Box<dyn Fn(&Object)>>>,
+        challenge: Option<Box<ChallengeHandler>>,
+    ) -> Id<Self, Shared> {
+        unsafe {
+            let this: Option<Id<Self, Owned>> = msg_send_id![msg_send_id![Self::class(), alloc], init];
+            match this {
+                None => unreachable!("initializer should never fail"),
+                Some(mut this) => {
+                    Ivar::write(&mut this.inner, inner);
+                    Ivar::write(&mut this.handler, Box::new(Box::new(handler)));
+                    Ivar::write(&mut this.policy, policy);
+                    Ivar::write(&mut this.observer, observer);
+                    Ivar::write(&mut this.terminated, terminated);
+                    Ivar::write(&mut this.download, download);
+                    Ivar::write(&mut this.challenge, challenge);
+                    this.into()
+                },
+            }
+        }
+    }
+
+    unsafe fn observe(&self, webview: &WKWebView, event: impl FnOnce(Url) -> NavigationEvent) {
+        if let Some(observer) = &*self.observer {
+            let url = webview.URL().and_then(|url| url.absoluteString());
+            if let Some(url) = url.and_then(|url| Url::parse(&url.to_string()).ok()) {
+                observer(event(url));
+            }
+        }
+    }
+
+    fn forward_target(&self, selector: Sel) -> Option<Id<NSObject, Shared>> {
+        let inner = (*self.inner).clone()?;
+        let responds: bool = unsafe { msg_send![&inner, respondsToSelector: selector] };
+        responds.then_some(inner)
+    }
+}
+
+const WK_PERMISSION_DECISION_PROMPT: NSInteger = 0;
+const WK_PERMISSION_DECISION_GRANT: NSInteger = 1;
+const WK_PERMISSION_DECISION_DENY: NSInteger = 2;
+
+declare_class!(
+    struct UIDelegate {
+        inner: IvarDrop<Option<Id<NSObject, Shared>>>,
+        handler: IvarDrop<Box<Box<dyn Fn(PermissionRequest) -> PermissionDecision>>>,
+    }
+
+    unsafe impl ClassType for UIDelegate {
+        type Super = NSObject;
+        const NAME: &'static str = "TauriWebviewUtilUIDelegate";
+    }
+
+    unsafe impl UIDelegate {
+        #[sel(webView:requestMediaCapturePermissionForOrigin:initiatedByFrame:type:decisionHandler:)]
+        fn request_media_capture_permission(
+            &self,
+            webview: &WKWebView,
+            origin: &Object,
+            frame: &Object,
+            capture_type: NSInteger,
+            decision_handler: &Block<(NSInteger,), ()>,
+        ) {
+            let kind = match capture_type {
+                0 => PermissionKind::Camera,
+                1 => PermissionKind::Microphone,
+                2 => PermissionKind::CameraAndMicrophone,
+                _ => PermissionKind::Other,
+            };
+            unsafe {
+                if let Some(decision) = self.decide(kind, origin) {
+                    decision_handler.call((decision,));
+                    return;
+                }
+                let selector = sel!(webView:requestMediaCapturePermissionForOrigin:initiatedByFrame:type:decisionHandler:);
+                if let Some(inner) = self.forward_target(selector) {
+                    let _: () = msg_send![
+                        &inner,
+                        webView: webview,
+                        requestMediaCapturePermissionForOrigin: origin,
+                        initiatedByFrame: frame,
+                        type: capture_type,
+                        decisionHandler: decision_handler,
+                    ];
+                } else {
+                    decision_handler.call((WK_PERMISSION_DECISION_PROMPT,));
+                }
+            }
+        }
+
+        #[sel(_webView:requestGeolocationPermissionForOrigin:initiatedByFrame:decisionHandler:)]
+        fn request_geolocation_permission(
+            &self,
+            webview: &WKWebView,
+            origin: &Object,
+            frame: &Object,
+            decision_handler: &Block<(NSInteger,), ()>,
+        ) {
+            unsafe {
+                if let Some(decision) = self.decide(PermissionKind::Geolocation, origin) {
+                    decision_handler.call((decision,));
+                    return;
+                }
+                let selector = sel!(_webView:requestGeolocationPermissionForOrigin:initiatedByFrame:decisionHandler:);
+                if let Some(inner) = self.forward_target(selector) {
+                    let _: () = msg_send![
+                        &inner,
+                        _webView: webview,
+                        requestGeolocationPermissionForOrigin: origin,
+                        initiatedByFrame: frame,
+                        decisionHandler: decision_handler,
+                    ];
+                } else {
+                    decision_handler.call((WK_PERMISSION_DECISION_PROMPT,));
+                }
+            }
+        }
+
+        #[sel(_webView:requestNotificationPermissionForSecurityOrigin:decisionHandler:)]
+        fn request_notification_permission(
+            &self,
+            webview: &WKWebView,
+            origin: &Object,
+            decision_handler: &Block<(Bool,), ()>,
+        ) {
+            unsafe {
+                if let Some(decision) = self.decide(PermissionKind::Notifications, origin) {
+                    decision_handler.call((Bool::new(decision == WK_PERMISSION_DECISION_GRANT),));
+                    return;
+                }
+                let selector = sel!(_webView:requestNotificationPermissionForSecurityOrigin:decisionHandler:);
+                if let Some(inner) = self.forward_target(selector) {
+                    let _: () = msg_send![
+                        &inner,
+                        _webView: webview,
+                        requestNotificationPermissionForSecurityOrigin: origin,
+                        decisionHandler: decision_handler,
+                    ];
+                } else {
+                    decision_handler.call((Bool::NO,));
+                }
+            }
+        }
+
+        #[sel(respondsToSelector:)]
+        fn responds_to_selector(&self, selector: Sel) -> bool {
+            let responds: bool = unsafe { msg_send![super(self), respondsToSelector: selector] };
+            responds || self.forward_target(selector).is_some()
+        }
+
+        #[sel(forwardingTargetForSelector:)]
+        fn forwarding_target_for_selector(&self, selector: Sel) -> *mut Object {
+            match self.forward_target(selector) {
+                Some(inner) => Id::as_ptr(&inner) as *mut Object,
+                None => std::ptr::null_mut(),
+            }
+        }
+    }
+);
+
+impl UIDelegate {
+    fn new(
+        inner: Option<Id<NSObject, Shared>>,
+        handler: impl Fn(PermissionRequest) -> PermissionDecision + 'static,
+    ) -> Id<Self, Shared> {
+        unsafe {
+            let this: Option<Id<Self, Owned>> = msg_send_id![msg_send_id![Self::class(), alloc], init];
+            match this {
+                None => unreachable!("initializer should never fail"),
+                Some(mut this) => {
+                    Ivar::write(&mut this.inner, inner);
+                    Ivar::write(&mut this.handler, Box::new(Box::new(handler)));
+                    this.into()
+                },
+            }
+        }
+    }
+
+    // NOTE: returns the `WKPermissionDecision` for the request, or `None` when it is left to the
+    // inner delegate.
+    unsafe fn decide(&self, kind: PermissionKind, origin: &Object) -> Option<NSInteger> {
+        let origin = security_origin_url(origin);
+        match (self.handler)(PermissionRequest { kind, origin }) {
+            PermissionDecision::Default => None,
+            PermissionDecision::Allow => Some(WK_PERMISSION_DECISION_GRANT),
+            PermissionDecision::Deny => Some(WK_PERMISSION_DECISION_DENY),
+        }
+    }
+
+    fn forward_target(&self, selector: Sel) -> Option<Id<NSObject, Shared>> {
+        let inner = (*self.inner).clone()?;
+        let responds: bool = unsafe { msg_send![&inner, respondsToSelector: selector] };
+        responds.then_some(inner)
+    }
+}
+
+unsafe fn security_origin_url(origin: &Object) -> Option<Url> {
+    let protocol: Id<NSString, Shared> = msg_send_id![origin, protocol];
+    let host: Id<NSString, Shared> = msg_send_id![origin, host];
+    let port: NSInteger = msg_send![origin, port];
+    let origin = match port {
+        0 => format!("{protocol}://{host}"),
+        port => format!("{protocol}://{host}:{port}"),
+    };
+    Url::parse(&origin).ok()
+}
+
+unsafe fn load_error_from_ns_error(error: &NSError) -> LoadError {
+    const NSURL_ERROR_DOMAIN: &str = "NSURLErrorDomain";
+    const WEBKIT_ERROR_DOMAIN: &str = "WebKitErrorDomain";
+    let domain = error.domain().to_string();
+    let code = error.code();
+    let kind = match (domain.as_str(), code) {
+        (NSURL_ERROR_DOMAIN, -999) => LoadErrorKind::Cancelled,
+        // NSURLErrorCannotFindHost, NSURLErrorDNSLookupFailed
+        (NSURL_ERROR_DOMAIN, -1003 | -1006) => LoadErrorKind::DnsFailure,
+        // NSURLErrorAppTransportSecurityRequiresSecureConnection
+        (NSURL_ERROR_DOMAIN, -1022) => LoadErrorKind::BlockedByPolicy,
+        // NSURLErrorSecureConnectionFailed through NSURLErrorClientCertificateRequired
+        (NSURL_ERROR_DOMAIN, -1206 ..= -1200) => LoadErrorKind::Tls,
+        (NSURL_ERROR_DOMAIN, _) => LoadErrorKind::Network,
+        // WebKitErrorCannotShowMIMEType, WebKitErrorCannotShowURL
+        (WEBKIT_ERROR_DOMAIN, 100 | 101) => LoadErrorKind::BlockedByPolicy,
+        // NOTE: WebKitErrorFrameLoadInterruptedByPolicyChange is reported whenever a navigation turns
+        // into a download or is cancelled by a navigation policy, so it is not a failure of the load.
+        (WEBKIT_ERROR_DOMAIN, 102) => LoadErrorKind::Cancelled,
+        _ => LoadErrorKind::Unknown,
+    };
+    let user_info: Id<NSObject, Shared> = msg_send_id![error, userInfo];
+    let key = NSString::from_str("NSErrorFailingURLStringKey");
+    let url: Option<Id<NSObject, Shared>> = msg_send_id![&user_info, objectForKey: &*key];
+    let url = url
+        .filter(|url| msg_send![url, isKindOfClass: NSString::class()])
+        .map(|url| Id::cast::<NSString>(url).to_string());
+    let description = error.localizedDescription().to_string();
+    LoadError::new(url.as_deref(), kind, code as i64, description)
+}
+
+unsafe fn main_frame_url_from_navigation_action(navigation_action: &Object) -> Option<String> {
+    let frame: Option<Id<NSObject, Shared>> = msg_send_id![navigation_action, targetFrame];
+    let is_main_frame: bool = msg_send![&frame?, isMainFrame];
+    if !is_main_frame {
+        return None;
+    }
+    let request: Id<NSURLRequest, Shared> = msg_send_id![navigation_action, request];
+    let url: Option<Id<NSURL, Shared>> = msg_send_id![&request, URL];
+    url.and_then(|url| url.absoluteString()).map(|url| url.to_string())
+}
+
+// NOTE: returns the trust of the server with the error it was rejected for, or `None` when the
+// challenge is not for server trust or the server is trusted.
+unsafe fn server_trust_error(challenge: &Object) -> Option<(*mut c_void, CertificateErrorInfo)> {
+    let space: Id<NSObject, Shared> = msg_send_id![challenge, protectionSpace];
+    let method: Option<Id<NSString, Shared>> = msg_send_id![&space, authenticationMethod];
+    if method?.to_string() != "NSURLAuthenticationMethodServerTrust" {
+        return None;
+    }
+    let trust: *mut c_void = msg_send![&space, serverTrust];
+    if trust.is_null() {
+        return None;
+    }
+    let mut error: *mut c_void = std::ptr::null_mut();
+    if security::SecTrustEvaluateWithError(trust, &mut error) {
+        return None;
+    }
+    let code = if error.is_null() {
+        0
+    } else {
+        let code = (*(error as *const NSError)).code();
+        security::CFRelease(error);
+        code
+    };
+    let kind = match code {
+        // errSecCertificateExpired, errSecCertificateNotValidYet
+        -67818 | -67819 => CertificateErrorKind::Expired,
+        // errSecNotTrusted
+        -67843 => CertificateErrorKind::UnknownAuthority,
+        // errSecHostNameMismatch
+        -67602 => CertificateErrorKind::HostMismatch,
+        // errSecCertificateRevoked
+        -67820 => CertificateErrorKind::Revoked,
+        0 => CertificateErrorKind::Other,
+        _ => CertificateErrorKind::Invalid,
+    };
+    let protocol: Option<Id<NSString, Shared>> = msg_send_id![&space, protocol];
+    let host: Id<NSString, Shared> = msg_send_id![&space, host];
+    let port: NSInteger = msg_send![&space, port];
+    let protocol = protocol.map_or_else(|| "https".into(), |protocol| protocol.to_string());
+    let url = Url::parse(&format!("{protocol}://{host}:{port}")).ok()?;
+    Some((trust, CertificateErrorInfo { url, kind }))
+}
+
+mod security {
+    use std::ffi::c_void;
+
+    #[link(name = "Security", kind = "framework")]
+    extern {
+        pub(super) fn SecTrustEvaluateWithError(trust: *mut c_void, error: *mut *mut c_void) -> bool;
+    }
+
+    #[link(name = "CoreFoundation", kind = "framework")]
+    extern {
+        pub(super) fn CFRelease(object: *mut c_void);
+    }
+}
+
+unsafe fn auth_challenge(challenge: &Object) -> Option<AuthChallenge> {
+    const AUTHENTICATION_METHODS: &[&str] = &[
+        "NSURLAuthenticationMethodDefault",
+        "NSURLAuthenticationMethodHTTPBasic",
+        "NSURLAuthenticationMethodHTTPDigest",
+        "NSURLAuthenticationMethodNTLM",
+    ];
+    let space: Id<NSObject, Shared> = msg_send_id![challenge, protectionSpace];
+    let method: Option<Id<NSString, Shared>> = msg_send_id![&space, authenticationMethod];
+    if !AUTHENTICATION_METHODS.contains(&method?.to_string().as_str()) {
+        return None;
+    }
+    let host: Id<NSString, Shared> = msg_send_id![&space, host];
+    let port: NSInteger = msg_send![&space, port];
+    let realm: Option<Id<NSString, Shared>> = msg_send_id![&space, realm];
+    let is_proxy: bool = msg_send![&space, isProxy];
+    let protocol: Option<Id<NSString, Shared>> = msg_send_id![&space, protocol];
+    let url = protocol.and_then(|protocol| Url::parse(&format!("{protocol}://{host}:{port}")).ok());
+    Some(AuthChallenge {
+        url: url.filter(|_| !is_proxy),
+        host: host.to_string(),
+        port: port as u16,
+        realm: realm.map(|realm| realm.to_string()),
+        is_proxy,
+    })
+}
+
+unsafe fn complete_auth_challenge(
+    completion_handler: &Block<(NSInteger, *mut Object), ()>,
+    credentials: Option<Credentials>,
+) {
+    const NSURL_CREDENTIAL_PERSISTENCE_FOR_SESSION: NSUInteger = 1;
+    match credentials {
+        Some(Credentials { username, password }) => {
+            let credential: Id<NSObject, Shared> = msg_send_id![
+                class!(NSURLCredential),
+                credentialWithUser: &*NSString::from_str(&username),
+                password: &*NSString::from_str(&password),
+                persistence: NSURL_CREDENTIAL_PERSISTENCE_FOR_SESSION,
+            ];
+            completion_handler.call((
+                NSURL_SESSION_AUTH_CHALLENGE_USE_CREDENTIAL,
+                Id::as_ptr(&credential) as *mut Object,
+            ));
+        },
+        None => {
+            completion_handler.call((
+                NSURL_SESSION_AUTH_CHALLENGE_PERFORM_DEFAULT_HANDLING,
+                std::ptr::null_mut(),
+            ));
+        },
+    }
+}
+
+type DownloadObserver = Arc<dyn Fn(DownloadUpdate) + Send + Sync>;
+
+struct DownloadState {
+    id: u64,
+    window: Window,
+    observer: DownloadObserver,
+    destination: RefCell<Option<PathBuf>>,
+    done: Arc<AtomicBool>,
+}
+
+declare_class!(
+    struct DownloadDelegate {
+        state: IvarDrop<Box<DownloadState>>,
+    }
+
+    unsafe impl ClassType for DownloadDelegate {
+        type Super = NSObject;
+        const NAME: &'static str = "TauriWebviewUtilDownloadDelegate";
+    }
+
+    unsafe impl DownloadDelegate {
+        // NOTE: the destination is decided once the handle decides it, so the completion handler
+        // is kept until then.
+        #[sel(download:decideDestinationUsingResponse:suggestedFilename:completionHandler:)]
+        fn decide_destination(
+            &self,
+            download: &Object,
+            _response: &Object,
+            suggested_filename: &NSString,
+            completion_handler: &Block<(*mut NSURL,), ()>,
+        ) {
+            unsafe {
+                let state = &*self.state;
+                let request: Option<Id<NSURLRequest, Shared>> = msg_send_id![download, originalRequest];
+                let url = request
+                    .and_then(|request| request.URL())
+                    .and_then(|url| url.absoluteString())
+                    .and_then(|url| Url::parse(&url.to_string()).ok());
+                let (url, download) = match (url, Id::retain(download as *const Object as *mut Object)) {
+                    (Some(url), Some(download)) => (url, download),
+                    _ => {
+                        completion_handler.call((std::ptr::null_mut(),));
+                        return;
+                    },
+                };
+                let suggested_filename = suggested_filename.to_string();
+                let (decision_tx, decision_rx) = oneshot::channel();
+                (state.observer)(DownloadUpdate::Started {
+                    id: state.id,
+                    download: NativeDownload(download),
+                    url,
+                    suggested_filename: (!suggested_filename.is_empty()).then(|| suggested_filename.clone()),
+                    decision: decision_tx,
+                });
+                let this = Id::retain(self as *const Self as *mut Self);
+                let pending = MainThreadBound((this, RcBlock::copy(completion_handler as *const _ as *mut _)));
+                let window = state.window.clone();
+                tauri::async_runtime::spawn(async move {
+                    let decision = decision_rx.await.unwrap_or_default();
+                    window
+                        .run_on_main_thread(move || {
+                            let MainThreadBound((this, completion_handler)) = pending;
+                            if let Some(this) = this {
+                                this.decide(decision, &suggested_filename, &completion_handler);
+                            }
+                        })
+                        .ok();
+                });
+            }
+        }
+
+        #[sel(downloadDidFinish:)]
+        fn download_did_finish(&self, _download: &Object) {
+            let state = &*self.state;
+            if !state.done.swap(true, Ordering::Relaxed) {
+                let destination = state.destination.borrow().clone();
+                let event = DownloadEvent::Finished { id: state.id, destination };
+                (state.observer)(DownloadUpdate::Event(event));
+            }
+        }
+
+        #[sel(download:didFailWithError:resumeData:)]
+        fn download_did_fail(&self, _download: &Object, error: &NSError, _resume_data: *mut NSData) {
+            const NSURL_ERROR_DOMAIN: &str = "NSURLErrorDomain";
+            const NSURL_ERROR_CANCELLED: NSInteger = -999;
+            let state = &*self.state;
+            if state.done.swap(true, Ordering::Relaxed) {
+                return;
+            }
+            let event = unsafe {
+                if error.domain().to_string() == NSURL_ERROR_DOMAIN && error.code() == NSURL_ERROR_CANCELLED {
+                    DownloadEvent::Cancelled { id: state.id }
+                } else {
+                    let reason = error.localizedDescription().to_string();
+                    DownloadEvent::Failed { id: state.id, reason }
+                }
+            };
+            (state.observer)(DownloadUpdate::Event(event));
+        }
+    }
+);
+
+impl DownloadDelegate {
+    fn new(window: Window, observer: DownloadObserver) -> Id<Self, Shared> {
+        unsafe {
+            let this: Option<Id<Self, Owned>> = msg_send_id![msg_send_id![Self::class(), alloc], init];
+            match this {
+                None => unreachable!("initializer should never fail"),
+                Some(mut this) => {
+                    let state = DownloadState {
+                        id: downloads::next_id(),
+                        window,
+                        observer,
+                        destination: RefCell::default(),
+                        done: Arc::default(),
+                    };
+                    Ivar::write(&mut this.state, Box::new(state));
+                    this.into()
+                },
+            }
+        }
+    }
+
+    // NOTE: without a destination, the download is saved to the downloads directory.
+    unsafe fn decide(
+        &self,
+        decision: DownloadDecision,
+        suggested_filename: &str,
+        completion_handler: &Block<(*mut NSURL,), ()>,
+    ) {
+        let state = &*self.state;
+        let destination = match decision {
+            DownloadDecision::Default => {
+                tauri::api::path::download_dir().map(|directory| downloads::unique_path(&directory, suggested_filename))
+            },
+            DownloadDecision::Destination(path) => Some(path),
+            DownloadDecision::Cancel => None,
+        };
+        let url = destination.as_ref().map(|path| {
+            let path = NSString::from_str(&path.to_string_lossy());
+            NSURL::fileURLWithPath(&path)
+        });
+        match url {
+            Some(url) => {
+                *state.destination.borrow_mut() = destination;
+                completion_handler.call((Id::as_ptr(&url) as *mut NSURL,));
+                self.poll_progress();
+            },
+            None => {
+                completion_handler.call((std::ptr::null_mut(),));
+                if !state.done.swap(true, Ordering::Relaxed) {
+                    (state.observer)(DownloadUpdate::Event(DownloadEvent::Cancelled { id: state.id }));
+                }
+            },
+        }
+    }
+
+    fn poll_progress(&self) {
+        const POLL_INTERVAL: std::time::Duration = std::time::Duration::from_millis(500);
+
+        let state = &*self.state;
+        let id = state.id;
+        let window = state.window.clone();
+        let observer = state.observer.clone();
+        let done = state.done.clone();
+        tauri::async_runtime::spawn(async move {
+            while !done.load(Ordering::Relaxed) {
+                tokio::time::sleep(POLL_INTERVAL).await;
+                let download = match crate::state::window_state(&window)
+                    .ok()
+                    .and_then(|state| state.downloads.lock().ok()?.get(&id).cloned())
+                {
+                    Some(download) => download,
+                    None => break,
+                };
+                let observer = observer.clone();
+                let polled = window.run_on_main_thread(move || unsafe {
+                    let progress: Id<NSObject, Shared> = msg_send_id![&download.0, progress];
+                    let received_bytes: i64 = msg_send![&progress, completedUnitCount];
+                    let total_bytes: i64 = msg_send![&progress, totalUnitCount];
+                    observer(DownloadUpdate::Event(DownloadEvent::Progress {
+                        id,
+                        received_bytes: received_bytes.max(0) as u64,
+                        total_bytes: Some(total_bytes as u64).filter(|_| total_bytes > 0),
+                    }));
+                });
+                if polled.is_err() {
+                    break;
+                }
+            }
+        });
+    }
+}
+
+// NOTE: downloads are only used on the main thread, the handle is merely kept in the window state
+// until the download ends.
+#[derive(Clone)]
+pub(crate) struct NativeDownload(Id<Object, Shared>);
+
+unsafe impl Send for NativeDownload {
+}
+
+unsafe impl Sync for NativeDownload {
+}
+
+// NOTE: Objective-C objects and blocks that are not thread safe are only moved through other threads
+// on their way back to the main thread.
+struct MainThreadBound<T>(T);
+
+unsafe impl<T> Send for MainThreadBound<T> {
+}
+
+// NOTE: `shouldPerformDownload` is only available since macOS 11.3, like downloads themselves.
+unsafe fn should_perform_download(navigation_action: &Object) -> bool {
+    let can_download: bool = msg_send![navigation_action, respondsToSelector: sel!(shouldPerformDownload)];
+    can_download && msg_send![navigation_action, shouldPerformDownload]
+}
+
+// NOTE: like browsers, responses that can not be shown and attachments are downloaded.
+unsafe fn is_download_response(navigation_response: &Object) -> bool {
+    let can_show: bool = msg_send![navigation_response, canShowMIMEType];
+    if !can_show {
+        return true;
+    }
+    let response: Id<NSObject, Shared> = msg_send_id![navigation_response, response];
+    let is_http: bool = msg_send![&response, isKindOfClass: class!(NSHTTPURLResponse)];
+    if !is_http {
+        return false;
+    }
+    let name = NSString::from_str("Content-Disposition");
+    let disposition: Option<Id<NSString, Shared>> = msg_send_id![&response, valueForHTTPHeaderField: &*name];
+    disposition.map_or(false, |disposition| {
+        disposition
+            .to_string()
+            .trim_start()
+            .to_ascii_lowercase()
+            .starts_with("attachment")
+    })
+}
+
+unsafe fn load_error_from_navigation_response(navigation_response: &Object) -> Option<LoadError> {
+    let is_for_main_frame: bool = msg_send![navigation_response, isForMainFrame];
+    let response: Id<NSObject, Shared> = msg_send_id![navigation_response, response];
+    let is_http: bool = msg_send![&response, isKindOfClass: class!(NSHTTPURLResponse)];
+    if !is_for_main_frame || !is_http {
+        return None;
+    }
+    let status: NSInteger = msg_send![&response, statusCode];
+    if status < 400 {
+        return None;
+    }
+    let url: Option<Id<NSURL, Shared>> = msg_send_id![&response, URL];
+    let url = url.and_then(|url| url.absoluteString()).map(|url| url.to_string());
+    let kind = LoadErrorKind::Http(status as u16);
+    Some(LoadError::new(url.as_deref(), kind, status as i64, kind.to_string()))
+}
+
 trait WebviewExtForWKWebView: private::WebviewExtForWKWebViewSealed {
     #[allow(non_snake_case)]
     unsafe fn WKWebView(&self) -> Id<WKWebView, Shared>;
@@ -0,0 +1,13 @@
+use crate::{platform, BoxResult, Error};
+use tauri::Window;
+
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip(window),
+    fields(window.label = window.label(), operation.id = crate::trace::operation_id(), backend = crate::trace::BACKEND)
+))]
+pub(crate) async fn set(window: &Window, factor: f64) -> BoxResult<()> {
+    if !factor.is_finite() || factor <= 0.0 {
+        return Err(Error::Other(format!("{factor} is not a valid zoom factor").into()).into());
+    }
+    platform::set_zoom(window, factor).await
+}
@@ -0,0 +1,152 @@
+// NOTE: conformance tests for the cookie formats and the platform converters. The serialized forms
+// are compared against the golden files in `tests/golden/cookies`, which can be rewritten from the
+// current output by running the tests with `UPDATE_GOLDEN=1`.
+//
+// Converting from `ICoreWebView2Cookie` needs a cookie manager from a live webview, so WebView2 is
+// only covered by the formats shared by all backends.
+
+use std::path::{Path, PathBuf};
+use tauri_webview_util::{deserialize_cookies, serialize_cookies, Cookie, CookieJarFormat};
+
+fn path(relative: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests").join(relative)
+}
+
+fn read(relative: &str) -> String {
+    let path = path(relative);
+    std::fs::read_to_string(&path).unwrap_or_else(|err| panic!("failed to read `{}`: {err}", path.display()))
+}
+
+// NOTE: trailing whitespace is ignored, so that editors adding a final newline do not break the
+// comparison.
+fn assert_golden(relative: &str, actual: &str) {
+    if std::env::var_os("UPDATE_GOLDEN").is_some() {
+        std::fs::write(path(relative), format!("{}\n", actual.trim_end())).unwrap();
+        return;
+    }
+    let expected = read(relative);
+    assert_eq!(
+        expected.trim_end(),
+        actual.trim_end(),
+        "output differs from `tests/{relative}`"
+    );
+}
+
+// NOTE: `Cookie` is compared by value rather than by its serialized text, since the serde form of
+// `expires` depends on the features `time` is built with.
+fn assert_golden_cookies(relative: &str, actual: &[Cookie]) {
+    if std::env::var_os("UPDATE_GOLDEN").is_some() {
+        assert_golden(relative, &serde_json::to_string_pretty(actual).unwrap());
+        return;
+    }
+    let expected = serde_json::from_str::<Vec<Cookie>>(&read(relative)).unwrap();
+    assert_eq!(expected, actual, "cookies differ from `tests/{relative}`");
+}
+
+fn recorded_cookies() -> Vec<Cookie> {
+    deserialize_cookies(&read("fixtures/cookies/devtools.json"), CookieJarFormat::DevToolsJson).unwrap()
+}
+
+// NOTE: each line is the URL of the response and the value of its `Set-Cookie` header, separated by
+// a tab.
+fn recorded_set_cookie_headers() -> Vec<(String, String)> {
+    read("fixtures/cookies/set-cookie.txt")
+        .lines()
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|line| {
+            let (url, header) = line.split_once('\t').expect("expected a tab separated URL and header");
+            (url.into(), header.into())
+        })
+        .collect()
+}
+
+#[test]
+fn serde_matches_golden() {
+    let cookies = recorded_cookies();
+    assert_golden_cookies("golden/cookies/serde.json", &cookies);
+    let text = serde_json::to_string(&cookies).unwrap();
+    assert_eq!(cookies, serde_json::from_str::<Vec<Cookie>>(&text).unwrap());
+}
+
+#[test]
+fn netscape_matches_golden() {
+    let cookies = recorded_cookies();
+    let text = serialize_cookies(&cookies, CookieJarFormat::Netscape).unwrap();
+    assert_golden("golden/cookies/netscape.txt", &text);
+}
+
+// NOTE: the Netscape format has no same-site policy, so it is the only field lost on the way back.
+#[test]
+fn netscape_round_trips() {
+    let mut cookies = recorded_cookies();
+    for cookie in &mut cookies {
+        cookie.same_site = None;
+    }
+    let text = serialize_cookies(&cookies, CookieJarFormat::Netscape).unwrap();
+    assert_eq!(cookies, deserialize_cookies(&text, CookieJarFormat::Netscape).unwrap());
+}
+
+#[test]
+fn devtools_json_matches_golden() {
+    let cookies = recorded_cookies();
+    let text = serialize_cookies(&cookies, CookieJarFormat::DevToolsJson).unwrap();
+    assert_golden("golden/cookies/devtools.json", &text);
+}
+
+// NOTE: the same-site policy is capitalized when written, so the golden file (rather than the
+// fixture) is what reads back unchanged.
+#[test]
+fn devtools_json_round_trips() {
+    let text = read("golden/cookies/devtools.json");
+    let cookies = deserialize_cookies(&text, CookieJarFormat::DevToolsJson).unwrap();
+    let written = serialize_cookies(&cookies, CookieJarFormat::DevToolsJson).unwrap();
+    assert_eq!(text.trim_end(), written.trim_end());
+}
+
+#[cfg(any(
+    target_os = "linux",
+    target_os = "dragonfly",
+    target_os = "freebsd",
+    target_os = "openbsd",
+    target_os = "netbsd"
+))]
+#[test]
+fn soup_cookie_matches_golden() {
+    let cookies = recorded_set_cookie_headers()
+        .into_iter()
+        .map(|(url, header)| {
+            let mut origin = soup::URI::new(Some(&url)).expect("expected a valid URL");
+            let cookie = soup::Cookie::parse(&header, &mut origin).expect("expected a valid header");
+            Cookie::try_from(cookie).unwrap()
+        })
+        .collect::<Vec<_>>();
+    assert_golden_cookies("golden/cookies/set-cookie.json", &cookies);
+}
+
+#[cfg(any(target_os = "ios", target_os = "macos"))]
+#[test]
+fn ns_http_cookie_matches_golden() {
+    use icrate::{
+        objc2::{
+            rc::{Id, Shared},
+            runtime::Object,
+            *,
+        },
+        Foundation::{NSHTTPCookie, NSString, NSURL},
+    };
+
+    let cookies = recorded_set_cookie_headers()
+        .into_iter()
+        .map(|(url, header)| unsafe {
+            let fields: Id<Object, Shared> = msg_send_id![class!(NSMutableDictionary), new];
+            let key = NSString::from_str("Set-Cookie");
+            let _: () = msg_send![&fields, setObject: &*NSString::from_str(&header), forKey: &*key];
+            let url = NSURL::URLWithString(&NSString::from_str(&url)).expect("expected a valid URL");
+            let cookies: Id<Object, Shared> =
+                msg_send_id![class!(NSHTTPCookie), cookiesWithResponseHeaderFields: &*fields, forURL: &*url];
+            let cookie: Option<Id<NSHTTPCookie, Shared>> = msg_send_id![&cookies, firstObject];
+            Cookie::try_from(&cookie.expect("expected a valid header")).unwrap()
+        })
+        .collect::<Vec<_>>();
+    assert_golden_cookies("golden/cookies/set-cookie.json", &cookies);
+}
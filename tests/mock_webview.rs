@@ -0,0 +1,100 @@
+// NOTE: the futures of the mock are ready right away, so they are polled once instead of being run
+// on an executor.
+#![cfg(feature = "mock")]
+
+use futures::FutureExt;
+use tauri_webview_util::{deserialize_cookies, CookieJarFormat, CookiePattern, Error, MockWebView, WebviewBackend};
+use url::Url;
+
+const COOKIES: &str = "\
+.example.com\tTRUE\t/\tFALSE\t0\tsession\tshared
+app.example.com\tFALSE\t/account\tTRUE\t0\ttoken\tsecret
+other.test\tFALSE\t/\tFALSE\t0\ttracking\tid
+expired.test\tFALSE\t/\tFALSE\t1\told\tvalue
+";
+
+fn webview() -> MockWebView {
+    MockWebView::with_cookies(deserialize_cookies(COOKIES, CookieJarFormat::Netscape).unwrap())
+}
+
+fn url(url: &str) -> Url {
+    Url::parse(url).unwrap()
+}
+
+fn names(cookies: Vec<tauri_webview_util::Cookie>) -> Vec<String> {
+    let mut names = cookies.into_iter().map(|cookie| cookie.name).collect::<Vec<_>>();
+    names.sort();
+    names
+}
+
+#[test]
+fn expired_cookies_are_dropped() {
+    let cookies = webview().get_cookies(None).now_or_never().unwrap().unwrap();
+    assert_eq!(names(cookies), ["session", "token", "tracking"]);
+}
+
+#[test]
+fn cookies_match_domain_path_and_scheme() {
+    let webview = webview();
+    let get = |target: &str| names(webview.get_cookies(Some(url(target))).now_or_never().unwrap().unwrap());
+    assert_eq!(get("https://app.example.com/account/settings"), ["session", "token"]);
+    assert_eq!(get("http://app.example.com/account"), ["session"]);
+    assert_eq!(get("https://app.example.com/accounts"), ["session"]);
+    assert_eq!(get("https://www.example.com/"), ["session"]);
+    assert_eq!(get("https://sub.other.test/"), Vec::<String>::new());
+}
+
+#[test]
+fn matching_cookies_are_deleted() {
+    let webview = webview();
+    let pattern = CookiePattern::builder().match_domain("*example.com").build();
+    let deleted = webview
+        .delete_matching_cookies(pattern)
+        .now_or_never()
+        .unwrap()
+        .unwrap();
+    assert_eq!(names(deleted), ["session", "token"]);
+    let remaining = webview.get_cookies(None).now_or_never().unwrap().unwrap();
+    assert_eq!(names(remaining), ["tracking"]);
+}
+
+#[test]
+fn cache_is_cleared_per_origin() {
+    let webview = MockWebView::new();
+    webview.insert_cache_entry(url("https://example.com/app.js"), 100);
+    webview.insert_cache_entry(url("https://example.com/app.css"), 20);
+    webview.insert_cache_entry(url("https://other.test/logo.png"), 5);
+    let usage = webview.cache_usage();
+    assert_eq!(usage.len(), 2);
+    assert_eq!(usage[0].origin, "https://example.com");
+    assert_eq!(usage[0].disk_cache, Some(120));
+    webview.clear_cache_for_origins(&[url("https://example.com/")]);
+    assert_eq!(webview.cache_entries(), [url("https://other.test/logo.png")]);
+    webview.clear_cache().now_or_never().unwrap().unwrap();
+    assert!(webview.cache_entries().is_empty());
+}
+
+#[test]
+fn navigation_is_recorded() {
+    let webview = MockWebView::new();
+    webview.navigate(url("https://example.com/")).unwrap();
+    webview.navigate(url("https://example.com/next")).unwrap();
+    assert_eq!(webview.url(), Some(url("https://example.com/next")));
+    assert_eq!(webview.history().len(), 2);
+}
+
+#[test]
+fn unavailable_store_fails_instead_of_panicking() {
+    let webview = MockWebView::without_store();
+    fn is_unavailable<T>(result: Result<T, Error>) -> bool {
+        matches!(result, Err(Error::StoreUnavailable { .. }))
+    }
+    assert!(is_unavailable(webview.get_cookies(None).now_or_never().unwrap()));
+    assert!(is_unavailable(webview.delete_cookies(None).now_or_never().unwrap()));
+    assert!(is_unavailable(webview.clear_cache().now_or_never().unwrap()));
+    let pattern = CookiePattern::builder().match_domain("*example.com").build();
+    let deleted = webview.delete_matching_cookies(pattern).now_or_never().unwrap();
+    assert!(is_unavailable(deleted));
+    webview.navigate(url("https://example.com/")).unwrap();
+    assert_eq!(webview.url(), Some(url("https://example.com/")));
+}